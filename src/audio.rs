@@ -2,15 +2,155 @@ use nih_plug::prelude::*;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU32, Ordering};
 
+use crate::editor::EditorEvent;
 use crate::editor::visualizer::VisualizerState;
-use crate::params::SongWalkerParams;
+use crate::meter::TruePeakMeter;
+use crate::monitor::EngineMonitor;
+use crate::params::{AUTOMATABLE_SLOTS, MasterOutput, SlotMix, SongWalkerParams};
 use crate::perf::pool::MixBuffer;
-use crate::slots::SlotManager;
+use crate::perf::simd;
+use crate::perf::workers::{DisjointSlice, RenderPool};
+use crate::slots::{MAX_SLOTS, Slot, SlotManager};
 use crate::transport::TransportState;
 
 /// Maximum number of samples in a single process block.
 pub const MAX_BLOCK_SIZE: usize = 8192;
 
+/// A processing stage that can delay the output.
+///
+/// Each stage reports its own latency; the engine reports the sum to the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatencyStage {
+    /// Lookahead limiting / dynamics on the master bus.
+    Lookahead,
+    /// Streaming sample prebuffer.
+    Prebuffer,
+    /// Decimation filters of oversampled slots.
+    Oversampling,
+}
+
+impl LatencyStage {
+    const COUNT: usize = 3;
+
+    fn index(self) -> usize {
+        match self {
+            LatencyStage::Lookahead => 0,
+            LatencyStage::Prebuffer => 1,
+            LatencyStage::Oversampling => 2,
+        }
+    }
+}
+
+/// Aggregates per-stage latency and tracks what was last reported to the host.
+#[derive(Debug, Default)]
+pub struct LatencyTracker {
+    stages: [u32; LatencyStage::COUNT],
+    reported: Option<u32>,
+}
+
+impl LatencyTracker {
+    /// Set the latency contributed by one stage (in samples).
+    pub fn set(&mut self, stage: LatencyStage, samples: u32) {
+        self.stages[stage.index()] = samples;
+    }
+
+    /// Latency contributed by one stage (in samples).
+    pub fn get(&self, stage: LatencyStage) -> u32 {
+        self.stages[stage.index()]
+    }
+
+    /// Total latency across all stages (in samples).
+    pub fn total(&self) -> u32 {
+        self.stages.iter().sum()
+    }
+
+    /// Return the new total if it differs from the last reported value,
+    /// marking it reported.
+    pub fn take_change(&mut self) -> Option<u32> {
+        let total = self.total();
+        if self.reported == Some(total) {
+            return None;
+        }
+        self.reported = Some(total);
+        Some(total)
+    }
+}
+
+/// Gain/pan/width matrix applied to a slot's output (see `slot_mix_matrix`).
+pub type MixMatrix = [[f32; 2]; 2];
+
+const SILENT: MixMatrix = [[0.0; 2]; 2];
+
+/// Time over which slot volume, pan, width and mute/solo changes ramp, so
+/// slider moves don't zipper and toggles don't click.
+pub const SMOOTHING_SECS: f32 = 0.005;
+
+/// One-pole ramp toward a target, stepped per sample. Smooths the master
+/// gain and pan, which the host automates once per block.
+#[derive(Debug, Clone, Copy)]
+pub struct OnePole {
+    value: f32,
+    target: f32,
+    coeff: f32,
+    /// False until the first target, which is taken as-is (no fade-in).
+    primed: bool,
+}
+
+impl OnePole {
+    pub fn new(time_secs: f32, sample_rate: f32) -> Self {
+        let mut smoother = Self { value: 0.0, target: 0.0, coeff: 1.0, primed: false };
+        smoother.set_time(time_secs, sample_rate);
+        smoother
+    }
+
+    /// Set the time constant (time to cover ~63% of a step).
+    pub fn set_time(&mut self, time_secs: f32, sample_rate: f32) {
+        let samples = (time_secs * sample_rate).max(1.0);
+        self.coeff = 1.0 - (-1.0 / samples).exp();
+    }
+
+    pub fn set_target(&mut self, target: f32) {
+        self.target = target;
+        if !self.primed {
+            self.value = target;
+            self.primed = true;
+        }
+    }
+
+    /// Jump to the next target instead of ramping to it.
+    pub fn reset(&mut self) {
+        self.primed = false;
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Whether the ramp has (for audio purposes) reached its target.
+    pub fn is_settled(&self) -> bool {
+        (self.target - self.value).abs() < 1.0e-5
+    }
+
+    /// Advance one sample and return the new value.
+    #[inline]
+    pub fn next(&mut self) -> f32 {
+        self.value += (self.target - self.value) * self.coeff;
+        self.value
+    }
+
+    /// Multiply a buffer by the ramp, through the SIMD path once settled.
+    pub fn apply(&mut self, buf: &mut [f32], n: usize) {
+        if self.is_settled() {
+            self.value = self.target;
+            simd::apply_gain(buf, self.value, n);
+            return;
+        }
+        for sample in &mut buf[..n.min(buf.len())] {
+            *sample *= self.next();
+        }
+    }
+}
+
 /// Pre-allocated audio engine resources.
 ///
 /// All buffers are allocated at `initialize()` time.
@@ -25,6 +165,28 @@ pub struct AudioEngine {
     sample_rate: f32,
     /// Max buffer size from the host.
     max_buffer_size: usize,
+    /// Per-stage latency, reported to the host when it changes.
+    latency: LatencyTracker,
+    /// Mix matrix each slot ended the last block with, ramped toward the
+    /// current volume/pan/width (`SILENT` while muted).
+    slot_mix: [MixMatrix; MAX_SLOTS],
+    /// Master gain × pan law for each output channel, smoothed per sample.
+    master_left: OnePole,
+    master_right: OnePole,
+    /// Per-slot render buffers for parallel rendering, mixed in slot order.
+    slot_buffers: Vec<MixBuffer>,
+    /// Worker threads for parallel slot rendering (spawned in `initialize`).
+    render_pool: Option<RenderPool>,
+    /// Render slots on the worker pool (Settings toggle).
+    parallel_render: bool,
+    /// Inter-sample peak detector on the master output.
+    true_peak: TruePeakMeter,
+    /// Mono fold-down, channel swap and polarity on the master output.
+    master_output: MasterOutput,
+    /// Audio input of the current block, fed to effect slots.
+    input: MixBuffer,
+    /// `input` holds host audio (otherwise it is silent).
+    input_connected: bool,
 }
 
 impl AudioEngine {
@@ -35,6 +197,17 @@ impl AudioEngine {
             output_right: vec![0.0; MAX_BLOCK_SIZE],
             sample_rate: 44100.0,
             max_buffer_size: MAX_BLOCK_SIZE,
+            latency: LatencyTracker::default(),
+            slot_mix: [SILENT; MAX_SLOTS],
+            master_left: OnePole::new(SMOOTHING_SECS, 44100.0),
+            master_right: OnePole::new(SMOOTHING_SECS, 44100.0),
+            slot_buffers: Vec::new(),
+            render_pool: None,
+            parallel_render: false,
+            true_peak: TruePeakMeter::new(),
+            master_output: MasterOutput::default(),
+            input: MixBuffer::new(MAX_BLOCK_SIZE),
+            input_connected: false,
         }
     }
 
@@ -42,14 +215,26 @@ impl AudioEngine {
         self.sample_rate = sample_rate;
         self.max_buffer_size = max_buffer_size;
         self.slot_buffer = MixBuffer::new(max_buffer_size);
+        self.input = MixBuffer::new(max_buffer_size);
+        self.input_connected = false;
         self.output_left.resize(max_buffer_size, 0.0);
         self.output_right.resize(max_buffer_size, 0.0);
+        self.master_left.set_time(SMOOTHING_SECS, sample_rate);
+        self.true_peak.reset();
+        self.master_right.set_time(SMOOTHING_SECS, sample_rate);
+        self.slot_buffers = (0..MAX_SLOTS).map(|_| MixBuffer::new(max_buffer_size)).collect();
+        if self.render_pool.is_none() {
+            self.render_pool = Some(RenderPool::with_available_parallelism());
+        }
     }
 
     pub fn reset(&mut self) {
         self.slot_buffer.clear();
         self.output_left.fill(0.0);
         self.output_right.fill(0.0);
+        self.slot_mix = [SILENT; MAX_SLOTS];
+        self.master_left.reset();
+        self.master_right.reset();
     }
 
     pub fn sample_rate(&self) -> f32 {
@@ -59,6 +244,62 @@ impl AudioEngine {
     pub fn max_buffer_size(&self) -> usize {
         self.max_buffer_size
     }
+
+    /// Render slots across the worker pool instead of one at a time. Only
+    /// takes effect once `initialize` has spawned the pool.
+    pub fn set_parallel_render(&mut self, enabled: bool) {
+        self.parallel_render = enabled;
+    }
+
+    /// Copy the audio input for the next block (fed to effect slots).
+    pub fn set_input(&mut self, left: &[f32], right: &[f32]) {
+        let n = left.len().min(right.len()).min(self.input.capacity());
+        self.input.left_mut()[..n].copy_from_slice(&left[..n]);
+        self.input.right_mut()[..n].copy_from_slice(&right[..n]);
+        self.input_connected = true;
+    }
+
+    /// No audio input for the next block: effect slots get silence.
+    pub fn clear_input(&mut self) {
+        if self.input_connected {
+            self.input.clear();
+            self.input_connected = false;
+        }
+    }
+
+    /// Master output utilities applied from the next block on.
+    pub fn set_master_output(&mut self, output: MasterOutput) {
+        self.master_output = output;
+    }
+
+    pub fn parallel_render(&self) -> bool {
+        self.parallel_render
+    }
+
+    /// Set the latency a processing stage adds (in samples).
+    pub fn set_stage_latency(&mut self, stage: LatencyStage, samples: u32) {
+        self.latency.set(stage, samples);
+    }
+
+    /// Total latency of the engine (in samples).
+    pub fn latency_samples(&self) -> u32 {
+        self.latency.total()
+    }
+
+    /// New total latency if it changed since it was last reported to the host.
+    pub fn take_latency_change(&mut self) -> Option<u32> {
+        self.latency.take_change()
+    }
+}
+
+/// Report a change of the total latency (lookahead, prebuffer,
+/// oversampling) through `set_latency`, the host context's
+/// `set_latency_samples`.
+pub fn report_latency(engine: &mut AudioEngine, slot_manager: &SlotManager, set_latency: impl FnOnce(u32)) {
+    engine.set_stage_latency(LatencyStage::Oversampling, slot_manager.oversampling_latency());
+    if let Some(samples) = engine.take_latency_change() {
+        set_latency(samples);
+    }
 }
 
 /// Main audio processing entry point. Called once per process block.
@@ -66,6 +307,8 @@ impl AudioEngine {
 /// This function:
 /// 1. Drains MIDI events from the host and routes them to slots
 /// 2. Calls render_and_mix to render all slots and produce final output
+/// 3. Copies the output to the host buffer
+/// 4. Sends runner notes from slots with MIDI out enabled to the host
 pub fn process_block(
     buffer: &mut Buffer,
     context: &mut impl ProcessContext<crate::SongWalkerPlugin>,
@@ -75,6 +318,7 @@ pub fn process_block(
     params: &SongWalkerParams,
     visualizer_state: &Arc<VisualizerState>,
     voice_count: &Arc<AtomicU32>,
+    monitor: &Arc<EngineMonitor>,
 ) {
     let num_samples = buffer.samples();
     if num_samples == 0 {
@@ -87,18 +331,26 @@ pub fn process_block(
         return;
     }
 
+    report_latency(engine, slot_manager, |samples| context.set_latency_samples(samples));
+
     // --- 1. Collect and route MIDI events ---
+    let recorder = monitor.midi_recorder();
+    let inspector = monitor.midi_inspector();
     while let Some(event) = context.next_event() {
-        crate::midi::route_event(&event, slot_manager, transport);
+        recorder.record(&event);
+        let taken = crate::midi::route_event(&event, slot_manager, transport);
+        inspector.record(&event, taken);
     }
 
     // --- 2. Render and mix into output buffer ---
     let master_gain = params.master_volume.value();
     let master_pan = params.master_pan.value();
+    engine.set_master_output(params.master_output());
     render_and_mix(
         num_samples, engine, slot_manager, transport,
-        master_gain, master_pan, visualizer_state, voice_count,
+        master_gain, master_pan, visualizer_state, voice_count, monitor,
     );
+    recorder.end_block(num_samples, engine.sample_rate());
 
     // --- 3. Copy rendered audio to host buffer ---
     let output = buffer.as_slice();
@@ -108,12 +360,35 @@ pub fn process_block(
             output[1][i] = engine.output_right[i];
         }
     }
+
+    // --- 4. Send runner-generated notes as MIDI output ---
+    slot_manager.drain_midi_out(|out| {
+        let event = if out.on {
+            NoteEvent::NoteOn {
+                timing: out.timing,
+                voice_id: None,
+                channel: out.channel,
+                note: out.note,
+                velocity: out.velocity,
+            }
+        } else {
+            NoteEvent::NoteOff {
+                timing: out.timing,
+                voice_id: None,
+                channel: out.channel,
+                note: out.note,
+                velocity: 0.0,
+            }
+        };
+        context.send_event(event);
+    });
 }
 
 /// Core render-and-mix function used by both the plugin and standalone audio backends.
 ///
 /// Renders all active slots into the engine's internal output buffers,
-/// applies master volume/pan, feeds the visualizer, and updates the voice count.
+/// applies master volume/pan and output utilities, feeds the visualizer, and updates the voice count
+/// and per-slot monitor.
 /// After calling this, read the result from `engine.output_left` / `engine.output_right`.
 pub fn render_and_mix(
     num_samples: usize,
@@ -124,6 +399,7 @@ pub fn render_and_mix(
     master_pan: f32,
     visualizer_state: &Arc<VisualizerState>,
     voice_count: &Arc<AtomicU32>,
+    monitor: &Arc<EngineMonitor>,
 ) {
     let num_samples = num_samples.min(engine.slot_buffer.capacity());
     if num_samples == 0 {
@@ -136,53 +412,94 @@ pub fn render_and_mix(
     engine.output_left[..num_samples].fill(0.0);
     engine.output_right[..num_samples].fill(0.0);
 
-    // --- 2. Render each active slot and mix into output ---
+    // --- 2. Deliver notes from runner tracks routed to other slots ---
+    slot_manager.dispatch_runner_routes(num_samples, sample_rate, transport);
+
+    // --- 3. Render each active slot and mix into output ---
     let any_solo = slot_manager.any_solo();
+    let mut jobs = [(0, SILENT, SILENT); MAX_SLOTS];
+    let mut job_count = 0;
 
     for slot_idx in 0..slot_manager.slot_count() {
-        let slot = &mut slot_manager.slots_mut()[slot_idx];
-
-        // Skip muted slots, or non-soloed slots when solo is active
-        if slot.is_muted() || (any_solo && !slot.is_solo()) {
+        let slot = &slot_manager.slots()[slot_idx];
+
+        // Muted slots, non-soloed slots when solo is active, and slots
+        // silenced by their group (mute/solo) fade out, then stop rendering
+        let audible = !slot.is_muted() && !(any_solo && !slot.is_solo());
+        let group_gain = if audible { slot_manager.group_gain(slot) } else { None };
+        let target = group_gain.map_or(SILENT, |g| {
+            slot_mix_matrix(slot.volume() * g, slot.pan(), slot.tuning().width)
+        });
+        let Some(previous) = engine.slot_mix.get(slot_idx).copied() else {
+            continue;
+        };
+        if previous == SILENT && target == SILENT {
             continue;
         }
-
-        // Clear scratch buffer
-        engine.slot_buffer.clear_n(num_samples);
-
-        // Render slot into scratch buffer (borrow both channels at once)
-        let (slot_left, slot_right) = engine.slot_buffer.channels_mut();
-        slot.render(
-            slot_left,
-            slot_right,
-            num_samples,
-            sample_rate,
-            transport,
-        );
-
-        // Apply slot volume and pan, then mix into output
-        let slot_gain = slot.volume();
-        let slot_pan = slot.pan();
-        let (pan_l, pan_r) = constant_power_pan(slot_pan);
-
-        let left_out = engine.slot_buffer.left();
-        let right_out = engine.slot_buffer.right();
-
-        for i in 0..num_samples {
-            engine.output_left[i] += left_out[i] * slot_gain * pan_l;
-            engine.output_right[i] += right_out[i] * slot_gain * pan_r;
+        engine.slot_mix[slot_idx] = target;
+        jobs[job_count] = (slot_idx, previous, target);
+        job_count += 1;
+    }
+    let jobs = &jobs[..job_count];
+
+    // Independent slots render on the worker pool into their own buffers,
+    // then mix in slot order so the result matches the serial path exactly
+    let pool = engine.render_pool.as_mut().filter(|_| engine.parallel_render);
+    if let Some(pool) = pool.filter(|_| jobs.len() > 1) {
+        let slots = DisjointSlice::new(slot_manager.slots_mut());
+        let buffers = DisjointSlice::new(&mut engine.slot_buffers);
+        let input = &engine.input;
+        pool.run(jobs.len(), &|job| {
+            let slot_idx = jobs[job].0;
+            // SAFETY: each slot index appears in `jobs` once
+            let (slot, buffer) = unsafe { (slots.get(slot_idx), buffers.get(slot_idx)) };
+            buffer.clear_n(num_samples);
+            if slot.is_effect() {
+                buffer.mix_from(input, num_samples);
+            }
+            let (left, right) = buffer.channels_mut();
+            slot.render(left, right, num_samples, sample_rate, transport);
+        });
+        for &(slot_idx, from, to) in jobs {
+            mix_buffer(
+                &mut engine.output_left,
+                &mut engine.output_right,
+                &engine.slot_buffers[slot_idx],
+                from,
+                to,
+                num_samples,
+                sample_rate,
+            );
         }
+    } else {
+        for &(slot_idx, from, to) in jobs {
+            let slot = &mut slot_manager.slots_mut()[slot_idx];
+            mix_slot(engine, slot, from, to, num_samples, transport);
+        }
+    }
+
+    // Slots removed while sounding play out their declick fade
+    for slot in slot_manager.retiring_mut() {
+        let m = slot_mix_matrix(slot.volume(), slot.pan(), slot.tuning().width);
+        mix_slot(engine, slot, m, m, num_samples, transport);
     }
+    slot_manager.finish_retiring();
 
-    // --- 3. Apply master volume and pan ---
+    // --- 4. Apply master volume and pan (ramped, so automation doesn't zipper),
+    //        then mono fold-down, channel swap and polarity ---
     let (master_pan_l, master_pan_r) = constant_power_pan(master_pan);
 
-    for i in 0..num_samples {
-        engine.output_left[i] *= master_gain * master_pan_l;
-        engine.output_right[i] *= master_gain * master_pan_r;
-    }
+    engine.master_left.set_target(master_gain * master_pan_l);
+    engine.master_right.set_target(master_gain * master_pan_r);
+    engine.master_left.apply(&mut engine.output_left, num_samples);
+    engine.master_right.apply(&mut engine.output_right, num_samples);
+    apply_master_output(
+        &mut engine.output_left[..num_samples],
+        &mut engine.output_right[..num_samples],
+        engine.master_output,
+    );
 
-    // --- 4. Feed visualizer levels and ring buffer (lock-free) ---
+    // --- 5. Feed visualizer levels and ring buffer (lock-free) ---
     {
         let mut peak_l = 0.0_f32;
         let mut peak_r = 0.0_f32;
@@ -203,6 +520,10 @@ pub fn render_and_mix(
         
         // Always succeeds (lock-free atomics)
         visualizer_state.update_levels(peak_l, peak_r, rms_l, rms_r);
+        let (true_peak_l, true_peak_r) = engine
+            .true_peak
+            .process(&engine.output_left[..num_samples], &engine.output_right[..num_samples]);
+        visualizer_state.update_true_peak(true_peak_l.max(true_peak_r));
 
         // Waveform uses try_lock internally, may skip if UI holds lock
         let step = (num_samples / 64).max(1);
@@ -211,11 +532,343 @@ pub fn render_and_mix(
         }
     }
 
-    // --- 5. Update live voice count ---
+    // --- 6. Update live voice count and per-slot monitor ---
     let total_voices: usize = (0..slot_manager.slot_count())
         .map(|i| slot_manager.slots()[i].active_voice_count())
         .sum();
     voice_count.store(total_voices as u32, Ordering::Relaxed);
+
+    monitor.set_timing(transport.bpm, sample_rate);
+    for (i, slot) in slot_manager.slots().iter().enumerate() {
+        monitor.set_held_notes(i, slot.held_notes());
+        monitor.set_play_event(i, slot.runner_state().play_event());
+        monitor.set_articulation(i, slot.active_keyswitch());
+        monitor.set_launch_countdown(i, slot.runner_state().launch_countdown(transport));
+        monitor.set_output_peak(i, slot.output_peak());
+        monitor.set_controllers(i, slot.preset_state().mod_wheel, slot.preset_state().expression);
+        monitor.set_pitch_bend(i, slot.bend_wheel());
+        let zone_count = slot.preset_state().active_preset.as_ref().map_or(0, |p| p.zones.len());
+        monitor.set_zone_usage(i, slot.preset_state().zone_usage(), zone_count);
+    }
+}
+
+/// Render one slot and mix it into the output through its mix matrix,
+/// ramping linearly from `from` to `to` over `SMOOTHING_SECS`.
+fn mix_slot(
+    engine: &mut AudioEngine,
+    slot: &mut Slot,
+    from: MixMatrix,
+    to: MixMatrix,
+    num_samples: usize,
+    transport: &TransportState,
+) {
+    let sample_rate = engine.sample_rate;
+
+    // Render slot into scratch buffer (borrow both channels at once);
+    // effect slots start from the audio input
+    engine.slot_buffer.clear_n(num_samples);
+    if slot.is_effect() {
+        engine.slot_buffer.mix_from(&engine.input, num_samples);
+    }
+    let (slot_left, slot_right) = engine.slot_buffer.channels_mut();
+    slot.render(slot_left, slot_right, num_samples, sample_rate, transport);
+
+    mix_buffer(
+        &mut engine.output_left,
+        &mut engine.output_right,
+        &engine.slot_buffer,
+        from,
+        to,
+        num_samples,
+        sample_rate,
+    );
+}
+
+/// Mix a rendered slot into the output through its mix matrix, ramping
+/// linearly from `from` to `to` over `SMOOTHING_SECS`.
+fn mix_buffer(
+    output_left: &mut [f32],
+    output_right: &mut [f32],
+    buffer: &MixBuffer,
+    from: MixMatrix,
+    to: MixMatrix,
+    num_samples: usize,
+    sample_rate: f32,
+) {
+    let left_out = buffer.left();
+    let right_out = buffer.right();
+
+    // Ramp sample by sample, the steady part through the SIMD mixer
+    let mut start = 0;
+    if from != to {
+        let ramp = (SMOOTHING_SECS * sample_rate).max(1.0);
+        start = (ramp as usize).min(num_samples);
+        for i in 0..start {
+            let t = ((i + 1) as f32 / ramp).min(1.0);
+            let m = |r: usize, c: usize| from[r][c] + (to[r][c] - from[r][c]) * t;
+            output_left[i] += left_out[i] * m(0, 0) + right_out[i] * m(0, 1);
+            output_right[i] += left_out[i] * m(1, 0) + right_out[i] * m(1, 1);
+        }
+    }
+    if to != SILENT && start < num_samples {
+        simd::mix_stereo(
+            &mut output_left[start..],
+            &mut output_right[start..],
+            &left_out[start..],
+            &right_out[start..],
+            to,
+            num_samples - start,
+        );
+    }
+}
+
+/// Apply host mix parameters that changed since the last block to their
+/// slots. Unchanged parameters are left alone so rack edits and MIDI rules
+/// that act on the slot directly are not overwritten every block.
+pub fn apply_slot_params(
+    slot_manager: &mut SlotManager,
+    params: &SongWalkerParams,
+    applied: &mut [Option<SlotMix>; AUTOMATABLE_SLOTS],
+) {
+    for ((slot, slot_params), last) in slot_manager
+        .slots_mut()
+        .iter_mut()
+        .zip(params.slots.iter())
+        .zip(applied.iter_mut())
+    {
+        let mix = slot_params.mix();
+        if *last == Some(mix) {
+            continue;
+        }
+        slot.set_volume(mix.volume);
+        slot.set_pan(mix.pan.clamp(-1.0, 1.0));
+        slot.set_muted(mix.muted);
+        slot.set_solo(mix.solo);
+        *last = Some(mix);
+    }
+}
+
+/// Apply an event from the editor to the slot rack.
+///
+/// Shared by the plugin `process()` and the standalone audio callback so both
+/// drain the editor channel identically.
+pub fn handle_editor_event(
+    event: EditorEvent,
+    slot_manager: &mut SlotManager,
+    transport: &TransportState,
+) {
+    match event {
+        EditorEvent::NoteOn { slot_index, note, velocity } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                let note_event = NoteEvent::NoteOn {
+                    timing: 0,
+                    voice_id: None,
+                    channel: 0,
+                    note,
+                    velocity,
+                };
+                slot.handle_midi_event(&note_event, transport);
+            }
+        }
+        EditorEvent::NoteOff { slot_index, note } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                let note_event = NoteEvent::NoteOff {
+                    timing: 0,
+                    voice_id: None,
+                    channel: 0,
+                    note,
+                    velocity: 0.0,
+                };
+                slot.handle_midi_event(&note_event, transport);
+            }
+        }
+        EditorEvent::PitchBend { slot_index, value } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                let bend_event = NoteEvent::MidiPitchBend {
+                    timing: 0,
+                    channel: 0,
+                    value: (value.clamp(-1.0, 1.0) + 1.0) * 0.5,
+                };
+                slot.handle_midi_event(&bend_event, transport);
+            }
+        }
+        EditorEvent::ModWheel { slot_index, value } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                let cc_event = NoteEvent::MidiCC {
+                    timing: 0,
+                    channel: 0,
+                    cc: 1,
+                    value: value.clamp(0.0, 1.0),
+                };
+                slot.handle_midi_event(&cc_event, transport);
+            }
+        }
+        EditorEvent::Panic => slot_manager.panic(),
+        EditorEvent::StopPreview => {
+            // All-notes-off on all slots
+            for slot in slot_manager.slots_mut() {
+                let all_off = NoteEvent::MidiCC {
+                    timing: 0,
+                    channel: 0,
+                    cc: 123,
+                    value: 0.0,
+                };
+                slot.handle_midi_event(&all_off, transport);
+            }
+        }
+        EditorEvent::RemoveSlot { slot_index } => {
+            slot_manager.remove_slot(slot_index);
+        }
+        EditorEvent::SetSlotGroup { slot_index, group } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_group(group);
+            }
+        }
+        EditorEvent::SetGroup { group_index, bus } => {
+            slot_manager.set_group(group_index, bus);
+        }
+        EditorEvent::SetSlotMidiOut { slot_index, channel } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.runner_state_mut().set_midi_out_channel(channel);
+            }
+        }
+        EditorEvent::SetSlotArp { slot_index, settings } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_arp_settings(settings);
+            }
+        }
+        EditorEvent::SetSlotHold { slot_index, hold } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_hold(hold, transport);
+            }
+        }
+        EditorEvent::ReleaseHeldNotes { slot_index } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.release_held(transport);
+            }
+        }
+        EditorEvent::PurgeZones { slot_index, instance } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.preset_state_mut().replace_zones(instance);
+            }
+        }
+        EditorEvent::SetSlotFrozen { slot_index, clip } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_frozen_clip(clip);
+            }
+        }
+        EditorEvent::SetSlotHumanize { slot_index, humanize } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.runner_state_mut().set_humanize(humanize);
+            }
+        }
+        EditorEvent::UnloadPreset { slot_index } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                // Sounding voices finish on the retired preset
+                slot.preset_state_mut().unload_preset();
+            }
+        }
+        // Engine settings are applied by the backend before this is called
+        EditorEvent::SetParallelRender { .. } => {}
+        EditorEvent::SetSlotLaunchQuantize { slot_index, quantize } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.runner_state_mut().set_launch_quantize(quantize);
+            }
+        }
+        EditorEvent::SetSlotMix { slot_index, volume, pan } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_volume(volume);
+                slot.set_pan(pan.clamp(-1.0, 1.0));
+            }
+        }
+        EditorEvent::SetSlotTuning { slot_index, tuning } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_tuning(tuning);
+            }
+        }
+        EditorEvent::SetSlotUnison { slot_index, unison } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_unison(unison);
+            }
+        }
+        EditorEvent::SetSlotLegato { slot_index, legato } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_legato(legato);
+            }
+        }
+        EditorEvent::SetSlotBend { slot_index, bend } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_bend(bend);
+            }
+        }
+        EditorEvent::SetSlotControllers { slot_index, controllers } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_controllers(controllers);
+            }
+        }
+        EditorEvent::SetSlotModMatrix { slot_index, matrix } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_mod_matrix(matrix);
+            }
+        }
+        EditorEvent::SetSlotPolyphony { slot_index, voices } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_polyphony(voices);
+            }
+        }
+        EditorEvent::SetSlotMidiFilter { slot_index, filter } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_midi_filter(filter);
+            }
+        }
+        EditorEvent::SetSlotFilter { slot_index, filter } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_filter(filter);
+            }
+        }
+        EditorEvent::SetSlotOutput { slot_index, output } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_output(output);
+            }
+        }
+        EditorEvent::SetSlotOversampling { slot_index, oversampling } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_oversampling(oversampling);
+            }
+        }
+        EditorEvent::SetSlotKeyswitches { slot_index, keyswitches } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                slot.set_keyswitches(keyswitches);
+            }
+        }
+        EditorEvent::SetResetOnStop { enabled } => {
+            slot_manager.set_reset_on_stop(enabled);
+        }
+        EditorEvent::SetGmMode { enabled } => {
+            slot_manager.set_gm_mode(enabled);
+            if enabled {
+                slot_manager.gm_mode_mut().reset();
+            }
+        }
+        EditorEvent::SetScaleLock { lock } => {
+            slot_manager.scale_filter_mut().set_lock(lock);
+        }
+        EditorEvent::SetMidiRules { rules } => {
+            slot_manager.set_midi_rules(rules);
+        }
+        EditorEvent::SetMacros { assignments } => {
+            slot_manager.set_macros(assignments);
+        }
+        EditorEvent::LoadRunnerProgram { slot_index, program } => {
+            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
+                // The editor keeps its own Arc, so replacing ours never frees here
+                slot.runner_state_mut().queue_program(program);
+                let runner = slot.runner_state();
+                let has_source = runner.has_program() || runner.has_pending_program();
+                slot.set_has_source(has_source);
+            }
+        }
+    }
 }
 
 /// Constant-power pan law. Returns (left_gain, right_gain).
@@ -226,10 +879,140 @@ pub fn constant_power_pan(pan: f32) -> (f32, f32) {
     (angle.cos(), angle.sin())
 }
 
+/// Mono fold-down, channel swap and polarity inversion, in that order.
+/// The fold-down averages the channels, so a centred source keeps its level.
+pub fn apply_master_output(left: &mut [f32], right: &mut [f32], output: MasterOutput) {
+    if output.is_bypassed() {
+        return;
+    }
+    let gain_l = if output.invert_left { -1.0 } else { 1.0 };
+    let gain_r = if output.invert_right { -1.0 } else { 1.0 };
+    for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+        let (mut a, mut b) = (*l, *r);
+        if output.mono {
+            let mid = (a + b) * 0.5;
+            (a, b) = (mid, mid);
+        }
+        if output.swap {
+            std::mem::swap(&mut a, &mut b);
+        }
+        *l = a * gain_l;
+        *r = b * gain_r;
+    }
+}
+
+/// Gain, constant-power pan and stereo width folded into one 2×2 matrix
+/// for `simd::mix_stereo`.
+#[inline]
+pub fn slot_mix_matrix(gain: f32, pan: f32, width: f32) -> MixMatrix {
+    let (pan_l, pan_r) = constant_power_pan(pan);
+    let (a, b) = (gain * pan_l, gain * pan_r);
+    let (same, cross) = ((1.0 + width) * 0.5, (1.0 - width) * 0.5);
+    [[a * same, b * cross], [a * cross, b * same]]
+}
+
+/// Mid/side stereo width: scales the side signal by `width`
+/// (0 = mono, 1 = unchanged).
+#[inline]
+pub fn stereo_width(left: f32, right: f32, width: f32) -> (f32, f32) {
+    let mid = (left + right) * 0.5;
+    let side = (left - right) * 0.5 * width;
+    (mid + side, mid - side)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_slot_mix_matrix_matches_pan_then_width() {
+        for (pan, width) in [(0.0, 1.0), (-0.5, 0.3), (0.8, 1.7)] {
+            let m = slot_mix_matrix(0.9, pan, width);
+            let (pl, pr) = constant_power_pan(pan);
+            let (l, r) = stereo_width(0.4 * 0.9 * pl, -0.2 * 0.9 * pr, width);
+            assert!((0.4 * m[0][0] - 0.2 * m[0][1] - l).abs() < 1e-6);
+            assert!((0.4 * m[1][0] - 0.2 * m[1][1] - r).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_master_output_fold_swap_invert() {
+        let (mut l, mut r) = ([1.0, 0.5], [0.0, -0.5]);
+        apply_master_output(&mut l, &mut r, MasterOutput { swap: true, invert_right: true, ..Default::default() });
+        assert_eq!((l, r), ([0.0, -0.5], [-1.0, -0.5]));
+
+        let (mut l, mut r) = ([1.0, 0.5], [0.0, -0.5]);
+        apply_master_output(&mut l, &mut r, MasterOutput { mono: true, invert_left: true, ..Default::default() });
+        assert_eq!((l, r), ([-0.5, 0.0], [0.5, 0.0]));
+
+        let (mut l, mut r) = ([1.0], [0.0]);
+        apply_master_output(&mut l, &mut r, MasterOutput::default());
+        assert_eq!((l, r), ([1.0], [0.0]));
+    }
+
+    #[test]
+    fn test_stereo_width() {
+        assert_eq!(stereo_width(1.0, 0.0, 1.0), (1.0, 0.0));
+        assert_eq!(stereo_width(1.0, 0.0, 0.0), (0.5, 0.5));
+        assert_eq!(stereo_width(1.0, 0.0, 2.0), (1.5, -0.5));
+    }
+
+    #[test]
+    fn test_latency_initial_report() {
+        let mut engine = AudioEngine::new();
+        assert_eq!(engine.latency_samples(), 0);
+        // First block always reports, so the host starts from a known value
+        assert_eq!(engine.take_latency_change(), Some(0));
+        assert_eq!(engine.take_latency_change(), None);
+    }
+
+    #[test]
+    fn test_latency_stages_aggregate() {
+        let mut engine = AudioEngine::new();
+        engine.take_latency_change();
+        engine.set_stage_latency(LatencyStage::Lookahead, 64);
+        engine.set_stage_latency(LatencyStage::Prebuffer, 128);
+        assert_eq!(engine.latency_samples(), 192);
+        assert_eq!(engine.take_latency_change(), Some(192));
+        assert_eq!(engine.take_latency_change(), None);
+    }
+
+    #[test]
+    fn test_latency_change_propagates_once() {
+        let mut engine = AudioEngine::new();
+        engine.set_stage_latency(LatencyStage::Lookahead, 32);
+        assert_eq!(engine.take_latency_change(), Some(32));
+        // Setting the same value again is not a change
+        engine.set_stage_latency(LatencyStage::Lookahead, 32);
+        assert_eq!(engine.take_latency_change(), None);
+        engine.set_stage_latency(LatencyStage::Lookahead, 0);
+        assert_eq!(engine.take_latency_change(), Some(0));
+    }
+
+    #[test]
+    fn test_oversampling_change_reaches_the_host() {
+        use crate::slots::oversampling::Oversampling;
+
+        let mut engine = AudioEngine::new();
+        let mut slot_manager = SlotManager::new_empty();
+        slot_manager.initialize(44100.0);
+        slot_manager.allocate_all();
+        let transport = TransportState::default();
+        let mut reported = Vec::new();
+        report_latency(&mut engine, &slot_manager, |samples| reported.push(samples));
+
+        handle_editor_event(
+            EditorEvent::SetSlotOversampling { slot_index: 0, oversampling: Oversampling::X4 },
+            &mut slot_manager,
+            &transport,
+        );
+        report_latency(&mut engine, &slot_manager, |samples| reported.push(samples));
+        report_latency(&mut engine, &slot_manager, |samples| reported.push(samples));
+        let latency = slot_manager.oversampling_latency();
+        assert!(latency > 0);
+        assert_eq!(reported, vec![0, latency]);
+    }
+
     #[test]
     fn test_constant_power_pan_center() {
         let (l, r) = constant_power_pan(0.0);
@@ -313,7 +1096,7 @@ mod tests {
         let fake_output_l: Vec<f32> = vec![amplitude; num_samples];
         let fake_output_r: Vec<f32> = vec![-amplitude; num_samples];
 
-        // Feed visualizer (same logic as in render_and_mix step 4)
+        // Feed visualizer (same logic as in render_and_mix step 5)
         {
             let mut peak_l = 0.0_f32;
             let mut peak_r = 0.0_f32;
@@ -531,6 +1314,7 @@ mod tests {
         let transport = crate::transport::TransportState::default();
         let visualizer_state = Arc::new(VisualizerState::new(512));
         let voice_count = Arc::new(AtomicU32::new(0));
+        let monitor = Arc::new(EngineMonitor::new());
 
         // Build a test preset
         let num_frames = 44100;
@@ -571,7 +1355,8 @@ mod tests {
             slot_index: 0,
             preset_id: Arc::new("test/relay".to_string()),
             instance: instance.clone(),
-            play_note: Some(60),
+            graph: Default::default(),
+            play_note: Some(crate::editor::PlayNote::new(60)),
         };
         ui_preset_loaded_tx.send(event).unwrap();
 
@@ -593,10 +1378,11 @@ mod tests {
             let slot = &mut slot_manager.slots_mut()[loaded.slot_index];
             slot.preset_state_mut()
                 .load_preset(loaded.preset_id.clone(), loaded.instance.clone());
-            if let Some(note) = loaded.play_note {
+            slot.preset_state_mut().set_graph(loaded.graph);
+            if let Some(play) = loaded.play_note {
                 let note_event = nih_plug::prelude::NoteEvent::NoteOn {
                     timing: 0, voice_id: None, channel: 0,
-                    note, velocity: 0.8,
+                    note: play.note, velocity: play.velocity,
                 };
                 slot_manager.slots_mut()[loaded.slot_index]
                     .handle_midi_event(&note_event, &transport);
@@ -617,6 +1403,7 @@ mod tests {
             0.0,  // master_pan (center)
             &visualizer_state,
             &voice_count,
+            &monitor,
         );
 
         // Check engine output has audio
@@ -638,4 +1425,238 @@ mod tests {
         }).unwrap_or(false);
         assert!(waveform_ok, "visualizer waveform should have non-zero data");
     }
+
+    #[test]
+    fn test_group_mute_silences_mix() {
+        use crate::slots::GroupBus;
+
+        let mut slot_manager = SlotManager::new_empty();
+        slot_manager.initialize(44100.0);
+        slot_manager.allocate_all();
+        let mut engine = AudioEngine::new();
+        engine.initialize(44100.0, 512);
+        let transport = TransportState::default();
+        let vis = Arc::new(VisualizerState::new(64));
+        let voice_count = Arc::new(AtomicU32::new(0));
+        let monitor = Arc::new(EngineMonitor::new());
+
+        handle_editor_event(
+            EditorEvent::SetSlotGroup { slot_index: 0, group: Some(0) },
+            &mut slot_manager,
+            &transport,
+        );
+        handle_editor_event(
+            EditorEvent::SetGroup {
+                group_index: 0,
+                bus: GroupBus { muted: true, ..GroupBus::default() },
+            },
+            &mut slot_manager,
+            &transport,
+        );
+        handle_editor_event(
+            EditorEvent::NoteOn { slot_index: 0, note: 69, velocity: 1.0 },
+            &mut slot_manager,
+            &transport,
+        );
+
+        render_and_mix(256, &mut engine, &mut slot_manager, &transport, 1.0, 0.0, &vis, &voice_count, &monitor);
+        let peak = engine.output_left[..256].iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+        assert_eq!(peak, 0.0, "muted group should produce silence");
+
+        handle_editor_event(
+            EditorEvent::SetGroup { group_index: 0, bus: GroupBus::default() },
+            &mut slot_manager,
+            &transport,
+        );
+        render_and_mix(256, &mut engine, &mut slot_manager, &transport, 1.0, 0.0, &vis, &voice_count, &monitor);
+        let peak = engine.output_left[..256].iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+        assert!(peak > 0.01, "unmuted group should be audible, peak={peak}");
+    }
+
+    #[test]
+    fn test_mute_fades_out_over_block_boundary() {
+        let mut slot_manager = SlotManager::new_empty();
+        slot_manager.initialize(44100.0);
+        slot_manager.allocate_all();
+        let mut engine = AudioEngine::new();
+        engine.initialize(44100.0, 512);
+        let transport = TransportState::default();
+        let vis = Arc::new(VisualizerState::new(64));
+        let voice_count = Arc::new(AtomicU32::new(0));
+        let monitor = Arc::new(EngineMonitor::new());
+
+        handle_editor_event(
+            EditorEvent::NoteOn { slot_index: 0, note: 69, velocity: 1.0 },
+            &mut slot_manager,
+            &transport,
+        );
+        render_and_mix(512, &mut engine, &mut slot_manager, &transport, 1.0, 0.0, &vis, &voice_count, &monitor);
+
+        slot_manager.slots_mut()[0].set_muted(true);
+        render_and_mix(512, &mut engine, &mut slot_manager, &transport, 1.0, 0.0, &vis, &voice_count, &monitor);
+        let head = engine.output_left[..8].iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+        let tail = engine.output_left[256..512].iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+        assert!(head > 0.0, "muting should fade, not cut");
+        assert_eq!(tail, 0.0, "slot should be silent once the fade ends");
+
+        render_and_mix(512, &mut engine, &mut slot_manager, &transport, 1.0, 0.0, &vis, &voice_count, &monitor);
+        let peak = engine.output_left[..512].iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+        assert_eq!(peak, 0.0, "muted slot should stay silent");
+    }
+
+    #[test]
+    fn test_volume_change_ramps() {
+        let mut slot_manager = SlotManager::new_empty();
+        slot_manager.initialize(44100.0);
+        slot_manager.allocate_all();
+        let mut engine = AudioEngine::new();
+        engine.initialize(44100.0, 512);
+        let transport = TransportState::default();
+        let vis = Arc::new(VisualizerState::new(64));
+        let voice_count = Arc::new(AtomicU32::new(0));
+        let monitor = Arc::new(EngineMonitor::new());
+
+        handle_editor_event(
+            EditorEvent::NoteOn { slot_index: 0, note: 69, velocity: 1.0 },
+            &mut slot_manager,
+            &transport,
+        );
+        render_and_mix(512, &mut engine, &mut slot_manager, &transport, 1.0, 0.0, &vis, &voice_count, &monitor);
+        let full = slot_mix_matrix(1.0, 0.0, 1.0);
+        assert_eq!(engine.slot_mix[0], full);
+
+        handle_editor_event(
+            EditorEvent::SetSlotMix { slot_index: 0, volume: 0.5, pan: 0.0 },
+            &mut slot_manager,
+            &transport,
+        );
+        assert_eq!(slot_manager.slots()[0].volume(), 0.5);
+        render_and_mix(512, &mut engine, &mut slot_manager, &transport, 1.0, 0.0, &vis, &voice_count, &monitor);
+        assert_eq!(engine.slot_mix[0], slot_mix_matrix(0.5, 0.0, 1.0), "ramp ends on the new volume");
+    }
+
+    #[test]
+    fn test_parallel_render_matches_serial() {
+        let transport = TransportState::default();
+        let vis = Arc::new(VisualizerState::new(64));
+        let voice_count = Arc::new(AtomicU32::new(0));
+        let monitor = Arc::new(EngineMonitor::new());
+        let mut outputs = Vec::new();
+        for parallel in [false, true] {
+            let mut slot_manager = SlotManager::new_empty();
+            slot_manager.initialize(44100.0);
+            slot_manager.allocate_all();
+            let mut engine = AudioEngine::new();
+            engine.initialize(44100.0, 256);
+            engine.set_parallel_render(parallel);
+            for slot_index in 0..4 {
+                handle_editor_event(
+                    EditorEvent::NoteOn { slot_index, note: 57 + slot_index as u8 * 4, velocity: 0.8 },
+                    &mut slot_manager,
+                    &transport,
+                );
+            }
+            let mut output = Vec::new();
+            for _ in 0..4 {
+                render_and_mix(256, &mut engine, &mut slot_manager, &transport, 1.0, 0.0, &vis, &voice_count, &monitor);
+                output.extend_from_slice(&engine.output_left[..256]);
+                output.extend_from_slice(&engine.output_right[..256]);
+            }
+            outputs.push(output);
+        }
+        assert!(outputs[0].iter().any(|&s| s != 0.0));
+        assert_eq!(outputs[0], outputs[1], "parallel render must be bit-identical");
+    }
+
+    #[test]
+    fn test_effect_slot_processes_audio_input() {
+        use songwalker_core::preset::instance::PresetInstance;
+        use songwalker_core::preset::{PresetCategory, PresetDescriptor, PresetNode, SamplerConfig};
+
+        let transport = TransportState::default();
+        let vis = Arc::new(VisualizerState::new(64));
+        let voice_count = Arc::new(AtomicU32::new(0));
+        let monitor = Arc::new(EngineMonitor::new());
+        let mut slot_manager = SlotManager::new_empty();
+        slot_manager.initialize(44100.0);
+        slot_manager.allocate_all();
+        let mut engine = AudioEngine::new();
+        engine.initialize(44100.0, 512);
+        engine.set_input(&[0.5; 512], &[0.5; 512]);
+
+        // No effect loaded: the input is not heard
+        render_and_mix(512, &mut engine, &mut slot_manager, &transport, 1.0, 0.0, &vis, &voice_count, &monitor);
+        assert!(engine.output_left[..512].iter().all(|&s| s == 0.0));
+
+        let effect = Arc::new(PresetInstance {
+            descriptor: PresetDescriptor {
+                format: None, version: None,
+                id: "fx".into(), name: "FX".into(),
+                category: PresetCategory::Effect,
+                tags: vec![], metadata: None, tuning: None,
+                graph: PresetNode::Sampler {
+                    config: SamplerConfig { zones: vec![], is_drum_kit: false, envelope: None },
+                },
+            },
+            zones: vec![],
+        });
+        slot_manager.slots_mut()[0]
+            .preset_state_mut()
+            .load_preset(Arc::new("test/fx".to_string()), effect);
+        assert!(slot_manager.slots()[0].is_effect());
+        render_and_mix(512, &mut engine, &mut slot_manager, &transport, 1.0, 0.0, &vis, &voice_count, &monitor);
+        assert!(engine.output_left[511] > 0.1, "effect slot should pass its input");
+
+        engine.clear_input();
+        render_and_mix(512, &mut engine, &mut slot_manager, &transport, 1.0, 0.0, &vis, &voice_count, &monitor);
+        assert!(engine.output_left[..512].iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_unload_preset_event_clears_instance() {
+        let mut slot_manager = SlotManager::new_empty();
+        slot_manager.allocate_all();
+        let transport = TransportState::default();
+        slot_manager.slots_mut()[2].preset_state_mut().preset_id =
+            Some(Arc::new("lib/piano".to_string()));
+
+        handle_editor_event(EditorEvent::UnloadPreset { slot_index: 2 }, &mut slot_manager, &transport);
+        let state = slot_manager.slots()[2].preset_state();
+        assert!(state.preset_id.is_none());
+        assert!(state.active_preset.is_none());
+
+        // Out-of-range slot is ignored
+        handle_editor_event(EditorEvent::UnloadPreset { slot_index: 99 }, &mut slot_manager, &transport);
+    }
+
+    #[test]
+    fn test_slot_params_apply_only_on_change() {
+        let mut slot_manager = SlotManager::new_empty();
+        slot_manager.allocate_all();
+        let params = SongWalkerParams::default();
+        let mut applied = [None; AUTOMATABLE_SLOTS];
+
+        apply_slot_params(&mut slot_manager, &params, &mut applied);
+        assert_eq!(slot_manager.slots()[0].volume(), 0.8);
+
+        // A MIDI rule mutes the slot directly; unchanged params leave it muted
+        slot_manager.slots_mut()[0].set_muted(true);
+        apply_slot_params(&mut slot_manager, &params, &mut applied);
+        assert!(slot_manager.slots()[0].is_muted());
+    }
+
+    #[test]
+    fn test_one_pole_ramps_to_target() {
+        let mut smoother = OnePole::new(0.001, 48000.0);
+        smoother.set_target(1.0);
+        assert_eq!(smoother.value(), 1.0, "first target is taken as-is");
+
+        smoother.set_target(0.0);
+        let first = smoother.next();
+        assert!(first > 0.9 && first < 1.0, "a step should ramp, got {first}");
+        let mut buf = [1.0f32; 960];
+        smoother.apply(&mut buf, 960);
+        assert!(buf[0] > buf[959]);
+        assert!(smoother.is_settled());
+    }
 }
@@ -0,0 +1,73 @@
+//! Settings files in the user config directory.
+//!
+//! Each feature keeps its settings in a JSON file of its own there
+//! (`theme.json`, `osc.json`, ...). A file that is missing or doesn't
+//! parse loads as the defaults, and files are written atomically (see
+//! `net::atomic`), so a crash mid-save never leaves a torn file.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// The config file called `name`.
+pub fn path(name: &str) -> Option<PathBuf> {
+    directories::ProjectDirs::from("org", "SongWalker", "songwalker-vsti").map(|d| d.config_dir().join(name))
+}
+
+/// The settings saved in `name` (defaults if unset or unreadable).
+pub fn load_json<T: DeserializeOwned + Default>(name: &str) -> T {
+    path(name).map(|p| read_json(&p)).unwrap_or_default()
+}
+
+/// Save settings to `name`.
+pub fn save_json<T: Serialize>(name: &str, value: &T) -> Result<(), String> {
+    let path = path(name).ok_or("No config directory")?;
+    write_json(&path, value).map_err(|e| format!("Failed to save {}: {}", name, e))
+}
+
+fn read_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    std::fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let json = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+    crate::net::atomic::write_atomic(path, &json).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    struct Settings {
+        enabled: bool,
+        port: u16,
+    }
+
+    #[test]
+    fn test_json_roundtrip_and_defaults() {
+        let dir = std::env::temp_dir().join(format!("sw-config-{}", std::process::id()));
+        let file = dir.join("nested").join("settings.json");
+        assert_eq!(read_json::<Settings>(&file), Settings::default());
+
+        let settings = Settings { enabled: true, port: 9000 };
+        write_json(&file, &settings).unwrap();
+        assert_eq!(read_json::<Settings>(&file), settings);
+
+        // Fields missing from the file take their defaults
+        std::fs::write(&file, r#"{"port": 8000}"#).unwrap();
+        assert_eq!(read_json::<Settings>(&file), Settings { enabled: false, port: 8000 });
+        std::fs::write(&file, "not json").unwrap();
+        assert_eq!(read_json::<Settings>(&file), Settings::default());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,187 @@
+//! Text size and hit-target settings, independent of window zoom.
+//!
+//! Zoom scales the whole window, which soon outgrows the screen. The font
+//! scale only enlarges text (egui's text styles plus every explicit size
+//! passed through `fs`), and large-targets mode pads buttons, sliders and
+//! checkboxes so they are easier to hit. Stored per machine in
+//! `accessibility.json` under the user config directory.
+//!
+//! Screen readers see the widgets through egui's AccessKit tree. Widgets
+//! whose visible text says little on its own ("S", "\u{2715}", a slider
+//! with no value shown) are named here with the slot or preset they act on.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use nih_plug_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use super::{colors, theme};
+
+/// Font scale range offered in Settings.
+pub const MIN_FONT_SCALE: f32 = 0.75;
+pub const MAX_FONT_SCALE: f32 = 2.0;
+
+/// Saved accessibility options.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    /// Multiplier for all text sizes.
+    pub font_scale: f32,
+    /// Larger buttons, sliders and checkboxes.
+    pub large_targets: bool,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            font_scale: 1.0,
+            large_targets: false,
+        }
+    }
+}
+
+/// Font scale as f32 bits (1.0 until the settings are applied).
+static FONT_SCALE: AtomicU32 = AtomicU32::new(0x3F80_0000);
+static LARGE_TARGETS: AtomicBool = AtomicBool::new(false);
+
+/// The active font scale.
+pub fn font_scale() -> f32 {
+    f32::from_bits(FONT_SCALE.load(Ordering::Relaxed))
+}
+
+/// The saved settings (defaults if unset).
+pub fn load_settings() -> AccessibilitySettings {
+    crate::config::load_json("accessibility.json")
+}
+
+/// Save the settings.
+pub fn save_settings(settings: &AccessibilitySettings) -> Result<(), String> {
+    crate::config::save_json("accessibility.json", settings)
+}
+
+/// Make `settings` the active ones. Takes effect on the next style
+/// rebuild (`theme::apply`).
+pub fn set_active(settings: &AccessibilitySettings) {
+    let scale = settings.font_scale.clamp(MIN_FONT_SCALE, MAX_FONT_SCALE);
+    FONT_SCALE.store(scale.to_bits(), Ordering::Relaxed);
+    LARGE_TARGETS.store(settings.large_targets, Ordering::Relaxed);
+}
+
+/// Scale the text styles and hit targets of a freshly built style.
+pub fn scale_style(style: &mut egui::Style) {
+    let scale = font_scale();
+    for (text_style, font) in egui::Style::default().text_styles {
+        style.text_styles.insert(text_style, egui::FontId::new(font.size * scale, font.family));
+    }
+    if LARGE_TARGETS.load(Ordering::Relaxed) {
+        style.spacing.interact_size = egui::vec2(48.0, 28.0);
+        style.spacing.button_padding = egui::vec2(10.0, 6.0);
+        style.spacing.icon_width = 20.0;
+        style.spacing.icon_width_inner = 12.0;
+        style.spacing.slider_width = 140.0;
+        style.spacing.combo_height = 300.0;
+    }
+}
+
+/// Accessibility options being edited in Settings.
+#[derive(Default)]
+pub struct AccessibilityState {
+    /// Saved settings, read when Settings is first drawn.
+    settings: Option<AccessibilitySettings>,
+}
+
+/// Text size and large-targets controls for the Settings tab.
+pub fn draw_settings(ui: &mut egui::Ui, state: &mut AccessibilityState) {
+    let settings = state.settings.get_or_insert_with(load_settings);
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Text size:").color(colors::subtext0()));
+        let slider = egui::Slider::new(&mut settings.font_scale, MIN_FONT_SCALE..=MAX_FONT_SCALE)
+            .step_by(0.05)
+            .custom_formatter(|v, _| format!("{}%", (v * 100.0).round()));
+        // Apply once the drag ends, so the slider doesn't grow under the pointer
+        let response = ui.add(slider).on_hover_text("Scales text only; zoom (Ctrl +/−) scales the whole window");
+        changed |= response.drag_stopped() || (response.changed() && !response.dragged());
+        if ui.small_button("Reset").clicked() {
+            settings.font_scale = 1.0;
+            changed = true;
+        }
+    });
+    changed |= ui
+        .checkbox(&mut settings.large_targets, "Large click targets")
+        .on_hover_text("Bigger buttons, sliders and checkboxes")
+        .changed();
+
+    if changed {
+        set_active(settings);
+        theme::apply(ui.ctx(), &theme::active());
+        if let Err(e) = save_settings(settings) {
+            nih_plug::debug::nih_log!("[Settings] {}", e);
+        }
+    }
+}
+
+// ── Screen-reader names ──────────────────────────────────────
+
+/// Name a widget for screen readers, in place of the name egui derives
+/// from its visible text.
+pub fn name(response: &egui::Response, typ: egui::WidgetType, name: impl Into<String>) {
+    let name = name.into();
+    let enabled = response.enabled();
+    response.widget_info(|| egui::WidgetInfo::labeled(typ, enabled, &name));
+}
+
+/// Name a button that shows whether it is on by its colour (mute, solo).
+pub fn name_toggle(response: &egui::Response, name: impl Into<String>, on: bool) {
+    let name = name.into();
+    let enabled = response.enabled();
+    response.widget_info(|| egui::WidgetInfo::selected(egui::WidgetType::Button, enabled, on, &name));
+}
+
+/// A linear gain as spoken: "-6 dB", or "silent" at zero.
+pub fn gain_text(gain: f32) -> String {
+    if gain <= 0.0 {
+        return "silent".to_string();
+    }
+    format!("{} dB", nih_plug::util::gain_to_db(gain).round() as i32)
+}
+
+/// A pan position (−1..1) as spoken: "centre", "40% left".
+pub fn pan_text(pan: f32) -> String {
+    let percent = (pan.abs() * 100.0).round() as u32;
+    match percent {
+        0 => "centre".to_string(),
+        _ if pan < 0.0 => format!("{}% left", percent),
+        _ => format!("{}% right", percent),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_style_scales_text_only() {
+        let mut style = egui::Style::default();
+        set_active(&AccessibilitySettings { font_scale: 5.0, large_targets: false });
+        assert_eq!(font_scale(), MAX_FONT_SCALE);
+        scale_style(&mut style);
+        let body = &style.text_styles[&egui::TextStyle::Body];
+        let default_body = &egui::Style::default().text_styles[&egui::TextStyle::Body];
+        assert_eq!(body.size, default_body.size * MAX_FONT_SCALE);
+        assert_eq!(style.spacing.interact_size, egui::Style::default().spacing.interact_size);
+        set_active(&AccessibilitySettings::default());
+    }
+
+    #[test]
+    fn test_spoken_gain_and_pan() {
+        assert_eq!(gain_text(0.5), "-6 dB");
+        assert_eq!(gain_text(1.0), "0 dB");
+        assert_eq!(gain_text(0.999), "0 dB");
+        assert_eq!(gain_text(0.0), "silent");
+        assert_eq!(pan_text(0.001), "centre");
+        assert_eq!(pan_text(-0.4), "40% left");
+        assert_eq!(pan_text(1.0), "100% right");
+    }
+}
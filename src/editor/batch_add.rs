@@ -0,0 +1,129 @@
+//! Adding several browser presets to the rack at once.
+//!
+//! Presets picked with Ctrl/Shift-click in the browser go into consecutive
+//! slots in one action, and their loads are tracked as one batch: the
+//! status line and the browser show how many have arrived until the last
+//! one is in. Each load still goes through the editor's `LoadManager`, so
+//! a preset picked twice is fetched once. The per-preset download size
+//! prompt is skipped for a batch; the progress shows what is still coming.
+
+use nih_plug_egui::egui;
+
+use super::loads::LoadTarget;
+use super::{EditorState, colors, fs};
+use crate::view_model;
+
+/// A batch being loaded.
+struct Batch {
+    /// (slot, preset id) of each preset in the batch.
+    slots: Vec<(usize, String)>,
+    loaded: usize,
+    failed: usize,
+}
+
+#[derive(Default)]
+pub struct BatchAddState {
+    current: Option<Batch>,
+}
+
+impl BatchAddState {
+    /// (arrived, total) of the batch being loaded.
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        self.current.as_ref().map(|b| (b.loaded + b.failed, b.slots.len()))
+    }
+}
+
+/// Add the browser's multi-selection to the rack and load it.
+pub fn add_selection(state: &mut EditorState) {
+    let selection = std::mem::take(&mut state.browser_state.multi_select);
+    let presets: Vec<(String, String, String)> = {
+        let Ok(pm) = state.preset_manager.lock() else { return };
+        selection
+            .into_iter()
+            .map(|(library, path)| {
+                let name = super::preset_info::find_preset(&pm, &library, &path)
+                    .map_or_else(|| path.rsplit('/').next().unwrap_or(&path).to_string(), |info| info.name);
+                (library, name, path)
+            })
+            .collect()
+    };
+    if presets.is_empty() {
+        return;
+    }
+
+    let slots = view_model::assign_presets(&state.plugin_state, &presets);
+    for ((library, _, path), &slot) in presets.iter().zip(&slots) {
+        state.loads.request(&state.jobs, &state.preset_manager, LoadTarget::Slot(slot), library, path, slot, None);
+    }
+    if let Some(&first) = slots.first() {
+        state.slot_rack_state.selected_slot = first;
+    }
+    let slots = presets
+        .iter()
+        .zip(slots)
+        .map(|((library, _, path), slot)| (slot, format!("{}/{}", library, path)))
+        .collect();
+    state.browser_state.batch.current = Some(Batch { slots, loaded: 0, failed: 0 });
+    poll(state);
+}
+
+/// Count the batch's arrivals and report progress. Called once per frame,
+/// after loaded presets have been forwarded.
+pub fn poll(state: &mut EditorState) {
+    let Some(batch) = &mut state.browser_state.batch.current else { return };
+    let (mut loaded, mut failed) = (0, 0);
+    for (slot, preset_id) in &batch.slots {
+        let arrived = state.active_presets_ui.get(slot).is_some_and(|(id, _)| **id == *preset_id);
+        if arrived {
+            loaded += 1;
+        } else if !state.loads.is_loading(preset_id) {
+            // Failed, or the slot was given something else meanwhile
+            failed += 1;
+        }
+    }
+    batch.loaded = loaded;
+    batch.failed = failed;
+
+    let total = batch.slots.len();
+    let message = if loaded + failed < total {
+        format!("Loading presets {}/{}\u{2026}", loaded + failed, total)
+    } else if failed == 0 {
+        format!("Added {} presets to the rack", total)
+    } else {
+        format!("\u{26a0} Added {} of {} presets ({} failed)", loaded, total, failed)
+    };
+    if loaded + failed == total {
+        state.browser_state.batch.current = None;
+    }
+    if let Ok(mut st) = state.status_text.lock() {
+        *st = message;
+    }
+}
+
+/// "Add N selected to rack" bar, shown while several presets are picked
+/// or a batch is loading.
+pub fn draw_bar(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
+    if let Some((arrived, total)) = state.browser_state.batch.progress() {
+        ui.add(
+            egui::ProgressBar::new(arrived as f32 / total.max(1) as f32)
+                .text(egui::RichText::new(format!("Loading {}/{}", arrived, total)).size(fs(10.0, z))),
+        );
+        return;
+    }
+    let count = state.browser_state.multi_select.len();
+    if count < 2 {
+        return;
+    }
+    ui.horizontal(|ui| {
+        if ui
+            .button(egui::RichText::new(format!("Add {} selected to rack", count)).color(colors::green()).size(fs(11.0, z)))
+            .on_hover_text("Load them into consecutive slots")
+            .clicked()
+        {
+            add_selection(state);
+        }
+        if ui.small_button(egui::RichText::new("Clear").size(fs(10.0, z))).clicked() {
+            state.browser_state.multi_select.clear();
+        }
+    });
+}
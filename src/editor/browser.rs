@@ -1,20 +1,31 @@
 use nih_plug_egui::egui;
 use std::sync::Arc;
 
-use super::colors;
-use super::zs;
-use super::EditorState;
-use super::PresetLoadedEvent;
-use crate::preset::loader::PresetLoader;
-use crate::preset::manager::{LibraryStatus, PresetManager};
-use crate::state::SlotConfig;
-
-/// Number of presets to show per page in the browser.
-const PAGE_SIZE: usize = 100;
+use super::batch_add::{self, BatchAddState};
+use super::{accessibility, colors, focus};
+use super::focus::{BrowserRow, FocusPanel};
+use super::{fs, zs};
+use super::{EditorState, PlayNote};
+use super::loads::LoadTarget;
+use super::download_check::{self, PendingAction};
+use super::piano::note_name;
+use super::preset_info::{self, InfoAction, PresetInfoState};
+use super::preview::{self, PreviewMode, PreviewPlayer};
+use crate::preset::{changes, integrity, library_index, local_library};
+use crate::preset::manager::LibraryStatus;
+use crate::preset::{revalidate, updates};
+use crate::view_model;
+
+/// Height of an expanded folder's preset list, in rows. Longer lists
+/// scroll inside the folder, laying out only the visible rows.
+const FOLDER_VISIBLE_ROWS: usize = 16;
+
+/// Scroll area id of the search results.
+const SEARCH_SCROLL_ID: &str = "search";
 
 /// Number of slots reserved for preview round-robin playback.
 /// This allows clicking multiple presets rapidly without cutting off previous ones.
-const PREVIEW_SLOTS: usize = 8;
+pub(crate) const PREVIEW_SLOTS: usize = 8;
 
 /// Persistent state for the preset browser.
 #[derive(Default)]
@@ -22,11 +33,51 @@ pub struct BrowserState {
     pub search_text: String,
     pub selected_category: Option<String>,
     pub selected_preset: Option<(String, String)>, // (library, preset_path)
-    /// Per-context page offset: key is a library name, sub-index key, or "search".
-    pub page_offsets: std::collections::HashMap<String, usize>,
+    /// Presets picked with Ctrl/Shift-click for adding to the rack
+    /// together, in the order picked.
+    pub multi_select: Vec<(String, String)>,
+    /// Shift-clicked row, resolved to a range by the list it is in.
+    shift_clicked: Option<(String, String)>,
+    /// Loading progress of the last batch add.
+    pub batch: BatchAddState,
+    /// Scroll the search results back to the top on the next frame (set
+    /// when the query or category changes). Folder lists keep their own
+    /// scroll position, stored by egui under the folder's key.
+    scroll_search_to_top: bool,
     /// Round-robin counter for preview slot allocation.
     /// Each preview click uses the next slot so multiple presets can play simultaneously.
     next_preview_slot: usize,
+    /// Preview note/chord/phrase options and scheduled note-offs.
+    pub preview: PreviewPlayer,
+    /// Descriptor details of selected presets.
+    pub info: PresetInfoState,
+}
+
+impl BrowserState {
+    /// The search text and category as a preset filter.
+    fn filter(&self) -> view_model::PresetFilter {
+        view_model::PresetFilter::new(&self.search_text, self.selected_category.as_deref())
+    }
+
+    /// Ctrl/Cmd-click: add a preset to the multi-selection or take it out,
+    /// starting from the single selection.
+    fn toggle_selected(&mut self, preset: (String, String)) {
+        if self.multi_select.is_empty() {
+            self.multi_select.extend(self.selected_preset.clone());
+        }
+        match self.multi_select.iter().position(|p| *p == preset) {
+            Some(i) => {
+                self.multi_select.remove(i);
+            }
+            None => self.multi_select.push(preset),
+        }
+        self.selected_preset = self.multi_select.last().cloned();
+    }
+
+    fn is_selected(&self, library: &str, path: &str) -> bool {
+        let is = |(lib, p): &(String, String)| lib == library && p == path;
+        self.selected_preset.as_ref().is_some_and(is) || self.multi_select.iter().any(is)
+    }
 }
 
 /// Category chip definitions matching the JS version.
@@ -41,6 +92,8 @@ const CATEGORIES: &[(&str, &str)] = &[
 /// Draw the preset browser panel (matches JS PresetBrowser layout).
 pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
     ui.set_clip_rect(ui.max_rect());
+    state.focus.browser_rows.clear();
+    reload_updated_presets(state);
     ui.vertical(|ui| {
         ui.set_max_width(ui.available_width());
         ui.spacing_mut().item_spacing = egui::vec2(zs(6.0, z), zs(3.0, z));
@@ -49,12 +102,14 @@ pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
         ui.horizontal(|ui| {
             ui.label(
                 egui::RichText::new("Presets")
-                    .color(colors::TEXT)
+                    .color(colors::text())
                     .strong()
-                    .size(zs(14.0, z)),
+                    .size(fs(14.0, z)),
             );
         });
 
+        draw_preview_options(ui, state, z);
+
         ui.add_space(zs(4.0, z));
 
         // --- Search bar ---
@@ -64,12 +119,9 @@ pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
                     .hint_text("Search presets…")
                     .desired_width(ui.available_width()),
             );
+            accessibility::name(&response, egui::WidgetType::TextEdit, "Search presets");
             if response.changed() {
-                if let Ok(mut pm) = state.preset_manager.lock() {
-                    pm.search_query = state.browser_state.search_text.clone();
-                }
-                // Reset all pagination when search changes
-                state.browser_state.page_offsets.clear();
+                state.browser_state.scroll_search_to_top = true;
             }
         });
 
@@ -87,20 +139,20 @@ pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
 
                 let color = if is_selected {
                     match value {
-                        "sampler" => colors::GREEN,
-                        "synth" => colors::BLUE,
-                        "composite" => colors::MAUVE,
-                        "effect" => colors::PEACH,
-                        _ => colors::BLUE,
+                        "sampler" => colors::green(),
+                        "synth" => colors::blue(),
+                        "composite" => colors::mauve(),
+                        "effect" => colors::peach(),
+                        _ => colors::blue(),
                     }
                 } else {
-                    colors::SUBTEXT0
+                    colors::subtext0()
                 };
 
                 if ui
                     .selectable_label(
                         is_selected,
-                        egui::RichText::new(label).color(color).size(zs(11.0, z)),
+                        egui::RichText::new(label).color(color).size(fs(11.0, z)),
                     )
                     .clicked()
                 {
@@ -109,29 +161,26 @@ pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
                     } else {
                         state.browser_state.selected_category = Some(value.to_string());
                     }
-                    if let Ok(mut pm) = state.preset_manager.lock() {
-                        pm.category_filter = state.browser_state.selected_category.clone();
-                    }
-                    // Reset pagination on category change
-                    state.browser_state.page_offsets.clear();
+                    state.browser_state.scroll_search_to_top = true;
                 }
             }
         });
 
+        batch_add::draw_bar(ui, state, z);
+
         ui.separator();
 
-        // --- Library tree ---
-        egui::ScrollArea::both()
-            .auto_shrink([false, false])
-            .show(ui, |ui| {
-                let search_active = !state.browser_state.search_text.is_empty();
+        // --- Info panel for the selected preset ---
+        draw_info_panel(ui, state, z);
 
-                if search_active {
-                    draw_search_results(ui, state, z);
-                } else {
-                    draw_library_tree(ui, state, z);
-                }
-            });
+        // --- Library tree (search results scroll on their own) ---
+        if !state.browser_state.search_text.is_empty() {
+            draw_search_results(ui, state, z);
+        } else {
+            egui::ScrollArea::both()
+                .auto_shrink([false, false])
+                .show(ui, |ui| draw_library_tree(ui, state, z));
+        }
 
         // --- Status bar ---
         ui.add_space(zs(4.0, z));
@@ -139,8 +188,8 @@ pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
             if !pm.status_message.is_empty() {
                 ui.label(
                     egui::RichText::new(&pm.status_message)
-                        .color(colors::OVERLAY0)
-                        .size(zs(10.0, z))
+                        .color(colors::overlay0())
+                        .size(fs(10.0, z))
                         .italics(),
                 );
             }
@@ -148,10 +197,187 @@ pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
     });
 }
 
+/// Info panel pinned to the bottom of the browser while a preset is selected.
+fn draw_info_panel(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
+    let Some((library, path)) = state.browser_state.selected_preset.clone() else {
+        return;
+    };
+    let info = state
+        .preset_manager
+        .lock()
+        .ok()
+        .and_then(|pm| preset_info::find_preset(&pm, &library, &path));
+    let Some(info) = info else { return };
+    let details = state
+        .browser_state
+        .info
+        .details(&state.jobs, &state.preset_manager, &library, &path);
+
+    let action = egui::TopBottomPanel::bottom("preset_info")
+        .frame(egui::Frame::NONE.fill(colors::mantle()).inner_margin(zs(6.0, z)))
+        .resizable(false)
+        .show_inside(ui, |ui| preset_info::draw(ui, &info, &library, &details, z))
+        .inner;
+    match action {
+        Some(InfoAction::Preview) => preview_preset(state, &library, &path),
+        Some(InfoAction::Add) => load_into_slot(state, &library, &info.name, &path),
+        Some(InfoAction::Download) => {
+            preset_info::spawn_download(
+                &state.jobs,
+                state.preset_manager.clone(),
+                state.status_text.clone(),
+                library,
+                path,
+            );
+        }
+        None => {}
+    }
+}
+
+/// Load a preset into the next preview slot and play it (after a size
+/// check if it has to be downloaded).
+pub(crate) fn preview_preset(state: &mut EditorState, lib_name: &str, preset_path: &str) {
+    let play = PlayNote::new(state.browser_state.preview.settings.note);
+    audition(state, lib_name, preset_path, play);
+}
+
+/// Preview a preset starting on a given note and velocity.
+pub(crate) fn audition(state: &mut EditorState, lib_name: &str, preset_path: &str, play: PlayNote) {
+    download_check::request(state, lib_name, preset_path, PendingAction::Preview(play));
+}
+
+/// Add a preset to the next available slot and load it there (after a size
+/// check if it has to be downloaded).
+pub(crate) fn load_into_slot(state: &mut EditorState, library: &str, name: &str, path: &str) {
+    download_check::request(state, library, path, PendingAction::AddToSlot { name: name.to_string() });
+}
+
+/// Load a preset into the next preview slot and play it.
+pub(crate) fn start_preview(state: &mut EditorState, lib_name: &str, preset_path: &str, play: PlayNote) {
+    let preview_slot = state.browser_state.next_preview_slot;
+    state.browser_state.next_preview_slot = (preview_slot + 1) % PREVIEW_SLOTS;
+    spawn_preset_load(state, LoadTarget::Preview, lib_name, preset_path, preview_slot, Some(play));
+}
+
+/// Add a preset to the next available slot and load it there.
+pub(crate) fn start_load_into_slot(state: &mut EditorState, library: &str, name: &str, path: &str) {
+    let slot_idx = add_preset_to_slot(state, library, name, path);
+    spawn_preset_load(state, LoadTarget::Slot(slot_idx), library, path, slot_idx, None);
+}
+
+/// Load a preset into a given slot, replacing its preset.
+pub(crate) fn start_load_into(state: &mut EditorState, slot_idx: usize, library: &str, name: &str, path: &str) {
+    let preset_id = format!("{}/{}", library, path);
+    let assigned = view_model::update_slot(&state.plugin_state, slot_idx, |cfg| cfg.assign_preset(name, preset_id));
+    if assigned {
+        spawn_preset_load(state, LoadTarget::Slot(slot_idx), library, path, slot_idx, None);
+    }
+}
+
+/// One preset in a browser list.
+struct PresetRow {
+    library: String,
+    name: String,
+    path: String,
+    category: String,
+}
+
+impl PresetRow {
+    fn new(library: &str, p: &crate::preset::manager::PresetInfo) -> Self {
+        Self {
+            library: library.to_string(),
+            name: p.name.clone(),
+            path: p.path.clone(),
+            category: p.category.clone(),
+        }
+    }
+}
+
+/// Row height used to lay out preset lists.
+fn preset_row_height(ui: &egui::Ui, z: f32) -> f32 {
+    ui.spacing().interact_size.y.max(zs(16.0, z))
+}
+
+/// Draw a preset list in its own scroll area of at most `max_height`,
+/// laying out only the rows in view. `scroll_id` keys the scroll position
+/// egui keeps for the list.
+fn draw_preset_rows(
+    ui: &mut egui::Ui,
+    state: &mut EditorState,
+    scroll_id: &str,
+    rows: &[PresetRow],
+    indent: f32,
+    max_height: f32,
+    z: f32,
+) {
+    let row_height = preset_row_height(ui, z);
+    let mut area = egui::ScrollArea::vertical()
+        .id_salt(scroll_id)
+        .auto_shrink([false, true])
+        .max_height(max_height);
+    if scroll_id == SEARCH_SCROLL_ID && std::mem::take(&mut state.browser_state.scroll_search_to_top) {
+        area = area.vertical_scroll_offset(0.0);
+    }
+
+    // Keyboard navigation walks every listed row, not just the visible ones
+    let selected = state.browser_state.selected_preset.clone();
+    let mut selected_index = None;
+    for (i, row) in rows.iter().enumerate() {
+        if selected.as_ref().is_some_and(|(lib, path)| *lib == row.library && *path == row.path) {
+            selected_index = Some(i);
+        }
+        state.focus.browser_rows.push(BrowserRow {
+            library: row.library.clone(),
+            path: row.path.clone(),
+            name: row.name.clone(),
+        });
+    }
+    if let Some(i) = selected_index.filter(|_| state.focus.scroll_to_selection) {
+        state.focus.scroll_to_selection = false;
+        let pitch = row_height + ui.spacing().item_spacing.y;
+        area = area.vertical_scroll_offset((i as f32 * pitch - max_height / 2.0).max(0.0));
+    }
+    area.show_rows(ui, row_height, rows.len(), |ui, range| {
+        let rows = &rows[range];
+        let mut badges = vec![RowBadges::default(); rows.len()];
+        if let Ok(new_presets) = changes::global().lock() {
+            for (row, badge) in rows.iter().zip(&mut badges) {
+                badge.new = new_presets.is_new(&row.library, &row.path);
+            }
+        }
+        if let Ok(updates) = updates::global().lock() {
+            for (row, badge) in rows.iter().zip(&mut badges) {
+                badge.update = updates.is_outdated(&row.library, &row.path);
+            }
+        }
+        for (row, badge) in rows.iter().zip(badges) {
+            draw_preset_row(ui, state, &row.library, &row.name, &row.path, &row.category, badge, indent, z);
+        }
+    });
+
+    // Shift-click picks the rows from the single selection to the clicked one
+    if let Some(target) = state.browser_state.shift_clicked.take() {
+        let index = |(lib, path): &(String, String)| rows.iter().position(|r| r.library == *lib && r.path == *path);
+        let browser = &mut state.browser_state;
+        match (browser.selected_preset.as_ref().and_then(index), index(&target)) {
+            (Some(a), Some(b)) => {
+                browser.multi_select =
+                    rows[a.min(b)..=a.max(b)].iter().map(|r| (r.library.clone(), r.path.clone())).collect();
+            }
+            _ => browser.toggle_selected(target),
+        }
+    }
+}
+
+/// Visible height of an expanded folder's list.
+fn folder_height(ui: &egui::Ui, z: f32) -> f32 {
+    FOLDER_VISIBLE_ROWS as f32 * (preset_row_height(ui, z) + ui.spacing().item_spacing.y)
+}
+
 /// Draw the collapsible library tree (no search active).
 fn draw_library_tree(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
     // Collect library info outside the lock
-    let libraries: Vec<(String, String, usize, LibraryStatus, bool)> = if let Ok(pm) = state.preset_manager.lock() {
+    let libraries: Vec<(String, String, usize, LibraryStatus, bool, bool)> = if let Ok(pm) = state.preset_manager.lock() {
         pm.libraries
             .iter()
             .map(|l| {
@@ -161,6 +387,7 @@ fn draw_library_tree(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
                     l.preset_count,
                     l.status.clone(),
                     l.expanded,
+                    local_library::is_local(&l.slug),
                 )
             })
             .collect()
@@ -171,14 +398,16 @@ fn draw_library_tree(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
     if libraries.is_empty() {
         ui.label(
             egui::RichText::new("No presets loaded. Check internet connection.")
-                .color(colors::OVERLAY0)
-                .size(zs(11.0, z))
+                .color(colors::overlay0())
+                .size(fs(11.0, z))
                 .italics(),
         );
         return;
     }
 
-    for (name, _desc, count, status, expanded) in &libraries {
+    for (name, _desc, count, status, expanded, local) in &libraries {
+        let new_count = changes::global().lock().map_or(0, |n| n.count(name));
+        let update_count = updates::global().lock().map_or(0, |u| u.count(name));
         // Library folder row
         let chevron = if *expanded { "\u{25BE}" } else { "\u{25B8}" };
         let status_indicator = match status {
@@ -194,7 +423,7 @@ fn draw_library_tree(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
 
         if response.hovered() {
             ui.painter()
-                .rect_filled(rect, zs(4.0, z), colors::SURFACE0.gamma_multiply(0.5));
+                .rect_filled(rect, zs(4.0, z), colors::surface0().gamma_multiply(0.5));
         }
 
         ui.allocate_new_ui(egui::UiBuilder::new().max_rect(rect), |ui| {
@@ -202,41 +431,84 @@ fn draw_library_tree(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
                 ui.add_space(zs(4.0, z));
                 ui.label(
                     egui::RichText::new(chevron)
-                        .color(colors::SUBTEXT0)
-                        .size(zs(12.0, z))
+                        .color(colors::subtext0())
+                        .size(fs(12.0, z))
                         .family(egui::FontFamily::Monospace),
                 );
-                ui.label(egui::RichText::new("\u{1F4C1}").size(zs(12.0, z)));
+                ui.label(egui::RichText::new("\u{1F4C1}").size(fs(12.0, z)));
                 ui.label(
                     egui::RichText::new(&format!("{}{}", name, status_indicator))
-                        .color(colors::TEXT)
-                        .size(zs(12.0, z)),
+                        .color(colors::text())
+                        .size(fs(12.0, z)),
                 );
                 ui.label(
                     egui::RichText::new(&format!("({})", count))
-                        .color(colors::OVERLAY0)
-                        .size(zs(11.0, z)),
+                        .color(colors::overlay0())
+                        .size(fs(11.0, z)),
                 );
+                if new_count > 0 {
+                    ui.label(
+                        egui::RichText::new(format!("{} new", new_count))
+                            .color(colors::yellow())
+                            .size(fs(10.0, z)),
+                    );
+                }
+                if update_count > 0 {
+                    ui.label(
+                        egui::RichText::new(format!("{} updated", update_count))
+                            .color(colors::peach())
+                            .size(fs(10.0, z)),
+                    )
+                    .on_hover_text("Cached presets with a newer version on the server");
+                }
+                // Refresh button, shown while the pointer is over the row
+                if !*local && ui.rect_contains_pointer(rect) {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui
+                            .small_button(egui::RichText::new("\u{21BB}").size(fs(11.0, z)))
+                            .on_hover_text("Check for an updated index")
+                            .clicked()
+                        {
+                            revalidate::spawn_library_refresh(&state.jobs, state.preset_manager.clone(), name.clone());
+                        }
+                    });
+                }
             });
         });
 
+        response.context_menu(|ui| {
+            if !*local && ui.button("Refresh").clicked() {
+                revalidate::spawn_library_refresh(&state.jobs, state.preset_manager.clone(), name.clone());
+                ui.close_menu();
+            }
+            if new_count > 0 && ui.button("Mark presets as seen").clicked() {
+                changes::mark_seen(name);
+                ui.close_menu();
+            }
+            if update_count > 0 && ui.button(format!("Update changed presets ({})", update_count)).clicked() {
+                let outdated = updates::global().lock().map(|u| u.outdated(name)).unwrap_or_default();
+                for path in outdated {
+                    updates::spawn_redownload(&state.jobs, state.preset_manager.clone(), name.clone(), path);
+                }
+                ui.close_menu();
+            }
+            if ui.button("Verify integrity").clicked() {
+                integrity::spawn_library_verification(
+                    &state.jobs,
+                    state.preset_manager.clone(),
+                    name.clone(),
+                );
+                ui.close_menu();
+            }
+        });
+
         // Handle click on library folder row
         if response.clicked() {
-            let lib_name = name.clone();
-            if let Ok(mut pm) = state.preset_manager.lock() {
-                if let Some(lib) = pm.libraries.iter_mut().find(|l| l.name == lib_name) {
-                    lib.expanded = !lib.expanded;
-                    let should_fetch = lib.expanded && lib.status == LibraryStatus::NotLoaded;
-                    let is_expanded = lib.expanded;
-                    drop(pm);
-
-                    if should_fetch {
-                        // Trigger background fetch
-                        PresetManager::fetch_library_index(state.preset_manager.clone(), lib_name);
-                    }
-                    let _ = is_expanded; // suppress warning
-                }
+            // Collapsing a browsed library ends the visit: its presets are no longer new
+            if *expanded {
+                changes::mark_seen(name);
             }
+            view_model::toggle_library(&state.jobs, &state.preset_manager, name);
         }
 
         // Show presets if library is expanded
@@ -268,21 +540,15 @@ fn draw_sub_indexes(
     z: f32,
 ) {
     // Collect sub-index info outside the lock
+    let filter = state.browser_state.filter();
     let sub_idxs: Vec<(String, String, usize, bool)> = if let Ok(pm) = state.preset_manager.lock()
     {
         pm.sub_indexes
             .get(lib_name)
             .map(|subs| {
-                let query = pm.search_query.to_lowercase();
                 subs.iter()
-                    .filter(|s| {
-                        // If there's a search query, filter sub-indexes by name
-                        if query.is_empty() {
-                            true
-                        } else {
-                            s.name.to_lowercase().contains(&query)
-                        }
-                    })
+                    // If there's a search query, filter sub-indexes by name
+                    .filter(|s| filter.matches_name(&s.name))
                     .map(|s| {
                         (
                             s.name.clone(),
@@ -305,8 +571,8 @@ fn draw_sub_indexes(
                     ui.add_space(zs(24.0, z));
                     ui.label(
                         egui::RichText::new("Loading…")
-                            .color(colors::OVERLAY0)
-                            .size(zs(11.0, z))
+                            .color(colors::overlay0())
+                            .size(fs(11.0, z))
                             .italics(),
                     );
                 });
@@ -316,8 +582,8 @@ fn draw_sub_indexes(
                     ui.add_space(zs(24.0, z));
                     ui.label(
                         egui::RichText::new("No sub-indexes")
-                            .color(colors::OVERLAY0)
-                            .size(zs(11.0, z))
+                            .color(colors::overlay0())
+                            .size(fs(11.0, z))
                             .italics(),
                     );
                 });
@@ -336,7 +602,7 @@ fn draw_sub_indexes(
 
         if response.hovered() {
             ui.painter()
-                .rect_filled(rect, zs(4.0, z), colors::SURFACE0.gamma_multiply(0.5));
+                .rect_filled(rect, zs(4.0, z), colors::surface0().gamma_multiply(0.5));
         }
 
         ui.allocate_new_ui(egui::UiBuilder::new().max_rect(rect), |ui| {
@@ -344,20 +610,20 @@ fn draw_sub_indexes(
                 ui.add_space(zs(24.0, z)); // Indent
                 ui.label(
                     egui::RichText::new(chevron)
-                        .color(colors::SUBTEXT0)
-                        .size(zs(11.0, z))
+                        .color(colors::subtext0())
+                        .size(fs(11.0, z))
                         .family(egui::FontFamily::Monospace),
                 );
-                ui.label(egui::RichText::new("\u{1F3B5}").size(zs(11.0, z)));
+                ui.label(egui::RichText::new("\u{1F3B5}").size(fs(11.0, z)));
                 ui.label(
                     egui::RichText::new(sub_name)
-                        .color(colors::SUBTEXT1)
-                        .size(zs(11.0, z)),
+                        .color(colors::subtext1())
+                        .size(fs(11.0, z)),
                 );
                 ui.label(
                     egui::RichText::new(&format!("({})", inst_count))
-                        .color(colors::OVERLAY0)
-                        .size(zs(10.0, z)),
+                        .color(colors::overlay0())
+                        .size(fs(10.0, z)),
                 );
             });
         });
@@ -385,7 +651,7 @@ fn draw_sub_indexes(
             }
 
             if should_fetch {
-                PresetManager::fetch_sub_index(state.preset_manager.clone(), lib, sn, sp);
+                library_index::start_sub_index_fetch(&state.jobs, state.preset_manager.clone(), lib, sn, sp);
             }
         }
 
@@ -405,10 +671,11 @@ fn draw_sub_index_presets(
     sub_key: &str,
     z: f32,
 ) {
-    let all_presets: Vec<(String, String, String)> = if let Ok(pm) = state.preset_manager.lock() {
-        pm.filtered_presets_for_sub_index(sub_key)
+    let filter = state.browser_state.filter();
+    let all_presets: Vec<PresetRow> = if let Ok(pm) = state.preset_manager.lock() {
+        filter.sub_index_presets(&pm, sub_key)
             .iter()
-            .map(|p| (p.name.clone(), p.path.clone(), p.category.clone()))
+            .map(|p| PresetRow::new(lib_name, p))
             .collect()
     } else {
         Vec::new()
@@ -419,23 +686,15 @@ fn draw_sub_index_presets(
             ui.add_space(zs(44.0, z));
             ui.label(
                 egui::RichText::new("Loading…")
-                    .color(colors::OVERLAY0)
-                    .size(zs(11.0, z))
+                    .color(colors::overlay0())
+                    .size(fs(11.0, z))
                     .italics(),
             );
         });
         return;
     }
 
-    let offset = *state.browser_state.page_offsets.get(sub_key).unwrap_or(&0);
-    let page = &all_presets[offset.min(all_presets.len())..];
-    let showing = page.len().min(PAGE_SIZE);
-
-    for (preset_name, preset_path, category) in &page[..showing] {
-        draw_preset_row(ui, state, lib_name, preset_name, preset_path, category, zs(44.0, z), z);
-    }
-
-    draw_pagination_controls(ui, state, sub_key, offset, all_presets.len(), zs(44.0, z), z);
+    draw_preset_rows(ui, state, sub_key, &all_presets, zs(44.0, z), folder_height(ui, z), z);
 }
 
 /// Draw a flat list of presets for a library (no sub-indexes).
@@ -448,10 +707,11 @@ fn draw_preset_list(
     indent: f32,
     z: f32,
 ) {
-    let all_presets: Vec<(String, String, String)> = if let Ok(pm) = state.preset_manager.lock() {
-        pm.filtered_presets_for_library(filter_lib)
+    let filter = state.browser_state.filter();
+    let all_presets: Vec<PresetRow> = if let Ok(pm) = state.preset_manager.lock() {
+        filter.library_presets(&pm, filter_lib)
             .iter()
-            .map(|p| (p.name.clone(), p.path.clone(), p.category.clone()))
+            .map(|p| PresetRow::new(lib_name, p))
             .collect()
     } else {
         Vec::new()
@@ -464,8 +724,8 @@ fn draw_preset_list(
                     ui.add_space(indent);
                     ui.label(
                         egui::RichText::new("Loading…")
-                            .color(colors::OVERLAY0)
-                            .size(zs(11.0, z))
+                            .color(colors::overlay0())
+                            .size(fs(11.0, z))
                             .italics(),
                     );
                 });
@@ -475,8 +735,8 @@ fn draw_preset_list(
                     ui.add_space(indent);
                     ui.label(
                         egui::RichText::new(&format!("⚠ {}", e))
-                            .color(colors::RED)
-                            .size(zs(11.0, z)),
+                            .color(colors::red())
+                            .size(fs(11.0, z)),
                     );
                 });
             }
@@ -485,8 +745,8 @@ fn draw_preset_list(
                     ui.add_space(indent);
                     ui.label(
                         egui::RichText::new("No presets")
-                            .color(colors::OVERLAY0)
-                            .size(zs(11.0, z))
+                            .color(colors::overlay0())
+                            .size(fs(11.0, z))
                             .italics(),
                     );
                 });
@@ -495,19 +755,19 @@ fn draw_preset_list(
         return;
     }
 
-    let page_key = filter_lib.to_string();
-    let offset = *state.browser_state.page_offsets.get(&page_key).unwrap_or(&0);
-    let page = &all_presets[offset.min(all_presets.len())..];
-    let showing = page.len().min(PAGE_SIZE);
-
-    for (preset_name, preset_path, category) in &page[..showing] {
-        draw_preset_row(ui, state, lib_name, preset_name, preset_path, category, indent, z);
-    }
-
-    draw_pagination_controls(ui, state, &page_key, offset, all_presets.len(), indent, z);
+    draw_preset_rows(ui, state, filter_lib, &all_presets, indent, folder_height(ui, z), z);
 }
 
 /// Draw a single preset row with play/add buttons and category indicator.
+/// Badges shown after a preset's name.
+#[derive(Debug, Clone, Copy, Default)]
+struct RowBadges {
+    /// Added since the library was last browsed.
+    new: bool,
+    /// The cached copy differs from the library's current version.
+    update: bool,
+}
+
 fn draw_preset_row(
     ui: &mut egui::Ui,
     state: &mut EditorState,
@@ -515,47 +775,49 @@ fn draw_preset_row(
     preset_name: &str,
     preset_path: &str,
     category: &str,
+    badges: RowBadges,
     indent: f32,
     z: f32,
 ) {
-    let is_selected = state.browser_state.selected_preset.as_ref()
-        == Some(&(lib_name.to_string(), preset_path.to_string()));
+    let is_selected = state.browser_state.is_selected(lib_name, preset_path);
 
     let cat_color = match category {
-        "sampler" => colors::GREEN,
-        "synth" => colors::BLUE,
-        "composite" => colors::MAUVE,
-        "effect" => colors::PEACH,
-        _ => colors::SUBTEXT0,
+        "sampler" => colors::green(),
+        "synth" => colors::blue(),
+        "composite" => colors::mauve(),
+        "effect" => colors::peach(),
+        _ => colors::subtext0(),
     };
 
-    ui.horizontal(|ui| {
+    let row = ui.horizontal(|ui| {
         ui.add_space(indent);
 
-        // Play button (painted triangle)
-        if play_triangle_button(ui, z).clicked() {
-            let preview_slot = state.browser_state.next_preview_slot;
-            state.browser_state.next_preview_slot = (preview_slot + 1) % PREVIEW_SLOTS;
-            spawn_preset_load(state, lib_name, preset_path, preview_slot, Some(60));
+        // Play button (painted triangle), velocity by click height
+        let preview_note = state.browser_state.preview.settings.note;
+        let button = play_triangle_button(ui, preview_note, z);
+        accessibility::name(&button, egui::WidgetType::Button, format!("Preview {}", preset_name));
+        if button.clicked() {
+            let position = button.interact_pointer_pos().map(|p| (p.y - button.rect.top()) / button.rect.height());
+            let velocity = preview::preview_velocity(position, ui.input(|i| i.modifiers));
+            audition(state, lib_name, preset_path, PlayNote { note: preview_note, velocity });
         }
 
         // "+" add-to-slot button
-        if ui
+        let add = ui
             .small_button(
                 egui::RichText::new("+")
-                    .color(colors::GREEN)
-                    .size(zs(10.0, z)),
+                    .color(colors::green())
+                    .size(fs(10.0, z)),
             )
-            .on_hover_text("Add to next available slot")
-            .clicked()
-        {
-            let slot_idx = add_preset_to_slot(state, lib_name, preset_name, preset_path);
-            spawn_preset_load(state, lib_name, preset_path, slot_idx, None);
+            .on_hover_text("Add to next available slot");
+        accessibility::name(&add, egui::WidgetType::Button, format!("Add {} to the next available slot", preset_name));
+        if add.clicked() {
+            load_into_slot(state, lib_name, preset_name, preset_path);
         }
 
         let dot = egui::RichText::new("●")
             .color(cat_color)
-            .size(zs(8.0, z));
+            .size(fs(8.0, z));
         ui.label(dot);
 
         let display_name = if preset_name.len() > 35 {
@@ -567,134 +829,128 @@ fn draw_preset_row(
         let response = ui.selectable_label(
             is_selected,
             egui::RichText::new(&display_name)
-                .color(if is_selected { colors::BLUE } else { colors::TEXT })
-                .size(zs(11.0, z)),
+                .color(if is_selected { colors::blue() } else { colors::text() })
+                .size(fs(11.0, z)),
         );
+        let mut spoken = match category {
+            "" => format!("{}, {}", preset_name, lib_name),
+            _ => format!("{}, {} in {}", preset_name, category, lib_name),
+        };
+        if badges.new {
+            spoken.push_str(", new");
+        }
+        if badges.update {
+            spoken.push_str(", update available");
+        }
+        accessibility::name(&response, egui::WidgetType::SelectableLabel, spoken);
+        let keyboard_selected = state
+            .browser_state
+            .selected_preset
+            .as_ref()
+            .is_some_and(|(lib, path)| lib == lib_name && path == preset_path);
+        if keyboard_selected && state.focus.panel == FocusPanel::Browser {
+            focus::anchor(&mut state.focus, &response);
+        }
+
+        if badges.new {
+            ui.label(egui::RichText::new("NEW").color(colors::yellow()).size(fs(9.0, z)).strong())
+                .on_hover_text("Added since you last browsed this library");
+        }
+        if badges.update
+            && ui
+                .small_button(egui::RichText::new("UPDATE").color(colors::peach()).size(fs(9.0, z)).strong())
+                .on_hover_text("A newer version is available. Click to download it and reload the slots using it")
+                .clicked()
+        {
+            updates::spawn_redownload(
+                &state.jobs,
+                state.preset_manager.clone(),
+                lib_name.to_string(),
+                preset_path.to_string(),
+            );
+        }
 
         if response.clicked() {
-            state.browser_state.selected_preset =
-                Some((lib_name.to_string(), preset_path.to_string()));
-            // Also trigger preview load/play on click
-            let preview_slot = state.browser_state.next_preview_slot;
-            state.browser_state.next_preview_slot = (preview_slot + 1) % PREVIEW_SLOTS;
-            spawn_preset_load(state, lib_name, preset_path, preview_slot, Some(60));
+            let modifiers = ui.input(|i| i.modifiers);
+            let clicked = (lib_name.to_string(), preset_path.to_string());
+            state.focus.panel = FocusPanel::Browser;
+            if modifiers.shift && state.browser_state.selected_preset.is_some() {
+                state.browser_state.shift_clicked = Some(clicked);
+            } else if modifiers.command {
+                state.browser_state.toggle_selected(clicked);
+            } else {
+                state.browser_state.multi_select.clear();
+                state.browser_state.selected_preset = Some(clicked);
+                // Also trigger preview load/play on click
+                preview_preset(state, lib_name, preset_path);
+            }
         }
 
-        response.on_hover_text(format!("{}/{}", lib_name, preset_path));
+        let info = &state.browser_state.info;
+        response.on_hover_ui(|ui| {
+            let status = info.estimate(&state.jobs, &state.preset_manager, lib_name, preset_path);
+            ui.label(format!("{}/{}", lib_name, preset_path));
+            ui.label(download_check::size_hint(&status));
+        });
     });
+
+    // Audition keys while the pointer is over the row
+    if ui.rect_contains_pointer(row.response.rect) && ui.memory(|m| m.focused().is_none()) {
+        let root = state.browser_state.preview.settings.note;
+        let (pressed, modifiers) = ui.input(|i| {
+            let pressed = preview::AUDITION_KEYS.into_iter().find(|k| i.key_pressed(*k));
+            (pressed, i.modifiers)
+        });
+        if let Some(note) = pressed.and_then(|key| preview::audition_note(root, key)) {
+            let velocity = preview::preview_velocity(None, modifiers);
+            audition(state, lib_name, preset_path, PlayNote { note, velocity });
+        }
+    }
 }
 
 /// Draw flat search results across all loaded presets.
 fn draw_search_results(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
-    let results: Vec<(String, String, String, String)> = if let Ok(pm) = state.preset_manager.lock() {
-        let mut all = Vec::new();
-        // Flat library presets
-        for lib in &pm.libraries {
-            for p in pm.filtered_presets_for_library(&lib.name) {
-                all.push((
-                    lib.name.clone(),
-                    p.name.clone(),
-                    p.path.clone(),
-                    p.category.clone(),
-                ));
-            }
-        }
-        // Sub-index presets (from hierarchical libraries)
-        for (key, _presets) in &pm.sub_index_presets {
-            let lib_name = key.split('/').next().unwrap_or(key);
-            for p in pm.filtered_presets_for_sub_index(key) {
-                all.push((
-                    lib_name.to_string(),
-                    p.name.clone(),
-                    p.path.clone(),
-                    p.category.clone(),
-                ));
-            }
-        }
-        all
+    let filter = state.browser_state.filter();
+    let results: Vec<PresetRow> = if let Ok(pm) = state.preset_manager.lock() {
+        filter.all_presets(&pm).into_iter().map(|(library, p)| PresetRow::new(library, p)).collect()
     } else {
         Vec::new()
     };
 
     if results.is_empty() {
         ui.label(
-            egui::RichText::new("No matching presets. Expand folders to load more.")
-                .color(colors::OVERLAY0)
-                .size(zs(11.0, z))
+            egui::RichText::new("No matching presets.")
+                .color(colors::overlay0())
+                .size(fs(11.0, z))
                 .italics(),
         );
         return;
     }
 
-    let page_key = "search".to_string();
-    let offset = *state.browser_state.page_offsets.get(&page_key).unwrap_or(&0);
-    let page = &results[offset.min(results.len())..];
-    let showing = page.len().min(PAGE_SIZE);
-
-    for (lib_name, preset_name, preset_path, category) in &page[..showing] {
-        draw_preset_row(ui, state, lib_name, preset_name, preset_path, category, 0.0, z);
-    }
-
-    draw_pagination_controls(ui, state, &page_key, offset, results.len(), 0.0, z);
+    // Fill the panel, leaving room for the status line
+    let height = (ui.available_height() - zs(24.0, z)).max(preset_row_height(ui, z));
+    draw_preset_rows(ui, state, SEARCH_SCROLL_ID, &results, 0.0, height, z);
 }
 
-/// Draw "Show previous" / "Show more" pagination controls.
-fn draw_pagination_controls(
-    ui: &mut egui::Ui,
-    state: &mut EditorState,
-    page_key: &str,
-    offset: usize,
-    total: usize,
-    indent: f32,
-    z: f32,
-) {
-    if total <= PAGE_SIZE && offset == 0 {
-        return; // Everything fits on one page
-    }
-
-    ui.horizontal(|ui| {
-        ui.add_space(indent);
-
-        let end = (offset + PAGE_SIZE).min(total);
-        ui.label(
-            egui::RichText::new(&format!("{}-{} of {}", offset + 1, end, total))
-                .color(colors::OVERLAY0)
-                .size(zs(10.0, z)),
-        );
-
-        if offset > 0 {
-            if ui
-                .small_button(
-                    egui::RichText::new("◀ Previous")
-                        .color(colors::BLUE)
-                        .size(zs(10.0, z)),
-                )
-                .clicked()
-            {
-                let new_offset = offset.saturating_sub(PAGE_SIZE);
-                state
-                    .browser_state
-                    .page_offsets
-                    .insert(page_key.to_string(), new_offset);
-            }
-        }
-
-        if offset + PAGE_SIZE < total {
-            if ui
-                .small_button(
-                    egui::RichText::new("Next ▶")
-                        .color(colors::BLUE)
-                        .size(zs(10.0, z)),
-                )
-                .clicked()
-            {
-                state
-                    .browser_state
-                    .page_offsets
-                    .insert(page_key.to_string(), offset + PAGE_SIZE);
-            }
+/// Reload the slots playing a preset that was just re-downloaded.
+fn reload_updated_presets(state: &mut EditorState) {
+    let refreshed = updates::global().lock().map(|mut u| u.take_refreshed()).unwrap_or_default();
+    for (library, path) in refreshed {
+        let preset_id = format!("{}/{}", library, path);
+        let slots: Vec<usize> = match state.plugin_state.lock() {
+            Ok(ps) => ps
+                .slot_configs
+                .iter()
+                .enumerate()
+                .filter(|(_, cfg)| cfg.preset_id.as_deref() == Some(preset_id.as_str()))
+                .map(|(idx, _)| idx)
+                .collect(),
+            Err(_) => continue,
+        };
+        for idx in slots {
+            spawn_preset_load(state, LoadTarget::Slot(idx), &library, &path, idx, None);
         }
-    });
+    }
 }
 
 /// Add a preset to the next available (empty) slot, or create a new one.
@@ -705,122 +961,101 @@ fn add_preset_to_slot(
     preset_name: &str,
     preset_path: &str,
 ) -> usize {
-    let preset_id = format!("{}/{}", library_name, preset_path);
-
-    if let Ok(mut ps) = state.plugin_state.lock() {
-        // Find the first empty slot (no preset assigned)
-        let empty_idx = ps
-            .slot_configs
-            .iter()
-            .position(|c| c.preset_id.is_none() && c.source_code.is_empty());
-
-        if let Some(idx) = empty_idx {
-            // Assign to existing empty slot
-            ps.slot_configs[idx].name = preset_name.to_string();
-            ps.slot_configs[idx].preset_id = Some(preset_id);
-            state.slot_rack_state.selected_slot = idx;
-            idx
-        } else {
-            // Create a new slot with this preset
-            let config = SlotConfig::new_preset(preset_name, &preset_id);
-            let idx = ps.add_slot_config(config);
+    match view_model::assign_preset(&state.plugin_state, library_name, preset_name, preset_path) {
+        Some(idx) => {
             state.slot_rack_state.selected_slot = idx;
             idx
         }
-    } else {
-        0
+        None => 0,
     }
 }
 
-/// Spawn a background thread that loads a preset (fetches JSON descriptor
-/// and decodes all sample data) then delivers the result to the audio thread
-/// via the `preset_loaded_tx` channel.
+/// Request a preset load into a slot (fetches JSON descriptor and decodes
+/// all sample data in the background), delivered to the audio thread once
+/// ready.
 ///
-/// If `play_note` is `Some(midi_note)`, the audio thread will also trigger a
+/// Goes through the editor's `LoadManager`, so a newer request for the same
+/// target cancels this one and duplicate requests share one fetch. If
+/// `play_note` is set, the audio thread will also trigger a
 /// NoteOn immediately after loading (used for the preview play button).
 fn spawn_preset_load(
-    state: &EditorState,
+    state: &mut EditorState,
+    target: LoadTarget,
     library_name: &str,
     preset_path: &str,
     slot_index: usize,
-    play_note: Option<u8>,
+    play_note: Option<PlayNote>,
 ) {
-    let preset_manager = state.preset_manager.clone();
-    let ui_preset_loaded_tx = state.ui_preset_loaded_tx.clone();
-    let status_text = state.status_text.clone();
-    let library = library_name.to_string();
-    let path = preset_path.to_string();
-
-    nih_plug::debug::nih_log!("[Browser] Spawning load for preset: {}/{} into slot {}", library_name, preset_path, slot_index);
+    nih_plug::debug::nih_log!("[Browser] Requesting load for preset: {}/{} into slot {}", library_name, preset_path, slot_index);
 
     // Display the short name in the status bar
-    let display_name = path.rsplit('/').next().unwrap_or(&path).to_string();
-    if let Ok(mut st) = status_text.lock() {
+    let display_name = preset_path.rsplit('/').next().unwrap_or(preset_path);
+    if let Ok(mut st) = state.status_text.lock() {
         *st = format!("Loading {}\u{2026}", display_name);
     }
 
-    std::thread::spawn(move || {
-        nih_plug::debug::nih_log!("[LoaderThread] Background thread started for {}/{}", library, path);
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build();
-
-        let Ok(rt) = rt else {
-            nih_plug::debug::nih_log!("[LoaderThread] Error: Failed to create async runtime");
-            if let Ok(mut st) = status_text.lock() {
-                *st = "\u{26a0} Failed to create async runtime".to_string();
-            }
-            return;
-        };
+    state.loads.request(
+        &state.jobs,
+        &state.preset_manager,
+        target,
+        library_name,
+        preset_path,
+        slot_index,
+        play_note,
+    );
+}
 
-        let (base_url, slug) = {
-            let pm = preset_manager.lock().unwrap();
-            let slug = pm
-                .libraries
-                .iter()
-                .find(|l| l.name == library)
-                .map(|l| l.slug.clone())
-                .unwrap_or_else(|| library.clone());
-            (pm.base_url.clone(), slug)
-        };
-        let loader = PresetLoader::new().with_base_url(base_url);
-
-        nih_plug::debug::nih_log!("[LoaderThread] Fetching preset: slug={} path={}", slug, path);
-
-        match rt.block_on(loader.load_preset(&slug, &path, 44100.0)) {
-            Ok(instance) => {
-                let preset_id = Arc::new(format!("{}/{}", library, path));
-                let zone_count = instance.zones.len();
-                nih_plug::debug::nih_log!("[LoaderThread] Successfully loaded preset {}: zones={}", preset_id, zone_count);
-                let _ = ui_preset_loaded_tx.try_send(PresetLoadedEvent {
-                    slot_index,
-                    preset_id,
-                    instance: Arc::new(instance),
-                    play_note,
-                });
-                if let Ok(mut st) = status_text.lock() {
-                    *st = format!("Loaded {} ({} zones)", display_name, zone_count);
-                }
-            }
-            Err(e) => {                nih_plug::debug::nih_log!("[LoaderThread] Error loading preset: {:?}", e);                if let Ok(mut st) = status_text.lock() {
-                    *st = format!("\u{26a0} Error: {}", e);
+/// Preview note, mode and length, plus a stop button for all previews.
+fn draw_preview_options(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
+    let player = &mut state.browser_state.preview;
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Preview").color(colors::subtext0()).size(fs(11.0, z)));
+
+        let settings = &mut player.settings;
+        ui.add(
+            egui::DragValue::new(&mut settings.note)
+                .range(24..=96)
+                .custom_formatter(|n, _| note_name(n as u8)),
+        )
+        .on_hover_text("Preview note");
+        egui::ComboBox::from_id_salt("preview_mode")
+            .width(zs(64.0, z))
+            .selected_text(settings.mode.label())
+            .show_ui(ui, |ui| {
+                for mode in PreviewMode::ALL {
+                    ui.selectable_value(&mut settings.mode, mode, mode.label());
                 }
-            }
+            });
+        ui.add(
+            egui::DragValue::new(&mut settings.duration_secs)
+                .range(0.25..=8.0)
+                .speed(0.05)
+                .fixed_decimals(2)
+                .suffix(" s"),
+        )
+        .on_hover_text("Release the preview after this long");
+
+        if ui
+            .small_button(egui::RichText::new("■").color(colors::red()).size(fs(10.0, z)))
+            .on_hover_text("Stop all previews")
+            .clicked()
+        {
+            player.stop_all(&state.event_tx);
         }
     });
 }
 
 /// Draw a small play triangle button (▶) using the egui painter.
 /// Returns the Response so the caller can check `.clicked()`.
-fn play_triangle_button(ui: &mut egui::Ui, z: f32) -> egui::Response {
+fn play_triangle_button(ui: &mut egui::Ui, note: u8, z: f32) -> egui::Response {
     let size = zs(18.0, z);
     let (rect, response) = ui.allocate_exact_size(egui::vec2(size, size), egui::Sense::click());
 
     if ui.is_rect_visible(rect) {
         let color = if response.hovered() {
-            colors::GREEN
+            colors::green()
         } else {
-            colors::TEAL
+            colors::teal()
         };
 
         // Draw a right-pointing triangle centered in the rect
@@ -838,5 +1073,9 @@ fn play_triangle_button(ui: &mut egui::Ui, z: f32) -> egui::Response {
         ));
     }
 
-    response.on_hover_text("Preview preset (C4)")
+    response.on_hover_text(format!(
+        "Preview preset ({0}). Click lower for louder, Shift for soft, Ctrl for full velocity. \
+         Over a row, keys A\u{2013}K audition it from {0} up an octave",
+        note_name(note)
+    ))
 }
@@ -2,76 +2,153 @@ use nih_plug_egui::egui;
 use egui::text::LayoutJob;
 
 use super::colors;
+use super::{fs, zs};
 
-/// Draw the `.sw` code editor panel (expanded view for runner slots).
+/// Draw the inline `.sw` code editor for a runner slot.
 ///
-/// This provides syntax highlighting using songwalker-core lexer token types.
-pub fn draw(ui: &mut egui::Ui, source: &mut String) {
-    ui.vertical(|ui| {
-        ui.label(egui::RichText::new(".sw Track Editor").color(colors::TEXT));
-        ui.separator();
-
-        // Build a custom layouter that applies syntax highlighting
-        let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
-            let highlights = syntax_highlight(text);
-            let mut job = LayoutJob::default();
-            job.wrap.max_width = wrap_width;
-
-            let font_id = egui::FontId::monospace(14.0);
-            let default_color = colors::TEXT;
-
-            if highlights.is_empty() {
-                // No highlighting — render all text in default color
-                job.append(text, 0.0, egui::TextFormat {
-                    font_id,
-                    color: default_color,
-                    ..Default::default()
-                });
-            } else {
-                let mut last_end = 0;
-                for (range, color) in &highlights {
-                    // Gap before this token (whitespace, etc.)
-                    if range.start > last_end {
-                        job.append(&text[last_end..range.start], 0.0, egui::TextFormat {
-                            font_id: font_id.clone(),
-                            color: default_color,
-                            ..Default::default()
-                        });
-                    }
-                    // The highlighted token
-                    let end = range.end.min(text.len());
-                    if range.start < end {
-                        job.append(&text[range.start..end], 0.0, egui::TextFormat {
-                            font_id: font_id.clone(),
-                            color: *color,
-                            ..Default::default()
-                        });
-                    }
-                    last_end = end;
-                }
-                // Remaining text after last token
-                if last_end < text.len() {
-                    job.append(&text[last_end..], 0.0, egui::TextFormat {
-                        font_id,
-                        color: default_color,
-                        ..Default::default()
-                    });
-                }
-            }
+/// Renders a line-number gutter, syntax highlighting using songwalker-core
+/// lexer token types, a highlight on the line currently playing, and a
+/// squiggle under the line reported by the compiler (hover it for the
+/// message). Returns `true` if the source was edited.
+pub fn draw(
+    ui: &mut egui::Ui,
+    id_salt: impl std::hash::Hash,
+    source: &mut String,
+    error: Option<&str>,
+    play_note: Option<usize>,
+    rows: usize,
+    z: f32,
+) -> bool {
+    let font_id = egui::FontId::monospace(fs(13.0, z));
+    let (row_height, char_width) =
+        ui.fonts(|f| (f.row_height(&font_id), f.glyph_width(&font_id, '0')));
+
+    let line_count = line_count(source);
+    let error_line = error.and_then(error_line);
+    let play_line = play_note.and_then(|n| note_lines(source).get(n).copied());
+    let digits = line_count.to_string().len().max(2);
+    let gutter_width = char_width * digits as f32 + zs(8.0, z);
 
+    let mut changed = false;
+    ui.horizontal_top(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        ui.add_space(gutter_width);
+
+        // Custom layouter that applies syntax highlighting (no wrapping, so
+        // every source line is exactly one row tall)
+        let layout_font = font_id.clone();
+        let mut layouter = |ui: &egui::Ui, text: &str, _wrap_width: f32| {
+            let mut job = highlight_job(text, &layout_font);
+            job.wrap.max_width = f32::INFINITY;
             ui.fonts(|f| f.layout_job(job))
         };
 
-        // Full-size code editor with syntax highlighting
-        let _response = ui.add(
-            egui::TextEdit::multiline(source)
-                .font(egui::TextStyle::Monospace)
-                .desired_rows(20)
-                .desired_width(f32::INFINITY)
-                .code_editor()
-                .layouter(&mut layouter),
-        );
+        let output = egui::TextEdit::multiline(source)
+            .id_salt(id_salt)
+            .font(font_id.clone())
+            .desired_rows(rows)
+            .desired_width(f32::INFINITY)
+            .code_editor()
+            .lock_focus(true)
+            .layouter(&mut layouter)
+            .show(ui);
+
+        changed = output.response.changed();
+
+        let text_rect = output.response.rect;
+        let origin = output.galley_pos;
+        let painter = ui.painter_at(text_rect.expand2(egui::vec2(gutter_width, 0.0)));
+        let row_top = |line: usize| origin.y + line as f32 * row_height;
+
+        // Current play position
+        if let Some(line) = play_line {
+            let rect = egui::Rect::from_min_size(
+                egui::pos2(text_rect.left(), row_top(line)),
+                egui::vec2(text_rect.width(), row_height),
+            );
+            painter.rect_filled(rect, 0.0, colors::blue().gamma_multiply(0.15));
+        }
+
+        // Line numbers
+        for line in 0..line_count {
+            let color = if Some(line) == error_line {
+                colors::red()
+            } else if Some(line) == play_line {
+                colors::blue()
+            } else {
+                colors::overlay0()
+            };
+            painter.text(
+                egui::pos2(text_rect.left() - zs(4.0, z), row_top(line)),
+                egui::Align2::RIGHT_TOP,
+                (line + 1).to_string(),
+                font_id.clone(),
+                color,
+            );
+        }
+
+        // Error squiggle with hover message
+        if let (Some(line), Some(message)) = (error_line, error) {
+            let chars = source.lines().nth(line).map(|l| l.chars().count()).unwrap_or(0);
+            let width = char_width * chars.max(1) as f32;
+            let y = row_top(line) + row_height - 1.0;
+            let step = zs(3.0, z);
+            let mut points = Vec::new();
+            let mut x = origin.x;
+            let mut up = false;
+            while x <= origin.x + width {
+                points.push(egui::pos2(x, if up { y - 1.5 } else { y + 1.5 }));
+                up = !up;
+                x += step;
+            }
+            painter.add(egui::Shape::line(points, egui::Stroke::new(1.0, colors::red())));
+
+            let error_rect = egui::Rect::from_min_max(
+                egui::pos2(text_rect.left() - gutter_width, row_top(line)),
+                egui::pos2(text_rect.right(), row_top(line) + row_height),
+            );
+            if ui.rect_contains_pointer(error_rect) {
+                output.response.on_hover_text_at_pointer(
+                    egui::RichText::new(message).color(colors::red()),
+                );
+            }
+        }
     });
+
+    changed
+}
+
+/// Build a syntax-highlighted layout job for the given source.
+fn highlight_job(text: &str, font_id: &egui::FontId) -> LayoutJob {
+    let highlights = syntax_highlight(text);
+    let mut job = LayoutJob::default();
+    let default_color = colors::text();
+
+    let format = |color| egui::TextFormat {
+        font_id: font_id.clone(),
+        color,
+        ..Default::default()
+    };
+
+    let mut last_end = 0;
+    for (range, color) in &highlights {
+        // Gap before this token (whitespace, etc.)
+        if range.start > last_end {
+            job.append(&text[last_end..range.start], 0.0, format(default_color));
+        }
+        // The highlighted token
+        let end = range.end.min(text.len());
+        if range.start < end {
+            job.append(&text[range.start..end], 0.0, format(*color));
+        }
+        last_end = end.max(last_end);
+    }
+    // Remaining text after last token (or all of it if lexing failed)
+    if last_end < text.len() {
+        job.append(&text[last_end..], 0.0, format(default_color));
+    }
+
+    job
 }
 
 /// Highlight tokens in the source code (returns colored layout jobs).
@@ -87,22 +164,22 @@ pub fn syntax_highlight(source: &str) -> Vec<(std::ops::Range<usize>, egui::Colo
         Ok(tokens) => {
             for spanned in &tokens {
                 let color = match &spanned.token {
-                    songwalker_core::token::Token::Track => colors::YELLOW,
+                    songwalker_core::token::Token::Track => colors::yellow(),
                     songwalker_core::token::Token::Const
                     | songwalker_core::token::Token::Let
-                    | songwalker_core::token::Token::For => colors::MAUVE,
-                    songwalker_core::token::Token::Number(_) => colors::PEACH,
-                    songwalker_core::token::Token::StringLit(_) => colors::GREEN,
+                    | songwalker_core::token::Token::For => colors::mauve(),
+                    songwalker_core::token::Token::Number(_) => colors::peach(),
+                    songwalker_core::token::Token::StringLit(_) => colors::green(),
                     songwalker_core::token::Token::Ident(name) => {
                         // Check if it looks like a note name (C, C#, D, etc. followed by octave)
                         if is_note_name(name) {
-                            colors::TEAL
+                            colors::teal()
                         } else {
-                            colors::TEXT
+                            colors::text()
                         }
                     }
-                    songwalker_core::token::Token::Comment(_) => colors::OVERLAY0,
-                    _ => colors::SUBTEXT0,
+                    songwalker_core::token::Token::Comment(_) => colors::overlay0(),
+                    _ => colors::subtext0(),
                 };
 
                 highlights.push((spanned.span.start..spanned.span.end, color));
@@ -116,6 +193,74 @@ pub fn syntax_highlight(source: &str) -> Vec<(std::ops::Range<usize>, egui::Colo
     highlights
 }
 
+/// Source line (0-based) of each note name in the source, in order.
+///
+/// Best-effort mapping from the runner's note ordinal back to the editor:
+/// the n-th note fired is assumed to come from the n-th note literal.
+pub fn note_lines(source: &str) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let mut lexer = songwalker_core::lexer::Lexer::new(source);
+    let Ok(tokens) = lexer.tokenize() else {
+        return lines;
+    };
+
+    let bytes = source.as_bytes();
+    let mut line = 0;
+    let mut offset = 0;
+    for spanned in &tokens {
+        if let songwalker_core::token::Token::Ident(name) = &spanned.token {
+            if is_note_name(name) {
+                let start = spanned.span.start.min(bytes.len());
+                if start >= offset {
+                    line += bytes[offset..start].iter().filter(|&&b| b == b'\n').count();
+                    offset = start;
+                }
+                lines.push(line);
+            }
+        }
+    }
+    lines
+}
+
+/// Extract the (0-based) line number from a compiler error message.
+///
+/// Understands "line N" and "N:M" (line:column) forms, both 1-based.
+pub fn error_line(message: &str) -> Option<usize> {
+    let lower = message.to_ascii_lowercase();
+    if let Some(pos) = lower.find("line") {
+        let digits: String = lower[pos + 4..]
+            .trim_start_matches([' ', ':'])
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        if let Ok(n) = digits.parse::<usize>() {
+            return n.checked_sub(1);
+        }
+    }
+
+    // "N:M" — first run of digits immediately followed by ':' and a digit
+    let bytes = lower.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i].is_ascii_digit() && (i == 0 || !bytes[i - 1].is_ascii_alphanumeric()) {
+            let start = i;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            if i + 1 < bytes.len() && bytes[i] == b':' && bytes[i + 1].is_ascii_digit() {
+                return lower[start..i].parse::<usize>().ok()?.checked_sub(1);
+            }
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Number of editor lines in the source (a trailing newline starts a new line).
+fn line_count(source: &str) -> usize {
+    source.split('\n').count()
+}
+
 /// Check if an identifier looks like a musical note name (C4, D#5, Eb3, etc.).
 fn is_note_name(name: &str) -> bool {
     let chars: Vec<char> = name.chars().collect();
@@ -138,3 +283,48 @@ fn is_note_name(name: &str) -> bool {
     // Must end with a digit (octave number)
     !rest.is_empty() && rest.iter().all(|c| c.is_ascii_digit())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_note_name() {
+        assert!(is_note_name("C4"));
+        assert!(is_note_name("D#5"));
+        assert!(is_note_name("Eb3"));
+        assert!(!is_note_name("kick"));
+        assert!(!is_note_name("C"));
+        assert!(!is_note_name("H4"));
+    }
+
+    #[test]
+    fn test_error_line_word_form() {
+        assert_eq!(error_line("Unexpected token at line 3"), Some(2));
+        assert_eq!(error_line("Line: 1, column 5: expected ')'"), Some(0));
+    }
+
+    #[test]
+    fn test_error_line_colon_form() {
+        assert_eq!(error_line("4:12: unexpected '}'"), Some(3));
+        assert_eq!(error_line("track drums: 2:1 bad note"), Some(1));
+    }
+
+    #[test]
+    fn test_error_line_missing() {
+        assert_eq!(error_line("unexpected end of input"), None);
+        assert_eq!(error_line("line 0"), None);
+    }
+
+    #[test]
+    fn test_line_count() {
+        assert_eq!(line_count(""), 1);
+        assert_eq!(line_count("C4 /4"), 1);
+        assert_eq!(line_count("C4 /4\nE4 /4\n"), 3);
+    }
+
+    #[test]
+    fn test_note_lines() {
+        assert_eq!(note_lines("C4 /4\n\nE4 /4 G4 /4\n"), vec![0, 2, 2]);
+    }
+}
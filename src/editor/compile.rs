@@ -0,0 +1,248 @@
+//! Background compilation of runner `.sw` source for live re-compile.
+//!
+//! Edits are debounced, compiled on the shared job pool, and the finished
+//! program is sent to the audio thread, which swaps it in at the next
+//! loop or bar boundary. A program with errors is never sent, so the
+//! previous one keeps playing. Starting a compile cancels the slot's
+//! previous one if it hasn't finished.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Receiver, Sender};
+
+use super::{EditorEvent, EditorState};
+use crate::jobs::{JobHandle, JobPriority};
+use crate::slots::runner_slot::RunnerProgram;
+use crate::view_model;
+
+/// Idle time after the last keystroke before compiling.
+pub const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Compile status of a slot's source, shown next to the code editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompileStatus {
+    /// Nothing compiled yet.
+    Idle,
+    /// Edited; waiting for the debounce to elapse.
+    Pending,
+    /// Compiling on a worker thread.
+    Compiling,
+    /// Compiled and sent to the audio thread.
+    Live,
+    /// Compilation failed; the previous program is still playing.
+    Error,
+}
+
+/// A finished compile job.
+struct CompileResult {
+    slot_index: usize,
+    generation: u64,
+    program: Arc<RunnerProgram>,
+}
+
+/// UI-side state for background compilation.
+pub struct CompileState {
+    status: HashMap<usize, CompileStatus>,
+    /// Slots edited since their last compile, with the time of the last edit.
+    scheduled: HashMap<usize, Instant>,
+    /// Latest job generation per slot (stale results are discarded).
+    generations: HashMap<usize, u64>,
+    next_generation: u64,
+    /// Latest compile job per slot, cancelled when a newer one starts.
+    jobs: HashMap<usize, JobHandle>,
+    result_tx: Sender<CompileResult>,
+    result_rx: Receiver<CompileResult>,
+    /// Programs sent to the audio thread. Kept alive here so the audio
+    /// thread never performs the final drop; released once it lets go.
+    retained: Vec<Arc<RunnerProgram>>,
+    /// Whether restored slot sources have been queued for compilation.
+    initialized: bool,
+}
+
+impl Default for CompileState {
+    fn default() -> Self {
+        let (result_tx, result_rx) = crossbeam_channel::unbounded();
+        Self {
+            status: HashMap::new(),
+            scheduled: HashMap::new(),
+            generations: HashMap::new(),
+            next_generation: 0,
+            jobs: HashMap::new(),
+            result_tx,
+            result_rx,
+            retained: Vec::new(),
+            initialized: false,
+        }
+    }
+}
+
+impl CompileState {
+    /// Queue a slot for compilation after the debounce interval. A compile
+    /// of the slot's previous source is cancelled.
+    pub fn schedule(&mut self, slot_index: usize) {
+        if let Some(stale) = self.jobs.remove(&slot_index) {
+            stale.cancel();
+        }
+        self.scheduled.insert(slot_index, Instant::now());
+        self.status.insert(slot_index, CompileStatus::Pending);
+    }
+
+    /// Forget a removed slot; the state of the slots after it moves up one
+    /// place. Their compiles in flight report under the old indices, so
+    /// they are cancelled and queued again.
+    pub fn remove_slot(&mut self, slot_index: usize) {
+        let mut in_flight: Vec<usize> = self.jobs.keys().copied().filter(|&k| k >= slot_index).collect();
+        in_flight.sort_unstable();
+        for k in &in_flight {
+            if let Some(job) = self.jobs.remove(k) {
+                job.cancel();
+            }
+        }
+        view_model::remove_slot_key(&mut self.status, slot_index);
+        view_model::remove_slot_key(&mut self.scheduled, slot_index);
+        view_model::remove_slot_key(&mut self.generations, slot_index);
+        for k in in_flight.into_iter().filter(|&k| k > slot_index) {
+            self.schedule(k - 1);
+        }
+    }
+
+    /// Current compile status for a slot.
+    pub fn status(&self, slot_index: usize) -> CompileStatus {
+        self.status.get(&slot_index).copied().unwrap_or(CompileStatus::Idle)
+    }
+
+    /// Slots whose debounce interval has elapsed at `now`.
+    fn due(&self, now: Instant) -> Vec<usize> {
+        self.scheduled
+            .iter()
+            .filter(|(_, edited)| now.duration_since(**edited) >= DEBOUNCE)
+            .map(|(idx, _)| *idx)
+            .collect()
+    }
+}
+
+/// Drive background compiles: start debounced jobs and apply finished
+/// results. Called once per frame.
+pub fn poll(state: &mut EditorState) {
+    let now = Instant::now();
+
+    // Compile sources restored from the saved project right away
+    if !state.compile_state.initialized {
+        state.compile_state.initialized = true;
+        if let Ok(ps) = state.plugin_state.lock() {
+            for (idx, cfg) in ps.slot_configs.iter().enumerate() {
+                if !cfg.source_code.is_empty() {
+                    state.compile_state.schedule(idx);
+                    let edited = now.checked_sub(DEBOUNCE).unwrap_or(now);
+                    state.compile_state.scheduled.insert(idx, edited);
+                }
+            }
+        }
+    }
+
+    // --- Start jobs whose debounce has elapsed ---
+    for idx in state.compile_state.due(now) {
+        state.compile_state.scheduled.remove(&idx);
+        let source = match state.plugin_state.lock() {
+            Ok(ps) => match ps.slot_configs.get(idx) {
+                Some(cfg) => cfg.source_code.clone(),
+                None => continue,
+            },
+            Err(_) => continue,
+        };
+
+        state.compile_state.next_generation += 1;
+        let generation = state.compile_state.next_generation;
+        state.compile_state.generations.insert(idx, generation);
+        state.compile_state.status.insert(idx, CompileStatus::Compiling);
+
+        let tx = state.compile_state.result_tx.clone();
+        let job = state.jobs.submit(JobPriority::Interactive, move |ctx| {
+            if ctx.is_cancelled() {
+                return;
+            }
+            let program = Arc::new(RunnerProgram::compile(&source));
+            if !ctx.is_cancelled() {
+                let _ = tx.send(CompileResult { slot_index: idx, generation, program });
+            }
+        });
+        if let Some(stale) = state.compile_state.jobs.insert(idx, job) {
+            stale.cancel();
+        }
+    }
+
+    // --- Apply finished jobs ---
+    while let Ok(result) = state.compile_state.result_rx.try_recv() {
+        let idx = result.slot_index;
+        let current = state.compile_state.generations.get(&idx).copied();
+        if current != Some(result.generation) || state.compile_state.scheduled.contains_key(&idx) {
+            // Superseded by a newer edit
+            continue;
+        }
+        state.compile_state.jobs.remove(&idx);
+
+        let compile_error = result.program.compile_error.clone();
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.compile_error = compile_error.clone();
+            }
+        }
+
+        if compile_error.is_some() {
+            state.compile_state.status.insert(idx, CompileStatus::Error);
+            continue;
+        }
+
+        let event = EditorEvent::LoadRunnerProgram {
+            slot_index: idx,
+            program: result.program.clone(),
+        };
+        if state.event_tx.try_send(event).is_ok() {
+            state.compile_state.retained.push(result.program);
+            state.compile_state.status.insert(idx, CompileStatus::Live);
+        } else {
+            // Audio channel full — try again shortly
+            state.compile_state.schedule(idx);
+        }
+    }
+
+    // Release programs the audio thread no longer references
+    state.compile_state.retained.retain(|p| Arc::strong_count(p) > 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_sets_pending() {
+        let mut cs = CompileState::default();
+        assert_eq!(cs.status(0), CompileStatus::Idle);
+        cs.schedule(0);
+        assert_eq!(cs.status(0), CompileStatus::Pending);
+    }
+
+    #[test]
+    fn test_remove_slot_moves_later_slots_up() {
+        let mut cs = CompileState::default();
+        cs.schedule(1);
+        cs.schedule(3);
+        cs.status.insert(2, CompileStatus::Error);
+        cs.remove_slot(1);
+        assert_eq!(cs.status(1), CompileStatus::Error);
+        assert_eq!(cs.status(2), CompileStatus::Pending);
+        assert_eq!(cs.status(3), CompileStatus::Idle);
+        assert_eq!(cs.due(Instant::now() + DEBOUNCE), vec![2]);
+    }
+
+    #[test]
+    fn test_due_respects_debounce() {
+        let mut cs = CompileState::default();
+        cs.schedule(2);
+        let now = Instant::now();
+        assert!(cs.due(now).is_empty());
+        assert_eq!(cs.due(now + DEBOUNCE), vec![2]);
+    }
+}
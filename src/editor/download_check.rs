@@ -0,0 +1,190 @@
+//! Size check before the browser loads a preset that isn't cached yet.
+//!
+//! Previews and adds from the browser go through [`request`]. A preset
+//! whose descriptor is already in the disk cache loads straight away;
+//! otherwise the load waits for the download estimate (see `preset_info`)
+//! and a measurement of the cache, and asks for confirmation if the preset
+//! is over the warning size or would take the cache past its limit.
+
+use std::sync::{Arc, Mutex};
+
+use nih_plug_egui::egui;
+
+use super::preset_info::DetailsStatus;
+use super::{EditorState, PlayNote, browser, colors};
+use crate::jobs::JobPriority;
+use crate::net::settings;
+use crate::preset::cache::DiskCache;
+use crate::preset::download_size::{self, DownloadLimits, SizeWarning};
+
+/// What to do with the preset once it's cleared.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PendingAction {
+    Preview(PlayNote),
+    AddToSlot { name: String },
+    /// Load into a given slot, replacing what it plays.
+    IntoSlot { name: String, slot_index: usize },
+}
+
+struct PendingLoad {
+    library: String,
+    path: String,
+    action: PendingAction,
+    /// Set once the estimate is in and something needs confirming.
+    warnings: Vec<SizeWarning>,
+    estimate: String,
+}
+
+#[derive(Default)]
+pub struct DownloadCheckState {
+    pending: Option<PendingLoad>,
+    /// Bytes in the cache directory (None while measuring).
+    cache_used: Arc<Mutex<Option<u64>>>,
+}
+
+/// Load a preset from the browser, checking its size first if it has to
+/// be downloaded.
+pub fn request(state: &mut EditorState, library: &str, path: &str, action: PendingAction) {
+    let limits = DownloadLimits::from_settings(&settings::current());
+    let off = limits.warn_bytes == 0 && limits.cache_limit_bytes == 0;
+    if off || is_cached(state, library, path) {
+        run(state, library, path, &action);
+        return;
+    }
+
+    if let Ok(mut used) = state.download_check.cache_used.lock() {
+        *used = None;
+    }
+    let cache_used = state.download_check.cache_used.clone();
+    state.jobs.submit(JobPriority::Interactive, move |_| {
+        let used = download_size::cache_usage();
+        if let Ok(mut u) = cache_used.lock() {
+            *u = Some(used);
+        }
+    });
+    state.download_check.pending = Some(PendingLoad {
+        library: library.to_string(),
+        path: path.to_string(),
+        action,
+        warnings: Vec::new(),
+        estimate: String::new(),
+    });
+    if let Ok(mut st) = state.status_text.lock() {
+        *st = "Checking download size\u{2026}".to_string();
+    }
+}
+
+/// Whether the preset's descriptor is in the disk cache (its samples were
+/// downloaded with it).
+fn is_cached(state: &EditorState, library: &str, path: &str) -> bool {
+    let slug = match state.preset_manager.lock() {
+        Ok(pm) => pm
+            .libraries
+            .iter()
+            .find(|l| l.name == library)
+            .map(|l| l.slug.clone())
+            .unwrap_or_else(|| library.to_string()),
+        Err(_) => return false,
+    };
+    DiskCache::new().read_preset(&slug, path).is_some()
+}
+
+fn run(state: &mut EditorState, library: &str, path: &str, action: &PendingAction) {
+    match action {
+        PendingAction::Preview(play) => browser::start_preview(state, library, path, *play),
+        PendingAction::AddToSlot { name } => browser::start_load_into_slot(state, library, name, path),
+        PendingAction::IntoSlot { name, slot_index } => {
+            browser::start_load_into(state, *slot_index, library, name, path)
+        }
+    }
+}
+
+/// Resolve a pending load once its estimate is in. Called once per frame.
+pub fn poll(state: &mut EditorState) {
+    let Some(pending) = &state.download_check.pending else { return };
+    if !pending.warnings.is_empty() {
+        return;
+    }
+    let status = state
+        .browser_state
+        .info
+        .estimate(&state.jobs, &state.preset_manager, &pending.library, &pending.path);
+    let details = match status {
+        DetailsStatus::Loading => return,
+        DetailsStatus::Ready(details) => Some(details),
+        // The load reports the problem itself
+        DetailsStatus::Failed(_) => None,
+    };
+    let Ok(used) = state.download_check.cache_used.lock().map(|u| *u) else { return };
+    let Some(used) = used else { return };
+
+    let Some(mut pending) = state.download_check.pending.take() else { return };
+    if let Some(details) = details {
+        let limits = DownloadLimits::from_settings(&settings::current());
+        pending.warnings = download_size::check(details.known_bytes as u64, used, limits);
+        pending.estimate = details.size_label();
+    }
+    if pending.warnings.is_empty() {
+        run(state, &pending.library, &pending.path, &pending.action);
+    } else {
+        state.download_check.pending = Some(pending);
+    }
+}
+
+/// Confirmation window for a load over the limits.
+pub fn draw(ctx: &egui::Context, state: &mut EditorState) {
+    let Some(pending) = &state.download_check.pending else { return };
+    if pending.warnings.is_empty() {
+        return;
+    }
+
+    let mut confirm = false;
+    let mut cancel = false;
+    egui::Window::new("Large download")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.set_max_width(340.0);
+            let name = pending.path.rsplit('/').next().unwrap_or(&pending.path);
+            ui.label(egui::RichText::new(format!("{} / {}", pending.library, name)).strong());
+            ui.label(
+                egui::RichText::new(format!("Estimated download: {}", pending.estimate))
+                    .color(colors::subtext0()),
+            );
+            ui.add_space(4.0);
+            for warning in &pending.warnings {
+                ui.label(egui::RichText::new(format!("\u{26a0} {}", warning.message())).color(colors::yellow()));
+            }
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                confirm = ui.button("Download").clicked();
+                cancel = ui.button("Cancel").clicked();
+            });
+            ui.label(
+                egui::RichText::new("The warning size and cache limit are in Settings \u{2192} Network.")
+                    .color(colors::overlay0())
+                    .small(),
+            );
+        });
+
+    if confirm {
+        if let Some(pending) = state.download_check.pending.take() {
+            run(state, &pending.library, &pending.path, &pending.action);
+        }
+    } else if cancel {
+        state.download_check.pending = None;
+        if let Ok(mut st) = state.status_text.lock() {
+            *st = "Download cancelled".to_string();
+        }
+    }
+}
+
+/// Tooltip line for a preset row: its estimated download, once known.
+pub fn size_hint(status: &DetailsStatus) -> String {
+    match status {
+        DetailsStatus::Loading => "Download: estimating\u{2026}".to_string(),
+        DetailsStatus::Ready(details) => format!("Download: {}", details.size_label()),
+        DetailsStatus::Failed(_) => "Download: unknown".to_string(),
+    }
+}
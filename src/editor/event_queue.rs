@@ -0,0 +1,278 @@
+//! The editor → audio event channel.
+//!
+//! Editor events reach the audio thread over a bounded channel that the
+//! audio side drains at the start of each block, so a burst of events (a
+//! fast drag across the piano, OSC or the web bridge sending chords) can
+//! fill it between two blocks. `EventSender` decides what gives way:
+//!
+//! - The last `capacity / RELEASE_SHARE` places are kept for releases
+//!   (note-offs, panic, stopping previews, releasing held notes), so a
+//!   flood of note-ons or settings can never leave a note stuck.
+//! - Notes that don't fit wait in a backlog, coalesced per slot and key:
+//!   a note-on released before it was delivered is dropped, and a
+//!   repeated note-on keeps the latest velocity. The backlog is flushed,
+//!   note-offs first, before the next send and on every editor frame.
+//! - Other events that don't fit are refused as before (callers that
+//!   must deliver retry) and counted; the status bar shows the count.
+//!
+//! Sending takes a short lock on the backlog, so it happens on the editor,
+//! OSC, bridge and loader threads only; the audio thread just receives.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+
+use super::EditorEvent;
+
+/// Events in flight between two audio blocks.
+pub const EVENT_CAPACITY: usize = 1024;
+
+/// One place in this many is kept for releases.
+const RELEASE_SHARE: usize = 8;
+
+/// Note-ons waiting in the backlog at most; more are dropped.
+const BACKLOG_LIMIT: usize = 512;
+
+/// A note event waiting for room in the channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Pending {
+    On { slot_index: usize, note: u8, velocity: f32 },
+    Off { slot_index: usize, note: u8 },
+}
+
+impl Pending {
+    fn from_event(event: &EditorEvent) -> Option<Self> {
+        match *event {
+            EditorEvent::NoteOn { slot_index, note, velocity } => Some(Self::On { slot_index, note, velocity }),
+            EditorEvent::NoteOff { slot_index, note } => Some(Self::Off { slot_index, note }),
+            _ => None,
+        }
+    }
+
+    fn key(&self) -> (usize, u8) {
+        match *self {
+            Self::On { slot_index, note, .. } | Self::Off { slot_index, note } => (slot_index, note),
+        }
+    }
+
+    fn is_on(&self) -> bool {
+        matches!(self, Self::On { .. })
+    }
+
+    fn event(self) -> EditorEvent {
+        match self {
+            Self::On { slot_index, note, velocity } => EditorEvent::NoteOn { slot_index, note, velocity },
+            Self::Off { slot_index, note } => EditorEvent::NoteOff { slot_index, note },
+        }
+    }
+}
+
+/// Events that silence something, and so may use the reserved places.
+fn is_release(event: &EditorEvent) -> bool {
+    matches!(
+        event,
+        EditorEvent::NoteOff { .. }
+            | EditorEvent::Panic
+            | EditorEvent::StopPreview
+            | EditorEvent::ReleaseHeldNotes { .. }
+    )
+}
+
+#[derive(Default)]
+struct Shared {
+    backlog: Mutex<Vec<Pending>>,
+    dropped: AtomicU64,
+}
+
+/// Sending half of the editor → audio channel.
+#[derive(Clone)]
+pub struct EventSender {
+    tx: Sender<EditorEvent>,
+    capacity: usize,
+    shared: Arc<Shared>,
+}
+
+/// Create the editor → audio channel.
+pub fn channel() -> (EventSender, Receiver<EditorEvent>) {
+    with_capacity(EVENT_CAPACITY)
+}
+
+fn with_capacity(capacity: usize) -> (EventSender, Receiver<EditorEvent>) {
+    let (tx, rx) = crossbeam_channel::bounded(capacity);
+    (EventSender { tx, capacity, shared: Arc::default() }, rx)
+}
+
+impl EventSender {
+    /// Room left for an event, keeping the reserved places for releases.
+    fn has_room(&self, release: bool) -> bool {
+        let limit = if release { self.capacity } else { self.capacity - self.capacity / RELEASE_SHARE };
+        self.tx.len() < limit
+    }
+
+    fn count_drop(&self) {
+        self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Send an event without blocking. Note events that don't fit are
+    /// queued (see the module docs) and count as sent; other events come
+    /// back in `TrySendError::Full`.
+    pub fn try_send(&self, event: EditorEvent) -> Result<(), TrySendError<EditorEvent>> {
+        let release = is_release(&event);
+        let Some(pending) = Pending::from_event(&event) else {
+            if matches!(event, EditorEvent::Panic) {
+                // Nothing queued before a panic should sound after it
+                if let Ok(mut backlog) = self.shared.backlog.lock() {
+                    backlog.retain(|p| !p.is_on());
+                }
+            }
+            self.flush();
+            if !self.has_room(release) {
+                self.count_drop();
+                return Err(TrySendError::Full(event));
+            }
+            return self.tx.try_send(event).inspect_err(|e| {
+                if e.is_full() {
+                    self.count_drop();
+                }
+            });
+        };
+
+        let Ok(mut backlog) = self.shared.backlog.lock() else {
+            return self.tx.try_send(event);
+        };
+        self.flush_backlog(&mut backlog);
+        if backlog.is_empty() && self.has_room(release) {
+            match self.tx.try_send(event) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Disconnected(event)) => return Err(TrySendError::Disconnected(event)),
+                Err(TrySendError::Full(_)) => {}
+            }
+        }
+        self.queue(&mut backlog, pending);
+        Ok(())
+    }
+
+    /// Add a note event to the backlog, merging it with the key's queued
+    /// events.
+    fn queue(&self, backlog: &mut Vec<Pending>, pending: Pending) {
+        let key = pending.key();
+        let last = backlog.iter().rposition(|p| p.key() == key);
+        match pending {
+            Pending::Off { .. } => {
+                // A queued note-on that is already released never sounds
+                let mut last = last;
+                if let Some(i) = last.filter(|&i| backlog[i].is_on()) {
+                    backlog.remove(i);
+                    last = backlog.iter().rposition(|p| p.key() == key);
+                }
+                // Still send the note-off unless one is queued: the note may
+                // have been retriggered from an earlier, delivered note-on
+                if last.is_none() {
+                    backlog.push(pending);
+                }
+            }
+            Pending::On { .. } => match last {
+                Some(i) if backlog[i].is_on() => backlog[i] = pending,
+                _ if backlog.len() >= BACKLOG_LIMIT => self.count_drop(),
+                _ => backlog.push(pending),
+            },
+        }
+    }
+
+    /// Send what fits of the backlog: every note-off first, then note-ons.
+    fn flush_backlog(&self, backlog: &mut Vec<Pending>) {
+        if backlog.is_empty() {
+            return;
+        }
+        let mut open = true;
+        backlog.retain(|p| {
+            if p.is_on() {
+                return true;
+            }
+            open = open && self.has_room(true) && self.tx.try_send(p.event()).is_ok();
+            !open
+        });
+        if !open {
+            return;
+        }
+        backlog.retain(|p| {
+            open = open && self.has_room(false) && self.tx.try_send(p.event()).is_ok();
+            !open
+        });
+    }
+
+    /// Send what fits of the backlog. Returns whether notes are still
+    /// waiting (the caller should try again soon).
+    pub fn flush(&self) -> bool {
+        let Ok(mut backlog) = self.shared.backlog.lock() else { return false };
+        self.flush_backlog(&mut backlog);
+        !backlog.is_empty()
+    }
+
+    /// Events dropped because the channel was full.
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn on(note: u8) -> EditorEvent {
+        EditorEvent::NoteOn { slot_index: 0, note, velocity: 0.8 }
+    }
+
+    fn off(note: u8) -> EditorEvent {
+        EditorEvent::NoteOff { slot_index: 0, note }
+    }
+
+    fn drain(rx: &Receiver<EditorEvent>) -> Vec<String> {
+        rx.try_iter().map(|e| format!("{:?}", e)).collect()
+    }
+
+    #[test]
+    fn test_backpressure_reserves_coalesces_and_counts() {
+        // 8 places, the last one for releases
+        let (tx, rx) = with_capacity(8);
+        for note in 0..7 {
+            tx.try_send(on(note)).unwrap();
+        }
+        // Full for settings and note-ons, but a note-off still fits
+        let mix = EditorEvent::SetSlotMix { slot_index: 0, volume: 1.0, pan: 0.0 };
+        assert!(matches!(tx.try_send(mix), Err(TrySendError::Full(_))));
+        assert_eq!(tx.dropped(), 1);
+        tx.try_send(off(0)).unwrap();
+        assert_eq!(rx.len(), 8);
+
+        // Queued: a released note-on is dropped, a repeated note-on keeps
+        // the last velocity
+        tx.try_send(on(10)).unwrap();
+        tx.try_send(off(10)).unwrap();
+        tx.try_send(on(11)).unwrap();
+        tx.try_send(EditorEvent::NoteOn { slot_index: 0, note: 11, velocity: 0.3 }).unwrap();
+        tx.try_send(off(1)).unwrap();
+        assert_eq!(tx.shared.backlog.lock().unwrap().len(), 3);
+
+        // Flushed note-offs first once the audio side has drained
+        drain(&rx);
+        assert!(!tx.flush());
+        let sent = drain(&rx);
+        assert_eq!(sent.len(), 3);
+        assert!(sent[0].contains("NoteOff") && sent[0].contains("note: 10"));
+        assert!(sent[1].contains("NoteOff") && sent[1].contains("note: 1 }"));
+        assert!(sent[2].contains("NoteOn") && sent[2].contains("velocity: 0.3"));
+
+        // A panic drops queued note-ons
+        for note in 0..7 {
+            tx.try_send(on(note)).unwrap();
+        }
+        tx.try_send(on(20)).unwrap();
+        tx.try_send(EditorEvent::Panic).unwrap();
+        assert!(!tx.flush());
+        drain(&rx);
+        assert!(!tx.flush());
+        assert!(rx.is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+//! Keyboard navigation across the browser and the slot rack.
+//!
+//! One panel has keyboard focus at a time (Tab switches). In the browser,
+//! Up/Down move the preset selection through every row of the expanded
+//! folders (or the search results), Enter loads the selection into a slot
+//! and Space previews it. In the rack, Up/Down move the selected slot,
+//! Enter reloads its preset, Space plays its root note and Delete removes
+//! it. Keys are left alone while a text field or other widget has focus.
+//!
+//! The selected row of the focused panel also holds egui's widget focus
+//! (the panel's anchor), so screen readers announce it as Tab and the
+//! arrows move the selection. The anchor keeps the navigation keys for
+//! this module instead of moving egui focus on to the next widget.
+
+use std::time::Instant;
+
+use nih_plug_egui::egui;
+
+use super::loads::LoadTarget;
+use super::{browser, EditorEvent, EditorState, PlayNote};
+
+/// Panel receiving navigation keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FocusPanel {
+    #[default]
+    Browser,
+    Rack,
+}
+
+impl FocusPanel {
+    pub fn other(self) -> Self {
+        match self {
+            Self::Browser => Self::Rack,
+            Self::Rack => Self::Browser,
+        }
+    }
+}
+
+/// A preset row as listed in the browser this frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowserRow {
+    pub library: String,
+    pub path: String,
+    pub name: String,
+}
+
+/// Keyboard focus, shared by the browser and slot rack.
+#[derive(Default)]
+pub struct FocusState {
+    pub panel: FocusPanel,
+    /// Every preset row in draw order, rebuilt by the browser each frame.
+    pub browser_rows: Vec<BrowserRow>,
+    /// Scroll the selected browser row into view on the next frame.
+    pub scroll_to_selection: bool,
+    /// Give widget focus to the focused panel's selected row when drawn.
+    pub move_focus: bool,
+    /// Widget holding focus for the panel, if it still does.
+    pub anchor: Option<egui::Id>,
+}
+
+/// A navigation key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavKey {
+    Up,
+    Down,
+    Enter,
+    Space,
+    Delete,
+    Tab,
+}
+
+/// Navigation keys pressed this frame, unless a widget other than the
+/// panel anchor owns the keyboard. Read before the panels are drawn, since
+/// egui hands Tab focus to the first focusable widget during the frame.
+pub fn read_keys(ctx: &egui::Context, focus: &FocusState) -> Vec<NavKey> {
+    if ctx.memory(|m| m.focused().is_some_and(|id| Some(id) != focus.anchor)) {
+        return Vec::new();
+    }
+    ctx.input(|i| {
+        if i.modifiers.command || i.modifiers.alt {
+            return Vec::new();
+        }
+        i.events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::Key { key, pressed: true, .. } => match key {
+                    egui::Key::ArrowUp => Some(NavKey::Up),
+                    egui::Key::ArrowDown => Some(NavKey::Down),
+                    egui::Key::Enter => Some(NavKey::Enter),
+                    egui::Key::Space => Some(NavKey::Space),
+                    egui::Key::Delete | egui::Key::Backspace => Some(NavKey::Delete),
+                    egui::Key::Tab => Some(NavKey::Tab),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    })
+}
+
+/// Move through a list of `len` items by `delta`, clamped to the ends.
+/// With nothing selected, Down starts at the first item and Up at the last.
+pub fn step(len: usize, current: Option<usize>, delta: isize) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    let next = match current {
+        Some(index) => index.saturating_add_signed(delta).min(len - 1),
+        None if delta < 0 => len - 1,
+        None => 0,
+    };
+    Some(next)
+}
+
+/// Make `response`, the selected row of the focused panel, its anchor:
+/// it takes widget focus after a navigation key, and while it has focus
+/// Tab and the arrows are left to `handle_keys`.
+pub fn anchor(focus: &mut FocusState, response: &egui::Response) {
+    if std::mem::take(&mut focus.move_focus) {
+        response.request_focus();
+    }
+    if response.has_focus() {
+        focus.anchor = Some(response.id);
+        let filter = egui::EventFilter {
+            tab: true,
+            horizontal_arrows: true,
+            vertical_arrows: true,
+            escape: false,
+        };
+        response.ctx.memory_mut(|m| m.set_focus_lock_filter(response.id, filter));
+    }
+}
+
+/// Handle this frame's navigation keys. Called after the panels are drawn,
+/// so `browser_rows` is current.
+pub fn handle_keys(ctx: &egui::Context, state: &mut EditorState, keys: &[NavKey]) {
+    for &key in keys {
+        if key == NavKey::Tab {
+            state.focus.panel = state.focus.panel.other();
+            // Tab is ours: take back the widget focus egui gave away
+            ctx.memory_mut(|m| {
+                if let Some(id) = m.focused() {
+                    m.surrender_focus(id);
+                }
+            });
+            state.focus.anchor = None;
+            state.focus.move_focus = true;
+            continue;
+        }
+        match state.focus.panel {
+            FocusPanel::Browser => browser_key(state, key),
+            FocusPanel::Rack => rack_key(state, key),
+        }
+    }
+}
+
+fn browser_key(state: &mut EditorState, key: NavKey) {
+    let rows = &state.focus.browser_rows;
+    let current = state.browser_state.selected_preset.as_ref().and_then(|(library, path)| {
+        rows.iter().position(|r| &r.library == library && &r.path == path)
+    });
+    let delta = match key {
+        NavKey::Up => -1,
+        NavKey::Down => 1,
+        NavKey::Enter | NavKey::Space => {
+            let Some(row) = current.map(|i| rows[i].clone()) else { return };
+            if key == NavKey::Space {
+                browser::preview_preset(state, &row.library, &row.path);
+            } else {
+                browser::load_into_slot(state, &row.library, &row.name, &row.path);
+            }
+            return;
+        }
+        _ => return,
+    };
+    if let Some(next) = step(rows.len(), current, delta) {
+        let row = &rows[next];
+        state.browser_state.selected_preset = Some((row.library.clone(), row.path.clone()));
+        state.focus.scroll_to_selection = true;
+        state.focus.move_focus = true;
+    }
+}
+
+fn rack_key(state: &mut EditorState, key: NavKey) {
+    let Ok(ps) = state.plugin_state.lock() else { return };
+    let slot_count = ps.slot_configs.len();
+    let selected = state.slot_rack_state.selected_slot;
+    let config = ps.slot_configs.get(selected).cloned();
+    drop(ps);
+
+    match key {
+        NavKey::Up | NavKey::Down => {
+            let delta = if key == NavKey::Up { -1 } else { 1 };
+            let current = (selected < slot_count).then_some(selected);
+            if let Some(next) = step(slot_count, current, delta) {
+                state.slot_rack_state.selected_slot = next;
+                state.focus.move_focus = true;
+            }
+        }
+        NavKey::Enter => {
+            let Some(preset_id) = config.and_then(|c| c.preset_id) else { return };
+            if let Some((library, path)) = preset_id.split_once('/') {
+                state.loads.request(
+                    &state.jobs,
+                    &state.preset_manager,
+                    LoadTarget::Slot(selected),
+                    library,
+                    path,
+                    selected,
+                    None,
+                );
+            }
+        }
+        NavKey::Space => {
+            let Some(config) = config else { return };
+            let play = PlayNote::new(config.root_note);
+            let _ = state.event_tx.try_send(EditorEvent::NoteOn {
+                slot_index: selected,
+                note: play.note,
+                velocity: play.velocity,
+            });
+            // The preview player sends the rest of the preview and the note-offs
+            state.browser_state.preview.start(selected, play, Instant::now());
+        }
+        NavKey::Delete => super::slot_actions::remove(state, selected),
+        NavKey::Tab => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_clamps_and_starts_at_ends() {
+        assert_eq!(step(0, None, 1), None);
+        assert_eq!(step(3, None, 1), Some(0));
+        assert_eq!(step(3, None, -1), Some(2));
+        assert_eq!(step(3, Some(0), -1), Some(0));
+        assert_eq!(step(3, Some(1), 1), Some(2));
+        assert_eq!(step(3, Some(2), 1), Some(2));
+    }
+}
@@ -0,0 +1,341 @@
+//! Freeze/unfreeze in the expanded slot view.
+//!
+//! Freezing renders the slot off the audio thread (see `slots::frozen`):
+//! the notes its hold mode has latched, or else its root note, held for
+//! the chosen number of beats at the current tempo. A runner slot plays
+//! its pattern from that trigger. The finished clip is sent to the audio
+//! thread, and this side keeps a reference to every clip the audio thread
+//! may still hold so it is never freed there.
+//!
+//! A frozen slot's clip can be bounced to a WAV file in the export format
+//! chosen here (see `export_audio`).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crossbeam_channel::{Receiver, Sender};
+use nih_plug_egui::egui;
+
+use super::{EditorEvent, EditorState, colors, fs, zs};
+use crate::export_audio::{self, BitDepth, ExportFormat};
+use crate::jobs::{JobHandle, JobPriority};
+use crate::preset::cache::DiskCache;
+use crate::preset::graph::PresetGraph;
+use crate::preset::manager::PresetManager;
+use crate::slots::frozen::{self, FrozenClip};
+use crate::slots::runner_slot::RunnerProgram;
+use crate::slots::{KeyswitchMap, Slot};
+use crate::state::SlotConfig;
+use crate::view_model;
+
+/// Beats held before the ring-out, offered in the slot view.
+const FREEZE_LENGTHS: [f64; 5] = [1.0, 2.0, 4.0, 8.0, 16.0];
+
+struct FreezeResult {
+    slot_index: usize,
+    generation: u64,
+    clip: Arc<FrozenClip>,
+}
+
+pub struct FreezeState {
+    /// Beats to hold the trigger for.
+    pub beats: f64,
+    /// Format of bounced clips.
+    pub export: ExportFormat,
+    /// Clip each frozen slot is playing.
+    frozen: HashMap<usize, Arc<FrozenClip>>,
+    /// Renders in flight, by slot (the generation discards stale ones).
+    rendering: HashMap<usize, (u64, JobHandle)>,
+    next_generation: u64,
+    /// Clips handed to the audio thread and not yet released by it.
+    retained: Vec<Arc<FrozenClip>>,
+    result_tx: Sender<FreezeResult>,
+    result_rx: Receiver<FreezeResult>,
+}
+
+impl Default for FreezeState {
+    fn default() -> Self {
+        let (result_tx, result_rx) = crossbeam_channel::unbounded();
+        Self {
+            beats: 4.0,
+            export: ExportFormat::default(),
+            frozen: HashMap::new(),
+            rendering: HashMap::new(),
+            next_generation: 0,
+            retained: Vec::new(),
+            result_tx,
+            result_rx,
+        }
+    }
+}
+
+impl FreezeState {
+    pub fn is_frozen(&self, slot_index: usize) -> bool {
+        self.frozen.contains_key(&slot_index)
+    }
+
+    pub fn is_rendering(&self, slot_index: usize) -> bool {
+        self.rendering.contains_key(&slot_index)
+    }
+
+    /// Forget a removed slot; the clips of the slots after it move up one
+    /// place. Renders in flight report under the old indices, so those
+    /// from the removed slot on are cancelled.
+    pub fn remove_slot(&mut self, slot_index: usize) {
+        let stale: Vec<usize> = self.rendering.keys().copied().filter(|&k| k >= slot_index).collect();
+        for k in stale {
+            if let Some((_, render)) = self.rendering.remove(&k) {
+                render.cancel();
+            }
+        }
+        if let Some(clip) = view_model::remove_slot_key(&mut self.frozen, slot_index) {
+            self.retained.push(clip);
+        }
+    }
+}
+
+/// Build a stand-alone copy of a slot for the offline render.
+fn offline_slot(
+    config: &SlotConfig,
+    preset: Option<(Arc<String>, Arc<crate::preset::instance::PresetInstance>)>,
+    graph: PresetGraph,
+) -> Slot {
+    let mut slot = Slot::new(0);
+    if let Some((id, instance)) = preset {
+        slot.preset_state_mut().load_preset(id, instance);
+        slot.preset_state_mut().set_graph(graph);
+    }
+    if !config.source_code.is_empty() {
+        let program = Arc::new(RunnerProgram::compile(&config.source_code));
+        if program.compile_error.is_none() {
+            slot.runner_state_mut().queue_program(program);
+            slot.set_has_source(true);
+        }
+    }
+    slot.runner_state_mut().set_humanize(config.humanize);
+    slot.set_arp_settings(config.arp);
+    slot.set_tuning(config.tuning);
+    slot.set_unison(config.unison);
+    slot.set_legato(config.legato);
+    slot.set_filter(config.filter);
+    slot.set_mod_matrix(config.mod_matrix);
+    slot.set_output(config.output);
+    slot.set_oversampling(config.oversampling);
+    slot.set_keyswitches(KeyswitchMap::from_articulations(&config.articulations));
+    slot
+}
+
+/// Synth, effect and layer nodes of a library preset, from the cached
+/// descriptor.
+fn cached_graph(pm: &PresetManager, preset_id: &str) -> PresetGraph {
+    let Some((library, path)) = preset_id.split_once('/') else {
+        return PresetGraph::default();
+    };
+    let slug = pm
+        .libraries
+        .iter()
+        .find(|l| l.name == library)
+        .map(|l| l.slug.clone())
+        .unwrap_or_else(|| library.to_string());
+    DiskCache::new()
+        .read_preset(&slug, path)
+        .map(|text| PresetGraph::parse(&text))
+        .unwrap_or_default()
+}
+
+/// Start rendering a slot to a clip.
+fn start_freeze(state: &mut EditorState, idx: usize, config: &SlotConfig) {
+    let preset = state.active_presets_ui.get(&idx).cloned();
+    let graph = match (&preset, state.preset_manager.lock()) {
+        (Some((id, _)), Ok(pm)) => cached_graph(&pm, id),
+        _ => PresetGraph::default(),
+    };
+    let mut notes = state.monitor.held_notes(idx);
+    if notes.is_empty() {
+        notes.push(config.root_note);
+    }
+    let beats = state.freeze.beats;
+    let bpm = state.monitor.tempo();
+    let sample_rate = state.monitor.sample_rate();
+    let config = config.clone();
+
+    let freeze = &mut state.freeze;
+    freeze.next_generation += 1;
+    let generation = freeze.next_generation;
+    let tx = freeze.result_tx.clone();
+    let job = state.jobs.submit(JobPriority::Interactive, move |ctx| {
+        if ctx.is_cancelled() {
+            return;
+        }
+        let mut slot = offline_slot(&config, preset, graph);
+        let clip = frozen::render_offline(&mut slot, &notes, 0.8, beats, bpm, sample_rate);
+        let _ = tx.send(FreezeResult { slot_index: idx, generation, clip: Arc::new(clip) });
+    });
+    if let Some((_, stale)) = freeze.rendering.insert(idx, (generation, job)) {
+        stale.cancel();
+    }
+}
+
+/// Unfreeze a slot (its clip stays retained until the audio thread lets go).
+fn unfreeze(state: &mut EditorState, idx: usize) {
+    if let Some((_, render)) = state.freeze.rendering.remove(&idx) {
+        render.cancel();
+    }
+    if let Some(clip) = state.freeze.frozen.remove(&idx) {
+        state.freeze.retained.push(clip);
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotFrozen { slot_index: idx, clip: None });
+    }
+}
+
+/// File name for a bounce of slot `idx` that doesn't overwrite an
+/// earlier one.
+fn bounce_path(dir: &std::path::Path, idx: usize, preset_id: Option<&str>) -> std::path::PathBuf {
+    let name = preset_id.map_or("", |id| id.rsplit('/').next().unwrap_or(id));
+    let stem: String = format!("Slot {} {}", idx + 1, name)
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' { c } else { '_' })
+        .collect();
+    let mut path = dir.join(format!("{}.wav", stem));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{} ({}).wav", stem, n));
+        n += 1;
+    }
+    path
+}
+
+/// Write a frozen slot's clip to a WAV file in the background.
+fn bounce(state: &mut EditorState, idx: usize) {
+    let Some(clip) = state.freeze.frozen.get(&idx).cloned() else { return };
+    let Some(dir) = export_audio::export_dir() else {
+        if let Ok(mut st) = state.status_text.lock() {
+            *st = "No documents directory to bounce to".into();
+        }
+        return;
+    };
+    let preset_id = state.active_presets_ui.get(&idx).map(|(id, _)| id.clone());
+    let path = bounce_path(&dir, idx, preset_id.as_deref().map(String::as_str));
+    let format = state.freeze.export;
+    let status = state.status_text.clone();
+    state.jobs.submit(JobPriority::Background, move |_| {
+        let rate = clip.sample_rate.round() as u32;
+        let message = match export_audio::write_wav(&path, &clip.left, &clip.right, rate, format) {
+            Ok(()) => format!("Slot {} bounced to {}", idx + 1, path.display()),
+            Err(e) => format!("Bounce failed: {}", e),
+        };
+        if let Ok(mut st) = status.lock() {
+            *st = message;
+        }
+    });
+}
+
+/// Freeze a slot, or unfreeze it if it is frozen.
+pub fn toggle(state: &mut EditorState, idx: usize, config: &SlotConfig) {
+    if state.freeze.is_frozen(idx) {
+        unfreeze(state, idx);
+    } else if !state.freeze.is_rendering(idx) {
+        start_freeze(state, idx, config);
+    }
+}
+
+/// Deliver finished renders and release clips the audio thread dropped.
+/// Called once per frame.
+pub fn poll(state: &mut EditorState) {
+    let freeze = &mut state.freeze;
+    while let Ok(result) = freeze.result_rx.try_recv() {
+        if freeze.rendering.get(&result.slot_index).map(|(g, _)| *g) != Some(result.generation) {
+            continue;
+        }
+        freeze.rendering.remove(&result.slot_index);
+        let event = EditorEvent::SetSlotFrozen { slot_index: result.slot_index, clip: Some(result.clip.clone()) };
+        if state.event_tx.try_send(event).is_ok() {
+            if let Some(previous) = freeze.frozen.insert(result.slot_index, result.clip) {
+                freeze.retained.push(previous);
+            }
+            if let Ok(mut st) = state.status_text.lock() {
+                *st = format!("Slot {} frozen", result.slot_index + 1);
+            }
+        }
+    }
+    freeze.retained.retain(|clip| Arc::strong_count(clip) > 1);
+}
+
+/// Freeze controls in the expanded slot view.
+pub fn draw_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let frozen = state.freeze.is_frozen(idx);
+    let rendering = state.freeze.is_rendering(idx);
+    ui.horizontal(|ui| {
+        if frozen {
+            let seconds = state.freeze.frozen.get(&idx).map_or(0.0, |c| c.duration_secs());
+            ui.label(
+                egui::RichText::new(format!("❄ Frozen ({:.1} s)", seconds))
+                    .color(colors::teal())
+                    .size(fs(11.0, z)),
+            );
+            if ui
+                .button(egui::RichText::new("Unfreeze").size(fs(11.0, z)))
+                .on_hover_text("Play the slot live again")
+                .clicked()
+            {
+                unfreeze(state, idx);
+            }
+            draw_export_controls(ui, state, idx, z);
+            return;
+        }
+
+        let label = if rendering { "Rendering…" } else { "❄ Freeze" };
+        if ui
+            .add_enabled(!rendering, egui::Button::new(egui::RichText::new(label).size(fs(11.0, z))))
+            .on_hover_text(
+                "Render the held notes (or the root note) to audio and play that instead; \
+                 a note-on plays the clip",
+            )
+            .clicked()
+        {
+            start_freeze(state, idx, config);
+        }
+        egui::ComboBox::from_id_salt(("freeze_beats", idx))
+            .selected_text(format!("{} beats", state.freeze.beats))
+            .width(zs(70.0, z))
+            .show_ui(ui, |ui| {
+                for beats in FREEZE_LENGTHS {
+                    ui.selectable_value(&mut state.freeze.beats, beats, format!("{} beats", beats));
+                }
+            });
+    });
+}
+
+/// Bounce button and export format of a frozen slot.
+fn draw_export_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, z: f32) {
+    if ui
+        .button(egui::RichText::new("Bounce WAV…").size(fs(11.0, z)))
+        .on_hover_text("Write the frozen clip to a WAV file in Documents/SongWalker/Bounces")
+        .clicked()
+    {
+        bounce(state, idx);
+    }
+    let export = &mut state.freeze.export;
+    egui::ComboBox::from_id_salt(("bounce_depth", idx))
+        .selected_text(export.bit_depth.label())
+        .width(zs(90.0, z))
+        .show_ui(ui, |ui| {
+            for depth in BitDepth::ALL {
+                ui.selectable_value(&mut export.bit_depth, depth, depth.label());
+            }
+        });
+    let rate_label = |rate: Option<u32>| rate.map_or("Render rate".to_string(), |r| format!("{} Hz", r));
+    egui::ComboBox::from_id_salt(("bounce_rate", idx))
+        .selected_text(rate_label(export.sample_rate))
+        .width(zs(90.0, z))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut export.sample_rate, None, rate_label(None));
+            for rate in export_audio::SAMPLE_RATES {
+                ui.selectable_value(&mut export.sample_rate, Some(rate), rate_label(Some(rate)));
+            }
+        });
+    if export.bit_depth != BitDepth::Float32 {
+        ui.checkbox(&mut export.dither, egui::RichText::new("Dither").size(fs(11.0, z)))
+            .on_hover_text("Add TPDF dither when reducing to integer samples");
+    }
+}
@@ -0,0 +1,71 @@
+//! Which editor front-end to open, stored per machine in `ui.json` under
+//! the user config directory.
+//!
+//! The egui editor is always built. The vizia editor is only available
+//! with the `vizia-editor` cargo feature; without it a saved `Vizia`
+//! choice falls back to egui.
+
+use serde::{Deserialize, Serialize};
+
+/// Editor front-end.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EditorFrontend {
+    #[default]
+    Egui,
+    Vizia,
+}
+
+impl EditorFrontend {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Egui => "egui",
+            Self::Vizia => "VIZIA",
+        }
+    }
+
+    /// Front-ends compiled into this build.
+    pub fn available() -> &'static [EditorFrontend] {
+        if cfg!(feature = "vizia-editor") {
+            &[Self::Egui, Self::Vizia]
+        } else {
+            &[Self::Egui]
+        }
+    }
+
+    /// The choice if it is compiled in, otherwise egui.
+    pub fn or_available(self) -> Self {
+        if Self::available().contains(&self) {
+            self
+        } else {
+            Self::Egui
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct UiSettings {
+    frontend: EditorFrontend,
+}
+
+/// The saved front-end (egui if unset or not compiled in).
+pub fn current() -> EditorFrontend {
+    crate::config::load_json::<UiSettings>("ui.json").frontend.or_available()
+}
+
+/// Save the front-end used the next time an editor window is opened.
+pub fn set(frontend: EditorFrontend) -> Result<(), String> {
+    crate::config::save_json("ui.json", &UiSettings { frontend })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_egui_always_available() {
+        assert!(EditorFrontend::available().contains(&EditorFrontend::Egui));
+        assert_eq!(EditorFrontend::Egui.or_available(), EditorFrontend::Egui);
+    }
+}
@@ -0,0 +1,347 @@
+//! Preset load requests from the editor: supersession and coalescing.
+//!
+//! Every load goes through [`LoadManager`], which keeps one fetch per
+//! preset no matter how many slots asked for it, and cancels a fetch as
+//! soon as nobody is waiting for it any more (the slot was given another
+//! preset, or a newer preview was requested).
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crossbeam_channel::{Receiver, Sender};
+
+use super::{EditorState, PlayNote, PresetLoadedEvent};
+use crate::jobs::{JobHandle, JobPool, JobPriority};
+use crate::preset::cache::DiskCache;
+use crate::preset::graph::PresetGraph;
+use crate::preset::instance::PresetInstance;
+use crate::preset::fetch::PresetFetcher;
+use crate::preset::manager::PresetManager;
+use crate::net::FetchError;
+
+/// Who is waiting for a load. A newer request with the same target
+/// supersedes the older one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoadTarget {
+    /// Load into a rack slot.
+    Slot(usize),
+    /// Browser preview (all previews share one target: only the most
+    /// recent click matters).
+    Preview,
+}
+
+/// A pending request for one target.
+#[derive(Debug, Clone)]
+struct Waiter {
+    preset_id: String,
+    slot_index: usize,
+    play_note: Option<PlayNote>,
+}
+
+/// An in-flight fetch of one preset.
+struct Inflight {
+    job_id: u64,
+    handle: JobHandle,
+}
+
+/// A finished fetch.
+struct LoadResult {
+    job_id: u64,
+    preset_id: String,
+    result: Result<(Arc<PresetInstance>, PresetGraph), FetchError>,
+}
+
+/// UI-side bookkeeping for preset loads.
+pub struct LoadManager {
+    /// Fetches in flight, keyed by preset id ("library/path").
+    inflight: HashMap<String, Inflight>,
+    /// The request each target is currently waiting on.
+    waiting: HashMap<LoadTarget, Waiter>,
+    next_job_id: u64,
+    result_tx: Sender<LoadResult>,
+    result_rx: Receiver<LoadResult>,
+}
+
+impl Default for LoadManager {
+    fn default() -> Self {
+        let (result_tx, result_rx) = crossbeam_channel::unbounded();
+        Self {
+            inflight: HashMap::new(),
+            waiting: HashMap::new(),
+            next_job_id: 0,
+            result_tx,
+            result_rx,
+        }
+    }
+}
+
+impl LoadManager {
+    /// Request a preset for `target`, delivered into `slot_index`.
+    ///
+    /// Supersedes the target's previous request (cancelling its fetch if no
+    /// one else needs it) and joins an existing fetch of the same preset
+    /// instead of starting another.
+    pub fn request(
+        &mut self,
+        jobs: &JobPool,
+        preset_manager: &Arc<Mutex<PresetManager>>,
+        target: LoadTarget,
+        library: &str,
+        path: &str,
+        slot_index: usize,
+        play_note: Option<PlayNote>,
+    ) {
+        let preset_id = format!("{}/{}", library, path);
+        let waiter = Waiter {
+            preset_id: preset_id.clone(),
+            slot_index,
+            play_note,
+        };
+        if let Some(previous) = self.waiting.insert(target, waiter) {
+            if previous.preset_id != preset_id {
+                self.cancel_if_unwanted(&previous.preset_id);
+            }
+        }
+
+        if self.inflight.contains_key(&preset_id) {
+            nih_plug::debug::nih_log!("[Loads] Joining in-flight fetch of {}", preset_id);
+            return;
+        }
+
+        self.next_job_id += 1;
+        let job_id = self.next_job_id;
+        let handle = spawn_fetch(
+            jobs,
+            preset_manager.clone(),
+            self.result_tx.clone(),
+            job_id,
+            library.to_string(),
+            path.to_string(),
+        );
+        self.inflight.insert(preset_id, Inflight { job_id, handle });
+    }
+
+    /// Drop the pending request for `target`, cancelling its fetch if no
+    /// one else needs it.
+    pub fn cancel(&mut self, target: LoadTarget) {
+        if let Some(previous) = self.waiting.remove(&target) {
+            self.cancel_if_unwanted(&previous.preset_id);
+        }
+    }
+
+    /// Number of fetches currently in flight.
+    pub fn in_flight(&self) -> usize {
+        self.inflight.len()
+    }
+
+    /// Whether a fetch for the preset is in flight.
+    pub fn is_loading(&self, preset_id: &str) -> bool {
+        self.inflight.contains_key(preset_id)
+    }
+
+    /// Cancel a fetch nobody is waiting for any more.
+    fn cancel_if_unwanted(&mut self, preset_id: &str) {
+        if self.waiting.values().any(|w| w.preset_id == preset_id) {
+            return;
+        }
+        if let Some(inflight) = self.inflight.remove(preset_id) {
+            nih_plug::debug::nih_log!("[Loads] Cancelling superseded fetch of {}", preset_id);
+            inflight.handle.cancel();
+        }
+    }
+
+    /// Mark a fetch finished, returning the waiters it satisfies.
+    /// Stale results (from a cancelled job) yield nothing.
+    fn complete(&mut self, job_id: u64, preset_id: &str) -> Vec<Waiter> {
+        match self.inflight.get(preset_id) {
+            Some(inflight) if inflight.job_id == job_id => {}
+            _ => return Vec::new(),
+        }
+        self.inflight.remove(preset_id);
+
+        let targets: Vec<LoadTarget> = self
+            .waiting
+            .iter()
+            .filter(|(_, w)| w.preset_id == preset_id)
+            .map(|(t, _)| *t)
+            .collect();
+        targets
+            .into_iter()
+            .filter_map(|t| self.waiting.remove(&t))
+            .collect()
+    }
+}
+
+/// Queue the fetch job for one preset.
+fn spawn_fetch(
+    jobs: &JobPool,
+    preset_manager: Arc<Mutex<PresetManager>>,
+    result_tx: Sender<LoadResult>,
+    job_id: u64,
+    library: String,
+    path: String,
+) -> JobHandle {
+    jobs.submit(JobPriority::Interactive, move |ctx| {
+        nih_plug::debug::nih_log!("[LoaderJob] Started for {}/{}", library, path);
+
+        let (base_url, slug) = {
+            let pm = preset_manager.lock().unwrap();
+            let slug = pm
+                .libraries
+                .iter()
+                .find(|l| l.name == library)
+                .map(|l| l.slug.clone())
+                .unwrap_or_else(|| library.clone());
+            (pm.base_url.clone(), slug)
+        };
+        let fetcher = PresetFetcher::new(base_url);
+
+        nih_plug::debug::nih_log!("[LoaderJob] Fetching preset: slug={} path={}", slug, path);
+
+        let Some(result) = ctx.block_on(fetcher.load_preset(&slug, &path)) else {
+            nih_plug::debug::nih_log!("[LoaderJob] Cancelled load of {}/{}", library, path);
+            return;
+        };
+        // Synth and effect nodes come from the descriptor the fetcher cached
+        let result = result.map(|instance| {
+            let graph = DiskCache::new()
+                .read_preset(&slug, &path)
+                .map(|text| PresetGraph::parse(&text))
+                .unwrap_or_default();
+            (Arc::new(instance), graph)
+        });
+        let _ = result_tx.send(LoadResult {
+            job_id,
+            preset_id: format!("{}/{}", library, path),
+            result,
+        });
+    })
+}
+
+/// Deliver finished fetches to every slot still waiting for them.
+/// Called once per frame, before loaded presets are forwarded to audio.
+pub fn poll(state: &mut EditorState) {
+    state.loads.deliver(&state.ui_preset_loaded_tx, &state.status_text);
+}
+
+impl LoadManager {
+    /// Send finished fetches as `PresetLoadedEvent`s on `preset_loaded_tx`
+    /// (one per waiting slot) and report the outcome in `status_text`.
+    pub fn deliver(
+        &mut self,
+        preset_loaded_tx: &Sender<PresetLoadedEvent>,
+        status_text: &Mutex<String>,
+    ) {
+        while let Ok(done) = self.result_rx.try_recv() {
+            let waiters = self.complete(done.job_id, &done.preset_id);
+            if waiters.is_empty() {
+                continue;
+            }
+
+            let display_name = done.preset_id.rsplit('/').next().unwrap_or(&done.preset_id);
+            match done.result {
+                Ok((instance, graph)) => {
+                    let preset_id = Arc::new(done.preset_id.clone());
+                    nih_plug::debug::nih_log!(
+                        "[Loads] Loaded {}: zones={}, slots={}",
+                        preset_id,
+                        instance.zones.len(),
+                        waiters.len()
+                    );
+                    for waiter in &waiters {
+                        let _ = preset_loaded_tx.try_send(PresetLoadedEvent {
+                            slot_index: waiter.slot_index,
+                            preset_id: preset_id.clone(),
+                            instance: instance.clone(),
+                            graph,
+                            play_note: waiter.play_note,
+                        });
+                    }
+                    if let Ok(mut st) = status_text.lock() {
+                        *st = format!("Loaded {} ({} zones)", display_name, instance.zones.len());
+                    }
+                }
+                Err(e) => {
+                    nih_plug::debug::nih_log!("[Loads] Error loading {}: {}", done.preset_id, e);
+                    if e.is_unreachable() {
+                        crate::net::connectivity::global().report_failure();
+                    }
+                    if let Ok(mut st) = status_text.lock() {
+                        *st = format!("\u{26a0} Error: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pool that is never started, so fetch jobs stay queued.
+    fn idle_pool() -> JobPool {
+        JobPool::new(1)
+    }
+
+    fn manager() -> Arc<Mutex<PresetManager>> {
+        Arc::new(Mutex::new(PresetManager::new()))
+    }
+
+    #[test]
+    fn test_duplicate_requests_share_one_fetch() {
+        let jobs = idle_pool();
+        let pm = manager();
+        let mut loads = LoadManager::default();
+        loads.request(&jobs, &pm, LoadTarget::Slot(0), "lib", "piano", 0, None);
+        loads.request(&jobs, &pm, LoadTarget::Slot(1), "lib", "piano", 1, None);
+        assert_eq!(loads.in_flight(), 1);
+        assert_eq!(jobs.queued(), 1);
+
+        let waiters = loads.complete(1, "lib/piano");
+        let mut slots: Vec<usize> = waiters.iter().map(|w| w.slot_index).collect();
+        slots.sort();
+        assert_eq!(slots, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_superseded_request_cancels_fetch() {
+        let jobs = idle_pool();
+        let pm = manager();
+        let mut loads = LoadManager::default();
+        loads.request(&jobs, &pm, LoadTarget::Preview, "lib", "piano", 0, Some(PlayNote::new(60)));
+        loads.request(&jobs, &pm, LoadTarget::Preview, "lib", "organ", 1, Some(PlayNote::new(60)));
+        assert!(!loads.is_loading("lib/piano"));
+        assert!(loads.is_loading("lib/organ"));
+        // The cancelled job is skipped by the pool
+        assert!(loads.complete(1, "lib/piano").is_empty());
+    }
+
+    #[test]
+    fn test_shared_fetch_survives_one_superseded_waiter() {
+        let jobs = idle_pool();
+        let pm = manager();
+        let mut loads = LoadManager::default();
+        loads.request(&jobs, &pm, LoadTarget::Slot(0), "lib", "piano", 0, None);
+        loads.request(&jobs, &pm, LoadTarget::Slot(1), "lib", "piano", 1, None);
+        loads.request(&jobs, &pm, LoadTarget::Slot(0), "lib", "organ", 0, None);
+        assert!(loads.is_loading("lib/piano"), "slot 1 still needs piano");
+
+        let waiters = loads.complete(1, "lib/piano");
+        assert_eq!(waiters.len(), 1);
+        assert_eq!(waiters[0].slot_index, 1);
+    }
+
+    #[test]
+    fn test_stale_result_ignored() {
+        let jobs = idle_pool();
+        let pm = manager();
+        let mut loads = LoadManager::default();
+        loads.request(&jobs, &pm, LoadTarget::Slot(0), "lib", "piano", 0, None);
+        loads.request(&jobs, &pm, LoadTarget::Slot(0), "lib", "organ", 0, None);
+        loads.request(&jobs, &pm, LoadTarget::Slot(0), "lib", "piano", 0, None);
+        // Result of the first (cancelled) piano job arrives late
+        assert!(loads.complete(1, "lib/piano").is_empty());
+        assert_eq!(loads.complete(3, "lib/piano").len(), 1);
+    }
+}
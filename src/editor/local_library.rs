@@ -0,0 +1,215 @@
+//! "Create library from folder…" in the Settings tab, and keeping local
+//! libraries listed in the browser.
+//!
+//! The scan runs as a background job (see `preset::local_library`). Once it
+//! finishes the library is written to its folder, seeded into the disk
+//! cache, remembered, and added to the browser. The root index refresh
+//! replaces the browser's library list, so `poll` puts local libraries back
+//! whenever they go missing, rebuilding the cached copy if it was cleared.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use nih_plug_egui::egui;
+
+use super::{EditorState, colors};
+use crate::jobs::{JobPool, JobPriority};
+use crate::preset::cache::DiskCache;
+use crate::preset::library_index;
+use crate::preset::local_library::{self, GeneratedLibrary, LocalLibrary};
+use crate::preset::manager::PresetManager;
+
+/// A scan in progress.
+struct Build {
+    done: Arc<AtomicUsize>,
+    total: Arc<AtomicUsize>,
+    result: Arc<Mutex<Option<Result<GeneratedLibrary, String>>>>,
+}
+
+#[derive(Default)]
+pub struct LocalLibraryState {
+    folder: String,
+    name: String,
+    build: Option<Build>,
+    /// Local libraries created so far (None until loaded).
+    registry: Option<Vec<LocalLibrary>>,
+    /// Outcome of the last build.
+    result: Option<Result<String, String>>,
+}
+
+/// Scan, write and cache a library in the background.
+fn spawn_build(state: &mut EditorState, folder: std::path::PathBuf, name: String, write: bool) -> Build {
+    let build = Build {
+        done: Arc::new(AtomicUsize::new(0)),
+        total: Arc::new(AtomicUsize::new(0)),
+        result: Arc::new(Mutex::new(None)),
+    };
+    let (done, total, result) = (build.done.clone(), build.total.clone(), build.result.clone());
+    state.jobs.submit(JobPriority::Background, move |_| {
+        let generated = local_library::build(&folder, &name, |d, t| {
+            done.store(d, Ordering::Relaxed);
+            total.store(t, Ordering::Relaxed);
+        })
+        .and_then(|generated| {
+            if write {
+                local_library::write_to_folder(&generated)?;
+            }
+            local_library::seed_cache(&generated)?;
+            Ok(generated)
+        });
+        if let Ok(mut r) = result.lock() {
+            *r = Some(generated);
+        }
+    });
+    build
+}
+
+/// List a local library in the browser and load its (cached) index.
+fn show_in_browser(jobs: &JobPool, manager: &Arc<Mutex<PresetManager>>, library: &LocalLibrary) {
+    let added = manager.lock().is_ok_and(|mut pm| local_library::register(&mut pm, library));
+    if added {
+        library_index::start_library_fetch(jobs, manager.clone(), library.name.clone());
+    }
+}
+
+/// Finish builds and keep local libraries listed. Called once per frame.
+pub fn poll(state: &mut EditorState) {
+    let registry = state
+        .local_library
+        .registry
+        .get_or_insert_with(local_library::load_registry)
+        .clone();
+    for library in &registry {
+        let listed = state
+            .preset_manager
+            .lock()
+            .is_ok_and(|pm| pm.libraries.iter().any(|l| l.name == library.name));
+        if listed {
+            continue;
+        }
+        if DiskCache::new().read_library_index(&library.slug).is_some() {
+            show_in_browser(&state.jobs, &state.preset_manager, library);
+        } else if state.local_library.build.is_none() {
+            // Cache was cleared: rebuild it from the folder
+            let build = spawn_build(state, library.folder.clone(), library.name.clone(), false);
+            state.local_library.build = Some(build);
+        }
+    }
+
+    let finished = match &state.local_library.build {
+        Some(build) => build.result.lock().ok().and_then(|mut r| r.take()),
+        None => return,
+    };
+    let Some(result) = finished else { return };
+    state.local_library.build = None;
+    let local = &mut state.local_library;
+    local.result = Some(match result {
+        Ok(generated) => {
+            let library = generated.library.clone();
+            let registry = local.registry.get_or_insert_with(Vec::new);
+            registry.retain(|l| l.name != library.name);
+            registry.push(library.clone());
+            let saved = local_library::save_registry(registry);
+            show_in_browser(&state.jobs, &state.preset_manager, &library);
+            let zones: usize = generated.presets.iter().map(|p| p.samples.len()).sum();
+            let mut message = format!(
+                "{}: {} presets, {} zones",
+                library.name,
+                generated.presets.len(),
+                zones
+            );
+            if !generated.skipped.is_empty() {
+                message.push_str(&format!(" ({} files skipped)", generated.skipped.len()));
+            }
+            for (file, reason) in &generated.skipped {
+                nih_plug::debug::nih_log!("[LocalLibrary] Skipped {}: {}", file, reason);
+            }
+            saved.map(|_| message)
+        }
+        Err(e) => Err(e),
+    });
+}
+
+/// Folder/name fields, the create button and the list of local libraries.
+pub fn draw_settings(ui: &mut egui::Ui, state: &mut EditorState) {
+    ui.label(egui::RichText::new("Local Libraries").color(colors::subtext0()))
+        .on_hover_text(
+            "Make a library from a folder of WAV/MP3 files: one preset per folder, \
+             root notes from the file names or pitch detection",
+        );
+    let local = &mut state.local_library;
+    egui::Grid::new("local_library_grid").num_columns(2).show(ui, |ui| {
+        ui.label("Folder:");
+        ui.add(egui::TextEdit::singleline(&mut local.folder).hint_text("/path/to/samples"));
+        ui.end_row();
+        ui.label("Name:");
+        ui.add(egui::TextEdit::singleline(&mut local.name).hint_text("(folder name)"));
+        ui.end_row();
+    });
+
+    let mut start = None;
+    ui.horizontal(|ui| {
+        match &local.build {
+            Some(build) => {
+                let (done, total) = (build.done.load(Ordering::Relaxed), build.total.load(Ordering::Relaxed));
+                ui.add(
+                    egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                        .text(format!("{} / {} files", done, total))
+                        .desired_width(160.0),
+                );
+                ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+            }
+            None => {
+                let folder = std::path::PathBuf::from(local.folder.trim());
+                if ui
+                    .add_enabled(folder.is_dir(), egui::Button::new("Create library from folder…"))
+                    .on_disabled_hover_text("Enter an existing folder")
+                    .clicked()
+                {
+                    let name = match local.name.trim() {
+                        "" => folder.file_name().and_then(|n| n.to_str()).unwrap_or("Local").to_string(),
+                        name => name.to_string(),
+                    };
+                    start = Some((folder, name));
+                }
+            }
+        }
+    });
+    if let Some((folder, name)) = start {
+        let build = spawn_build(state, folder, name, true);
+        state.local_library.build = Some(build);
+        state.local_library.result = None;
+    }
+
+    let local = &mut state.local_library;
+    match &local.result {
+        Some(Ok(message)) => {
+            ui.label(egui::RichText::new(message).color(colors::green()).small());
+        }
+        Some(Err(e)) => {
+            ui.label(egui::RichText::new(e).color(colors::red()).small());
+        }
+        None => {}
+    }
+
+    let mut removed = None;
+    for library in local.registry.iter().flatten() {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(&library.name).color(colors::text()).small());
+            ui.label(egui::RichText::new(library.folder.display().to_string()).color(colors::overlay0()).small());
+            if ui.small_button("Remove").on_hover_text("Remove from the browser (files are kept)").clicked() {
+                removed = Some(library.name.clone());
+            }
+        });
+    }
+    if let Some(name) = removed {
+        if let Some(registry) = local.registry.as_mut() {
+            registry.retain(|l| l.name != name);
+            local.result = local_library::save_registry(registry).err().map(Err);
+        }
+        if let Ok(mut pm) = state.preset_manager.lock() {
+            pm.libraries.retain(|l| l.name != name);
+            pm.library_presets.remove(&name);
+        }
+    }
+}
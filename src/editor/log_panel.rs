@@ -0,0 +1,283 @@
+//! Log console tab: recent log records with level and text filters,
+//! copy-to-clipboard and a bug-report export.
+
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+
+use log::{Level, LevelFilter};
+use nih_plug_egui::egui;
+
+use super::{colors, fs, EditorState};
+use crate::logs::{self, LogEntry};
+
+/// Records included in a bug report.
+const REPORT_LINES: usize = 500;
+
+/// Levels offered in the capture and display filters.
+const LEVELS: [LevelFilter; 5] = [
+    LevelFilter::Error,
+    LevelFilter::Warn,
+    LevelFilter::Info,
+    LevelFilter::Debug,
+    LevelFilter::Trace,
+];
+
+pub struct LogPanelState {
+    /// Least severe level shown.
+    show_level: LevelFilter,
+    /// Case-insensitive text filter.
+    filter: String,
+    /// Status bar message last copied into the log.
+    last_status: String,
+    /// Outcome of the last bug-report export.
+    report: Option<Result<String, String>>,
+}
+
+impl Default for LogPanelState {
+    fn default() -> Self {
+        Self {
+            show_level: LevelFilter::Trace,
+            filter: String::new(),
+            last_status: String::new(),
+            report: None,
+        }
+    }
+}
+
+/// Copy new status bar messages into the log, so the console has a
+/// history even where the host owns the logger.
+pub fn poll(state: &mut EditorState) {
+    let Ok(status) = state.status_text.lock().map(|s| s.clone()) else { return };
+    if status.is_empty() || status == state.log_panel.last_status {
+        return;
+    }
+    let level = if status.starts_with('\u{26a0}') { Level::Warn } else { Level::Info };
+    logs::push(level, "status", status.clone());
+    state.log_panel.last_status = status;
+}
+
+fn level_color(level: Level) -> egui::Color32 {
+    match level {
+        Level::Error => colors::red(),
+        Level::Warn => colors::peach(),
+        Level::Info => colors::text(),
+        Level::Debug => colors::subtext0(),
+        Level::Trace => colors::overlay0(),
+    }
+}
+
+/// Records passing the level and text filters.
+fn filtered(entries: Vec<LogEntry>, show_level: LevelFilter, filter: &str) -> Vec<LogEntry> {
+    let filter = filter.to_lowercase();
+    entries
+        .into_iter()
+        .filter(|e| e.level <= show_level)
+        .filter(|e| {
+            filter.is_empty()
+                || e.message.to_lowercase().contains(&filter)
+                || e.target.to_lowercase().contains(&filter)
+        })
+        .collect()
+}
+
+/// Plain-text report: versions, platform, audio setup, rack and recent logs.
+pub fn bug_report(state: &EditorState) -> String {
+    let mut out = String::new();
+    out.push_str("SongWalker bug report\n=====================\n\n");
+    out.push_str(&format!("Version: {}\n", env!("CARGO_PKG_VERSION")));
+    out.push_str(&format!("Platform: {} {}\n", std::env::consts::OS, std::env::consts::ARCH));
+    let build = if state.device_state.is_some() { "standalone" } else { "plugin" };
+    out.push_str(&format!("Build: {}\n", build));
+
+    out.push_str("\n[Audio]\n");
+    if let Some(ds) = &state.device_state {
+        let audio = ds.audio_device_names.get(ds.selected_audio_idx).map_or("(none)", String::as_str);
+        let midi = ds
+            .selected_midi_idx
+            .and_then(|i| ds.midi_input_names.get(i))
+            .map_or("(none)", String::as_str);
+        out.push_str(&format!("Audio output: {}\nMIDI input: {}\n", audio, midi));
+        out.push_str(&format!("Output latency: {}\n", ds.latency.summary()));
+        out.push_str(&format!("Tempo: {:.1} BPM\n", ds.tempo));
+    } else {
+        out.push_str("Audio/MIDI: provided by the host\n");
+    }
+    out.push_str(&format!("Voices: {}\n", state.voice_count.load(Ordering::Relaxed)));
+
+    out.push_str("\n[Rack]\n");
+    if let Ok(ps) = state.plugin_state.lock() {
+        out.push_str(&format!("Slots: {}, groups: {}\n", ps.slot_configs.len(), ps.groups.len()));
+        for (i, slot) in ps.slot_configs.iter().enumerate() {
+            out.push_str(&format!(
+                "  {}: {} ({})\n",
+                i + 1,
+                slot.name,
+                slot.preset_id.as_deref().unwrap_or("no preset")
+            ));
+        }
+    }
+
+    out.push_str("\n[Network]\n");
+    if let Ok(pm) = state.preset_manager.lock() {
+        out.push_str(&format!("Library URL: {}\n", pm.base_url));
+    }
+    out.push_str(&format!("Connectivity: {}\n", crate::net::connectivity::global().state().label()));
+    let cache = crate::preset::sample_cache::global().stats();
+    out.push_str(&format!("Sample cache: {} samples, {} bytes\n", cache.samples, cache.bytes));
+    out.push_str(&format!("Instances sharing the preset manager: {}\n", crate::preset::shared::instances()));
+
+    let entries = logs::buffer().snapshot();
+    let skip = entries.len().saturating_sub(REPORT_LINES);
+    out.push_str(&format!("\n[Log] (last {} records)\n", entries.len() - skip));
+    for entry in &entries[skip..] {
+        out.push_str(&entry.line());
+        out.push('\n');
+    }
+    out
+}
+
+/// Where bug reports are written.
+fn report_dir() -> Option<PathBuf> {
+    directories::ProjectDirs::from("org", "SongWalker", "songwalker-vsti")
+        .map(|d| d.data_dir().join("bug-reports"))
+}
+
+/// Write a bug report and return its path.
+fn export_report(report: &str) -> Result<PathBuf, String> {
+    let dir = report_dir().ok_or("No data directory")?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let path = dir.join(format!("songwalker-report-{}.txt", stamp));
+    std::fs::write(&path, report).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Draw the log tab.
+pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
+    let buffer = logs::buffer();
+
+    ui.horizontal_wrapped(|ui| {
+        ui.label(egui::RichText::new("Log").color(colors::text()).strong().size(fs(14.0, z)));
+
+        ui.label(egui::RichText::new("Capture:").color(colors::subtext0()));
+        let mut capture = buffer.level();
+        egui::ComboBox::from_id_salt("log_capture_level")
+            .selected_text(capture.as_str())
+            .show_ui(ui, |ui| {
+                for level in LEVELS {
+                    ui.selectable_value(&mut capture, level, level.as_str());
+                }
+            })
+            .response
+            .on_hover_text("Most verbose level recorded from now on");
+        if capture != buffer.level() {
+            buffer.set_level(capture);
+        }
+
+        let panel = &mut state.log_panel;
+        ui.label(egui::RichText::new("Show:").color(colors::subtext0()));
+        egui::ComboBox::from_id_salt("log_show_level")
+            .selected_text(panel.show_level.as_str())
+            .show_ui(ui, |ui| {
+                for level in LEVELS {
+                    ui.selectable_value(&mut panel.show_level, level, level.as_str());
+                }
+            });
+        ui.add(
+            egui::TextEdit::singleline(&mut panel.filter)
+                .hint_text("Filter…")
+                .desired_width(140.0),
+        );
+    });
+
+    let entries = filtered(buffer.snapshot(), state.log_panel.show_level, &state.log_panel.filter);
+
+    ui.horizontal(|ui| {
+        if ui.button("Copy").on_hover_text("Copy the records shown").clicked() {
+            let text: Vec<String> = entries.iter().map(LogEntry::line).collect();
+            ui.ctx().copy_text(text.join("\n"));
+        }
+        if ui.button("Clear").clicked() {
+            buffer.clear();
+        }
+        if ui
+            .button("Export bug report…")
+            .on_hover_text("Write recent logs with system and audio details to a file")
+            .clicked()
+        {
+            let report = bug_report(state);
+            state.log_panel.report = Some(export_report(&report).map(|path| {
+                ui.ctx().copy_text(path.display().to_string());
+                format!("Report written to {} (path copied)", path.display())
+            }));
+        }
+        let dropped = buffer.dropped();
+        if dropped > 0 {
+            ui.label(
+                egui::RichText::new(format!("{} older records dropped", dropped))
+                    .color(colors::overlay0())
+                    .small(),
+            );
+        }
+    });
+    match &state.log_panel.report {
+        Some(Ok(message)) => {
+            ui.label(egui::RichText::new(message).color(colors::green()).small());
+        }
+        Some(Err(e)) => {
+            ui.label(egui::RichText::new(e).color(colors::red()).small());
+        }
+        None => {}
+    }
+    ui.separator();
+
+    if entries.is_empty() {
+        ui.label(
+            egui::RichText::new("No log records.")
+                .color(colors::overlay0())
+                .size(fs(11.0, z))
+                .italics(),
+        );
+        return;
+    }
+    let font = egui::FontId::monospace(fs(11.0, z));
+    let row_height = ui.fonts(|f| f.row_height(&font));
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .stick_to_bottom(true)
+        .show_rows(ui, row_height, entries.len(), |ui, range| {
+            for entry in &entries[range] {
+                ui.label(
+                    egui::RichText::new(entry.line())
+                        .font(font.clone())
+                        .color(level_color(entry.level)),
+                );
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: Level, message: &str) -> LogEntry {
+        LogEntry { time: 0.0, level, target: "test".into(), message: message.into() }
+    }
+
+    #[test]
+    fn test_filtered_by_level_and_text() {
+        let entries = vec![
+            entry(Level::Error, "Load failed"),
+            entry(Level::Debug, "Fetching index"),
+            entry(Level::Info, "Loaded piano"),
+        ];
+        let shown = filtered(entries.clone(), LevelFilter::Info, "");
+        assert_eq!(shown.len(), 2);
+        let shown = filtered(entries, LevelFilter::Trace, "LOAD");
+        assert_eq!(shown.len(), 2);
+        assert_eq!(shown[1].message, "Loaded piano");
+    }
+}
@@ -0,0 +1,146 @@
+//! Macro knobs above the slot rack, and their assignment panel (see
+//! `slots::macros`).
+
+use std::sync::Arc;
+
+use nih_plug_egui::egui;
+
+use super::{EditorEvent, EditorState, GlobalParams, accessibility, colors, fs, zs};
+use crate::slots::{MACRO_COUNT, MacroAssignment, MacroCurve, MacroTarget};
+
+/// Draw the macro strip: a knob per macro and, when expanded, the list
+/// of assignments.
+pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, params: &dyn GlobalParams, z: f32) {
+    let (mut assignments, slot_names) = match state.plugin_state.lock() {
+        Ok(ps) => (ps.macros.clone(), ps.slot_configs.iter().map(|c| c.display_name()).collect::<Vec<_>>()),
+        Err(_) => return,
+    };
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Macros").color(colors::subtext0()).size(fs(12.0, z)));
+        for index in 0..MACRO_COUNT {
+            let assigned = assignments.iter().filter(|a| a.macro_index == index).count();
+            let mut value = params.macro_value(index);
+            ui.vertical(|ui| {
+                let response = ui
+                    .add(egui::Slider::new(&mut value, 0.0..=1.0).vertical().show_value(false))
+                    .on_hover_text(match assigned {
+                        0 => "Not assigned".to_string(),
+                        1 => "1 assignment".to_string(),
+                        n => format!("{} assignments", n),
+                    });
+                accessibility::name(
+                    &response,
+                    egui::WidgetType::Slider,
+                    format!("Macro {}, {}%", index + 1, (value * 100.0).round()),
+                );
+                if response.changed() {
+                    params.set_macro_value(index, value);
+                }
+                let color = if assigned > 0 { colors::text() } else { colors::overlay0() };
+                ui.label(egui::RichText::new(format!("M{}", index + 1)).color(color).size(fs(10.0, z)));
+            });
+        }
+        let expanded = &mut state.slot_rack_state.macros_expanded;
+        if ui
+            .selectable_label(*expanded, egui::RichText::new("Assign\u{2026}").size(fs(11.0, z)))
+            .on_hover_text("Choose what each macro drives")
+            .clicked()
+        {
+            *expanded = !*expanded;
+        }
+    });
+
+    if !state.slot_rack_state.macros_expanded {
+        return;
+    }
+    let before = assignments.clone();
+    let mut remove = None;
+    for (i, assignment) in assignments.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = zs(4.0, z);
+            if draw_assignment(ui, i, assignment, &slot_names) {
+                remove = Some(i);
+            }
+        });
+    }
+    if let Some(i) = remove {
+        assignments.remove(i);
+    }
+    if ui
+        .small_button(egui::RichText::new("+ Add assignment").color(colors::green()).size(fs(11.0, z)))
+        .clicked()
+    {
+        let slot = state.slot_rack_state.selected_slot;
+        assignments.push(MacroAssignment::new(0, slot, MacroTarget::Volume));
+    }
+
+    if assignments != before {
+        let _ = state.event_tx.try_send(EditorEvent::SetMacros { assignments: Arc::new(assignments.clone()) });
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            ps.macros = assignments;
+        }
+    }
+}
+
+/// One row of the assignment list. Returns whether Remove was clicked.
+fn draw_assignment(ui: &mut egui::Ui, i: usize, assignment: &mut MacroAssignment, slot_names: &[String]) -> bool {
+    egui::ComboBox::from_id_salt(("macro_index", i))
+        .width(48.0)
+        .selected_text(format!("M{}", assignment.macro_index + 1))
+        .show_ui(ui, |ui| {
+            for index in 0..MACRO_COUNT {
+                ui.selectable_value(&mut assignment.macro_index, index, format!("M{}", index + 1));
+            }
+        });
+    ui.label("\u{2192}");
+
+    let slot_text = |slot: usize| match slot_names.get(slot) {
+        Some(name) => format!("{}: {}", slot + 1, name),
+        None => format!("{}: (none)", slot + 1),
+    };
+    egui::ComboBox::from_id_salt(("macro_slot", i))
+        .width(120.0)
+        .selected_text(slot_text(assignment.slot_index))
+        .show_ui(ui, |ui| {
+            for slot in 0..slot_names.len() {
+                ui.selectable_value(&mut assignment.slot_index, slot, slot_text(slot));
+            }
+        });
+
+    let target = assignment.target;
+    egui::ComboBox::from_id_salt(("macro_target", i))
+        .width(70.0)
+        .selected_text(target.label())
+        .show_ui(ui, |ui| {
+            for t in MacroTarget::ALL {
+                ui.selectable_value(&mut assignment.target, t, t.label());
+            }
+        });
+    if assignment.target != target {
+        // The old range means nothing for the new target
+        (assignment.min, assignment.max) = assignment.target.range();
+    }
+
+    let (lo, hi) = assignment.target.range();
+    let (speed, suffix) = match assignment.target {
+        MacroTarget::FilterCutoff => (10.0, " Hz"),
+        _ => (0.01, ""),
+    };
+    ui.add(egui::DragValue::new(&mut assignment.min).range(lo..=hi).speed(speed).suffix(suffix))
+        .on_hover_text("Value with the macro at 0");
+    ui.label("\u{2013}");
+    ui.add(egui::DragValue::new(&mut assignment.max).range(lo..=hi).speed(speed).suffix(suffix))
+        .on_hover_text("Value with the macro at 100%");
+
+    egui::ComboBox::from_id_salt(("macro_curve", i))
+        .width(60.0)
+        .selected_text(assignment.curve.label())
+        .show_ui(ui, |ui| {
+            for curve in MacroCurve::ALL {
+                ui.selectable_value(&mut assignment.curve, curve, curve.label());
+            }
+        });
+
+    ui.small_button("\u{2715}").on_hover_text("Remove").clicked()
+}
@@ -0,0 +1,70 @@
+//! "Rec MIDI" in the header: capture incoming MIDI to a `.mid` file.
+//!
+//! The audio thread records into the monitor's `MidiRecorder`; this drains
+//! it every frame while armed and exports the take when recording stops.
+
+use nih_plug_egui::egui;
+
+use super::{EditorState, colors, fs};
+use crate::midi::recorder::{self, RecordedEvent};
+
+#[derive(Default)]
+pub struct MidiCaptureState {
+    /// Events of the take in progress.
+    take: Vec<RecordedEvent>,
+    /// Outcome of the last export (file path or error), for the tooltip.
+    result: Option<Result<String, String>>,
+}
+
+/// Record toggle for the header bar.
+pub fn draw_button(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
+    let recorder = state.monitor.midi_recorder();
+    let recording = recorder.is_recording();
+    if recording {
+        recorder.drain_into(&mut state.midi_capture.take);
+    }
+
+    let (text, color) = if recording {
+        (format!("■ Stop MIDI ({})", state.midi_capture.take.len()), colors::red())
+    } else {
+        ("● Rec MIDI".to_string(), colors::subtext0())
+    };
+    let hover = match &state.midi_capture.result {
+        Some(Ok(path)) => format!("Record incoming MIDI to a .mid file\nLast take: {}", path),
+        Some(Err(e)) => format!("Record incoming MIDI to a .mid file\nLast take: {}", e),
+        None => "Record incoming MIDI to a .mid file".to_string(),
+    };
+    let clicked = ui
+        .selectable_label(recording, egui::RichText::new(text).color(color).size(fs(12.0, z)))
+        .on_hover_text(hover)
+        .clicked();
+    if !clicked {
+        return;
+    }
+
+    let capture = &mut state.midi_capture;
+    if !recording {
+        capture.take.clear();
+        recorder.start();
+        return;
+    }
+    recorder.stop();
+    recorder.drain_into(&mut capture.take);
+    let dropped = recorder.dropped();
+    let result = recorder::export(&capture.take, recorder.sample_rate())
+        .map(|path| path.display().to_string());
+    let status = match &result {
+        Ok(path) if dropped > 0 => format!("MIDI take saved to {} ({} events dropped)", path, dropped),
+        Ok(path) => format!("MIDI take saved to {}", path),
+        Err(e) => format!("MIDI take not saved: {}", e),
+    };
+    match &result {
+        Ok(_) => log::info!("{}", status),
+        Err(_) => log::warn!("{}", status),
+    }
+    if let Ok(mut st) = state.status_text.lock() {
+        *st = status;
+    }
+    capture.result = Some(result);
+    capture.take = Vec::new();
+}
@@ -0,0 +1,173 @@
+//! MIDI monitor tab: recent incoming events with the slots that took them.
+//!
+//! Events come from the audio thread's `MidiInspector` ring; the tab keeps
+//! its own longer history so pausing and scrolling back don't lose rows.
+
+use std::collections::VecDeque;
+
+use nih_plug_egui::egui;
+
+use super::{colors, fs, piano, EditorState};
+use crate::midi::inspector::InspectedEvent;
+
+/// Events kept by the tab.
+const HISTORY: usize = 1000;
+
+#[derive(Default)]
+pub struct MidiMonitorState {
+    events: VecDeque<InspectedEvent>,
+    /// Sequence number of the last event read from the ring.
+    last_seq: u64,
+    /// Stop adding rows (events arriving meanwhile are skipped).
+    paused: bool,
+    /// Read buffer reused between frames.
+    scratch: Vec<InspectedEvent>,
+}
+
+/// Data bytes of an event, for display.
+fn data_text(event: &InspectedEvent) -> String {
+    match event.bytes[0] & 0xF0 {
+        0x80 | 0x90 => format!("{:<4} vel {}", piano::note_name(event.bytes[1]), event.bytes[2]),
+        0xE0 => {
+            let bend = (i32::from(event.bytes[2]) << 7 | i32::from(event.bytes[1])) - 8192;
+            format!("{:+}", bend)
+        }
+        0xF0 => String::new(),
+        _ => event.bytes[1..usize::from(event.len.max(1))]
+            .iter()
+            .map(u8::to_string)
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// Slot numbers (1-based) in a slot mask, or "–" for none.
+fn slots_text(mask: u16) -> String {
+    if mask == 0 {
+        return "–".into();
+    }
+    (0..16)
+        .filter(|i| mask & (1 << i) != 0)
+        .map(|i| (i + 1).to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Names of the slots in a slot mask (`names` by slot index).
+fn slot_names(mask: u16, names: &[String]) -> String {
+    names
+        .iter()
+        .enumerate()
+        .take(16)
+        .filter(|(i, _)| mask & (1 << i) != 0)
+        .map(|(_, name)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// One monitor row.
+fn line(event: &InspectedEvent, names: &[String]) -> String {
+    let channel = event.channel().map_or("–".to_string(), |c| c.to_string());
+    format!(
+        "{:>10.3}  {:>2}  {:<13} {:<14} {:<8} {}",
+        event.time_us as f64 / 1_000_000.0,
+        channel,
+        event.kind(),
+        data_text(event),
+        slots_text(event.slots),
+        slot_names(event.slots, names)
+    )
+    .trim_end()
+    .to_string()
+}
+
+/// Pull new events from the audio thread's ring.
+fn poll(state: &mut EditorState) {
+    let monitor = &mut state.midi_monitor;
+    monitor.scratch.clear();
+    monitor.last_seq = state.monitor.midi_inspector().read_since(monitor.last_seq, &mut monitor.scratch);
+    if monitor.paused {
+        return;
+    }
+    monitor.events.extend(monitor.scratch.drain(..));
+    let excess = monitor.events.len().saturating_sub(HISTORY);
+    monitor.events.drain(..excess);
+}
+
+/// Draw the MIDI monitor tab.
+pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
+    poll(state);
+    ui.ctx().request_repaint();
+    let names: Vec<String> = state
+        .plugin_state
+        .lock()
+        .map(|ps| ps.slot_configs.iter().take(16).map(|c| c.display_name()).collect())
+        .unwrap_or_default();
+
+    let monitor = &mut state.midi_monitor;
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("MIDI Monitor").color(colors::text()).strong().size(fs(14.0, z)));
+        let pause_label = if monitor.paused { "▶ Resume" } else { "⏸ Pause" };
+        if ui.button(pause_label).clicked() {
+            monitor.paused = !monitor.paused;
+        }
+        if ui.button("Clear").clicked() {
+            monitor.events.clear();
+        }
+        if ui.button("Copy").on_hover_text("Copy the events shown").clicked() {
+            let text: Vec<String> = monitor.events.iter().map(|e| line(e, &names)).collect();
+            ui.ctx().copy_text(text.join("\n"));
+        }
+        ui.label(
+            egui::RichText::new(format!("{} events", monitor.events.len()))
+                .color(colors::overlay0())
+                .small(),
+        );
+    });
+    ui.separator();
+
+    let font = egui::FontId::monospace(fs(11.0, z));
+    ui.label(
+        egui::RichText::new(format!("{:>10}  {:>2}  {:<13} {:<14} {:<8} {}", "Time (s)", "Ch", "Type", "Data", "Slots", "Names"))
+            .font(font.clone())
+            .color(colors::subtext0()),
+    );
+    if monitor.events.is_empty() {
+        ui.label(
+            egui::RichText::new("No MIDI received yet.")
+                .color(colors::overlay0())
+                .size(fs(11.0, z))
+                .italics(),
+        );
+        return;
+    }
+    let row_height = ui.fonts(|f| f.row_height(&font));
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .stick_to_bottom(true)
+        .show_rows(ui, row_height, monitor.events.len(), |ui, range| {
+            for event in monitor.events.range(range) {
+                let color = if event.slots == 0 { colors::overlay0() } else { colors::text() };
+                ui.label(egui::RichText::new(line(event, &names)).font(font.clone()).color(color));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_text() {
+        let bend = InspectedEvent { seq: 1, time_us: 1_500_000, bytes: [0xE1, 0, 0x60], len: 3, slots: 0b1001 };
+        assert_eq!(data_text(&bend), "+4096");
+        assert_eq!(slots_text(bend.slots), "1,4");
+        assert_eq!(slots_text(0), "–");
+        let cc = InspectedEvent { bytes: [0xB0, 1, 64], ..bend };
+        assert_eq!(data_text(&cc), "1 64");
+        assert!(line(&cc, &[]).contains("CC"));
+        let names = ["Piano".to_string(), "Bass".to_string(), "Pad".to_string(), "Lead".to_string()];
+        assert_eq!(slot_names(bend.slots, &names), "Piano, Lead");
+        assert!(line(&bend, &names).ends_with("1,4      Piano, Lead"));
+    }
+}
@@ -0,0 +1,66 @@
+//! Settings editor for the rack's NRPN/SysEx rules (see `midi::rules`).
+
+use std::sync::Arc;
+
+use nih_plug_egui::egui;
+
+use super::{EditorEvent, EditorState, colors};
+use crate::midi::rules::{format_rules, parse_rules};
+
+const SYNTAX_HELP: &str = "One rule per line:\n\
+    nrpn <msb:lsb> [value <0–127>] [ch <1–16>] => <action>\n\
+    sysex F0 … F7 => <action>\n\n\
+    Actions: load <slot> <library/path>, mute <slot>, solo <slot>";
+
+/// Rule text being edited.
+#[derive(Default)]
+pub struct MidiRulesState {
+    /// None until the settings tab is shown.
+    draft: Option<String>,
+    /// Parse error from the last "Apply".
+    error: Option<String>,
+}
+
+pub fn draw_settings(ui: &mut egui::Ui, state: &mut EditorState) {
+    let saved = state
+        .plugin_state
+        .lock()
+        .map(|ps| format_rules(&ps.midi_rules))
+        .unwrap_or_default();
+    let rules_state = &mut state.midi_rules;
+    let draft = rules_state.draft.get_or_insert_with(|| saved.clone());
+
+    ui.label(egui::RichText::new("MIDI Rules").color(colors::subtext0()))
+        .on_hover_text(SYNTAX_HELP);
+    ui.add(
+        egui::TextEdit::multiline(draft)
+            .code_editor()
+            .desired_rows(3)
+            .desired_width(f32::INFINITY)
+            .hint_text("nrpn 1:2 => load 1 FluidR3_GM/Acoustic Grand Piano"),
+    );
+
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(*draft != saved, egui::Button::new("Apply"))
+            .clicked()
+        {
+            match parse_rules(draft) {
+                Ok(rules) => {
+                    let _ = state.event_tx.try_send(EditorEvent::SetMidiRules {
+                        rules: Arc::new(rules.clone()),
+                    });
+                    *draft = format_rules(&rules);
+                    if let Ok(mut ps) = state.plugin_state.lock() {
+                        ps.midi_rules = rules;
+                    }
+                    rules_state.error = None;
+                }
+                Err(e) => rules_state.error = Some(e),
+            }
+        }
+        if let Some(ref err) = rules_state.error {
+            ui.label(egui::RichText::new(err).color(colors::red()));
+        }
+    });
+}
@@ -0,0 +1,225 @@
+//! Mixer tab: every slot as a vertical channel strip side by side, like a
+//! console, then the group buses. The strips edit the same `SlotConfig`s
+//! as the rack list, through the same slot actions.
+//!
+//! The rack has no send effects, so a strip's routing selector picks the
+//! bus the slot feeds: a group, or straight to the master.
+
+use nih_plug_egui::egui;
+
+use super::{EditorState, accessibility, colors, fs, slot_actions, slot_rack, zs};
+use crate::state::{SlotConfig, SlotGroup};
+
+/// Strip width and fader length, before zoom.
+const STRIP_WIDTH: f32 = 78.0;
+const FADER_LENGTH: f32 = 150.0;
+
+/// Draw the mixer: a strip per slot, then a strip per group bus.
+pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
+    let (configs, groups) = match state.plugin_state.lock() {
+        Ok(ps) => (ps.slot_configs.clone(), ps.groups.clone()),
+        Err(_) => return,
+    };
+
+    ui.label(egui::RichText::new("Mixer").color(colors::text()).strong().size(fs(14.0, z)));
+    ui.separator();
+    if configs.is_empty() {
+        ui.label(
+            egui::RichText::new("No slots. Add some in the Slot Rack tab.")
+                .color(colors::overlay0())
+                .italics(),
+        );
+        return;
+    }
+
+    let group_names: Vec<String> = groups.iter().map(|g| g.name.clone()).collect();
+    ui.horizontal_top(|ui| {
+        ui.spacing_mut().item_spacing = egui::vec2(zs(4.0, z), zs(4.0, z));
+        for (idx, config) in configs.iter().enumerate() {
+            draw_slot_strip(ui, state, idx, config, &group_names, z);
+        }
+        if !groups.is_empty() {
+            ui.separator();
+        }
+        for (group_idx, group) in groups.iter().enumerate() {
+            draw_group_strip(ui, state, group_idx, group, z);
+        }
+    });
+
+    // The meters move without any input
+    ui.ctx().request_repaint_after(std::time::Duration::from_millis(50));
+}
+
+fn strip_frame(selected: bool, z: f32) -> egui::Frame {
+    egui::Frame::NONE
+        .fill(if selected { colors::mantle() } else { colors::crust() })
+        .inner_margin(egui::Margin::same(zs(4.0, z) as i8))
+        .corner_radius(zs(4.0, z))
+        .stroke(egui::Stroke::new(1.0, if selected { colors::blue() } else { colors::surface0() }))
+}
+
+/// Mute and solo buttons; returns which were clicked.
+fn draw_mute_solo(ui: &mut egui::Ui, what: &str, muted: bool, solo: bool, z: f32) -> (bool, bool) {
+    ui.horizontal(|ui| {
+        let mute_color = if muted { colors::red() } else { colors::overlay0() };
+        let mute = ui.button(egui::RichText::new("M").color(mute_color).size(fs(11.0, z)));
+        accessibility::name_toggle(&mute, format!("{} mute", what), muted);
+        let solo_color = if solo { colors::yellow() } else { colors::overlay0() };
+        let solo_button = ui.button(egui::RichText::new("S").color(solo_color).size(fs(11.0, z)));
+        accessibility::name_toggle(&solo_button, format!("{} solo", what), solo);
+        (mute.clicked(), solo_button.clicked())
+    })
+    .inner
+}
+
+/// Vertical fader over the 0–1.5 gain range.
+fn draw_fader(ui: &mut egui::Ui, what: &str, volume: &mut f32, z: f32) -> egui::Response {
+    ui.spacing_mut().slider_width = zs(FADER_LENGTH, z);
+    let fader = ui.add(egui::Slider::new(volume, 0.0..=1.5).vertical().show_value(false));
+    accessibility::name(
+        &fader,
+        egui::WidgetType::Slider,
+        format!("{} volume, {}", what, accessibility::gain_text(*volume)),
+    );
+    fader
+}
+
+/// Peak meter of a slot's output, with a clip light on top (click to clear).
+fn draw_meter(ui: &mut egui::Ui, peak: f32, clipped: bool, z: f32) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(zs(8.0, z), zs(FADER_LENGTH, z)), egui::Sense::click());
+    let painter = ui.painter();
+    painter.rect_filled(rect, 1.0, colors::surface0());
+    let color = if peak > 1.0 {
+        colors::red()
+    } else if peak > 0.707 {
+        colors::yellow()
+    } else {
+        colors::green()
+    };
+    let top = rect.bottom() - peak.clamp(0.0, 1.0) * rect.height();
+    painter.rect_filled(egui::Rect::from_min_max(egui::pos2(rect.left(), top), rect.max), 1.0, color);
+    if clipped {
+        let light = egui::Rect::from_min_size(rect.min, egui::vec2(rect.width(), zs(4.0, z)));
+        painter.rect_filled(light, 1.0, colors::red());
+    }
+    response.on_hover_text(if clipped { "Clipped \u{2014} click to clear" } else { "Output level" })
+}
+
+fn gain_label(ui: &mut egui::Ui, volume: f32, z: f32) {
+    let text = match volume {
+        v if v <= 0.0 => "-inf dB".to_string(),
+        v => format!("{:.1} dB", nih_plug::util::gain_to_db(v)),
+    };
+    ui.label(egui::RichText::new(text).color(colors::subtext0()).size(fs(10.0, z)));
+}
+
+fn draw_slot_strip(
+    ui: &mut egui::Ui,
+    state: &mut EditorState,
+    idx: usize,
+    config: &SlotConfig,
+    group_names: &[String],
+    z: f32,
+) {
+    let selected = state.slot_rack_state.selected_slot == idx;
+    let what = format!("Slot {}", idx + 1);
+    strip_frame(selected, z).show(ui, |ui| {
+        ui.set_width(zs(STRIP_WIDTH, z));
+        ui.vertical_centered(|ui| {
+            ui.label(egui::RichText::new((idx + 1).to_string()).color(colors::overlay0()).strong().size(fs(11.0, z)));
+            let name = ui
+                .add(
+                    egui::Label::new(
+                        egui::RichText::new(config.display_name()).color(colors::text()).strong().size(fs(11.0, z)),
+                    )
+                    .truncate()
+                    .sense(egui::Sense::click()),
+                )
+                .on_hover_text(config.display_name());
+            accessibility::name(
+                &name,
+                egui::WidgetType::SelectableLabel,
+                format!("{}: {}", what, config.display_name()),
+            );
+            if name.clicked() {
+                state.slot_rack_state.selected_slot = idx;
+            }
+
+            // Routing: the bus this slot feeds
+            let bus_name = |group: Option<usize>| {
+                group.and_then(|g| group_names.get(g)).map_or("Master", String::as_str).to_string()
+            };
+            let mut group = config.group;
+            egui::ComboBox::from_id_salt(("mixer_route", idx))
+                .width(zs(STRIP_WIDTH - 8.0, z))
+                .selected_text(bus_name(group))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut group, None, "Master");
+                    for (gi, name) in group_names.iter().enumerate() {
+                        ui.selectable_value(&mut group, Some(gi), name);
+                    }
+                })
+                .response
+                .on_hover_text("Bus this slot feeds");
+            if group != config.group {
+                slot_actions::set_group(state, idx, group);
+            }
+
+            let mut pan = config.pan;
+            ui.spacing_mut().slider_width = zs(STRIP_WIDTH - 8.0, z);
+            let pan_slider = ui
+                .add(egui::Slider::new(&mut pan, -1.0..=1.0).show_value(false))
+                .on_hover_text(format!("Pan {}", accessibility::pan_text(pan)));
+            accessibility::name(
+                &pan_slider,
+                egui::WidgetType::Slider,
+                format!("{} pan, {}", what, accessibility::pan_text(pan)),
+            );
+            if pan_slider.changed() {
+                slot_actions::set_mix(state, idx, config.volume, pan);
+            }
+
+            let (mute, solo) = draw_mute_solo(ui, &what, config.muted, config.solo, z);
+            if mute {
+                slot_actions::toggle_mute(state, idx);
+            }
+            if solo {
+                slot_actions::toggle_solo(state, idx);
+            }
+
+            let mut volume = config.volume;
+            ui.horizontal(|ui| {
+                if draw_fader(ui, &what, &mut volume, z).changed() {
+                    slot_actions::set_mix(state, idx, volume, config.pan);
+                }
+                if draw_meter(ui, state.monitor.output_peak(idx), state.monitor.clipped(idx), z).clicked() {
+                    state.monitor.clear_clip(idx);
+                }
+            });
+            gain_label(ui, volume, z);
+        });
+    });
+}
+
+fn draw_group_strip(ui: &mut egui::Ui, state: &mut EditorState, group_idx: usize, group: &SlotGroup, z: f32) {
+    let mut edited = group.clone();
+    let what = format!("Group {}", group.name);
+    strip_frame(false, z).fill(colors::surface0().gamma_multiply(0.4)).show(ui, |ui| {
+        ui.set_width(zs(STRIP_WIDTH, z));
+        ui.vertical_centered(|ui| {
+            ui.label(egui::RichText::new("Bus").color(colors::mauve()).strong().size(fs(11.0, z)));
+            ui.add(
+                egui::Label::new(egui::RichText::new(&group.name).color(colors::text()).strong().size(fs(11.0, z)))
+                    .truncate(),
+            );
+            let (mute, solo) = draw_mute_solo(ui, &what, edited.muted, edited.solo, z);
+            edited.muted ^= mute;
+            edited.solo ^= solo;
+            draw_fader(ui, &what, &mut edited.volume, z);
+            gain_label(ui, edited.volume, z);
+        });
+    });
+    if (edited.volume, edited.muted, edited.solo) != (group.volume, group.muted, group.solo) {
+        slot_rack::update_group(state, group_idx, &edited);
+    }
+}
@@ -5,11 +5,40 @@
 //! - Right panel: Slot rack (Kontakt-style) with inline editors
 //! - Bottom: Visualizer and status bar
 
+pub mod accessibility;
+pub mod batch_add;
 pub mod browser;
 pub mod code_editor;
+pub mod compile;
+pub mod download_check;
+pub mod event_queue;
+pub mod focus;
+pub mod freeze;
+pub mod frontend;
+pub mod loads;
+pub mod local_library;
+pub mod log_panel;
+pub mod macros;
+pub mod midi_capture;
+pub mod midi_monitor;
+pub mod midi_rules;
+pub mod mixer;
+pub mod network;
+pub mod onboarding;
+pub mod patch_export;
+pub mod performance;
 pub mod piano;
+pub mod preset_info;
+pub mod preview;
+pub mod purge;
+pub mod quick_load;
+pub mod slot_actions;
+pub mod slot_clipboard;
 pub mod slot_rack;
+pub mod surprise;
+pub mod theme;
 pub mod visualizer;
+pub mod web_bridge;
 
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicU32, Ordering};
@@ -31,6 +60,15 @@ pub trait GlobalParams {
     fn set_max_voices(&self, v: i32);
     fn pitch_bend_range(&self) -> i32;
     fn set_pitch_bend_range(&self, v: i32);
+    fn master_output(&self) -> crate::params::MasterOutput;
+    fn set_master_output(&self, output: crate::params::MasterOutput);
+    /// Host mix parameters of a slot, or None if it has none (past the
+    /// automatable bank, or in the standalone where there is no host).
+    fn slot_mix(&self, slot_index: usize) -> Option<crate::params::SlotMix>;
+    fn set_slot_mix(&self, slot_index: usize, mix: crate::params::SlotMix);
+    /// Position (0–1) of a macro knob.
+    fn macro_value(&self, index: usize) -> f32;
+    fn set_macro_value(&self, index: usize, value: f32);
 }
 
 /// Plugin-side implementation — wraps nih-plug's ParamSetter for DAW automation.
@@ -64,6 +102,62 @@ impl GlobalParams for PluginGlobalParams<'_> {
         self.setter.set_parameter(&self.params.pitch_bend_range, v);
         self.setter.end_set_parameter(&self.params.pitch_bend_range);
     }
+    fn master_output(&self) -> crate::params::MasterOutput {
+        self.params.master_output()
+    }
+    fn set_master_output(&self, output: crate::params::MasterOutput) {
+        let current = self.params.master_output();
+        let p = self.params;
+        for (param, old, new) in [
+            (&p.mono, current.mono, output.mono),
+            (&p.swap_channels, current.swap, output.swap),
+            (&p.invert_left, current.invert_left, output.invert_left),
+            (&p.invert_right, current.invert_right, output.invert_right),
+        ] {
+            if old != new {
+                self.setter.begin_set_parameter(param);
+                self.setter.set_parameter(param, new);
+                self.setter.end_set_parameter(param);
+            }
+        }
+    }
+    fn slot_mix(&self, slot_index: usize) -> Option<crate::params::SlotMix> {
+        self.params.slots.get(slot_index).map(|p| p.mix())
+    }
+    fn set_slot_mix(&self, slot_index: usize, mix: crate::params::SlotMix) {
+        let Some(p) = self.params.slots.get(slot_index) else { return };
+        let current = p.mix();
+        // Only touch changed parameters so the host records no spurious edits
+        if current.volume != mix.volume {
+            self.setter.begin_set_parameter(&p.volume);
+            self.setter.set_parameter(&p.volume, mix.volume);
+            self.setter.end_set_parameter(&p.volume);
+        }
+        if current.pan != mix.pan {
+            self.setter.begin_set_parameter(&p.pan);
+            self.setter.set_parameter(&p.pan, mix.pan);
+            self.setter.end_set_parameter(&p.pan);
+        }
+        if current.muted != mix.muted {
+            self.setter.begin_set_parameter(&p.mute);
+            self.setter.set_parameter(&p.mute, mix.muted);
+            self.setter.end_set_parameter(&p.mute);
+        }
+        if current.solo != mix.solo {
+            self.setter.begin_set_parameter(&p.solo);
+            self.setter.set_parameter(&p.solo, mix.solo);
+            self.setter.end_set_parameter(&p.solo);
+        }
+    }
+    fn macro_value(&self, index: usize) -> f32 {
+        self.params.macros.get(index).map_or(0.0, |m| m.value.value())
+    }
+    fn set_macro_value(&self, index: usize, value: f32) {
+        let Some(m) = self.params.macros.get(index) else { return };
+        self.setter.begin_set_parameter(&m.value);
+        self.setter.set_parameter(&m.value, value);
+        self.setter.end_set_parameter(&m.value);
+    }
 }
 
 // ── Standalone device state ──────────────────────────────────
@@ -77,16 +171,61 @@ pub struct DeviceState {
     pub selected_midi_idx: Option<usize>,
     /// Set by UI — the standalone app checks this after draw and performs the switch.
     pub pending_audio_switch: Option<String>,
+    /// Output buffer size and safe mode as edited in Settings.
+    pub buffer_settings: crate::standalone::latency::BufferSettings,
+    /// Set by UI — the standalone app saves these and restarts the stream.
+    pub pending_buffer: Option<crate::standalone::latency::BufferSettings>,
+    /// Output latency and callback timing, updated by the audio callback.
+    pub latency: Arc<crate::standalone::latency::LatencyStats>,
     /// Set by UI — the standalone app checks this after draw and performs the switch.
     pub pending_midi_switch: Option<String>,
     /// Set by UI — standalone app refreshes device lists.
     pub needs_refresh: bool,
+    /// OSC remote control settings as edited in the UI.
+    pub osc_settings: crate::standalone::osc::OscSettings,
+    /// Set by UI — the standalone app restarts the OSC server with these.
+    pub pending_osc: Option<crate::standalone::osc::OscSettings>,
+    /// "Listening on port …" or the last bind error.
+    pub osc_status: String,
+    /// Tempo (BPM) edited in the header; copied to the audio params each frame.
+    pub tempo: f32,
+    /// Metronome settings as edited in the header.
+    pub metronome: crate::standalone::metronome::MetronomeSettings,
+    /// Set by UI — the standalone app saves and applies these.
+    pub pending_metronome: Option<crate::standalone::metronome::MetronomeSettings>,
+    /// Set by UI — the standalone app starts a count-in.
+    pub count_in_requested: bool,
+    /// Beats left in a running count-in (0 = none), updated by the audio callback.
+    pub count_in_beats: Arc<AtomicU32>,
+    /// Time signature as edited in the header.
+    pub time_signature: (i32, i32),
+    /// Loop region as edited in the header.
+    pub loop_region: crate::standalone::transport::LoopRegion,
+    /// Set by UI — the standalone app forwards these to the audio callback.
+    pub pending_transport: Vec<crate::standalone::transport::TransportCommand>,
+    /// Play head published by the audio callback.
+    pub transport_display: Arc<crate::standalone::transport::TransportDisplay>,
+    /// Ableton Link settings as edited in the UI.
+    pub link_settings: crate::standalone::link::LinkSettings,
+    /// Set by UI — the standalone app saves these and joins/leaves the session.
+    pub pending_link: Option<crate::standalone::link::LinkSettings>,
+    /// Link peer count, updated by the session.
+    pub link_status: Arc<crate::standalone::link::LinkStatus>,
+    /// Whether to restore the last session at startup (saved by the app).
+    pub restore_session: crate::standalone::session::RestoreMode,
+    /// Compact performance layout (see `performance`); the app resizes the window.
+    pub performance_mode: bool,
+    /// Keep the window above other applications (applied by the app).
+    pub always_on_top: bool,
 }
 
+use crate::jobs::JobPool;
+use crate::monitor::EngineMonitor;
 use crate::params::SongWalkerParams;
 use crate::preset::manager::PresetManager;
 use crate::preset::instance::PresetInstance;
-use crate::state::PluginState;
+use crate::state::{EditorLayout, PluginState};
+use crate::view_model;
 
 /// Events sent from the editor UI to the audio thread.
 #[derive(Debug, Clone)]
@@ -95,8 +234,85 @@ pub enum EditorEvent {
     NoteOn { slot_index: usize, note: u8, velocity: f32 },
     /// Release a note on a specific slot.
     NoteOff { slot_index: usize, note: u8 },
+    /// Move a slot's bend wheel (−1..1, 0 = centre), as MIDI pitch bend.
+    PitchBend { slot_index: usize, value: f32 },
+    /// Move a slot's mod wheel (0–1), as MIDI CC1.
+    ModWheel { slot_index: usize, value: f32 },
     /// Stop all preview playback.
     StopPreview,
+    /// Kill every voice on every slot with a short fade and reset sustain,
+    /// arpeggiators and runner schedulers.
+    Panic,
+    /// Remove a slot; the slots after it move up one place.
+    RemoveSlot { slot_index: usize },
+    /// Assign a slot to a group bus (None = ungrouped).
+    SetSlotGroup { slot_index: usize, group: Option<usize> },
+    /// Update a group bus's volume/mute/solo.
+    SetGroup { group_index: usize, bus: crate::slots::GroupBus },
+    /// Enable runner MIDI output on a channel (0–15), or disable it (None).
+    SetSlotMidiOut { slot_index: usize, channel: Option<u8> },
+    /// Update a slot's arpeggiator settings.
+    SetSlotArp { slot_index: usize, settings: crate::slots::ArpSettings },
+    /// Turn a slot's hold mode on or off (off releases the latched notes).
+    SetSlotHold { slot_index: usize, hold: bool },
+    /// Release the notes latched by a slot's hold mode.
+    ReleaseHeldNotes { slot_index: usize },
+    /// Swap a slot's preset for a copy with its unplayed zones purged
+    /// (see `slots::zone_usage`). The replaced copy goes to the collector.
+    PurgeZones { slot_index: usize, instance: Arc<PresetInstance> },
+    /// Play a rendered clip in place of a slot's voices (None = unfreeze).
+    /// The editor keeps its own `Arc` so the audio thread never frees it.
+    SetSlotFrozen { slot_index: usize, clip: Option<Arc<crate::slots::frozen::FrozenClip>> },
+    /// Update a runner slot's humanize settings.
+    SetSlotHumanize { slot_index: usize, humanize: crate::slots::Humanize },
+    /// Update a runner slot's launch quantization.
+    SetSlotLaunchQuantize { slot_index: usize, quantize: crate::slots::LaunchQuantize },
+    /// Update a slot's volume and pan (smoothed on the audio thread).
+    SetSlotMix { slot_index: usize, volume: f32, pan: f32 },
+    /// Update a slot's tuning and stereo width.
+    SetSlotTuning { slot_index: usize, tuning: crate::slots::SlotTuning },
+    /// Update a slot's unison voices, detune and spread.
+    SetSlotUnison { slot_index: usize, unison: crate::slots::UnisonSettings },
+    /// Update a slot's retrigger and legato crossfades.
+    SetSlotLegato { slot_index: usize, legato: crate::slots::LegatoSettings },
+    /// Update a slot's bend range override and destination.
+    SetSlotBend { slot_index: usize, bend: crate::slots::BendSettings },
+    /// Update a slot's mod wheel destination and controller depths.
+    SetSlotControllers { slot_index: usize, controllers: crate::slots::ControllerSettings },
+    /// Update a slot's modulation matrix.
+    SetSlotModMatrix { slot_index: usize, matrix: crate::slots::ModMatrix },
+    /// Change a slot's polyphony (its pool is resized off the audio thread).
+    SetSlotPolyphony { slot_index: usize, voices: usize },
+    /// Replace a slot's input MIDI filter.
+    SetSlotMidiFilter { slot_index: usize, filter: crate::slots::MidiFilter },
+    /// Update a slot's voice low-pass filter.
+    SetSlotFilter { slot_index: usize, filter: crate::slots::VoiceFilterSettings },
+    /// Update a slot's output trim and saturation.
+    SetSlotOutput { slot_index: usize, output: crate::slots::SlotOutput },
+    /// Update a slot's oversampling.
+    SetSlotOversampling { slot_index: usize, oversampling: crate::slots::Oversampling },
+    /// Update a slot's articulation keyswitches.
+    SetSlotKeyswitches { slot_index: usize, keyswitches: crate::slots::KeyswitchMap },
+    /// Turn parallel slot rendering on or off (handled by the backend,
+    /// which owns the `AudioEngine`).
+    SetParallelRender { enabled: bool },
+    /// Unload a slot's preset (its `SlotConfig` keeps the preset id).
+    UnloadPreset { slot_index: usize },
+    /// Turn the reset of every slot on transport stop on or off.
+    SetResetOnStop { enabled: bool },
+    /// Turn GM mode on (resetting every channel's sound) or off.
+    SetGmMode { enabled: bool },
+    /// Change the rack's scale lock.
+    SetScaleLock { lock: crate::midi::scale::ScaleLock },
+    /// Replace the rack's NRPN/SysEx rules.
+    SetMidiRules { rules: Arc<Vec<crate::midi::rules::MidiRule>> },
+    /// Replace the macro knob assignments.
+    SetMacros { assignments: Arc<Vec<crate::slots::MacroAssignment>> },
+    /// Hot-swap a recompiled runner program into a slot.
+    LoadRunnerProgram {
+        slot_index: usize,
+        program: Arc<crate::slots::runner_slot::RunnerProgram>,
+    },
 }
 
 /// Event sent when a preset has been fully loaded (samples decoded) on a
@@ -110,9 +326,26 @@ pub struct PresetLoadedEvent {
     pub preset_id: Arc<String>,
     /// Fully-decoded preset ready for the audio thread.
     pub instance: Arc<PresetInstance>,
-    /// If `Some(note)`, trigger a NoteOn at this note immediately after
-    /// loading (used by the preview play button).
-    pub play_note: Option<u8>,
+    /// Synth and effect nodes read from the descriptor.
+    pub graph: crate::preset::graph::PresetGraph,
+    /// If set, trigger a NoteOn immediately after loading (used by the
+    /// preview play button).
+    pub play_note: Option<PlayNote>,
+}
+
+/// A note played as soon as a loaded preset reaches the audio thread.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayNote {
+    pub note: u8,
+    /// 0.0–1.0.
+    pub velocity: f32,
+}
+
+impl PlayNote {
+    /// `note` at the default preview velocity.
+    pub fn new(note: u8) -> Self {
+        Self { note, velocity: preview::PREVIEW_VELOCITY }
+    }
 }
 
 /// The application icon (PNG), embedded at compile time.
@@ -127,6 +360,12 @@ pub fn zs(base: f32, _zoom: f32) -> f32 {
     base
 }
 
+/// Scale a font size by the accessibility text scale (independent of zoom).
+#[inline]
+pub fn fs(base: f32, _zoom: f32) -> f32 {
+    base * accessibility::font_scale()
+}
+
 /// Default editor window size.
 const EDITOR_WIDTH: u32 = 800;
 const EDITOR_HEIGHT: u32 = 600;
@@ -134,29 +373,76 @@ const EDITOR_HEIGHT: u32 = 600;
 const MIN_WIDTH: f32 = 400.0;
 const MIN_HEIGHT: f32 = 300.0;
 
-/// Catppuccin Mocha color palette (matches the web editor).
+/// Colors of the active theme, by Catppuccin role (see `theme`).
 pub mod colors {
     use nih_plug_egui::egui::Color32;
 
-    pub const BASE: Color32 = Color32::from_rgb(30, 30, 46);
-    pub const MANTLE: Color32 = Color32::from_rgb(24, 24, 37);
-    pub const CRUST: Color32 = Color32::from_rgb(17, 17, 27);
-    pub const SURFACE0: Color32 = Color32::from_rgb(49, 50, 68);
-    pub const SURFACE1: Color32 = Color32::from_rgb(69, 71, 90);
-    pub const SURFACE2: Color32 = Color32::from_rgb(88, 91, 112);
-    pub const TEXT: Color32 = Color32::from_rgb(205, 214, 244);
-    pub const SUBTEXT0: Color32 = Color32::from_rgb(166, 173, 200);
-    pub const SUBTEXT1: Color32 = Color32::from_rgb(186, 194, 222);
-    pub const BLUE: Color32 = Color32::from_rgb(137, 180, 250);
-    pub const GREEN: Color32 = Color32::from_rgb(166, 227, 161);
-    pub const PEACH: Color32 = Color32::from_rgb(250, 179, 135);
-    pub const RED: Color32 = Color32::from_rgb(243, 139, 168);
-    pub const MAUVE: Color32 = Color32::from_rgb(203, 166, 247);
-    pub const YELLOW: Color32 = Color32::from_rgb(249, 226, 175);
-    pub const TEAL: Color32 = Color32::from_rgb(148, 226, 213);
-    pub const LAVENDER: Color32 = Color32::from_rgb(180, 190, 254);
-    pub const PINK: Color32 = Color32::from_rgb(245, 194, 231);
-    pub const OVERLAY0: Color32 = Color32::from_rgb(108, 112, 134);
+    use super::theme;
+
+    pub fn base() -> Color32 {
+        theme::active().base
+    }
+    pub fn mantle() -> Color32 {
+        theme::active().mantle
+    }
+    pub fn crust() -> Color32 {
+        theme::active().crust
+    }
+    pub fn surface0() -> Color32 {
+        theme::active().surface0
+    }
+    pub fn surface1() -> Color32 {
+        theme::active().surface1
+    }
+    pub fn surface2() -> Color32 {
+        theme::active().surface2
+    }
+    pub fn text() -> Color32 {
+        theme::active().text
+    }
+    pub fn subtext0() -> Color32 {
+        theme::active().subtext0
+    }
+    pub fn subtext1() -> Color32 {
+        theme::active().subtext1
+    }
+    pub fn blue() -> Color32 {
+        theme::active().blue
+    }
+    pub fn green() -> Color32 {
+        theme::active().green
+    }
+    pub fn peach() -> Color32 {
+        theme::active().peach
+    }
+    pub fn red() -> Color32 {
+        theme::active().red
+    }
+    pub fn mauve() -> Color32 {
+        theme::active().mauve
+    }
+    pub fn yellow() -> Color32 {
+        theme::active().yellow
+    }
+    pub fn teal() -> Color32 {
+        theme::active().teal
+    }
+    pub fn lavender() -> Color32 {
+        theme::active().lavender
+    }
+    pub fn pink() -> Color32 {
+        theme::active().pink
+    }
+    pub fn overlay0() -> Color32 {
+        theme::active().overlay0
+    }
+
+    /// Fill of the piano's white and black keys: light and dark whatever
+    /// the theme.
+    pub fn piano_keys() -> (Color32, Color32) {
+        let p = theme::active();
+        if p.dark { (p.text, p.base) } else { (p.base, p.text) }
+    }
 }
 
 /// Create a default EguiState for use in params persistence.
@@ -170,15 +456,18 @@ pub fn create(
     plugin_state: Arc<Mutex<PluginState>>,
     params: Arc<SongWalkerParams>,
     editor_state: Arc<EguiState>,
-    event_tx: Sender<EditorEvent>,
+    event_tx: event_queue::EventSender,
     audio_preset_loaded_tx: Sender<PresetLoadedEvent>,
     ui_preset_loaded_tx: Sender<PresetLoadedEvent>,
     ui_preset_loaded_rx: Receiver<PresetLoadedEvent>,
     status_text: Arc<Mutex<String>>,
     visualizer_state: Arc<visualizer::VisualizerState>,
     voice_count: Arc<AtomicU32>,
+    monitor: Arc<EngineMonitor>,
+    jobs: Arc<JobPool>,
 ) -> Option<Box<dyn Editor>> {
     let egui_state_for_resize = editor_state.clone();
+    let layout = plugin_state.lock().map(|ps| ps.layout).unwrap_or_default();
 
     create_egui_editor(
         editor_state,
@@ -186,10 +475,24 @@ pub fn create(
             egui_state: Some(egui_state_for_resize),
             preset_manager,
             plugin_state,
-            current_tab: EditorTab::SlotRack,
+            current_tab: layout.tab,
             browser_state: browser::BrowserState::default(),
             slot_rack_state: slot_rack::SlotRackState::default(),
-            piano_state: piano::PianoState::default(),
+            compile_state: compile::CompileState::default(),
+            loads: loads::LoadManager::default(),
+            network: network::NetworkState::default(),
+            midi_rules: midi_rules::MidiRulesState::default(),
+            patch_export: patch_export::PatchExportState::default(),
+            local_library: local_library::LocalLibraryState::default(),
+            web_bridge: web_bridge::WebBridgeState::default(),
+            slot_clipboard: slot_clipboard::SlotClipboardState::default(),
+            quick_load: quick_load::QuickLoadState::default(),
+            midi_capture: midi_capture::MidiCaptureState::default(),
+            surprise: surprise::SurpriseState::default(),
+            freeze: freeze::FreezeState::default(),
+            purge: purge::PurgeState::default(),
+            download_check: download_check::DownloadCheckState::default(),
+            piano_state: piano::PianoState::new(layout.piano_visible),
             event_tx,
             audio_preset_loaded_tx,
             ui_preset_loaded_tx,
@@ -197,10 +500,20 @@ pub fn create(
             status_text,
             visualizer_state,
             voice_count,
-            zoom_level: 1.0,
+            monitor,
+            jobs,
+            zoom_level: layout.zoom.clamp(0.5, 2.0),
             resize_drag_start: None,
             active_presets_ui: std::collections::HashMap::new(),
             device_state: None,
+            slot_params_seen: Default::default(),
+            focus: Default::default(),
+            log_panel: Default::default(),
+            midi_monitor: Default::default(),
+            onboarding: Default::default(),
+            theme: Default::default(),
+            accessibility: Default::default(),
+            layout,
         },
         |ctx, _state| {
             // Apply dark theme on init
@@ -229,10 +542,15 @@ pub fn create(
 }
 
 /// Which tab/panel is active in the main area.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EditorTab {
     SlotRack,
+    /// The slots as console channel strips.
+    Mixer,
     Settings,
+    Log,
+    MidiMonitor,
 }
 
 /// Persistent state for the editor (not the audio state).
@@ -244,9 +562,36 @@ pub struct EditorState {
     pub current_tab: EditorTab,
     pub browser_state: browser::BrowserState,
     pub slot_rack_state: slot_rack::SlotRackState,
+    /// Background compilation of runner source.
+    pub compile_state: compile::CompileState,
+    /// Pending preset loads (supersession and coalescing).
+    pub loads: loads::LoadManager,
+    /// Offline probing and reconnect handling.
+    pub network: network::NetworkState,
+    /// MIDI rule text in the Settings tab.
+    pub midi_rules: midi_rules::MidiRulesState,
+    /// Patch list export in the Settings tab.
+    pub patch_export: patch_export::PatchExportState,
+    /// Local libraries built from sample folders (Settings tab).
+    pub local_library: local_library::LocalLibraryState,
+    /// WebSocket server for the SongWalker web editor (Settings tab).
+    pub web_bridge: web_bridge::WebBridgeState,
+    /// Paste window for slots copied as JSON.
+    pub slot_clipboard: slot_clipboard::SlotClipboardState,
+    /// Preset search popup for filling an empty slot.
+    pub quick_load: quick_load::QuickLoadState,
+    /// Take in progress for the header's MIDI record button.
+    pub midi_capture: midi_capture::MidiCaptureState,
+    /// "Surprise me" slot count and category weights.
+    pub surprise: surprise::SurpriseState,
+    /// Slot freeze renders and the clips of frozen slots.
+    pub freeze: freeze::FreezeState,
+    pub purge: purge::PurgeState,
+    /// Browser load waiting on its download size check.
+    pub download_check: download_check::DownloadCheckState,
     pub piano_state: piano::PianoState,
     /// Channel for sending events (note on/off, preview) to the audio thread.
-    pub event_tx: Sender<EditorEvent>,
+    pub event_tx: event_queue::EventSender,
     /// Channel for sending fully-loaded presets to the audio thread.
     pub audio_preset_loaded_tx: Sender<PresetLoadedEvent>,
     /// Channel for sending presets from background threads to the UI.
@@ -259,6 +604,10 @@ pub struct EditorState {
     pub visualizer_state: Arc<visualizer::VisualizerState>,
     /// Live voice count from the audio thread.
     pub voice_count: Arc<AtomicU32>,
+    /// Per-slot telemetry from the audio thread (runner play position, etc.).
+    pub monitor: Arc<EngineMonitor>,
+    /// Shared background job pool (preset downloads).
+    pub jobs: Arc<JobPool>,
     /// UI zoom level (1.0 = 100%, range 0.5–2.0).
     pub zoom_level: f32,
     /// Tracks the drag anchor for window resize: (start_pointer_pos, start_window_size).
@@ -269,55 +618,30 @@ pub struct EditorState {
     pub active_presets_ui: std::collections::HashMap<usize, (Arc<String>, Arc<PresetInstance>)>,
     /// Standalone-only: available audio/MIDI devices and switch commands.
     pub device_state: Option<Box<DeviceState>>,
+    /// Slot mix parameter values as last synced with the rack.
+    pub slot_params_seen: view_model::SlotParamsSeen,
+    /// Panel receiving navigation keys, and the browser rows they move through.
+    pub focus: focus::FocusState,
+    /// Log console filters and last export result.
+    pub log_panel: log_panel::LogPanelState,
+    /// Recent incoming MIDI shown in the MIDI tab.
+    pub midi_monitor: midi_monitor::MidiMonitorState,
+    /// First-run welcome window and starter download.
+    pub onboarding: onboarding::OnboardingState,
+    /// Theme picker in Settings.
+    pub theme: theme::ThemeState,
+    /// Text size and hit-target options in Settings.
+    pub accessibility: accessibility::AccessibilityState,
+    /// Layout as last recorded into `PluginState` (panel widths are read
+    /// back from here as the panels' initial widths).
+    pub layout: EditorLayout,
 }
 
-/// Apply the Catppuccin Mocha theme to egui, matching the web editor CSS.
+/// Apply the saved theme to egui (Catppuccin Mocha, matching the web
+/// editor CSS, unless another was chosen in Settings).
 pub(crate) fn apply_theme(ctx: &egui::Context) {
-    let mut style = (*ctx.style()).clone();
-
-    // Dark background — matches --bg / --surface
-    style.visuals.dark_mode = true;
-    style.visuals.panel_fill = colors::BASE;
-    style.visuals.window_fill = colors::MANTLE;
-
-    // Widget colors — buttons use --surface bg + --border border (4px radius)
-    style.visuals.widgets.noninteractive.bg_fill = colors::SURFACE0;
-    style.visuals.widgets.noninteractive.fg_stroke =
-        egui::Stroke::new(1.0, colors::TEXT);
-    style.visuals.widgets.noninteractive.weak_bg_fill = colors::MANTLE;
-    style.visuals.widgets.noninteractive.corner_radius = egui::CornerRadius::same(4);
-    style.visuals.widgets.inactive.bg_fill = colors::MANTLE;
-    style.visuals.widgets.inactive.weak_bg_fill = colors::MANTLE;
-    style.visuals.widgets.inactive.bg_stroke =
-        egui::Stroke::new(1.0, colors::SURFACE0);
-    style.visuals.widgets.inactive.fg_stroke =
-        egui::Stroke::new(1.0, colors::SUBTEXT0);
-    style.visuals.widgets.inactive.corner_radius = egui::CornerRadius::same(4);
-    style.visuals.widgets.hovered.bg_fill = colors::SURFACE0;
-    style.visuals.widgets.hovered.weak_bg_fill = colors::SURFACE0;
-    style.visuals.widgets.hovered.fg_stroke =
-        egui::Stroke::new(1.0, colors::TEXT);
-    style.visuals.widgets.hovered.corner_radius = egui::CornerRadius::same(4);
-    style.visuals.widgets.active.bg_fill = colors::SURFACE1;
-    style.visuals.widgets.active.weak_bg_fill = colors::SURFACE1;
-    style.visuals.widgets.active.fg_stroke =
-        egui::Stroke::new(1.0, colors::TEXT);
-    style.visuals.widgets.active.corner_radius = egui::CornerRadius::same(4);
-
-    // Selection
-    style.visuals.selection.bg_fill = colors::BLUE.linear_multiply(0.3);
-    style.visuals.selection.stroke = egui::Stroke::new(1.0, colors::BLUE);
-
-    // Window / panel borders — matching --border (#313244 = SURFACE0)
-    style.visuals.window_stroke = egui::Stroke::new(1.0, colors::SURFACE0);
-    style.visuals.window_corner_radius = egui::CornerRadius::same(4);
-
-    // Spacing — tighter to match web CSS
-    style.spacing.item_spacing = egui::vec2(6.0, 4.0);
-    style.spacing.button_padding = egui::vec2(6.0, 3.0);
-    style.spacing.window_margin = egui::Margin::same(8);
-
-    ctx.set_style(style);
+    accessibility::set_active(&accessibility::load_settings());
+    theme::apply_settings(ctx, &theme::load_settings());
 }
 
 /// Apply a zoom level change and resize the window proportionally.
@@ -360,19 +684,38 @@ pub(crate) fn draw_editor(
     state.visualizer_state.decay_levels(0.92); // Approx 500ms decay
 
     // --- Drain loaded presets (background thread → UI → audio thread) ---
-    while let Ok(loaded) = state.ui_preset_loaded_rx.try_recv() {
-        nih_plug::debug::nih_log!("[UI] Received PresetLoadedEvent for {} into slot {}, play_note={:?}", loaded.preset_id, loaded.slot_index, loaded.play_note);
-        // Keep a reference on the UI side to prevent deallocation on the audio thread
-        state.active_presets_ui.insert(
-            loaded.slot_index,
-            (loaded.preset_id.clone(), loaded.instance.clone()),
-        );
-        // Forward a clone (or the original, since we have clones in the map) to the audio thread
-        match state.audio_preset_loaded_tx.try_send(loaded) {
-            Ok(()) => nih_plug::debug::nih_log!("[UI] Forwarded preset to audio thread"),
-            Err(e) => nih_plug::debug::nih_log!("[UI] FAILED to forward preset to audio thread: {:?}", e),
-        }
+    loads::poll(state);
+    network::poll(state);
+    let previews = view_model::forward_loaded_presets(
+        &state.ui_preset_loaded_rx,
+        &state.audio_preset_loaded_tx,
+        &mut state.active_presets_ui,
+    );
+
+    // --- Preview chord/phrase notes and auto-release ---
+    let now = std::time::Instant::now();
+    for (slot_index, play) in previews {
+        state.browser_state.preview.start(slot_index, play, now);
     }
+    state.browser_state.preview.poll(now, &state.event_tx);
+    // Notes waiting for room in the audio channel (see `event_queue`)
+    state.event_tx.flush();
+
+    // --- Host automation ↔ rack mix ---
+    view_model::sync_slot_params(&state.plugin_state, params, &mut state.slot_params_seen);
+
+    // --- Live re-compile of runner source (debounced, off-thread) ---
+    compile::poll(state);
+    batch_add::poll(state);
+    freeze::poll(state);
+    purge::poll(state);
+    download_check::poll(state);
+    local_library::poll(state);
+    web_bridge::poll(state);
+    log_panel::poll(state);
+
+    // --- Keyboard navigation (applied once the panels are drawn) ---
+    let nav_keys = focus::read_keys(ctx, &state.focus);
 
     let prev_zoom = state.zoom_level;
 
@@ -408,11 +751,17 @@ pub(crate) fn draw_editor(
 
     let z = state.zoom_level;
 
+    // --- Compact performance window (standalone) ---
+    if state.device_state.as_ref().is_some_and(|ds| ds.performance_mode) {
+        performance::draw(ctx, state, z);
+        return;
+    }
+
     // --- Header bar ---
     egui::TopBottomPanel::top("header")
         .frame(
             egui::Frame::NONE
-                .fill(colors::BASE)
+                .fill(colors::base())
                 .inner_margin(egui::Margin::symmetric(zs(16.0, z) as i8, zs(8.0, z) as i8))
                 .stroke(egui::Stroke::NONE),
         )
@@ -422,14 +771,14 @@ pub(crate) fn draw_editor(
                     ui.horizontal(|ui| {
                         ui.label(
                             egui::RichText::new("SongWalker")
-                                .color(colors::BLUE)
+                                .color(colors::blue())
                                 .strong()
-                                .size(zs(16.0, z)),
+                                .size(fs(16.0, z)),
                         );
                         ui.label(
                             egui::RichText::new("VSTi")
-                                .color(colors::SUBTEXT0)
-                                .size(zs(12.0, z)),
+                                .color(colors::subtext0())
+                                .size(fs(12.0, z)),
                         );
                         ui.add_space(zs(8.0, z));
 
@@ -439,51 +788,99 @@ pub(crate) fn draw_editor(
                         {
                             state.current_tab = EditorTab::SlotRack;
                         }
+                        if ui
+                            .selectable_label(state.current_tab == EditorTab::Mixer, "Mixer")
+                            .on_hover_text("All slots as channel strips side by side")
+                            .clicked()
+                        {
+                            state.current_tab = EditorTab::Mixer;
+                        }
                         if ui
                             .selectable_label(state.current_tab == EditorTab::Settings, "⚙ Settings")
                             .clicked()
                         {
                             state.current_tab = EditorTab::Settings;
                         }
+                        if ui
+                            .selectable_label(state.current_tab == EditorTab::Log, "Log")
+                            .on_hover_text("Log console and bug report export")
+                            .clicked()
+                        {
+                            state.current_tab = EditorTab::Log;
+                        }
+                        if ui
+                            .selectable_label(state.current_tab == EditorTab::MidiMonitor, "MIDI")
+                            .on_hover_text("Recent incoming MIDI and the slots that took it")
+                            .clicked()
+                        {
+                            state.current_tab = EditorTab::MidiMonitor;
+                        }
 
                         // Piano keyboard toggle
-                        let piano_color = if state.piano_state.visible { colors::BLUE } else { colors::SUBTEXT0 };
+                        let piano_color = if state.piano_state.visible { colors::blue() } else { colors::subtext0() };
                         if ui
                             .selectable_label(
                                 state.piano_state.visible,
-                                egui::RichText::new("Piano").color(piano_color).size(zs(14.0, z)),
+                                egui::RichText::new("Piano").color(piano_color).size(fs(14.0, z)),
                             )
                             .clicked()
                         {
                             state.piano_state.visible = !state.piano_state.visible;
                         }
 
+                        // Panic: kill all voices on every slot
+                        if ui
+                            .button(
+                                egui::RichText::new("Panic")
+                                    .color(colors::red())
+                                    .strong()
+                                    .size(fs(12.0, z)),
+                            )
+                            .on_hover_text("Silence all slots and reset sustain, arpeggiators and runners")
+                            .clicked()
+                        {
+                            state.browser_state.preview.stop_all(&state.event_tx);
+                            let _ = state.event_tx.try_send(EditorEvent::Panic);
+                        }
+
+                        midi_capture::draw_button(ui, state, z);
+
+                        // Transport, tempo, metronome and count-in (standalone
+                        // only; the plugin follows the host)
+                        if let Some(ref mut ds) = state.device_state {
+                            ui.add_space(zs(8.0, z));
+                            draw_transport_controls(ui, ds, z);
+                            draw_metronome_controls(ui, ds, z);
+                            ui.add_space(zs(8.0, z));
+                            performance::draw_window_controls(ui, ds, z);
+                        }
+
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                             ui.hyperlink_to(
-                                egui::RichText::new("♥ Donate").color(colors::PINK).size(zs(12.0, z)),
+                                egui::RichText::new("♥ Donate").color(colors::pink()).size(fs(12.0, z)),
                                 "https://github.com/sponsors/clevertree",
                             );
 
                             ui.add_space(zs(8.0, z));
 
                             // Zoom controls
-                            if ui
-                                .button(egui::RichText::new("+").color(colors::SUBTEXT0).size(zs(12.0, z)))
-                                .on_hover_text("Zoom in")
-                                .clicked()
-                            {
+                            let zoom_in = ui
+                                .button(egui::RichText::new("+").color(colors::subtext0()).size(fs(12.0, z)))
+                                .on_hover_text("Zoom in");
+                            accessibility::name(&zoom_in, egui::WidgetType::Button, "Zoom in");
+                            if zoom_in.clicked() {
                                 state.zoom_level = (state.zoom_level + 0.1).min(2.0);
                             }
                             ui.label(
                                 egui::RichText::new(format!("{}%", (state.zoom_level * 100.0) as u32))
-                                    .color(colors::SUBTEXT0)
-                                    .size(zs(10.0, z)),
+                                    .color(colors::subtext0())
+                                    .size(fs(10.0, z)),
                             );
-                            if ui
-                                .button(egui::RichText::new("−").color(colors::SUBTEXT0).size(zs(12.0, z)))
-                                .on_hover_text("Zoom out")
-                                .clicked()
-                            {
+                            let zoom_out = ui
+                                .button(egui::RichText::new("−").color(colors::subtext0()).size(fs(12.0, z)))
+                                .on_hover_text("Zoom out");
+                            accessibility::name(&zoom_out, egui::WidgetType::Button, "Zoom out");
+                            if zoom_out.clicked() {
                                 state.zoom_level = (state.zoom_level - 0.1).max(0.5);
                             }
                         });
@@ -496,7 +893,7 @@ pub(crate) fn draw_editor(
                     egui::pos2(rect.left(), rect.bottom()),
                     egui::pos2(rect.right(), rect.bottom()),
                 ],
-                egui::Stroke::new(1.0, colors::SURFACE0),
+                egui::Stroke::new(1.0, colors::surface0()),
             );
         });
 
@@ -504,7 +901,7 @@ pub(crate) fn draw_editor(
     egui::TopBottomPanel::bottom("status_bar")
         .frame(
             egui::Frame::NONE
-                .fill(colors::MANTLE)
+                .fill(colors::mantle())
                 .inner_margin(egui::Margin::symmetric(zs(12.0, z) as i8, zs(3.0, z) as i8)),
         )
         .show(ctx, |ui| {
@@ -515,7 +912,7 @@ pub(crate) fn draw_editor(
                     egui::pos2(rect.left(), rect.top()),
                     egui::pos2(rect.right(), rect.top()),
                 ],
-                egui::Stroke::new(1.0, colors::SURFACE0),
+                egui::Stroke::new(1.0, colors::surface0()),
             );
             egui::ScrollArea::horizontal()
                 .show(ui, |ui| {
@@ -527,39 +924,84 @@ pub(crate) fn draw_editor(
                         if status_msg.is_empty() {
                             ui.label(
                                 egui::RichText::new("Ready")
-                                    .color(colors::GREEN)
-                                    .size(zs(11.0, z))
+                                    .color(colors::green())
+                                    .size(fs(11.0, z))
                                     .family(egui::FontFamily::Monospace),
                             );
                         } else {
                             let is_error = status_msg.starts_with('\u{26a0}') || status_msg.starts_with("Error");
-                            let color = if is_error { colors::RED } else { colors::TEAL };
+                            let color = if is_error { colors::red() } else { colors::teal() };
                             ui.label(
                                 egui::RichText::new(&status_msg)
                                     .color(color)
-                                    .size(zs(11.0, z))
+                                    .size(fs(11.0, z))
                                     .family(egui::FontFamily::Monospace),
                             );
                         }
 
                         ui.label(
                             egui::RichText::new(format!("Voices: {}/256", state.voice_count.load(Ordering::Relaxed)))
-                                .color(colors::SUBTEXT0)
-                                .size(zs(11.0, z))
+                                .color(colors::subtext0())
+                                .size(fs(11.0, z))
                                 .family(egui::FontFamily::Monospace),
                         );
+                        let dropped = state.event_tx.dropped();
+                        if dropped > 0 {
+                            ui.label(
+                                egui::RichText::new(format!("Dropped: {}", dropped))
+                                    .color(colors::peach())
+                                    .size(fs(11.0, z))
+                                    .family(egui::FontFamily::Monospace),
+                            )
+                            .on_hover_text(
+                                "Editor events the audio engine had no room for. \
+                                 Note-offs go first; dense note bursts are merged before anything is dropped.",
+                            );
+                        }
                         ui.label(
                             egui::RichText::new("CPU: 0.0%")
-                                .color(colors::SUBTEXT0)
-                                .size(zs(11.0, z))
+                                .color(colors::subtext0())
+                                .size(fs(11.0, z))
                                 .family(egui::FontFamily::Monospace),
                         );
+                        let connectivity = crate::net::connectivity::global().state();
+                        let net_color = match connectivity {
+                            crate::net::Connectivity::Online => colors::green(),
+                            crate::net::Connectivity::Offline => colors::red(),
+                            crate::net::Connectivity::Unknown => colors::overlay0(),
+                        };
                         ui.label(
-                            egui::RichText::new("Cache: 0 MB")
-                                .color(colors::SUBTEXT0)
-                                .size(zs(11.0, z))
+                            egui::RichText::new(format!("\u{25cf} {}", connectivity.label()))
+                                .color(net_color)
+                                .size(fs(11.0, z))
                                 .family(egui::FontFamily::Monospace),
                         );
+
+                        let loaded_bytes = crate::preset::memory::total_bytes(
+                            state.active_presets_ui.values().map(|(_, p)| p.as_ref()),
+                        );
+                        ui.label(
+                            egui::RichText::new(format!("Mem: {}", crate::preset::memory::format_bytes(loaded_bytes)))
+                                .color(colors::subtext0())
+                                .size(fs(11.0, z))
+                                .family(egui::FontFamily::Monospace),
+                        )
+                        .on_hover_text("Sample memory used by loaded presets");
+                        let cache = crate::preset::sample_cache::global().stats();
+                        ui.label(
+                            egui::RichText::new(format!("Cache: {:.1} MB", cache.bytes as f64 / (1024.0 * 1024.0)))
+                                .color(colors::subtext0())
+                                .size(fs(11.0, z))
+                                .family(egui::FontFamily::Monospace),
+                        )
+                        .on_hover_text(format!(
+                            "{} samples shared by {} zones\n{:.1} MB saved by deduplication\n{} hits / {} misses",
+                            cache.samples,
+                            cache.references,
+                            cache.bytes_saved() as f64 / (1024.0 * 1024.0),
+                            cache.hits,
+                            cache.misses,
+                        ));
                     });
                 });
         });
@@ -570,9 +1012,9 @@ pub(crate) fn draw_editor(
             .resizable(false)
             .frame(
                 egui::Frame::NONE
-                    .fill(colors::CRUST)
+                    .fill(colors::crust())
                     .inner_margin(egui::Margin::symmetric(zs(8.0, z) as i8, zs(4.0, z) as i8))
-                    .stroke(egui::Stroke::new(1.0, colors::SURFACE0)),
+                    .stroke(egui::Stroke::new(1.0, colors::surface0())),
             )
             .show(ctx, |ui| {
                 piano::draw(ui, state, z);
@@ -580,14 +1022,14 @@ pub(crate) fn draw_editor(
     }
 
     // --- Output visualizer (right side panel, like web editor) ---
-    egui::SidePanel::right("visualizer_panel")
-        .default_width(130.0)
+    let visualizer_panel = egui::SidePanel::right("visualizer_panel")
+        .default_width(state.layout.visualizer_width)
         .min_width(80.0)
         .max_width(250.0)
         .resizable(true)
         .frame(
             egui::Frame::NONE
-                .fill(colors::CRUST)
+                .fill(colors::crust())
                 .inner_margin(egui::Margin::symmetric(zs(4.0, z) as i8, zs(4.0, z) as i8)),
         )
         .show(ctx, |ui| {
@@ -595,19 +1037,20 @@ pub(crate) fn draw_editor(
             visualizer::draw(ui, &state.visualizer_state);
         });
 
-    egui::SidePanel::left("browser_panel")
-        .default_width(zs(200.0, z))
+    let browser_panel = egui::SidePanel::left("browser_panel")
+        .default_width(state.layout.browser_width)
         .min_width(160.0)
         .max_width(zs(400.0, z))
         .resizable(true)
         .frame(
             egui::Frame::NONE
-                .fill(colors::MANTLE)
+                .fill(colors::mantle())
                 .inner_margin(egui::Margin::symmetric(zs(10.0, z) as i8, zs(8.0, z) as i8)),
         )
         .show(ctx, |ui| {
             browser::draw(ui, state, z);
         });
+    record_layout(state, browser_panel.response.rect.width(), visualizer_panel.response.rect.width());
 
     // --- Central content: Slot rack or settings ---
     egui::CentralPanel::default().show(ctx, |ui| {
@@ -616,15 +1059,35 @@ pub(crate) fn draw_editor(
             .show(ui, |ui| {
                 match state.current_tab {
                     EditorTab::SlotRack => {
+                        macros::draw(ui, state, params, z);
                         slot_rack::draw(ui, state, z);
                     }
+                    EditorTab::Mixer => {
+                        mixer::draw(ui, state, z);
+                    }
                     EditorTab::Settings => {
                         draw_settings(ui, state, params);
                     }
+                    EditorTab::Log => {
+                        log_panel::draw(ui, state, z);
+                    }
+                    EditorTab::MidiMonitor => {
+                        midi_monitor::draw(ui, state, z);
+                    }
                 }
             });
     });
 
+    if state.current_tab == EditorTab::SlotRack {
+        focus::handle_keys(ctx, state, &nav_keys);
+        slot_clipboard::handle_shortcuts(ctx, state);
+    }
+
+    onboarding::draw(ctx, state);
+    download_check::draw(ctx, state);
+    slot_clipboard::draw(ctx, state);
+    quick_load::draw(ctx, state);
+
     // --- Resize corner (bottom-right) ---
     // Uses delta-based tracking to avoid CentralPanel margin coordinate issues.
     // Calls EguiState::set_requested_size() which feeds into nih_plug_egui's
@@ -635,6 +1098,25 @@ pub(crate) fn draw_editor(
     apply_zoom_change(ctx, state, prev_zoom);
 }
 
+/// Store the current layout in `PluginState` when it changes, so it is
+/// saved with the project (or the standalone's layout file).
+fn record_layout(state: &mut EditorState, browser_width: f32, visualizer_width: f32) {
+    let layout = EditorLayout {
+        zoom: state.zoom_level,
+        tab: state.current_tab,
+        piano_visible: state.piano_state.visible,
+        browser_width,
+        visualizer_width,
+    };
+    if layout == state.layout {
+        return;
+    }
+    state.layout = layout;
+    if let Ok(mut ps) = state.plugin_state.lock() {
+        ps.layout = layout;
+    }
+}
+
 /// Draw a draggable resize corner in the bottom-right of the window.
 /// Uses delta-based calculation: on drag start, records the pointer position
 /// and current window size. On drag move, computes new_size = start_size + delta.
@@ -703,18 +1185,197 @@ fn draw_resize_corner(ctx: &egui::Context, state: &mut EditorState) {
         });
 }
 
+/// Play/stop, position, time signature and loop controls in the standalone header.
+fn draw_transport_controls(ui: &mut egui::Ui, ds: &mut DeviceState, z: f32) {
+    use crate::standalone::transport::{self, TransportCommand, TIME_SIGNATURES};
+
+    let playing = ds.transport_display.playing();
+    let play_color = if playing { colors::green() } else { colors::subtext0() };
+    let play = ui
+        .selectable_label(playing, egui::RichText::new("▶").color(play_color).size(fs(14.0, z)))
+        .on_hover_text("Play");
+    accessibility::name_toggle(&play, "Play", playing);
+    if play.clicked() {
+        ds.pending_transport.push(TransportCommand::Play);
+    }
+    let stop = ui
+        .button(egui::RichText::new("■").color(colors::subtext0()).size(fs(14.0, z)))
+        .on_hover_text("Stop (twice to return to the start)");
+    accessibility::name(&stop, egui::WidgetType::Button, "Stop");
+    if stop.clicked() {
+        ds.pending_transport.push(TransportCommand::Stop);
+    }
+
+    let (numerator, denominator) = ds.time_signature;
+    ui.label(
+        egui::RichText::new(transport::format_position(
+            ds.transport_display.position_beats(),
+            numerator,
+            denominator,
+        ))
+        .color(colors::text())
+        .monospace()
+        .size(fs(12.0, z)),
+    );
+    if playing {
+        ui.ctx().request_repaint();
+    }
+
+    let mut time_signature = ds.time_signature;
+    egui::ComboBox::from_id_salt("time_signature")
+        .width(zs(48.0, z))
+        .selected_text(format!("{}/{}", numerator, denominator))
+        .show_ui(ui, |ui| {
+            for &(n, d) in TIME_SIGNATURES {
+                ui.selectable_value(&mut time_signature, (n, d), format!("{}/{}", n, d));
+            }
+        });
+    if time_signature != ds.time_signature {
+        ds.time_signature = time_signature;
+        ds.pending_transport.push(TransportCommand::TimeSignature {
+            numerator: time_signature.0,
+            denominator: time_signature.1,
+        });
+        // Loop points are in bars, so they move with the bar length
+        ds.pending_transport.push(TransportCommand::Loop(ds.loop_region));
+    }
+
+    let mut region = ds.loop_region;
+    let loop_color = if region.enabled { colors::blue() } else { colors::subtext0() };
+    if ui
+        .selectable_label(
+            region.enabled,
+            egui::RichText::new("Loop").color(loop_color).size(fs(12.0, z)),
+        )
+        .on_hover_text("Loop the bars below")
+        .clicked()
+    {
+        region.enabled = !region.enabled;
+    }
+    if region.enabled {
+        // Shown 1-based, stored 0-based
+        let mut start = region.start_bar + 1;
+        ui.add(egui::DragValue::new(&mut start).range(1..=999).prefix("bar "))
+            .on_hover_text("Loop start");
+        region.start_bar = start - 1;
+        ui.add(egui::DragValue::new(&mut region.length_bars).range(1..=64).suffix(" bars"))
+            .on_hover_text("Loop length");
+    }
+    if region != ds.loop_region {
+        ds.loop_region = region;
+        ds.pending_transport.push(TransportCommand::Loop(region));
+    }
+}
+
+/// Tempo, metronome and count-in controls in the standalone header.
+fn draw_metronome_controls(ui: &mut egui::Ui, ds: &mut DeviceState, z: f32) {
+    use crate::standalone::metronome::MAX_COUNT_IN_BARS;
+
+    ui.add(
+        egui::DragValue::new(&mut ds.tempo)
+            .range(20.0..=300.0)
+            .speed(0.5)
+            .fixed_decimals(1)
+            .suffix(" BPM"),
+    )
+    .on_hover_text("Tempo");
+
+    let mut settings = ds.metronome;
+    let click_color = if settings.enabled { colors::blue() } else { colors::subtext0() };
+    if ui
+        .selectable_label(
+            settings.enabled,
+            egui::RichText::new("Click").color(click_color).size(fs(12.0, z)),
+        )
+        .on_hover_text("Metronome while the transport plays")
+        .clicked()
+    {
+        settings.enabled = !settings.enabled;
+    }
+    ui.add(egui::Slider::new(&mut settings.volume, 0.0..=1.0).show_value(false))
+        .on_hover_text("Metronome volume");
+
+    let bars_label = |bars: u8| match bars {
+        0 => "No count-in".to_string(),
+        1 => "1 bar".to_string(),
+        n => format!("{} bars", n),
+    };
+    egui::ComboBox::from_id_salt("count_in_bars")
+        .selected_text(bars_label(settings.count_in_bars))
+        .show_ui(ui, |ui| {
+            for bars in 0..=MAX_COUNT_IN_BARS {
+                ui.selectable_value(&mut settings.count_in_bars, bars, bars_label(bars));
+            }
+        });
+
+    let remaining = ds.count_in_beats.load(Ordering::Relaxed);
+    if remaining > 0 {
+        ui.label(
+            egui::RichText::new(format!("Count-in {}", remaining))
+                .color(colors::yellow())
+                .strong()
+                .size(fs(12.0, z)),
+        );
+        ui.ctx().request_repaint();
+    } else if settings.count_in_bars > 0
+        && ui.button("Count-in").on_hover_text("Count in, then play").clicked()
+    {
+        ds.count_in_requested = true;
+    }
+
+    if settings != ds.metronome {
+        ds.metronome = settings;
+        ds.pending_metronome = Some(settings);
+    }
+}
+
+/// Output buffer size, safe mode and the latency read-out (standalone).
+fn draw_buffer_settings(ui: &mut egui::Ui, ds: &mut DeviceState) {
+    use crate::standalone::latency::BUFFER_SIZES;
+
+    let mut settings = ds.buffer_settings;
+    ui.horizontal(|ui| {
+        let label = ui.label(egui::RichText::new("Buffer:").color(colors::subtext0()));
+        let text = |frames: Option<u32>| frames.map_or("Device default".to_string(), |f| format!("{} frames", f));
+        egui::ComboBox::from_id_salt("audio_buffer_combo")
+            .selected_text(text(settings.frames))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut settings.frames, None, text(None));
+                for frames in BUFFER_SIZES {
+                    ui.selectable_value(&mut settings.frames, Some(frames), text(Some(frames)));
+                }
+            })
+            .response
+            .labelled_by(label.id);
+        ui.checkbox(&mut settings.safe_mode, "Safe mode")
+            .on_hover_text("Double the buffer and restart audio when the output drops out");
+    });
+    if settings != ds.buffer_settings {
+        ds.buffer_settings = settings;
+        ds.pending_buffer = Some(settings);
+    }
+
+    ui.label(
+        egui::RichText::new(format!("Output latency: {}", ds.latency.summary()))
+            .color(colors::subtext1())
+            .small(),
+    )
+    .on_hover_text("Device-reported output latency (or the buffer length), callback jitter and audio load");
+    ui.ctx().request_repaint_after(std::time::Duration::from_secs(1));
+}
+
 /// Draw the settings panel.
 fn draw_settings(
     ui: &mut egui::Ui,
     state: &mut EditorState,
     params: &dyn GlobalParams,
 ) {
-    ui.heading(egui::RichText::new("Settings").color(colors::TEXT));
+    ui.heading(egui::RichText::new("Settings").color(colors::text()));
     ui.separator();
 
     // --- Audio / MIDI device selection (standalone only) ---
     if let Some(ref mut ds) = state.device_state {
-        ui.label(egui::RichText::new("Audio Output:").color(colors::SUBTEXT0));
+        let audio_label = ui.label(egui::RichText::new("Audio Output:").color(colors::subtext0()));
         let current_name = ds.audio_device_names.get(ds.selected_audio_idx)
             .cloned()
             .unwrap_or_else(|| "(none)".into());
@@ -727,11 +1388,15 @@ fn draw_settings(
                         ds.pending_audio_switch = Some(name.clone());
                     }
                 }
-            });
+            })
+            .response
+            .labelled_by(audio_label.id);
+
+        draw_buffer_settings(ui, ds);
 
         ui.add_space(4.0);
 
-        ui.label(egui::RichText::new("MIDI Input:").color(colors::SUBTEXT0));
+        let midi_label = ui.label(egui::RichText::new("MIDI Input:").color(colors::subtext0()));
         let midi_current = ds.selected_midi_idx
             .and_then(|i| ds.midi_input_names.get(i).cloned())
             .unwrap_or_else(|| "None".into());
@@ -748,12 +1413,89 @@ fn draw_settings(
                         ds.pending_midi_switch = Some(name.clone());
                     }
                 }
-            });
+            })
+            .response
+            .labelled_by(midi_label.id);
 
         if ui.button("↻ Refresh Devices").clicked() {
             ds.needs_refresh = true;
         }
 
+        ui.add_space(4.0);
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Restore last session:").color(colors::subtext0()));
+            for mode in crate::standalone::session::RestoreMode::ALL {
+                ui.radio_value(&mut ds.restore_session, mode, mode.label());
+            }
+        });
+
+        ui.add_space(4.0);
+
+        ui.horizontal(|ui| {
+            let osc = &mut ds.osc_settings;
+            let mut changed = ui
+                .checkbox(&mut osc.enabled, "OSC remote control")
+                .on_hover_text("/slot/{n}/volume, pan, mute, solo, load, note_on, note_off")
+                .changed();
+            ui.label(egui::RichText::new("Port:").color(colors::subtext0()));
+            // Rebind once the user has finished editing the port
+            let port = ui.add(egui::DragValue::new(&mut osc.port).range(1024..=65535));
+            changed |= port.drag_stopped() || port.lost_focus();
+            if changed {
+                ds.pending_osc = Some(*osc);
+            }
+        });
+        if !ds.osc_status.is_empty() {
+            ui.label(egui::RichText::new(&ds.osc_status).color(colors::subtext1()).small());
+        }
+
+        if crate::standalone::link::available() {
+            ui.horizontal(|ui| {
+                if ui
+                    .checkbox(&mut ds.link_settings.enabled, "Ableton Link")
+                    .on_hover_text("Sync tempo, phase and play/stop with Link apps on the network")
+                    .changed()
+                {
+                    ds.pending_link = Some(ds.link_settings);
+                }
+                if ds.link_status.enabled() {
+                    let peers = ds.link_status.peers();
+                    let text = match peers {
+                        1 => "1 peer".to_string(),
+                        n => format!("{} peers", n),
+                    };
+                    ui.label(egui::RichText::new(text).color(colors::subtext1()).small());
+                    // Peers come and go without any UI input
+                    ui.ctx().request_repaint_after(std::time::Duration::from_secs(1));
+                }
+            });
+        }
+
+        ui.separator();
+    }
+
+    // --- Theme ---
+    theme::draw_settings(ui, &mut state.theme);
+    accessibility::draw_settings(ui, &mut state.accessibility);
+    ui.separator();
+
+    // --- Editor front-end ---
+    let available = frontend::EditorFrontend::available();
+    if available.len() > 1 {
+        let mut choice = frontend::current();
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Editor:").color(colors::subtext0()));
+            for &option in available {
+                if ui.radio_value(&mut choice, option, option.label()).clicked() {
+                    if let Err(e) = frontend::set(choice) {
+                        nih_plug::debug::nih_log!("[Settings] {}", e);
+                    }
+                }
+            }
+        })
+        .response
+        .on_hover_text("Takes effect the next time the editor (or standalone app) is opened");
         ui.separator();
     }
 
@@ -767,45 +1509,147 @@ fn draw_settings(
 
     ui.separator();
 
+    network::draw_settings(ui, state);
+    network::draw_mirror_settings(ui, state);
+    network::draw_auth_settings(ui, state);
+
+    ui.separator();
+
+    midi_rules::draw_settings(ui, state);
+
+    ui.separator();
+
+    patch_export::draw_settings(ui, state);
+
+    ui.separator();
+
+    local_library::draw_settings(ui, state);
+
+    ui.separator();
+
+    web_bridge::draw_settings(ui, state);
+
+    ui.separator();
+
     // Master Volume slider
     ui.horizontal(|ui| {
-        ui.label(
+        let label = ui.label(
             egui::RichText::new("Master Volume:")
-                .color(colors::SUBTEXT0),
+                .color(colors::subtext0()),
         );
         let vol_db = nih_plug::util::gain_to_db(params.master_volume_gain());
         let mut vol_db_val = vol_db;
         let slider = egui::Slider::new(&mut vol_db_val, -60.0..=6.0)
             .suffix(" dB")
             .text("");
-        if ui.add(slider).changed() {
+        if ui.add(slider).labelled_by(label.id).changed() {
             params.set_master_volume_gain(nih_plug::util::db_to_gain(vol_db_val));
         }
     });
 
+    // Master output utilities
+    ui.horizontal(|ui| {
+        ui.label(
+            egui::RichText::new("Output:")
+                .color(colors::subtext0()),
+        );
+        let mut output = params.master_output();
+        let mut changed = false;
+        changed |= ui
+            .checkbox(&mut output.mono, "Mono")
+            .on_hover_text("Fold the master output to mono to check the mix")
+            .changed();
+        changed |= ui.checkbox(&mut output.swap, "Swap L/R").changed();
+        changed |= ui
+            .checkbox(&mut output.invert_left, "Ø L")
+            .on_hover_text("Invert the polarity of the left channel")
+            .changed();
+        changed |= ui
+            .checkbox(&mut output.invert_right, "Ø R")
+            .on_hover_text("Invert the polarity of the right channel")
+            .changed();
+        if changed {
+            params.set_master_output(output);
+        }
+    });
+
     ui.separator();
 
     // Max Voices slider
     ui.horizontal(|ui| {
-        ui.label(
+        let label = ui.label(
             egui::RichText::new("Max Voices:")
-                .color(colors::SUBTEXT0),
+                .color(colors::subtext0()),
         );
         let mut voices = params.max_voices();
         let slider = egui::Slider::new(&mut voices, 8..=1024)
             .text("");
-        if ui.add(slider).changed() {
+        if ui
+            .add(slider)
+            .labelled_by(label.id)
+            .on_hover_text("Ceiling for each slot's Voices setting")
+            .changed()
+        {
             params.set_max_voices(voices);
         }
     });
 
     ui.separator();
 
+    // Parallel slot rendering
+    let mut parallel = state.plugin_state.lock().map(|ps| ps.parallel_render).unwrap_or(false);
+    if ui
+        .checkbox(&mut parallel, "Render slots in parallel")
+        .on_hover_text("Spread slot rendering across CPU cores; helps with many heavy slots")
+        .changed()
+    {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            ps.parallel_render = parallel;
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetParallelRender { enabled: parallel });
+    }
+
+    // Reset on transport stop
+    let mut reset_on_stop = state.plugin_state.lock().map(|ps| ps.reset_on_stop).unwrap_or(true);
+    if ui
+        .checkbox(&mut reset_on_stop, "Reset slots when the transport stops")
+        .on_hover_text("Release held and sustained notes and stop runners when playback stops")
+        .changed()
+    {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            ps.reset_on_stop = reset_on_stop;
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetResetOnStop { enabled: reset_on_stop });
+    }
+
+    // GM mode
+    let mut gm_mode = state.plugin_state.lock().map(|ps| ps.gm_mode).unwrap_or(false);
+    if ui
+        .checkbox(&mut gm_mode, "GM mode")
+        .on_hover_text(format!(
+            "Replace the rack with 16 slots on MIDI channels 1–16 (drums on 10) that follow program \
+             and bank changes with {} presets, for playing General MIDI files",
+            crate::midi::gm::GM_LIBRARY
+        ))
+        .changed()
+    {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if gm_mode {
+                crate::midi::gm::configure(&mut ps);
+            } else {
+                ps.gm_mode = false;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetGmMode { enabled: gm_mode });
+    }
+
+    ui.separator();
+
     // Pitch Bend Range
     ui.horizontal(|ui| {
         ui.label(
             egui::RichText::new("Pitch Bend Range:")
-                .color(colors::SUBTEXT0),
+                .color(colors::subtext0()),
         );
         let mut bend = params.pitch_bend_range();
         let slider = egui::Slider::new(&mut bend, 1..=48)
@@ -819,13 +1663,13 @@ fn draw_settings(
     ui.separator();
 
     ui.horizontal(|ui| {
-        ui.label(egui::RichText::new("License:").color(colors::SUBTEXT0));
-        ui.label(egui::RichText::new("GPL-3.0 — Free & Open Source").color(colors::GREEN));
+        ui.label(egui::RichText::new("License:").color(colors::subtext0()));
+        ui.label(egui::RichText::new("GPL-3.0 — Free & Open Source").color(colors::green()));
     });
 
     ui.horizontal(|ui| {
-        ui.label(egui::RichText::new("Version:").color(colors::SUBTEXT0));
-        ui.label(egui::RichText::new(env!("CARGO_PKG_VERSION")).color(colors::TEXT));
+        ui.label(egui::RichText::new("Version:").color(colors::subtext0()));
+        ui.label(egui::RichText::new(env!("CARGO_PKG_VERSION")).color(colors::text()));
     });
 
     ui.separator();
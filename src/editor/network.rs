@@ -0,0 +1,317 @@
+//! Connectivity follow-up in the editor: probe while offline, re-attempt
+//! failed library fetches once the network is back, and re-check library
+//! indexes on the schedule in the network settings. Also the network,
+//! mirror and private library credential sections of Settings.
+
+use std::time::{Duration, Instant};
+
+use nih_plug_egui::egui;
+
+use super::{EditorState, colors};
+use crate::jobs::JobPriority;
+use crate::net::auth::{self, AuthScheme, LibraryAuth};
+use crate::net::mirrors;
+use crate::net::{
+    Connectivity, HttpClient, NetworkSettings, RetryPolicy, ValidatorStore, connectivity, settings,
+};
+use crate::preset::library_index;
+use crate::preset::manager::LibraryStatus;
+use crate::preset::revalidate;
+
+/// Interval between reachability probes while offline.
+pub const PROBE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Library refresh schedules offered in Settings, in hours (0 = startup only).
+const REFRESH_CHOICES: [(u64, &str); 4] = [
+    (0, "At startup"),
+    (1, "Every hour"),
+    (6, "Every 6 hours"),
+    (24, "Daily"),
+];
+
+/// UI-side connectivity bookkeeping.
+#[derive(Default)]
+pub struct NetworkState {
+    /// Reconnect count last acted on.
+    seen_reconnects: u64,
+    last_probe: Option<Instant>,
+    /// Network settings being edited (None until the settings tab is shown).
+    settings_draft: Option<NetworkSettings>,
+    /// Result of the last "Apply".
+    settings_error: Option<String>,
+    /// Refresh interval of the applied settings, read when they change.
+    refresh_interval: Option<Option<Duration>>,
+    /// Private library credentials being entered.
+    auth_draft: AuthDraft,
+    /// Result of the last credential change.
+    auth_error: Option<String>,
+    /// Mirrors of the current library being edited, one per line (None
+    /// until shown).
+    mirror_draft: Option<String>,
+    /// Result of the last mirror change.
+    mirror_error: Option<String>,
+}
+
+/// The "Private libraries" form.
+#[derive(Default)]
+struct AuthDraft {
+    base_url: String,
+    basic: bool,
+    username: String,
+    secret: String,
+}
+
+/// Called once per frame.
+pub fn poll(state: &mut EditorState) {
+    let monitor = connectivity::global();
+
+    // Back online: retry libraries that failed and revalidate the rest
+    let reconnects = monitor.reconnects();
+    if reconnects != state.network.seen_reconnects {
+        state.network.seen_reconnects = reconnects;
+        nih_plug::debug::nih_log!(
+            "[Network] Back online, retrying {} failed libraries",
+            failed_libraries(state).len()
+        );
+        retry_failed_libraries(state);
+        revalidate::spawn_revalidation(&state.jobs, state.preset_manager.clone());
+        return;
+    }
+
+    // Scheduled re-check of every library
+    let interval = *state.network.refresh_interval.get_or_insert_with(|| settings::current().refresh_interval());
+    if let Some(interval) = interval {
+        if revalidate::last_pass().is_none_or(|t| t.elapsed() >= interval) {
+            nih_plug::debug::nih_log!("[Network] Scheduled library refresh");
+            revalidate::spawn_revalidation(&state.jobs, state.preset_manager.clone());
+        }
+    }
+
+    // Probe while offline, or while a library fetch has failed (it may have
+    // failed because the network dropped)
+    let suspect = monitor.state() == Connectivity::Offline || !failed_libraries(state).is_empty();
+    let due = state
+        .network
+        .last_probe
+        .is_none_or(|t| t.elapsed() >= PROBE_INTERVAL);
+    if suspect && due {
+        state.network.last_probe = Some(Instant::now());
+        let url = match state.preset_manager.lock() {
+            Ok(pm) => format!("{}/index.json", pm.base_url),
+            Err(_) => return,
+        };
+        state.jobs.submit(JobPriority::Background, move |ctx| {
+            let client = HttpClient::new(ValidatorStore::in_memory()).with_retry(RetryPolicy::NONE);
+            let _ = ctx.block_on(client.probe(&url));
+        });
+    }
+}
+
+/// Names of libraries whose index fetch failed.
+fn failed_libraries(state: &EditorState) -> Vec<String> {
+    match state.preset_manager.lock() {
+        Ok(pm) => pm
+            .libraries
+            .iter()
+            .filter(|l| matches!(l.status, LibraryStatus::Error(_)))
+            .map(|l| l.name.clone())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Fetch the libraries whose index fetch failed again.
+fn retry_failed_libraries(state: &EditorState) {
+    for name in failed_libraries(state) {
+        library_index::start_library_fetch(&state.jobs, state.preset_manager.clone(), name);
+    }
+}
+
+/// Private library credentials for the Settings tab: the configured
+/// servers, and a form to add one (see `net::auth`).
+pub fn draw_auth_settings(ui: &mut egui::Ui, state: &mut EditorState) {
+    ui.label(egui::RichText::new("Private libraries").color(colors::subtext0()));
+
+    let mut removed = None;
+    for (library, has_secret) in auth::configured() {
+        ui.horizontal(|ui| {
+            ui.label(&library.base_url);
+            let detail = match &library.scheme {
+                AuthScheme::Bearer => library.scheme.label().to_string(),
+                AuthScheme::Basic { username } => format!("{} ({})", library.scheme.label(), username),
+            };
+            ui.label(egui::RichText::new(detail).color(colors::overlay0()).small());
+            if !has_secret {
+                ui.label(egui::RichText::new("\u{26a0} not in keyring").color(colors::yellow()).small())
+                    .on_hover_text("The keyring didn't return the secret; enter it again");
+            }
+            if ui.small_button("Remove").clicked() {
+                removed = Some(library.base_url.clone());
+            }
+        });
+    }
+    if let Some(base_url) = removed {
+        state.network.auth_error = auth::remove(&base_url).err();
+    }
+
+    let default_url = state.preset_manager.lock().map(|pm| pm.base_url.clone()).unwrap_or_default();
+    let draft = &mut state.network.auth_draft;
+    let mut save = false;
+    egui::Grid::new("library_auth_grid").num_columns(2).show(ui, |ui| {
+        ui.label("Library URL:");
+        ui.add(egui::TextEdit::singleline(&mut draft.base_url).hint_text(default_url.as_str()));
+        ui.end_row();
+
+        ui.label("Sign in with:");
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut draft.basic, false, "Bearer token");
+            ui.radio_value(&mut draft.basic, true, "User name and password");
+        });
+        ui.end_row();
+
+        if draft.basic {
+            ui.label("User name:");
+            ui.text_edit_singleline(&mut draft.username);
+            ui.end_row();
+        }
+
+        ui.label(if draft.basic { "Password:" } else { "Token:" });
+        let secret = ui.add(egui::TextEdit::singleline(&mut draft.secret).password(true));
+        save = secret.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        ui.end_row();
+    });
+
+    ui.horizontal(|ui| {
+        save |= ui
+            .add_enabled(!draft.secret.is_empty(), egui::Button::new("Save"))
+            .on_hover_text("Store the secret in the system keyring and use it for every request to this library")
+            .clicked();
+        if let Some(ref err) = state.network.auth_error {
+            ui.label(egui::RichText::new(err).color(colors::red()));
+        }
+    });
+
+    if save {
+        let draft = std::mem::take(&mut state.network.auth_draft);
+        let base_url = if draft.base_url.trim().is_empty() { default_url } else { draft.base_url };
+        let scheme = if draft.basic { AuthScheme::Basic { username: draft.username } } else { AuthScheme::Bearer };
+        state.network.auth_error = auth::set(LibraryAuth { base_url, scheme }, &draft.secret).err();
+        if state.network.auth_error.is_none() {
+            retry_failed_libraries(state);
+        }
+    }
+}
+
+/// Mirrors of the current library URL for the Settings tab, with how
+/// each host has been doing (see `net::mirrors`).
+pub fn draw_mirror_settings(ui: &mut egui::Ui, state: &mut EditorState) {
+    let base_url = state.preset_manager.lock().map(|pm| pm.base_url.clone()).unwrap_or_default();
+    let Ok(mut mirrors) = mirrors::global().lock() else { return };
+    let network = &mut state.network;
+    let draft = network.mirror_draft.get_or_insert_with(|| mirrors.mirrors_of(&base_url).join("\n"));
+
+    ui.label(egui::RichText::new("Library mirrors").color(colors::subtext0()))
+        .on_hover_text("Other addresses serving this library, tried when the main host is unreachable or slow");
+    ui.add(
+        egui::TextEdit::multiline(draft)
+            .desired_rows(2)
+            .desired_width(f32::INFINITY)
+            .hint_text("One URL per line, e.g. http://[2001:db8::1]/songwalker-library"),
+    );
+    let listed: Vec<String> = draft.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect();
+    ui.horizontal_wrapped(|ui| {
+        for host in std::iter::once(&base_url).chain(&listed) {
+            let host_name = host.trim_start_matches("https://").trim_start_matches("http://");
+            let label = format!("{}: {}", host_name, mirrors.health_label(host));
+            ui.label(egui::RichText::new(label).color(colors::overlay0()).small());
+        }
+    });
+    ui.horizontal(|ui| {
+        let changed = listed != mirrors.mirrors_of(&base_url);
+        if ui.add_enabled(changed, egui::Button::new("Apply mirrors")).clicked() {
+            network.mirror_error = mirrors.set_mirrors(&base_url, listed.clone()).err();
+        }
+        if let Some(ref err) = network.mirror_error {
+            ui.label(egui::RichText::new(err).color(colors::red()));
+        }
+    });
+}
+
+/// Proxy / CA certificate / timeout / download size fields for the Settings tab.
+pub fn draw_settings(ui: &mut egui::Ui, state: &mut EditorState) {
+    let network = &mut state.network;
+    let draft = network.settings_draft.get_or_insert_with(settings::current);
+
+    ui.label(egui::RichText::new("Network").color(colors::subtext0()));
+    egui::Grid::new("network_settings_grid")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("HTTP proxy:");
+            ui.add(egui::TextEdit::singleline(&mut draft.http_proxy).hint_text("http://host:port"));
+            ui.end_row();
+
+            ui.label("HTTPS proxy:");
+            ui.add(
+                egui::TextEdit::singleline(&mut draft.https_proxy).hint_text("http://host:port"),
+            );
+            ui.end_row();
+
+            ui.label("CA certificate:");
+            ui.add(
+                egui::TextEdit::singleline(&mut draft.ca_cert_path).hint_text("/path/to/ca.pem"),
+            );
+            ui.end_row();
+
+            ui.label("Timeout:");
+            ui.add(
+                egui::DragValue::new(&mut draft.timeout_secs)
+                    .range(1..=600)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+
+            ui.label("Warn above:")
+                .on_hover_text("Ask before loading a preset whose download is larger (0 = never)");
+            ui.add(egui::DragValue::new(&mut draft.warn_download_mb).range(0..=100_000).suffix(" MB"));
+            ui.end_row();
+
+            ui.label("Cache limit:")
+                .on_hover_text("Ask before a download would grow the cache past this (0 = no limit)");
+            ui.add(egui::DragValue::new(&mut draft.cache_limit_mb).range(0..=1_000_000).suffix(" MB"));
+            ui.end_row();
+
+            ui.label("Check libraries:")
+                .on_hover_text("How often to look for updated library indexes while the editor is open");
+            ui.horizontal(|ui| {
+                let selected = REFRESH_CHOICES
+                    .iter()
+                    .find(|(hours, _)| *hours == draft.refresh_hours)
+                    .map_or_else(|| format!("Every {} hours", draft.refresh_hours), |(_, label)| label.to_string());
+                egui::ComboBox::from_id_salt("library_refresh_schedule")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        for (hours, label) in REFRESH_CHOICES {
+                            ui.selectable_value(&mut draft.refresh_hours, hours, label);
+                        }
+                    });
+                if ui.button("Check now").on_hover_text("Look for updated library indexes now").clicked() {
+                    revalidate::spawn_revalidation(&state.jobs, state.preset_manager.clone());
+                }
+            });
+            ui.end_row();
+        });
+
+    ui.horizontal(|ui| {
+        let changed = *draft != settings::current();
+        if ui
+            .add_enabled(changed, egui::Button::new("Apply"))
+            .clicked()
+        {
+            network.settings_error = settings::update(draft.clone()).err();
+            network.refresh_interval = None;
+        }
+        if let Some(ref err) = network.settings_error {
+            ui.label(egui::RichText::new(err).color(colors::red()));
+        }
+    });
+}
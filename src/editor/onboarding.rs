@@ -0,0 +1,283 @@
+//! First-run welcome: starter library download and a demo rack.
+//!
+//! When nothing has been cached yet (no root index on disk) and the rack is
+//! empty, a welcome window offers to download a small General MIDI starter
+//! set in the background and to load a demo rack whose piano sounds as soon
+//! as it arrives. The window is not shown again once it has been answered;
+//! that choice is stored per machine in `onboarding.json`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use nih_plug_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use super::loads::LoadTarget;
+use super::{colors, EditorState, PlayNote};
+use crate::jobs::{JobHandle, JobPool, JobPriority};
+use crate::preset::cache::DiskCache;
+use crate::preset::fetch::PresetFetcher;
+use crate::preset::manager::PresetManager;
+use crate::programs::FactoryProgram;
+use crate::view_model;
+
+/// Presets fetched into the cache by the starter download.
+pub const STARTER_PRESETS: &[&str] = &[
+    "FluidR3_GM/Acoustic Grand Piano",
+    "FluidR3_GM/Electric Piano 1",
+    "FluidR3_GM/Drawbar Organ",
+    "FluidR3_GM/Acoustic Guitar (nylon)",
+    "FluidR3_GM/Acoustic Bass",
+    "FluidR3_GM/String Ensemble 1",
+    "FluidR3_GM/Synth Strings 1",
+    "FluidR3_GM/Trumpet",
+    "FluidR3_GM/Flute",
+    "FluidR3_GM/Pad 2 (warm)",
+    "FluidR3_GM/Standard Kit/Kick",
+    "FluidR3_GM/Standard Kit/Snare",
+    "FluidR3_GM/Standard Kit/Closed Hi-Hat",
+];
+
+/// Rack loaded by "Get started". Its first slot is played once loaded.
+const DEMO_PROGRAM: FactoryProgram = FactoryProgram::PianoStrings;
+
+/// Note played when the demo piano arrives.
+const DEMO_NOTE: u8 = 60;
+
+/// Saved onboarding state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OnboardingSettings {
+    /// The welcome window was answered (started or skipped).
+    pub completed: bool,
+}
+
+/// The saved settings (defaults if unset).
+pub fn load_settings() -> OnboardingSettings {
+    crate::config::load_json("onboarding.json")
+}
+
+/// Save the settings.
+pub fn save_settings(settings: &OnboardingSettings) -> Result<(), String> {
+    crate::config::save_json("onboarding.json", settings)
+}
+
+/// Whether to show the welcome window.
+pub fn should_offer(settings: &OnboardingSettings, rack_empty: bool, cache_empty: bool) -> bool {
+    !settings.completed && rack_empty && cache_empty
+}
+
+/// Progress of the starter download, shared with its job.
+#[derive(Default)]
+pub struct StarterProgress {
+    pub done: AtomicUsize,
+    pub failed: AtomicUsize,
+}
+
+/// A running (or finished) starter download.
+struct StarterDownload {
+    progress: Arc<StarterProgress>,
+    handle: JobHandle,
+}
+
+impl StarterDownload {
+    fn finished(&self) -> bool {
+        self.progress.done.load(Ordering::Relaxed) >= STARTER_PRESETS.len()
+    }
+}
+
+/// Welcome window state.
+#[derive(Default)]
+pub struct OnboardingState {
+    /// First-run check done (once per editor).
+    checked: bool,
+    /// Window shown.
+    open: bool,
+    download: Option<StarterDownload>,
+    /// Download outcome already reported in the status bar.
+    reported: bool,
+}
+
+/// Queue the starter download. Each preset (descriptor and samples) goes
+/// through the loader, which leaves it in the disk cache.
+pub fn spawn_starter_download(
+    jobs: &JobPool,
+    manager: Arc<Mutex<PresetManager>>,
+    progress: Arc<StarterProgress>,
+) -> JobHandle {
+    jobs.submit(JobPriority::Background, move |ctx| {
+        for preset_id in STARTER_PRESETS {
+            if ctx.is_cancelled() {
+                return;
+            }
+            let Some((library, path)) = preset_id.split_once('/') else {
+                continue;
+            };
+            let (base_url, slug) = {
+                let Ok(mgr) = manager.lock() else { return };
+                let slug = mgr
+                    .libraries
+                    .iter()
+                    .find(|l| l.name == library)
+                    .map(|l| l.slug.clone())
+                    .unwrap_or_else(|| library.to_string());
+                (mgr.base_url.clone(), slug)
+            };
+            let fetcher = PresetFetcher::new(base_url);
+            let Some(result) = ctx.block_on(fetcher.load_preset(&slug, path)) else {
+                return;
+            };
+            if let Err(e) = result {
+                nih_plug::debug::nih_log!("[Onboarding] Failed to fetch {}: {}", preset_id, e);
+                progress.failed.fetch_add(1, Ordering::Relaxed);
+            }
+            progress.done.fetch_add(1, Ordering::Relaxed);
+        }
+    })
+}
+
+/// Fill the rack with the demo program and load it, playing the first
+/// slot once it arrives.
+fn load_demo_rack(state: &mut EditorState) {
+    for (i, (name, preset_id)) in DEMO_PROGRAM.slots().iter().enumerate() {
+        let Some((library, path)) = preset_id.split_once('/') else {
+            continue;
+        };
+        let Some(slot_index) = view_model::assign_preset(&state.plugin_state, library, name, path) else {
+            continue;
+        };
+        let play_note = (i == 0).then_some(PlayNote::new(DEMO_NOTE));
+        state.loads.request(
+            &state.jobs,
+            &state.preset_manager,
+            LoadTarget::Slot(slot_index),
+            library,
+            path,
+            slot_index,
+            play_note,
+        );
+    }
+    state.slot_rack_state.selected_slot = 0;
+    state.piano_state.visible = true;
+    if let Ok(mut st) = state.status_text.lock() {
+        *st = "Loading demo rack\u{2026} play the keyboard below once it's ready".to_string();
+    }
+}
+
+/// Remember that the welcome window was answered.
+fn mark_completed() {
+    let settings = OnboardingSettings { completed: true };
+    if let Err(e) = save_settings(&settings) {
+        nih_plug::debug::nih_log!("[Onboarding] {}", e);
+    }
+}
+
+/// Draw the welcome window if this is a first run.
+pub fn draw(ctx: &egui::Context, state: &mut EditorState) {
+    if !state.onboarding.checked {
+        state.onboarding.checked = true;
+        let rack_empty = state.plugin_state.lock().map(|ps| ps.slot_configs.is_empty()).unwrap_or(false);
+        let cache_empty = DiskCache::new().read_root_index().is_none();
+        state.onboarding.open = should_offer(&load_settings(), rack_empty, cache_empty);
+    }
+    report_download(state);
+    if !state.onboarding.open {
+        return;
+    }
+
+    let mut start = false;
+    let mut skip = false;
+    egui::Window::new("Welcome to SongWalker")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.set_max_width(340.0);
+            ui.label("Presets are listed in the browser on the left. Click + on one to add it to the rack, or \u{25b6} to hear it first.");
+            ui.add_space(4.0);
+            ui.label(
+                egui::RichText::new(format!(
+                    "Get started downloads a General MIDI starter set ({} presets) for offline use and loads a piano and strings rack.",
+                    STARTER_PRESETS.len()
+                ))
+                .color(colors::subtext0()),
+            );
+            ui.add_space(6.0);
+            match &state.onboarding.download {
+                Some(download) => {
+                    let done = download.progress.done.load(Ordering::Relaxed);
+                    let total = STARTER_PRESETS.len();
+                    ui.add(
+                        egui::ProgressBar::new(done as f32 / total as f32)
+                            .text(format!("{} / {} presets", done, total)),
+                    );
+                    let label = if download.finished() { "Done" } else { "Continue in background" };
+                    if ui.button(label).clicked() {
+                        state.onboarding.open = false;
+                    }
+                }
+                None => {
+                    ui.horizontal(|ui| {
+                        start = ui.button("Get started").clicked();
+                        skip = ui.button("Skip").clicked();
+                    });
+                }
+            }
+        });
+
+    if start {
+        let progress = Arc::new(StarterProgress::default());
+        let handle = spawn_starter_download(&state.jobs, state.preset_manager.clone(), progress.clone());
+        state.onboarding.download = Some(StarterDownload { progress, handle });
+        load_demo_rack(state);
+        // The window stays to show progress, but won't come back
+        mark_completed();
+    } else if skip {
+        state.onboarding.open = false;
+        mark_completed();
+    }
+}
+
+/// Put the download outcome in the status bar once it finishes.
+fn report_download(state: &mut EditorState) {
+    let onboarding = &mut state.onboarding;
+    let Some(download) = &onboarding.download else { return };
+    if onboarding.reported || !download.finished() || download.handle.is_cancelled() {
+        return;
+    }
+    onboarding.reported = true;
+    let failed = download.progress.failed.load(Ordering::Relaxed);
+    let message = if failed == 0 {
+        "Starter set downloaded".to_string()
+    } else {
+        format!("\u{26a0} Starter set: {} of {} presets failed to download", failed, STARTER_PRESETS.len())
+    };
+    if let Ok(mut st) = state.status_text.lock() {
+        *st = message;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offered_only_on_a_fresh_install() {
+        let fresh = OnboardingSettings::default();
+        assert!(should_offer(&fresh, true, true));
+        assert!(!should_offer(&fresh, false, true));
+        assert!(!should_offer(&fresh, true, false));
+        assert!(!should_offer(&OnboardingSettings { completed: true }, true, true));
+    }
+
+    #[test]
+    fn test_starter_presets_are_library_paths() {
+        for id in STARTER_PRESETS {
+            let (library, path) = id.split_once('/').unwrap();
+            assert!(!library.is_empty() && !path.is_empty(), "{}", id);
+        }
+        for (_, id) in DEMO_PROGRAM.slots() {
+            assert!(STARTER_PRESETS.contains(id), "{}", id);
+        }
+    }
+}
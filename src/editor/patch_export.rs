@@ -0,0 +1,83 @@
+//! "Export patch list…" in the Settings tab.
+
+use nih_plug_egui::egui;
+
+use super::{EditorState, colors};
+use crate::preset::manager::LibraryStatus;
+use crate::preset::patchlist::{self, PatchListFormat};
+
+#[derive(Default)]
+pub struct PatchExportState {
+    library: Option<String>,
+    format: PatchListFormat,
+    /// Outcome of the last export.
+    result: Option<Result<String, String>>,
+}
+
+pub fn draw_settings(ui: &mut egui::Ui, state: &mut EditorState) {
+    let libraries: Vec<String> = state
+        .preset_manager
+        .lock()
+        .map(|pm| {
+            pm.libraries
+                .iter()
+                .filter(|l| l.status == LibraryStatus::Loaded)
+                .map(|l| l.name.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    let export = &mut state.patch_export;
+    if export
+        .library
+        .as_ref()
+        .is_none_or(|l| !libraries.contains(l))
+    {
+        export.library = libraries.first().cloned();
+    }
+
+    ui.label(egui::RichText::new("Patch List").color(colors::subtext0()))
+        .on_hover_text("Named GM programs for your DAW's patch browser");
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt("patch_export_library")
+            .selected_text(export.library.as_deref().unwrap_or("(no library loaded)"))
+            .show_ui(ui, |ui| {
+                for name in &libraries {
+                    ui.selectable_value(&mut export.library, Some(name.clone()), name);
+                }
+            });
+        egui::ComboBox::from_id_salt("patch_export_format")
+            .selected_text(export.format.label())
+            .show_ui(ui, |ui| {
+                for format in PatchListFormat::ALL {
+                    ui.selectable_value(&mut export.format, format, format.label());
+                }
+            });
+
+        let button = ui.add_enabled(
+            export.library.is_some(),
+            egui::Button::new("Export patch list…"),
+        );
+        if button.clicked() {
+            if let Some(library) = &export.library {
+                export.result = Some(match state.preset_manager.lock() {
+                    Ok(pm) => {
+                        patchlist::export(&pm, library, export.format).map(|(path, count)| {
+                            format!("{} programs written to {}", count, path.display())
+                        })
+                    }
+                    Err(_) => Err("Preset manager unavailable".into()),
+                });
+            }
+        }
+    });
+
+    match &export.result {
+        Some(Ok(message)) => {
+            ui.label(egui::RichText::new(message).color(colors::green()).small());
+        }
+        Some(Err(e)) => {
+            ui.label(egui::RichText::new(e).color(colors::red()).small());
+        }
+        None => {}
+    }
+}
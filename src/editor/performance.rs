@@ -0,0 +1,109 @@
+//! Performance mode: a compact standalone layout with just the piano, a
+//! slot selector and the output meters, small enough to float over a DAW
+//! or notation software while tracking. The standalone app resizes the
+//! window and applies always-on-top (see `DeviceState`).
+
+use nih_plug_egui::egui;
+
+use super::{DeviceState, EditorEvent, EditorState, colors, fs, piano, visualizer, zs};
+
+/// Header toggles for the mini window and always-on-top.
+pub fn draw_window_controls(ui: &mut egui::Ui, ds: &mut DeviceState, z: f32) {
+    if ui
+        .selectable_label(ds.performance_mode, egui::RichText::new("Mini").size(fs(12.0, z)))
+        .on_hover_text("Performance mode: just the piano, slot selector and meters")
+        .clicked()
+    {
+        ds.performance_mode = !ds.performance_mode;
+    }
+    draw_on_top_toggle(ui, ds, z);
+}
+
+fn draw_on_top_toggle(ui: &mut egui::Ui, ds: &mut DeviceState, z: f32) {
+    let color = if ds.always_on_top { colors::blue() } else { colors::subtext0() };
+    if ui
+        .selectable_label(ds.always_on_top, egui::RichText::new("On top").color(color).size(fs(12.0, z)))
+        .on_hover_text("Keep the window above other applications")
+        .clicked()
+    {
+        ds.always_on_top = !ds.always_on_top;
+    }
+}
+
+/// Draw the performance layout in place of the full editor.
+pub fn draw(ctx: &egui::Context, state: &mut EditorState, z: f32) {
+    let slot_names: Vec<String> = state
+        .plugin_state
+        .lock()
+        .map(|ps| ps.slot_configs.iter().map(|c| c.display_name()).collect())
+        .unwrap_or_default();
+
+    egui::TopBottomPanel::top("performance_bar")
+        .frame(
+            egui::Frame::NONE
+                .fill(colors::base())
+                .inner_margin(egui::Margin::symmetric(zs(8.0, z) as i8, zs(4.0, z) as i8)),
+        )
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let selected = &mut state.slot_rack_state.selected_slot;
+                let slot_text = |slot: usize| match slot_names.get(slot) {
+                    Some(name) => format!("{}: {}", slot + 1, name),
+                    None => format!("{}: (none)", slot + 1),
+                };
+                egui::ComboBox::from_id_salt("performance_slot")
+                    .width(zs(160.0, z))
+                    .selected_text(slot_text(*selected))
+                    .show_ui(ui, |ui| {
+                        for slot in 0..slot_names.len() {
+                            ui.selectable_value(selected, slot, slot_text(slot));
+                        }
+                    })
+                    .response
+                    .on_hover_text("Slot the piano plays");
+
+                if ui
+                    .button(egui::RichText::new("Panic").color(colors::red()).strong().size(fs(12.0, z)))
+                    .on_hover_text("Silence all slots")
+                    .clicked()
+                {
+                    state.browser_state.preview.stop_all(&state.event_tx);
+                    let _ = state.event_tx.try_send(EditorEvent::Panic);
+                }
+
+                let Some(ref mut ds) = state.device_state else { return };
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui
+                        .button(egui::RichText::new("Full view").size(fs(12.0, z)))
+                        .on_hover_text("Back to the full editor")
+                        .clicked()
+                    {
+                        ds.performance_mode = false;
+                    }
+                    draw_on_top_toggle(ui, ds, z);
+                });
+            });
+        });
+
+    egui::SidePanel::right("performance_meters")
+        .exact_width(zs(90.0, z))
+        .resizable(false)
+        .frame(
+            egui::Frame::NONE
+                .fill(colors::crust())
+                .inner_margin(egui::Margin::symmetric(zs(4.0, z) as i8, zs(4.0, z) as i8)),
+        )
+        .show(ctx, |ui| {
+            visualizer::draw(ui, &state.visualizer_state);
+        });
+
+    egui::CentralPanel::default()
+        .frame(
+            egui::Frame::NONE
+                .fill(colors::crust())
+                .inner_margin(egui::Margin::symmetric(zs(8.0, z) as i8, zs(4.0, z) as i8)),
+        )
+        .show(ctx, |ui| {
+            piano::draw(ui, state, z);
+        });
+}
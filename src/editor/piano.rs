@@ -2,15 +2,21 @@
 //!
 //! Renders a 2-octave piano (C3–B4) at the bottom of the editor.
 //! When a key is pressed, a NoteOn event is sent via crossbeam channel
-//! to the audio thread, targeting the currently selected slot.
+//! to the audio thread, targeting the currently selected slot. A bend
+//! wheel and a mod wheel beside the keys show the slot's incoming values
+//! and send bend or CC1 to it when dragged.
 
 use nih_plug_egui::egui;
 use std::collections::HashSet;
 
 use super::colors;
-use super::zs;
+use super::{accessibility, fs, zs};
 use super::EditorState;
 use super::EditorEvent;
+use super::event_queue::EventSender;
+use crate::monitor::EngineMonitor;
+use crate::midi::scale::{Scale, ScaleLock, ScaleMode};
+use crate::preset::drums;
 
 /// Persistent state for the piano keyboard.
 pub struct PianoState {
@@ -22,6 +28,9 @@ pub struct PianoState {
     pub active_notes: HashSet<u8>,
     /// The last note triggered by mouse (for drag-across-keys).
     last_mouse_note: Option<u8>,
+    /// Wheel values last sent while dragging (None when not dragged).
+    bend_sent: Option<f32>,
+    mod_sent: Option<f32>,
 }
 
 impl Default for PianoState {
@@ -31,16 +40,21 @@ impl Default for PianoState {
             octave_offset: 0,
             active_notes: HashSet::new(),
             last_mouse_note: None,
+            bend_sent: None,
+            mod_sent: None,
         }
     }
 }
 
 impl PianoState {
+    /// A piano that starts shown or hidden.
+    pub fn new(visible: bool) -> Self {
+        Self { visible, ..Default::default() }
+    }
+
     /// Base MIDI note for the leftmost key.
     pub fn base_note(&self) -> u8 {
-        // Use i16 to avoid i8 overflow on extreme octave offsets
-        let note = 48_i16 + self.octave_offset as i16 * 12;
-        note.clamp(0, 108) as u8
+        base_note_for_offset(self.octave_offset)
     }
 
     /// Range label (e.g., "C3–B4").
@@ -51,25 +65,62 @@ impl PianoState {
     }
 }
 
+/// Leftmost key for an octave offset (0 = C3).
+pub fn base_note_for_offset(octave_offset: i8) -> u8 {
+    // Use i16 to avoid i8 overflow on extreme octave offsets
+    let note = 48_i16 + octave_offset as i16 * 12;
+    note.clamp(0, 108) as u8
+}
+
 /// Number of white keys in 2 octaves (C to B × 2 = 14 white keys).
-const NUM_WHITE_KEYS: usize = 14;
+pub const NUM_WHITE_KEYS: usize = 14;
 /// Total semitones in 2 octaves.
-const NUM_SEMITONES: usize = 24;
+pub const NUM_SEMITONES: usize = 24;
 
 /// Whether a semitone offset (0–11) within an octave is a black key.
-const fn is_black_key(semitone: u8) -> bool {
+pub const fn is_black_key(semitone: u8) -> bool {
     matches!(semitone % 12, 1 | 3 | 6 | 8 | 10)
 }
 
 /// Draw the piano keyboard panel.
 pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
+    // Articulation keyswitches of the selected slot, and the active one
+    let selected = state.slot_rack_state.selected_slot;
+    let keyswitches: Vec<u8> = state
+        .plugin_state
+        .lock()
+        .ok()
+        .and_then(|ps| {
+            ps.slot_configs
+                .get(selected)
+                .map(|c| c.articulations.iter().map(|a| a.key).collect())
+        })
+        .unwrap_or_default();
+    let active_keyswitch = state.monitor.articulation(selected);
+    // Drum kits label their keys with GM drum names
+    let drum_kit = state
+        .active_presets_ui
+        .get(&selected)
+        .is_some_and(|(_, preset)| drums::is_drum_kit(&preset.descriptor));
+    let keyswitch_fill = |note: u8, fill: egui::Color32| {
+        if active_keyswitch == Some(note) {
+            colors::yellow()
+        } else if keyswitches.contains(&note) {
+            colors::peach()
+        } else {
+            fill
+        }
+    };
+
+    let mut scale_lock = state.plugin_state.lock().map(|ps| ps.scale_lock).unwrap_or_default();
+
     let piano = &mut state.piano_state;
     let base_note = piano.base_note();
 
     ui.horizontal(|ui| {
         // Octave shift controls
         if ui
-            .button(egui::RichText::new("◀").size(zs(12.0, z)))
+            .button(egui::RichText::new("◀").size(fs(12.0, z)))
             .on_hover_text("Shift down one octave")
             .clicked()
         {
@@ -77,12 +128,12 @@ pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
         }
         ui.label(
             egui::RichText::new(piano.range_label())
-                .color(colors::SUBTEXT0)
-                .size(zs(11.0, z))
+                .color(colors::subtext0())
+                .size(fs(11.0, z))
                 .family(egui::FontFamily::Monospace),
         );
         if ui
-            .button(egui::RichText::new("▶").size(zs(12.0, z)))
+            .button(egui::RichText::new("▶").size(fs(12.0, z)))
             .on_hover_text("Shift up one octave")
             .clicked()
         {
@@ -94,34 +145,42 @@ pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
         // Display current playing slot
         let slot_index = state.slot_rack_state.selected_slot;
         let slot_name = if let Ok(ps) = state.plugin_state.lock() {
-            ps.slot_configs.get(slot_index).map(|c| {
-                if let Some(ref pid) = c.preset_id {
-                    pid.clone()
-                } else if !c.source_code.is_empty() {
-                    "Source".to_string()
-                } else {
-                    "Empty".to_string()
-                }
-            }).unwrap_or_else(|| "None".to_string())
+            ps.slot_configs
+                .get(slot_index)
+                .map(|c| c.display_name())
+                .unwrap_or_else(|| "None".to_string())
         } else {
             "???".to_string()
         };
 
         ui.label(
             egui::RichText::new(format!("Playing Slot {}: {}", slot_index + 1, slot_name))
-                .color(colors::TEAL)
-                .size(zs(11.0, z)),
+                .color(colors::teal())
+                .size(fs(11.0, z)),
         );
+
+        ui.add_space(zs(12.0, z));
+        draw_scale_controls(ui, &mut scale_lock, z);
     });
 
+    if let Ok(mut ps) = state.plugin_state.lock() {
+        if ps.scale_lock != scale_lock {
+            ps.scale_lock = scale_lock;
+            let _ = state.event_tx.try_send(EditorEvent::SetScaleLock { lock: scale_lock });
+        }
+    }
+
     // Piano drawing area — use available_width() to get the actual remaining
     // visible width at the current cursor position (after horizontal controls).
     let desired_height = zs(70.0, z);
-    let available_w = ui.available_width();
-    let (rect, response) = ui.allocate_exact_size(
-        egui::vec2(available_w, desired_height),
-        egui::Sense::click_and_drag(),
-    );
+    let selected = state.slot_rack_state.selected_slot;
+    let (rect, response) = ui
+        .horizontal(|ui| {
+            draw_wheels(ui, piano, &state.monitor, &state.event_tx, selected, desired_height, z);
+            let available_w = ui.available_width();
+            ui.allocate_exact_size(egui::vec2(available_w, desired_height), egui::Sense::click_and_drag())
+        })
+        .inner;
 
     let painter = ui.painter_at(rect);
     let white_key_width = rect.width() / NUM_WHITE_KEYS as f32;
@@ -156,23 +215,74 @@ pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
         }
     }
 
+    let (white_fill, black_fill) = colors::piano_keys();
+
     // Draw white keys
     for &(midi_note, key_rect) in &white_rects {
         let is_active = piano.active_notes.contains(&midi_note);
-        let fill = if is_active { colors::BLUE } else { colors::TEXT };
+        let fill = if is_active { colors::blue() } else { keyswitch_fill(midi_note, white_fill) };
         painter.rect_filled(key_rect, 0.0, fill);
-        painter.rect_stroke(key_rect, 0.0, egui::Stroke::new(1.0, colors::SURFACE1), egui::StrokeKind::Outside);
+        painter.rect_stroke(key_rect, 0.0, egui::Stroke::new(1.0, colors::surface1()), egui::StrokeKind::Outside);
     }
 
     // Draw black keys (on top of white)
     for &(midi_note, key_rect) in &black_rects {
         let is_active = piano.active_notes.contains(&midi_note);
         // Use darker base for black keys to contrast with CRUST panel background
-        let fill = if is_active { colors::BLUE } else { colors::BASE };
+        let fill = if is_active { colors::blue() } else { keyswitch_fill(midi_note, black_fill) };
         painter.rect_filled(key_rect, 0.0, fill);
-        painter.rect_stroke(key_rect, 0.0, egui::Stroke::new(1.0, colors::CRUST), egui::StrokeKind::Outside);
+        painter.rect_stroke(key_rect, 0.0, egui::Stroke::new(1.0, colors::crust()), egui::StrokeKind::Outside);
+    }
+
+    // Scale tones get a strip along the top of the key, the root in mauve
+    if scale_lock.is_active() {
+        let strip = zs(4.0, z);
+        for &(midi_note, key_rect) in white_rects.iter().chain(black_rects.iter()) {
+            if !scale_lock.contains(midi_note) {
+                continue;
+            }
+            let color = if midi_note % 12 == scale_lock.root { colors::mauve() } else { colors::green() };
+            let top = if is_black_key(midi_note % 12) { key_rect.top() } else { rect.top() };
+            let mark = egui::Rect::from_min_size(
+                egui::pos2(key_rect.left() + 1.0, top),
+                egui::vec2(key_rect.width() - 2.0, strip),
+            );
+            painter.rect_filled(mark, 0.0, color);
+        }
+    }
+
+    // GM drum labels along the bottom of each key
+    if drum_kit {
+        let font = egui::FontId::proportional(zs(8.0, z));
+        let keys = white_rects.iter().map(|k| (k, black_fill)).chain(
+            black_rects.iter().map(|k| (k, white_fill)),
+        );
+        for (&(midi_note, key_rect), color) in keys {
+            if let Some(label) = drums::gm_drum_short_name(midi_note) {
+                painter.text(
+                    key_rect.center_bottom() - egui::vec2(0.0, zs(3.0, z)),
+                    egui::Align2::CENTER_BOTTOM,
+                    label,
+                    font.clone(),
+                    color,
+                );
+            }
+        }
     }
 
+    // Name the hovered key (GM drum name on drum kits)
+    let hovered_note = response.hover_pos().and_then(|pos| {
+        black_rects
+            .iter()
+            .find(|(_, r)| r.contains(pos))
+            .or_else(|| white_rects.iter().find(|(_, r)| r.contains(pos)))
+            .map(|&(note, _)| note)
+    });
+    let response = match hovered_note {
+        Some(note) => response.on_hover_text(drums::note_label(note, drum_kit)),
+        None => response,
+    };
+
     // --- Mouse interaction ---
     let pointer_pos = response.interact_pointer_pos();
 
@@ -240,6 +350,121 @@ pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
     }
 }
 
+/// Wheel position for a pointer height in the wheel's rect: −1..1 for the
+/// bend wheel (centred), 0–1 for the mod wheel.
+fn wheel_value(rect: egui::Rect, y: f32, centred: bool) -> f32 {
+    let t = ((rect.bottom() - y) / rect.height().max(1.0)).clamp(0.0, 1.0);
+    if centred { t * 2.0 - 1.0 } else { t }
+}
+
+/// Paint a wheel: its track, the travel from rest to `value`, and a thumb.
+fn paint_wheel(ui: &egui::Ui, rect: egui::Rect, value: f32, centred: bool, active: bool) {
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, colors::surface0());
+    let t = if centred { (value + 1.0) * 0.5 } else { value };
+    let y = rect.bottom() - rect.height() * t.clamp(0.0, 1.0);
+    let rest = if centred { rect.center().y } else { rect.bottom() };
+    let color = if active { colors::blue() } else { colors::mauve() };
+    let travel = egui::Rect::from_x_y_ranges(rect.x_range(), y.min(rest)..=y.max(rest));
+    painter.rect_filled(travel, 2.0, color.gamma_multiply(0.5));
+    painter.hline(rect.x_range(), y, egui::Stroke::new(2.0, color));
+    if centred {
+        painter.hline(rect.x_range(), rest, egui::Stroke::new(1.0, colors::overlay0()));
+    }
+}
+
+/// Bend and mod wheels for the selected slot. They show its incoming
+/// values; dragging sends bend or CC1, and the bend wheel springs back to
+/// centre when let go.
+fn draw_wheels(
+    ui: &mut egui::Ui,
+    piano: &mut PianoState,
+    monitor: &EngineMonitor,
+    event_tx: &EventSender,
+    slot_index: usize,
+    height: f32,
+    z: f32,
+) {
+    let size = egui::vec2(zs(14.0, z), height);
+
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::drag());
+    let dragged = response.dragged().then(|| response.interact_pointer_pos()).flatten();
+    let bend = match dragged {
+        Some(pos) => {
+            let value = wheel_value(rect, pos.y, true);
+            if piano.bend_sent != Some(value) {
+                piano.bend_sent = Some(value);
+                let _ = event_tx.try_send(EditorEvent::PitchBend { slot_index, value });
+            }
+            value
+        }
+        None => {
+            if piano.bend_sent.take().is_some() {
+                let _ = event_tx.try_send(EditorEvent::PitchBend { slot_index, value: 0.0 });
+            }
+            monitor.pitch_bend(slot_index)
+        }
+    };
+    paint_wheel(ui, rect, bend, true, dragged.is_some());
+    let spoken = format!("Pitch bend, {:+.0}%", bend * 100.0);
+    accessibility::name(&response, egui::WidgetType::Slider, &spoken);
+    response.on_hover_text(spoken);
+
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::drag());
+    let dragged = response.dragged().then(|| response.interact_pointer_pos()).flatten();
+    let mod_wheel = match dragged {
+        Some(pos) => {
+            let value = wheel_value(rect, pos.y, false);
+            if piano.mod_sent != Some(value) {
+                piano.mod_sent = Some(value);
+                let _ = event_tx.try_send(EditorEvent::ModWheel { slot_index, value });
+            }
+            value
+        }
+        None => {
+            piano.mod_sent = None;
+            monitor.controllers(slot_index).0
+        }
+    };
+    paint_wheel(ui, rect, mod_wheel, false, dragged.is_some());
+    let spoken = format!("Mod wheel, {}", (mod_wheel * 127.0).round() as u8);
+    accessibility::name(&response, egui::WidgetType::Slider, &spoken);
+    response.on_hover_text(spoken);
+}
+
+/// Mode, key and scale of the scale lock.
+fn draw_scale_controls(ui: &mut egui::Ui, lock: &mut ScaleLock, z: f32) {
+    const KEYS: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+    ui.label(egui::RichText::new("Scale:").color(colors::subtext0()).size(fs(11.0, z)))
+        .on_hover_text("Snap incoming notes to the scale, or block notes outside it");
+    egui::ComboBox::from_id_salt("scale_lock_mode")
+        .selected_text(lock.mode.label())
+        .width(zs(56.0, z))
+        .show_ui(ui, |ui| {
+            for mode in ScaleMode::ALL {
+                ui.selectable_value(&mut lock.mode, mode, mode.label());
+            }
+        });
+    ui.add_enabled_ui(lock.is_active(), |ui| {
+        egui::ComboBox::from_id_salt("scale_lock_root")
+            .selected_text(KEYS[usize::from(lock.root % 12)])
+            .width(zs(40.0, z))
+            .show_ui(ui, |ui| {
+                for (root, name) in KEYS.iter().enumerate() {
+                    ui.selectable_value(&mut lock.root, root as u8, *name);
+                }
+            });
+        egui::ComboBox::from_id_salt("scale_lock_scale")
+            .selected_text(lock.scale.label())
+            .width(zs(110.0, z))
+            .show_ui(ui, |ui| {
+                for scale in Scale::ALL {
+                    ui.selectable_value(&mut lock.scale, scale, scale.label());
+                }
+            });
+    });
+}
+
 /// Convert a MIDI note number to a name (e.g., 60 → "C4").
 pub fn note_name(note: u8) -> String {
     const NAMES: [&str; 12] = [
@@ -276,6 +501,15 @@ mod tests {
         assert!(!is_black_key(11));
     }
 
+    #[test]
+    fn test_wheel_value_from_pointer_height() {
+        let rect = egui::Rect::from_min_size(egui::pos2(0.0, 10.0), egui::vec2(14.0, 100.0));
+        assert_eq!(wheel_value(rect, 60.0, true), 0.0);
+        assert_eq!(wheel_value(rect, 10.0, true), 1.0);
+        assert_eq!(wheel_value(rect, 200.0, true), -1.0);
+        assert_eq!(wheel_value(rect, 85.0, false), 0.25);
+    }
+
     #[test]
     fn test_is_black_key_wraps_octave() {
         // Semitone 13 = C# in second octave
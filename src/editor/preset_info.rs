@@ -0,0 +1,418 @@
+//! Info panel for the preset selected in the browser.
+//!
+//! The library index already gives name, category, tags, GM program and
+//! zone count. Description, license/attribution and the sample list only
+//! live in the preset's own descriptor, which is fetched on first selection
+//! (through the shared HTTP store, so it is a 304 after that) and kept for
+//! the session. External samples that don't declare their size are sized
+//! with a HEAD request, so the download estimate is complete where the
+//! server allows it.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use nih_plug_egui::egui;
+
+use super::colors;
+use super::fs;
+use crate::jobs::{JobHandle, JobPool, JobPriority};
+use crate::net::HttpClient;
+use crate::preset::integrity;
+use crate::preset::fetch::PresetFetcher;
+use crate::preset::manager::{PresetInfo, PresetManager};
+use crate::preset::memory::format_bytes;
+
+/// Details read from a preset descriptor.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PresetDetails {
+    pub description: Option<String>,
+    pub license: Option<String>,
+    pub attribution: Option<String>,
+    /// Sample zones across the whole graph.
+    pub zones: usize,
+    /// Samples downloaded separately from the descriptor.
+    pub external_samples: usize,
+    /// Descriptor plus every sample whose size is known (inline data, or
+    /// an external sample that declares its size).
+    pub known_bytes: usize,
+    /// External samples that don't declare a size.
+    pub unknown_sizes: usize,
+    /// URLs (as written in the descriptor) of the samples without a size.
+    pub unsized_urls: Vec<String>,
+}
+
+impl PresetDetails {
+    /// Estimated download, e.g. "14.2 MB" or "3.1 MB+ (2 of 40 samples unsized)".
+    pub fn size_label(&self) -> String {
+        if self.unknown_sizes > 0 {
+            format!(
+                "{}+ ({} of {} samples unsized)",
+                format_bytes(self.known_bytes),
+                self.unknown_sizes,
+                self.external_samples
+            )
+        } else {
+            format_bytes(self.known_bytes)
+        }
+    }
+}
+
+/// Most HEAD requests made to size one preset's samples.
+const MAX_SIZE_PROBES: usize = 64;
+
+/// Fetch state of one preset's details.
+#[derive(Debug, Clone)]
+pub enum DetailsStatus {
+    Loading,
+    Ready(PresetDetails),
+    Failed(String),
+}
+
+/// Descriptor details fetched so far, keyed by preset id ("library/path").
+#[derive(Default)]
+pub struct PresetInfoState {
+    details: Arc<Mutex<HashMap<String, DetailsStatus>>>,
+    /// Fetch for the current selection; superseded by the next one.
+    fetch: Option<JobHandle>,
+}
+
+/// First string among `keys` in a JSON object.
+fn first_str(object: &serde_json::Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|k| object.get(*k).and_then(|v| v.as_str()))
+        .map(str::to_string)
+}
+
+/// Walk a descriptor's graph, counting zones and sample sizes.
+fn walk(value: &serde_json::Value, details: &mut PresetDetails) {
+    match value {
+        serde_json::Value::Object(map) => {
+            if let Some(zones) = map.get("zones").and_then(|z| z.as_array()) {
+                details.zones += zones.len();
+            }
+            if let Some(audio) = map.get("audio") {
+                if let Some(data) = audio.get("data").and_then(|d| d.as_str()) {
+                    // Base64: 3 bytes per 4 characters
+                    details.known_bytes += data.len() / 4 * 3;
+                } else if audio.get("url").is_some() {
+                    details.external_samples += 1;
+                    match audio.get("size").or_else(|| audio.get("bytes")).and_then(|s| s.as_u64()) {
+                        Some(size) => details.known_bytes += size as usize,
+                        None => {
+                            details.unknown_sizes += 1;
+                            if let Some(url) = audio.get("url").and_then(|u| u.as_str()) {
+                                details.unsized_urls.push(url.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+            for (key, child) in map {
+                if key != "audio" && key != "metadata" {
+                    walk(child, details);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                walk(item, details);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Read the details shown in the panel from a descriptor's JSON text.
+pub fn parse_details(text: &str) -> Result<PresetDetails, String> {
+    let json: serde_json::Value =
+        serde_json::from_str(text).map_err(|e| format!("Invalid preset: {}", e))?;
+    let mut details = PresetDetails {
+        known_bytes: text.len(),
+        ..Default::default()
+    };
+    if let Some(meta) = json.get("metadata") {
+        details.description = first_str(meta, &["description", "comment"]);
+        details.license = first_str(meta, &["license", "licence"]);
+        details.attribution = first_str(meta, &["attribution", "author", "source", "credits"]);
+    }
+    walk(json.get("graph").unwrap_or(&json), &mut details);
+    Ok(details)
+}
+
+impl PresetInfoState {
+    /// Details for the selected preset, starting a fetch if they aren't
+    /// known yet. The fetch supersedes the previous selection's.
+    pub fn details(
+        &mut self,
+        jobs: &JobPool,
+        preset_manager: &Mutex<PresetManager>,
+        library: &str,
+        path: &str,
+    ) -> DetailsStatus {
+        let (status, started) = self.lookup(jobs, preset_manager, library, path, JobPriority::Interactive);
+        if let Some(previous) = started.and_then(|handle| self.fetch.replace(handle)) {
+            previous.cancel();
+        }
+        status
+    }
+
+    /// Details for a preset that isn't selected (a hovered row, a pending
+    /// load), fetched in the background without superseding anything.
+    pub fn estimate(
+        &self,
+        jobs: &JobPool,
+        preset_manager: &Mutex<PresetManager>,
+        library: &str,
+        path: &str,
+    ) -> DetailsStatus {
+        self.lookup(jobs, preset_manager, library, path, JobPriority::Background).0
+    }
+
+    /// Known status, or `Loading` and the fetch just started.
+    fn lookup(
+        &self,
+        jobs: &JobPool,
+        preset_manager: &Mutex<PresetManager>,
+        library: &str,
+        path: &str,
+        priority: JobPriority,
+    ) -> (DetailsStatus, Option<JobHandle>) {
+        let preset_id = format!("{}/{}", library, path);
+        if let Some(status) = self.details.lock().ok().and_then(|d| d.get(&preset_id).cloned()) {
+            return (status, None);
+        }
+        let Some((base_url, slug)) = preset_manager.lock().ok().map(|pm| {
+            let slug = pm
+                .libraries
+                .iter()
+                .find(|l| l.name == library)
+                .map(|l| l.slug.clone())
+                .unwrap_or_else(|| library.to_string());
+            (pm.base_url.clone(), slug)
+        }) else {
+            return (DetailsStatus::Loading, None);
+        };
+        let url = format!("{}/{}/{}", base_url, slug, path);
+
+        if let Ok(mut d) = self.details.lock() {
+            d.insert(preset_id.clone(), DetailsStatus::Loading);
+        }
+        let details = self.details.clone();
+        let path = path.to_string();
+        let handle = jobs.submit(priority, move |ctx| {
+            let client = HttpClient::with_default_store();
+            let status = match ctx.block_on(client.get_revalidated(&url)) {
+                Some(Ok(fetched)) => match parse_details(&fetched.text()) {
+                    Ok(mut parsed) => {
+                        // Size what the descriptor doesn't
+                        let urls: Vec<String> = parsed
+                            .unsized_urls
+                            .iter()
+                            .take(MAX_SIZE_PROBES)
+                            .map(|u| integrity::sample_url(&base_url, &slug, &path, u))
+                            .collect();
+                        for url in urls {
+                            match ctx.block_on(client.content_length(&url)) {
+                                Some(Some(size)) => {
+                                    parsed.known_bytes += size as usize;
+                                    parsed.unknown_sizes -= 1;
+                                }
+                                Some(None) => {}
+                                None => {
+                                    if let Ok(mut d) = details.lock() {
+                                        d.remove(&preset_id);
+                                    }
+                                    return;
+                                }
+                            }
+                        }
+                        DetailsStatus::Ready(parsed)
+                    }
+                    Err(e) => DetailsStatus::Failed(e),
+                },
+                Some(Err(e)) => DetailsStatus::Failed(e),
+                None => {
+                    // Cancelled: fetch again when selected next time
+                    if let Ok(mut d) = details.lock() {
+                        d.remove(&preset_id);
+                    }
+                    return;
+                }
+            };
+            if let Ok(mut d) = details.lock() {
+                d.insert(preset_id, status);
+            }
+        });
+        (DetailsStatus::Loading, Some(handle))
+    }
+}
+
+/// Fetch a preset and its samples into the disk cache without loading it
+/// into a slot, so it is ready offline.
+pub fn spawn_download(
+    jobs: &JobPool,
+    preset_manager: Arc<Mutex<PresetManager>>,
+    status_text: Arc<Mutex<String>>,
+    library: String,
+    path: String,
+) -> JobHandle {
+    jobs.submit(JobPriority::Background, move |ctx| {
+        let Some((base_url, slug)) = preset_manager.lock().ok().map(|pm| {
+            let slug = pm
+                .libraries
+                .iter()
+                .find(|l| l.name == library)
+                .map(|l| l.slug.clone())
+                .unwrap_or_else(|| library.clone());
+            (pm.base_url.clone(), slug)
+        }) else {
+            return;
+        };
+        let fetcher = PresetFetcher::new(base_url);
+        let Some(result) = ctx.block_on(fetcher.load_preset(&slug, &path)) else {
+            return;
+        };
+        let name = path.rsplit('/').next().unwrap_or(&path);
+        if let Ok(mut st) = status_text.lock() {
+            *st = match result {
+                Ok(_) => format!("Downloaded {}", name),
+                Err(e) => format!("\u{26a0} Error: {}", e),
+            };
+        }
+    })
+}
+
+/// Index entry of a preset, from its library or one of its sub-indexes.
+pub fn find_preset(pm: &PresetManager, library: &str, path: &str) -> Option<PresetInfo> {
+    let prefix = format!("{}/", library);
+    pm.library_presets
+        .get(library)
+        .into_iter()
+        .flatten()
+        .chain(
+            pm.sub_index_presets
+                .iter()
+                .filter(|(key, _)| key.starts_with(&prefix))
+                .flat_map(|(_, presets)| presets),
+        )
+        .find(|p| p.path == path)
+        .cloned()
+}
+
+/// What the user asked for from the panel's buttons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfoAction {
+    Preview,
+    Add,
+    Download,
+}
+
+/// Draw the panel. Returns the button clicked, if any.
+pub fn draw(
+    ui: &mut egui::Ui,
+    info: &PresetInfo,
+    library: &str,
+    details: &DetailsStatus,
+    z: f32,
+) -> Option<InfoAction> {
+    let mut action = None;
+    let label = |ui: &mut egui::Ui, key: &str, value: &str| {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(egui::RichText::new(key).color(colors::subtext0()).size(fs(10.0, z)));
+            ui.label(egui::RichText::new(value).color(colors::text()).size(fs(10.0, z)));
+        });
+    };
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new(&info.name).color(colors::text()).strong().size(fs(12.0, z)));
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .small_button(egui::RichText::new("\u{2B07}").color(colors::blue()).size(fs(10.0, z)))
+                .on_hover_text("Download preset and samples to the cache")
+                .clicked()
+            {
+                action = Some(InfoAction::Download);
+            }
+            if ui
+                .small_button(egui::RichText::new("+").color(colors::green()).size(fs(10.0, z)))
+                .on_hover_text("Add to next available slot")
+                .clicked()
+            {
+                action = Some(InfoAction::Add);
+            }
+            if ui
+                .small_button(egui::RichText::new("\u{25B6}").color(colors::teal()).size(fs(10.0, z)))
+                .on_hover_text("Preview")
+                .clicked()
+            {
+                action = Some(InfoAction::Preview);
+            }
+        });
+    });
+    label(ui, "Path:", &format!("{}/{}", library, info.path));
+    label(ui, "Category:", &info.category);
+    if !info.tags.is_empty() {
+        label(ui, "Tags:", &info.tags.join(", "));
+    }
+    if let Some(program) = info.gm_program {
+        label(ui, "GM program:", &(program as u32 + 1).to_string());
+    }
+
+    match details {
+        DetailsStatus::Loading => {
+            label(ui, "Zones:", &info.zone_count.to_string());
+            ui.label(
+                egui::RichText::new("Loading details…")
+                    .color(colors::overlay0())
+                    .size(fs(10.0, z))
+                    .italics(),
+            );
+        }
+        DetailsStatus::Ready(d) => {
+            label(ui, "Zones:", &d.zones.max(info.zone_count as usize).to_string());
+            label(ui, "Download:", &d.size_label());
+            if let Some(description) = &d.description {
+                ui.label(egui::RichText::new(description).color(colors::subtext1()).size(fs(10.0, z)));
+            }
+            if let Some(license) = &d.license {
+                label(ui, "License:", license);
+            }
+            if let Some(attribution) = &d.attribution {
+                label(ui, "Attribution:", attribution);
+            }
+        }
+        DetailsStatus::Failed(e) => {
+            label(ui, "Zones:", &info.zone_count.to_string());
+            ui.label(egui::RichText::new(format!("\u{26A0} {}", e)).color(colors::red()).size(fs(10.0, z)));
+        }
+    }
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_details() {
+        let text = r#"{
+            "name": "Piano",
+            "metadata": { "description": "Bright grand", "license": "CC-BY 4.0", "author": "FluidR3" },
+            "graph": { "type": "sampler", "config": { "zones": [
+                { "audio": { "type": "external", "url": "a.flac", "size": 1000 } },
+                { "audio": { "type": "external", "url": "b.flac" } },
+                { "audio": { "type": "inline", "data": "AAAA" } }
+            ] } }
+        }"#;
+        let d = parse_details(text).unwrap();
+        assert_eq!(d.description.as_deref(), Some("Bright grand"));
+        assert_eq!(d.license.as_deref(), Some("CC-BY 4.0"));
+        assert_eq!(d.attribution.as_deref(), Some("FluidR3"));
+        assert_eq!(d.zones, 3);
+        assert_eq!(d.external_samples, 2);
+        assert_eq!(d.unknown_sizes, 1);
+        assert_eq!(d.unsized_urls, vec!["b.flac".to_string()]);
+        assert_eq!(d.known_bytes, text.len() + 1000 + 3);
+        assert!(parse_details("not json").is_err());
+    }
+}
@@ -0,0 +1,300 @@
+//! Browser preview player: which note(s) a preview plays and when they stop.
+//!
+//! The audio thread plays the root note as soon as the preset arrives (the
+//! load's `play_note`). Chord notes, the rest of a phrase and every note-off
+//! are scheduled here and sent through the editor event channel as their
+//! time comes, so a preview never rings on until its slot is reused.
+//!
+//! Previews are velocity sensitive: where the play button is clicked sets
+//! the velocity, and Shift / Ctrl force a soft or a full one. Holding
+//! Shift or Ctrl while pressing a row's audition key (see `AUDITION_KEYS`)
+//! works the same way.
+
+use std::time::{Duration, Instant};
+
+use nih_plug_egui::egui;
+
+use super::event_queue::EventSender;
+use super::{EditorEvent, PlayNote};
+
+/// Default preview velocity.
+pub const PREVIEW_VELOCITY: f32 = 0.8;
+/// Velocity range of the play button, from its top edge to its bottom.
+const CLICK_VELOCITY: (f32, f32) = (0.25, 1.0);
+/// Velocity with Shift held.
+const SOFT_VELOCITY: f32 = 0.3;
+
+/// Keys that audition the hovered browser row, laid out like a piano
+/// octave on the home row (A = the preview note, W = a semitone up, …,
+/// K = an octave up).
+pub const AUDITION_KEYS: [egui::Key; 13] = [
+    egui::Key::A,
+    egui::Key::W,
+    egui::Key::S,
+    egui::Key::E,
+    egui::Key::D,
+    egui::Key::F,
+    egui::Key::T,
+    egui::Key::G,
+    egui::Key::Y,
+    egui::Key::H,
+    egui::Key::U,
+    egui::Key::J,
+    egui::Key::K,
+];
+
+/// Velocity of a preview started with `modifiers` held, clicked at
+/// `position` (0.0 top – 1.0 bottom of the play button; `None` for a key).
+pub fn preview_velocity(position: Option<f32>, modifiers: egui::Modifiers) -> f32 {
+    if modifiers.command || modifiers.ctrl {
+        1.0
+    } else if modifiers.shift {
+        SOFT_VELOCITY
+    } else if let Some(position) = position {
+        let (soft, loud) = CLICK_VELOCITY;
+        soft + (loud - soft) * position.clamp(0.0, 1.0)
+    } else {
+        PREVIEW_VELOCITY
+    }
+}
+
+/// The note an audition key plays, `root` being the preview note. Notes
+/// past MIDI 127 are dropped.
+pub fn audition_note(root: u8, key: egui::Key) -> Option<u8> {
+    let offset = AUDITION_KEYS.iter().position(|k| *k == key)?;
+    root.checked_add(offset as u8).filter(|n| *n <= 127)
+}
+
+/// What a preview plays, starting at the root note.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PreviewMode {
+    /// The root alone.
+    #[default]
+    Note,
+    /// A major triad on the root.
+    Chord,
+    /// Root, third, fifth and octave one after another.
+    Phrase,
+}
+
+impl PreviewMode {
+    pub const ALL: [PreviewMode; 3] = [Self::Note, Self::Chord, Self::Phrase];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Note => "Note",
+            Self::Chord => "Chord",
+            Self::Phrase => "Phrase",
+        }
+    }
+
+    /// Semitone offsets from the root.
+    fn intervals(self) -> &'static [u8] {
+        match self {
+            Self::Note => &[0],
+            Self::Chord => &[0, 4, 7],
+            Self::Phrase => &[0, 4, 7, 12],
+        }
+    }
+}
+
+/// Preview options, as edited in the browser header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreviewSettings {
+    /// Root note (MIDI), C4 by default.
+    pub note: u8,
+    pub mode: PreviewMode,
+    /// Time until every note is released.
+    pub duration_secs: f32,
+}
+
+impl Default for PreviewSettings {
+    fn default() -> Self {
+        Self {
+            note: 60,
+            mode: PreviewMode::Note,
+            duration_secs: 1.5,
+        }
+    }
+}
+
+/// One scheduled note-on (`on`) or note-off, relative to the preview start.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreviewStep {
+    pub at: Duration,
+    pub note: u8,
+    pub on: bool,
+}
+
+/// Every note event of a preview except the root note-on, which the audio
+/// thread plays when the preset arrives. Notes past MIDI 127 are dropped.
+pub fn preview_steps(settings: &PreviewSettings) -> Vec<PreviewStep> {
+    let duration = Duration::from_secs_f32(settings.duration_secs.max(0.05));
+    let notes: Vec<u8> = settings
+        .mode
+        .intervals()
+        .iter()
+        .filter_map(|&i| settings.note.checked_add(i).filter(|&n| n <= 127))
+        .collect();
+    let mut steps = Vec::with_capacity(notes.len() * 2);
+    match settings.mode {
+        PreviewMode::Note | PreviewMode::Chord => {
+            for &note in notes.iter().skip(1) {
+                steps.push(PreviewStep { at: Duration::ZERO, note, on: true });
+            }
+            for &note in &notes {
+                steps.push(PreviewStep { at: duration, note, on: false });
+            }
+        }
+        PreviewMode::Phrase => {
+            // Equal steps, each note released as the next one starts
+            let step = duration / notes.len().max(1) as u32;
+            for (k, &note) in notes.iter().enumerate() {
+                let start = step * k as u32;
+                if k > 0 {
+                    steps.push(PreviewStep { at: start, note, on: true });
+                }
+                steps.push(PreviewStep { at: start + step, note, on: false });
+            }
+        }
+    }
+    steps
+}
+
+/// Scheduled preview notes waiting to be sent.
+#[derive(Default)]
+pub struct PreviewPlayer {
+    pub settings: PreviewSettings,
+    /// (due time, slot, step, velocity), in no particular order.
+    pending: Vec<(Instant, usize, PreviewStep, f32)>,
+}
+
+impl PreviewPlayer {
+    /// Schedule the rest of a preview whose root note just started on `slot_index`.
+    pub fn start(&mut self, slot_index: usize, root: PlayNote, now: Instant) {
+        // A new preview on the same slot replaces the old one's schedule
+        self.pending.retain(|&(_, slot, _, _)| slot != slot_index);
+        let settings = PreviewSettings { note: root.note, ..self.settings };
+        for step in preview_steps(&settings) {
+            self.pending.push((now + step.at, slot_index, step, root.velocity));
+        }
+    }
+
+    /// Send the events that are due. Returns the time of the next one, so
+    /// the caller can schedule a repaint.
+    pub fn poll(&mut self, now: Instant, event_tx: &EventSender) -> Option<Instant> {
+        let mut k = 0;
+        while k < self.pending.len() {
+            let (due, slot_index, step, velocity) = self.pending[k];
+            if due > now {
+                k += 1;
+                continue;
+            }
+            let event = if step.on {
+                EditorEvent::NoteOn { slot_index, note: step.note, velocity }
+            } else {
+                EditorEvent::NoteOff { slot_index, note: step.note }
+            };
+            if event_tx.try_send(event).is_err() {
+                // Channel full: try again next frame
+                break;
+            }
+            self.pending.swap_remove(k);
+        }
+        self.pending.iter().map(|&(due, _, _, _)| due).min()
+    }
+
+    /// Whether any preview notes are still scheduled.
+    pub fn is_playing(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Drop every schedule and silence all previews.
+    pub fn stop_all(&mut self, event_tx: &EventSender) {
+        self.pending.clear();
+        let _ = event_tx.try_send(EditorEvent::StopPreview);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(mode: PreviewMode) -> PreviewSettings {
+        PreviewSettings {
+            note: 60,
+            mode,
+            duration_secs: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_single_note_releases_after_duration() {
+        let steps = preview_steps(&settings(PreviewMode::Note));
+        assert_eq!(
+            steps,
+            vec![PreviewStep { at: Duration::from_secs(1), note: 60, on: false }]
+        );
+    }
+
+    #[test]
+    fn test_chord_adds_third_and_fifth() {
+        let steps = preview_steps(&settings(PreviewMode::Chord));
+        let ons: Vec<u8> = steps.iter().filter(|s| s.on).map(|s| s.note).collect();
+        let offs: Vec<u8> = steps.iter().filter(|s| !s.on).map(|s| s.note).collect();
+        assert_eq!(ons, vec![64, 67]);
+        assert_eq!(offs, vec![60, 64, 67]);
+    }
+
+    #[test]
+    fn test_phrase_steps_in_sequence() {
+        let steps = preview_steps(&settings(PreviewMode::Phrase));
+        let ons: Vec<(u128, u8)> = steps
+            .iter()
+            .filter(|s| s.on)
+            .map(|s| (s.at.as_millis(), s.note))
+            .collect();
+        assert_eq!(ons, vec![(250, 64), (500, 67), (750, 72)]);
+        assert_eq!(steps.last(), Some(&PreviewStep { at: Duration::from_secs(1), note: 72, on: false }));
+    }
+
+    #[test]
+    fn test_velocity_and_audition_keys() {
+        let none = egui::Modifiers::NONE;
+        assert_eq!(preview_velocity(None, none), PREVIEW_VELOCITY);
+        assert!(preview_velocity(Some(0.0), none) < preview_velocity(Some(1.0), none));
+        assert_eq!(preview_velocity(Some(1.0), none), 1.0);
+        assert_eq!(preview_velocity(Some(1.0), egui::Modifiers::SHIFT), SOFT_VELOCITY);
+        assert_eq!(preview_velocity(Some(0.0), egui::Modifiers::CTRL), 1.0);
+
+        assert_eq!(audition_note(60, egui::Key::A), Some(60));
+        assert_eq!(audition_note(60, egui::Key::E), Some(63));
+        assert_eq!(audition_note(60, egui::Key::K), Some(72));
+        assert_eq!(audition_note(120, egui::Key::K), None);
+        assert_eq!(audition_note(60, egui::Key::Z), None);
+    }
+
+    #[test]
+    fn test_player_sends_due_events_and_stops() {
+        let (tx, rx) = crate::editor::event_queue::channel();
+        let mut player = PreviewPlayer {
+            settings: settings(PreviewMode::Chord),
+            ..Default::default()
+        };
+        let now = Instant::now();
+        player.start(2, PlayNote { note: 60, velocity: 0.5 }, now);
+        player.poll(now, &tx);
+        let ons: Vec<EditorEvent> = rx.try_iter().collect();
+        assert_eq!(ons.len(), 2);
+        assert!(ons.iter().all(|e| matches!(e, EditorEvent::NoteOn { velocity, .. } if *velocity == 0.5)));
+        assert!(player.is_playing());
+        player.poll(now + Duration::from_secs(2), &tx);
+        assert_eq!(rx.try_iter().count(), 3);
+        assert!(!player.is_playing());
+
+        player.start(2, PlayNote::new(60), now);
+        player.stop_all(&tx);
+        assert!(!player.is_playing());
+        assert!(matches!(rx.try_recv(), Ok(EditorEvent::StopPreview)));
+    }
+}
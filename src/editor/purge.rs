@@ -0,0 +1,129 @@
+//! Purge in the expanded slot view: drop the PCM of the zones a slot
+//! hasn't played this session (see `slots::zone_usage`).
+//!
+//! A purged slot stays purged. When a note lands on a purged zone the
+//! preset is reloaded in the background like any slot load, and once it
+//! arrives it is purged again, now keeping the zone that was played. The
+//! audio thread takes preset loads before editor events in each block, so
+//! the new purge always applies to the reloaded preset.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use nih_plug_egui::egui;
+use songwalker_core::preset::instance::PresetInstance;
+
+use super::loads::LoadTarget;
+use super::{EditorEvent, EditorState, colors, fs};
+use crate::preset::memory;
+use crate::slots::zone_usage;
+use crate::view_model;
+
+struct Purged {
+    preset_id: Arc<String>,
+    /// The purged copy sent to the audio thread (a different instance in
+    /// `active_presets_ui` means the preset was loaded again).
+    instance: Arc<PresetInstance>,
+    /// PCM bytes dropped by the purge.
+    freed: usize,
+    /// A reload for a played purged zone is in flight.
+    reloading: bool,
+}
+
+#[derive(Default)]
+pub struct PurgeState {
+    slots: HashMap<usize, Purged>,
+}
+
+impl PurgeState {
+    pub fn is_purged(&self, slot: usize) -> bool {
+        self.slots.contains_key(&slot)
+    }
+
+    /// Forget a removed slot; the slots after it move up one place.
+    pub fn remove_slot(&mut self, slot: usize) {
+        view_model::remove_slot_key(&mut self.slots, slot);
+    }
+}
+
+/// Purge the unplayed zones of a slot's preset. Returns the bytes freed.
+fn purge(state: &mut EditorState, idx: usize) -> Option<usize> {
+    let (preset_id, instance) = state.active_presets_ui.get(&idx).cloned()?;
+    if state.loads.is_loading(&preset_id) {
+        return None;
+    }
+    let usage = state.monitor.zone_usage(idx);
+    let (copy, freed) = zone_usage::purged_copy(&instance, &usage)?;
+    let copy = Arc::new(copy);
+    let event = EditorEvent::PurgeZones { slot_index: idx, instance: copy.clone() };
+    state.event_tx.try_send(event).ok()?;
+    state.active_presets_ui.insert(idx, (preset_id.clone(), copy.clone()));
+    state.purge.slots.insert(idx, Purged { preset_id, instance: copy, freed, reloading: false });
+    Some(freed)
+}
+
+/// Reload purged slots that played a purged zone, and purge reloaded
+/// ones again.
+pub fn poll(state: &mut EditorState) {
+    let slots: Vec<usize> = state.purge.slots.keys().copied().collect();
+    for idx in slots {
+        let Some(purged) = state.purge.slots.get_mut(&idx) else { continue };
+        let Some((preset_id, instance)) = state.active_presets_ui.get(&idx).cloned() else {
+            state.purge.slots.remove(&idx);
+            continue;
+        };
+        if preset_id != purged.preset_id {
+            // Another preset was loaded: nothing purged any more
+            state.purge.slots.remove(&idx);
+        } else if !Arc::ptr_eq(&instance, &purged.instance) {
+            state.purge.slots.remove(&idx);
+            purge(state, idx);
+        } else if !purged.reloading && state.monitor.zones_missing(idx) {
+            purged.reloading = true;
+            if let Some((library, path)) = preset_id.split_once('/') {
+                state.loads.request(
+                    &state.jobs,
+                    &state.preset_manager,
+                    LoadTarget::Slot(idx),
+                    library,
+                    path,
+                    idx,
+                    None,
+                );
+            }
+        }
+    }
+}
+
+/// Purge controls in the expanded slot view.
+pub fn draw_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, z: f32) {
+    if !state.active_presets_ui.contains_key(&idx) {
+        return;
+    }
+    ui.horizontal(|ui| {
+        if ui
+            .button(egui::RichText::new("Purge unused zones").size(fs(11.0, z)))
+            .on_hover_text(
+                "Free the samples of zones not played this session; \
+                 they load again in the background when played",
+            )
+            .clicked()
+        {
+            let message = match purge(state, idx) {
+                Some(freed) => format!("Slot {}: purged {}", idx + 1, memory::format_bytes(freed)),
+                None => format!("Slot {}: nothing to purge", idx + 1),
+            };
+            if let Ok(mut st) = state.status_text.lock() {
+                *st = message;
+            }
+        }
+        if let Some(purged) = state.purge.slots.get(&idx) {
+            let text = if purged.reloading {
+                "Reloading played zones…".to_string()
+            } else {
+                format!("Purged ({} freed)", memory::format_bytes(purged.freed))
+            };
+            ui.label(egui::RichText::new(text).color(colors::teal()).size(fs(10.0, z)));
+        }
+    });
+}
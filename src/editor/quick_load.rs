@@ -0,0 +1,98 @@
+//! Quick-load popup for an empty slot: search every indexed preset (with
+//! the browser's filtering) and load one straight into that slot.
+//!
+//! Matches are listed whole name first, then names starting with the
+//! search text, then the rest; Enter loads the top one.
+
+use nih_plug_egui::egui;
+
+use super::download_check::{self, PendingAction};
+use super::{EditorState, colors};
+use crate::view_model::PresetFilter;
+
+/// Matches listed at once.
+const MAX_RESULTS: usize = 50;
+
+#[derive(Default)]
+pub struct QuickLoadState {
+    /// Slot the popup loads into.
+    target: Option<usize>,
+    query: String,
+}
+
+/// Open the popup for a slot.
+pub fn open(state: &mut EditorState, idx: usize) {
+    state.quick_load = QuickLoadState { target: Some(idx), ..Default::default() };
+}
+
+/// One match: (library, name, path).
+type Match = (String, String, String);
+
+fn matches(state: &EditorState, query: &str) -> Vec<Match> {
+    let filter = PresetFilter::new(query, None);
+    let Ok(pm) = state.preset_manager.lock() else { return Vec::new() };
+    let mut found: Vec<(u8, Match)> = filter
+        .all_presets(&pm)
+        .into_iter()
+        .map(|(library, p)| (filter.rank(&p.name), (library.to_string(), p.name.clone(), p.path.clone())))
+        .collect();
+    found.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.1.cmp(&b.1.1)));
+    found.into_iter().map(|(_, m)| m).take(MAX_RESULTS).collect()
+}
+
+/// The popup, if open.
+pub fn draw(ctx: &egui::Context, state: &mut EditorState) {
+    let Some(idx) = state.quick_load.target else { return };
+
+    let results = matches(state, &state.quick_load.query);
+    let mut open = true;
+    let mut chosen = None;
+    egui::Window::new(format!("Load into slot {}", idx + 1))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 80.0])
+        .show(ctx, |ui| {
+            let search = ui.add(
+                egui::TextEdit::singleline(&mut state.quick_load.query)
+                    .desired_width(280.0)
+                    .hint_text("Search presets\u{2026}"),
+            );
+            if !search.has_focus() && !search.lost_focus() {
+                search.request_focus();
+            }
+            if search.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                chosen = results.first().cloned();
+            }
+
+            if results.is_empty() {
+                ui.label(
+                    egui::RichText::new("No matching presets. Expand a library in the browser to index it.")
+                        .color(colors::overlay0())
+                        .small(),
+                );
+                return;
+            }
+            egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                for (i, m) in results.iter().enumerate() {
+                    let (library, name, _) = m;
+                    let text = egui::RichText::new(format!("{}  \u{00B7} {}", name, library));
+                    let text = if i == 0 { text.color(colors::blue()) } else { text.color(colors::text()) };
+                    if ui.selectable_label(false, text).clicked() {
+                        chosen = Some(m.clone());
+                    }
+                }
+            });
+        });
+
+    if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+        open = false;
+    }
+    if let Some((library, name, path)) = chosen {
+        download_check::request(state, &library, &path, PendingAction::IntoSlot { name, slot_index: idx });
+        open = false;
+    }
+    if !open {
+        state.quick_load = QuickLoadState::default();
+    }
+}
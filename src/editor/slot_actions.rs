@@ -0,0 +1,193 @@
+//! Slot operations shared by the strip buttons, the keyboard and the
+//! strip's right-click menu, so each action behaves the same wherever it
+//! is triggered from.
+
+use nih_plug_egui::egui;
+
+use super::download_check::{self, PendingAction};
+use super::{EditorEvent, EditorState, freeze, preset_info, slot_clipboard};
+use crate::state::SlotConfig;
+use crate::view_model;
+
+fn set_status(state: &EditorState, message: String) {
+    if let Ok(mut st) = state.status_text.lock() {
+        *st = message;
+    }
+}
+
+/// Remove a slot, keeping the selection within the rack. The slots after
+/// it move up, taking their editor-side state with them.
+pub fn remove(state: &mut EditorState, idx: usize) {
+    let remaining = {
+        let Ok(mut ps) = state.plugin_state.lock() else { return };
+        let events = view_model::remove_slot(&mut ps, idx);
+        if events.is_empty() {
+            return;
+        }
+        for event in events {
+            let _ = state.event_tx.try_send(event);
+        }
+        ps.slot_configs.len()
+    };
+    view_model::remove_slot_key(&mut state.active_presets_ui, idx);
+    state.compile_state.remove_slot(idx);
+    state.freeze.remove_slot(idx);
+    state.purge.remove_slot(idx);
+    state.slot_rack_state.renaming = None;
+    let selected = &mut state.slot_rack_state.selected_slot;
+    *selected = (*selected).min(remaining.saturating_sub(1));
+}
+
+/// Edit a slot's title in place (see `slot_rack`), starting from its
+/// custom name if it has one.
+pub fn start_rename(state: &mut EditorState, idx: usize, config: &SlotConfig) {
+    let text = if config.renamed { config.name.clone() } else { String::new() };
+    state.slot_rack_state.renaming = Some((idx, text));
+}
+
+/// Give a slot a custom name; a blank name goes back to the preset's.
+pub fn rename(state: &mut EditorState, idx: usize, name: &str) {
+    view_model::update_slot(&state.plugin_state, idx, |cfg| cfg.rename(name));
+}
+
+/// Set a slot's fader gain and pan.
+pub fn set_mix(state: &mut EditorState, idx: usize, volume: f32, pan: f32) {
+    view_model::update_slot(&state.plugin_state, idx, |cfg| {
+        cfg.volume = volume;
+        cfg.pan = pan;
+    });
+    let _ = state.event_tx.try_send(EditorEvent::SetSlotMix { slot_index: idx, volume, pan });
+}
+
+pub fn toggle_mute(state: &mut EditorState, idx: usize) {
+    view_model::update_slot(&state.plugin_state, idx, |cfg| cfg.muted = !cfg.muted);
+}
+
+pub fn toggle_solo(state: &mut EditorState, idx: usize) {
+    view_model::update_slot(&state.plugin_state, idx, |cfg| cfg.solo = !cfg.solo);
+}
+
+/// Route a slot into a group bus, or straight to the master (None).
+pub fn set_group(state: &mut EditorState, idx: usize, group: Option<usize>) {
+    view_model::update_slot(&state.plugin_state, idx, |cfg| cfg.group = group);
+    let _ = state.event_tx.try_send(EditorEvent::SetSlotGroup { slot_index: idx, group });
+}
+
+/// Free a slot's preset samples, keeping the preset assigned.
+pub fn unload(state: &mut EditorState, idx: usize) {
+    state.active_presets_ui.remove(&idx);
+    let _ = state.event_tx.try_send(EditorEvent::UnloadPreset { slot_index: idx });
+}
+
+/// Unload a slot's preset and unassign it.
+pub fn clear_preset(state: &mut EditorState, idx: usize) {
+    if view_model::update_slot(&state.plugin_state, idx, |cfg| cfg.preset_id = None) {
+        unload(state, idx);
+        set_status(state, format!("Slot {} cleared", idx + 1));
+    }
+}
+
+/// Add a copy of a slot to the end of its group and select it.
+pub fn duplicate(state: &mut EditorState, idx: usize) -> Option<usize> {
+    let (config, copy) = {
+        let mut ps = state.plugin_state.lock().ok()?;
+        let config = ps.slot_configs.get(idx)?.clone();
+        let copy = ps.add_slot_config(SlotConfig { group: config.group, ..SlotConfig::default() });
+        (config, copy)
+    };
+    slot_clipboard::apply(state, copy, config);
+    state.slot_rack_state.selected_slot = copy;
+    set_status(state, format!("Slot {} duplicated to slot {}", idx + 1, copy + 1));
+    Some(copy)
+}
+
+/// Swap a slot with the one above or below it in its group; the selection
+/// follows it.
+pub fn move_slot(state: &mut EditorState, idx: usize, up: bool) {
+    let Some(other) = view_model::neighbour_slot(&state.plugin_state, idx, up) else { return };
+    let configs = state
+        .plugin_state
+        .lock()
+        .ok()
+        .and_then(|ps| Some((ps.slot_configs.get(idx)?.clone(), ps.slot_configs.get(other)?.clone())));
+    let Some((moved, displaced)) = configs else { return };
+    slot_clipboard::apply(state, other, moved);
+    slot_clipboard::apply(state, idx, displaced);
+    if state.slot_rack_state.selected_slot == idx {
+        state.slot_rack_state.selected_slot = other;
+    }
+}
+
+/// Load the preset selected in the browser into a slot.
+pub fn load_browser_selection(state: &mut EditorState, idx: usize) {
+    let Some((library, path)) = state.browser_state.selected_preset.clone() else { return };
+    let name = state
+        .preset_manager
+        .lock()
+        .ok()
+        .and_then(|pm| preset_info::find_preset(&pm, &library, &path))
+        .map_or_else(|| path.rsplit('/').next().unwrap_or(&path).to_string(), |info| info.name);
+    download_check::request(state, &library, &path, PendingAction::IntoSlot { name, slot_index: idx });
+}
+
+/// Right-click menu of a slot strip.
+pub fn context_menu(response: &egui::Response, state: &mut EditorState, idx: usize, config: &SlotConfig) {
+    response.context_menu(|ui| {
+        if ui.button("Rename").clicked() {
+            start_rename(state, idx, config);
+            ui.close_menu();
+        }
+        ui.separator();
+
+        let selection = state.browser_state.selected_preset.as_ref().map(|(_, path)| path.clone());
+        let load_label = match &selection {
+            Some(path) => format!("Load \u{201C}{}\u{201D}", path.rsplit('/').next().unwrap_or(path)),
+            None => "Load browser selection".to_string(),
+        };
+        if ui.add_enabled(selection.is_some(), egui::Button::new(load_label)).clicked() {
+            load_browser_selection(state, idx);
+            ui.close_menu();
+        }
+        if ui.add_enabled(config.preset_id.is_some(), egui::Button::new("Clear preset")).clicked() {
+            clear_preset(state, idx);
+            ui.close_menu();
+        }
+        ui.separator();
+
+        if ui.button("Duplicate").clicked() {
+            duplicate(state, idx);
+            ui.close_menu();
+        }
+        if ui.button("Copy settings").clicked() {
+            slot_clipboard::copy(ui.ctx(), state, idx);
+            ui.close_menu();
+        }
+        if ui.button("Paste settings\u{2026}").clicked() {
+            slot_clipboard::open_paste(state, idx);
+            ui.close_menu();
+        }
+        ui.separator();
+
+        let above = view_model::neighbour_slot(&state.plugin_state, idx, true);
+        let below = view_model::neighbour_slot(&state.plugin_state, idx, false);
+        if ui.add_enabled(above.is_some(), egui::Button::new("Move up")).clicked() {
+            move_slot(state, idx, true);
+            ui.close_menu();
+        }
+        if ui.add_enabled(below.is_some(), egui::Button::new("Move down")).clicked() {
+            move_slot(state, idx, false);
+            ui.close_menu();
+        }
+        ui.separator();
+
+        let freeze_label = if state.freeze.is_frozen(idx) { "Unfreeze" } else { "Freeze" };
+        if ui.add_enabled(!state.freeze.is_rendering(idx), egui::Button::new(freeze_label)).clicked() {
+            freeze::toggle(state, idx, config);
+            ui.close_menu();
+        }
+        if ui.button("Remove slot").clicked() {
+            remove(state, idx);
+            ui.close_menu();
+        }
+    });
+}
@@ -0,0 +1,250 @@
+//! Copy and paste slots through the system clipboard as JSON.
+//!
+//! A copied slot is its `SlotConfig` (preset id, source, mix and every
+//! per-slot setting) wrapped with a format tag, so it can be pasted into
+//! another slot, another instance of the plugin, or kept in a text file.
+//! Group membership is not carried over: group indices belong to the rack
+//! they came from.
+//!
+//! egui only hands out clipboard text in a paste event, so pasting is
+//! either Ctrl+V with the rack focused (replacing the selected slot, or
+//! adding one to an empty rack) or the slot's "Paste…" window.
+
+use nih_plug_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use super::focus::FocusPanel;
+use super::loads::LoadTarget;
+use super::{EditorEvent, EditorState, colors};
+use crate::state::SlotConfig;
+
+/// Tag identifying clipboard text as a SongWalker slot.
+const FORMAT: &str = "songwalker-slot";
+const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct ClipboardSlot {
+    format: String,
+    version: u32,
+    slot: SlotConfig,
+}
+
+/// Paste window: the slot it pastes into and the text pasted so far.
+#[derive(Default)]
+pub struct SlotClipboardState {
+    target: Option<usize>,
+    text: String,
+    error: Option<String>,
+}
+
+/// Clipboard text for a slot.
+pub fn to_json(config: &SlotConfig) -> String {
+    let clip = ClipboardSlot {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        slot: config.clone(),
+    };
+    serde_json::to_string_pretty(&clip).unwrap_or_default()
+}
+
+/// Read a slot back from clipboard text.
+pub fn from_json(text: &str) -> Result<SlotConfig, String> {
+    let clip: ClipboardSlot =
+        serde_json::from_str(text.trim()).map_err(|_| "The clipboard doesn't hold a SongWalker slot".to_string())?;
+    if clip.format != FORMAT {
+        return Err("The clipboard doesn't hold a SongWalker slot".into());
+    }
+    if clip.version > VERSION {
+        return Err(format!("Slot was copied from a newer version (format {})", clip.version));
+    }
+    let mut config = clip.slot;
+    config.group = None;
+    config.compile_error = None;
+    Ok(config)
+}
+
+/// Copy a slot to the system clipboard.
+pub fn copy(ctx: &egui::Context, state: &EditorState, idx: usize) {
+    let Some(config) = state.plugin_state.lock().ok().and_then(|ps| ps.slot_configs.get(idx).cloned()) else {
+        return;
+    };
+    ctx.copy_text(to_json(&config));
+    if let Ok(mut st) = state.status_text.lock() {
+        *st = format!("Slot {} copied", idx + 1);
+    }
+}
+
+/// Replace a slot with a pasted config and bring the audio thread, preset
+/// and runner program in line with it. The slot keeps its group.
+pub fn paste(state: &mut EditorState, idx: usize, config: SlotConfig) {
+    if apply(state, idx, config) {
+        if let Ok(mut st) = state.status_text.lock() {
+            *st = format!("Pasted into slot {}", idx + 1);
+        }
+    }
+}
+
+/// Replace a slot's config and bring the audio thread, preset and runner
+/// program in line with it (see `paste`). Returns false if there is no
+/// such slot.
+pub fn apply(state: &mut EditorState, idx: usize, mut config: SlotConfig) -> bool {
+    let previous = {
+        let Ok(mut ps) = state.plugin_state.lock() else { return false };
+        let Some(target) = ps.slot_configs.get_mut(idx) else { return false };
+        config.group = target.group;
+        std::mem::replace(target, config.clone())
+    };
+
+    for event in crate::view_model::sync_slot(idx, &config) {
+        let _ = state.event_tx.try_send(event);
+    }
+
+    match config.preset_id.as_deref().and_then(|id| id.split_once('/')) {
+        Some((library, path)) if config.preset_id != previous.preset_id => {
+            state.loads.request(
+                &state.jobs,
+                &state.preset_manager,
+                LoadTarget::Slot(idx),
+                library,
+                path,
+                idx,
+                None,
+            );
+        }
+        None if previous.preset_id.is_some() => {
+            state.active_presets_ui.remove(&idx);
+            let _ = state.event_tx.try_send(EditorEvent::UnloadPreset { slot_index: idx });
+        }
+        _ => {}
+    }
+    if config.source_code != previous.source_code {
+        state.compile_state.schedule(idx);
+    }
+    true
+}
+
+/// Paste into the selected slot, or a new one if the rack is empty.
+fn paste_selected(state: &mut EditorState, config: SlotConfig) {
+    let selected = state.slot_rack_state.selected_slot;
+    let idx = match state.plugin_state.lock() {
+        Ok(ps) if selected < ps.slot_configs.len() => selected,
+        Ok(mut ps) if ps.slot_configs.is_empty() => ps.add_slot_config(SlotConfig::default()),
+        _ => return,
+    };
+    state.slot_rack_state.selected_slot = idx;
+    paste(state, idx, config);
+}
+
+/// Ctrl+C / Ctrl+V on the selected slot while the rack has keyboard focus
+/// and no widget owns the keyboard.
+pub fn handle_shortcuts(ctx: &egui::Context, state: &mut EditorState) {
+    if state.focus.panel != FocusPanel::Rack || ctx.memory(|m| m.focused().is_some()) {
+        return;
+    }
+    let (copied, pasted) = ctx.input(|i| {
+        let copied = i.events.iter().any(|e| matches!(e, egui::Event::Copy));
+        let pasted = i.events.iter().find_map(|e| match e {
+            egui::Event::Paste(text) => Some(text.clone()),
+            _ => None,
+        });
+        (copied, pasted)
+    });
+    if copied {
+        copy(ctx, state, state.slot_rack_state.selected_slot);
+    }
+    if let Some(text) = pasted {
+        match from_json(&text) {
+            Ok(config) => paste_selected(state, config),
+            Err(e) => {
+                if let Ok(mut st) = state.status_text.lock() {
+                    *st = e;
+                }
+            }
+        }
+    }
+}
+
+/// Open the paste window for a slot.
+pub fn open_paste(state: &mut EditorState, idx: usize) {
+    state.slot_clipboard = SlotClipboardState {
+        target: Some(idx),
+        ..Default::default()
+    };
+}
+
+/// The paste window, if open: a text box to paste a copied slot into.
+pub fn draw(ctx: &egui::Context, state: &mut EditorState) {
+    let Some(idx) = state.slot_clipboard.target else { return };
+
+    let mut open = true;
+    let mut pasted = None;
+    egui::Window::new(format!("Paste into slot {}", idx + 1))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            let clip = &mut state.slot_clipboard;
+            ui.label(egui::RichText::new("Press Ctrl+V to paste a copied slot.").color(colors::subtext0()));
+            let response = ui.add(
+                egui::TextEdit::multiline(&mut clip.text)
+                    .desired_rows(6)
+                    .desired_width(320.0)
+                    .code_editor()
+                    .hint_text("{ \"format\": \"songwalker-slot\", … }"),
+            );
+            if clip.text.is_empty() && !response.has_focus() {
+                response.request_focus();
+            }
+            if response.changed() {
+                match from_json(&clip.text) {
+                    Ok(config) => pasted = Some(config),
+                    Err(e) => clip.error = Some(e),
+                }
+            }
+            if let Some(e) = &clip.error {
+                ui.label(egui::RichText::new(e).color(colors::red()).small());
+            }
+        });
+
+    if let Some(config) = pasted {
+        paste(state, idx, config);
+        open = false;
+    }
+    if !open {
+        state.slot_clipboard = SlotClipboardState::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_drops_group() {
+        let mut config = SlotConfig::new_preset("Piano", "FluidR3_GM/piano/preset.json");
+        config.volume = 0.5;
+        config.hold = true;
+        config.source_code = "C4 /4".into();
+        config.group = Some(2);
+        config.compile_error = Some("stale".into());
+
+        let pasted = from_json(&to_json(&config)).unwrap();
+        assert_eq!(pasted.preset_id.as_deref(), Some("FluidR3_GM/piano/preset.json"));
+        assert_eq!(pasted.volume, 0.5);
+        assert!(pasted.hold);
+        assert_eq!(pasted.source_code, "C4 /4");
+        assert_eq!(pasted.group, None);
+        assert_eq!(pasted.compile_error, None);
+    }
+
+    #[test]
+    fn test_rejects_other_text() {
+        assert!(from_json("hello").is_err());
+        assert!(from_json(r#"{"name": "Piano"}"#).is_err(), "a bare config has no format tag");
+        let other = to_json(&SlotConfig::default()).replace(FORMAT, "something-else");
+        assert!(from_json(&other).is_err());
+        let newer = to_json(&SlotConfig::default()).replace("\"version\": 1", "\"version\": 99");
+        assert!(from_json(&newer).unwrap_err().contains("newer version"));
+    }
+}
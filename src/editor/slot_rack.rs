@@ -1,9 +1,23 @@
 use nih_plug_egui::egui;
 
-use super::colors;
-use super::zs;
-use super::EditorState;
-use crate::state::SlotConfig;
+use super::code_editor;
+use super::compile::CompileStatus;
+use super::loads::LoadTarget;
+use super::{accessibility, colors, focus};
+use super::focus::FocusPanel;
+use super::slot_actions;
+use super::{fs, zs};
+use super::{EditorEvent, EditorState};
+use crate::preset::memory;
+use crate::slots::arpeggiator::MAX_OCTAVES;
+use crate::slots::mod_matrix;
+use crate::slots::legato::{MAX_CROSSFADE_MS, MIN_CROSSFADE_MS};
+use crate::slots::unison::{MAX_DETUNE_CENTS, MAX_UNISON_VOICES};
+use crate::slots::{
+    ArpMode, Articulation, BendDestination, DEFAULT_POLYPHONY, ModDestination, PressureDestination, GroupBus, KeyswitchMap, LaunchQuantize, MAX_POLYPHONY, MidiFilter,
+    MidiFilterSettings, ModMatrix, ModSource, ModTarget, Oversampling, SlotOutput, SlotTuning, VoiceFilterSettings,
+};
+use crate::state::{SlotConfig, SlotGroup};
 
 /// Persistent state for the slot rack UI.
 #[derive(Default)]
@@ -12,6 +26,10 @@ pub struct SlotRackState {
     pub selected_slot: usize,
     /// Whether the code editor is expanded for the selected slot.
     pub editor_expanded: bool,
+    /// Slot whose title is being edited, with the text typed so far.
+    pub renaming: Option<(usize, String)>,
+    /// Whether the macro assignment list is shown.
+    pub macros_expanded: bool,
 }
 
 /// Draw the Kontakt-style slot rack.
@@ -20,61 +38,59 @@ pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
     ui.vertical(|ui| {
         ui.spacing_mut().item_spacing = egui::vec2(zs(6.0, z), zs(4.0, z));
 
-        // Header with "Add Slot" button
+        // Header with "Add Slot" / "Add Group" buttons
         ui.horizontal(|ui| {
             ui.label(
                 egui::RichText::new("Slot Rack")
-                    .color(colors::TEXT)
+                    .color(colors::text())
                     .strong()
-                    .size(zs(14.0, z)),
+                    .size(fs(14.0, z)),
             );
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 if ui
-                    .button(egui::RichText::new("+ Add Slot").color(colors::GREEN).size(zs(12.0, z)))
+                    .button(egui::RichText::new("+ Add Slot").color(colors::green()).size(fs(12.0, z)))
                     .clicked()
                 {
                     if let Ok(mut ps) = state.plugin_state.lock() {
                         ps.add_slot_config(SlotConfig::default());
                     }
                 }
+                if ui
+                    .button(egui::RichText::new("+ Add Group").color(colors::mauve()).size(fs(12.0, z)))
+                    .clicked()
+                {
+                    if let Ok(mut ps) = state.plugin_state.lock() {
+                        let name = format!("Group {}", ps.groups.len() + 1);
+                        ps.add_group(&name);
+                    }
+                }
+                super::surprise::draw_button(ui, state, z);
             });
         });
 
         ui.separator();
 
-        // Slot list
+        // Slot list: grouped slots under their group header, then ungrouped slots
         egui::ScrollArea::vertical()
             .auto_shrink([false, false])
             .show(ui, |ui| {
-                let slot_count = if let Ok(ps) = state.plugin_state.lock() {
-                    ps.slot_configs.len()
+                let (slot_count, group_count) = if let Ok(ps) = state.plugin_state.lock() {
+                    (ps.slot_configs.len(), ps.groups.len())
                 } else {
-                    0
+                    (0, 0)
                 };
 
-                for idx in 0..slot_count {
-                    let is_selected = state.slot_rack_state.selected_slot == idx;
+                for group_idx in 0..group_count {
+                    draw_group(ui, state, group_idx, z);
+                }
 
-                    egui::Frame::NONE
-                        .fill(if is_selected {
-                            colors::MANTLE
-                        } else {
-                            colors::CRUST
-                        })
-                        .inner_margin(egui::Margin::symmetric(zs(10.0, z) as i8, zs(6.0, z) as i8))
-                        .outer_margin(egui::Margin::symmetric(0, 1))
-                        .corner_radius(zs(4.0, z))
-                        .stroke(egui::Stroke::new(
-                            1.0,
-                            if is_selected {
-                                colors::BLUE
-                            } else {
-                                colors::SURFACE0
-                            },
-                        ))
-                        .show(ui, |ui| {
-                            draw_slot_strip(ui, state, idx, z);
-                        });
+                let ungrouped = if let Ok(ps) = state.plugin_state.lock() {
+                    ps.slots_in_group(None)
+                } else {
+                    Vec::new()
+                };
+                for idx in ungrouped {
+                    draw_slot_frame(ui, state, idx, z);
                 }
 
                 if slot_count == 0 {
@@ -83,7 +99,7 @@ pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
                             egui::RichText::new(
                                 "No slots. Click '+ Add Slot' to get started.",
                             )
-                            .color(colors::OVERLAY0)
+                            .color(colors::overlay0())
                             .italics(),
                         );
                     });
@@ -92,6 +108,226 @@ pub fn draw(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
     });
 }
 
+/// Draw a group header (collapse, name, volume, mute/solo) and its member slots.
+fn draw_group(ui: &mut egui::Ui, state: &mut EditorState, group_idx: usize, z: f32) {
+    let (group, members) = if let Ok(ps) = state.plugin_state.lock() {
+        match ps.groups.get(group_idx) {
+            Some(g) => (g.clone(), ps.slots_in_group(Some(group_idx))),
+            None => return,
+        }
+    } else {
+        return;
+    };
+
+    let mut changed = false;
+    let mut removed = false;
+    let mut edited = group.clone();
+
+    egui::Frame::NONE
+        .fill(colors::surface0().gamma_multiply(0.4))
+        .inner_margin(egui::Margin::symmetric(zs(8.0, z) as i8, zs(4.0, z) as i8))
+        .outer_margin(egui::Margin::symmetric(0, 1))
+        .corner_radius(zs(4.0, z))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                let chevron = if edited.collapsed { "\u{25B8}" } else { "\u{25BE}" };
+                if ui
+                    .add(egui::Label::new(
+                        egui::RichText::new(chevron)
+                            .color(colors::subtext0())
+                            .size(fs(12.0, z))
+                            .family(egui::FontFamily::Monospace),
+                    ).sense(egui::Sense::click()))
+                    .clicked()
+                {
+                    edited.collapsed = !edited.collapsed;
+                    changed = true;
+                }
+
+                let name_edit = ui.add(
+                    egui::TextEdit::singleline(&mut edited.name)
+                        .desired_width(zs(90.0, z))
+                        .font(egui::TextStyle::Body),
+                );
+                if name_edit.changed() {
+                    changed = true;
+                }
+
+                ui.label(
+                    egui::RichText::new(format!("({})", members.len()))
+                        .color(colors::overlay0())
+                        .size(fs(10.0, z)),
+                );
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let remove = ui
+                        .button(egui::RichText::new("\u{2715}").color(colors::red()).size(fs(11.0, z)))
+                        .on_hover_text("Remove group (slots are kept)");
+                    accessibility::name(&remove, egui::WidgetType::Button, format!("Remove group {}", edited.name));
+                    if remove.clicked() {
+                        removed = true;
+                    }
+
+                    let solo_color = if edited.solo { colors::yellow() } else { colors::overlay0() };
+                    let solo = ui.button(egui::RichText::new("S").color(solo_color).size(fs(11.0, z)));
+                    accessibility::name_toggle(&solo, format!("Group {} solo", edited.name), edited.solo);
+                    if solo.clicked() {
+                        edited.solo = !edited.solo;
+                        changed = true;
+                    }
+
+                    let mute_color = if edited.muted { colors::red() } else { colors::overlay0() };
+                    let mute = ui.button(egui::RichText::new("M").color(mute_color).size(fs(11.0, z)));
+                    accessibility::name_toggle(&mute, format!("Group {} mute", edited.name), edited.muted);
+                    if mute.clicked() {
+                        edited.muted = !edited.muted;
+                        changed = true;
+                    }
+
+                    let volume = ui
+                        .add(egui::Slider::new(&mut edited.volume, 0.0..=1.5).show_value(false))
+                        .on_hover_text("Group volume");
+                    accessibility::name(
+                        &volume,
+                        egui::WidgetType::Slider,
+                        format!("Group {} volume, {}", edited.name, accessibility::gain_text(edited.volume)),
+                    );
+                    if volume.changed() {
+                        changed = true;
+                    }
+                });
+            });
+
+            if !edited.collapsed {
+                for &idx in &members {
+                    draw_slot_frame(ui, state, idx, z);
+                }
+            }
+        });
+
+    if removed {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            ps.remove_group(group_idx);
+        }
+        sync_groups_to_audio(state);
+    } else if changed {
+        update_group(state, group_idx, &edited);
+    }
+}
+
+/// Store an edited group and push its bus settings to the audio thread.
+pub(super) fn update_group(state: &EditorState, group_idx: usize, edited: &SlotGroup) {
+    if let Ok(mut ps) = state.plugin_state.lock() {
+        if let Some(g) = ps.groups.get_mut(group_idx) {
+            *g = edited.clone();
+        }
+    }
+    let _ = state.event_tx.try_send(EditorEvent::SetGroup {
+        group_index: group_idx,
+        bus: GroupBus {
+            volume: edited.volume,
+            muted: edited.muted,
+            solo: edited.solo,
+        },
+    });
+}
+
+/// Push every group bus and slot→group assignment to the audio thread.
+///
+/// Used after structural changes (group removal) that shift group indices.
+fn sync_groups_to_audio(state: &EditorState) {
+    let Ok(ps) = state.plugin_state.lock() else { return };
+    for event in crate::view_model::sync_groups(&ps.groups) {
+        let _ = state.event_tx.try_send(event);
+    }
+    for (slot_index, config) in ps.slot_configs.iter().enumerate() {
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotGroup {
+            slot_index,
+            group: config.group,
+        });
+    }
+}
+
+/// Draw the framed slot strip for one slot.
+fn draw_slot_frame(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, z: f32) {
+    let is_selected = state.slot_rack_state.selected_slot == idx;
+    // The selection is outlined more heavily while the rack has keyboard focus
+    let stroke_width = if is_selected && state.focus.panel == FocusPanel::Rack { 2.0 } else { 1.0 };
+
+    egui::Frame::NONE
+        .fill(if is_selected {
+            colors::mantle()
+        } else {
+            colors::crust()
+        })
+        .inner_margin(egui::Margin::symmetric(zs(10.0, z) as i8, zs(6.0, z) as i8))
+        .outer_margin(egui::Margin::symmetric(0, 1))
+        .corner_radius(zs(4.0, z))
+        .stroke(egui::Stroke::new(
+            stroke_width,
+            if is_selected {
+                colors::blue()
+            } else {
+                colors::surface0()
+            },
+        ))
+        .show(ui, |ui| {
+            draw_slot_strip(ui, state, idx, z);
+        });
+}
+
+/// The slot's name, or the rename box while it is being edited. Enter or
+/// clicking away keeps the new name (blank for the preset's), Escape
+/// cancels.
+fn draw_slot_title(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let renaming = match &mut state.slot_rack_state.renaming {
+        Some((slot, text)) if *slot == idx => Some(text),
+        _ => None,
+    };
+    if let Some(text) = renaming {
+        let edit = ui.add(
+            egui::TextEdit::singleline(text)
+                .font(egui::FontId::proportional(fs(13.0, z)))
+                .desired_width(zs(160.0, z))
+                .hint_text(config.display_name()),
+        );
+        if !edit.has_focus() && !edit.lost_focus() {
+            edit.request_focus();
+        }
+        if edit.lost_focus() {
+            let name = text.clone();
+            state.slot_rack_state.renaming = None;
+            if !ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                slot_actions::rename(state, idx, &name);
+            }
+        }
+        return;
+    }
+
+    let title = ui
+        .add(
+            egui::Label::new(
+                egui::RichText::new(config.display_name()).color(colors::text()).strong().size(fs(13.0, z)),
+            )
+            .sense(egui::Sense::click()),
+        )
+        .on_hover_text("Double-click to rename");
+    if title.double_clicked() {
+        slot_actions::start_rename(state, idx, config);
+    } else if title.clicked() {
+        select_slot(state, idx, config);
+    }
+}
+
+/// Select a slot; clicking an empty one opens the quick-load popup.
+fn select_slot(state: &mut EditorState, idx: usize, config: &SlotConfig) {
+    state.slot_rack_state.selected_slot = idx;
+    state.focus.panel = FocusPanel::Rack;
+    if config.preset_id.is_none() && config.source_code.is_empty() {
+        super::quick_load::open(state, idx);
+    }
+}
+
 /// Draw a single slot strip (one row in the rack).
 fn draw_slot_strip(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, z: f32) {
     let slot_config = if let Ok(ps) = state.plugin_state.lock() {
@@ -106,86 +342,204 @@ fn draw_slot_strip(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, z: f3
     let max_width = ui.available_width();
     ui.set_max_width(max_width);
 
-    // Click to select
+    // Click to select, right-click for the slot menu. The strip senses
+    // clicks behind its buttons, which keep theirs.
     let response = ui
-        .horizontal(|ui| {
-            // Slot number
-            ui.label(
-                egui::RichText::new(format!("{}.", idx + 1))
-                    .color(colors::OVERLAY0)
-                    .strong()
-                    .size(zs(12.0, z)),
-            );
+        .scope_builder(egui::UiBuilder::new().sense(egui::Sense::click()), |ui| {
+            ui.horizontal(|ui| {
+                // Slot number
+                ui.label(
+                    egui::RichText::new(format!("{}.", idx + 1))
+                        .color(colors::overlay0())
+                        .strong()
+                        .size(fs(12.0, z)),
+                );
 
-            // Slot name / preset name
-            let name = if let Some(ref preset_id) = config.preset_id {
-                preset_id.clone()
-            } else if !config.source_code.is_empty() {
-                "Source".to_string()
-            } else {
-                "Empty".to_string()
-            };
-
-            ui.label(egui::RichText::new(&name).color(colors::TEXT).strong().size(zs(13.0, z)));
+                // Slot name (double-click to rename)
+                draw_slot_title(ui, state, idx, &config, z);
 
-            // MIDI channel
-            let ch_text = if config.midi_channel == 0 {
-                "All".to_string()
-            } else {
-                format!("Ch:{}", config.midi_channel)
-            };
-            ui.label(egui::RichText::new(ch_text).color(colors::SUBTEXT0).size(zs(10.0, z)));
-
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                // Remove button
-                if ui
-                    .button(egui::RichText::new("\u{2715}").color(colors::RED).size(zs(11.0, z)))
-                    .clicked()
-                {
-                    if let Ok(mut ps) = state.plugin_state.lock() {
-                        ps.remove_slot_config(idx);
-                    }
+                // Sample memory of the loaded preset
+                let loaded_bytes = state
+                    .active_presets_ui
+                    .get(&idx)
+                    .map(|(_, instance)| memory::preset_bytes(instance));
+                if let Some(bytes) = loaded_bytes {
+                    ui.label(
+                        egui::RichText::new(memory::format_bytes(bytes))
+                            .color(colors::subtext0())
+                            .size(fs(10.0, z)),
+                    );
                 }
 
-                // Solo button
-                let solo_color = if config.solo {
-                    colors::YELLOW
+                // MIDI channel
+                let ch_text = if config.midi_channel == 0 {
+                    "All".to_string()
                 } else {
-                    colors::OVERLAY0
+                    format!("Ch:{}", config.midi_channel)
                 };
-                if ui
-                    .button(egui::RichText::new("S").color(solo_color).size(zs(11.0, z)))
-                    .clicked()
-                {
-                    if let Ok(mut ps) = state.plugin_state.lock() {
-                        if let Some(cfg) = ps.slot_configs.get_mut(idx) {
-                            cfg.solo = !cfg.solo;
-                        }
-                    }
+                ui.label(egui::RichText::new(ch_text).color(colors::subtext0()).size(fs(10.0, z)));
+
+                // Active articulation
+                let articulation = state
+                    .monitor
+                    .articulation(idx)
+                    .and_then(|key| config.articulations.iter().find(|a| a.key == key));
+                if let Some(a) = articulation {
+                    ui.label(egui::RichText::new(&a.name).color(colors::peach()).size(fs(10.0, z)))
+                        .on_hover_text(format!("Articulation (keyswitch {})", note_name(a.key)));
                 }
 
-                // Mute button
-                let mute_color = if config.muted {
-                    colors::RED
-                } else {
-                    colors::OVERLAY0
-                };
-                if ui
-                    .button(egui::RichText::new("M").color(mute_color).size(zs(11.0, z)))
-                    .clicked()
-                {
-                    if let Ok(mut ps) = state.plugin_state.lock() {
-                        if let Some(cfg) = ps.slot_configs.get_mut(idx) {
-                            cfg.muted = !cfg.muted;
+                // Quantized runner launch countdown (beats to go)
+                if let Some(beats) = state.monitor.launch_countdown(idx) {
+                    ui.label(
+                        egui::RichText::new(format!("\u{23F5} {}", beats.ceil().max(1.0) as u32))
+                            .color(colors::yellow())
+                            .size(fs(10.0, z)),
+                    )
+                    .on_hover_text(format!("Launching on the next {}", config.launch_quantize.label().to_lowercase()));
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    // Remove button
+                    let remove = ui.button(egui::RichText::new("\u{2715}").color(colors::red()).size(fs(11.0, z)));
+                    accessibility::name(&remove, egui::WidgetType::Button, format!("Remove slot {}", idx + 1));
+                    if remove.clicked() {
+                        slot_actions::remove(state, idx);
+                    }
+
+                    // Copy / paste the slot as JSON
+                    let paste = ui
+                        .small_button(egui::RichText::new("Paste\u{2026}").color(colors::subtext0()).size(fs(10.0, z)))
+                        .on_hover_text("Replace this slot with one copied from the clipboard");
+                    accessibility::name(&paste, egui::WidgetType::Button, format!("Paste into slot {}", idx + 1));
+                    if paste.clicked() {
+                        super::slot_clipboard::open_paste(state, idx);
+                    }
+                    let copy = ui
+                        .small_button(egui::RichText::new("Copy").color(colors::subtext0()).size(fs(10.0, z)))
+                        .on_hover_text("Copy this slot's settings to the clipboard (Ctrl+C)");
+                    accessibility::name(&copy, egui::WidgetType::Button, format!("Copy slot {}", idx + 1));
+                    if copy.clicked() {
+                        super::slot_clipboard::copy(ui.ctx(), state, idx);
+                    }
+
+                    // Unload (keeps the config) / reload the slot's preset
+                    if let Some(ref preset_id) = config.preset_id {
+                        if loaded_bytes.is_some() {
+                            if ui
+                                .small_button(egui::RichText::new("Unload").color(colors::subtext0()).size(fs(10.0, z)))
+                                .on_hover_text("Free this preset's samples; it can be reloaded later")
+                                .clicked()
+                            {
+                                slot_actions::unload(state, idx);
+                            }
+                        } else if state.loads.is_loading(preset_id) {
+                            ui.label(egui::RichText::new("Loading\u{2026}").color(colors::teal()).size(fs(10.0, z)));
+                        } else if let Some((library, path)) = preset_id.split_once('/') {
+                            if ui
+                                .small_button(egui::RichText::new("Reload").color(colors::green()).size(fs(10.0, z)))
+                                .on_hover_text("Load this slot's preset again")
+                                .clicked()
+                            {
+                                state.loads.request(
+                                    &state.jobs,
+                                    &state.preset_manager,
+                                    LoadTarget::Slot(idx),
+                                    library,
+                                    path,
+                                    idx,
+                                    None,
+                                );
+                            }
                         }
                     }
-                }
-            });
+
+                    // Solo button
+                    let solo_color = if config.solo {
+                        colors::yellow()
+                    } else {
+                        colors::overlay0()
+                    };
+                    let solo = ui.button(egui::RichText::new("S").color(solo_color).size(fs(11.0, z)));
+                    accessibility::name_toggle(&solo, format!("Slot {} solo", idx + 1), config.solo);
+                    if solo.clicked() {
+                        slot_actions::toggle_solo(state, idx);
+                    }
+
+                    // Mute button
+                    let mute_color = if config.muted {
+                        colors::red()
+                    } else {
+                        colors::overlay0()
+                    };
+                    let mute = ui.button(egui::RichText::new("M").color(mute_color).size(fs(11.0, z)));
+                    accessibility::name_toggle(&mute, format!("Slot {} mute", idx + 1), config.muted);
+                    if mute.clicked() {
+                        slot_actions::toggle_mute(state, idx);
+                    }
+
+                    // Clip LED: lit once the slot output went over 0 dBFS, click to clear
+                    let clipped = state.monitor.clipped(idx);
+                    let peak = state.monitor.output_peak(idx);
+                    let led_color = if clipped {
+                        colors::red()
+                    } else if peak > 0.0 {
+                        colors::green().gamma_multiply(0.3 + 0.7 * peak.min(1.0))
+                    } else {
+                        colors::surface1()
+                    };
+                    let led_size = zs(8.0, z);
+                    let (led_rect, led) = ui.allocate_exact_size(egui::vec2(led_size, led_size), egui::Sense::click());
+                    ui.painter().circle_filled(led_rect.center(), led_size * 0.5, led_color);
+                    let led = led.on_hover_text(if clipped {
+                        "Slot output clipped \u{2014} click to clear"
+                    } else {
+                        "Slot output level"
+                    });
+                    let spoken = if clipped { "clipped, activate to clear" } else { "not clipped" };
+                    accessibility::name(&led, egui::WidgetType::Button, format!("Slot {} clip indicator, {}", idx + 1, spoken));
+                    if led.clicked() {
+                        state.monitor.clear_clip(idx);
+                    }
+
+                    // Live mod wheel / expression
+                    let (mod_wheel, expression) = state.monitor.controllers(idx);
+                    let bar = egui::vec2(zs(3.0, z), zs(12.0, z));
+                    let (cc_rect, cc) = ui.allocate_exact_size(egui::vec2(bar.x * 2.0 + zs(2.0, z), bar.y), egui::Sense::hover());
+                    for (i, (value, color)) in [(mod_wheel, colors::mauve()), (expression, colors::teal())].into_iter().enumerate() {
+                        let x = cc_rect.left() + i as f32 * (bar.x + zs(2.0, z));
+                        let track = egui::Rect::from_min_size(egui::pos2(x, cc_rect.top()), bar);
+                        ui.painter().rect_filled(track, 1.0, colors::surface1());
+                        let fill = egui::Rect::from_min_max(
+                            egui::pos2(x, track.bottom() - bar.y * value.clamp(0.0, 1.0)),
+                            track.right_bottom(),
+                        );
+                        ui.painter().rect_filled(fill, 1.0, color);
+                    }
+                    cc.on_hover_text(format!(
+                        "Mod wheel {} · Expression {}",
+                        (mod_wheel * 127.0).round() as u8,
+                        (expression * 127.0).round() as u8
+                    ));
+                });
+            })
         })
         .response;
+    let mut spoken = format!("Slot {}: {}", idx + 1, config.display_name());
+    if config.muted {
+        spoken.push_str(", muted");
+    }
+    if config.solo {
+        spoken.push_str(", soloed");
+    }
+    accessibility::name(&response, egui::WidgetType::SelectableLabel, spoken);
+    if state.slot_rack_state.selected_slot == idx && state.focus.panel == FocusPanel::Rack {
+        focus::anchor(&mut state.focus, &response);
+    }
 
+    slot_actions::context_menu(&response, state, idx, &config);
     if response.clicked() {
-        state.slot_rack_state.selected_slot = idx;
+        select_slot(state, idx, &config);
     }
 
     // --- Expanded controls for selected slot ---
@@ -193,37 +547,113 @@ fn draw_slot_strip(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, z: f3
         ui.separator();
 
         ui.horizontal(|ui| {
-            ui.label(egui::RichText::new("Vol:").color(colors::SUBTEXT0).size(zs(11.0, z)));
+            ui.label(egui::RichText::new("Vol:").color(colors::subtext0()).size(fs(11.0, z)));
             let mut vol = config.volume;
-            if ui
-                .add(egui::Slider::new(&mut vol, 0.0..=1.5).show_value(false))
-                .changed()
-            {
-                if let Ok(mut ps) = state.plugin_state.lock() {
-                    if let Some(cfg) = ps.slot_configs.get_mut(idx) {
-                        cfg.volume = vol;
-                    }
-                }
+            let volume = ui.add(egui::Slider::new(&mut vol, 0.0..=1.5).show_value(false));
+            accessibility::name(
+                &volume,
+                egui::WidgetType::Slider,
+                format!("Slot {} volume, {}", idx + 1, accessibility::gain_text(vol)),
+            );
+            if volume.changed() {
+                slot_actions::set_mix(state, idx, vol, config.pan);
             }
 
-            ui.label(egui::RichText::new("Pan:").color(colors::SUBTEXT0).size(zs(11.0, z)));
+            ui.label(egui::RichText::new("Pan:").color(colors::subtext0()).size(fs(11.0, z)));
             let mut pan = config.pan;
-            if ui
-                .add(egui::Slider::new(&mut pan, -1.0..=1.0).show_value(false))
-                .changed()
-            {
+            let pan_slider = ui.add(egui::Slider::new(&mut pan, -1.0..=1.0).show_value(false));
+            accessibility::name(
+                &pan_slider,
+                egui::WidgetType::Slider,
+                format!("Slot {} pan, {}", idx + 1, accessibility::pan_text(pan)),
+            );
+            if pan_slider.changed() {
+                slot_actions::set_mix(state, idx, config.volume, pan);
+            }
+        });
+
+        // Group assignment
+        let group_names: Vec<String> = if let Ok(ps) = state.plugin_state.lock() {
+            ps.groups.iter().map(|g| g.name.clone()).collect()
+        } else {
+            Vec::new()
+        };
+        if !group_names.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Group:").color(colors::subtext0()).size(fs(11.0, z)));
+                let current = config
+                    .group
+                    .and_then(|g| group_names.get(g).cloned())
+                    .unwrap_or_else(|| "None".to_string());
+                let mut selected = config.group;
+                egui::ComboBox::from_id_salt(("slot_group_combo", idx))
+                    .selected_text(current)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut selected, None, "None");
+                        for (gi, name) in group_names.iter().enumerate() {
+                            ui.selectable_value(&mut selected, Some(gi), name);
+                        }
+                    });
+                if selected != config.group {
+                    slot_actions::set_group(state, idx, selected);
+                }
+            });
+        }
+
+        // Runner MIDI output to the host
+        ui.horizontal(|ui| {
+            let mut enabled = config.midi_out_channel.is_some();
+            let mut channel = config.midi_out_channel.unwrap_or(0);
+            ui.checkbox(
+                &mut enabled,
+                egui::RichText::new("MIDI out").color(colors::subtext0()).size(fs(11.0, z)),
+            )
+            .on_hover_text("Send notes played by this slot's .sw source to the host");
+            ui.add_enabled_ui(enabled, |ui| {
+                egui::ComboBox::from_id_salt(("slot_midi_out_combo", idx))
+                    .selected_text(format!("Ch {}", channel + 1))
+                    .width(zs(60.0, z))
+                    .show_ui(ui, |ui| {
+                        for ch in 0..16u8 {
+                            ui.selectable_value(&mut channel, ch, format!("Ch {}", ch + 1));
+                        }
+                    });
+            });
+            let selected = enabled.then_some(channel);
+            if selected != config.midi_out_channel {
                 if let Ok(mut ps) = state.plugin_state.lock() {
                     if let Some(cfg) = ps.slot_configs.get_mut(idx) {
-                        cfg.pan = pan;
+                        cfg.midi_out_channel = selected;
                     }
                 }
+                let _ = state.event_tx.try_send(EditorEvent::SetSlotMidiOut {
+                    slot_index: idx,
+                    channel: selected,
+                });
             }
         });
 
+        draw_polyphony_controls(ui, state, idx, &config, z);
+        draw_hold_controls(ui, state, idx, &config, z);
+        super::freeze::draw_controls(ui, state, idx, &config, z);
+        super::purge::draw_controls(ui, state, idx, z);
+        draw_arp_controls(ui, state, idx, &config, z);
+        draw_tuning_controls(ui, state, idx, &config, z);
+        draw_unison_controls(ui, state, idx, &config, z);
+        draw_legato_controls(ui, state, idx, &config, z);
+        draw_bend_controls(ui, state, idx, &config, z);
+        draw_controller_controls(ui, state, idx, &config, z);
+        draw_mod_matrix_controls(ui, state, idx, &config, z);
+        draw_filter_controls(ui, state, idx, &config, z);
+        draw_output_controls(ui, state, idx, &config, z);
+        draw_oversampling_controls(ui, state, idx, &config, z);
+        draw_midi_filter_controls(ui, state, idx, &config, z);
+        draw_articulation_controls(ui, state, idx, &config, z);
+
         ui.separator();
 
         ui.horizontal(|ui| {
-            ui.label(egui::RichText::new("Root Note:").color(colors::SUBTEXT0).size(11.0));
+            ui.label(egui::RichText::new("Root Note:").color(colors::subtext0()).size(11.0));
             let mut root = config.root_note as i32;
             if ui
                 .add(egui::Slider::new(&mut root, 0..=127))
@@ -237,34 +667,719 @@ fn draw_slot_strip(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, z: f3
             }
             ui.label(
                 egui::RichText::new(note_name(config.root_note))
-                    .color(colors::TEAL)
-                    .size(zs(11.0, z)),
+                    .color(colors::teal())
+                    .size(fs(11.0, z)),
             );
         });
 
         // Code editor (always available, like the web editor)
         let mut source = config.source_code.clone();
-        let response = ui.add(
-            egui::TextEdit::multiline(&mut source)
-                .font(egui::TextStyle::Monospace)
-                .desired_rows(6)
-                .desired_width(ui.available_width())
-                .code_editor(),
+        let changed = code_editor::draw(
+            ui,
+            ("slot_code_editor", idx),
+            &mut source,
+            config.compile_error.as_deref(),
+            state.monitor.play_event(idx),
+            6,
+            z,
         );
 
-        if response.changed() {
+        if changed {
             if let Ok(mut ps) = state.plugin_state.lock() {
                 if let Some(cfg) = ps.slot_configs.get_mut(idx) {
                     cfg.source_code = source;
                 }
             }
+            state.compile_state.schedule(idx);
+        }
+
+        draw_compile_status(ui, state.compile_state.status(idx), z);
+
+        if !config.source_code.is_empty() {
+            draw_humanize_controls(ui, state, idx, &config, z);
+            draw_launch_controls(ui, state, idx, &config, z);
         }
 
         // Show compile error if any
         if let Some(ref err) = config.compile_error {
-            ui.label(egui::RichText::new(err).color(colors::RED).size(zs(11.0, z)));
+            ui.label(egui::RichText::new(err).color(colors::red()).size(fs(11.0, z)));
+        }
+    }
+}
+
+/// Hold (latch) toggle and its release button in the expanded slot view.
+/// Per-slot polyphony in the expanded slot view.
+fn draw_polyphony_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut voices = config.polyphony;
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Voices:").color(colors::subtext0()).size(fs(11.0, z)));
+        ui.add(egui::DragValue::new(&mut voices).range(1..=MAX_POLYPHONY as u16))
+            .on_hover_text("Notes this slot can play at once, up to Max Voices in Settings");
+        if voices != DEFAULT_POLYPHONY as u16 && ui.small_button("Reset").clicked() {
+            voices = DEFAULT_POLYPHONY as u16;
+        }
+    });
+
+    if voices != config.polyphony {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.polyphony = voices;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotPolyphony { slot_index: idx, voices: voices as usize });
+    }
+}
+
+fn draw_hold_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut hold = config.hold;
+    ui.horizontal(|ui| {
+        ui.checkbox(
+            &mut hold,
+            egui::RichText::new("Hold").color(colors::subtext0()).size(fs(11.0, z)),
+        )
+        .on_hover_text("Keys toggle notes on and off instead of playing while held");
+        if ui
+            .add_enabled(hold, egui::Button::new(egui::RichText::new("Release held").size(fs(11.0, z))))
+            .on_hover_text("Stop every note latched by hold")
+            .clicked()
+        {
+            let _ = state.event_tx.try_send(EditorEvent::ReleaseHeldNotes { slot_index: idx });
+        }
+    });
+
+    if hold != config.hold {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.hold = hold;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotHold { slot_index: idx, hold });
+    }
+}
+
+/// Tempo-synced arpeggiator rates: (label, beats per step).
+const ARP_RATES: [(&str, f64); 6] = [
+    ("1/4", 1.0),
+    ("1/8", 0.5),
+    ("1/8T", 1.0 / 3.0),
+    ("1/16", 0.25),
+    ("1/16T", 1.0 / 6.0),
+    ("1/32", 0.125),
+];
+
+/// Arpeggiator controls in the expanded slot view.
+fn draw_arp_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut arp = config.arp;
+
+    ui.horizontal(|ui| {
+        ui.checkbox(
+            &mut arp.enabled,
+            egui::RichText::new("Arp").color(colors::subtext0()).size(fs(11.0, z)),
+        );
+        ui.add_enabled_ui(arp.enabled, |ui| {
+            egui::ComboBox::from_id_salt(("slot_arp_mode", idx))
+                .selected_text(arp.mode.label())
+                .width(zs(80.0, z))
+                .show_ui(ui, |ui| {
+                    for mode in ArpMode::ALL {
+                        ui.selectable_value(&mut arp.mode, mode, mode.label());
+                    }
+                });
+            let rate_label = ARP_RATES
+                .iter()
+                .find(|(_, beats)| (beats - arp.rate).abs() < 1e-6)
+                .map(|(label, _)| *label)
+                .unwrap_or("?");
+            egui::ComboBox::from_id_salt(("slot_arp_rate", idx))
+                .selected_text(rate_label)
+                .width(zs(56.0, z))
+                .show_ui(ui, |ui| {
+                    for (label, beats) in ARP_RATES {
+                        ui.selectable_value(&mut arp.rate, beats, label);
+                    }
+                });
+            ui.checkbox(
+                &mut arp.latch,
+                egui::RichText::new("Latch").color(colors::subtext0()).size(fs(11.0, z)),
+            );
+        });
+    });
+
+    if arp.enabled {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Gate:").color(colors::subtext0()).size(fs(11.0, z)));
+            let mut gate_pct = arp.gate * 100.0;
+            if ui.add(egui::Slider::new(&mut gate_pct, 5.0..=100.0).suffix("%")).changed() {
+                arp.gate = gate_pct / 100.0;
+            }
+            ui.label(egui::RichText::new("Octaves:").color(colors::subtext0()).size(fs(11.0, z)));
+            ui.add(egui::Slider::new(&mut arp.octaves, 1..=MAX_OCTAVES));
+        });
+    }
+
+    if arp != config.arp {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.arp = arp;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotArp { slot_index: idx, settings: arp });
+    }
+}
+
+/// Coarse/fine tune and stereo width in the expanded slot view.
+fn draw_tuning_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut tuning = config.tuning;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Tune:").color(colors::subtext0()).size(fs(11.0, z)));
+        ui.add(egui::DragValue::new(&mut tuning.coarse).range(-24..=24).suffix(" st"));
+        ui.add(egui::DragValue::new(&mut tuning.fine).range(-100.0..=100.0).speed(0.5).suffix(" ct"));
+        ui.label(egui::RichText::new("Width:").color(colors::subtext0()).size(fs(11.0, z)));
+        let mut width_pct = tuning.width * 100.0;
+        if ui.add(egui::Slider::new(&mut width_pct, 0.0..=200.0).suffix("%")).changed() {
+            tuning.width = width_pct / 100.0;
+        }
+        if tuning != SlotTuning::default() && ui.small_button("Reset").clicked() {
+            tuning = SlotTuning::default();
+        }
+    });
+
+    if tuning != config.tuning {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.tuning = tuning;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotTuning { slot_index: idx, tuning });
+    }
+}
+
+/// Unison voices, detune and stereo spread in the expanded slot view.
+fn draw_unison_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut unison = config.unison;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Unison:").color(colors::subtext0()).size(fs(11.0, z)));
+        ui.add(egui::DragValue::new(&mut unison.voices).range(1..=MAX_UNISON_VOICES).suffix(" voices"))
+            .on_hover_text("Voices per note; each note uses this many voices of the slot's polyphony");
+        ui.add_enabled_ui(unison.is_active(), |ui| {
+            let detune = egui::DragValue::new(&mut unison.detune_cents).range(0.0..=MAX_DETUNE_CENTS).speed(0.5);
+            ui.add(detune.suffix(" ct")).on_hover_text("Detune of the outermost voices");
+            ui.label(egui::RichText::new("Spread:").color(colors::subtext0()).size(fs(11.0, z)));
+            let mut spread_pct = unison.spread * 100.0;
+            if ui.add(egui::Slider::new(&mut spread_pct, 0.0..=100.0).suffix("%")).changed() {
+                unison.spread = spread_pct / 100.0;
+            }
+        });
+    });
+
+    if unison != config.unison {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.unison = unison;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotUnison { slot_index: idx, unison });
+    }
+}
+
+/// Retrigger and legato crossfades in the expanded slot view.
+fn draw_legato_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut legato = config.legato;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Crossfade:").color(colors::subtext0()).size(fs(11.0, z)));
+        ui.checkbox(&mut legato.retrigger, "Repeats")
+            .on_hover_text("Crossfade a key played again while it still sounds");
+        ui.checkbox(&mut legato.legato, "Legato")
+            .on_hover_text("A key played while others are held takes over from them");
+        ui.add_enabled_ui(legato.is_active(), |ui| {
+            let range = MIN_CROSSFADE_MS..=MAX_CROSSFADE_MS;
+            ui.add(egui::DragValue::new(&mut legato.crossfade_ms).range(range).speed(0.2).suffix(" ms"))
+                .on_hover_text("Crossfade length");
+        });
+    });
+
+    if legato != config.legato {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.legato = legato;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotLegato { slot_index: idx, legato });
+    }
+}
+
+/// Pitch bend range override and destination in the expanded slot view.
+fn draw_bend_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut bend = config.bend;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Bend:").color(colors::subtext0()).size(fs(11.0, z)));
+        for option in BendDestination::ALL {
+            ui.radio_value(&mut bend.destination, option, option.label());
+        }
+    });
+    ui.horizontal(|ui| {
+        let mut own_range = bend.range.is_some();
+        if ui
+            .checkbox(
+                &mut own_range,
+                egui::RichText::new("Own range").color(colors::subtext0()).size(fs(11.0, z)),
+            )
+            .on_hover_text("Off: follow Pitch Bend Range in Settings")
+            .changed()
+        {
+            bend.range = own_range.then_some(2);
+        }
+        if let Some(range) = bend.range.as_mut() {
+            ui.add(egui::DragValue::new(range).range(1..=48).suffix(" st"));
+        }
+    });
+    if bend.destination == BendDestination::FilterCutoff && !config.filter.enabled {
+        ui.label(egui::RichText::new("Enable the filter for bend to move its cutoff").color(colors::overlay0()).size(fs(10.0, z)));
+    }
+
+    if bend != config.bend {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.bend = bend;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotBend { slot_index: idx, bend });
+    }
+}
+
+/// Mod wheel and aftertouch destinations and controller depths in the
+/// expanded slot view.
+fn draw_controller_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut controllers = config.controllers;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Mod wheel:").color(colors::subtext0()).size(fs(11.0, z)));
+        for option in ModDestination::ALL {
+            ui.radio_value(&mut controllers.mod_destination, option, option.label());
+        }
+        let mut depth_pct = controllers.mod_depth * 100.0;
+        if ui.add(egui::Slider::new(&mut depth_pct, 0.0..=100.0).suffix("%")).on_hover_text("Mod wheel depth").changed() {
+            controllers.mod_depth = depth_pct / 100.0;
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Expression:").color(colors::subtext0()).size(fs(11.0, z)))
+            .on_hover_text("How far CC11 can turn the slot down");
+        let mut depth_pct = controllers.expression_depth * 100.0;
+        if ui.add(egui::Slider::new(&mut depth_pct, 0.0..=100.0).suffix("%")).changed() {
+            controllers.expression_depth = depth_pct / 100.0;
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Aftertouch:").color(colors::subtext0()).size(fs(11.0, z)))
+            .on_hover_text("Channel pressure from the keyboard");
+        for option in PressureDestination::ALL {
+            ui.radio_value(&mut controllers.pressure_destination, option, option.label());
+        }
+        if controllers.pressure_destination != PressureDestination::Off {
+            let mut depth_pct = controllers.pressure_depth * 100.0;
+            if ui.add(egui::Slider::new(&mut depth_pct, 0.0..=100.0).suffix("%")).on_hover_text("Aftertouch depth").changed() {
+                controllers.pressure_depth = depth_pct / 100.0;
+            }
+        }
+    });
+    let cutoff_routed = controllers.mod_destination == ModDestination::FilterCutoff
+        || controllers.pressure_destination == PressureDestination::FilterCutoff;
+    if cutoff_routed && !config.filter.enabled {
+        ui.label(egui::RichText::new("Enable the filter for controllers to move its cutoff").color(colors::overlay0()).size(fs(10.0, z)));
+    }
+
+    if controllers != config.controllers {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.controllers = controllers;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotControllers { slot_index: idx, controllers });
+    }
+}
+
+/// Modulation matrix in the expanded slot view: a depth per source and
+/// target, and the rates of the matrix LFOs.
+fn draw_mod_matrix_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut matrix = config.mod_matrix;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Mod matrix:").color(colors::subtext0()).size(fs(11.0, z)))
+            .on_hover_text("Depth of each route, from −100% to 100%");
+        ui.label(egui::RichText::new("LFO 1").size(fs(10.0, z)));
+        ui.add(egui::DragValue::new(&mut matrix.lfo1_hz).range(0.0..=mod_matrix::MAX_LFO_HZ).speed(0.02).suffix(" Hz"));
+        ui.label(egui::RichText::new("LFO 2").size(fs(10.0, z)));
+        ui.add(egui::DragValue::new(&mut matrix.lfo2_hz).range(0.0..=mod_matrix::MAX_LFO_HZ).speed(0.02).suffix(" Hz"));
+        if matrix.is_active() && ui.small_button("Clear").clicked() {
+            matrix.depth = ModMatrix::default().depth;
         }
+    });
+    egui::Grid::new(("slot_mod_matrix", idx)).num_columns(ModTarget::ALL.len() + 1).show(ui, |ui| {
+        ui.label("");
+        for target in ModTarget::ALL {
+            ui.label(egui::RichText::new(target.label()).color(colors::subtext0()).size(fs(10.0, z)));
+        }
+        ui.end_row();
+        for source in ModSource::ALL {
+            ui.label(egui::RichText::new(source.label()).color(colors::subtext0()).size(fs(10.0, z)));
+            for target in ModTarget::ALL {
+                let mut pct = matrix.depth(source, target) * 100.0;
+                let color = if pct == 0.0 { colors::overlay0() } else { colors::text() };
+                ui.visuals_mut().override_text_color = Some(color);
+                if ui.add(egui::DragValue::new(&mut pct).range(-100.0..=100.0).speed(0.5).suffix("%")).changed() {
+                    matrix.set_depth(source, target, pct / 100.0);
+                }
+                ui.visuals_mut().override_text_color = None;
+            }
+            ui.end_row();
+        }
+    });
+    let cutoff_routed = ModSource::ALL.iter().any(|&s| matrix.depth(s, ModTarget::FilterCutoff) != 0.0);
+    if cutoff_routed && !config.filter.enabled {
+        ui.label(egui::RichText::new("Enable the filter for the matrix to move its cutoff").color(colors::overlay0()).size(fs(10.0, z)));
+    }
+
+    if matrix != config.mod_matrix {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.mod_matrix = matrix;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotModMatrix { slot_index: idx, matrix });
+    }
+}
+
+/// Voice low-pass filter and its envelope in the expanded slot view.
+fn draw_filter_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut filter = config.filter;
+
+    ui.horizontal(|ui| {
+        ui.checkbox(
+            &mut filter.enabled,
+            egui::RichText::new("Filter").color(colors::subtext0()).size(fs(11.0, z)),
+        );
+        ui.add_enabled_ui(filter.enabled, |ui| {
+            ui.add(
+                egui::DragValue::new(&mut filter.cutoff_hz)
+                    .range(20.0..=20000.0)
+                    .speed(10.0)
+                    .suffix(" Hz"),
+            )
+            .on_hover_text("Cutoff");
+            ui.label(egui::RichText::new("Res:").color(colors::subtext0()).size(fs(11.0, z)));
+            let mut res_pct = filter.resonance * 100.0;
+            if ui.add(egui::Slider::new(&mut res_pct, 0.0..=100.0).suffix("%")).changed() {
+                filter.resonance = res_pct / 100.0;
+            }
+            if filter != VoiceFilterSettings::default() && ui.small_button("Reset").clicked() {
+                filter = VoiceFilterSettings::default();
+            }
+        });
+    });
+
+    if filter.enabled {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Env:").color(colors::subtext0()).size(fs(11.0, z)))
+                .on_hover_text("Cutoff shift at full envelope");
+            ui.add(egui::DragValue::new(&mut filter.env_amount).range(-8.0..=8.0).speed(0.05).suffix(" oct"));
+            ui.label(egui::RichText::new("A").color(colors::subtext0()).size(fs(11.0, z)));
+            ui.add(egui::DragValue::new(&mut filter.attack_secs).range(0.0..=10.0).speed(0.005).suffix(" s"));
+            ui.label(egui::RichText::new("D").color(colors::subtext0()).size(fs(11.0, z)));
+            ui.add(egui::DragValue::new(&mut filter.decay_secs).range(0.0..=10.0).speed(0.005).suffix(" s"));
+            ui.label(egui::RichText::new("S").color(colors::subtext0()).size(fs(11.0, z)));
+            ui.add(egui::DragValue::new(&mut filter.sustain_level).range(0.0..=1.0).speed(0.01));
+            ui.label(egui::RichText::new("R").color(colors::subtext0()).size(fs(11.0, z)));
+            ui.add(egui::DragValue::new(&mut filter.release_secs).range(0.0..=10.0).speed(0.005).suffix(" s"));
+        });
     }
+
+    if filter != config.filter {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.filter = filter;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotFilter { slot_index: idx, filter });
+    }
+}
+
+/// Output trim and saturation in the expanded slot view.
+fn draw_output_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut output = config.output;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Trim:").color(colors::subtext0()).size(fs(11.0, z)))
+            .on_hover_text("Gain ahead of the fader");
+        ui.add(egui::Slider::new(&mut output.trim_db, -24.0..=12.0).step_by(0.5).suffix(" dB"));
+        ui.checkbox(
+            &mut output.saturate,
+            egui::RichText::new("Saturate").color(colors::subtext0()).size(fs(11.0, z)),
+        )
+        .on_hover_text("Soft-clip the slot output instead of letting it go over 0 dBFS");
+        if output != SlotOutput::default() && ui.small_button("Reset").clicked() {
+            output = SlotOutput::default();
+        }
+    });
+
+    if output != config.output {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.output = output;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotOutput { slot_index: idx, output });
+    }
+}
+
+/// Oversampling mode in the expanded slot view, with its latency and cost.
+fn draw_oversampling_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut oversampling = config.oversampling;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Oversampling:").color(colors::subtext0()).size(fs(11.0, z)))
+            .on_hover_text("Render the voices at a higher rate to reduce aliasing from bright synth sounds");
+        egui::ComboBox::from_id_salt(("oversampling", idx))
+            .selected_text(oversampling.label())
+            .width(zs(56.0, z))
+            .show_ui(ui, |ui| {
+                for mode in Oversampling::ALL {
+                    ui.selectable_value(&mut oversampling, mode, mode.label());
+                }
+            });
+        if oversampling != Oversampling::Off {
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} samples latency · ~{}x voice CPU",
+                    oversampling.latency(),
+                    oversampling.factor()
+                ))
+                .color(colors::overlay0())
+                .size(fs(10.0, z)),
+            );
+        }
+    });
+
+    if oversampling != config.oversampling {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.oversampling = oversampling;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotOversampling { slot_index: idx, oversampling });
+    }
+}
+
+/// Controllers listed by name in the blocked-CC menu.
+const CC_NAMES: [(u8, &str); 8] = [
+    (1, "Mod wheel"),
+    (2, "Breath"),
+    (7, "Volume"),
+    (10, "Pan"),
+    (11, "Expression"),
+    (64, "Sustain"),
+    (66, "Sostenuto"),
+    (74, "Brightness"),
+];
+
+/// Input MIDI filter in the expanded slot view.
+fn draw_midi_filter_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut filter = config.midi_filter.clone();
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Keys:").color(colors::subtext0()).size(fs(11.0, z)))
+            .on_hover_text("Notes outside this range are ignored");
+        ui.add(
+            egui::DragValue::new(&mut filter.note_low)
+                .range(0..=filter.note_high)
+                .custom_formatter(|v, _| note_name(v as u8)),
+        );
+        ui.label(egui::RichText::new("–").color(colors::subtext0()));
+        ui.add(
+            egui::DragValue::new(&mut filter.note_high)
+                .range(filter.note_low..=127)
+                .custom_formatter(|v, _| note_name(v as u8)),
+        );
+        ui.checkbox(
+            &mut filter.ignore_pitch_bend,
+            egui::RichText::new("Ignore bend").color(colors::subtext0()).size(fs(11.0, z)),
+        );
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Velocity:").color(colors::subtext0()).size(fs(11.0, z)))
+            .on_hover_text("Incoming velocities are scaled, then clamped into the range");
+        let mut scale_pct = filter.velocity_scale * 100.0;
+        if ui.add(egui::DragValue::new(&mut scale_pct).range(0.0..=400.0).suffix("%")).changed() {
+            filter.velocity_scale = scale_pct / 100.0;
+        }
+        let mut low = (filter.velocity_min * 127.0).round() as u8;
+        let mut high = (filter.velocity_max * 127.0).round() as u8;
+        let low_changed = ui.add(egui::DragValue::new(&mut low).range(0..=high)).changed();
+        ui.label(egui::RichText::new("–").color(colors::subtext0()));
+        let high_changed = ui.add(egui::DragValue::new(&mut high).range(low..=127)).changed();
+        if low_changed || high_changed {
+            filter.velocity_min = low as f32 / 127.0;
+            filter.velocity_max = high as f32 / 127.0;
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Block CCs:").color(colors::subtext0()).size(fs(11.0, z)));
+        let summary = match filter.blocked_ccs.len() {
+            0 => "None".to_string(),
+            1..=3 => filter.blocked_ccs.iter().map(|cc| cc.to_string()).collect::<Vec<_>>().join(", "),
+            n => format!("{} blocked", n),
+        };
+        egui::ComboBox::from_id_salt(("slot_blocked_ccs", idx))
+            .selected_text(summary)
+            .width(zs(90.0, z))
+            .height(zs(300.0, z))
+            .show_ui(ui, |ui| {
+                // Channel mode messages (120–127) always pass
+                for cc in 0..120u8 {
+                    let name = CC_NAMES.iter().find(|(n, _)| *n == cc).map(|(_, name)| *name);
+                    let label = match name {
+                        Some(name) => format!("CC {} {}", cc, name),
+                        None => format!("CC {}", cc),
+                    };
+                    let mut blocked = filter.blocked_ccs.contains(&cc);
+                    if ui.checkbox(&mut blocked, label).changed() {
+                        if blocked {
+                            filter.blocked_ccs.push(cc);
+                            filter.blocked_ccs.sort_unstable();
+                        } else {
+                            filter.blocked_ccs.retain(|c| *c != cc);
+                        }
+                    }
+                }
+            });
+        if !filter.is_default() && ui.small_button("Reset").clicked() {
+            filter = MidiFilterSettings::default();
+        }
+    });
+
+    if filter != config.midi_filter {
+        let audio_filter = MidiFilter::from_settings(&filter);
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.midi_filter = filter;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotMidiFilter { slot_index: idx, filter: audio_filter });
+    }
+}
+
+/// Articulation keyswitch list in the expanded slot view.
+fn draw_articulation_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut articulations = config.articulations.clone();
+    let mut remove = None;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Articulations:").color(colors::subtext0()).size(fs(11.0, z)))
+            .on_hover_text("Keyswitch keys choose which preset layer later notes play; the first is the default");
+        if ui.small_button("+").clicked() {
+            let key = articulations.iter().map(|a| a.key + 1).max().unwrap_or(24).min(127);
+            let layer = articulations.len();
+            articulations.push(Articulation { key, name: format!("Layer {}", layer + 1), layer });
+        }
+    });
+    for (i, a) in articulations.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut a.key)
+                    .range(0..=127)
+                    .custom_formatter(|v, _| note_name(v as u8)),
+            );
+            ui.add(egui::TextEdit::singleline(&mut a.name).desired_width(zs(90.0, z)));
+            ui.label(egui::RichText::new("Layer").color(colors::subtext0()).size(fs(11.0, z)));
+            let mut layer = a.layer + 1;
+            if ui.add(egui::DragValue::new(&mut layer).range(1..=64)).changed() {
+                a.layer = layer - 1;
+            }
+            if ui
+                .small_button(egui::RichText::new("\u{2715}").color(colors::red()))
+                .clicked()
+            {
+                remove = Some(i);
+            }
+        });
+    }
+    if let Some(i) = remove {
+        articulations.remove(i);
+    }
+
+    if articulations != config.articulations {
+        let keyswitches = KeyswitchMap::from_articulations(&articulations);
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.articulations = articulations;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotKeyswitches { slot_index: idx, keyswitches });
+    }
+}
+
+/// Humanize controls for runner playback (shown when the slot has source).
+fn draw_humanize_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut humanize = config.humanize;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Humanize:").color(colors::subtext0()).size(fs(11.0, z)));
+        ui.add(egui::Slider::new(&mut humanize.timing_ms, 0.0..=50.0).text("± ms"));
+        let mut vel_pct = humanize.velocity * 100.0;
+        if ui.add(egui::Slider::new(&mut vel_pct, 0.0..=100.0).text("vel %")).changed() {
+            humanize.velocity = vel_pct / 100.0;
+        }
+        ui.add(egui::Slider::new(&mut humanize.swing, 50.0..=75.0).text("swing %"));
+    });
+
+    if humanize != config.humanize {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.humanize = humanize;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotHumanize { slot_index: idx, humanize });
+    }
+}
+
+/// Launch quantization for runner slots (clip-launcher style).
+fn draw_launch_controls(ui: &mut egui::Ui, state: &mut EditorState, idx: usize, config: &SlotConfig, z: f32) {
+    let mut quantize = config.launch_quantize;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Launch:").color(colors::subtext0()).size(fs(11.0, z)))
+            .on_hover_text("While the host plays, triggered runners wait for the next boundary");
+        for option in LaunchQuantize::ALL {
+            ui.radio_value(&mut quantize, option, option.label());
+        }
+    });
+
+    if quantize != config.launch_quantize {
+        if let Ok(mut ps) = state.plugin_state.lock() {
+            if let Some(cfg) = ps.slot_configs.get_mut(idx) {
+                cfg.launch_quantize = quantize;
+            }
+        }
+        let _ = state.event_tx.try_send(EditorEvent::SetSlotLaunchQuantize { slot_index: idx, quantize });
+    }
+}
+
+/// Small compile-status indicator shown under the code editor.
+fn draw_compile_status(ui: &mut egui::Ui, status: CompileStatus, z: f32) {
+    let (text, color) = match status {
+        CompileStatus::Idle => return,
+        CompileStatus::Pending => ("● editing…", colors::overlay0()),
+        CompileStatus::Compiling => ("● compiling…", colors::yellow()),
+        CompileStatus::Live => ("● live", colors::green()),
+        CompileStatus::Error => ("● error — previous version still playing", colors::red()),
+    };
+    ui.label(egui::RichText::new(text).color(color).size(fs(10.0, z)));
 }
 
 /// Convert a MIDI note number to a name (e.g., 60 → "C4").
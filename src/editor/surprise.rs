@@ -0,0 +1,231 @@
+//! "Surprise me": fill slots with random presets from the loaded libraries.
+//!
+//! Each category gets a weight (0 leaves it out); presets are drawn without
+//! repeats, with a preset's chance proportional to its category's weight.
+//! The chosen presets go into the first slots without source code, adding
+//! slots up to the rack's limit, and load through the usual `LoadManager`.
+
+use std::collections::BTreeMap;
+
+use nih_plug_egui::egui;
+
+use super::loads::LoadTarget;
+use super::{EditorState, colors, fs, zs};
+use crate::preset::manager::PresetManager;
+use crate::slots::MAX_SLOTS;
+use crate::state::SlotConfig;
+
+/// A preset that can be drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub library: String,
+    pub name: String,
+    pub path: String,
+    pub category: String,
+}
+
+pub struct SurpriseState {
+    /// Slots to fill.
+    pub count: usize,
+    /// Category → weight; categories not listed weigh 1.
+    pub weights: BTreeMap<String, f32>,
+    rng: u64,
+}
+
+impl Default for SurpriseState {
+    fn default() -> Self {
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0x2545_F491_4F6C_DD1D);
+        Self { count: 4, weights: BTreeMap::new(), rng: seed | 1 }
+    }
+}
+
+impl SurpriseState {
+    fn weight(&self, category: &str) -> f32 {
+        self.weights.get(category).copied().unwrap_or(1.0).max(0.0)
+    }
+}
+
+/// Every preset of the loaded library and sub-indexes.
+pub fn candidates(pm: &PresetManager) -> Vec<Candidate> {
+    let flat = pm.library_presets.iter().map(|(library, presets)| (library.as_str(), presets));
+    let nested = pm
+        .sub_index_presets
+        .iter()
+        .map(|(key, presets)| (key.split('/').next().unwrap_or(key), presets));
+    flat.chain(nested)
+        .flat_map(|(library, presets)| {
+            presets.iter().map(move |p| Candidate {
+                library: library.to_string(),
+                name: p.name.clone(),
+                path: p.path.clone(),
+                category: p.category.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Draw up to `count` distinct candidates, weighted by `weight(category)`.
+pub fn pick(
+    candidates: &[Candidate],
+    weight: impl Fn(&str) -> f32,
+    count: usize,
+    rng: &mut u64,
+) -> Vec<Candidate> {
+    let mut weights: Vec<f32> = candidates.iter().map(|c| weight(&c.category)).collect();
+    let mut picked = Vec::with_capacity(count);
+    while picked.len() < count {
+        let total: f32 = weights.iter().sum();
+        if total <= 0.0 {
+            break;
+        }
+        let mut target = next_unit(rng) * total;
+        let idx = weights
+            .iter()
+            .position(|w| {
+                target -= w;
+                *w > 0.0 && target < 0.0
+            })
+            .unwrap_or_else(|| weights.iter().rposition(|w| *w > 0.0).unwrap_or(0));
+        weights[idx] = 0.0;
+        picked.push(candidates[idx].clone());
+    }
+    picked
+}
+
+/// Next xorshift value in 0..1.
+fn next_unit(rng: &mut u64) -> f32 {
+    *rng ^= *rng << 13;
+    *rng ^= *rng >> 7;
+    *rng ^= *rng << 17;
+    (*rng >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// "Surprise me" menu for the slot rack header.
+pub fn draw_button(ui: &mut egui::Ui, state: &mut EditorState, z: f32) {
+    let (pool, categories) = match state.preset_manager.lock() {
+        Ok(pm) => (candidates(&pm), pm.available_categories()),
+        Err(_) => (Vec::new(), Vec::new()),
+    };
+    ui.menu_button(egui::RichText::new("🎲 Surprise me").color(colors::yellow()).size(fs(12.0, z)), |ui| {
+        if pool.is_empty() {
+            ui.label(
+                egui::RichText::new("Open a library in the browser first.")
+                    .color(colors::overlay0())
+                    .italics(),
+            );
+            return;
+        }
+        let surprise = &mut state.surprise;
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Slots:").color(colors::subtext0()).size(fs(11.0, z)));
+            ui.add(egui::Slider::new(&mut surprise.count, 1..=MAX_SLOTS));
+        });
+        ui.label(egui::RichText::new("Category weights").color(colors::subtext0()).size(fs(11.0, z)))
+            .on_hover_text("How likely each category is; 0 leaves it out");
+        egui::ScrollArea::vertical().max_height(zs(200.0, z)).show(ui, |ui| {
+            for category in &categories {
+                let mut weight = surprise.weight(category);
+                ui.horizontal(|ui| {
+                    ui.add(egui::Slider::new(&mut weight, 0.0..=4.0).max_decimals(1));
+                    ui.label(egui::RichText::new(category).size(fs(11.0, z)));
+                });
+                if weight != surprise.weight(category) {
+                    surprise.weights.insert(category.clone(), weight);
+                }
+            }
+        });
+        ui.separator();
+        if ui.button("Fill slots").clicked() {
+            let picked = pick(&pool, |c| surprise.weight(c), surprise.count, &mut surprise.rng);
+            fill_slots(state, &picked);
+            ui.close_menu();
+        }
+    });
+}
+
+/// Put the picked presets into the first slots without source code and
+/// start loading them.
+fn fill_slots(state: &mut EditorState, picked: &[Candidate]) {
+    let mut targets = Vec::with_capacity(picked.len());
+    if let Ok(mut ps) = state.plugin_state.lock() {
+        let mut next = 0;
+        for candidate in picked {
+            while next < ps.slot_configs.len() && !ps.slot_configs[next].source_code.is_empty() {
+                next += 1;
+            }
+            if next == ps.slot_configs.len() {
+                if next >= MAX_SLOTS {
+                    break;
+                }
+                ps.add_slot_config(SlotConfig::default());
+            }
+            let cfg = &mut ps.slot_configs[next];
+            cfg.assign_preset(&candidate.name, format!("{}/{}", candidate.library, candidate.path));
+            targets.push((next, candidate));
+            next += 1;
+        }
+    }
+    for (idx, candidate) in &targets {
+        state.loads.request(
+            &state.jobs,
+            &state.preset_manager,
+            LoadTarget::Slot(*idx),
+            &candidate.library,
+            &candidate.path,
+            *idx,
+            None,
+        );
+    }
+    if let Ok(mut st) = state.status_text.lock() {
+        *st = format!("Surprise: loading {} presets\u{2026}", targets.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(name: &str, category: &str) -> Candidate {
+        Candidate {
+            library: "lib".into(),
+            name: name.into(),
+            path: format!("{}/preset.json", name),
+            category: category.into(),
+        }
+    }
+
+    #[test]
+    fn test_pick_is_distinct_and_skips_zero_weights() {
+        let pool = vec![
+            candidate("piano", "keys"),
+            candidate("organ", "keys"),
+            candidate("kit", "drums"),
+            candidate("bass", "bass"),
+        ];
+        let mut rng = 0x1234_5678;
+        for _ in 0..50 {
+            let picked = pick(&pool, |c| if c == "drums" { 0.0 } else { 1.0 }, 3, &mut rng);
+            assert_eq!(picked.len(), 3);
+            assert!(picked.iter().all(|c| c.category != "drums"));
+            let mut names: Vec<_> = picked.iter().map(|c| c.name.as_str()).collect();
+            names.sort();
+            names.dedup();
+            assert_eq!(names.len(), 3);
+        }
+        // Fewer candidates than asked for
+        assert_eq!(pick(&pool, |c| f32::from(c == "bass"), 3, &mut rng).len(), 1);
+    }
+
+    #[test]
+    fn test_pick_follows_weights() {
+        let pool = vec![candidate("piano", "keys"), candidate("kit", "drums")];
+        let mut rng = 42;
+        let keys = (0..1000)
+            .filter(|_| pick(&pool, |c| if c == "keys" { 9.0 } else { 1.0 }, 1, &mut rng)[0].category == "keys")
+            .count();
+        assert!((850..950).contains(&keys), "{}", keys);
+    }
+}
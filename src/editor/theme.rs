@@ -0,0 +1,444 @@
+//! Editor color themes.
+//!
+//! The `colors` accessors read the active palette, so every panel follows
+//! the theme without holding colors of its own. Built in are Catppuccin
+//! Mocha (the default, matching the web editor), Catppuccin Latte (light)
+//! and a high-contrast theme; a user palette can be loaded from a JSON or
+//! TOML file. The choice is stored per machine in `theme.json` under the
+//! user config directory.
+//!
+//! A palette file names colors by their Catppuccin role, as `#rrggbb`.
+//! Roles it leaves out come from the theme it `extends` (Mocha if unset):
+//!
+//! ```toml
+//! extends = "latte"
+//! base = "#fdf6e3"
+//! blue = "#268bd2"
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use nih_plug_egui::egui::{self, Color32};
+use serde::{Deserialize, Serialize};
+
+/// Colors of a theme, named by Catppuccin role.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    /// Dark background with light text (egui's dark visuals).
+    pub dark: bool,
+    pub base: Color32,
+    pub mantle: Color32,
+    pub crust: Color32,
+    pub surface0: Color32,
+    pub surface1: Color32,
+    pub surface2: Color32,
+    pub overlay0: Color32,
+    pub text: Color32,
+    pub subtext0: Color32,
+    pub subtext1: Color32,
+    pub blue: Color32,
+    pub green: Color32,
+    pub peach: Color32,
+    pub red: Color32,
+    pub mauve: Color32,
+    pub yellow: Color32,
+    pub teal: Color32,
+    pub lavender: Color32,
+    pub pink: Color32,
+}
+
+impl Palette {
+    /// Catppuccin Mocha (matches the web editor).
+    pub const MOCHA: Self = Self {
+        dark: true,
+        base: Color32::from_rgb(30, 30, 46),
+        mantle: Color32::from_rgb(24, 24, 37),
+        crust: Color32::from_rgb(17, 17, 27),
+        surface0: Color32::from_rgb(49, 50, 68),
+        surface1: Color32::from_rgb(69, 71, 90),
+        surface2: Color32::from_rgb(88, 91, 112),
+        overlay0: Color32::from_rgb(108, 112, 134),
+        text: Color32::from_rgb(205, 214, 244),
+        subtext0: Color32::from_rgb(166, 173, 200),
+        subtext1: Color32::from_rgb(186, 194, 222),
+        blue: Color32::from_rgb(137, 180, 250),
+        green: Color32::from_rgb(166, 227, 161),
+        peach: Color32::from_rgb(250, 179, 135),
+        red: Color32::from_rgb(243, 139, 168),
+        mauve: Color32::from_rgb(203, 166, 247),
+        yellow: Color32::from_rgb(249, 226, 175),
+        teal: Color32::from_rgb(148, 226, 213),
+        lavender: Color32::from_rgb(180, 190, 254),
+        pink: Color32::from_rgb(245, 194, 231),
+    };
+
+    /// Catppuccin Latte (light).
+    pub const LATTE: Self = Self {
+        dark: false,
+        base: Color32::from_rgb(239, 241, 245),
+        mantle: Color32::from_rgb(230, 233, 239),
+        crust: Color32::from_rgb(220, 224, 232),
+        surface0: Color32::from_rgb(204, 208, 218),
+        surface1: Color32::from_rgb(188, 192, 204),
+        surface2: Color32::from_rgb(172, 176, 190),
+        overlay0: Color32::from_rgb(156, 160, 176),
+        text: Color32::from_rgb(76, 79, 105),
+        subtext0: Color32::from_rgb(108, 111, 133),
+        subtext1: Color32::from_rgb(92, 95, 119),
+        blue: Color32::from_rgb(30, 102, 245),
+        green: Color32::from_rgb(64, 160, 43),
+        peach: Color32::from_rgb(254, 100, 11),
+        red: Color32::from_rgb(210, 15, 57),
+        mauve: Color32::from_rgb(136, 57, 239),
+        yellow: Color32::from_rgb(223, 142, 29),
+        teal: Color32::from_rgb(23, 146, 153),
+        lavender: Color32::from_rgb(114, 135, 253),
+        pink: Color32::from_rgb(234, 118, 203),
+    };
+
+    /// Black background, white text and saturated accents.
+    pub const HIGH_CONTRAST: Self = Self {
+        dark: true,
+        base: Color32::from_rgb(0, 0, 0),
+        mantle: Color32::from_rgb(12, 12, 12),
+        crust: Color32::from_rgb(0, 0, 0),
+        surface0: Color32::from_rgb(72, 72, 72),
+        surface1: Color32::from_rgb(104, 104, 104),
+        surface2: Color32::from_rgb(136, 136, 136),
+        overlay0: Color32::from_rgb(176, 176, 176),
+        text: Color32::from_rgb(255, 255, 255),
+        subtext0: Color32::from_rgb(230, 230, 230),
+        subtext1: Color32::from_rgb(242, 242, 242),
+        blue: Color32::from_rgb(90, 170, 255),
+        green: Color32::from_rgb(80, 255, 80),
+        peach: Color32::from_rgb(255, 170, 60),
+        red: Color32::from_rgb(255, 80, 80),
+        mauve: Color32::from_rgb(220, 140, 255),
+        yellow: Color32::from_rgb(255, 255, 0),
+        teal: Color32::from_rgb(0, 255, 255),
+        lavender: Color32::from_rgb(180, 180, 255),
+        pink: Color32::from_rgb(255, 140, 220),
+    };
+
+    /// The color slot for a role name.
+    fn role_mut(&mut self, role: &str) -> Option<&mut Color32> {
+        Some(match role {
+            "base" => &mut self.base,
+            "mantle" => &mut self.mantle,
+            "crust" => &mut self.crust,
+            "surface0" => &mut self.surface0,
+            "surface1" => &mut self.surface1,
+            "surface2" => &mut self.surface2,
+            "overlay0" => &mut self.overlay0,
+            "text" => &mut self.text,
+            "subtext0" => &mut self.subtext0,
+            "subtext1" => &mut self.subtext1,
+            "blue" => &mut self.blue,
+            "green" => &mut self.green,
+            "peach" => &mut self.peach,
+            "red" => &mut self.red,
+            "mauve" => &mut self.mauve,
+            "yellow" => &mut self.yellow,
+            "teal" => &mut self.teal,
+            "lavender" => &mut self.lavender,
+            "pink" => &mut self.pink,
+            _ => return None,
+        })
+    }
+}
+
+/// Parse `#rrggbb` (the `#` is optional).
+fn parse_hex(text: &str) -> Option<Color32> {
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Color32::from_rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// A theme selectable in Settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeKind {
+    #[default]
+    Mocha,
+    Latte,
+    HighContrast,
+    /// A user palette file.
+    Custom,
+}
+
+impl ThemeKind {
+    pub const ALL: [ThemeKind; 4] = [Self::Mocha, Self::Latte, Self::HighContrast, Self::Custom];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Mocha => "Mocha (dark)",
+            Self::Latte => "Latte (light)",
+            Self::HighContrast => "High contrast",
+            Self::Custom => "Custom file",
+        }
+    }
+
+    /// The built-in palette (`None` for a custom file).
+    pub fn builtin(self) -> Option<Palette> {
+        match self {
+            Self::Mocha => Some(Palette::MOCHA),
+            Self::Latte => Some(Palette::LATTE),
+            Self::HighContrast => Some(Palette::HIGH_CONTRAST),
+            Self::Custom => None,
+        }
+    }
+}
+
+/// A user palette file.
+#[derive(Debug, Deserialize)]
+struct PaletteFile {
+    #[serde(default)]
+    extends: ThemeKind,
+    dark: Option<bool>,
+    #[serde(flatten)]
+    colors: BTreeMap<String, String>,
+}
+
+impl PaletteFile {
+    fn into_palette(self) -> Result<Palette, String> {
+        let mut palette = self
+            .extends
+            .builtin()
+            .ok_or("A palette file can only extend a built-in theme")?;
+        for (role, value) in &self.colors {
+            let slot = palette.role_mut(role).ok_or_else(|| format!("Unknown color '{}'", role))?;
+            *slot = parse_hex(value).ok_or_else(|| format!("Invalid color for '{}': {}", role, value))?;
+        }
+        palette.dark = self.dark.unwrap_or(palette.dark);
+        Ok(palette)
+    }
+}
+
+/// Parse palette file text; TOML if `toml` is set, otherwise JSON.
+pub fn parse_palette(text: &str, toml: bool) -> Result<Palette, String> {
+    let file: PaletteFile = if toml {
+        toml::from_str(text).map_err(|e| format!("Invalid palette: {}", e))?
+    } else {
+        serde_json::from_str(text).map_err(|e| format!("Invalid palette: {}", e))?
+    };
+    file.into_palette()
+}
+
+/// Load a palette file, picking the format by extension.
+pub fn load_palette(path: &Path) -> Result<Palette, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let toml = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    parse_palette(&text, toml)
+}
+
+/// The saved theme choice.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeSettings {
+    pub theme: ThemeKind,
+    /// Palette file used by `ThemeKind::Custom`.
+    pub custom_path: Option<PathBuf>,
+}
+
+impl ThemeSettings {
+    /// The palette to show.
+    pub fn palette(&self) -> Result<Palette, String> {
+        match self.theme.builtin() {
+            Some(palette) => Ok(palette),
+            None => {
+                let path = self.custom_path.as_deref().ok_or("No palette file chosen")?;
+                load_palette(path)
+            }
+        }
+    }
+}
+
+/// The saved theme (Mocha if unset).
+pub fn load_settings() -> ThemeSettings {
+    crate::config::load_json("theme.json")
+}
+
+/// Save the theme choice.
+pub fn save_settings(settings: &ThemeSettings) -> Result<(), String> {
+    crate::config::save_json("theme.json", settings)
+}
+
+/// Palette read by the `colors` accessors.
+static ACTIVE: RwLock<Palette> = RwLock::new(Palette::MOCHA);
+
+/// The active palette.
+pub fn active() -> Palette {
+    ACTIVE.read().map(|p| *p).unwrap_or(Palette::MOCHA)
+}
+
+/// Make `palette` the active one and restyle egui to match.
+pub fn apply(ctx: &egui::Context, palette: &Palette) {
+    if let Ok(mut active) = ACTIVE.write() {
+        *active = *palette;
+    }
+
+    let mut style = (*ctx.style()).clone();
+    let p = palette;
+
+    // Background — matches --bg / --surface
+    style.visuals = if p.dark { egui::Visuals::dark() } else { egui::Visuals::light() };
+    style.visuals.panel_fill = p.base;
+    style.visuals.window_fill = p.mantle;
+    style.visuals.extreme_bg_color = p.crust;
+    style.visuals.faint_bg_color = p.mantle;
+    style.visuals.hyperlink_color = p.blue;
+    style.visuals.warn_fg_color = p.peach;
+    style.visuals.error_fg_color = p.red;
+
+    // Widget colors — buttons use --surface bg + --border border (4px radius)
+    style.visuals.widgets.noninteractive.bg_fill = p.surface0;
+    style.visuals.widgets.noninteractive.fg_stroke = egui::Stroke::new(1.0, p.text);
+    style.visuals.widgets.noninteractive.bg_stroke = egui::Stroke::new(1.0, p.surface0);
+    style.visuals.widgets.noninteractive.weak_bg_fill = p.mantle;
+    style.visuals.widgets.noninteractive.corner_radius = egui::CornerRadius::same(4);
+    style.visuals.widgets.inactive.bg_fill = p.mantle;
+    style.visuals.widgets.inactive.weak_bg_fill = p.mantle;
+    style.visuals.widgets.inactive.bg_stroke = egui::Stroke::new(1.0, p.surface0);
+    style.visuals.widgets.inactive.fg_stroke = egui::Stroke::new(1.0, p.subtext0);
+    style.visuals.widgets.inactive.corner_radius = egui::CornerRadius::same(4);
+    style.visuals.widgets.hovered.bg_fill = p.surface0;
+    style.visuals.widgets.hovered.weak_bg_fill = p.surface0;
+    style.visuals.widgets.hovered.fg_stroke = egui::Stroke::new(1.0, p.text);
+    style.visuals.widgets.hovered.corner_radius = egui::CornerRadius::same(4);
+    style.visuals.widgets.active.bg_fill = p.surface1;
+    style.visuals.widgets.active.weak_bg_fill = p.surface1;
+    style.visuals.widgets.active.fg_stroke = egui::Stroke::new(1.0, p.text);
+    style.visuals.widgets.active.corner_radius = egui::CornerRadius::same(4);
+    style.visuals.widgets.open = style.visuals.widgets.active;
+
+    // Selection
+    style.visuals.selection.bg_fill = p.blue.linear_multiply(0.3);
+    style.visuals.selection.stroke = egui::Stroke::new(1.0, p.blue);
+
+    // Window / panel borders — matching --border (SURFACE0)
+    style.visuals.window_stroke = egui::Stroke::new(1.0, p.surface0);
+    style.visuals.window_corner_radius = egui::CornerRadius::same(4);
+
+    // Spacing — tighter to match web CSS
+    style.spacing.item_spacing = egui::vec2(6.0, 4.0);
+    style.spacing.button_padding = egui::vec2(6.0, 3.0);
+    style.spacing.window_margin = egui::Margin::same(8);
+
+    super::accessibility::scale_style(&mut style);
+    ctx.set_style(style);
+}
+
+/// Theme options being edited in Settings.
+#[derive(Default)]
+pub struct ThemeState {
+    /// Saved choice, read when Settings is first drawn.
+    settings: Option<ThemeSettings>,
+    /// Palette file path as typed.
+    path_text: String,
+    /// Why the last palette couldn't be applied.
+    error: Option<String>,
+}
+
+/// Apply `settings`, falling back to Mocha if its palette can't be loaded.
+/// Returns the load error, if any.
+pub fn apply_settings(ctx: &egui::Context, settings: &ThemeSettings) -> Option<String> {
+    match settings.palette() {
+        Ok(palette) => {
+            apply(ctx, &palette);
+            None
+        }
+        Err(e) => {
+            nih_plug::debug::nih_log!("[Theme] {}", e);
+            apply(ctx, &Palette::MOCHA);
+            Some(e)
+        }
+    }
+}
+
+/// Theme picker for the Settings tab.
+pub fn draw_settings(ui: &mut egui::Ui, state: &mut ThemeState) {
+    let settings = state.settings.get_or_insert_with(|| {
+        let saved = load_settings();
+        state.path_text = saved
+            .custom_path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        saved
+    });
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Theme:").color(super::colors::subtext0()));
+        for kind in ThemeKind::ALL {
+            changed |= ui.radio_value(&mut settings.theme, kind, kind.label()).changed();
+        }
+    });
+    if settings.theme == ThemeKind::Custom {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Palette file:").color(super::colors::subtext0()));
+            ui.add(
+                egui::TextEdit::singleline(&mut state.path_text)
+                    .hint_text("/path/to/palette.toml or .json")
+                    .desired_width(path_field_width(ui)),
+            );
+            if ui.button("Load").on_hover_text("Load (or reload) the palette file").clicked() {
+                let path = state.path_text.trim();
+                settings.custom_path = (!path.is_empty()).then(|| PathBuf::from(path));
+                changed = true;
+            }
+        });
+    }
+
+    if changed {
+        state.error = apply_settings(ui.ctx(), settings);
+        if let Err(e) = save_settings(settings) {
+            nih_plug::debug::nih_log!("[Settings] {}", e);
+        }
+    }
+    if let Some(e) = &state.error {
+        ui.label(egui::RichText::new(format!("\u{26A0} {}", e)).color(super::colors::red()).small());
+    }
+}
+
+/// Width of the palette path field, leaving room for the Load button.
+fn path_field_width(ui: &egui::Ui) -> f32 {
+    (ui.available_width() - 60.0).max(120.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(parse_hex("#1e1e2e"), Some(Color32::from_rgb(30, 30, 46)));
+        assert_eq!(parse_hex("FFFFFF"), Some(Color32::WHITE));
+        assert_eq!(parse_hex("#fff"), None);
+        assert_eq!(parse_hex("#gggggg"), None);
+    }
+
+    #[test]
+    fn test_palette_file_json_and_toml() {
+        let json = r##"{ "extends": "latte", "blue": "#268bd2" }"##;
+        let palette = parse_palette(json, false).unwrap();
+        assert!(!palette.dark);
+        assert_eq!(palette.blue, Color32::from_rgb(38, 139, 210));
+        assert_eq!(palette.base, Palette::LATTE.base);
+
+        let toml = "base = \"#000000\"\ndark = true\n";
+        let palette = parse_palette(toml, true).unwrap();
+        assert_eq!(palette.base, Color32::BLACK);
+        assert_eq!(palette.text, Palette::MOCHA.text);
+
+        assert!(parse_palette(r##"{ "bleu": "#000000" }"##, false).is_err());
+        assert!(parse_palette(r#"{ "blue": "navy" }"#, false).is_err());
+        assert!(parse_palette(r#"{ "extends": "custom" }"#, false).is_err());
+    }
+}
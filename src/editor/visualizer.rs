@@ -1,4 +1,4 @@
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
 use nih_plug_egui::egui;
 use parking_lot::Mutex;
@@ -50,6 +50,10 @@ pub struct VisualizerState {
     rms_left: AtomicU32,
     /// RMS level for Right channel (atomic f32 bits).
     rms_right: AtomicU32,
+    /// Inter-sample peak of both channels (atomic f32 bits).
+    true_peak: AtomicU32,
+    /// Latched once the true peak goes over 0 dBTP, until cleared.
+    clipped: AtomicBool,
 }
 
 /// Inner waveform ring buffer (protected by Mutex).
@@ -72,6 +76,8 @@ impl VisualizerState {
             peak_right: AtomicU32::new(0),
             rms_left: AtomicU32::new(0),
             rms_right: AtomicU32::new(0),
+            true_peak: AtomicU32::new(0),
+            clipped: AtomicBool::new(false),
         }
     }
 
@@ -97,12 +103,37 @@ impl VisualizerState {
         store_f32(&self.rms_right, rms_r);
     }
 
+    /// Update the inter-sample peak; latches the clip indicator above
+    /// 0 dBTP (lock-free, always succeeds).
+    pub fn update_true_peak(&self, true_peak: f32) {
+        fetch_max_f32(&self.true_peak, true_peak);
+        if true_peak > 1.0 {
+            self.clipped.store(true, Ordering::Relaxed);
+        }
+    }
+
     /// Decay peak levels (call periodically from UI thread).
     pub fn decay_levels(&self, amount: f32) {
         let pl = load_f32(&self.peak_left) * amount;
         let pr = load_f32(&self.peak_right) * amount;
+        let tp = load_f32(&self.true_peak) * amount;
         store_f32(&self.peak_left, if pl < 0.001 { 0.0 } else { pl });
         store_f32(&self.peak_right, if pr < 0.001 { 0.0 } else { pr });
+        store_f32(&self.true_peak, if tp < 0.001 { 0.0 } else { tp });
+    }
+
+    /// Read the inter-sample peak (lock-free).
+    pub fn true_peak(&self) -> f32 {
+        load_f32(&self.true_peak)
+    }
+
+    /// Whether the output went over 0 dBTP since the indicator was cleared.
+    pub fn clipped(&self) -> bool {
+        self.clipped.load(Ordering::Relaxed)
+    }
+
+    pub fn clear_clip(&self) {
+        self.clipped.store(false, Ordering::Relaxed);
     }
 
     /// Read current peak levels (lock-free).
@@ -131,6 +162,8 @@ impl VisualizerState {
         store_f32(&self.peak_right, 0.0);
         store_f32(&self.rms_left, 0.0);
         store_f32(&self.rms_right, 0.0);
+        store_f32(&self.true_peak, 0.0);
+        self.clipped.store(false, Ordering::Relaxed);
         if let Some(mut wf) = self.waveform.try_lock() {
             wf.left.fill(0.0);
             wf.right.fill(0.0);
@@ -149,7 +182,7 @@ pub fn draw(ui: &mut egui::Ui, state: &VisualizerState) {
     // --- Peak section ---
     ui.label(
         egui::RichText::new("Peak")
-            .color(colors::SUBTEXT0)
+            .color(colors::subtext0())
             .size(11.0)
             .strong(),
     );
@@ -158,11 +191,15 @@ pub fn draw(ui: &mut egui::Ui, state: &VisualizerState) {
     // Two vertical bars side by side
     let meter_height = 80.0_f32.min(ui.available_height() * 0.25);
     let meter_size = egui::vec2(panel_width, meter_height);
-    let (meter_rect, _) = ui.allocate_exact_size(meter_size, egui::Sense::hover());
+    let (meter_rect, meter_response) = ui.allocate_exact_size(meter_size, egui::Sense::click());
+    if meter_response.on_hover_text("Click to clear the clip indicator").clicked() {
+        state.clear_clip();
+    }
+    let clipped = state.clipped();
 
     if ui.is_rect_visible(meter_rect) {
         let painter = ui.painter_at(meter_rect);
-        painter.rect_filled(meter_rect, 2.0, colors::SURFACE0);
+        painter.rect_filled(meter_rect, 2.0, colors::surface0());
 
         let spacing = 4.0;
         let bar_w = ((meter_rect.width() - spacing * 3.0) / 2.0).max(4.0);
@@ -180,19 +217,19 @@ pub fn draw(ui: &mut egui::Ui, state: &VisualizerState) {
         draw_meter(&painter, rect_r, peak_right, rms_right);
     }
 
-    // Peak dB text
-    let max_peak = peak_left.max(peak_right);
-    let db_text = if max_peak < 0.0001 {
-        "\u{2212}\u{221e} dB".to_string()
+    // True peak dB text (inter-sample, so overs between samples count)
+    let true_peak = state.true_peak().max(peak_left).max(peak_right);
+    let db_text = if true_peak < 0.0001 {
+        "\u{2212}\u{221e} dBTP".to_string()
     } else {
-        format!("{:.1} dB", 20.0 * max_peak.log10())
+        format!("{:.1} dBTP", 20.0 * true_peak.log10())
     };
-    let db_color = if max_peak > 1.0 {
-        colors::RED
-    } else if max_peak > 0.707 {
-        colors::YELLOW
+    let db_color = if clipped || true_peak > 1.0 {
+        colors::red()
+    } else if true_peak > 0.707 {
+        colors::yellow()
     } else {
-        colors::GREEN
+        colors::green()
     };
     ui.label(
         egui::RichText::new(db_text)
@@ -208,7 +245,7 @@ pub fn draw(ui: &mut egui::Ui, state: &VisualizerState) {
     // --- Output / Waveform section ---
     ui.label(
         egui::RichText::new("Output")
-            .color(colors::SUBTEXT0)
+            .color(colors::subtext0())
             .size(11.0)
             .strong(),
     );
@@ -221,7 +258,7 @@ pub fn draw(ui: &mut egui::Ui, state: &VisualizerState) {
 
     if ui.is_rect_visible(wf_rect) {
         let painter = ui.painter_at(wf_rect);
-        painter.rect_filled(wf_rect, 2.0, colors::CRUST);
+        painter.rect_filled(wf_rect, 2.0, colors::crust());
 
         // Center line
         let center_y = wf_rect.center().y;
@@ -230,7 +267,7 @@ pub fn draw(ui: &mut egui::Ui, state: &VisualizerState) {
                 egui::pos2(wf_rect.left(), center_y),
                 egui::pos2(wf_rect.right(), center_y),
             ],
-            egui::Stroke::new(0.5, colors::SURFACE1),
+            egui::Stroke::new(0.5, colors::surface1()),
         );
 
         let width = state.width() as f32;
@@ -247,7 +284,7 @@ pub fn draw(ui: &mut egui::Ui, state: &VisualizerState) {
                 half_height,
                 center_y,
                 width,
-                colors::TEAL.gamma_multiply(0.7),
+                colors::teal().gamma_multiply(0.7),
             );
 
             // Right channel (mauve)
@@ -259,21 +296,21 @@ pub fn draw(ui: &mut egui::Ui, state: &VisualizerState) {
                 half_height,
                 center_y,
                 width,
-                colors::MAUVE.gamma_multiply(0.7),
+                colors::mauve().gamma_multiply(0.7),
             );
         }) {
             // Waveform drawn successfully
         }
 
-        // Clipping indicator (red border if over 0dB)
-        if peak_left > 1.0 || peak_right > 1.0 {
-            painter.rect_stroke(wf_rect, 2.0, egui::Stroke::new(1.0, colors::RED), egui::StrokeKind::Outside);
+        // Clipping indicator (red border once over 0 dBTP; click the meters to clear)
+        if clipped {
+            painter.rect_stroke(wf_rect, 2.0, egui::Stroke::new(1.0, colors::red()), egui::StrokeKind::Outside);
         }
     }
 }
 
 fn draw_meter(painter: &egui::Painter, rect: egui::Rect, peak: f32, rms: f32) {
-    painter.rect_filled(rect, 1.0, colors::SURFACE0);
+    painter.rect_filled(rect, 1.0, colors::surface0());
 
     // Draw peak bar first (background, dimmer)
     let peak_h = peak.min(1.0) * rect.height();
@@ -282,9 +319,9 @@ fn draw_meter(painter: &egui::Painter, rect: egui::Rect, peak: f32, rms: f32) {
         rect.max
     );
 
-    let peak_color = if peak > 1.0 { colors::RED }
-                     else if peak > 0.707 { colors::YELLOW }
-                     else { colors::GREEN };
+    let peak_color = if peak > 1.0 { colors::red() }
+                     else if peak > 0.707 { colors::yellow() }
+                     else { colors::green() };
 
     painter.rect_filled(peak_rect, 1.0, peak_color.gamma_multiply(0.4));
 
@@ -415,6 +452,19 @@ mod tests {
         assert_eq!(pr, 0.001); // 0.002 * 0.5 = 0.001, at threshold
     }
 
+    #[test]
+    fn test_true_peak_latches_clip() {
+        let vis = VisualizerState::new(4);
+        vis.update_true_peak(0.9);
+        assert!(!vis.clipped());
+        vis.update_true_peak(1.05);
+        vis.decay_levels(0.5);
+        assert!(vis.clipped());
+        assert!((vis.true_peak() - 0.525).abs() < 1e-6);
+        vis.clear_clip();
+        assert!(!vis.clipped());
+    }
+
     #[test]
     fn test_clear() {
         let vis = VisualizerState::new(4);
@@ -0,0 +1,957 @@
+//! WebSocket bridge to the SongWalker web editor.
+//!
+//! An optional server on `127.0.0.1` that the web editor connects to while
+//! this editor is open (in the plugin as well as the standalone app). It
+//! speaks JSON text messages; slots are numbered from 1, as in the UI:
+//!
+//! | From the web editor                                   | Effect                          |
+//! |-------------------------------------------------------|---------------------------------|
+//! | `{"type":"source","slot":n,"code":"…"}`               | Replace the slot's `.sw` source |
+//! | `{"type":"play","slot":n}` / `{"type":"stop","slot":n}` | Trigger/release the root note (or `note`) |
+//! | `{"type":"note_on","slot":n,"note":60,"velocity":0.8}` | Note on                        |
+//! | `{"type":"note_off","slot":n,"note":60}`              | Note off                        |
+//! | `{"type":"panic"}`                                    | Silence every slot              |
+//!
+//! The bridge answers with `hello` on connect, `compiled` once pushed
+//! source has compiled (or failed to), `error` for messages it can't use,
+//! and `telemetry` (master levels, voice count, per-slot output) about 20
+//! times a second. Notes go straight to the audio thread's event channel;
+//! everything else is applied on the UI thread like an edit in the rack.
+//! Settings are stored per machine in `web_bridge.json`.
+//!
+//! Any web page can open a WebSocket to localhost, so the upgrade request
+//! must come from the web editor's origin (`ALLOWED_ORIGINS`; clients that
+//! aren't browsers send none) and carry the session token shown in
+//! Settings, as `ws://127.0.0.1:<port>/?token=<token>`. The token changes
+//! every time the server starts.
+
+use std::collections::HashSet;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use crossbeam_channel::{Receiver, Sender};
+use nih_plug_egui::egui;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use super::compile::CompileStatus;
+use super::visualizer::VisualizerState;
+use super::event_queue::EventSender;
+use super::{EditorEvent, EditorState, colors};
+use crate::monitor::EngineMonitor;
+use crate::view_model;
+
+/// Default TCP port.
+pub const DEFAULT_PORT: u16 = 8765;
+
+/// How often connection threads wake up to read, write and check for stop.
+const TICK: Duration = Duration::from_millis(20);
+
+/// Interval between telemetry messages.
+const TELEMETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Largest request head or message accepted from a client.
+const MAX_HANDSHAKE: usize = 8 * 1024;
+const MAX_MESSAGE: usize = 1024 * 1024;
+
+/// Magic string appended to the client key (RFC 6455 §1.3).
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Pages allowed to connect: the web editor.
+pub const ALLOWED_ORIGINS: [&str; 2] = ["https://songwalker.org", "https://www.songwalker.org"];
+
+// ── Settings ─────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BridgeSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for BridgeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+        }
+    }
+}
+
+impl BridgeSettings {
+    /// Saved settings (disabled if unset).
+    pub fn load() -> Self {
+        crate::config::load_json("web_bridge.json")
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        crate::config::save_json("web_bridge.json", self)
+    }
+}
+
+// ── Wire format ──────────────────────────────────────────────
+
+/// `Sec-WebSocket-Accept` value for a client's `Sec-WebSocket-Key`.
+pub fn accept_key(key: &str) -> String {
+    let mut sha = Sha1::new();
+    sha.update(key.trim().as_bytes());
+    sha.update(ACCEPT_GUID.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(sha.finalize())
+}
+
+/// The parts of an upgrade request the server checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Handshake {
+    /// `Sec-WebSocket-Key`.
+    pub key: String,
+    /// `Origin`, sent by browsers.
+    pub origin: Option<String>,
+    /// The `token` query parameter.
+    pub token: Option<String>,
+}
+
+impl Handshake {
+    /// Accept a browser only from an allowed origin, and any client only
+    /// with the session token.
+    pub fn authorize(&self, session_token: &str) -> Result<(), String> {
+        if let Some(origin) = &self.origin {
+            if !ALLOWED_ORIGINS.iter().any(|allowed| origin.eq_ignore_ascii_case(allowed)) {
+                return Err(format!("Refused connection from origin {}", origin));
+            }
+        }
+        match &self.token {
+            Some(token) if tokens_match(token, session_token) => Ok(()),
+            Some(_) => Err("Refused connection with a wrong token".into()),
+            None => Err("Refused connection without a token".into()),
+        }
+    }
+}
+
+/// Compare tokens in time that doesn't depend on where they differ, so a
+/// client can't find the session token a byte at a time.
+fn tokens_match(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// The upgrade request in an HTTP request head, if it is one.
+pub fn parse_handshake(head: &str) -> Result<Handshake, String> {
+    let mut lines = head.lines();
+    let request = lines.next().unwrap_or_default();
+    let Some(target) = request.strip_prefix("GET ").and_then(|r| r.split_whitespace().next()) else {
+        return Err("Not a WebSocket upgrade request".into());
+    };
+    let token = target
+        .split_once('?')
+        .and_then(|(_, query)| query.split('&').find_map(|pair| pair.strip_prefix("token=")))
+        .map(str::to_string);
+    let mut key = None;
+    let mut origin = None;
+    let mut upgrade = false;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else { continue };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "upgrade" => upgrade = value.eq_ignore_ascii_case("websocket"),
+            "sec-websocket-key" => key = Some(value.to_string()),
+            "origin" => origin = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    match key {
+        Some(key) if upgrade => Ok(Handshake { key, origin, token }),
+        _ => Err("Missing WebSocket upgrade headers".into()),
+    }
+}
+
+/// A new session token: 128 bits from the OS-seeded hasher keys.
+fn session_token() -> String {
+    (0..2).map(|_| format!("{:016x}", RandomState::new().build_hasher().finish())).collect()
+}
+
+/// A complete frame from a client (fragmented messages are not supported).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+    Ping(Vec<u8>),
+    Pong,
+    Close,
+}
+
+const OP_TEXT: u8 = 0x1;
+const OP_BINARY: u8 = 0x2;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+/// Decode the first frame in `buf`: the frame and the bytes it used, or
+/// `None` if more bytes are needed.
+pub fn decode_frame(buf: &[u8]) -> Result<Option<(Frame, usize)>, String> {
+    if buf.len() < 2 {
+        return Ok(None);
+    }
+    let fin = buf[0] & 0x80 != 0;
+    let opcode = buf[0] & 0x0F;
+    if !fin || opcode == 0 {
+        return Err("Fragmented messages are not supported".into());
+    }
+    if buf[1] & 0x80 == 0 {
+        return Err("Client frames must be masked".into());
+    }
+    let (len, mut pos) = match buf[1] & 0x7F {
+        126 => match buf.get(2..4) {
+            Some(b) => (u16::from_be_bytes([b[0], b[1]]) as usize, 4),
+            None => return Ok(None),
+        },
+        127 => match buf.get(2..10) {
+            Some(b) => {
+                let len = u64::from_be_bytes(b.try_into().unwrap_or_default());
+                (usize::try_from(len).unwrap_or(usize::MAX), 10)
+            }
+            None => return Ok(None),
+        },
+        n => (n as usize, 2),
+    };
+    if len > MAX_MESSAGE {
+        return Err(format!("Message too large ({} bytes)", len));
+    }
+    let Some(mask) = buf.get(pos..pos + 4) else { return Ok(None) };
+    let mask = [mask[0], mask[1], mask[2], mask[3]];
+    pos += 4;
+    let Some(payload) = buf.get(pos..pos + len) else { return Ok(None) };
+    let payload: Vec<u8> = payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]).collect();
+
+    let frame = match opcode {
+        OP_TEXT => Frame::Text(String::from_utf8(payload).map_err(|_| "Text message is not UTF-8")?),
+        OP_BINARY => Frame::Binary(payload),
+        OP_CLOSE => Frame::Close,
+        OP_PING => Frame::Ping(payload),
+        OP_PONG => Frame::Pong,
+        other => return Err(format!("Unknown opcode {:#x}", other)),
+    };
+    Ok(Some((frame, pos + len)))
+}
+
+/// Encode an unmasked server frame.
+pub fn encode_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(payload.len() + 10);
+    out.push(0x80 | opcode);
+    match payload.len() {
+        n if n < 126 => out.push(n as u8),
+        n if n <= u16::MAX as usize => {
+            out.push(126);
+            out.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        n => {
+            out.push(127);
+            out.extend_from_slice(&(n as u64).to_be_bytes());
+        }
+    }
+    out.extend_from_slice(payload);
+    out
+}
+
+// ── Commands ─────────────────────────────────────────────────
+
+fn default_velocity() -> f32 {
+    0.8
+}
+
+/// A message as the web editor sends it (slots from 1).
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum WireMessage {
+    Source {
+        slot: usize,
+        code: String,
+    },
+    Play {
+        slot: usize,
+        note: Option<u8>,
+        #[serde(default = "default_velocity")]
+        velocity: f32,
+    },
+    Stop {
+        slot: usize,
+        note: Option<u8>,
+    },
+    NoteOn {
+        slot: usize,
+        note: u8,
+        #[serde(default = "default_velocity")]
+        velocity: f32,
+    },
+    NoteOff {
+        slot: usize,
+        note: u8,
+    },
+    Panic,
+}
+
+/// A decoded bridge action. Slot indices are 0-based.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BridgeCommand {
+    SetSource {
+        slot_index: usize,
+        code: String,
+    },
+    /// Note on at `note`, or the slot's root note.
+    Play {
+        slot_index: usize,
+        note: Option<u8>,
+        velocity: f32,
+    },
+    Stop {
+        slot_index: usize,
+        note: Option<u8>,
+    },
+    NoteOn {
+        slot_index: usize,
+        note: u8,
+        velocity: f32,
+    },
+    NoteOff {
+        slot_index: usize,
+        note: u8,
+    },
+    Panic,
+}
+
+impl BridgeCommand {
+    /// Decode a text message from the web editor.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let msg: WireMessage = serde_json::from_str(text).map_err(|e| format!("Bad message: {}", e))?;
+        let index = |slot: usize| slot.checked_sub(1).ok_or("Slots are numbered from 1".to_string());
+        let key = |note: u8| match note {
+            0..=127 => Ok(note),
+            _ => Err(format!("Note {} is out of range", note)),
+        };
+        let velocity = |v: f32| v.clamp(0.0, 1.0);
+        Ok(match msg {
+            WireMessage::Source { slot, code } => Self::SetSource { slot_index: index(slot)?, code },
+            WireMessage::Play { slot, note, velocity: v } => Self::Play {
+                slot_index: index(slot)?,
+                note: note.map(key).transpose()?,
+                velocity: velocity(v),
+            },
+            WireMessage::Stop { slot, note } => Self::Stop {
+                slot_index: index(slot)?,
+                note: note.map(key).transpose()?,
+            },
+            WireMessage::NoteOn { slot, note, velocity: v } => Self::NoteOn {
+                slot_index: index(slot)?,
+                note: key(note)?,
+                velocity: velocity(v),
+            },
+            WireMessage::NoteOff { slot, note } => Self::NoteOff {
+                slot_index: index(slot)?,
+                note: key(note)?,
+            },
+            WireMessage::Panic => Self::Panic,
+        })
+    }
+
+    /// The audio event for commands that don't need the UI thread.
+    fn direct_event(&self) -> Option<EditorEvent> {
+        match *self {
+            Self::NoteOn { slot_index, note, velocity } => {
+                Some(EditorEvent::NoteOn { slot_index, note, velocity })
+            }
+            Self::NoteOff { slot_index, note } => Some(EditorEvent::NoteOff { slot_index, note }),
+            Self::Panic => Some(EditorEvent::Panic),
+            _ => None,
+        }
+    }
+
+    /// Apply a command on the UI thread. Commands for slots that don't
+    /// exist are ignored.
+    fn apply(self, state: &mut EditorState) {
+        if let Some(event) = self.direct_event() {
+            let _ = state.event_tx.try_send(event);
+            return;
+        }
+        let root_note = |state: &EditorState, slot_index: usize| {
+            state
+                .plugin_state
+                .lock()
+                .ok()
+                .and_then(|ps| ps.slot_configs.get(slot_index).map(|c| c.root_note))
+        };
+        match self {
+            Self::SetSource { slot_index, code } => {
+                let exists = view_model::update_slot(&state.plugin_state, slot_index, |c| c.source_code = code);
+                if exists {
+                    state.compile_state.schedule(slot_index);
+                    state.web_bridge.awaiting.insert(slot_index);
+                } else {
+                    state.web_bridge.send(error_message(&format!("No slot {}", slot_index + 1)));
+                }
+            }
+            Self::Play { slot_index, note, velocity } => {
+                if let Some(note) = note.or_else(|| root_note(state, slot_index)) {
+                    let _ = state.event_tx.try_send(EditorEvent::NoteOn { slot_index, note, velocity });
+                }
+            }
+            Self::Stop { slot_index, note } => {
+                if let Some(note) = note.or_else(|| root_note(state, slot_index)) {
+                    let _ = state.event_tx.try_send(EditorEvent::NoteOff { slot_index, note });
+                }
+            }
+            Self::NoteOn { .. } | Self::NoteOff { .. } | Self::Panic => {}
+        }
+    }
+}
+
+fn error_message(message: &str) -> String {
+    serde_json::json!({ "type": "error", "message": message }).to_string()
+}
+
+// ── Server ───────────────────────────────────────────────────
+
+/// Engine state the connection threads read for telemetry.
+#[derive(Clone)]
+struct Telemetry {
+    visualizer: Arc<VisualizerState>,
+    voice_count: Arc<AtomicU32>,
+    monitor: Arc<EngineMonitor>,
+    slot_count: Arc<AtomicUsize>,
+}
+
+impl Telemetry {
+    fn message(&self) -> String {
+        let (peak_l, peak_r) = self.visualizer.peak_levels();
+        let (rms_l, rms_r) = self.visualizer.rms_levels();
+        let slots: Vec<_> = (0..self.slot_count.load(Ordering::Relaxed))
+            .map(|i| {
+                serde_json::json!({
+                    "slot": i + 1,
+                    "peak": self.monitor.output_peak(i),
+                    "event": self.monitor.play_event(i),
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "type": "telemetry",
+            "peak": [peak_l, peak_r],
+            "rms": [rms_l, rms_r],
+            "voices": self.voice_count.load(Ordering::Relaxed),
+            "slots": slots,
+        })
+        .to_string()
+    }
+}
+
+/// Senders to each connected client's thread.
+type Clients = Arc<Mutex<Vec<Sender<String>>>>;
+
+/// A running server. Dropping it closes every connection.
+pub struct BridgeServer {
+    port: u16,
+    /// Token clients must present.
+    token: Arc<str>,
+    stop: Arc<AtomicBool>,
+    clients: Clients,
+    /// Connections past the handshake.
+    connected: Arc<AtomicUsize>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl BridgeServer {
+    /// Listen on `127.0.0.1:port`. Notes are sent to `event_tx`, other
+    /// commands to `commands` for the UI thread.
+    fn start(
+        port: u16,
+        event_tx: EventSender,
+        commands: Sender<BridgeCommand>,
+        telemetry: Telemetry,
+    ) -> Result<Self, String> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .map_err(|e| format!("Bridge: can't listen on port {}: {}", port, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+
+        let token: Arc<str> = session_token().into();
+        let thread_token = token.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+        let clients: Clients = Arc::new(Mutex::new(Vec::new()));
+        let connected = Arc::new(AtomicUsize::new(0));
+        let (thread_stop, thread_clients, thread_connected) = (stop.clone(), clients.clone(), connected.clone());
+        let thread = std::thread::Builder::new()
+            .name("songwalker-bridge".into())
+            .spawn(move || {
+                let mut connections: Vec<JoinHandle<()>> = Vec::new();
+                while !thread_stop.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, peer)) => {
+                            log::info!("[Bridge] Connection from {peer}");
+                            let (tx, rx) = crossbeam_channel::unbounded();
+                            if let Ok(mut c) = thread_clients.lock() {
+                                c.push(tx);
+                            }
+                            let connection = Connection {
+                                stop: thread_stop.clone(),
+                                token: thread_token.clone(),
+                                event_tx: event_tx.clone(),
+                                commands: commands.clone(),
+                                outgoing: rx,
+                                telemetry: telemetry.clone(),
+                            };
+                            let connected = thread_connected.clone();
+                            let spawned = std::thread::Builder::new()
+                                .name("songwalker-bridge-client".into())
+                                .spawn(move || {
+                                    if let Err(e) = connection.run(stream, &connected) {
+                                        log::debug!("[Bridge] {e}");
+                                    }
+                                });
+                            connections.extend(spawned.ok());
+                        }
+                        Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::sleep(TICK),
+                        Err(e) => {
+                            log::warn!("[Bridge] {e}");
+                            std::thread::sleep(TICK);
+                        }
+                    }
+                    connections.retain(|c| !c.is_finished());
+                }
+                for connection in connections {
+                    let _ = connection.join();
+                }
+            })
+            .map_err(|e| e.to_string())?;
+
+        log::info!("[Bridge] Listening on ws://127.0.0.1:{port}");
+        Ok(Self {
+            port,
+            token,
+            stop,
+            clients,
+            connected,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Address for the web editor, with this session's token.
+    pub fn url(&self) -> String {
+        format!("ws://127.0.0.1:{}/?token={}", self.port, self.token)
+    }
+
+    /// Send a message to every connected client.
+    fn broadcast(&self, message: &str) {
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.retain(|tx| tx.send(message.to_string()).is_ok());
+        }
+    }
+
+    /// Number of open connections.
+    fn connected(&self) -> usize {
+        self.connected.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for BridgeServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// One client, served on its own thread.
+struct Connection {
+    stop: Arc<AtomicBool>,
+    token: Arc<str>,
+    event_tx: EventSender,
+    commands: Sender<BridgeCommand>,
+    outgoing: Receiver<String>,
+    telemetry: Telemetry,
+}
+
+fn send_text(stream: &mut TcpStream, text: &str) -> Result<(), String> {
+    stream
+        .write_all(&encode_frame(OP_TEXT, text.as_bytes()))
+        .map_err(|e| e.to_string())
+}
+
+impl Connection {
+    fn run(self, mut stream: TcpStream, connected: &AtomicUsize) -> Result<(), String> {
+        stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+        stream.set_read_timeout(Some(TICK)).map_err(|e| e.to_string())?;
+        let Some(buf) = self.handshake(&mut stream)? else { return Ok(()) };
+        connected.fetch_add(1, Ordering::Relaxed);
+        let result = self.serve(&mut stream, buf);
+        connected.fetch_sub(1, Ordering::Relaxed);
+        result
+    }
+
+    /// Answer the upgrade request. Returns bytes read past the request
+    /// head, or `None` if the client went away first.
+    fn handshake(&self, stream: &mut TcpStream) -> Result<Option<Vec<u8>>, String> {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        let started = Instant::now();
+        let head_len = loop {
+            if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break end + 4;
+            }
+            if buf.len() > MAX_HANDSHAKE || started.elapsed() > Duration::from_secs(5) {
+                return Err("Handshake timed out".into());
+            }
+            if self.stop.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+            match stream.read(&mut chunk) {
+                Ok(0) => return Ok(None),
+                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(e) => return Err(e.to_string()),
+            }
+        };
+        let head = String::from_utf8_lossy(&buf[..head_len]).into_owned();
+        let handshake = match parse_handshake(&head) {
+            Ok(handshake) => handshake,
+            Err(e) => {
+                let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n");
+                return Err(e);
+            }
+        };
+        if let Err(e) = handshake.authorize(&self.token) {
+            let _ = stream.write_all(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n");
+            return Err(e);
+        }
+        let response = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Accept: {}\r\n\r\n",
+            accept_key(&handshake.key)
+        );
+        stream.write_all(response.as_bytes()).map_err(|e| e.to_string())?;
+        buf.drain(..head_len);
+        let hello = serde_json::json!({
+            "type": "hello",
+            "version": env!("CARGO_PKG_VERSION"),
+            "slots": self.telemetry.slot_count.load(Ordering::Relaxed),
+        });
+        send_text(stream, &hello.to_string())?;
+        Ok(Some(buf))
+    }
+
+    /// Exchange messages until the client closes or the server stops.
+    fn serve(&self, stream: &mut TcpStream, mut buf: Vec<u8>) -> Result<(), String> {
+        let mut chunk = [0u8; 4096];
+        let mut last_telemetry = Instant::now();
+        loop {
+            if self.stop.load(Ordering::Relaxed) {
+                let _ = stream.write_all(&encode_frame(OP_CLOSE, &[]));
+                return Ok(());
+            }
+            match stream.read(&mut chunk) {
+                Ok(0) => return Ok(()),
+                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(e) => return Err(e.to_string()),
+            }
+
+            while let Some((frame, used)) = decode_frame(&buf)? {
+                buf.drain(..used);
+                match frame {
+                    Frame::Text(text) => match BridgeCommand::parse(&text) {
+                        Ok(cmd) => match cmd.direct_event() {
+                            Some(event) => {
+                                let _ = self.event_tx.try_send(event);
+                            }
+                            None => {
+                                let _ = self.commands.send(cmd);
+                            }
+                        },
+                        Err(e) => send_text(stream, &error_message(&e))?,
+                    },
+                    Frame::Ping(payload) => {
+                        stream
+                            .write_all(&encode_frame(OP_PONG, &payload))
+                            .map_err(|e| e.to_string())?;
+                    }
+                    Frame::Close => {
+                        let _ = stream.write_all(&encode_frame(OP_CLOSE, &[]));
+                        return Ok(());
+                    }
+                    Frame::Binary(_) => send_text(stream, &error_message("Binary messages are not supported"))?,
+                    Frame::Pong => {}
+                }
+            }
+
+            for message in self.outgoing.try_iter() {
+                send_text(stream, &message)?;
+            }
+            if last_telemetry.elapsed() >= TELEMETRY_INTERVAL {
+                last_telemetry = Instant::now();
+                send_text(stream, &self.telemetry.message())?;
+            }
+        }
+    }
+}
+
+// ── Editor state ─────────────────────────────────────────────
+
+pub struct WebBridgeState {
+    /// None until loaded on the first frame.
+    settings: Option<BridgeSettings>,
+    server: Option<BridgeServer>,
+    /// Error from the last start, shown in Settings.
+    error: Option<String>,
+    /// Slots whose pushed source hasn't finished compiling.
+    awaiting: HashSet<usize>,
+    slot_count: Arc<AtomicUsize>,
+    command_tx: Sender<BridgeCommand>,
+    command_rx: Receiver<BridgeCommand>,
+}
+
+impl Default for WebBridgeState {
+    fn default() -> Self {
+        let (command_tx, command_rx) = crossbeam_channel::unbounded();
+        Self {
+            settings: None,
+            server: None,
+            error: None,
+            awaiting: HashSet::new(),
+            slot_count: Arc::new(AtomicUsize::new(0)),
+            command_tx,
+            command_rx,
+        }
+    }
+}
+
+impl WebBridgeState {
+    fn send(&self, message: String) {
+        if let Some(server) = &self.server {
+            server.broadcast(&message);
+        }
+    }
+}
+
+/// Start or stop the server to match the settings.
+fn restart(state: &mut EditorState) {
+    let settings = state.web_bridge.settings.unwrap_or_default();
+    // Release the port before binding it again
+    state.web_bridge.server = None;
+    state.web_bridge.error = None;
+    if !settings.enabled {
+        return;
+    }
+    let telemetry = Telemetry {
+        visualizer: state.visualizer_state.clone(),
+        voice_count: state.voice_count.clone(),
+        monitor: state.monitor.clone(),
+        slot_count: state.web_bridge.slot_count.clone(),
+    };
+    match BridgeServer::start(
+        settings.port,
+        state.event_tx.clone(),
+        state.web_bridge.command_tx.clone(),
+        telemetry,
+    ) {
+        Ok(server) => state.web_bridge.server = Some(server),
+        Err(e) => {
+            log::warn!("[Bridge] {e}");
+            state.web_bridge.error = Some(e);
+        }
+    }
+}
+
+/// Apply commands from the web editor and report compile results. Called
+/// once per frame.
+pub fn poll(state: &mut EditorState) {
+    if state.web_bridge.settings.is_none() {
+        state.web_bridge.settings = Some(BridgeSettings::load());
+        restart(state);
+    }
+    if state.web_bridge.server.is_none() {
+        return;
+    }
+
+    let commands: Vec<_> = state.web_bridge.command_rx.try_iter().collect();
+    for cmd in commands {
+        cmd.apply(state);
+    }
+
+    if let Ok(ps) = state.plugin_state.lock() {
+        state.web_bridge.slot_count.store(ps.slot_configs.len(), Ordering::Relaxed);
+    }
+
+    let done: Vec<usize> = state
+        .web_bridge
+        .awaiting
+        .iter()
+        .copied()
+        .filter(|&idx| matches!(state.compile_state.status(idx), CompileStatus::Live | CompileStatus::Error))
+        .collect();
+    for idx in done {
+        state.web_bridge.awaiting.remove(&idx);
+        let error = state
+            .plugin_state
+            .lock()
+            .ok()
+            .and_then(|ps| ps.slot_configs.get(idx).and_then(|c| c.compile_error.clone()));
+        let message = serde_json::json!({
+            "type": "compiled",
+            "slot": idx + 1,
+            "ok": error.is_none(),
+            "error": error,
+        });
+        state.web_bridge.send(message.to_string());
+    }
+}
+
+/// Enable checkbox, port and connection status in the Settings tab.
+pub fn draw_settings(ui: &mut egui::Ui, state: &mut EditorState) {
+    let Some(mut settings) = state.web_bridge.settings else { return };
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        changed |= ui
+            .checkbox(&mut settings.enabled, "Web editor bridge")
+            .on_hover_text(
+                "Let the SongWalker web editor on this computer push source into slots, \
+                 play them and show levels while this window is open",
+            )
+            .changed();
+        ui.label("Port:");
+        let port = ui.add(egui::DragValue::new(&mut settings.port).range(1024..=65535));
+        changed |= port.lost_focus() || port.drag_stopped();
+    });
+    if changed && Some(settings) != state.web_bridge.settings {
+        state.web_bridge.settings = Some(settings);
+        restart(state);
+        if let Err(e) = settings.save() {
+            state.web_bridge.error = Some(e);
+        }
+    } else {
+        state.web_bridge.settings = Some(settings);
+    }
+
+    let bridge = &state.web_bridge;
+    if let Some(e) = &bridge.error {
+        ui.label(egui::RichText::new(e).color(colors::red()).small());
+    } else if let Some(server) = &bridge.server {
+        let text = format!("Listening on ws://127.0.0.1:{} ({} connected)", server.port(), server.connected());
+        ui.label(egui::RichText::new(text).color(colors::subtext1()).small());
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(server.url()).color(colors::subtext0()).small().monospace());
+            if ui
+                .small_button("Copy")
+                .on_hover_text("Copy the address to paste into the web editor (it changes each time the bridge starts)")
+                .clicked()
+            {
+                ui.ctx().copy_text(server.url());
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode a masked frame the way a browser would.
+    fn client_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let unmasked = encode_frame(opcode, payload);
+        let header = unmasked.len() - payload.len();
+        let mut out = unmasked[..header].to_vec();
+        out[1] |= 0x80;
+        out.extend_from_slice(&mask);
+        out.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        out
+    }
+
+    #[test]
+    fn test_accept_key() {
+        // Example from RFC 6455 §1.3
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+        let head = "GET / HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+                    Connection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n";
+        assert_eq!(parse_handshake(head).unwrap().key, "dGhlIHNhbXBsZSBub25jZQ==");
+        assert!(parse_handshake("GET / HTTP/1.1\r\nHost: localhost\r\n\r\n").is_err());
+    }
+
+    #[test]
+    fn test_refuses_foreign_origins_and_missing_tokens() {
+        let head = |target: &str, origin: &str| {
+            format!(
+                "GET {} HTTP/1.1\r\nHost: 127.0.0.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+                 Origin: {}\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+                target, origin
+            )
+        };
+        let token = session_token();
+        assert_eq!(token.len(), 32);
+        assert_ne!(token, session_token(), "each session gets its own");
+        let with_token = format!("/?token={}", token);
+
+        let editor = parse_handshake(&head(&with_token, "https://songwalker.org")).unwrap();
+        assert_eq!(editor.token.as_deref(), Some(token.as_str()));
+        assert!(editor.authorize(&token).is_ok());
+
+        let foreign = parse_handshake(&head(&with_token, "https://evil.example")).unwrap();
+        assert!(foreign.authorize(&token).unwrap_err().contains("evil.example"));
+        let lookalike = parse_handshake(&head(&with_token, "https://songwalker.org.evil.example")).unwrap();
+        assert!(lookalike.authorize(&token).is_err());
+
+        assert!(parse_handshake(&head("/", "https://songwalker.org")).unwrap().authorize(&token).is_err());
+        assert!(parse_handshake(&head("/?token=guess", "https://songwalker.org")).unwrap().authorize(&token).is_err());
+        let last = if token.ends_with('0') { '1' } else { '0' };
+        let near_miss = format!("/?token={}{}", &token[..token.len() - 1], last);
+        assert!(parse_handshake(&head(&near_miss, "https://songwalker.org")).unwrap().authorize(&token).is_err());
+    }
+
+    #[test]
+    fn test_decode_frames() {
+        let text = client_frame(OP_TEXT, b"hello");
+        assert_eq!(decode_frame(&text).unwrap(), Some((Frame::Text("hello".into()), text.len())));
+        assert_eq!(decode_frame(&text[..text.len() - 1]).unwrap(), None, "partial frame");
+
+        // 16-bit length, followed by a second frame
+        let long = "x".repeat(300);
+        let mut buf = client_frame(OP_TEXT, long.as_bytes());
+        let first = buf.len();
+        buf.extend(client_frame(OP_PING, b"p"));
+        assert_eq!(decode_frame(&buf).unwrap(), Some((Frame::Text(long), first)));
+        assert_eq!(decode_frame(&buf[first..]).unwrap().map(|f| f.0), Some(Frame::Ping(b"p".to_vec())));
+
+        // Unmasked and fragmented frames are rejected
+        assert!(decode_frame(&encode_frame(OP_TEXT, b"hi")).is_err());
+        let mut fragment = client_frame(OP_TEXT, b"hi");
+        fragment[0] &= 0x7F;
+        assert!(decode_frame(&fragment).is_err());
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            BridgeCommand::parse(r#"{"type":"source","slot":2,"code":"C4 /4"}"#),
+            Ok(BridgeCommand::SetSource { slot_index: 1, code: "C4 /4".into() })
+        );
+        assert_eq!(
+            BridgeCommand::parse(r#"{"type":"play","slot":1}"#),
+            Ok(BridgeCommand::Play { slot_index: 0, note: None, velocity: 0.8 })
+        );
+        assert_eq!(
+            BridgeCommand::parse(r#"{"type":"note_on","slot":1,"note":60,"velocity":2.0}"#),
+            Ok(BridgeCommand::NoteOn { slot_index: 0, note: 60, velocity: 1.0 })
+        );
+        assert_eq!(BridgeCommand::parse(r#"{"type":"panic"}"#), Ok(BridgeCommand::Panic));
+        assert!(BridgeCommand::parse(r#"{"type":"play","slot":0}"#).is_err(), "slots start at 1");
+        assert!(BridgeCommand::parse(r#"{"type":"note_off","slot":1,"note":200}"#).is_err());
+        assert!(BridgeCommand::parse(r#"{"type":"rewind"}"#).is_err());
+    }
+}
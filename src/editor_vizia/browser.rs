@@ -9,6 +9,7 @@ use nih_plug_vizia::vizia::prelude::*;
 
 use super::{AppEvent, Data};
 use crate::preset::manager::{LibraryStatus, PresetManager};
+use crate::view_model;
 
 /// Category definitions matching the web editor.
 const CATEGORIES: &[(&str, &str)] = &[
@@ -71,12 +72,21 @@ pub fn build(cx: &mut Context) {
             Binding::new(cx, Data::search_text, |cx, search| {
                 let search_val = search.get(cx);
 
-                // We need to access preset_manager from cx.
-                // Since vizia is retained-mode, we build the tree from
-                // a snapshot of the data that we read each time the binding fires.
-                Binding::new(cx, Data::preset_manager, move |cx, pm_lens| {
-                    let pm_arc = pm_lens.get(cx);
-                    build_library_tree(cx, &pm_arc, &search_val);
+                Binding::new(cx, Data::category_filter, move |cx, category| {
+                    let category = category.get(cx);
+                    let filter = view_model::PresetFilter::new(
+                        &search_val,
+                        (!category.is_empty()).then_some(category.as_str()),
+                    );
+                    let search_val = search_val.clone();
+
+                    // We need to access preset_manager from cx.
+                    // Since vizia is retained-mode, we build the tree from
+                    // a snapshot of the data that we read each time the binding fires.
+                    Binding::new(cx, Data::preset_manager, move |cx, pm_lens| {
+                        let pm_arc = pm_lens.get(cx);
+                        build_library_tree(cx, &pm_arc, &search_val, &filter);
+                    });
                 });
             });
         })
@@ -90,6 +100,7 @@ fn build_library_tree(
     cx: &mut Context,
     pm_arc: &Arc<Mutex<PresetManager>>,
     search_text: &str,
+    filter: &view_model::PresetFilter,
 ) {
     let Ok(pm) = pm_arc.lock() else { return };
     let search_active = !search_text.is_empty();
@@ -98,7 +109,7 @@ fn build_library_tree(
         // Flat search results
         let mut results = Vec::new();
         for lib in &pm.libraries {
-            for p in pm.filtered_presets_for_library(&lib.name) {
+            for p in filter.library_presets(&pm, &lib.name) {
                 results.push((
                     lib.name.clone(),
                     p.name.clone(),
@@ -130,7 +141,7 @@ fn build_library_tree(
         for lib in &pm.libraries {
             if lib.expanded {
                 presets_by_lib.push(
-                    pm.filtered_presets_for_library(&lib.name)
+                    filter.library_presets(&pm, &lib.name)
                         .iter()
                         .take(200)
                         .map(|p| (p.name.clone(), p.path.clone(), p.category.clone()))
@@ -232,6 +243,17 @@ fn build_preset_row(
         let lib_btn = lib_add.clone();
         let name_btn = name_add.clone();
         let path_btn = path_add.clone();
+        let lib_play = lib_add.clone();
+        let path_play = path_add.clone();
+
+        Button::new(
+            cx,
+            move |cx| {
+                cx.emit(AppEvent::PreviewPreset(lib_play.clone(), path_play.clone()));
+            },
+            |cx| Label::new(cx, "\u{25B6}"),
+        )
+        .class("play-btn");
 
         Button::new(
             cx,
@@ -3,23 +3,47 @@
 //! Layout mirrors the SongWalker web editor:
 //! - Left panel: Preset browser (search, libraries, categories, instruments)
 //! - Right panel: Slot rack (Kontakt-style) with inline editors
-//! - Bottom: Status bar
+//! - Far right: Output visualizer
+//! - Bottom: Piano keyboard (toggleable) and status bar
+//!
+//! Talks to the audio thread over the same `EditorEvent` /
+//! `PresetLoadedEvent` channels as the egui editor.
 
 pub mod browser;
+pub mod piano;
 pub mod resize_handle;
 pub mod slot_rack;
+pub mod visualizer;
 
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use crossbeam_channel::{Receiver, Sender};
 use nih_plug::prelude::*;
 use nih_plug_vizia::vizia::prelude::*;
+use nih_plug_vizia::widgets::ParamSlider;
 use nih_plug_vizia::{assets, create_vizia_editor, ViziaState, ViziaTheming};
 
+use crate::editor::browser::PREVIEW_SLOTS;
+use crate::editor::loads::{LoadManager, LoadTarget};
+use crate::editor::PlayNote;
+use crate::editor::piano::{base_note_for_offset, note_name};
+use crate::editor::preview::PreviewPlayer;
+use crate::editor::visualizer::VisualizerState;
+use crate::editor::event_queue::EventSender;
+use crate::editor::{EditorEvent, PresetLoadedEvent};
+use crate::jobs::JobPool;
+use crate::monitor::EngineMonitor;
 use crate::params::SongWalkerParams;
 use crate::preset::manager::PresetManager;
+use crate::editor::frontend::{self, EditorFrontend};
 use crate::state::PluginState;
+use crate::view_model::{self, ActivePresets};
 
+use self::piano::PianoKeyboard;
 use self::resize_handle::{SharedWindowSize, WindowResizeHandle};
+use self::visualizer::OutputVisualizer;
 
 /// Default editor window size.
 pub const EDITOR_WIDTH: u32 = 800;
@@ -27,6 +51,8 @@ pub const EDITOR_HEIGHT: u32 = 600;
 /// Minimum window size.
 const MIN_WIDTH: u32 = 400;
 const MIN_HEIGHT: u32 = 300;
+/// Interval of the editor tick (load delivery, meters, status bar).
+const TICK_INTERVAL: Duration = Duration::from_millis(33);
 
 /// Shared mutable window dimensions read by ViziaState's size_fn.
 /// Stored globally so it survives editor close/re-open cycles.
@@ -53,6 +79,99 @@ pub struct Data {
     pub search_text: String,
     /// Selected category filter (empty = all)
     pub category_filter: String,
+    /// Slot the piano plays into.
+    pub selected_slot: usize,
+    /// Preview slot used by the next browser preview (round-robin).
+    pub next_preview_slot: usize,
+    pub piano_visible: bool,
+    /// Octave offset of the piano (0 = C3–B4).
+    pub octave_offset: i8,
+    /// Status bar text (mirrors `status_text`).
+    pub status: String,
+    /// Live voice count (mirrors `voice_count`).
+    pub voices: u32,
+    /// Incremented every tick; views bound to it redraw continuously.
+    pub tick: u64,
+
+    /// Channel for sending events (note on/off) to the audio thread.
+    pub event_tx: EventSender,
+    /// Channel for sending fully-loaded presets to the audio thread.
+    pub audio_preset_loaded_tx: Sender<PresetLoadedEvent>,
+    pub ui_preset_loaded_tx: Sender<PresetLoadedEvent>,
+    pub ui_preset_loaded_rx: Receiver<PresetLoadedEvent>,
+    pub status_text: Arc<Mutex<String>>,
+    pub visualizer_state: Arc<VisualizerState>,
+    pub voice_count: Arc<AtomicU32>,
+    pub monitor: Arc<EngineMonitor>,
+    pub jobs: Arc<JobPool>,
+    pub loads: Arc<Mutex<LoadManager>>,
+    /// Chord/phrase notes and auto-release of browser previews.
+    pub preview: Arc<Mutex<PreviewPlayer>>,
+    /// UI-side references to loaded presets, so the audio thread is never
+    /// the one to drop them.
+    pub active_presets: Arc<Mutex<ActivePresets>>,
+}
+
+impl Data {
+    /// Request a preset load through the shared load manager.
+    fn request_load(
+        &self,
+        target: LoadTarget,
+        library: &str,
+        path: &str,
+        slot_index: usize,
+        play_note: Option<PlayNote>,
+    ) {
+        let display_name = path.rsplit('/').next().unwrap_or(path);
+        if let Ok(mut st) = self.status_text.lock() {
+            *st = format!("Loading {}\u{2026}", display_name);
+        }
+        if let Ok(mut loads) = self.loads.lock() {
+            loads.request(
+                &self.jobs,
+                &self.preset_manager,
+                target,
+                library,
+                path,
+                slot_index,
+                play_note,
+            );
+        }
+    }
+
+    /// Per-tick work: deliver finished loads to the audio thread and
+    /// refresh the status bar mirrors.
+    fn tick(&mut self) {
+        self.tick = self.tick.wrapping_add(1);
+        self.visualizer_state.decay_levels(0.85);
+
+        if let Ok(mut loads) = self.loads.lock() {
+            loads.deliver(&self.ui_preset_loaded_tx, &self.status_text);
+        }
+        let previews = match self.active_presets.lock() {
+            Ok(mut active) => view_model::forward_loaded_presets(
+                &self.ui_preset_loaded_rx,
+                &self.audio_preset_loaded_tx,
+                &mut active,
+            ),
+            Err(_) => Vec::new(),
+        };
+        if let Ok(mut preview) = self.preview.lock() {
+            let now = std::time::Instant::now();
+            for (slot_index, play) in previews {
+                preview.start(slot_index, play, now);
+            }
+            preview.poll(now, &self.event_tx);
+        }
+        self.event_tx.flush();
+
+        if let Ok(st) = self.status_text.lock() {
+            if *st != self.status {
+                self.status = st.clone();
+            }
+        }
+        self.voices = self.voice_count.load(Ordering::Relaxed);
+    }
 }
 
 /// Events emitted by UI widgets and handled by the Data model.
@@ -71,6 +190,16 @@ pub enum AppEvent {
     SetSlotPan(usize, f32),
     SetSlotRootNote(usize, u8),
     SetSlotSource(usize, String),
+    SelectSlot(usize),
+    PreviewPreset(String, String),
+    TogglePiano,
+    /// Silence every slot (header Panic button).
+    Panic,
+    ShiftOctave(i8),
+    PianoNoteOn(u8),
+    PianoNoteOff(u8),
+    SetFrontend(EditorFrontend),
+    Tick,
 }
 
 impl Model for Data {
@@ -81,55 +210,59 @@ impl Model for Data {
             }
             AppEvent::SetSearchText(text) => {
                 self.search_text = text.clone();
-                if let Ok(mut pm) = self.preset_manager.lock() {
-                    pm.search_query = text.clone();
-                }
             }
             AppEvent::SetCategoryFilter(cat) => {
                 self.category_filter = cat.clone();
-                if let Ok(mut pm) = self.preset_manager.lock() {
-                    pm.category_filter = if cat.is_empty() {
-                        None
-                    } else {
-                        Some(cat.clone())
-                    };
-                }
             }
             AppEvent::ToggleLibrary(name) => {
-                let pm_arc = self.preset_manager.clone();
-                let pm_arc2 = pm_arc.clone();
-                let mut should_fetch = false;
-                if let Ok(mut pm) = pm_arc.lock() {
-                    if let Some(lib) = pm.libraries.iter_mut().find(|l| l.name == *name) {
-                        lib.expanded = !lib.expanded;
-                        should_fetch =
-                            lib.expanded && lib.status == crate::preset::manager::LibraryStatus::NotLoaded;
-                    }
-                }
-                if should_fetch {
-                    PresetManager::fetch_library_index(pm_arc2, name.clone());
-                }
+                view_model::toggle_library(&self.jobs, &self.preset_manager, name);
             }
             AppEvent::SelectPreset(_lib, _path) => {
                 // Selection tracking — future use
             }
             AppEvent::AddPresetToSlot(lib_name, preset_name, preset_path) => {
-                let preset_id = format!("{}/{}", lib_name, preset_path);
-                if let Ok(mut ps) = self.plugin_state.lock() {
-                    let empty_idx = ps
-                        .slot_configs
-                        .iter()
-                        .position(|c| c.preset_id.is_none() && c.source_code.is_empty());
-                    if let Some(idx) = empty_idx {
-                        ps.slot_configs[idx].name = preset_name.clone();
-                        ps.slot_configs[idx].preset_id = Some(preset_id);
-                    } else {
-                        let config =
-                            crate::state::SlotConfig::new_preset(preset_name, &preset_id);
-                        ps.add_slot_config(config);
-                    }
+                let assigned =
+                    view_model::assign_preset(&self.plugin_state, lib_name, preset_name, preset_path);
+                if let Some(idx) = assigned {
+                    self.selected_slot = idx;
+                    self.request_load(LoadTarget::Slot(idx), lib_name, preset_path, idx, None);
                 }
             }
+            AppEvent::PreviewPreset(lib_name, preset_path) => {
+                let slot = self.next_preview_slot;
+                self.next_preview_slot = (slot + 1) % PREVIEW_SLOTS;
+                let note = self.preview.lock().map(|p| p.settings.note).unwrap_or(60);
+                self.request_load(LoadTarget::Preview, lib_name, preset_path, slot, Some(PlayNote::new(note)));
+            }
+            AppEvent::SelectSlot(idx) => {
+                self.selected_slot = *idx;
+            }
+            AppEvent::TogglePiano => {
+                self.piano_visible = !self.piano_visible;
+            }
+            AppEvent::Panic => {
+                if let Ok(mut preview) = self.preview.lock() {
+                    preview.stop_all(&self.event_tx);
+                }
+                let _ = self.event_tx.try_send(EditorEvent::Panic);
+            }
+            AppEvent::ShiftOctave(delta) => {
+                self.octave_offset = (self.octave_offset + delta).clamp(-4, 4);
+            }
+            AppEvent::PianoNoteOn(note) => {
+                let _ = self.event_tx.try_send(EditorEvent::NoteOn {
+                    slot_index: self.selected_slot,
+                    note: *note,
+                    velocity: 0.8,
+                });
+            }
+            AppEvent::PianoNoteOff(note) => {
+                let _ = self.event_tx.try_send(EditorEvent::NoteOff {
+                    slot_index: self.selected_slot,
+                    note: *note,
+                });
+            }
+            AppEvent::Tick => self.tick(),
             AppEvent::AddEmptySlot => {
                 if let Ok(mut ps) = self.plugin_state.lock() {
                     ps.add_slot_config(crate::state::SlotConfig::default());
@@ -137,62 +270,65 @@ impl Model for Data {
             }
             AppEvent::RemoveSlot(idx) => {
                 if let Ok(mut ps) = self.plugin_state.lock() {
-                    ps.remove_slot_config(*idx);
+                    for event in view_model::remove_slot(&mut ps, *idx) {
+                        let _ = self.event_tx.try_send(event);
+                    }
+                }
+                if let Ok(mut active) = self.active_presets.lock() {
+                    view_model::remove_slot_key(&mut active, *idx);
                 }
             }
             AppEvent::ToggleSolo(idx) => {
-                if let Ok(mut ps) = self.plugin_state.lock() {
-                    if let Some(cfg) = ps.slot_configs.get_mut(*idx) {
-                        cfg.solo = !cfg.solo;
-                    }
-                }
+                view_model::update_slot(&self.plugin_state, *idx, |cfg| cfg.solo = !cfg.solo);
             }
             AppEvent::ToggleMute(idx) => {
-                if let Ok(mut ps) = self.plugin_state.lock() {
-                    if let Some(cfg) = ps.slot_configs.get_mut(*idx) {
-                        cfg.muted = !cfg.muted;
-                    }
-                }
+                view_model::update_slot(&self.plugin_state, *idx, |cfg| cfg.muted = !cfg.muted);
             }
             AppEvent::SetSlotVolume(idx, vol) => {
-                if let Ok(mut ps) = self.plugin_state.lock() {
-                    if let Some(cfg) = ps.slot_configs.get_mut(*idx) {
-                        cfg.volume = *vol;
-                    }
-                }
+                view_model::update_slot(&self.plugin_state, *idx, |cfg| cfg.volume = *vol);
             }
             AppEvent::SetSlotPan(idx, pan) => {
-                if let Ok(mut ps) = self.plugin_state.lock() {
-                    if let Some(cfg) = ps.slot_configs.get_mut(*idx) {
-                        cfg.pan = *pan;
-                    }
-                }
+                view_model::update_slot(&self.plugin_state, *idx, |cfg| cfg.pan = *pan);
             }
             AppEvent::SetSlotRootNote(idx, note) => {
-                if let Ok(mut ps) = self.plugin_state.lock() {
-                    if let Some(cfg) = ps.slot_configs.get_mut(*idx) {
-                        cfg.root_note = *note;
-                    }
-                }
+                view_model::update_slot(&self.plugin_state, *idx, |cfg| cfg.root_note = *note);
             }
             AppEvent::SetSlotSource(idx, src) => {
-                if let Ok(mut ps) = self.plugin_state.lock() {
-                    if let Some(cfg) = ps.slot_configs.get_mut(*idx) {
-                        cfg.source_code = src.clone();
-                    }
+                view_model::update_slot(&self.plugin_state, *idx, |cfg| {
+                    cfg.source_code = src.clone()
+                });
+            }
+            AppEvent::SetFrontend(choice) => {
+                if let Err(e) = frontend::set(*choice) {
+                    nih_plug::debug::nih_log!("[Settings] {}", e);
                 }
             }
         });
     }
 }
 
-/// Create the plugin editor.
+/// Create the plugin editor. Takes the same channels and shared state as
+/// the egui editor's `create`.
 pub fn create(
     preset_manager: Arc<Mutex<PresetManager>>,
     plugin_state: Arc<Mutex<PluginState>>,
     params: Arc<SongWalkerParams>,
     editor_state: Arc<ViziaState>,
+    event_tx: EventSender,
+    audio_preset_loaded_tx: Sender<PresetLoadedEvent>,
+    ui_preset_loaded_tx: Sender<PresetLoadedEvent>,
+    ui_preset_loaded_rx: Receiver<PresetLoadedEvent>,
+    status_text: Arc<Mutex<String>>,
+    visualizer_state: Arc<VisualizerState>,
+    voice_count: Arc<AtomicU32>,
+    monitor: Arc<EngineMonitor>,
+    jobs: Arc<JobPool>,
 ) -> Option<Box<dyn Editor>> {
+    // Load bookkeeping outlives editor close/re-open, like the channels
+    let loads = Arc::new(Mutex::new(LoadManager::default()));
+    let preview = Arc::new(Mutex::new(PreviewPlayer::default()));
+    let active_presets = Arc::new(Mutex::new(ActivePresets::new()));
+
     create_vizia_editor(editor_state, ViziaTheming::Custom, move |cx, _| {
         assets::register_noto_sans_light(cx);
         assets::register_noto_sans_thin(cx);
@@ -210,9 +346,36 @@ pub fn create(
             active_tab: 0,
             search_text: String::new(),
             category_filter: String::new(),
+            selected_slot: 0,
+            next_preview_slot: 0,
+            piano_visible: false,
+            octave_offset: 0,
+            status: "Ready".to_string(),
+            voices: 0,
+            tick: 0,
+            event_tx: event_tx.clone(),
+            audio_preset_loaded_tx: audio_preset_loaded_tx.clone(),
+            ui_preset_loaded_tx: ui_preset_loaded_tx.clone(),
+            ui_preset_loaded_rx: ui_preset_loaded_rx.clone(),
+            status_text: status_text.clone(),
+            visualizer_state: visualizer_state.clone(),
+            voice_count: voice_count.clone(),
+            monitor: monitor.clone(),
+            jobs: jobs.clone(),
+            loads: loads.clone(),
+            preview: preview.clone(),
+            active_presets: active_presets.clone(),
         }
         .build(cx);
 
+        // Drive load delivery, meters and the status bar
+        let timer = cx.add_timer(TICK_INTERVAL, None, |cx, action| {
+            if let TimerAction::Tick(_) = action {
+                cx.emit(AppEvent::Tick);
+            }
+        });
+        cx.start_timer(timer);
+
         // Main layout: header, content (browser + rack/settings), status bar
         VStack::new(cx, |cx| {
             // ── Header ──
@@ -237,9 +400,30 @@ pub fn create(
                 })
                 .width(Stretch(1.0))
                 .height(Stretch(1.0));
+
+                // Far right: output visualizer (rebuilt every tick)
+                let vis = visualizer_state.clone();
+                VStack::new(cx, move |cx| {
+                    Label::new(cx, "Output").class("panel-title");
+                    Binding::new(cx, Data::tick, move |cx, _| {
+                        OutputVisualizer::new(cx, vis.clone())
+                            .width(Stretch(1.0))
+                            .height(Stretch(1.0));
+                    });
+                })
+                .id("visualizer-panel")
+                .width(Pixels(96.0))
+                .height(Stretch(1.0));
             })
             .height(Stretch(1.0));
 
+            // ── Piano ──
+            Binding::new(cx, Data::piano_visible, |cx, visible| {
+                if visible.get(cx) {
+                    build_piano(cx);
+                }
+            });
+
             // ── Status bar ──
             build_status_bar(cx);
         })
@@ -277,6 +461,21 @@ fn build_header(cx: &mut Context) {
         .class("tab-button")
         .checked(Data::active_tab.map(|t| *t == 1));
 
+        Button::new(
+            cx,
+            |cx| cx.emit(AppEvent::TogglePiano),
+            |cx| Label::new(cx, "\u{1F3B9} Piano"),
+        )
+        .class("tab-button")
+        .checked(Data::piano_visible);
+
+        Button::new(
+            cx,
+            |cx| cx.emit(AppEvent::Panic),
+            |cx| Label::new(cx, "Panic"),
+        )
+        .class("panic-button");
+
         // Right-aligned items
         HStack::new(cx, |cx| {
             Label::new(cx, "\u{2665} Donate")
@@ -291,19 +490,72 @@ fn build_header(cx: &mut Context) {
 /// Build the bottom status bar.
 fn build_status_bar(cx: &mut Context) {
     HStack::new(cx, |cx| {
-        Label::new(cx, "Ready")
+        Label::new(cx, Data::status)
             .class("status-text")
             .class("ready");
-        Label::new(cx, "Voices: 0/256")
-            .class("status-text");
-        Label::new(cx, "CPU: 0.0%")
-            .class("status-text");
-        Label::new(cx, "Cache: 0 MB")
+        Label::new(cx, Data::voices.map(|v| format!("Voices: {}", v)))
             .class("status-text");
+        // Refreshed with the tick so cache and connectivity stay current
+        Label::new(
+            cx,
+            Data::tick.map(|_| {
+                let stats = crate::preset::sample_cache::global().stats();
+                format!("Cache: {}", crate::preset::memory::format_bytes(stats.bytes))
+            }),
+        )
+        .class("status-text");
+        Label::new(
+            cx,
+            Data::tick.map(|_| crate::net::connectivity::global().state().label().to_string()),
+        )
+        .class("status-text");
     })
     .id("status-bar");
 }
 
+/// Build the piano strip: octave controls, target slot, keyboard.
+fn build_piano(cx: &mut Context) {
+    VStack::new(cx, |cx| {
+        HStack::new(cx, |cx| {
+            Button::new(
+                cx,
+                |cx| cx.emit(AppEvent::ShiftOctave(-1)),
+                |cx| Label::new(cx, "\u{25C0}"),
+            )
+            .class("slot-btn");
+            Label::new(
+                cx,
+                Data::octave_offset.map(|o| {
+                    let base = base_note_for_offset(*o);
+                    format!("{}\u{2013}{}", note_name(base), note_name(base + 23))
+                }),
+            )
+            .class("piano-range");
+            Button::new(
+                cx,
+                |cx| cx.emit(AppEvent::ShiftOctave(1)),
+                |cx| Label::new(cx, "\u{25B6}"),
+            )
+            .class("slot-btn");
+            Label::new(
+                cx,
+                Data::selected_slot.map(|s| format!("Playing Slot {}", s + 1)),
+            )
+            .class("piano-slot");
+        })
+        .height(Auto)
+        .col_between(Pixels(8.0));
+
+        Binding::new(cx, Data::octave_offset, |cx, offset| {
+            PianoKeyboard::new(cx, base_note_for_offset(offset.get(cx)))
+                .width(Stretch(1.0))
+                .height(Pixels(70.0));
+        });
+    })
+    .id("piano-panel")
+    .height(Auto);
+}
+
 /// Build the settings panel.
 fn build_settings(cx: &mut Context) {
     VStack::new(cx, |cx| {
@@ -315,15 +567,63 @@ fn build_settings(cx: &mut Context) {
             .width(Stretch(1.0))
             .background_color(Color::rgb(49, 50, 68));
 
-        Label::new(cx, "Master Volume:");
-        // TODO: ParamSlider for master_volume
-        // ParamSlider::new(cx, Data::params, |p| &p.master_volume);
+        Label::new(cx, "Library URL:");
+        Label::new(
+            cx,
+            Data::preset_manager.map(|pm| {
+                pm.lock().map(|pm| pm.base_url.clone()).unwrap_or_default()
+            }),
+        )
+        .class("settings-value");
 
-        Label::new(cx, "Master Pan:");
-        // TODO: ParamSlider for master_pan
+        HStack::new(cx, |cx| {
+            Label::new(cx, "Editor:");
+            for &option in EditorFrontend::available() {
+                Button::new(
+                    cx,
+                    move |cx| cx.emit(AppEvent::SetFrontend(option)),
+                    move |cx| Label::new(cx, option.label()),
+                )
+                .class("tab-button");
+            }
+            Label::new(cx, "(applies when the editor is reopened)");
+        })
+        .class("settings-row");
+
+        HStack::new(cx, |cx| {
+            Label::new(cx, "Audio / MIDI:");
+            Label::new(cx, "Devices, sample rate and buffer size are set by the host");
+        })
+        .class("settings-row");
+
+        Element::new(cx)
+            .height(Pixels(1.0))
+            .width(Stretch(1.0))
+            .background_color(Color::rgb(49, 50, 68));
+
+        HStack::new(cx, |cx| {
+            Label::new(cx, "Master Volume:");
+            ParamSlider::new(cx, Data::params, |p| &p.master_volume);
+        })
+        .class("settings-row");
+
+        HStack::new(cx, |cx| {
+            Label::new(cx, "Master Pan:");
+            ParamSlider::new(cx, Data::params, |p| &p.master_pan);
+        })
+        .class("settings-row");
 
-        Label::new(cx, "Max Voices:");
-        // TODO: ParamSlider for max_voices
+        HStack::new(cx, |cx| {
+            Label::new(cx, "Max Voices:");
+            ParamSlider::new(cx, Data::params, |p| &p.max_voices);
+        })
+        .class("settings-row");
+
+        HStack::new(cx, |cx| {
+            Label::new(cx, "Pitch Bend Range:");
+            ParamSlider::new(cx, Data::params, |p| &p.pitch_bend_range);
+        })
+        .class("settings-row");
 
         Element::new(cx)
             .height(Pixels(1.0))
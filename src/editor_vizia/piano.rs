@@ -0,0 +1,142 @@
+//! Piano keyboard view (2 octaves) for the VIZIA editor.
+//!
+//! Key layout and note ranges match the egui piano. Presses are emitted as
+//! [`AppEvent::PianoNoteOn`] / [`AppEvent::PianoNoteOff`]; the `Data` model
+//! forwards them to the selected slot over the shared `EditorEvent` channel.
+
+use nih_plug_vizia::vizia::prelude::*;
+use nih_plug_vizia::vizia::vg;
+
+use super::AppEvent;
+use crate::editor::piano::{is_black_key, NUM_SEMITONES, NUM_WHITE_KEYS};
+
+/// Catppuccin colors used by the keys (same as the egui piano).
+const WHITE_KEY: (u8, u8, u8) = (205, 214, 244);
+const BLACK_KEY: (u8, u8, u8) = (30, 30, 46);
+const ACTIVE_KEY: (u8, u8, u8) = (137, 180, 250);
+const KEY_BORDER: (u8, u8, u8) = (69, 71, 90);
+
+/// Clickable 2-octave keyboard starting at `base_note`.
+pub struct PianoKeyboard {
+    base_note: u8,
+    /// Note held by the mouse (for drag-across-keys).
+    held: Option<u8>,
+}
+
+impl PianoKeyboard {
+    pub fn new(cx: &mut Context, base_note: u8) -> Handle<'_, Self> {
+        Self {
+            base_note,
+            held: None,
+        }
+        .build(cx, |_| {})
+    }
+
+    /// Key rectangles as `(note, x, y, w, h)`, black keys last.
+    fn layout(&self, bounds: BoundingBox) -> Vec<(u8, f32, f32, f32, f32)> {
+        let white_w = bounds.w / NUM_WHITE_KEYS as f32;
+        let black_w = white_w * 0.6;
+        let black_h = bounds.h * 0.6;
+
+        let mut whites = Vec::with_capacity(NUM_WHITE_KEYS);
+        let mut blacks = Vec::with_capacity(NUM_SEMITONES - NUM_WHITE_KEYS);
+        let mut white_idx = 0;
+        for semitone in 0..NUM_SEMITONES as u8 {
+            let note = self.base_note.saturating_add(semitone);
+            if is_black_key(semitone) {
+                let x = bounds.x + white_idx as f32 * white_w - black_w * 0.5;
+                blacks.push((note, x, bounds.y + bounds.h - black_h, black_w, black_h));
+            } else {
+                let x = bounds.x + white_idx as f32 * white_w;
+                whites.push((note, x, bounds.y, white_w, bounds.h));
+                white_idx += 1;
+            }
+        }
+        whites.extend(blacks);
+        whites
+    }
+
+    /// Note under the pointer (black keys overlap white keys).
+    fn hit(&self, bounds: BoundingBox, x: f32, y: f32) -> Option<u8> {
+        self.layout(bounds)
+            .into_iter()
+            .rev()
+            .find(|&(_, kx, ky, kw, kh)| x >= kx && x < kx + kw && y >= ky && y < ky + kh)
+            .map(|(note, ..)| note)
+    }
+
+    fn release(&mut self, cx: &mut EventContext) {
+        if let Some(note) = self.held.take() {
+            cx.emit(AppEvent::PianoNoteOff(note));
+        }
+    }
+}
+
+impl View for PianoKeyboard {
+    fn element(&self) -> Option<&'static str> {
+        Some("piano-keyboard")
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|window_event, meta| match *window_event {
+            WindowEvent::MouseDown(MouseButton::Left) => {
+                let bounds = cx.cache.get_bounds(cx.current());
+                if let Some(note) = self.hit(bounds, cx.mouse().cursorx, cx.mouse().cursory) {
+                    cx.capture();
+                    self.held = Some(note);
+                    cx.emit(AppEvent::PianoNoteOn(note));
+                    cx.needs_redraw();
+                    meta.consume();
+                }
+            }
+            WindowEvent::MouseMove(x, y) => {
+                if self.held.is_some() {
+                    let bounds = cx.cache.get_bounds(cx.current());
+                    let note = self.hit(bounds, x, y);
+                    if note.is_some() && note != self.held {
+                        // Glissando: release the old key, press the new one
+                        self.release(cx);
+                        self.held = note;
+                        if let Some(note) = note {
+                            cx.emit(AppEvent::PianoNoteOn(note));
+                        }
+                        cx.needs_redraw();
+                    }
+                }
+            }
+            WindowEvent::MouseUp(MouseButton::Left) => {
+                if self.held.is_some() {
+                    cx.release();
+                    self.release(cx);
+                    cx.needs_redraw();
+                }
+            }
+            _ => {}
+        });
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let bounds = cx.bounds();
+        if bounds.w == 0.0 || bounds.h == 0.0 {
+            return;
+        }
+
+        let rgb = |(r, g, b): (u8, u8, u8)| vg::Color::rgb(r, g, b);
+        let mut border = vg::Paint::color(rgb(KEY_BORDER));
+        border.set_line_width(1.0);
+
+        for (note, x, y, w, h) in self.layout(bounds) {
+            let fill = if self.held == Some(note) {
+                ACTIVE_KEY
+            } else if is_black_key(note.wrapping_sub(self.base_note)) {
+                BLACK_KEY
+            } else {
+                WHITE_KEY
+            };
+            let mut path = vg::Path::new();
+            path.rect(x, y, w, h);
+            canvas.fill_path(&path, &vg::Paint::color(rgb(fill)));
+            canvas.stroke_path(&path, &border);
+        }
+    }
+}
@@ -39,7 +39,10 @@ pub fn build(cx: &mut Context) {
             // Re-read plugin state to build slot cards
             Binding::new(cx, Data::plugin_state, |cx, ps_lens| {
                 let ps_arc = ps_lens.get(cx);
-                build_slot_list(cx, &ps_arc);
+                Binding::new(cx, Data::selected_slot, move |cx, selected| {
+                    let selected = selected.get(cx);
+                    build_slot_list(cx, &ps_arc, selected);
+                });
             });
         })
         .height(Stretch(1.0));
@@ -48,7 +51,7 @@ pub fn build(cx: &mut Context) {
 }
 
 /// Build the list of slot cards from a snapshot of PluginState.
-fn build_slot_list(cx: &mut Context, ps_arc: &Arc<Mutex<PluginState>>) {
+fn build_slot_list(cx: &mut Context, ps_arc: &Arc<Mutex<PluginState>>, selected: usize) {
     let Ok(ps) = ps_arc.lock() else { return };
 
     if ps.slot_configs.is_empty() {
@@ -62,12 +65,17 @@ fn build_slot_list(cx: &mut Context, ps_arc: &Arc<Mutex<PluginState>>) {
     drop(ps);
 
     for (idx, config) in slots.iter().enumerate() {
-        build_slot_card(cx, idx, config);
+        build_slot_card(cx, idx, config, idx == selected);
     }
 }
 
 /// Build a single slot card.
-fn build_slot_card(cx: &mut Context, idx: usize, config: &crate::state::SlotConfig) {
+fn build_slot_card(
+    cx: &mut Context,
+    idx: usize,
+    config: &crate::state::SlotConfig,
+    is_selected: bool,
+) {
     let name = if let Some(ref preset_id) = config.preset_id {
         preset_id.clone()
     } else if !config.source_code.is_empty() {
@@ -153,7 +161,10 @@ fn build_slot_card(cx: &mut Context, idx: usize, config: &crate::state::SlotConf
                 .font_size(11.0);
         }
     })
-    .class("slot-card");
+    .class("slot-card")
+    .checked(is_selected)
+    // Clicking a card makes it the piano's target
+    .on_press(move |cx| cx.emit(AppEvent::SelectSlot(idx)));
 }
 
 /// Convert a MIDI note number to a name (e.g., 60 → "C4").
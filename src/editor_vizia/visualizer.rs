@@ -0,0 +1,127 @@
+//! Output visualizer (peak/RMS meters and waveform) for the VIZIA editor.
+//!
+//! Reads the same lock-free [`VisualizerState`] the audio thread feeds for
+//! the egui editor. The view redraws on every editor tick.
+
+use std::sync::Arc;
+
+use nih_plug_vizia::vizia::prelude::*;
+use nih_plug_vizia::vizia::vg;
+
+use crate::editor::visualizer::VisualizerState;
+
+const METER_BG: (u8, u8, u8) = (49, 50, 68);
+const WAVE_BG: (u8, u8, u8) = (17, 17, 27);
+const CENTER_LINE: (u8, u8, u8) = (69, 71, 90);
+const GREEN: (u8, u8, u8) = (166, 227, 161);
+const YELLOW: (u8, u8, u8) = (249, 226, 175);
+const RED: (u8, u8, u8) = (243, 139, 168);
+const TEAL: (u8, u8, u8) = (148, 226, 213);
+const MAUVE: (u8, u8, u8) = (203, 166, 247);
+
+/// Height of the meter section, as a fraction of the view.
+const METER_FRACTION: f32 = 0.3;
+
+/// Stereo peak/RMS meters above a scrolling waveform.
+pub struct OutputVisualizer {
+    state: Arc<VisualizerState>,
+}
+
+impl OutputVisualizer {
+    pub fn new(cx: &mut Context, state: Arc<VisualizerState>) -> Handle<'_, Self> {
+        Self { state }.build(cx, |_| {})
+    }
+}
+
+/// Meter color for a linear peak level (same thresholds as the egui meter).
+fn level_color(peak: f32) -> (u8, u8, u8) {
+    if peak > 1.0 {
+        RED
+    } else if peak > 0.707 {
+        YELLOW
+    } else {
+        GREEN
+    }
+}
+
+fn paint((r, g, b): (u8, u8, u8), alpha: f32) -> vg::Paint {
+    let mut color = vg::Color::rgb(r, g, b);
+    color.set_alphaf(alpha);
+    vg::Paint::color(color)
+}
+
+fn fill_rect(canvas: &mut Canvas, x: f32, y: f32, w: f32, h: f32, paint: &vg::Paint) {
+    let mut path = vg::Path::new();
+    path.rect(x, y, w, h);
+    canvas.fill_path(&path, paint);
+}
+
+impl View for OutputVisualizer {
+    fn element(&self) -> Option<&'static str> {
+        Some("output-visualizer")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let bounds = cx.bounds();
+        if bounds.w == 0.0 || bounds.h == 0.0 {
+            return;
+        }
+
+        // --- Meters ---
+        let meter_h = bounds.h * METER_FRACTION;
+        fill_rect(canvas, bounds.x, bounds.y, bounds.w, meter_h, &paint(METER_BG, 1.0));
+
+        let (peak_l, peak_r) = self.state.peak_levels();
+        let (rms_l, rms_r) = self.state.rms_levels();
+        let spacing = 4.0;
+        let bar_w = ((bounds.w - spacing * 3.0) / 2.0).max(4.0);
+        let bar_h = meter_h - spacing * 2.0;
+        for (i, (peak, rms)) in [(peak_l, rms_l), (peak_r, rms_r)].into_iter().enumerate() {
+            let x = bounds.x + spacing + i as f32 * (bar_w + spacing);
+            let bottom = bounds.y + spacing + bar_h;
+            let color = level_color(peak);
+            let peak_h = peak.min(1.0) * bar_h;
+            fill_rect(canvas, x, bottom - peak_h, bar_w, peak_h, &paint(color, 0.4));
+            let rms_h = rms.min(1.0) * bar_h;
+            fill_rect(canvas, x, bottom - rms_h, bar_w, rms_h, &paint(color, 0.8));
+        }
+
+        // --- Waveform ---
+        let wave_y = bounds.y + meter_h + spacing;
+        let wave_h = (bounds.h - meter_h - spacing).max(1.0);
+        fill_rect(canvas, bounds.x, wave_y, bounds.w, wave_h, &paint(WAVE_BG, 1.0));
+
+        let center_y = wave_y + wave_h / 2.0;
+        let half_h = wave_h / 2.0;
+        let mut center = vg::Path::new();
+        center.move_to(bounds.x, center_y);
+        center.line_to(bounds.x + bounds.w, center_y);
+        let mut center_paint = paint(CENTER_LINE, 1.0);
+        center_paint.set_line_width(0.5);
+        canvas.stroke_path(&center, &center_paint);
+
+        let width = self.state.width().max(1) as f32;
+        self.state.with_waveform(|left, right, cursor| {
+            for (buffer, color) in [(left, TEAL), (right, MAUVE)] {
+                let len = buffer.len();
+                if len < 2 {
+                    continue;
+                }
+                let mut path = vg::Path::new();
+                for i in 0..len {
+                    let sample = buffer[(cursor + i) % len].clamp(-1.0, 1.0);
+                    let x = bounds.x + (i as f32 / width) * bounds.w;
+                    let y = center_y - sample * half_h;
+                    if i == 0 {
+                        path.move_to(x, y);
+                    } else {
+                        path.line_to(x, y);
+                    }
+                }
+                let mut stroke = paint(color, 0.7);
+                stroke.set_line_width(1.0);
+                canvas.stroke_path(&path, &stroke);
+            }
+        });
+    }
+}
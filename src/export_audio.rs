@@ -0,0 +1,212 @@
+//! WAV export shared by everything that writes rendered audio to disk
+//! (currently the bounce of a frozen slot; see `editor::freeze`).
+//!
+//! The engine renders 32-bit float. Exporting at another sample rate
+//! resamples with a windowed-sinc interpolator, and exporting to 16 or 24
+//! bit integer adds TPDF dither — the sum of two uniform random values,
+//! ±1 LSB peak — before rounding, so the quantisation error becomes a
+//! steady noise floor instead of distortion that follows the signal.
+
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Sample format of the written file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BitDepth {
+    Int16,
+    #[default]
+    Int24,
+    Float32,
+}
+
+impl BitDepth {
+    pub const ALL: [BitDepth; 3] = [BitDepth::Int16, BitDepth::Int24, BitDepth::Float32];
+
+    pub fn bits(self) -> u16 {
+        match self {
+            BitDepth::Int16 => 16,
+            BitDepth::Int24 => 24,
+            BitDepth::Float32 => 32,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BitDepth::Int16 => "16-bit",
+            BitDepth::Int24 => "24-bit",
+            BitDepth::Float32 => "32-bit float",
+        }
+    }
+}
+
+/// Export sample rates offered besides the render rate.
+pub const SAMPLE_RATES: [u32; 4] = [44100, 48000, 88200, 96000];
+
+/// Output format options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportFormat {
+    pub bit_depth: BitDepth,
+    /// `None` keeps the rate the audio was rendered at.
+    pub sample_rate: Option<u32>,
+    /// TPDF dither when writing integer samples.
+    pub dither: bool,
+}
+
+impl Default for ExportFormat {
+    fn default() -> Self {
+        Self { bit_depth: BitDepth::default(), sample_rate: None, dither: true }
+    }
+}
+
+/// TPDF dither source (xorshift, so exports are reproducible).
+struct Dither {
+    state: u32,
+}
+
+impl Dither {
+    fn new() -> Self {
+        Self { state: 0x9E37_79B9 }
+    }
+
+    fn uniform(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state as f32 / u32::MAX as f32 - 0.5
+    }
+
+    /// Triangular noise in (-1, 1), in LSBs.
+    fn next(&mut self) -> f32 {
+        self.uniform() + self.uniform()
+    }
+}
+
+/// Quantise `sample` (±1.0 full scale) to a signed integer of `bits`,
+/// optionally dithered.
+fn quantize(sample: f32, bits: u16, dither: Option<&mut Dither>) -> i32 {
+    let max = ((1_i64 << (bits - 1)) - 1) as f32;
+    let noise = dither.map_or(0.0, Dither::next);
+    (sample * max + noise).round().clamp(-max - 1.0, max) as i32
+}
+
+/// Half-width of the resampling kernel, in zero crossings.
+const SINC_HALF_WIDTH: i64 = 16;
+
+/// Resample `input` from `from` to `to` Hz with a Blackman-windowed sinc,
+/// band-limited to the lower of the two Nyquist frequencies.
+pub fn resample(input: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || input.is_empty() || from == 0 || to == 0 {
+        return input.to_vec();
+    }
+    let ratio = to as f64 / from as f64;
+    let cutoff = ratio.min(1.0);
+    let half_width = SINC_HALF_WIDTH as f64 / cutoff;
+    let out_len = (input.len() as f64 * ratio).round() as usize;
+    (0..out_len)
+        .map(|n| {
+            let centre = n as f64 / ratio;
+            let first = (centre - half_width).ceil().max(0.0) as usize;
+            let last = ((centre + half_width).floor() as usize).min(input.len() - 1);
+            let mut acc = 0.0;
+            for (i, x) in input.iter().enumerate().take(last + 1).skip(first) {
+                let t = i as f64 - centre;
+                let arg = std::f64::consts::PI * t * cutoff;
+                let sinc = if arg == 0.0 { 1.0 } else { arg.sin() / arg };
+                let phase = std::f64::consts::PI * (t / half_width + 1.0);
+                let window = 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+                acc += *x as f64 * sinc * window * cutoff;
+            }
+            acc as f32
+        })
+        .collect()
+}
+
+/// Encode a stereo buffer rendered at `source_rate` as a WAV file.
+pub fn encode_wav(left: &[f32], right: &[f32], source_rate: u32, format: ExportFormat) -> Result<Vec<u8>, String> {
+    let rate = format.sample_rate.unwrap_or(source_rate);
+    let left = resample(left, source_rate, rate);
+    let right = resample(right, source_rate, rate);
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: rate,
+        bits_per_sample: format.bit_depth.bits(),
+        sample_format: match format.bit_depth {
+            BitDepth::Float32 => hound::SampleFormat::Float,
+            _ => hound::SampleFormat::Int,
+        },
+    };
+
+    let mut bytes = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut bytes, spec).map_err(|e| e.to_string())?;
+    let mut dither = format.dither.then(Dither::new);
+    for (l, r) in left.iter().zip(&right) {
+        for s in [*l, *r] {
+            match format.bit_depth {
+                BitDepth::Float32 => writer.write_sample(s),
+                depth => writer.write_sample(quantize(s, depth.bits(), dither.as_mut())),
+            }
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    writer.finalize().map_err(|e| e.to_string())?;
+    Ok(bytes.into_inner())
+}
+
+/// Write a stereo buffer rendered at `source_rate` to `path` as WAV.
+pub fn write_wav(path: &Path, left: &[f32], right: &[f32], source_rate: u32, format: ExportFormat) -> Result<(), String> {
+    let bytes = encode_wav(left, right, source_rate, format)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    crate::net::atomic::write_atomic(path, &bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Folder exported audio goes to.
+pub fn export_dir() -> Option<PathBuf> {
+    directories::UserDirs::new()
+        .and_then(|d| d.document_dir().map(|p| p.join("SongWalker").join("Bounces")))
+        .or_else(|| {
+            directories::ProjectDirs::from("org", "SongWalker", "songwalker-vsti").map(|d| d.data_dir().join("bounces"))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_dither_and_resample() {
+        let tone: Vec<f32> = (0..4800).map(|n| 0.5 * (n as f32 * std::f32::consts::TAU / 48.0).sin()).collect();
+
+        let format = ExportFormat { bit_depth: BitDepth::Int16, sample_rate: None, dither: true };
+        let bytes = encode_wav(&tone, &tone, 48000, format).unwrap();
+        let mut reader = hound::WavReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.spec().bits_per_sample, 16);
+        let samples: Vec<i32> = reader.samples::<i32>().map(Result::unwrap).collect();
+        assert_eq!(samples.len(), tone.len() * 2);
+        // Dither stays within ±1 LSB of the undithered value
+        for (s, x) in samples.iter().step_by(2).zip(&tone) {
+            assert!((s - quantize(*x, 16, None)).abs() <= 1);
+        }
+
+        // Silence dithers to at most ±1 LSB and isn't all zero
+        let mut dither = Dither::new();
+        let noise: Vec<i32> = (0..1000).map(|_| quantize(0.0, 16, Some(&mut dither))).collect();
+        assert!(noise.iter().all(|s| s.abs() <= 1) && noise.iter().any(|s| *s != 0));
+
+        // A 1 kHz tone resampled to 44.1 kHz keeps its length and level
+        let resampled = resample(&tone, 48000, 44100);
+        assert_eq!(resampled.len(), 4410);
+        let peak = resampled[100..4300].iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+        assert!((peak - 0.5).abs() < 0.01, "peak {peak}");
+
+        let format = ExportFormat { bit_depth: BitDepth::Float32, sample_rate: Some(96000), dither: true };
+        let bytes = encode_wav(&tone, &tone, 48000, format).unwrap();
+        let reader = hound::WavReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.spec().sample_rate, 96000);
+        assert_eq!(reader.len() as usize, tone.len() * 4);
+    }
+}
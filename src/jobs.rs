@@ -0,0 +1,328 @@
+//! Shared background job pool for network and decode work.
+//!
+//! One pool is owned by the plugin (or standalone app) and shared with the
+//! editor. A fixed set of worker threads — each with its own async runtime,
+//! created once — pulls jobs from two queues: interactive jobs (the user
+//! clicked something) always run before background jobs. The number of
+//! workers caps how many downloads run at once.
+//!
+//! Jobs can be cancelled through their [`JobHandle`]. A queued job that is
+//! cancelled never runs; a running job sees the cancellation through
+//! [`JobContext::is_cancelled`], and [`JobContext::block_on`] abandons the
+//! future it is waiting on.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+
+use tokio::sync::Notify;
+
+/// Maximum number of jobs (downloads) running at the same time.
+pub const MAX_CONCURRENT_JOBS: usize = 4;
+
+/// Scheduling priority of a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobPriority {
+    /// Triggered directly by the user; runs before any background job.
+    Interactive,
+    /// Speculative or bulk work (indexing, prefetching).
+    Background,
+}
+
+/// Cancellation flag shared between a job and its handle.
+#[derive(Default)]
+struct CancelToken {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancelToken {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+        self.notify.notify_waiters();
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
+/// Handle to a submitted job, used to cancel it.
+#[derive(Clone)]
+pub struct JobHandle {
+    token: Arc<CancelToken>,
+}
+
+impl JobHandle {
+    /// Request cancellation. Has no effect if the job already finished.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+/// Passed to a running job: access to the worker's runtime and its
+/// cancellation state.
+pub struct JobContext<'a> {
+    runtime: &'a tokio::runtime::Runtime,
+    token: &'a CancelToken,
+}
+
+impl JobContext<'_> {
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Run a future to completion on the worker's runtime.
+    ///
+    /// Returns `None` if the job was cancelled before or while waiting.
+    pub fn block_on<F: Future>(&self, future: F) -> Option<F::Output> {
+        let token = self.token;
+        self.runtime.block_on(async move {
+            // Created before the check so a concurrent cancel can't be missed
+            let cancelled = token.notify.notified();
+            if token.is_cancelled() {
+                return None;
+            }
+            tokio::select! {
+                output = future => Some(output),
+                _ = cancelled => None,
+            }
+        })
+    }
+}
+
+type JobFn = Box<dyn FnOnce(&JobContext) + Send>;
+
+struct QueuedJob {
+    run: JobFn,
+    token: Arc<CancelToken>,
+}
+
+#[derive(Default)]
+struct Queues {
+    interactive: VecDeque<QueuedJob>,
+    background: VecDeque<QueuedJob>,
+    shutdown: bool,
+}
+
+impl Queues {
+    /// Next job to run, skipping cancelled ones.
+    fn pop(&mut self) -> Option<QueuedJob> {
+        while let Some(job) = self
+            .interactive
+            .pop_front()
+            .or_else(|| self.background.pop_front())
+        {
+            if !job.token.is_cancelled() {
+                return Some(job);
+            }
+        }
+        None
+    }
+
+    fn len(&self) -> usize {
+        self.interactive.len() + self.background.len()
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    queues: Mutex<Queues>,
+    available: Condvar,
+}
+
+/// Fixed-size pool of background workers.
+///
+/// Jobs submitted before [`JobPool::start`] are queued and run once the
+/// workers are up, so the pool can be created (and handed to the editor)
+/// before the plugin is initialized.
+pub struct JobPool {
+    shared: Arc<Shared>,
+    workers: usize,
+    started: AtomicBool,
+}
+
+impl Default for JobPool {
+    fn default() -> Self {
+        Self::new(MAX_CONCURRENT_JOBS)
+    }
+}
+
+impl JobPool {
+    /// Create a pool with `workers` threads (not started yet).
+    pub fn new(workers: usize) -> Self {
+        Self {
+            shared: Arc::new(Shared::default()),
+            workers: workers.max(1),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Spawn the worker threads. Calling this again is a no-op.
+    pub fn start(&self) {
+        if self.started.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        for i in 0..self.workers {
+            let shared = self.shared.clone();
+            let spawned = std::thread::Builder::new()
+                .name(format!("songwalker-job-{}", i))
+                .spawn(move || worker_loop(shared));
+            if let Err(e) = spawned {
+                log::warn!("Failed to spawn job worker {}: {}", i, e);
+            }
+        }
+    }
+
+    /// Queue a job. Returns a handle that can cancel it.
+    pub fn submit(
+        &self,
+        priority: JobPriority,
+        job: impl FnOnce(&JobContext) + Send + 'static,
+    ) -> JobHandle {
+        let token = Arc::new(CancelToken::default());
+        let queued = QueuedJob {
+            run: Box::new(job),
+            token: token.clone(),
+        };
+        if let Ok(mut queues) = self.shared.queues.lock() {
+            match priority {
+                JobPriority::Interactive => queues.interactive.push_back(queued),
+                JobPriority::Background => queues.background.push_back(queued),
+            }
+        }
+        self.shared.available.notify_one();
+        JobHandle { token }
+    }
+
+    /// Number of jobs waiting for a worker.
+    pub fn queued(&self) -> usize {
+        self.shared.queues.lock().map(|q| q.len()).unwrap_or(0)
+    }
+}
+
+impl Drop for JobPool {
+    fn drop(&mut self) {
+        // Cancel pending work and let the workers exit on their own; running
+        // downloads are abandoned rather than joined.
+        if let Ok(mut queues) = self.shared.queues.lock() {
+            let queues = &mut *queues;
+            for job in queues.interactive.drain(..).chain(queues.background.drain(..)) {
+                job.token.cancel();
+            }
+            queues.shutdown = true;
+        }
+        self.shared.available.notify_all();
+    }
+}
+
+fn worker_loop(shared: Arc<Shared>) {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(rt) => rt,
+        Err(e) => {
+            log::warn!("Job worker failed to create async runtime: {}", e);
+            return;
+        }
+    };
+
+    loop {
+        let job = {
+            let Ok(mut queues) = shared.queues.lock() else {
+                return;
+            };
+            loop {
+                if queues.shutdown {
+                    return;
+                }
+                if let Some(job) = queues.pop() {
+                    break job;
+                }
+                queues = match shared.available.wait(queues) {
+                    Ok(q) => q,
+                    Err(_) => return,
+                };
+            }
+        };
+
+        let ctx = JobContext {
+            runtime: &runtime,
+            token: &job.token,
+        };
+        (job.run)(&ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_jobs_run_after_start() {
+        let pool = JobPool::new(1);
+        let (tx, rx) = crossbeam_channel::unbounded();
+        pool.submit(JobPriority::Interactive, move |_| {
+            let _ = tx.send(1);
+        });
+        assert_eq!(pool.queued(), 1, "nothing runs before start()");
+        pool.start();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(1));
+    }
+
+    #[test]
+    fn test_interactive_runs_before_background() {
+        let pool = JobPool::new(1);
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let tx2 = tx.clone();
+        pool.submit(JobPriority::Background, move |_| {
+            let _ = tx.send("background");
+        });
+        pool.submit(JobPriority::Interactive, move |_| {
+            let _ = tx2.send("interactive");
+        });
+        pool.start();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok("interactive"));
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok("background"));
+    }
+
+    #[test]
+    fn test_cancelled_job_never_runs() {
+        let pool = JobPool::new(1);
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let tx2 = tx.clone();
+        let handle = pool.submit(JobPriority::Interactive, move |_| {
+            let _ = tx.send("cancelled");
+        });
+        pool.submit(JobPriority::Interactive, move |_| {
+            let _ = tx2.send("kept");
+        });
+        handle.cancel();
+        pool.start();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok("kept"));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_block_on_returns_none_when_cancelled() {
+        let pool = JobPool::new(1);
+        pool.start();
+        let (started_tx, started_rx) = crossbeam_channel::unbounded();
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let handle = pool.submit(JobPriority::Interactive, move |ctx| {
+            let _ = started_tx.send(());
+            let result = ctx.block_on(std::future::pending::<()>());
+            let _ = tx.send(result.is_none());
+        });
+        started_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        handle.cancel();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(true));
+    }
+}
@@ -6,16 +6,27 @@
 use nih_plug::prelude::*;
 
 pub mod audio;
+pub mod config;
 pub mod editor;
+#[cfg(feature = "vizia-editor")]
+pub mod editor_vizia;
+pub mod export_audio;
+pub mod jobs;
+pub mod logs;
+pub mod meter;
 pub mod midi;
+pub mod monitor;
+pub mod net;
 pub mod params;
 pub mod perf;
 pub mod plugin;
 pub mod preset;
+pub mod programs;
 pub mod slots;
 pub mod standalone;
 pub mod state;
 pub mod transport;
+pub mod view_model;
 
 pub use plugin::SongWalkerPlugin;
 
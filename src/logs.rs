@@ -0,0 +1,179 @@
+//! In-memory log buffer for the editor's log console.
+//!
+//! `install` puts a logger in front of the usual one (env_logger in the
+//! standalone) that keeps the most recent records in a bounded ring buffer
+//! and still forwards everything to the inner logger. Inside a plugin host
+//! nih-plug has already installed its logger, so only what is `push`ed
+//! directly (the editor's status messages) reaches the buffer there.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Records kept; older ones are dropped.
+pub const CAPACITY: usize = 2000;
+
+/// One captured log record.
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    /// Seconds since the Unix epoch.
+    pub time: f64,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl LogEntry {
+    /// One line of text, as copied or exported.
+    pub fn line(&self) -> String {
+        let secs = self.time as u64;
+        format!(
+            "{:02}:{:02}:{:02}.{:03} {:<5} [{}] {}",
+            secs / 3600 % 24,
+            secs / 60 % 60,
+            secs % 60,
+            (self.time.fract() * 1000.0) as u32,
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+/// Bounded ring buffer of recent records.
+pub struct LogBuffer {
+    entries: Mutex<VecDeque<LogEntry>>,
+    /// Most verbose level captured (as `LevelFilter` discriminant).
+    level: AtomicUsize,
+    /// Records dropped to stay within capacity, so the console can say so.
+    dropped: AtomicUsize,
+}
+
+impl LogBuffer {
+    fn new() -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::with_capacity(CAPACITY)),
+            level: AtomicUsize::new(LevelFilter::Info as usize),
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    /// Most verbose level being captured.
+    pub fn level(&self) -> LevelFilter {
+        match self.level.load(Ordering::Relaxed) {
+            0 => LevelFilter::Off,
+            1 => LevelFilter::Error,
+            2 => LevelFilter::Warn,
+            3 => LevelFilter::Info,
+            4 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    }
+
+    pub fn set_level(&self, level: LevelFilter) {
+        self.level.store(level as usize, Ordering::Relaxed);
+    }
+
+    /// Add a record if its level is captured.
+    pub fn push(&self, level: Level, target: &str, message: String) {
+        if level > self.level() {
+            return;
+        }
+        let time = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or_default();
+        let Ok(mut entries) = self.entries.lock() else { return };
+        if entries.len() == CAPACITY {
+            entries.pop_front();
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        entries.push_back(LogEntry { time, level, target: target.to_string(), message });
+    }
+
+    /// Copy of the buffered records, oldest first.
+    pub fn snapshot(&self) -> Vec<LogEntry> {
+        self.entries.lock().map(|e| e.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Records dropped so far.
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+/// The process-wide buffer.
+pub fn buffer() -> &'static LogBuffer {
+    static BUFFER: OnceLock<LogBuffer> = OnceLock::new();
+    BUFFER.get_or_init(LogBuffer::new)
+}
+
+/// Add a message to the buffer without going through `log`.
+pub fn push(level: Level, target: &str, message: impl Into<String>) {
+    buffer().push(level, target, message.into());
+}
+
+/// Logger that copies records into the buffer before passing them on.
+struct TeeLogger {
+    inner: Box<dyn Log>,
+}
+
+impl Log for TeeLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= buffer().level() || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        buffer().push(record.level(), record.target(), record.args().to_string());
+        if self.inner.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Install the buffering logger in front of `inner`. Fails if a logger is
+/// already installed.
+pub fn install(inner: Box<dyn Log>) -> Result<(), log::SetLoggerError> {
+    log::set_boxed_logger(Box::new(TeeLogger { inner }))?;
+    // Filtering happens in the buffer and the inner logger
+    log::set_max_level(LevelFilter::Trace);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_is_bounded_and_filtered() {
+        let buffer = LogBuffer::new();
+        buffer.push(Level::Debug, "test", "hidden".into());
+        assert!(buffer.snapshot().is_empty());
+
+        for i in 0..CAPACITY + 5 {
+            buffer.push(Level::Warn, "test", format!("message {}", i));
+        }
+        let entries = buffer.snapshot();
+        assert_eq!(entries.len(), CAPACITY);
+        assert_eq!(entries[0].message, "message 5");
+        assert_eq!(buffer.dropped(), 5);
+
+        buffer.set_level(LevelFilter::Trace);
+        buffer.push(Level::Trace, "test", "shown".into());
+        assert_eq!(buffer.snapshot().last().unwrap().message, "shown");
+        assert!(buffer.snapshot().last().unwrap().line().contains("TRACE [test] shown"));
+    }
+}
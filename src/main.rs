@@ -5,8 +5,10 @@
 /// This gives us runtime audio device switching, PulseAudio/PipeWire support,
 /// and MIDI device selection from the Settings panel.
 fn main() {
-    // Initialize logger for easier automated testing.
-    env_logger::init();
+    // Initialize logger for easier automated testing. Records are also kept
+    // for the editor's log console.
+    let stderr = env_logger::Builder::from_default_env().build();
+    let _ = songwalker_vsti::logs::install(Box::new(stderr));
 
     // Ensure all panics are logged properly before crashing.
     std::panic::set_hook(Box::new(|panic_info| {
@@ -24,6 +26,42 @@ fn main() {
         eprintln!("CRASH in {}:{}: {}", filename, line, message);
     }));
 
+    // `--bench [iterations]`: time the audio path and exit
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(pos) = args.iter().position(|a| a == "--bench") {
+        use songwalker_vsti::perf::bench;
+        let iterations = args
+            .get(pos + 1)
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(bench::DEFAULT_ITERATIONS);
+        bench::run_cli(iterations);
+        return;
+    }
+
+    // `--render-check [--bless] [--strict]`: compare offline renders with
+    // the stored references and exit
+    if args.iter().any(|a| a == "--render-check") {
+        use songwalker_vsti::perf::render_check;
+        let bless = args.iter().any(|a| a == "--bless");
+        let strict = args.iter().any(|a| a == "--strict");
+        if !render_check::run_cli(bless, strict) {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // The VIZIA front-end can't live in an eframe window: run it under
+    // nih-plug's own standalone wrapper instead.
+    #[cfg(feature = "vizia-editor")]
+    {
+        use songwalker_vsti::editor::frontend::{self, EditorFrontend};
+        use songwalker_vsti::SongWalkerPlugin;
+        if frontend::current() == EditorFrontend::Vizia {
+            nih_plug::wrapper::standalone::nih_export_standalone::<SongWalkerPlugin>();
+            return;
+        }
+    }
+
     // Launch the custom standalone app (cpal + midir + eframe)
     songwalker_vsti::standalone::run();
 }
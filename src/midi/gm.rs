@@ -0,0 +1,350 @@
+//! GM mode: the rack as a 16-part multi-timbral General MIDI module.
+//!
+//! Turning GM mode on replaces the rack with one slot per MIDI channel
+//! (slot 1 on channel 1 … slot 16 on channel 16) and loads a piano on every
+//! channel but 10, which gets a drum kit. Program changes then load the
+//! preset with that GM program from [`GM_LIBRARY`]. Bank select (CC 0)
+//! picks among presets sharing a program, numbered as in the exported
+//! patch list (see `preset::patchlist`); a bank the library doesn't have
+//! falls back to bank 0, as on a GS module. Bank 120 (GM2) or 127 (XG)
+//! turns any channel into a drum channel. A GM, GS or XG "system on"
+//! message resets every channel to its default sound.
+//!
+//! The audio thread only tracks bank select and sends requests; presets are
+//! looked up and fetched by the loader thread ([`spawn_loader`]), which
+//! fetches the GM library's index first if the browser hasn't.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use nih_plug::prelude::NoteEvent;
+
+use super::{BANK_SELECT, SysEx};
+use crate::editor::PresetLoadedEvent;
+use crate::editor::loads::{LoadManager, LoadTarget};
+use crate::jobs::JobPool;
+use crate::preset::drums;
+use crate::preset::library_index;
+use crate::preset::manager::{LibraryStatus, PresetManager};
+use crate::preset::patchlist;
+use crate::state::{PluginState, SlotConfig};
+use crate::view_model;
+
+/// Library GM programs are loaded from.
+pub const GM_LIBRARY: &str = "FluidR3_GM";
+
+/// MIDI channels, one slot each.
+pub const GM_CHANNELS: usize = 16;
+
+/// The GM percussion channel (channel 10, 0-based).
+pub const DRUM_CHANNEL: u8 = 9;
+
+/// Capacity of the request channel (audio thread → loader).
+pub const GM_CHANNEL_CAPACITY: usize = 32;
+
+/// Bank select values that switch a channel to drums (GM2 and XG).
+const DRUM_BANKS: [u8; 2] = [120, 127];
+
+/// How often the loader checks for finished preset fetches.
+const DELIVER_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A request from the audio thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GmRequest {
+    /// Every channel back to its default sound.
+    Reset,
+    /// A program change (channel 0–15) with the channel's current bank.
+    Program { channel: u8, bank: u8, program: u8 },
+}
+
+/// The sound a channel asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Sound {
+    drums: bool,
+    bank: u8,
+    program: u8,
+}
+
+impl Sound {
+    fn default_for(channel: u8) -> Self {
+        Self::for_program(channel, 0, 0)
+    }
+
+    fn for_program(channel: u8, bank: u8, program: u8) -> Self {
+        Self {
+            drums: channel == DRUM_CHANNEL || DRUM_BANKS.contains(&bank),
+            bank,
+            program,
+        }
+    }
+}
+
+/// Whether a SysEx message is a GM, GM2, GS or XG "system on" (reset),
+/// for any device id.
+pub fn is_system_on(bytes: &[u8]) -> bool {
+    match bytes {
+        [0xF0, 0x7E, _, 0x09, 0x01 | 0x03, 0xF7] => true,
+        [0xF0, 0x41, _, 0x42, 0x12, 0x40, 0x00, 0x7F, 0x00, 0x41, 0xF7] => true,
+        [0xF0, 0x43, device, 0x4C, 0x00, 0x00, 0x7E, 0x00, 0xF7] => device & 0xF0 == 0x10,
+        _ => false,
+    }
+}
+
+/// Audio-side GM state: bank select per channel and the loader channel.
+#[derive(Default)]
+pub struct GmMode {
+    enabled: bool,
+    banks: [u8; GM_CHANNELS],
+    tx: Option<Sender<GmRequest>>,
+}
+
+impl GmMode {
+    /// Send program requests to a loader thread.
+    pub fn set_sender(&mut self, tx: Sender<GmRequest>) {
+        self.tx = Some(tx);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.banks = [0; GM_CHANNELS];
+    }
+
+    /// Put every channel back on bank 0 and its default sound.
+    pub fn reset(&mut self) {
+        self.banks = [0; GM_CHANNELS];
+        self.send(GmRequest::Reset);
+    }
+
+    /// Track bank select and request sounds for program changes and
+    /// system on messages. Does nothing while GM mode is off.
+    pub fn observe(&mut self, event: &NoteEvent<SysEx>) {
+        if !self.enabled {
+            return;
+        }
+        match event {
+            NoteEvent::MidiCC { channel, cc: BANK_SELECT, value, .. } => {
+                if let Some(bank) = self.banks.get_mut(*channel as usize) {
+                    *bank = (value.clamp(0.0, 1.0) * 127.0).round() as u8;
+                }
+            }
+            NoteEvent::MidiProgramChange { channel, program, .. } => {
+                if let Some(bank) = self.banks.get(*channel as usize) {
+                    self.send(GmRequest::Program { channel: *channel, bank: *bank, program: *program });
+                }
+            }
+            NoteEvent::MidiSysEx { message, .. } if is_system_on(message.bytes()) => self.reset(),
+            _ => {}
+        }
+    }
+
+    fn send(&self, request: GmRequest) {
+        if let Some(tx) = &self.tx {
+            // A full channel drops the request; the next program change retries
+            let _ = tx.try_send(request);
+        }
+    }
+}
+
+/// Replace the rack with one empty slot per channel and turn GM mode on.
+/// Sounds are loaded once the audio thread resets GM mode.
+pub fn configure(state: &mut PluginState) {
+    state.slot_configs = (0..GM_CHANNELS as u8)
+        .map(|channel| SlotConfig {
+            name: if channel == DRUM_CHANNEL { "Drums".into() } else { format!("Ch {}", channel + 1) },
+            midi_channel: i32::from(channel) + 1,
+            ..SlotConfig::default()
+        })
+        .collect();
+    state.groups.clear();
+    state.gm_mode = true;
+}
+
+/// Preset id of a sound in `library`'s loaded index. Drum channels take
+/// the kit with the program number, else the first kit by name.
+fn resolve(mgr: &PresetManager, library: &str, sound: Sound) -> Option<String> {
+    if sound.drums {
+        let mut kits: Vec<_> = patchlist::loaded_presets(mgr, library)
+            .filter(|p| drums::has_drum_tag(&p.tags))
+            .collect();
+        kits.sort_by(|a, b| a.name.cmp(&b.name));
+        let kit = kits.iter().find(|p| p.gm_program == Some(sound.program)).or(kits.first())?;
+        return Some(format!("{}/{}", library, kit.path));
+    }
+    let patches = patchlist::collect_patches(mgr, library);
+    patches
+        .iter()
+        .find(|p| p.program == sound.program && p.bank == sound.bank)
+        .or_else(|| patches.iter().find(|p| p.program == sound.program && p.bank == 0))
+        .map(|p| p.preset_id.clone())
+}
+
+/// Whether the GM library's index is loaded, starting its fetch if it
+/// hasn't been.
+fn index_ready(jobs: &JobPool, preset_manager: &Arc<Mutex<PresetManager>>) -> bool {
+    let status = {
+        let Ok(mgr) = preset_manager.lock() else { return false };
+        mgr.libraries.iter().find(|l| l.name == GM_LIBRARY).map(|l| l.status.clone())
+    };
+    match status {
+        Some(LibraryStatus::Loaded | LibraryStatus::Offline) => true,
+        Some(LibraryStatus::NotLoaded) => {
+            library_index::start_library_fetch(jobs, preset_manager.clone(), GM_LIBRARY.into());
+            false
+        }
+        // Loading, failed, or not yet listed by the root index
+        _ => false,
+    }
+}
+
+/// Spawn the thread that loads the sounds GM mode asks for, delivering
+/// them straight to the audio thread (so it works with the editor
+/// closed). Requests wait until the GM library's index is loaded; only the
+/// latest per channel is kept. The thread exits once every sender has
+/// been dropped.
+pub fn spawn_loader(
+    rx: Receiver<GmRequest>,
+    jobs: Arc<JobPool>,
+    preset_manager: Arc<Mutex<PresetManager>>,
+    plugin_state: Arc<Mutex<PluginState>>,
+    preset_loaded_tx: Sender<PresetLoadedEvent>,
+    status_text: Arc<Mutex<String>>,
+) {
+    let spawned = std::thread::Builder::new()
+        .name("songwalker-gm".into())
+        .spawn(move || {
+            let mut loads = LoadManager::default();
+            let mut pending: [Option<Sound>; GM_CHANNELS] = [None; GM_CHANNELS];
+            loop {
+                match rx.recv_timeout(DELIVER_INTERVAL) {
+                    Ok(GmRequest::Reset) => {
+                        for (channel, sound) in pending.iter_mut().enumerate() {
+                            *sound = Some(Sound::default_for(channel as u8));
+                        }
+                    }
+                    Ok(GmRequest::Program { channel, bank, program }) => {
+                        if let Some(sound) = pending.get_mut(channel as usize) {
+                            *sound = Some(Sound::for_program(channel, bank, program));
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+
+                if pending.iter().any(Option::is_some) && index_ready(&jobs, &preset_manager) {
+                    let resolved: Vec<(usize, Option<String>)> = match preset_manager.lock() {
+                        Ok(mgr) => pending
+                            .iter_mut()
+                            .enumerate()
+                            .filter_map(|(slot_index, sound)| {
+                                let sound = sound.take()?;
+                                Some((slot_index, resolve(&mgr, GM_LIBRARY, sound)))
+                            })
+                            .collect(),
+                        Err(_) => Vec::new(),
+                    };
+                    for (slot_index, preset_id) in resolved {
+                        let Some(preset_id) = preset_id else {
+                            nih_plug::debug::nih_log!("[GM] No preset in {} for slot {}", GM_LIBRARY, slot_index + 1);
+                            continue;
+                        };
+                        let Some((library, path)) = preset_id.split_once('/') else {
+                            continue;
+                        };
+                        let name = path.rsplit('/').next().unwrap_or(path).to_string();
+                        let exists = view_model::update_slot(&plugin_state, slot_index, |c| {
+                            c.name = name;
+                            c.preset_id = Some(preset_id.clone());
+                        });
+                        if exists {
+                            loads.request(
+                                &jobs,
+                                &preset_manager,
+                                LoadTarget::Slot(slot_index),
+                                library,
+                                path,
+                                slot_index,
+                                None,
+                            );
+                        }
+                    }
+                }
+                loads.deliver(&preset_loaded_tx, &status_text);
+            }
+        });
+    if let Err(e) = spawned {
+        log::warn!("Failed to spawn GM loader thread: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preset::manager::PresetInfo;
+    use nih_plug::prelude::SysExMessage;
+
+    fn preset(name: &str, gm_program: Option<u8>, tags: &[&str]) -> PresetInfo {
+        PresetInfo {
+            name: name.into(),
+            path: name.into(),
+            category: String::new(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            gm_program,
+            zone_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_bank_select_and_program_change() {
+        let (tx, rx) = crossbeam_channel::bounded(GM_CHANNEL_CAPACITY);
+        let mut gm = GmMode::default();
+        gm.set_sender(tx);
+        let program = NoteEvent::MidiProgramChange { timing: 0, channel: 2, program: 40 };
+        gm.observe(&program);
+        assert!(rx.try_recv().is_err(), "ignored while off");
+
+        gm.set_enabled(true);
+        gm.observe(&NoteEvent::MidiCC { timing: 0, channel: 2, cc: BANK_SELECT, value: 1.0 / 127.0 });
+        gm.observe(&program);
+        assert_eq!(rx.try_recv(), Ok(GmRequest::Program { channel: 2, bank: 1, program: 40 }));
+
+        let gs_reset = [0xF0, 0x41, 0x10, 0x42, 0x12, 0x40, 0x00, 0x7F, 0x00, 0x41, 0xF7];
+        gm.observe(&NoteEvent::MidiSysEx { timing: 0, message: SysEx::from_buffer(&gs_reset).unwrap() });
+        assert_eq!(rx.try_recv(), Ok(GmRequest::Reset));
+        gm.observe(&program);
+        assert_eq!(rx.try_recv(), Ok(GmRequest::Program { channel: 2, bank: 0, program: 40 }));
+        assert!(is_system_on(&[0xF0, 0x7E, 0x7F, 0x09, 0x01, 0xF7]));
+        assert!(is_system_on(&[0xF0, 0x43, 0x10, 0x4C, 0x00, 0x00, 0x7E, 0x00, 0xF7]));
+    }
+
+    #[test]
+    fn test_resolve_banks_and_drums() {
+        let mut mgr = PresetManager::new();
+        mgr.library_presets.insert(
+            "GM".into(),
+            vec![
+                preset("Grand Piano", Some(0), &[]),
+                preset("Warm Piano", Some(0), &[]),
+                preset("Room Kit", Some(8), &["drums"]),
+                preset("Standard Kit", None, &["drums"]),
+            ],
+        );
+        let resolve = |drums, bank, program| resolve(&mgr, "GM", Sound { drums, bank, program });
+        assert_eq!(resolve(false, 1, 0).as_deref(), Some("GM/Warm Piano"));
+        assert_eq!(resolve(false, 5, 0).as_deref(), Some("GM/Grand Piano"), "missing bank falls back");
+        assert_eq!(resolve(false, 0, 40), None);
+        assert_eq!(resolve(true, 0, 8).as_deref(), Some("GM/Room Kit"));
+        assert_eq!(resolve(true, 0, 0).as_deref(), Some("GM/Room Kit"), "first kit by name");
+
+        let mut state = PluginState::default();
+        configure(&mut state);
+        assert_eq!(state.slot_configs.len(), GM_CHANNELS);
+        assert_eq!(state.slot_configs[9].name, "Drums");
+        assert_eq!(state.slot_configs[15].midi_channel, 16);
+        assert!(Sound::for_program(0, 127, 0).drums);
+    }
+}
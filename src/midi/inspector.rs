@@ -0,0 +1,165 @@
+//! Recent incoming MIDI for the editor's MIDI monitor.
+//!
+//! The audio thread writes each event it routes, with the slots that took
+//! it, into a fixed ring of atomics: no allocation, no locks, and a full
+//! ring simply overwrites the oldest entry. Every entry carries its
+//! sequence number (written last), so the editor can read the entries
+//! after the last one it saw and skip any overwritten mid-read.
+
+use std::sync::atomic::{AtomicU64, Ordering, fence};
+use std::time::Instant;
+
+use nih_plug::prelude::*;
+
+use super::SysEx;
+
+/// Entries kept in the ring.
+pub const INSPECTOR_CAPACITY: usize = 256;
+
+/// Status byte recorded for SysEx (its data isn't kept).
+const SYSEX: u8 = 0xF0;
+
+#[derive(Default)]
+struct Entry {
+    /// Sequence number once the entry is complete, 0 while being written.
+    seq: AtomicU64,
+    /// Microseconds since the inspector was created.
+    time: AtomicU64,
+    /// Message bytes, length and slot mask (see `pack`).
+    data: AtomicU64,
+}
+
+/// An event read back from the ring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InspectedEvent {
+    pub seq: u64,
+    /// Microseconds since the inspector was created.
+    pub time_us: u64,
+    pub bytes: [u8; 3],
+    pub len: u8,
+    /// Slots that took the event, one bit per slot index.
+    pub slots: u16,
+}
+
+impl InspectedEvent {
+    /// MIDI channel (1–16), if it is a channel message.
+    pub fn channel(&self) -> Option<u8> {
+        (self.bytes[0] < SYSEX).then_some((self.bytes[0] & 0x0F) + 1)
+    }
+
+    /// Message type, for display.
+    pub fn kind(&self) -> &'static str {
+        match self.bytes[0] & 0xF0 {
+            0x80 => "Note Off",
+            0x90 => "Note On",
+            0xA0 => "Poly Pressure",
+            0xB0 => "CC",
+            0xC0 => "Program",
+            0xD0 => "Pressure",
+            0xE0 => "Pitch Bend",
+            _ => "SysEx",
+        }
+    }
+}
+
+/// Lock-free ring of the most recent routed events.
+pub struct MidiInspector {
+    entries: [Entry; INSPECTOR_CAPACITY],
+    /// Sequence number of the next entry (the first is 1).
+    next: AtomicU64,
+    epoch: Instant,
+}
+
+impl Default for MidiInspector {
+    fn default() -> Self {
+        Self {
+            entries: std::array::from_fn(|_| Entry::default()),
+            next: AtomicU64::new(1),
+            epoch: Instant::now(),
+        }
+    }
+}
+
+fn pack(bytes: [u8; 3], len: u8, slots: u16) -> u64 {
+    u64::from(bytes[0])
+        | u64::from(bytes[1]) << 8
+        | u64::from(bytes[2]) << 16
+        | u64::from(len) << 24
+        | u64::from(slots) << 32
+}
+
+impl MidiInspector {
+    /// Record a routed event and the slots that took it (audio thread).
+    /// Single writer: only the thread routing MIDI calls this.
+    pub fn record(&self, event: &NoteEvent<SysEx>, slots: u16) {
+        let (bytes, len) = match super::recorder::channel_message(event) {
+            Some(message) => message,
+            None if matches!(event, NoteEvent::MidiSysEx { .. }) => ([SYSEX, 0, 0], 1),
+            None => return,
+        };
+        let seq = self.next.fetch_add(1, Ordering::Relaxed);
+        let entry = &self.entries[seq as usize % INSPECTOR_CAPACITY];
+        entry.seq.store(0, Ordering::Relaxed);
+        fence(Ordering::Release);
+        entry.time.store(self.epoch.elapsed().as_micros() as u64, Ordering::Relaxed);
+        entry.data.store(pack(bytes, len, slots), Ordering::Relaxed);
+        entry.seq.store(seq, Ordering::Release);
+    }
+
+    /// Append the events recorded after `after` (a sequence number, 0 for
+    /// all) to `out`, oldest first, and return the last sequence number
+    /// seen (UI thread).
+    pub fn read_since(&self, after: u64, out: &mut Vec<InspectedEvent>) -> u64 {
+        let next = self.next.load(Ordering::Acquire);
+        let first = (after + 1).max(next.saturating_sub(INSPECTOR_CAPACITY as u64));
+        for seq in first..next {
+            let entry = &self.entries[seq as usize % INSPECTOR_CAPACITY];
+            if entry.seq.load(Ordering::Acquire) != seq {
+                continue;
+            }
+            let time_us = entry.time.load(Ordering::Relaxed);
+            let data = entry.data.load(Ordering::Relaxed);
+            fence(Ordering::Acquire);
+            if entry.seq.load(Ordering::Relaxed) != seq {
+                continue;
+            }
+            out.push(InspectedEvent {
+                seq,
+                time_us,
+                bytes: [data as u8, (data >> 8) as u8, (data >> 16) as u8],
+                len: (data >> 24) as u8,
+                slots: (data >> 32) as u16,
+            });
+        }
+        next.saturating_sub(1).max(after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_keeps_the_latest_events() {
+        let inspector = MidiInspector::default();
+        let on = |note| NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 2, note, velocity: 1.0 };
+        inspector.record(&on(60), 0b101);
+
+        let mut out = Vec::new();
+        let last = inspector.read_since(0, &mut out);
+        assert_eq!(last, 1);
+        assert_eq!(out[0].channel(), Some(3));
+        assert_eq!(out[0].kind(), "Note On");
+        assert_eq!(out[0].slots, 0b101);
+        assert_eq!(inspector.read_since(last, &mut out), last, "nothing new");
+        assert_eq!(out.len(), 1);
+
+        for note in 0..INSPECTOR_CAPACITY as u8 + 10 {
+            inspector.record(&on(note % 128), 0);
+        }
+        out.clear();
+        inspector.read_since(last, &mut out);
+        assert_eq!(out.len(), INSPECTOR_CAPACITY - 1, "overwritten entries are skipped");
+        assert!(out.windows(2).all(|w| w[0].seq < w[1].seq));
+    }
+}
@@ -0,0 +1,263 @@
+use nih_plug::prelude::*;
+
+use crate::slots::SlotManager;
+use crate::transport::TransportState;
+
+pub mod gm;
+pub mod inspector;
+pub mod recorder;
+pub mod rules;
+pub mod scale;
+
+/// Bank select (MSB) controller.
+pub const BANK_SELECT: u8 = 0;
+/// Modulation wheel controller.
+pub const MOD_WHEEL: u8 = 1;
+/// Expression controller.
+pub const EXPRESSION: u8 = 11;
+/// Sustain pedal controller.
+pub const SUSTAIN_PEDAL: u8 = 64;
+/// Channel mode message: silence all voices immediately.
+pub const ALL_SOUND_OFF: u8 = 120;
+/// Channel mode message: release all held notes.
+pub const ALL_NOTES_OFF: u8 = 123;
+
+/// Longest SysEx message accepted (including the F0/F7 framing).
+pub const SYSEX_MAX_LEN: usize = 64;
+
+/// A raw SysEx message, held inline so it can cross the audio thread
+/// without allocating. Longer messages are dropped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SysEx {
+    data: [u8; SYSEX_MAX_LEN],
+    len: usize,
+}
+
+impl SysEx {
+    pub fn bytes(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
+impl SysExMessage for SysEx {
+    type Buffer = [u8; SYSEX_MAX_LEN];
+
+    fn from_buffer(buffer: &[u8]) -> Option<Self> {
+        if buffer.len() > SYSEX_MAX_LEN {
+            return None;
+        }
+        let mut data = [0; SYSEX_MAX_LEN];
+        data[..buffer.len()].copy_from_slice(buffer);
+        Some(Self { data, len: buffer.len() })
+    }
+
+    fn to_buffer(self) -> (Self::Buffer, usize) {
+        (self.data, self.len)
+    }
+}
+
+/// Route a MIDI event from the host to the appropriate slot(s).
+///
+/// Events are routed based on each slot's MIDI channel setting:
+/// - Channel 0 = receive all channels
+/// - Channel 1–16 = receive only that channel
+///
+/// NRPN and SysEx messages are also matched against the rack's MIDI rules
+/// (see [`rules`]); SysEx is not passed on to slots. Notes then pass the
+/// rack's scale lock (see [`scale`]). In GM mode, bank select, program
+/// changes and system on messages also pick slot sounds (see [`gm`]).
+///
+/// Returns the slots that took the event, one bit per slot index.
+pub fn route_event(
+    event: &NoteEvent<SysEx>,
+    slot_manager: &mut SlotManager,
+    transport: &TransportState,
+) -> u16 {
+    if let Some(fired) = slot_manager.midi_rules_mut().observe(event) {
+        slot_manager.fire_rules(fired);
+    }
+    slot_manager.gm_mode_mut().observe(event);
+    let Some(event) = slot_event(event) else { return 0 };
+    let Some(event) = slot_manager.scale_filter_mut().apply(event) else { return 0 };
+    let channel = event_channel(&event);
+
+    let mut taken = 0;
+    for (i, slot) in slot_manager.slots_mut().iter_mut().enumerate() {
+        let slot_ch = slot.midi_channel();
+        // Channel 0 means "all", otherwise must match
+        if (slot_ch == 0 || slot_ch == (channel as i32 + 1)) && slot.handle_midi_event(&event, transport) {
+            taken |= 1 << i;
+        }
+    }
+    taken
+}
+
+/// The part of an event slots care about, or `None` for events they ignore.
+fn slot_event(event: &NoteEvent<SysEx>) -> Option<NoteEvent<()>> {
+    Some(match *event {
+        NoteEvent::NoteOn { timing, voice_id, channel, note, velocity } => {
+            NoteEvent::NoteOn { timing, voice_id, channel, note, velocity }
+        }
+        NoteEvent::NoteOff { timing, voice_id, channel, note, velocity } => {
+            NoteEvent::NoteOff { timing, voice_id, channel, note, velocity }
+        }
+        NoteEvent::PolyPressure { timing, voice_id, channel, note, pressure } => {
+            NoteEvent::PolyPressure { timing, voice_id, channel, note, pressure }
+        }
+        NoteEvent::MidiCC { timing, channel, cc, value } => {
+            NoteEvent::MidiCC { timing, channel, cc, value }
+        }
+        NoteEvent::MidiPitchBend { timing, channel, value } => {
+            NoteEvent::MidiPitchBend { timing, channel, value }
+        }
+        NoteEvent::MidiChannelPressure { timing, channel, pressure } => {
+            NoteEvent::MidiChannelPressure { timing, channel, pressure }
+        }
+        _ => return None,
+    })
+}
+
+/// Extract the MIDI channel (0–15) from a NoteEvent.
+fn event_channel(event: &NoteEvent<()>) -> u8 {
+    match event {
+        NoteEvent::NoteOn { channel, .. } => *channel,
+        NoteEvent::NoteOff { channel, .. } => *channel,
+        NoteEvent::PolyPressure { channel, .. } => *channel,
+        NoteEvent::MidiCC { channel, .. } => *channel,
+        NoteEvent::MidiPitchBend { channel, .. } => *channel,
+        NoteEvent::MidiChannelPressure { channel, .. } => *channel,
+        _ => 0,
+    }
+}
+
+/// Convert a MIDI note number (0–127) to frequency in Hz (A4 = 440 Hz).
+#[inline]
+pub fn midi_to_freq(note: u8) -> f32 {
+    440.0 * 2.0_f32.powf((note as f32 - 69.0) / 12.0)
+}
+
+/// Convert a MIDI velocity (0–127) to a normalized float (0.0–1.0).
+#[inline]
+pub fn velocity_to_float(velocity: f32) -> f32 {
+    velocity.clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_midi_to_freq_a4() {
+        let freq = midi_to_freq(69);
+        assert!((freq - 440.0).abs() < 0.01, "A4 should be 440 Hz, got {}", freq);
+    }
+
+    #[test]
+    fn test_midi_to_freq_c4() {
+        let freq = midi_to_freq(60);
+        assert!((freq - 261.63).abs() < 0.1, "C4 should be ~261.63 Hz, got {}", freq);
+    }
+
+    #[test]
+    fn test_midi_to_freq_boundaries() {
+        assert!(midi_to_freq(0) > 0.0, "Note 0 should have positive frequency");
+        assert!(midi_to_freq(127) < 20000.0, "Note 127 should be < 20kHz");
+        // Octave relationship: note+12 should be double the frequency
+        let f60 = midi_to_freq(60);
+        let f72 = midi_to_freq(72);
+        assert!((f72 / f60 - 2.0).abs() < 0.01, "Octave should double frequency");
+    }
+
+    #[test]
+    fn test_velocity_to_float_normal() {
+        assert_eq!(velocity_to_float(0.0), 0.0);
+        assert_eq!(velocity_to_float(0.5), 0.5);
+        assert_eq!(velocity_to_float(1.0), 1.0);
+    }
+
+    #[test]
+    fn test_velocity_to_float_clamp() {
+        assert_eq!(velocity_to_float(-0.5), 0.0);
+        assert_eq!(velocity_to_float(1.5), 1.0);
+    }
+
+    #[test]
+    fn test_event_channel_note_on() {
+        let event = NoteEvent::NoteOn {
+            timing: 0,
+            voice_id: None,
+            channel: 5,
+            note: 60,
+            velocity: 0.8,
+        };
+        assert_eq!(event_channel(&event), 5);
+    }
+
+    #[test]
+    fn test_event_channel_note_off() {
+        let event = NoteEvent::NoteOff {
+            timing: 0,
+            voice_id: None,
+            channel: 10,
+            note: 60,
+            velocity: 0.0,
+        };
+        assert_eq!(event_channel(&event), 10);
+    }
+
+    #[test]
+    fn test_event_channel_cc() {
+        let event = NoteEvent::MidiCC {
+            timing: 0,
+            channel: 3,
+            cc: 1,
+            value: 0.5,
+        };
+        assert_eq!(event_channel(&event), 3);
+    }
+
+    #[test]
+    fn test_sysex_buffer_limits() {
+        let sysex = SysEx::from_buffer(&[0xF0, 0x7D, 0xF7]).unwrap();
+        assert_eq!(sysex.bytes(), &[0xF0, 0x7D, 0xF7]);
+        assert!(SysEx::from_buffer(&[0; SYSEX_MAX_LEN + 1]).is_none());
+    }
+
+    #[test]
+    fn test_route_event_fires_rules() {
+        let mut slot_manager = SlotManager::new_empty();
+        slot_manager.allocate_all();
+        let (tx, rx) = crossbeam_channel::bounded(4);
+        slot_manager.set_rule_sender(tx);
+        let rules = rules::parse_rules("sysex F0 7D 01 F7 => mute 2").unwrap();
+        slot_manager.set_midi_rules(std::sync::Arc::new(rules));
+
+        let event = NoteEvent::MidiSysEx {
+            timing: 0,
+            message: SysEx::from_buffer(&[0xF0, 0x7D, 0x01, 0xF7]).unwrap(),
+        };
+        route_event(&event, &mut slot_manager, &TransportState::default());
+        assert!(slot_manager.slots()[1].is_muted());
+        assert_eq!(rx.try_recv().unwrap().index, 0);
+
+        route_event(&event, &mut slot_manager, &TransportState::default());
+        assert!(!slot_manager.slots()[1].is_muted(), "rule toggles");
+    }
+
+    #[test]
+    fn test_route_event_applies_scale_lock() {
+        let mut slot_manager = SlotManager::new_empty();
+        slot_manager.allocate_all();
+        slot_manager.scale_filter_mut().set_lock(scale::ScaleLock {
+            mode: scale::ScaleMode::Block,
+            root: 0,
+            scale: scale::Scale::Major,
+        });
+        let on = |note| NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note, velocity: 0.8 };
+        assert_eq!(route_event(&on(61), &mut slot_manager, &TransportState::default()), 0);
+        assert_eq!(slot_manager.slots()[0].active_voice_count(), 0);
+        let taken = route_event(&on(60), &mut slot_manager, &TransportState::default());
+        assert_eq!(taken & 1, 1, "slot 1 took the note");
+        assert_eq!(slot_manager.slots()[0].active_voice_count(), 1);
+    }
+}
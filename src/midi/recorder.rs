@@ -0,0 +1,273 @@
+//! Capture of incoming MIDI to a standard MIDI file.
+//!
+//! The audio thread stamps every event it receives (host events in the
+//! plugin, hardware input in the standalone) with a frame count since the
+//! take started and pushes it into a bounded channel; nothing allocates or
+//! locks on that side. The editor drains the channel while recording and,
+//! when the take stops, writes it as a format-0 `.mid` file.
+//!
+//! The file is written at a fixed 120 BPM with 480 ticks per quarter, so
+//! tick positions follow wall-clock time whatever tempo was playing.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+use crossbeam_channel::{Receiver, Sender};
+use nih_plug::prelude::*;
+
+use super::SysEx;
+
+/// Events buffered between editor drains; more are dropped (and counted).
+pub const CAPTURE_CAPACITY: usize = 8192;
+
+/// Ticks per quarter note in written files.
+pub const TICKS_PER_QUARTER: u16 = 480;
+/// Tempo written to files (µs per quarter, 120 BPM).
+const MICROS_PER_QUARTER: u32 = 500_000;
+
+/// A channel message captured from the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordedEvent {
+    /// Frames since the take started.
+    pub frame: u64,
+    pub bytes: [u8; 3],
+    pub len: u8,
+}
+
+impl RecordedEvent {
+    pub fn message(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+}
+
+/// Shared between the audio thread (recording) and the editor (takes).
+pub struct MidiRecorder {
+    armed: AtomicBool,
+    /// Frames elapsed in the current take (advanced by the audio thread).
+    frames: AtomicU64,
+    /// Sample rate of the take, as f32 bits.
+    sample_rate: AtomicU32,
+    /// Events lost because the channel was full.
+    dropped: AtomicU32,
+    tx: Sender<RecordedEvent>,
+    rx: Receiver<RecordedEvent>,
+}
+
+impl Default for MidiRecorder {
+    fn default() -> Self {
+        let (tx, rx) = crossbeam_channel::bounded(CAPTURE_CAPACITY);
+        Self {
+            armed: AtomicBool::new(false),
+            frames: AtomicU64::new(0),
+            sample_rate: AtomicU32::new(44100.0_f32.to_bits()),
+            dropped: AtomicU32::new(0),
+            tx,
+            rx,
+        }
+    }
+}
+
+impl MidiRecorder {
+    pub fn is_recording(&self) -> bool {
+        self.armed.load(Ordering::Relaxed)
+    }
+
+    /// Start a new take (UI thread). Anything left from the last one is
+    /// discarded.
+    pub fn start(&self) {
+        self.armed.store(false, Ordering::Relaxed);
+        while self.rx.try_recv().is_ok() {}
+        self.frames.store(0, Ordering::Relaxed);
+        self.dropped.store(0, Ordering::Relaxed);
+        self.armed.store(true, Ordering::Relaxed);
+    }
+
+    /// Stop recording (UI thread); drain once more to collect the tail.
+    pub fn stop(&self) {
+        self.armed.store(false, Ordering::Relaxed);
+    }
+
+    /// Move captured events into `take` (UI thread).
+    pub fn drain_into(&self, take: &mut Vec<RecordedEvent>) {
+        take.extend(self.rx.try_iter());
+    }
+
+    /// Events lost in this take because the editor didn't drain in time.
+    pub fn dropped(&self) -> u32 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Sample rate the take's frames count in.
+    pub fn sample_rate(&self) -> f32 {
+        f32::from_bits(self.sample_rate.load(Ordering::Relaxed))
+    }
+
+    /// Capture an incoming event (audio thread). SysEx and events with no
+    /// channel message are skipped.
+    pub fn record(&self, event: &NoteEvent<SysEx>) {
+        if !self.is_recording() {
+            return;
+        }
+        let Some((bytes, len)) = channel_message(event) else {
+            return;
+        };
+        let frame = self.frames.load(Ordering::Relaxed) + u64::from(event.timing());
+        if self.tx.try_send(RecordedEvent { frame, bytes, len }).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Advance the take clock past a block (audio thread).
+    pub fn end_block(&self, num_samples: usize, sample_rate: f32) {
+        if self.is_recording() {
+            self.frames.fetch_add(num_samples as u64, Ordering::Relaxed);
+            self.sample_rate.store(sample_rate.to_bits(), Ordering::Relaxed);
+        }
+    }
+}
+
+/// Raw bytes of a channel event (velocities and values back to 7 bits,
+/// pitch bend to 14).
+pub(crate) fn channel_message(event: &NoteEvent<SysEx>) -> Option<([u8; 3], u8)> {
+    let seven = |v: f32| (v.clamp(0.0, 1.0) * 127.0).round() as u8;
+    Some(match *event {
+        NoteEvent::NoteOn { channel, note, velocity, .. } => ([0x90 | channel, note, seven(velocity).max(1)], 3),
+        NoteEvent::NoteOff { channel, note, velocity, .. } => ([0x80 | channel, note, seven(velocity)], 3),
+        NoteEvent::PolyPressure { channel, note, pressure, .. } => ([0xA0 | channel, note, seven(pressure)], 3),
+        NoteEvent::MidiCC { channel, cc, value, .. } => ([0xB0 | channel, cc, seven(value)], 3),
+        NoteEvent::MidiProgramChange { channel, program, .. } => ([0xC0 | channel, program, 0], 2),
+        NoteEvent::MidiChannelPressure { channel, pressure, .. } => ([0xD0 | channel, seven(pressure), 0], 2),
+        NoteEvent::MidiPitchBend { channel, value, .. } => {
+            let bend = (value.clamp(0.0, 1.0) * 16383.0).round() as u16;
+            ([0xE0 | channel, (bend & 0x7F) as u8, (bend >> 7) as u8], 3)
+        }
+        _ => return None,
+    })
+}
+
+/// A take as a format-0 standard MIDI file.
+pub fn to_smf(take: &[RecordedEvent], sample_rate: f32) -> Vec<u8> {
+    let ticks_per_sec = f64::from(TICKS_PER_QUARTER) * 1_000_000.0 / f64::from(MICROS_PER_QUARTER);
+    let mut events: Vec<&RecordedEvent> = take.iter().collect();
+    events.sort_by_key(|e| e.frame);
+
+    let mut track = Vec::new();
+    // Tempo
+    track.extend_from_slice(&[0x00, 0xFF, 0x51, 0x03]);
+    track.extend_from_slice(&MICROS_PER_QUARTER.to_be_bytes()[1..]);
+    let mut last_tick = 0;
+    for event in events {
+        let tick = (event.frame as f64 / f64::from(sample_rate.max(1.0)) * ticks_per_sec).round() as u64;
+        write_var_len(&mut track, (tick - last_tick).min(0x0FFF_FFFF) as u32);
+        last_tick = tick;
+        track.extend_from_slice(event.message());
+    }
+    // End of track
+    track.extend_from_slice(&[0x00, 0xFF, 0x2F, 0x00]);
+
+    let mut file = Vec::with_capacity(track.len() + 22);
+    file.extend_from_slice(b"MThd");
+    file.extend_from_slice(&6u32.to_be_bytes());
+    file.extend_from_slice(&0u16.to_be_bytes()); // format 0
+    file.extend_from_slice(&1u16.to_be_bytes()); // one track
+    file.extend_from_slice(&TICKS_PER_QUARTER.to_be_bytes());
+    file.extend_from_slice(b"MTrk");
+    file.extend_from_slice(&(track.len() as u32).to_be_bytes());
+    file.extend_from_slice(&track);
+    file
+}
+
+/// MIDI variable-length quantity.
+fn write_var_len(out: &mut Vec<u8>, value: u32) {
+    let mut groups = [0u8; 4];
+    let mut n = 0;
+    let mut v = value;
+    loop {
+        groups[n] = (v & 0x7F) as u8;
+        n += 1;
+        v >>= 7;
+        if v == 0 {
+            break;
+        }
+    }
+    for i in (0..n).rev() {
+        out.push(if i > 0 { groups[i] | 0x80 } else { groups[i] });
+    }
+}
+
+/// Where takes are saved: `Documents/SongWalker/MIDI Captures`, or the app
+/// data directory if there is no documents folder.
+pub fn export_dir() -> Option<PathBuf> {
+    directories::UserDirs::new()
+        .and_then(|d| d.document_dir().map(|p| p.join("SongWalker").join("MIDI Captures")))
+        .or_else(|| {
+            directories::ProjectDirs::from("org", "SongWalker", "songwalker-vsti")
+                .map(|d| d.data_dir().join("midi-captures"))
+        })
+}
+
+/// Write a take and return the file.
+pub fn export(take: &[RecordedEvent], sample_rate: f32) -> Result<PathBuf, String> {
+    if take.is_empty() {
+        return Err("Nothing was recorded".into());
+    }
+    let dir = export_dir().ok_or("No documents directory")?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let path = dir.join(format!("songwalker-take-{}.mid", stamp));
+    crate::net::atomic::write_atomic(&path, &to_smf(take, sample_rate))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note_on(timing: u32, note: u8) -> NoteEvent<SysEx> {
+        NoteEvent::NoteOn { timing, voice_id: None, channel: 2, note, velocity: 1.0 }
+    }
+
+    #[test]
+    fn test_records_only_while_armed_with_block_timing() {
+        let recorder = MidiRecorder::default();
+        recorder.record(&note_on(0, 60));
+        recorder.end_block(512, 48000.0);
+
+        recorder.start();
+        recorder.record(&note_on(10, 60));
+        recorder.end_block(512, 48000.0);
+        recorder.record(&NoteEvent::MidiPitchBend { timing: 5, channel: 0, value: 0.5 });
+        recorder.stop();
+        recorder.record(&note_on(0, 62));
+
+        let mut take = Vec::new();
+        recorder.drain_into(&mut take);
+        assert_eq!(take.len(), 2);
+        assert_eq!((take[0].frame, take[0].message()), (10, &[0x92, 60, 127][..]));
+        assert_eq!((take[1].frame, take[1].message()), (517, &[0xE0, 0x00, 0x40][..]));
+        assert_eq!(recorder.sample_rate(), 48000.0);
+    }
+
+    #[test]
+    fn test_smf_layout() {
+        let take = [
+            RecordedEvent { frame: 44100, bytes: [0x80, 60, 0], len: 3 },
+            RecordedEvent { frame: 0, bytes: [0x90, 60, 100], len: 3 },
+        ];
+        let smf = to_smf(&take, 44100.0);
+        assert_eq!(&smf[..4], b"MThd");
+        assert_eq!(&smf[8..14], &[0, 0, 0, 1, 0x01, 0xE0]);
+        assert_eq!(&smf[14..18], b"MTrk");
+        let track = &smf[22..];
+        assert_eq!(u32::from_be_bytes(smf[18..22].try_into().unwrap()) as usize, track.len());
+        // Tempo, note on at 0, note off one second (960 ticks) later, end
+        assert_eq!(&track[..7], &[0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20]);
+        assert_eq!(&track[7..11], &[0x00, 0x90, 60, 100]);
+        assert_eq!(&track[11..16], &[0x87, 0x40, 0x80, 60, 0]);
+        assert_eq!(&track[16..], &[0x00, 0xFF, 0x2F, 0x00]);
+    }
+}
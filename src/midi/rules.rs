@@ -0,0 +1,575 @@
+//! MIDI rules: NRPN and SysEx messages that trigger rack actions.
+//!
+//! Rules belong to the rack (`PluginState::midi_rules`) and are edited as
+//! text in Settings, one per line:
+//!
+//! ```text
+//! # Hardware scene buttons
+//! nrpn 1:2 => load 1 FluidR3_GM/Acoustic Grand Piano
+//! nrpn 300 value 127 ch 2 => mute 3
+//! sysex F0 7D 01 02 F7 => solo 2
+//! ```
+//!
+//! An NRPN parameter is `msb:lsb` or a 14-bit number and fires on its data
+//! entry (CC 6); `value` matches the data entry value and `ch` limits the
+//! rule to one channel (1–16). A SysEx rule matches the whole message,
+//! F0 and F7 included. Slots are numbered from 1, as in the rack.
+//!
+//! Matching runs on the audio thread in [`route_event`](super::route_event):
+//! mute and solo toggles take effect there, and every fired rule is also
+//! sent to the dispatcher thread ([`spawn_dispatcher`]), which loads
+//! presets and mirrors toggles in the slot configs the editor shows.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use nih_plug::prelude::NoteEvent;
+use serde::{Deserialize, Serialize};
+
+use super::SysEx;
+use crate::editor::PresetLoadedEvent;
+use crate::editor::loads::{LoadManager, LoadTarget};
+use crate::jobs::JobPool;
+use crate::preset::manager::PresetManager;
+use crate::slots::MAX_SLOTS;
+use crate::state::PluginState;
+use crate::view_model;
+
+/// Capacity of the fired-rule channel (audio thread → dispatcher).
+pub const RULE_CHANNEL_CAPACITY: usize = 32;
+
+/// NRPN parameter number the spec reserves as "no parameter selected".
+const NRPN_NULL: u16 = 0x3FFF;
+
+/// What a rule listens for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleTrigger {
+    Nrpn {
+        /// 1–16, or `None` for any channel.
+        #[serde(default)]
+        channel: Option<u8>,
+        param: u16,
+        /// Data entry value (0–127), or `None` for any value.
+        #[serde(default)]
+        value: Option<u8>,
+    },
+    SysEx {
+        bytes: Vec<u8>,
+    },
+}
+
+/// What a rule does. Slot indices are 0-based.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    LoadPreset {
+        slot_index: usize,
+        preset_id: String,
+    },
+    ToggleMute {
+        slot_index: usize,
+    },
+    ToggleSolo {
+        slot_index: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MidiRule {
+    pub trigger: RuleTrigger,
+    pub action: RuleAction,
+}
+
+// ── Text syntax ──────────────────────────────────────────────
+
+/// Parse the Settings text, one rule per line (`#` starts a comment).
+pub fn parse_rules(text: &str) -> Result<Vec<MidiRule>, String> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line = line.split('#').next().unwrap_or("").trim();
+            (!line.is_empty())
+                .then(|| MidiRule::parse(line).map_err(|e| format!("Line {}: {}", i + 1, e)))
+        })
+        .collect()
+}
+
+/// Inverse of [`parse_rules`].
+pub fn format_rules(rules: &[MidiRule]) -> String {
+    rules.iter().map(|r| format!("{}\n", r)).collect()
+}
+
+impl MidiRule {
+    /// Parse one `trigger => action` line.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let (trigger, action) = line
+            .split_once("=>")
+            .ok_or("Expected `trigger => action`")?;
+        Ok(Self {
+            trigger: parse_trigger(trigger)?,
+            action: parse_action(action)?,
+        })
+    }
+}
+
+fn parse_trigger(text: &str) -> Result<RuleTrigger, String> {
+    let mut tokens = text.split_whitespace();
+    match tokens.next() {
+        Some("nrpn") => {
+            let param = tokens.next().ok_or("Missing NRPN parameter")?;
+            let param = match param.split_once(':') {
+                Some((msb, lsb)) => ((parse_7bit(msb)? as u16) << 7) | parse_7bit(lsb)? as u16,
+                None => param
+                    .parse::<u16>()
+                    .ok()
+                    .filter(|p| *p < NRPN_NULL)
+                    .ok_or_else(|| format!("Invalid NRPN parameter '{}'", param))?,
+            };
+            let (mut channel, mut value) = (None, None);
+            while let Some(key) = tokens.next() {
+                let arg = tokens
+                    .next()
+                    .ok_or_else(|| format!("Missing value after '{}'", key))?;
+                match key {
+                    "value" => value = Some(parse_7bit(arg)?),
+                    "ch" => {
+                        channel = Some(
+                            arg.parse::<u8>()
+                                .ok()
+                                .filter(|c| (1..=16).contains(c))
+                                .ok_or_else(|| format!("Invalid channel '{}'", arg))?,
+                        )
+                    }
+                    other => return Err(format!("Unknown NRPN option '{}'", other)),
+                }
+            }
+            Ok(RuleTrigger::Nrpn {
+                channel,
+                param,
+                value,
+            })
+        }
+        Some("sysex") => {
+            let bytes = tokens
+                .map(|t| u8::from_str_radix(t.trim_start_matches("0x"), 16))
+                .collect::<Result<Vec<u8>, _>>()
+                .map_err(|_| "SysEx bytes must be hex")?;
+            if bytes.first() != Some(&0xF0) || bytes.last() != Some(&0xF7) || bytes.len() < 3 {
+                return Err("SysEx must start with F0 and end with F7".into());
+            }
+            if bytes.len() > super::SYSEX_MAX_LEN {
+                return Err(format!("SysEx longer than {} bytes", super::SYSEX_MAX_LEN));
+            }
+            Ok(RuleTrigger::SysEx { bytes })
+        }
+        Some(other) => Err(format!(
+            "Unknown trigger '{}' (expected nrpn or sysex)",
+            other
+        )),
+        None => Err("Missing trigger".into()),
+    }
+}
+
+fn parse_action(text: &str) -> Result<RuleAction, String> {
+    let text = text.trim();
+    let (verb, rest) = text
+        .split_once(char::is_whitespace)
+        .ok_or("Missing slot number")?;
+    let rest = rest.trim_start();
+    let (slot, preset_id) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let slot_index = slot
+        .parse::<usize>()
+        .ok()
+        .filter(|n| (1..=MAX_SLOTS).contains(n))
+        .ok_or_else(|| format!("Invalid slot '{}' (1–{})", slot, MAX_SLOTS))?
+        - 1;
+    let preset_id = preset_id.trim();
+    match verb {
+        "load" if preset_id.contains('/') => Ok(RuleAction::LoadPreset {
+            slot_index,
+            preset_id: preset_id.to_string(),
+        }),
+        "load" => Err("Expected a preset id: load <slot> <library/path>".into()),
+        "mute" | "solo" if !preset_id.is_empty() => {
+            Err(format!("Unexpected '{}' after slot number", preset_id))
+        }
+        "mute" => Ok(RuleAction::ToggleMute { slot_index }),
+        "solo" => Ok(RuleAction::ToggleSolo { slot_index }),
+        other => Err(format!(
+            "Unknown action '{}' (expected load, mute or solo)",
+            other
+        )),
+    }
+}
+
+fn parse_7bit(text: &str) -> Result<u8, String> {
+    text.parse::<u8>()
+        .ok()
+        .filter(|v| *v < 128)
+        .ok_or_else(|| format!("'{}' is not 0–127", text))
+}
+
+impl fmt::Display for MidiRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.trigger {
+            RuleTrigger::Nrpn {
+                channel,
+                param,
+                value,
+            } => {
+                write!(f, "nrpn {}:{}", param >> 7, param & 0x7F)?;
+                if let Some(v) = value {
+                    write!(f, " value {}", v)?;
+                }
+                if let Some(c) = channel {
+                    write!(f, " ch {}", c)?;
+                }
+            }
+            RuleTrigger::SysEx { bytes } => {
+                write!(f, "sysex")?;
+                for b in bytes {
+                    write!(f, " {:02X}", b)?;
+                }
+            }
+        }
+        match &self.action {
+            RuleAction::LoadPreset {
+                slot_index,
+                preset_id,
+            } => {
+                write!(f, " => load {} {}", slot_index + 1, preset_id)
+            }
+            RuleAction::ToggleMute { slot_index } => write!(f, " => mute {}", slot_index + 1),
+            RuleAction::ToggleSolo { slot_index } => write!(f, " => solo {}", slot_index + 1),
+        }
+    }
+}
+
+// ── Matching (audio thread) ──────────────────────────────────
+
+/// A completed NRPN or SysEx message, ready to be matched against rules.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuleMatch {
+    /// `channel` is 0-based, as in `NoteEvent`.
+    Nrpn {
+        channel: u8,
+        param: u16,
+        value: u8,
+    },
+    SysEx(SysEx),
+}
+
+impl RuleTrigger {
+    pub fn matches(&self, m: &RuleMatch) -> bool {
+        match (self, m) {
+            (
+                Self::Nrpn {
+                    channel,
+                    param,
+                    value,
+                },
+                RuleMatch::Nrpn {
+                    channel: ch,
+                    param: p,
+                    value: v,
+                },
+            ) => {
+                param == p
+                    && channel.is_none_or(|c| c == ch + 1)
+                    && value.is_none_or(|value| value == *v)
+            }
+            (Self::SysEx { bytes }, RuleMatch::SysEx(sysex)) => bytes.as_slice() == sysex.bytes(),
+            _ => false,
+        }
+    }
+}
+
+/// NRPN parameter selection of one channel.
+#[derive(Debug, Clone, Copy, Default)]
+struct NrpnSelection {
+    msb: Option<u8>,
+    lsb: Option<u8>,
+}
+
+/// Audio-thread matcher: tracks NRPN selection per channel and recognizes
+/// messages some rule listens for. Never allocates.
+pub struct MidiRuleEngine {
+    rules: Arc<Vec<MidiRule>>,
+    nrpn: [NrpnSelection; 16],
+}
+
+impl Default for MidiRuleEngine {
+    fn default() -> Self {
+        Self {
+            rules: Arc::new(Vec::new()),
+            nrpn: [NrpnSelection::default(); 16],
+        }
+    }
+}
+
+impl MidiRuleEngine {
+    pub fn rules(&self) -> &Arc<Vec<MidiRule>> {
+        &self.rules
+    }
+
+    /// Swap in a new rule set, returning the old one so the caller can
+    /// retire it off the audio thread.
+    pub fn set_rules(&mut self, rules: Arc<Vec<MidiRule>>) -> Arc<Vec<MidiRule>> {
+        std::mem::replace(&mut self.rules, rules)
+    }
+
+    /// Feed one incoming event. Returns a match if it completes an NRPN
+    /// data entry or is a SysEx message that some rule listens for.
+    pub fn observe(&mut self, event: &NoteEvent<SysEx>) -> Option<RuleMatch> {
+        let m = match event {
+            NoteEvent::MidiCC {
+                channel, cc, value, ..
+            } => {
+                let selection = &mut self.nrpn[(*channel & 0x0F) as usize];
+                let value = (value * 127.0).round().clamp(0.0, 127.0) as u8;
+                match cc {
+                    99 => selection.msb = Some(value),
+                    98 => selection.lsb = Some(value),
+                    // Selecting an RPN deselects the NRPN
+                    100 | 101 => *selection = NrpnSelection::default(),
+                    6 => {
+                        if let (Some(msb), Some(lsb)) = (selection.msb, selection.lsb) {
+                            let param = ((msb as u16) << 7) | lsb as u16;
+                            if param != NRPN_NULL {
+                                return self.matched(RuleMatch::Nrpn {
+                                    channel: *channel,
+                                    param,
+                                    value,
+                                });
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                return None;
+            }
+            NoteEvent::MidiSysEx { message, .. } => RuleMatch::SysEx(*message),
+            _ => return None,
+        };
+        self.matched(m)
+    }
+
+    fn matched(&self, m: RuleMatch) -> Option<RuleMatch> {
+        self.rules
+            .iter()
+            .any(|r| r.trigger.matches(&m))
+            .then_some(m)
+    }
+}
+
+/// A rule that fired, sent from the audio thread to the dispatcher.
+/// Holds the rule set rather than a copy of the rule so sending it never
+/// allocates.
+#[derive(Debug, Clone)]
+pub struct FiredRule {
+    pub rules: Arc<Vec<MidiRule>>,
+    pub index: usize,
+}
+
+impl FiredRule {
+    pub fn rule(&self) -> &MidiRule {
+        &self.rules[self.index]
+    }
+}
+
+// ── Dispatch (background thread) ─────────────────────────────
+
+/// How often the dispatcher checks for finished preset fetches.
+const DELIVER_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Spawn the thread that carries out fired rules: preset loads go through
+/// a [`LoadManager`] and are delivered straight to the audio thread, so
+/// rules work with the editor closed. The thread exits once every sender
+/// has been dropped.
+pub fn spawn_dispatcher(
+    rx: Receiver<FiredRule>,
+    jobs: Arc<JobPool>,
+    preset_manager: Arc<Mutex<PresetManager>>,
+    plugin_state: Arc<Mutex<PluginState>>,
+    preset_loaded_tx: Sender<PresetLoadedEvent>,
+    status_text: Arc<Mutex<String>>,
+) {
+    let spawned = std::thread::Builder::new()
+        .name("songwalker-midi-rules".into())
+        .spawn(move || {
+            let mut loads = LoadManager::default();
+            loop {
+                match rx.recv_timeout(DELIVER_INTERVAL) {
+                    Ok(fired) => {
+                        nih_plug::debug::nih_log!("[MidiRules] {}", fired.rule());
+                        match &fired.rule().action {
+                            RuleAction::LoadPreset {
+                                slot_index,
+                                preset_id,
+                            } => {
+                                let Some((library, path)) = preset_id.split_once('/') else {
+                                    continue;
+                                };
+                                let name = path.rsplit('/').next().unwrap_or(path).to_string();
+                                let exists =
+                                    view_model::update_slot(&plugin_state, *slot_index, |c| {
+                                        c.name = name;
+                                        c.preset_id = Some(preset_id.clone());
+                                    });
+                                if exists {
+                                    loads.request(
+                                        &jobs,
+                                        &preset_manager,
+                                        LoadTarget::Slot(*slot_index),
+                                        library,
+                                        path,
+                                        *slot_index,
+                                        None,
+                                    );
+                                }
+                            }
+                            // Already applied on the audio thread
+                            RuleAction::ToggleMute { slot_index } => {
+                                view_model::update_slot(&plugin_state, *slot_index, |c| {
+                                    c.muted = !c.muted
+                                });
+                            }
+                            RuleAction::ToggleSolo { slot_index } => {
+                                view_model::update_slot(&plugin_state, *slot_index, |c| {
+                                    c.solo = !c.solo
+                                });
+                            }
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                loads.deliver(&preset_loaded_tx, &status_text);
+            }
+        });
+    if let Err(e) = spawned {
+        log::warn!("Failed to spawn MIDI rule dispatcher thread: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nih_plug::prelude::SysExMessage;
+
+    fn cc(channel: u8, cc: u8, value: u8) -> NoteEvent<SysEx> {
+        NoteEvent::MidiCC {
+            timing: 0,
+            channel,
+            cc,
+            value: value as f32 / 127.0,
+        }
+    }
+
+    fn engine(text: &str) -> MidiRuleEngine {
+        let mut engine = MidiRuleEngine::default();
+        engine.set_rules(Arc::new(parse_rules(text).unwrap()));
+        engine
+    }
+
+    #[test]
+    fn test_parse_and_format_roundtrip() {
+        let text = "nrpn 1:2 => load 1 FluidR3_GM/Acoustic Grand Piano\n\
+                    nrpn 2:44 value 127 ch 2 => mute 3\n\
+                    sysex F0 7D 01 02 F7 => solo 16\n";
+        let rules = parse_rules(text).unwrap();
+        assert_eq!(
+            rules[0].action,
+            RuleAction::LoadPreset {
+                slot_index: 0,
+                preset_id: "FluidR3_GM/Acoustic Grand Piano".into()
+            }
+        );
+        assert_eq!(
+            rules[1].trigger,
+            RuleTrigger::Nrpn {
+                channel: Some(2),
+                param: 300,
+                value: Some(127)
+            }
+        );
+        assert_eq!(format_rules(&rules), text);
+    }
+
+    #[test]
+    fn test_parse_comments_and_numeric_param() {
+        let rules = parse_rules("# scenes\n\n  nrpn 300 => mute 1  # drums\n").unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(
+            rules[0].trigger,
+            RuleTrigger::Nrpn {
+                channel: None,
+                param: 300,
+                value: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_line() {
+        let err = parse_rules("nrpn 1:2 => mute 1\nnrpn 1:2 => mute 0").unwrap_err();
+        assert!(err.starts_with("Line 2:"), "{err}");
+        assert!(parse_rules("nrpn 1:200 => mute 1").is_err());
+        assert!(
+            parse_rules("sysex 7D 01 => mute 1").is_err(),
+            "unframed SysEx"
+        );
+        assert!(
+            parse_rules("nrpn 1:2 => load 1").is_err(),
+            "missing preset id"
+        );
+        assert!(parse_rules("nrpn 1:2 => mute 1 extra").is_err());
+        assert!(parse_rules("cc 7 => mute 1").is_err());
+    }
+
+    #[test]
+    fn test_nrpn_fires_on_data_entry() {
+        let mut engine = engine("nrpn 1:2 value 64 ch 3 => mute 1");
+        assert_eq!(engine.observe(&cc(2, 99, 1)), None);
+        assert_eq!(engine.observe(&cc(2, 98, 2)), None);
+        assert_eq!(engine.observe(&cc(2, 6, 10)), None, "value doesn't match");
+        assert_eq!(
+            engine.observe(&cc(2, 6, 64)),
+            Some(RuleMatch::Nrpn {
+                channel: 2,
+                param: 130,
+                value: 64
+            })
+        );
+        // Other channel has no selection
+        assert_eq!(engine.observe(&cc(0, 6, 64)), None);
+        // An RPN selection clears the NRPN
+        engine.observe(&cc(2, 101, 0));
+        assert_eq!(engine.observe(&cc(2, 6, 64)), None);
+    }
+
+    #[test]
+    fn test_sysex_matches_whole_message() {
+        let mut engine = engine("sysex F0 7D 01 F7 => solo 2");
+        let sysex = |bytes: &[u8]| NoteEvent::MidiSysEx {
+            timing: 0,
+            message: SysEx::from_buffer(bytes).unwrap(),
+        };
+        assert!(engine.observe(&sysex(&[0xF0, 0x7D, 0x01, 0xF7])).is_some());
+        assert!(
+            engine
+                .observe(&sysex(&[0xF0, 0x7D, 0x01, 0x00, 0xF7]))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_rules_serialize_in_state() {
+        let rules = parse_rules("nrpn 1:2 => load 1 Lib/Piano").unwrap();
+        let json = serde_json::to_string(&rules).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<MidiRule>>(&json).unwrap(), rules);
+    }
+}
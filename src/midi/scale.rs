@@ -0,0 +1,232 @@
+//! Scale lock: keep incoming notes in a key.
+//!
+//! Applied in `route_event` before notes reach any slot. Out-of-scale
+//! note-ons are snapped to the nearest scale tone (ties go down) or
+//! dropped, and each key remembers what its note-on became so the
+//! matching note-off (and poly pressure) follows it even if the lock
+//! changes while the key is down.
+
+use nih_plug::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What happens to notes outside the scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ScaleMode {
+    #[default]
+    Off,
+    Snap,
+    Block,
+}
+
+impl ScaleMode {
+    pub const ALL: [ScaleMode; 3] = [ScaleMode::Off, ScaleMode::Snap, ScaleMode::Block];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ScaleMode::Off => "Off",
+            ScaleMode::Snap => "Snap",
+            ScaleMode::Block => "Block",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Scale {
+    #[default]
+    Major,
+    NaturalMinor,
+    HarmonicMinor,
+    MelodicMinor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+    MajorPentatonic,
+    MinorPentatonic,
+    Blues,
+}
+
+impl Scale {
+    pub const ALL: [Scale; 12] = [
+        Scale::Major,
+        Scale::NaturalMinor,
+        Scale::HarmonicMinor,
+        Scale::MelodicMinor,
+        Scale::Dorian,
+        Scale::Phrygian,
+        Scale::Lydian,
+        Scale::Mixolydian,
+        Scale::Locrian,
+        Scale::MajorPentatonic,
+        Scale::MinorPentatonic,
+        Scale::Blues,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Scale::Major => "Major",
+            Scale::NaturalMinor => "Minor",
+            Scale::HarmonicMinor => "Harmonic Minor",
+            Scale::MelodicMinor => "Melodic Minor",
+            Scale::Dorian => "Dorian",
+            Scale::Phrygian => "Phrygian",
+            Scale::Lydian => "Lydian",
+            Scale::Mixolydian => "Mixolydian",
+            Scale::Locrian => "Locrian",
+            Scale::MajorPentatonic => "Major Pentatonic",
+            Scale::MinorPentatonic => "Minor Pentatonic",
+            Scale::Blues => "Blues",
+        }
+    }
+
+    /// Semitones above the root.
+    pub fn intervals(self) -> &'static [u8] {
+        match self {
+            Scale::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Scale::NaturalMinor => &[0, 2, 3, 5, 7, 8, 10],
+            Scale::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            Scale::MelodicMinor => &[0, 2, 3, 5, 7, 9, 11],
+            Scale::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+            Scale::Phrygian => &[0, 1, 3, 5, 7, 8, 10],
+            Scale::Lydian => &[0, 2, 4, 6, 7, 9, 11],
+            Scale::Mixolydian => &[0, 2, 4, 5, 7, 9, 10],
+            Scale::Locrian => &[0, 1, 3, 5, 6, 8, 10],
+            Scale::MajorPentatonic => &[0, 2, 4, 7, 9],
+            Scale::MinorPentatonic => &[0, 3, 5, 7, 10],
+            Scale::Blues => &[0, 3, 5, 6, 7, 10],
+        }
+    }
+}
+
+/// Scale lock settings (persisted in `PluginState`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScaleLock {
+    pub mode: ScaleMode,
+    /// Pitch class of the key (0 = C).
+    pub root: u8,
+    pub scale: Scale,
+}
+
+impl ScaleLock {
+    pub fn is_active(&self) -> bool {
+        self.mode != ScaleMode::Off
+    }
+
+    /// Whether a note is a tone of the scale.
+    pub fn contains(&self, note: u8) -> bool {
+        let degree = (note % 12 + 12 - self.root % 12) % 12;
+        self.scale.intervals().contains(&degree)
+    }
+
+    /// The nearest scale tone to `note`, preferring the lower on a tie.
+    pub fn snap(&self, note: u8) -> u8 {
+        (0..12u8)
+            .flat_map(|d| [note.checked_sub(d), note.checked_add(d).filter(|n| *n < 128)])
+            .flatten()
+            .find(|n| self.contains(*n))
+            .unwrap_or(note)
+    }
+}
+
+/// Marks a key whose note-on was blocked.
+const BLOCKED: u8 = u8::MAX;
+
+/// The scale lock and the note each held key was turned into.
+#[derive(Debug, Clone, Copy)]
+pub struct ScaleFilter {
+    lock: ScaleLock,
+    mapped: [u8; 128],
+}
+
+impl Default for ScaleFilter {
+    fn default() -> Self {
+        Self { lock: ScaleLock::default(), mapped: std::array::from_fn(|i| i as u8) }
+    }
+}
+
+impl ScaleFilter {
+    pub fn lock(&self) -> ScaleLock {
+        self.lock
+    }
+
+    /// Change the lock; keys already down keep their mapping until released.
+    pub fn set_lock(&mut self, lock: ScaleLock) {
+        self.lock = ScaleLock { root: lock.root % 12, ..lock };
+    }
+
+    /// The event as the slots should see it, or `None` if it's blocked.
+    pub fn apply(&mut self, event: NoteEvent<()>) -> Option<NoteEvent<()>> {
+        match event {
+            NoteEvent::NoteOn { timing, voice_id, channel, note, velocity } => {
+                let key = usize::from(note & 0x7F);
+                let note = match self.lock.mode {
+                    ScaleMode::Off => note,
+                    ScaleMode::Snap => self.lock.snap(note),
+                    ScaleMode::Block if self.lock.contains(note) => note,
+                    ScaleMode::Block => BLOCKED,
+                };
+                self.mapped[key] = note;
+                (note != BLOCKED).then_some(NoteEvent::NoteOn { timing, voice_id, channel, note, velocity })
+            }
+            NoteEvent::NoteOff { timing, voice_id, channel, note, velocity } => {
+                let key = usize::from(note & 0x7F);
+                let note = std::mem::replace(&mut self.mapped[key], key as u8);
+                (note != BLOCKED).then_some(NoteEvent::NoteOff { timing, voice_id, channel, note, velocity })
+            }
+            NoteEvent::PolyPressure { timing, voice_id, channel, note, pressure } => {
+                let note = self.mapped[usize::from(note & 0x7F)];
+                (note != BLOCKED).then_some(NoteEvent::PolyPressure { timing, voice_id, channel, note, pressure })
+            }
+            event => Some(event),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn on(note: u8) -> NoteEvent<()> {
+        NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note, velocity: 0.8 }
+    }
+
+    fn off(note: u8) -> NoteEvent<()> {
+        NoteEvent::NoteOff { timing: 0, voice_id: None, channel: 0, note, velocity: 0.0 }
+    }
+
+    #[test]
+    fn test_contains_and_snap() {
+        let d_minor = ScaleLock { mode: ScaleMode::Snap, root: 2, scale: Scale::NaturalMinor };
+        // D E F G A Bb C
+        let tones: Vec<u8> = (60..72).filter(|n| d_minor.contains(*n)).collect();
+        assert_eq!(tones, vec![60, 62, 64, 65, 67, 69, 70]);
+        assert_eq!(d_minor.snap(61), 60, "tie goes down");
+        assert_eq!(d_minor.snap(71), 70);
+        assert_eq!(d_minor.snap(64), 64);
+
+        let pentatonic = ScaleLock { mode: ScaleMode::Snap, root: 0, scale: Scale::MajorPentatonic };
+        assert_eq!(pentatonic.snap(65), 64);
+        assert_eq!(pentatonic.snap(66), 67);
+    }
+
+    #[test]
+    fn test_filter_snaps_blocks_and_follows_note_offs() {
+        let mut filter = ScaleFilter::default();
+        filter.set_lock(ScaleLock { mode: ScaleMode::Snap, root: 0, scale: Scale::Major });
+        assert_eq!(filter.apply(on(61)), Some(on(60)));
+
+        // The note-off follows the note-on even after the lock changes
+        filter.set_lock(ScaleLock { mode: ScaleMode::Block, root: 0, scale: Scale::Major });
+        assert_eq!(filter.apply(off(61)), Some(off(60)));
+
+        assert_eq!(filter.apply(on(66)), None);
+        assert_eq!(filter.apply(off(66)), None);
+        assert_eq!(filter.apply(on(67)), Some(on(67)));
+
+        filter.set_lock(ScaleLock::default());
+        assert_eq!(filter.apply(on(66)), Some(on(66)));
+        assert_eq!(filter.apply(off(66)), Some(off(66)));
+    }
+}
@@ -0,0 +1,322 @@
+//! Lock-free per-slot telemetry published by the audio thread for the editor.
+//!
+//! Like the visualizer levels, every field is an atomic so the audio thread
+//! never blocks and the UI reads whatever the last block wrote.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+use crate::midi::inspector::MidiInspector;
+use crate::midi::recorder::MidiRecorder;
+use crate::slots::MAX_SLOTS;
+use crate::slots::zone_usage::{ZONE_WORDS, ZoneUsage};
+
+/// Sentinel for "no value" in `u32` atomics.
+const NONE: u32 = u32::MAX;
+
+/// Telemetry for one slot.
+pub struct SlotMonitor {
+    /// Ordinal of the last runner note fired (NONE if idle).
+    play_event: AtomicU32,
+    /// Keyswitch key of the active articulation (NONE if none).
+    articulation: AtomicU32,
+    /// Beats until a quantized runner launch, as f32 bits (NONE if none).
+    launch_countdown: AtomicU32,
+    /// Output peak of the last block, as f32 bits.
+    output_peak: AtomicU32,
+    /// Latched when the output went over 0 dBFS, until cleared.
+    clipped: AtomicBool,
+    /// Notes latched by hold mode (low and high 64 keys).
+    held_notes: [AtomicU64; 2],
+    /// Mod wheel (CC1) and expression (CC11), as f32 bits.
+    mod_wheel: AtomicU32,
+    expression: AtomicU32,
+    /// Bend wheel (−1..1), as f32 bits.
+    pitch_bend: AtomicU32,
+    /// Zones of the loaded preset played so far (see `zone_usage`).
+    zones_used: [AtomicU64; ZONE_WORDS],
+    /// A purged zone was played and needs reloading.
+    zones_missing: AtomicBool,
+}
+
+impl Default for SlotMonitor {
+    fn default() -> Self {
+        Self {
+            play_event: AtomicU32::new(NONE),
+            articulation: AtomicU32::new(NONE),
+            launch_countdown: AtomicU32::new(NONE),
+            output_peak: AtomicU32::new(0),
+            clipped: AtomicBool::new(false),
+            held_notes: [AtomicU64::new(0), AtomicU64::new(0)],
+            mod_wheel: AtomicU32::new(0),
+            expression: AtomicU32::new(1.0_f32.to_bits()),
+            pitch_bend: AtomicU32::new(0),
+            zones_used: std::array::from_fn(|_| AtomicU64::new(0)),
+            zones_missing: AtomicBool::new(false),
+        }
+    }
+}
+
+/// Telemetry for the whole rack, shared between audio thread and editor.
+pub struct EngineMonitor {
+    slots: [SlotMonitor; MAX_SLOTS],
+    /// Capture of incoming MIDI for the header's record button.
+    midi_recorder: MidiRecorder,
+    /// Recent routed MIDI for the editor's MIDI monitor.
+    midi_inspector: MidiInspector,
+    /// Tempo and sample rate of the last block, as f32 bits.
+    tempo: AtomicU32,
+    sample_rate: AtomicU32,
+}
+
+impl Default for EngineMonitor {
+    fn default() -> Self {
+        Self {
+            slots: std::array::from_fn(|_| SlotMonitor::default()),
+            midi_recorder: MidiRecorder::default(),
+            midi_inspector: MidiInspector::default(),
+            tempo: AtomicU32::new(120.0_f32.to_bits()),
+            sample_rate: AtomicU32::new(44100.0_f32.to_bits()),
+        }
+    }
+}
+
+impl EngineMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish the runner play position for a slot (audio thread).
+    pub fn set_play_event(&self, slot: usize, event: Option<usize>) {
+        if let Some(m) = self.slots.get(slot) {
+            let v = event.map(|e| e.min(NONE as usize - 1) as u32).unwrap_or(NONE);
+            m.play_event.store(v, Ordering::Relaxed);
+        }
+    }
+
+    /// Read the runner play position for a slot (UI thread).
+    pub fn play_event(&self, slot: usize) -> Option<usize> {
+        let v = self.slots.get(slot)?.play_event.load(Ordering::Relaxed);
+        (v != NONE).then_some(v as usize)
+    }
+
+    /// Publish the active articulation keyswitch for a slot (audio thread).
+    pub fn set_articulation(&self, slot: usize, key: Option<u8>) {
+        if let Some(m) = self.slots.get(slot) {
+            m.articulation.store(key.map_or(NONE, u32::from), Ordering::Relaxed);
+        }
+    }
+
+    /// Read the active articulation keyswitch for a slot (UI thread).
+    pub fn articulation(&self, slot: usize) -> Option<u8> {
+        let v = self.slots.get(slot)?.articulation.load(Ordering::Relaxed);
+        (v != NONE).then_some(v as u8)
+    }
+
+    /// Publish the beats left until a waiting runner launches (audio thread).
+    pub fn set_launch_countdown(&self, slot: usize, beats: Option<f64>) {
+        if let Some(m) = self.slots.get(slot) {
+            let v = beats.map_or(NONE, |b| (b as f32).to_bits());
+            m.launch_countdown.store(v, Ordering::Relaxed);
+        }
+    }
+
+    /// Read the beats left until a waiting runner launches (UI thread).
+    pub fn launch_countdown(&self, slot: usize) -> Option<f32> {
+        let v = self.slots.get(slot)?.launch_countdown.load(Ordering::Relaxed);
+        (v != NONE).then(|| f32::from_bits(v))
+    }
+
+    /// Publish a slot's output peak, latching its clip LED (audio thread).
+    pub fn set_output_peak(&self, slot: usize, peak: f32) {
+        if let Some(m) = self.slots.get(slot) {
+            m.output_peak.store(peak.to_bits(), Ordering::Relaxed);
+            if peak > 1.0 {
+                m.clipped.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Read a slot's output peak (UI thread).
+    pub fn output_peak(&self, slot: usize) -> f32 {
+        self.slots
+            .get(slot)
+            .map_or(0.0, |m| f32::from_bits(m.output_peak.load(Ordering::Relaxed)))
+    }
+
+    /// Whether a slot clipped since its LED was cleared (UI thread).
+    pub fn clipped(&self, slot: usize) -> bool {
+        self.slots.get(slot).is_some_and(|m| m.clipped.load(Ordering::Relaxed))
+    }
+
+    pub fn clear_clip(&self, slot: usize) {
+        if let Some(m) = self.slots.get(slot) {
+            m.clipped.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Publish a slot's mod wheel and expression values (audio thread).
+    pub fn set_controllers(&self, slot: usize, mod_wheel: f32, expression: f32) {
+        if let Some(m) = self.slots.get(slot) {
+            m.mod_wheel.store(mod_wheel.to_bits(), Ordering::Relaxed);
+            m.expression.store(expression.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Read a slot's mod wheel and expression values (UI thread).
+    pub fn controllers(&self, slot: usize) -> (f32, f32) {
+        self.slots.get(slot).map_or((0.0, 1.0), |m| {
+            (
+                f32::from_bits(m.mod_wheel.load(Ordering::Relaxed)),
+                f32::from_bits(m.expression.load(Ordering::Relaxed)),
+            )
+        })
+    }
+
+    /// Publish a slot's bend wheel position (audio thread).
+    pub fn set_pitch_bend(&self, slot: usize, bend: f32) {
+        if let Some(m) = self.slots.get(slot) {
+            m.pitch_bend.store(bend.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Read a slot's bend wheel position, −1..1 (UI thread).
+    pub fn pitch_bend(&self, slot: usize) -> f32 {
+        self.slots.get(slot).map_or(0.0, |m| f32::from_bits(m.pitch_bend.load(Ordering::Relaxed)))
+    }
+
+    /// Publish the notes a slot's hold mode has latched (audio thread).
+    pub fn set_held_notes(&self, slot: usize, notes: u128) {
+        if let Some(m) = self.slots.get(slot) {
+            m.held_notes[0].store(notes as u64, Ordering::Relaxed);
+            m.held_notes[1].store((notes >> 64) as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Read the notes a slot's hold mode has latched (UI thread).
+    pub fn held_notes(&self, slot: usize) -> Vec<u8> {
+        let Some(m) = self.slots.get(slot) else {
+            return Vec::new();
+        };
+        let bits = u128::from(m.held_notes[0].load(Ordering::Relaxed))
+            | u128::from(m.held_notes[1].load(Ordering::Relaxed)) << 64;
+        (0..128u8).filter(|n| bits & (1 << n) != 0).collect()
+    }
+
+    /// Publish which zones of a slot's preset were played (audio thread).
+    /// Only the words covering `zone_count` zones are written.
+    pub fn set_zone_usage(&self, slot: usize, usage: &ZoneUsage, zone_count: usize) {
+        if let Some(m) = self.slots.get(slot) {
+            let words = zone_count.div_ceil(64).min(ZONE_WORDS);
+            for (word, bits) in m.zones_used.iter().zip(&usage.used).take(words) {
+                word.store(*bits, Ordering::Relaxed);
+            }
+            m.zones_missing.store(usage.any_missing(), Ordering::Relaxed);
+        }
+    }
+
+    /// Read which zones of a slot's preset were played (UI thread).
+    pub fn zone_usage(&self, slot: usize) -> ZoneUsage {
+        let mut usage = ZoneUsage::default();
+        if let Some(m) = self.slots.get(slot) {
+            for (bits, word) in usage.used.iter_mut().zip(&m.zones_used) {
+                *bits = word.load(Ordering::Relaxed);
+            }
+        }
+        usage
+    }
+
+    /// Whether a slot played a purged zone since it was last purged.
+    pub fn zones_missing(&self, slot: usize) -> bool {
+        self.slots.get(slot).is_some_and(|m| m.zones_missing.load(Ordering::Relaxed))
+    }
+
+    /// Publish the engine's tempo and sample rate (audio thread).
+    pub fn set_timing(&self, bpm: f64, sample_rate: f32) {
+        self.tempo.store((bpm as f32).to_bits(), Ordering::Relaxed);
+        self.sample_rate.store(sample_rate.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Tempo of the last block (UI thread).
+    pub fn tempo(&self) -> f64 {
+        f64::from(f32::from_bits(self.tempo.load(Ordering::Relaxed)))
+    }
+
+    /// Sample rate of the last block (UI thread).
+    pub fn sample_rate(&self) -> f32 {
+        f32::from_bits(self.sample_rate.load(Ordering::Relaxed))
+    }
+
+    pub fn midi_recorder(&self) -> &MidiRecorder {
+        &self.midi_recorder
+    }
+
+    pub fn midi_inspector(&self) -> &MidiInspector {
+        &self.midi_inspector
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_play_event_default_none() {
+        let m = EngineMonitor::new();
+        assert_eq!(m.play_event(0), None);
+    }
+
+    #[test]
+    fn test_play_event_roundtrip() {
+        let m = EngineMonitor::new();
+        m.set_play_event(3, Some(7));
+        assert_eq!(m.play_event(3), Some(7));
+        m.set_play_event(3, None);
+        assert_eq!(m.play_event(3), None);
+    }
+
+    #[test]
+    fn test_articulation_roundtrip() {
+        let m = EngineMonitor::new();
+        assert_eq!(m.articulation(1), None);
+        m.set_articulation(1, Some(24));
+        assert_eq!(m.articulation(1), Some(24));
+    }
+
+    #[test]
+    fn test_launch_countdown_roundtrip() {
+        let m = EngineMonitor::new();
+        assert_eq!(m.launch_countdown(2), None);
+        m.set_launch_countdown(2, Some(1.5));
+        assert_eq!(m.launch_countdown(2), Some(1.5));
+        m.set_launch_countdown(2, None);
+        assert_eq!(m.launch_countdown(2), None);
+    }
+
+    #[test]
+    fn test_output_peak_latches_clip() {
+        let m = EngineMonitor::new();
+        m.set_output_peak(0, 0.5);
+        assert_eq!(m.output_peak(0), 0.5);
+        assert!(!m.clipped(0));
+        m.set_output_peak(0, 1.2);
+        m.set_output_peak(0, 0.1);
+        assert!(m.clipped(0));
+        m.clear_clip(0);
+        assert!(!m.clipped(0));
+    }
+
+    #[test]
+    fn test_held_notes_roundtrip() {
+        let m = EngineMonitor::new();
+        m.set_held_notes(1, 1 << 60 | 1 << 100);
+        assert_eq!(m.held_notes(1), vec![60, 100]);
+        assert!(m.held_notes(0).is_empty());
+    }
+
+    #[test]
+    fn test_play_event_out_of_range_ignored() {
+        let m = EngineMonitor::new();
+        m.set_play_event(MAX_SLOTS, Some(1));
+        assert_eq!(m.play_event(MAX_SLOTS), None);
+    }
+}
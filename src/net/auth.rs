@@ -0,0 +1,204 @@
+//! Credentials for private preset libraries.
+//!
+//! A library server can ask for a bearer token or HTTP basic auth. Which
+//! scheme each server uses (and the basic-auth user name) is kept in
+//! `library_auth.json` under the user config directory, keyed by the
+//! server's base URL; the token or password itself goes to the OS keyring
+//! (Keychain, Credential Manager or Secret Service) and never to disk.
+//! Every request `HttpClient` makes to a URL under a configured base URL
+//! carries the credentials; preset and sample downloads go through it (see
+//! `preset::fetch`).
+
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+use reqwest::RequestBuilder;
+use serde::{Deserialize, Serialize};
+
+/// Keyring service name the secrets are stored under.
+const KEYRING_SERVICE: &str = "songwalker-vsti";
+
+/// How a server expects to be authenticated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum AuthScheme {
+    /// `Authorization: Bearer <token>`.
+    Bearer,
+    /// `Authorization: Basic`, with the password as the secret.
+    Basic { username: String },
+}
+
+impl AuthScheme {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Bearer => "Bearer token",
+            Self::Basic { .. } => "Basic auth",
+        }
+    }
+}
+
+/// Authentication for one library server (secret not included).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LibraryAuth {
+    /// Base URL the credentials apply to (and every URL under it).
+    pub base_url: String,
+    pub scheme: AuthScheme,
+}
+
+/// A configured server with its secret, as held in memory.
+struct Entry {
+    auth: LibraryAuth,
+    /// None if the keyring had nothing (or couldn't be reached).
+    secret: Option<String>,
+}
+
+fn config_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("org", "SongWalker", "songwalker-vsti")
+        .map(|d| d.config_dir().join("library_auth.json"))
+}
+
+fn keyring_entry(base_url: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, base_url).map_err(|e| format!("Keyring unavailable: {}", e))
+}
+
+fn store() -> &'static RwLock<Vec<Entry>> {
+    static STORE: OnceLock<RwLock<Vec<Entry>>> = OnceLock::new();
+    STORE.get_or_init(|| {
+        let configured: Vec<LibraryAuth> = config_path()
+            .and_then(|p| std::fs::read(p).ok())
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        let entries = configured
+            .into_iter()
+            .map(|auth| {
+                let secret = keyring_entry(&auth.base_url).and_then(|e| e.get_password().map_err(|e| e.to_string()));
+                if let Err(e) = &secret {
+                    log::warn!("[Auth] No secret for {}: {}", auth.base_url, e);
+                }
+                Entry { auth, secret: secret.ok() }
+            })
+            .collect();
+        RwLock::new(entries)
+    })
+}
+
+fn save(entries: &[Entry]) -> Result<(), String> {
+    let Some(path) = config_path() else { return Ok(()) };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let configured: Vec<&LibraryAuth> = entries.iter().map(|e| &e.auth).collect();
+    let json = serde_json::to_vec_pretty(&configured).map_err(|e| e.to_string())?;
+    super::atomic::write_atomic(&path, &json).map_err(|e| format!("Failed to save library credentials: {}", e))
+}
+
+/// Normalise a base URL for matching and as the keyring key.
+fn normalize(base_url: &str) -> String {
+    base_url.trim().trim_end_matches('/').to_string()
+}
+
+/// Whether `url` is `base_url` or under it.
+fn is_under(base_url: &str, url: &str) -> bool {
+    url.strip_prefix(base_url)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?', '#']))
+}
+
+/// Configured servers, and whether each has its secret available.
+pub fn configured() -> Vec<(LibraryAuth, bool)> {
+    store()
+        .read()
+        .map(|entries| entries.iter().map(|e| (e.auth.clone(), e.secret.is_some())).collect())
+        .unwrap_or_default()
+}
+
+/// Set the credentials for a server, replacing any it had.
+pub fn set(mut auth: LibraryAuth, secret: &str) -> Result<(), String> {
+    auth.base_url = normalize(&auth.base_url);
+    if auth.base_url.is_empty() {
+        return Err("Enter the library URL".to_string());
+    }
+    if secret.is_empty() {
+        return Err("Enter a token or password".to_string());
+    }
+    keyring_entry(&auth.base_url)?
+        .set_password(secret)
+        .map_err(|e| format!("Failed to store the secret in the keyring: {}", e))?;
+    let mut entries = store().write().map_err(|_| "Credentials unavailable".to_string())?;
+    entries.retain(|e| e.auth.base_url != auth.base_url);
+    entries.push(Entry { auth, secret: Some(secret.to_string()) });
+    save(&entries)
+}
+
+/// Forget a server's credentials, removing its secret from the keyring.
+pub fn remove(base_url: &str) -> Result<(), String> {
+    let base_url = normalize(base_url);
+    if let Ok(entry) = keyring_entry(&base_url) {
+        // Already gone is fine
+        let _ = entry.delete_credential();
+    }
+    let mut entries = store().write().map_err(|_| "Credentials unavailable".to_string())?;
+    entries.retain(|e| e.auth.base_url != base_url);
+    save(&entries)
+}
+
+/// The most specific configured credentials covering `url`.
+fn credential_for<'a>(entries: &'a [Entry], url: &str) -> Option<(&'a AuthScheme, &'a str)> {
+    entries
+        .iter()
+        .filter(|e| is_under(&e.auth.base_url, url))
+        .max_by_key(|e| e.auth.base_url.len())
+        .and_then(|e| Some((&e.auth.scheme, e.secret.as_deref()?)))
+}
+
+fn authorize(request: RequestBuilder, scheme: &AuthScheme, secret: &str) -> RequestBuilder {
+    match scheme {
+        AuthScheme::Bearer => request.bearer_auth(secret),
+        AuthScheme::Basic { username } => request.basic_auth(username, Some(secret)),
+    }
+}
+
+/// Add the credentials configured for `url`'s server, if any.
+pub fn apply(request: RequestBuilder, url: &str) -> RequestBuilder {
+    let Ok(entries) = store().read() else { return request };
+    match credential_for(&entries, url) {
+        Some((scheme, secret)) => authorize(request, scheme, secret),
+        None => request,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credentials_match_their_server_only() {
+        let entry = |base_url: &str, scheme: AuthScheme, secret: &str| Entry {
+            auth: LibraryAuth { base_url: normalize(base_url), scheme },
+            secret: Some(secret.to_string()),
+        };
+        let entries = vec![
+            entry("https://presets.example.com/", AuthScheme::Bearer, "abc"),
+            entry(
+                "https://presets.example.com/team",
+                AuthScheme::Basic { username: "me".into() },
+                "pw",
+            ),
+        ];
+
+        let (scheme, secret) = credential_for(&entries, "https://presets.example.com/index.json").unwrap();
+        assert_eq!((scheme, secret), (&AuthScheme::Bearer, "abc"));
+        // The longer base URL wins
+        let (scheme, _) = credential_for(&entries, "https://presets.example.com/team/lib/index.json").unwrap();
+        assert_eq!(scheme.label(), "Basic auth");
+        assert!(credential_for(&entries, "https://presets.example.com.evil/index.json").is_none());
+        assert!(credential_for(&entries, "https://presets.example.com/teamwork/x").is_some_and(|(s, _)| *s == AuthScheme::Bearer));
+
+        let client = reqwest::Client::new();
+        let request = authorize(client.get("https://presets.example.com/a"), scheme, "pw").build().unwrap();
+        let header = request.headers()[reqwest::header::AUTHORIZATION].to_str().unwrap();
+        assert_eq!(header, "Basic bWU6cHc=");
+
+        let json = serde_json::to_string(&entries[1].auth).unwrap();
+        assert!(json.contains("\"kind\":\"basic\"") && !json.contains("pw\""));
+    }
+}
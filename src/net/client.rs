@@ -0,0 +1,388 @@
+//! Shared HTTP client with conditional revalidation.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use reqwest::header::{CONTENT_LENGTH, IF_MODIFIED_SINCE, IF_NONE_MATCH};
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+use super::connectivity;
+use super::error::FetchError;
+use super::mirrors::{self, Mirrors};
+use super::retry::{self, RetryPolicy};
+use super::validators::{ValidatorStore, Validators};
+
+/// User agent sent with every request.
+pub const USER_AGENT: &str = concat!("SongWalker-VSTi/", env!("CARGO_PKG_VERSION"));
+
+/// Where a fetched body came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// Downloaded: new, or changed since the stored copy.
+    Fresh,
+    /// Server answered 304; the stored copy is current.
+    Unchanged,
+    /// Network unavailable; the stored copy was used as-is.
+    Offline,
+}
+
+/// A fetched document.
+#[derive(Debug, Clone)]
+pub struct Fetched {
+    pub body: Vec<u8>,
+    pub freshness: Freshness,
+}
+
+impl Fetched {
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// HTTP client shared by all fetches from this crate.
+pub struct HttpClient {
+    client: reqwest::Client,
+    validators: ValidatorStore,
+    retry: RetryPolicy,
+    mirrors: &'static Mutex<Mirrors>,
+}
+
+impl HttpClient {
+    pub fn new(validators: ValidatorStore) -> Self {
+        let client = super::settings::current()
+            .build_client(USER_AGENT)
+            .unwrap_or_else(|e| {
+                nih_plug::debug::nih_log!("[Http] {}; using defaults", e);
+                reqwest::Client::builder()
+                    .user_agent(USER_AGENT)
+                    .timeout(Duration::from_secs(super::settings::DEFAULT_TIMEOUT_SECS))
+                    .build()
+                    .unwrap_or_default()
+            });
+        Self { client, validators, retry: RetryPolicy::default(), mirrors: mirrors::global() }
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Use other mirror lists than the configured ones.
+    pub fn with_mirrors(mut self, mirrors: &'static Mutex<Mirrors>) -> Self {
+        self.mirrors = mirrors;
+        self
+    }
+
+    /// Send a request built by `build` for a URL, retrying transient
+    /// failures with backoff.
+    ///
+    /// Reports reachability to the global connectivity monitor.
+    async fn send(&self, url: &str, build: impl Fn(&str) -> RequestBuilder) -> Result<Response, FetchError> {
+        let mut attempt = 0;
+        loop {
+            let last = attempt + 1 >= self.retry.max_attempts;
+            match self.send_to_hosts(url, &build).await {
+                Ok(response) => {
+                    connectivity::global().report_success();
+                    if retry::is_retryable_status(response.status()) && !last {
+                        nih_plug::debug::nih_log!("[Http] {} from {}, retrying", response.status(), url);
+                    } else {
+                        return Ok(response);
+                    }
+                }
+                Err(e) => {
+                    let err = FetchError::from_reqwest(url, &e);
+                    if err.is_unreachable() {
+                        connectivity::global().report_failure();
+                    }
+                    if !retry::is_transient_error(&e) || last {
+                        return Err(err);
+                    }
+                    nih_plug::debug::nih_log!("[Http] {} failed ({}), retrying", url, e);
+                }
+            }
+            self.back_off(attempt).await;
+            attempt += 1;
+        }
+    }
+
+    async fn back_off(&self, attempt: u32) {
+        tokio::time::sleep(self.retry.delay(attempt, retry::jitter_random())).await;
+    }
+
+    /// One attempt at `url`, failing over to the mirrors of its repository
+    /// (see `mirrors`), best host first. Each request carries the
+    /// credentials configured for the host it goes to, never those of the
+    /// URL it stands in for.
+    async fn send_to_hosts(
+        &self,
+        url: &str,
+        build: &impl Fn(&str) -> RequestBuilder,
+    ) -> Result<Response, reqwest::Error> {
+        let mut candidates = self.mirrors.lock().map(|m| m.candidates(url, Instant::now())).unwrap_or_default();
+        if candidates.len() < 2 {
+            return super::auth::apply(build(url), url).send().await;
+        }
+        let report = |host: &str, elapsed: Option<Duration>| {
+            if let Ok(mut m) = self.mirrors.lock() {
+                match elapsed {
+                    Some(elapsed) => m.report_success(host, elapsed),
+                    None => m.report_failure(host, Instant::now()),
+                }
+            }
+        };
+
+        let (last_host, last_url) = candidates.pop().unwrap_or_default();
+        for (host, candidate) in candidates {
+            let started = Instant::now();
+            let request = super::auth::apply(build(&candidate), &candidate).timeout(mirrors::SLOW_AFTER);
+            match request.send().await {
+                Ok(response) if !response.status().is_server_error() => {
+                    report(&host, Some(started.elapsed()));
+                    return Ok(response);
+                }
+                Ok(response) => {
+                    nih_plug::debug::nih_log!("[Http] {} from {}, trying a mirror", response.status(), candidate)
+                }
+                Err(e) => nih_plug::debug::nih_log!("[Http] {} failed ({}), trying a mirror", candidate, e),
+            }
+            report(&host, None);
+        }
+
+        // The last host gets the full timeout
+        let started = Instant::now();
+        let result = super::auth::apply(build(&last_url), &last_url).send().await;
+        let ok = result.as_ref().is_ok_and(|r| !r.status().is_server_error());
+        report(&last_host, ok.then(|| started.elapsed()));
+        result
+    }
+
+    /// Last stored body for `url`, without touching the network.
+    pub fn cached(&self, url: &str) -> Option<Vec<u8>> {
+        self.validators.body(url)
+    }
+
+    /// Drop the stored copy of `url` so the next fetch is unconditional.
+    pub fn evict(&self, url: &str) {
+        self.validators.evict(url);
+    }
+
+    /// Whether `url` answers at all (no retries).
+    pub async fn probe(&self, url: &str) -> bool {
+        match super::auth::apply(self.client.head(url), url).send().await {
+            Ok(_) => {
+                connectivity::global().report_success();
+                true
+            }
+            Err(_) => {
+                connectivity::global().report_failure();
+                false
+            }
+        }
+    }
+
+    /// Size of `url` from a HEAD request's `Content-Length` (no retries).
+    pub async fn content_length(&self, url: &str) -> Option<u64> {
+        let response = super::auth::apply(self.client.head(url), url).send().await.ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        response
+            .headers()
+            .get(CONTENT_LENGTH)?
+            .to_str()
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    /// Client using the default on-disk validator store.
+    pub fn with_default_store() -> Self {
+        Self::new(ValidatorStore::open_default())
+    }
+
+    /// GET `url`, revalidating a stored copy with `If-None-Match` /
+    /// `If-Modified-Since`.
+    ///
+    /// Transient failures are retried with backoff; after that it falls back
+    /// to the stored copy (as [`Freshness::Offline`]) when the network is
+    /// unreachable or the server errors.
+    pub async fn get_revalidated(&self, url: &str) -> Result<Fetched, String> {
+        let stored = self.validators.get(url).filter(|v| !v.is_empty());
+        let build = |target: &str| {
+            let mut request = self.client.get(target);
+            if let Some(v) = &stored {
+                if let Some(etag) = &v.etag {
+                    request = request.header(IF_NONE_MATCH, etag);
+                }
+                if let Some(modified) = &v.last_modified {
+                    request = request.header(IF_MODIFIED_SINCE, modified);
+                }
+            }
+            request
+        };
+
+        let offline = |err: FetchError| match self.validators.body(url) {
+            Some(body) => Ok(Fetched { body, freshness: Freshness::Offline }),
+            None => Err(err.to_string()),
+        };
+
+        let response = match self.send(url, build).await {
+            Ok(r) => r,
+            Err(e) => return offline(e),
+        };
+
+        if response.status() == StatusCode::NOT_MODIFIED && stored.is_some() {
+            if let Some(body) = self.validators.body(url) {
+                return Ok(Fetched { body, freshness: Freshness::Unchanged });
+            }
+            // Body lost locally: fetch it again unconditionally
+            return self.get_fresh(url).await;
+        }
+        if !response.status().is_success() {
+            return offline(FetchError::Status(response.status().as_u16(), url.to_string()));
+        }
+
+        let validators = Validators::from_headers(response.headers());
+        let body = match response.bytes().await {
+            Ok(b) => b.to_vec(),
+            Err(e) => return offline(FetchError::from_reqwest(url, &e)),
+        };
+        self.validators.store(url, validators, &body);
+        Ok(Fetched { body, freshness: Freshness::Fresh })
+    }
+
+    /// Plain GET of `url`, retried like any other request; a body that
+    /// breaks off mid-download is retried too. Nothing is stored: the
+    /// caller caches what it needs.
+    pub async fn get(&self, url: &str) -> Result<Vec<u8>, FetchError> {
+        let mut attempt = 0;
+        loop {
+            let response = self.send(url, |target| self.client.get(target)).await?;
+            if !response.status().is_success() {
+                return Err(FetchError::Status(response.status().as_u16(), url.to_string()));
+            }
+            match response.bytes().await {
+                Ok(body) => return Ok(body.to_vec()),
+                Err(e) if attempt + 1 < self.retry.max_attempts && (e.is_body() || retry::is_transient_error(&e)) => {
+                    nih_plug::debug::nih_log!("[Http] {} broke off ({}), retrying", url, e);
+                }
+                Err(e) => return Err(FetchError::from_reqwest(url, &e)),
+            }
+            self.back_off(attempt).await;
+            attempt += 1;
+        }
+    }
+
+    /// Unconditional GET, storing the result for later revalidation.
+    async fn get_fresh(&self, url: &str) -> Result<Fetched, String> {
+        let response = self.send(url, |target| self.client.get(target)).await?;
+        if !response.status().is_success() {
+            return Err(FetchError::Status(response.status().as_u16(), url.to_string()).into());
+        }
+        let validators = Validators::from_headers(response.headers());
+        let body = response.bytes().await.map_err(|e| FetchError::from_reqwest(url, &e))?.to_vec();
+        self.validators.store(url, validators, &body);
+        Ok(Fetched { body, freshness: Freshness::Fresh })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::mock::{MockLibrary, block_on};
+
+    fn client() -> HttpClient {
+        let retry = RetryPolicy { max_attempts: 3, base_delay: Duration::ZERO, max_delay: Duration::ZERO, jitter: 0.0 };
+        HttpClient::new(ValidatorStore::in_memory()).with_retry(retry)
+    }
+
+    #[test]
+    fn test_revalidates_stored_copy() {
+        let server = MockLibrary::fixture();
+        let url = server.url_for("index.json");
+        let client = client();
+        block_on(async {
+            let first = client.get_revalidated(&url).await.unwrap();
+            assert_eq!(first.freshness, Freshness::Fresh);
+            let second = client.get_revalidated(&url).await.unwrap();
+            assert_eq!(second.freshness, Freshness::Unchanged);
+            assert_eq!(second.body, first.body);
+
+            // A changed document is downloaded again
+            server.set("index.json", r#"{"libraries": []}"#);
+            let third = client.get_revalidated(&url).await.unwrap();
+            assert_eq!((third.freshness, third.text().as_str()), (Freshness::Fresh, r#"{"libraries": []}"#));
+        });
+    }
+
+    #[test]
+    fn test_retries_then_falls_back_to_stored_copy() {
+        let server = MockLibrary::fixture();
+        let url = server.url_for("index.json");
+        let client = client();
+        block_on(async {
+            // Two 503s are retried through
+            server.fail("index.json", 503, 2);
+            assert_eq!(client.get_revalidated(&url).await.unwrap().freshness, Freshness::Fresh);
+            assert_eq!(server.hits("index.json"), 3);
+
+            // An outage past the retries serves the stored copy
+            server.fail("index.json", 500, 3);
+            let fetched = client.get_revalidated(&url).await.unwrap();
+            assert_eq!(fetched.freshness, Freshness::Offline);
+
+            // Nothing stored: the error comes through
+            server.fail("other.json", 404, 1);
+            let err = client.get_revalidated(&server.url_for("other.json")).await.unwrap_err();
+            assert!(err.contains("404"), "{}", err);
+        });
+    }
+
+    #[test]
+    fn test_get_classifies_failures() {
+        let server = MockLibrary::fixture();
+        let client = client();
+        // A port nothing listens on
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        block_on(async {
+            let err = client.get(&server.url_for("missing.json")).await.unwrap_err();
+            assert_eq!((err.status(), err.is_unreachable()), (Some(404), false));
+            let err = client.get(&format!("http://{}/index.json", closed)).await.unwrap_err();
+            assert!(err.is_unreachable(), "{}", err);
+        });
+    }
+
+    #[test]
+    fn test_fails_over_to_a_mirror() {
+        use crate::net::mirrors::MirrorList;
+
+        let primary = MockLibrary::fixture();
+        let mirror = MockLibrary::fixture();
+        let lists = vec![MirrorList { base_url: primary.url(), mirrors: vec![mirror.url()] }];
+        let mirrors: &'static Mutex<Mirrors> = Box::leak(Box::new(Mutex::new(Mirrors::new(lists))));
+        let client = HttpClient::new(ValidatorStore::in_memory()).with_retry(RetryPolicy::NONE).with_mirrors(mirrors);
+        block_on(async {
+            primary.fail("index.json", 500, 1);
+            let body = client.get(&primary.url_for("index.json")).await.unwrap();
+            assert!(String::from_utf8_lossy(&body).contains("libraries"));
+            assert_eq!((primary.hits("index.json"), mirror.hits("index.json")), (1, 1));
+
+            // The failed primary now goes last
+            client.get(&primary.url_for("index.json")).await.unwrap();
+            assert_eq!((primary.hits("index.json"), mirror.hits("index.json")), (1, 2));
+        });
+        assert_eq!(mirrors.lock().unwrap().health_label(&primary.url()), "failing");
+    }
+
+    #[test]
+    fn test_content_length_from_head() {
+        let server = MockLibrary::start();
+        server.set("sample.wav", vec![0u8; 1234]);
+        let client = client();
+        block_on(async {
+            assert_eq!(client.content_length(&server.url_for("sample.wav")).await, Some(1234));
+            assert_eq!(client.content_length(&server.url_for("missing.wav")).await, None);
+        });
+    }
+}
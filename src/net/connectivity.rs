@@ -0,0 +1,92 @@
+//! Process-wide online/offline tracking.
+//!
+//! Every request made through [`super::HttpClient`] reports whether the
+//! server could be reached. The editor shows the state in the status bar
+//! and re-attempts failed library fetches when it flips back to online.
+
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+
+/// Current network reachability.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// No request has completed yet.
+    Unknown,
+    Online,
+    Offline,
+}
+
+/// Shared reachability state.
+#[derive(Default)]
+pub struct ConnectivityMonitor {
+    state: AtomicU8,
+    /// Number of offline → online transitions so far.
+    reconnects: AtomicU64,
+}
+
+/// The process-wide monitor.
+pub fn global() -> &'static ConnectivityMonitor {
+    static MONITOR: OnceLock<ConnectivityMonitor> = OnceLock::new();
+    MONITOR.get_or_init(ConnectivityMonitor::default)
+}
+
+const UNKNOWN: u8 = 0;
+const ONLINE: u8 = 1;
+const OFFLINE: u8 = 2;
+
+impl ConnectivityMonitor {
+    pub fn state(&self) -> Connectivity {
+        match self.state.load(Ordering::Acquire) {
+            ONLINE => Connectivity::Online,
+            OFFLINE => Connectivity::Offline,
+            _ => Connectivity::Unknown,
+        }
+    }
+
+    /// The server answered (any HTTP status).
+    pub fn report_success(&self) {
+        if self.state.swap(ONLINE, Ordering::AcqRel) == OFFLINE {
+            self.reconnects.fetch_add(1, Ordering::AcqRel);
+        }
+    }
+
+    /// The server could not be reached (connect error or timeout).
+    pub fn report_failure(&self) {
+        self.state.store(OFFLINE, Ordering::Release);
+    }
+
+    /// Count of offline → online transitions; compare against a previously
+    /// seen value to detect a reconnect.
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects.load(Ordering::Acquire)
+    }
+}
+
+impl Connectivity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Connectivity::Unknown => "Connecting\u{2026}",
+            Connectivity::Online => "Online",
+            Connectivity::Offline => "Offline",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_counted_once() {
+        let m = ConnectivityMonitor::default();
+        assert_eq!(m.state(), Connectivity::Unknown);
+        m.report_success();
+        assert_eq!(m.reconnects(), 0, "unknown → online is not a reconnect");
+        m.report_failure();
+        assert_eq!(m.state(), Connectivity::Offline);
+        m.report_success();
+        m.report_success();
+        assert_eq!(m.state(), Connectivity::Online);
+        assert_eq!(m.reconnects(), 1);
+    }
+}
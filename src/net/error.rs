@@ -0,0 +1,64 @@
+//! Typed fetch errors, so callers can tell an unreachable host from a
+//! server that answered with an error or a body that didn't decode.
+
+use std::fmt;
+
+/// Why a fetch failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchError {
+    /// No answer: the connection failed or timed out.
+    Unreachable(String),
+    /// The server answered with an error status.
+    Status(u16, String),
+    /// Anything else: a dropped body, a document or sample that didn't
+    /// parse or decode.
+    Other(String),
+}
+
+impl FetchError {
+    /// Classify a transport error for `url`.
+    pub fn from_reqwest(url: &str, err: &reqwest::Error) -> Self {
+        let message = format!("Failed to fetch {}: {}", url, err);
+        if err.is_connect() || err.is_timeout() {
+            Self::Unreachable(message)
+        } else {
+            Self::Other(message)
+        }
+    }
+
+    /// Whether the host couldn't be reached, i.e. we may be offline.
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self, Self::Unreachable(_))
+    }
+
+    /// HTTP status of a server error, if that is what this is.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Status(status, _) => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unreachable(message) | Self::Other(message) => f.write_str(message),
+            Self::Status(status, url) => write!(f, "HTTP {} fetching {}", status, url),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+impl From<String> for FetchError {
+    fn from(message: String) -> Self {
+        Self::Other(message)
+    }
+}
+
+impl From<FetchError> for String {
+    fn from(err: FetchError) -> Self {
+        err.to_string()
+    }
+}
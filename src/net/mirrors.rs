@@ -0,0 +1,200 @@
+//! Mirror failover for library hosts.
+//!
+//! A library repository (a base URL, like the library URL in Settings) can
+//! list mirrors: other base URLs serving the same files, such as a second
+//! host or an IPv6 address (`http://[2001:db8::1]/library`). For each
+//! request `HttpClient` tries the repository's hosts best first — a host
+//! that failed within `COOLDOWN` goes last, otherwise the fastest recent
+//! responder leads — and moves on to the next when a host can't be
+//! reached, answers with a server error or takes longer than `SLOW_AFTER`.
+//! A mirror gets the credentials configured for its own URL (see `auth`),
+//! not the repository's.
+//!
+//! The lists are kept in `mirrors.json` under the user config directory;
+//! host health lives in memory only.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// A host slower than this is abandoned for the next one (the last host
+/// gets the full request timeout).
+pub const SLOW_AFTER: Duration = Duration::from_secs(8);
+
+/// How long a host that failed is tried last.
+const COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Weight of the newest response time in a host's average.
+const LATENCY_SMOOTHING: f64 = 0.3;
+
+/// Mirrors of one repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MirrorList {
+    /// The repository's primary base URL.
+    pub base_url: String,
+    /// Alternate base URLs, in order of preference.
+    pub mirrors: Vec<String>,
+}
+
+impl MirrorList {
+    fn hosts(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.base_url).chain(&self.mirrors)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct HostHealth {
+    /// Smoothed response time in milliseconds.
+    latency_ms: Option<f64>,
+    failed_at: Option<Instant>,
+}
+
+/// Configured mirror lists and the health of each host.
+#[derive(Default)]
+pub struct Mirrors {
+    lists: Vec<MirrorList>,
+    health: HashMap<String, HostHealth>,
+}
+
+fn normalize(url: &str) -> String {
+    url.trim().trim_end_matches('/').to_string()
+}
+
+/// The part of `url` after `base_url`, if `url` is under it.
+fn path_under<'a>(base_url: &str, url: &'a str) -> Option<&'a str> {
+    url.strip_prefix(base_url)
+        .filter(|rest| rest.is_empty() || rest.starts_with(['/', '?', '#']))
+}
+
+/// The process-wide mirror lists (loaded from disk on first use).
+pub fn global() -> &'static Mutex<Mirrors> {
+    static MIRRORS: OnceLock<Mutex<Mirrors>> = OnceLock::new();
+    MIRRORS.get_or_init(|| {
+        Mutex::new(Mirrors::new(crate::config::load_json("mirrors.json")))
+    })
+}
+
+impl Mirrors {
+    /// Mirrors with `lists`, every host untried.
+    pub fn new(lists: Vec<MirrorList>) -> Self {
+        Self { lists, health: HashMap::new() }
+    }
+
+    /// Mirrors listed for a repository.
+    pub fn mirrors_of(&self, base_url: &str) -> Vec<String> {
+        let base_url = normalize(base_url);
+        self.lists
+            .iter()
+            .find(|l| l.base_url == base_url)
+            .map(|l| l.mirrors.clone())
+            .unwrap_or_default()
+    }
+
+    /// Replace a repository's mirrors (none removes its list) and save.
+    pub fn set_mirrors(&mut self, base_url: &str, mirrors: Vec<String>) -> Result<(), String> {
+        let base_url = normalize(base_url);
+        let mirrors: Vec<String> =
+            mirrors.iter().map(|m| normalize(m)).filter(|m| !m.is_empty() && *m != base_url).collect();
+        if let Some(bad) = mirrors.iter().find(|m| !m.starts_with("http://") && !m.starts_with("https://")) {
+            return Err(format!("Not an http(s) URL: {}", bad));
+        }
+        self.lists.retain(|l| l.base_url != base_url);
+        if !mirrors.is_empty() {
+            self.lists.push(MirrorList { base_url, mirrors });
+        }
+
+        crate::config::save_json("mirrors.json", &self.lists)
+    }
+
+    /// Where to request `url`, best host first, as (host, URL on that
+    /// host). Just `url` itself if its repository has no mirrors.
+    pub fn candidates(&self, url: &str, now: Instant) -> Vec<(String, String)> {
+        let found = self
+            .lists
+            .iter()
+            .find_map(|list| list.hosts().find_map(|host| Some((list, path_under(host, url)?))));
+        let Some((list, path)) = found else {
+            return vec![(String::new(), url.to_string())];
+        };
+
+        let mut hosts: Vec<(bool, f64, &String)> = list
+            .hosts()
+            .map(|host| {
+                let health = self.health.get(host).copied().unwrap_or_default();
+                let cooling = health.failed_at.is_some_and(|t| now.duration_since(t) < COOLDOWN);
+                (cooling, health.latency_ms.unwrap_or(f64::INFINITY), host)
+            })
+            .collect();
+        // Stable, so untried hosts keep the listed order
+        hosts.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        hosts.into_iter().map(|(_, _, host)| (host.clone(), format!("{}{}", host, path))).collect()
+    }
+
+    pub fn report_success(&mut self, host: &str, elapsed: Duration) {
+        let health = self.health.entry(host.to_string()).or_default();
+        let ms = elapsed.as_secs_f64() * 1000.0;
+        health.latency_ms = Some(health.latency_ms.map_or(ms, |avg| avg + LATENCY_SMOOTHING * (ms - avg)));
+        health.failed_at = None;
+    }
+
+    pub fn report_failure(&mut self, host: &str, now: Instant) {
+        self.health.entry(host.to_string()).or_default().failed_at = Some(now);
+    }
+
+    /// Health of a host for Settings: "120 ms", "failing" or "untried".
+    pub fn health_label(&self, host: &str) -> String {
+        match self.health.get(&normalize(host)) {
+            Some(HostHealth { failed_at: Some(_), .. }) => "failing".to_string(),
+            Some(HostHealth { latency_ms: Some(ms), .. }) => format!("{:.0} ms", ms),
+            _ => "untried".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates_follow_host_health() {
+        let mut mirrors = Mirrors {
+            lists: vec![MirrorList {
+                base_url: "https://a.example.com/lib".into(),
+                mirrors: vec!["https://b.example.com".into(), "http://[2001:db8::1]/lib".into()],
+            }],
+            health: HashMap::new(),
+        };
+        let now = Instant::now();
+        let urls = |m: &Mirrors, url: &str| -> Vec<String> {
+            m.candidates(url, now).into_iter().map(|(_, u)| u).collect()
+        };
+
+        // Untried hosts keep the listed order, with the path carried over
+        assert_eq!(
+            urls(&mirrors, "https://a.example.com/lib/Piano/index.json"),
+            [
+                "https://a.example.com/lib/Piano/index.json",
+                "https://b.example.com/Piano/index.json",
+                "http://[2001:db8::1]/lib/Piano/index.json",
+            ]
+        );
+        assert_eq!(urls(&mirrors, "https://other.example.com/x"), ["https://other.example.com/x"]);
+
+        // A failing primary goes last, a faster mirror first
+        mirrors.report_failure("https://a.example.com/lib", now);
+        mirrors.report_success("http://[2001:db8::1]/lib", Duration::from_millis(40));
+        mirrors.report_success("https://b.example.com", Duration::from_millis(200));
+        let order = urls(&mirrors, "https://b.example.com/index.json");
+        assert_eq!(order[0], "http://[2001:db8::1]/lib/index.json");
+        assert_eq!(order[2], "https://a.example.com/lib/index.json");
+        assert_eq!(mirrors.health_label("https://b.example.com/"), "200 ms");
+
+        // The cooldown ends
+        let later = now + COOLDOWN * 2;
+        assert_eq!(mirrors.candidates("https://b.example.com/x", later)[2].0, "https://a.example.com/lib");
+        mirrors.report_success("https://a.example.com/lib", Duration::from_millis(10));
+        assert_eq!(mirrors.candidates("https://b.example.com/x", later)[0].0, "https://a.example.com/lib");
+    }
+}
@@ -0,0 +1,271 @@
+//! Local HTTP server standing in for the library host in tests.
+//!
+//! Serves a map of paths to bodies on `127.0.0.1` from a background thread,
+//! with the behaviour the fetch layer depends on: an `ETag` per body and a
+//! 304 for a matching `If-None-Match`, 404 for unknown paths, and injected
+//! failures (a status for the next N requests to a path). [`MockLibrary::fixture`]
+//! starts one preloaded with a miniature songwalker-library.
+//!
+//! Plain `std::net` with one request per connection (`Connection: close`);
+//! enough for reqwest and no extra dependencies.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::preset::integrity::sha256_hex;
+
+/// Library slug, preset path and sample file of the fixture.
+pub const FIXTURE_LIBRARY: &str = "MockLib";
+pub const FIXTURE_PRESET: &str = "piano/preset.json";
+pub const FIXTURE_SAMPLE: &str = "piano/C4.wav";
+
+#[derive(Default)]
+struct State {
+    bodies: HashMap<String, Vec<u8>>,
+    /// Path → (status, remaining responses with it).
+    failures: HashMap<String, (u16, u32)>,
+    /// Path → requests received.
+    hits: HashMap<String, u32>,
+}
+
+/// A running mock library host; stops when dropped.
+pub struct MockLibrary {
+    addr: std::net::SocketAddr,
+    state: Arc<Mutex<State>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl MockLibrary {
+    /// Start an empty server on a free port.
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let addr = listener.local_addr().expect("mock server address");
+        let state = Arc::new(Mutex::new(State::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (thread_state, thread_stop) = (state.clone(), stop.clone());
+        std::thread::Builder::new()
+            .name("mock-library".into())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    if thread_stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        let state = thread_state.clone();
+                        std::thread::spawn(move || serve(stream, &state));
+                    }
+                }
+            })
+            .expect("spawn mock server");
+        Self { addr, state, stop }
+    }
+
+    /// Start a server holding a one-preset library: root index, library
+    /// index, a sampler preset pinned by sha256, and its WAV sample.
+    pub fn fixture() -> Self {
+        Self::fixture_as(FIXTURE_LIBRARY)
+    }
+
+    /// [`Self::fixture`] with the library under another slug (one the disk
+    /// cache hasn't seen, so loads have to ask the server).
+    pub fn fixture_as(library: &str) -> Self {
+        let server = Self::start();
+        let sample = fixture_wav();
+        server.set(
+            "index.json",
+            serde_json::json!({
+                "libraries": [{"name": library, "path": format!("{}/index.json", library)}]
+            })
+            .to_string(),
+        );
+        server.set(
+            &format!("{}/index.json", library),
+            serde_json::json!({
+                "name": library,
+                "presets": [{"name": "Mock Piano", "path": FIXTURE_PRESET, "category": "sampler"}]
+            })
+            .to_string(),
+        );
+        server.set(
+            &format!("{}/{}", library, FIXTURE_PRESET),
+            serde_json::json!({
+                "id": "mock-piano",
+                "name": "Mock Piano",
+                "category": "sampler",
+                "graph": {"type": "Sampler", "config": {"zones": [{
+                    "keyRange": {"low": 0, "high": 127},
+                    "pitch": {"rootNote": 60, "fineTuneCents": 0},
+                    "sampleRate": 44100,
+                    "audio": {"type": "external", "url": "C4.wav", "codec": "wav", "sha256": sha256_hex(&sample)}
+                }]}}
+            })
+            .to_string(),
+        );
+        server.set(&format!("{}/{}", library, FIXTURE_SAMPLE), sample);
+        server
+    }
+
+    /// Base URL, as passed to `PresetFetcher::new`.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Absolute URL of `path`.
+    pub fn url_for(&self, path: &str) -> String {
+        format!("{}/{}", self.url(), path)
+    }
+
+    /// Serve `body` at `path` (replacing any previous body, so its ETag changes).
+    pub fn set(&self, path: &str, body: impl Into<Vec<u8>>) {
+        self.state.lock().unwrap().bodies.insert(path.to_string(), body.into());
+    }
+
+    pub fn remove(&self, path: &str) {
+        self.state.lock().unwrap().bodies.remove(path);
+    }
+
+    /// Answer the next `times` requests for `path` with `status`.
+    pub fn fail(&self, path: &str, status: u16, times: u32) {
+        self.state.lock().unwrap().failures.insert(path.to_string(), (status, times));
+    }
+
+    /// Requests received for `path`.
+    pub fn hits(&self, path: &str) -> u32 {
+        self.state.lock().unwrap().hits.get(path).copied().unwrap_or(0)
+    }
+}
+
+impl Drop for MockLibrary {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Wake the accept loop so it sees the flag
+        let _ = TcpStream::connect_timeout(&self.addr, Duration::from_millis(100));
+    }
+}
+
+/// Run a future to completion on a fresh runtime.
+pub fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("test runtime")
+        .block_on(future)
+}
+
+/// Answer one request.
+fn serve(mut stream: TcpStream, state: &Mutex<State>) {
+    let mut reader = BufReader::new(match stream.try_clone() {
+        Ok(s) => s,
+        Err(_) => return,
+    });
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let path = parts.next().unwrap_or("/").trim_start_matches('/').to_string();
+    let mut if_none_match = None;
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if line.trim().is_empty() => break,
+            Ok(_) => {
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.trim().eq_ignore_ascii_case("if-none-match") {
+                        if_none_match = Some(value.trim().to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    let (status, body, etag) = {
+        let mut state = state.lock().unwrap();
+        *state.hits.entry(path.clone()).or_default() += 1;
+        let failure = state.failures.get_mut(&path).filter(|(_, remaining)| *remaining > 0);
+        if let Some((status, remaining)) = failure {
+            *remaining -= 1;
+            (*status, Vec::new(), None)
+        } else {
+            match state.bodies.get(&path) {
+                Some(body) => {
+                    let etag = format!("\"{}\"", &sha256_hex(body)[..16]);
+                    if if_none_match.as_deref() == Some(etag.as_str()) {
+                        (304, Vec::new(), Some(etag))
+                    } else {
+                        (200, body.clone(), Some(etag))
+                    }
+                }
+                None => (404, Vec::new(), None),
+            }
+        }
+    };
+
+    let mut head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    if let Some(etag) = etag {
+        head.push_str(&format!("ETag: {}\r\n", etag));
+    }
+    head.push_str("\r\n");
+    let _ = stream.write_all(head.as_bytes());
+    if method != "HEAD" {
+        let _ = stream.write_all(&body);
+    }
+    let _ = stream.flush();
+}
+
+/// A short 16-bit mono WAV (a quarter second of 261.6 Hz sine).
+fn fixture_wav() -> Vec<u8> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut cursor = std::io::Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut cursor, spec).expect("wav writer");
+    for i in 0..11025 {
+        let s = (std::f32::consts::TAU * 261.6 * i as f32 / 44100.0).sin();
+        let _ = writer.write_sample((s * 16000.0) as i16);
+    }
+    let _ = writer.finalize();
+    cursor.into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serves_fixture_with_etags_and_failures() {
+        let server = MockLibrary::fixture();
+        let index = server.url_for("index.json");
+        block_on(async {
+            let client = reqwest::Client::new();
+            let response = client.get(&index).send().await.unwrap();
+            assert_eq!(response.status(), 200);
+            let etag = response.headers()["etag"].to_str().unwrap().to_string();
+            let root: serde_json::Value = response.json().await.unwrap();
+            assert_eq!(root["libraries"][0]["name"], FIXTURE_LIBRARY);
+
+            let response = client.get(&index).header("If-None-Match", etag).send().await.unwrap();
+            assert_eq!(response.status(), 304);
+
+            server.fail("index.json", 503, 1);
+            assert_eq!(client.get(&index).send().await.unwrap().status(), 503);
+            assert_eq!(client.get(&index).send().await.unwrap().status(), 200);
+            let missing = client.get(server.url_for("missing.json")).send().await.unwrap();
+            assert_eq!(missing.status(), 404);
+        });
+        assert_eq!(server.hits("index.json"), 4);
+    }
+}
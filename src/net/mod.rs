@@ -0,0 +1,30 @@
+//! HTTP layer for library fetches made from this crate.
+//!
+//! Wraps a `reqwest` client (built from the user's proxy, CA certificate and
+//! timeout settings) with the policies the library host needs: conditional
+//! revalidation of cached documents (ETag / Last-Modified) so unchanged
+//! indexes cost a 304, bounded retries with backoff for transient failures,
+//! online/offline tracking, and an offline fallback to the last good copy.
+//! Stored copies are written atomically and checksummed; a copy that fails
+//! its checksum is evicted and fetched again. Requests to private libraries
+//! carry the credentials configured for them (see `auth`), and preset loads
+//! fail over to a library's mirrors (see `mirrors`).
+
+pub mod atomic;
+pub mod auth;
+pub mod client;
+pub mod connectivity;
+pub mod error;
+pub mod mirrors;
+#[cfg(test)]
+pub(crate) mod mock;
+pub mod retry;
+pub mod settings;
+pub mod validators;
+
+pub use client::{Fetched, Freshness, HttpClient};
+pub use connectivity::Connectivity;
+pub use error::FetchError;
+pub use retry::RetryPolicy;
+pub use settings::NetworkSettings;
+pub use validators::{ValidatorStore, Validators};
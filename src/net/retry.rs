@@ -0,0 +1,105 @@
+//! Bounded retries with exponential backoff and jitter.
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+/// Retry policy for transient network failures.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first (1 = no retries).
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+    /// Upper bound for any single delay.
+    pub max_delay: Duration,
+    /// Random spread applied to each delay (0.0–1.0, fraction of the delay).
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+            jitter: 0.25,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// No retries (used for connectivity probes).
+    pub const NONE: RetryPolicy = RetryPolicy {
+        max_attempts: 1,
+        base_delay: Duration::ZERO,
+        max_delay: Duration::ZERO,
+        jitter: 0.0,
+    };
+
+    /// Delay before retry number `retry` (0-based), with `random` in 0..1
+    /// spreading it by ±`jitter`.
+    pub fn delay(&self, retry: u32, random: f64) -> Duration {
+        let exp = self.base_delay.as_secs_f64() * 2f64.powi(retry.min(16) as i32);
+        let capped = exp.min(self.max_delay.as_secs_f64());
+        let spread = 1.0 + self.jitter * (random.clamp(0.0, 1.0) * 2.0 - 1.0);
+        Duration::from_secs_f64((capped * spread).max(0.0))
+    }
+}
+
+/// Whether a response status is worth retrying (server overload/outage).
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Whether a transport error is transient (and means we may be offline).
+pub fn is_transient_error(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout() || err.is_request()
+}
+
+/// Cheap pseudo-random value in 0..1 for jitter.
+pub fn jitter_random() -> f64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    let mut x = nanos ^ 0x9E37_79B9;
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    x as f64 / u32::MAX as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_grows_exponentially() {
+        let p = RetryPolicy { jitter: 0.0, ..RetryPolicy::default() };
+        assert_eq!(p.delay(0, 0.5), Duration::from_millis(500));
+        assert_eq!(p.delay(1, 0.5), Duration::from_secs(1));
+        assert_eq!(p.delay(2, 0.5), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_delay_capped() {
+        let p = RetryPolicy { jitter: 0.0, ..RetryPolicy::default() };
+        assert_eq!(p.delay(10, 0.5), p.max_delay);
+    }
+
+    #[test]
+    fn test_jitter_bounds() {
+        let p = RetryPolicy::default();
+        let base = p.delay(0, 0.5).as_secs_f64();
+        assert!(p.delay(0, 0.0).as_secs_f64() >= base * 0.75 - 1e-9);
+        assert!(p.delay(0, 1.0).as_secs_f64() <= base * 1.25 + 1e-9);
+    }
+
+    #[test]
+    fn test_retryable_status() {
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+    }
+}
@@ -0,0 +1,149 @@
+//! User network settings (proxy, custom CA, timeout, download warnings,
+//! library refresh schedule), stored per machine in `network.json` under the user config directory.
+
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Default request timeout in seconds.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+/// Default size above which a preset download asks for confirmation.
+pub const DEFAULT_WARN_DOWNLOAD_MB: u64 = 100;
+/// Default size of the sample cache before downloads ask for confirmation.
+pub const DEFAULT_CACHE_LIMIT_MB: u64 = 4096;
+
+/// Settings applied when building the HTTP client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkSettings {
+    /// Proxy for `http://` URLs (e.g. "http://proxy.local:3128").
+    pub http_proxy: String,
+    /// Proxy for `https://` URLs.
+    pub https_proxy: String,
+    /// PEM file with an extra trusted root certificate.
+    pub ca_cert_path: String,
+    /// Per-request timeout.
+    pub timeout_secs: u64,
+    /// Ask before loading a preset whose download is larger (0 = never).
+    pub warn_download_mb: u64,
+    /// Ask before a download would grow the cache past this (0 = no limit).
+    pub cache_limit_mb: u64,
+    /// Re-check library indexes this often while the editor is open
+    /// (0 = only at startup).
+    pub refresh_hours: u64,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            http_proxy: String::new(),
+            https_proxy: String::new(),
+            ca_cert_path: String::new(),
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            warn_download_mb: DEFAULT_WARN_DOWNLOAD_MB,
+            cache_limit_mb: DEFAULT_CACHE_LIMIT_MB,
+            refresh_hours: 0,
+        }
+    }
+}
+
+impl NetworkSettings {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.clamp(1, 600))
+    }
+
+    /// Interval of scheduled library refreshes, if any.
+    pub fn refresh_interval(&self) -> Option<Duration> {
+        (self.refresh_hours > 0).then(|| Duration::from_secs(self.refresh_hours * 3600))
+    }
+
+    /// Build a `reqwest` client with these settings.
+    pub fn build_client(&self, user_agent: &str) -> Result<reqwest::Client, String> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(user_agent)
+            .timeout(self.timeout());
+
+        let http_proxy = self.http_proxy.trim();
+        if !http_proxy.is_empty() {
+            let proxy = reqwest::Proxy::http(http_proxy)
+                .map_err(|e| format!("Invalid HTTP proxy: {}", e))?;
+            builder = builder.proxy(proxy);
+        }
+        let https_proxy = self.https_proxy.trim();
+        if !https_proxy.is_empty() {
+            let proxy = reqwest::Proxy::https(https_proxy)
+                .map_err(|e| format!("Invalid HTTPS proxy: {}", e))?;
+            builder = builder.proxy(proxy);
+        }
+
+        let ca_path = self.ca_cert_path.trim();
+        if !ca_path.is_empty() {
+            let pem = std::fs::read(ca_path)
+                .map_err(|e| format!("Can't read CA certificate {}: {}", ca_path, e))?;
+            let cert = reqwest::Certificate::from_pem(&pem)
+                .map_err(|e| format!("Invalid CA certificate: {}", e))?;
+            builder = builder.add_root_certificate(cert);
+        }
+
+        builder
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))
+    }
+}
+
+fn store() -> &'static RwLock<NetworkSettings> {
+    static SETTINGS: OnceLock<RwLock<NetworkSettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        RwLock::new(crate::config::load_json("network.json"))
+    })
+}
+
+/// Settings in effect (loaded from disk on first use).
+pub fn current() -> NetworkSettings {
+    store().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// Validate, apply and persist new settings. Clients built afterwards use them.
+pub fn update(settings: NetworkSettings) -> Result<(), String> {
+    settings.build_client(super::client::USER_AGENT)?;
+    if let Ok(mut s) = store().write() {
+        *s = settings.clone();
+    }
+    crate::config::save_json("network.json", &settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_build() {
+        assert!(NetworkSettings::default().build_client("test").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_proxy_rejected() {
+        let s = NetworkSettings {
+            https_proxy: "::not a url::".into(),
+            ..Default::default()
+        };
+        assert!(s.build_client("test").unwrap_err().contains("HTTPS proxy"));
+    }
+
+    #[test]
+    fn test_missing_ca_rejected() {
+        let s = NetworkSettings {
+            ca_cert_path: "/nonexistent/ca.pem".into(),
+            ..Default::default()
+        };
+        assert!(s.build_client("test").is_err());
+    }
+
+    #[test]
+    fn test_old_json_gets_defaults() {
+        let s: NetworkSettings = serde_json::from_str(r#"{"http_proxy":"http://p:1"}"#).unwrap();
+        assert_eq!(s.timeout_secs, DEFAULT_TIMEOUT_SECS);
+        assert_eq!(s.http_proxy, "http://p:1");
+    }
+}
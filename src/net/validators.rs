@@ -0,0 +1,263 @@
+//! Persistent HTTP cache validators (ETag / Last-Modified) and the bodies
+//! they validate.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use reqwest::header::{HeaderMap, ETAG, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+
+use super::atomic::write_atomic;
+use crate::preset::integrity::sha256_hex;
+
+/// Validators returned by the server for one URL.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let get = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            etag: get(ETAG),
+            last_modified: get(LAST_MODIFIED),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Stored state for one URL.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Entry {
+    #[serde(flatten)]
+    validators: Validators,
+    /// sha256 of the stored body; a body that doesn't match is corrupt.
+    #[serde(default)]
+    body_sha256: Option<String>,
+}
+
+/// On-disk store: `validators.json` plus one body file per URL.
+///
+/// Files are written atomically and bodies are checksummed, so a crash
+/// mid-write can't leave a truncated copy that is served forever: a body
+/// that fails its checksum is evicted along with its validators, and the
+/// next request fetches it again unconditionally.
+///
+/// With no directory (cache location unavailable) everything is kept in
+/// memory for the session only.
+pub struct ValidatorStore {
+    dir: Option<PathBuf>,
+    entries: Mutex<HashMap<String, Entry>>,
+    /// Bodies kept in memory when there is no directory.
+    memory_bodies: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl ValidatorStore {
+    /// Store in the user cache directory (`…/songwalker-vsti/http`).
+    pub fn open_default() -> Self {
+        let dir = directories::ProjectDirs::from("org", "SongWalker", "songwalker-vsti")
+            .map(|d| d.cache_dir().join("http"));
+        match dir {
+            Some(dir) => Self::open(dir),
+            None => Self::in_memory(),
+        }
+    }
+
+    /// Store in `dir`, loading any validators saved there.
+    pub fn open(dir: PathBuf) -> Self {
+        let path = dir.join("validators.json");
+        let entries = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                // Unreadable index: start over rather than fail every request
+                nih_plug::debug::nih_log!(
+                    "[HttpCache] Discarding corrupt {}: {}",
+                    path.display(),
+                    e
+                );
+                let _ = std::fs::remove_file(&path);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self {
+            dir: Some(dir),
+            entries: Mutex::new(entries),
+            memory_bodies: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Session-only store.
+    pub fn in_memory() -> Self {
+        Self {
+            dir: None,
+            entries: Mutex::new(HashMap::new()),
+            memory_bodies: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Validators last seen for `url`.
+    pub fn get(&self, url: &str) -> Option<Validators> {
+        self.entries.lock().ok()?.get(url).map(|e| e.validators.clone())
+    }
+
+    /// Body last downloaded for `url`, or `None` if it is missing or fails
+    /// its checksum (in which case it is evicted).
+    pub fn body(&self, url: &str) -> Option<Vec<u8>> {
+        let Some(dir) = &self.dir else {
+            return self.memory_bodies.lock().ok()?.get(url).cloned();
+        };
+        let body = std::fs::read(dir.join(body_file_name(url))).ok()?;
+        let expected = self.entries.lock().ok()?.get(url)?.body_sha256.clone();
+        match expected {
+            // Stored before checksums were recorded
+            None => Some(body),
+            Some(hash) if hash == sha256_hex(&body) => Some(body),
+            Some(_) => {
+                nih_plug::debug::nih_log!("[HttpCache] Evicting corrupt copy of {}", url);
+                self.evict(url);
+                None
+            }
+        }
+    }
+
+    /// Forget `url`: its validators and stored body.
+    pub fn evict(&self, url: &str) {
+        let snapshot = {
+            let Ok(mut entries) = self.entries.lock() else { return };
+            entries.remove(url);
+            serde_json::to_vec_pretty(&*entries).ok()
+        };
+        match &self.dir {
+            Some(dir) => {
+                let _ = std::fs::remove_file(dir.join(body_file_name(url)));
+                if let Some(json) = snapshot {
+                    let _ = write_atomic(&dir.join("validators.json"), &json);
+                }
+            }
+            None => {
+                if let Ok(mut bodies) = self.memory_bodies.lock() {
+                    bodies.remove(url);
+                }
+            }
+        }
+    }
+
+    /// Record a fresh response.
+    pub fn store(&self, url: &str, validators: Validators, body: &[u8]) {
+        let snapshot = {
+            let Ok(mut entries) = self.entries.lock() else { return };
+            let entry = Entry {
+                validators,
+                body_sha256: Some(sha256_hex(body)),
+            };
+            entries.insert(url.to_string(), entry);
+            serde_json::to_vec_pretty(&*entries).ok()
+        };
+
+        match &self.dir {
+            Some(dir) => {
+                if std::fs::create_dir_all(dir).is_err() {
+                    return;
+                }
+                // Body first: a crash before the index is updated leaves an
+                // entry whose checksum doesn't match, which is evicted on read
+                if write_atomic(&dir.join(body_file_name(url)), body).is_err() {
+                    return;
+                }
+                if let Some(json) = snapshot {
+                    let _ = write_atomic(&dir.join("validators.json"), &json);
+                }
+            }
+            None => {
+                if let Ok(mut bodies) = self.memory_bodies.lock() {
+                    bodies.insert(url.to_string(), body.to_vec());
+                }
+            }
+        }
+    }
+}
+
+/// Body file name for a URL (hex sha256, so any URL is a valid file name).
+fn body_file_name(url: &str) -> String {
+    format!("{}.body", sha256_hex(url.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sw-validators-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_store_roundtrip_on_disk() {
+        let dir = temp_dir("disk");
+        let validators = Validators { etag: Some("\"abc\"".into()), last_modified: None };
+        ValidatorStore::open(dir.clone()).store("https://x/index.json", validators.clone(), b"{}");
+
+        let reopened = ValidatorStore::open(dir.clone());
+        assert_eq!(reopened.get("https://x/index.json"), Some(validators));
+        assert_eq!(reopened.body("https://x/index.json").as_deref(), Some(&b"{}"[..]));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_corrupt_body_is_evicted() {
+        let dir = temp_dir("corrupt");
+        let validators = Validators { etag: Some("\"abc\"".into()), last_modified: None };
+        let store = ValidatorStore::open(dir.clone());
+        store.store("https://x/a.mp3", validators, b"complete file");
+        // Simulate a torn write
+        std::fs::write(dir.join(body_file_name("https://x/a.mp3")), b"compl").unwrap();
+
+        assert!(store.body("https://x/a.mp3").is_none());
+        assert!(store.get("https://x/a.mp3").is_none(), "next fetch is unconditional");
+        assert!(ValidatorStore::open(dir.clone()).get("https://x/a.mp3").is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_corrupt_index_is_discarded() {
+        let dir = temp_dir("index");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("validators.json"), b"{\"https://x\": {\"et").unwrap();
+        let store = ValidatorStore::open(dir.clone());
+        assert!(store.get("https://x").is_none());
+        store.store("https://x", Validators::default(), b"ok");
+        assert_eq!(store.body("https://x").as_deref(), Some(&b"ok"[..]));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_in_memory_store() {
+        let store = ValidatorStore::in_memory();
+        assert!(store.get("u").is_none());
+        store.store("u", Validators::default(), b"body");
+        assert_eq!(store.body("u").as_deref(), Some(&b"body"[..]));
+    }
+
+    #[test]
+    fn test_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, "\"v1\"".parse().unwrap());
+        let v = Validators::from_headers(&headers);
+        assert_eq!(v.etag.as_deref(), Some("\"v1\""));
+        assert!(v.last_modified.is_none());
+        assert!(!v.is_empty());
+        assert!(Validators::default().is_empty());
+    }
+}
@@ -3,10 +3,30 @@ use std::sync::Arc;
 use nih_plug::prelude::*;
 use nih_plug_egui::EguiState;
 
+use crate::slots::MACRO_COUNT;
+
+/// Pan display: "C", "30L", "75R".
+fn v2s_pan() -> Arc<dyn Fn(f32) -> String + Send + Sync> {
+    Arc::new(|v| {
+        if v.abs() < 0.01 {
+            "C".to_string()
+        } else if v < 0.0 {
+            format!("{:.0}L", -v * 100.0)
+        } else {
+            format!("{:.0}R", v * 100.0)
+        }
+    })
+}
+
+/// Number of slots with host-automatable mix parameters. Slots past this
+/// are mixed from the rack only.
+pub const AUTOMATABLE_SLOTS: usize = 16;
+
 /// Top-level plugin parameters exposed to the DAW for automation.
 ///
-/// Per-slot parameters are managed dynamically by the slot manager;
-/// these are the global controls.
+/// Besides the global controls there is a fixed bank of mix parameters for
+/// the first [`AUTOMATABLE_SLOTS`] slots, kept in sync with their
+/// `SlotConfig` so the host timeline can automate the rack.
 #[derive(Params)]
 pub struct SongWalkerParams {
     /// Editor window size/scale state (persisted across DAW sessions).
@@ -21,6 +41,22 @@ pub struct SongWalkerParams {
     #[id = "master_pan"]
     pub master_pan: FloatParam,
 
+    /// Fold the master output to mono (for checking mixes).
+    #[id = "master_mono"]
+    pub mono: BoolParam,
+
+    /// Swap the left and right master channels.
+    #[id = "master_swap"]
+    pub swap_channels: BoolParam,
+
+    /// Invert the polarity of the left master channel.
+    #[id = "invert_l"]
+    pub invert_left: BoolParam,
+
+    /// Invert the polarity of the right master channel.
+    #[id = "invert_r"]
+    pub invert_right: BoolParam,
+
     /// Global max polyphony across all slots.
     #[id = "max_voices"]
     pub max_voices: IntParam,
@@ -28,6 +64,18 @@ pub struct SongWalkerParams {
     /// Pitch bend range in semitones.
     #[id = "bend_range"]
     pub pitch_bend_range: IntParam,
+
+    /// Factory program; choosing one rebuilds the rack (see `programs`).
+    #[id = "program"]
+    pub program: EnumParam<crate::programs::FactoryProgram>,
+
+    /// Mix parameters of the first slots ("Slot 1" … "Slot 16" groups).
+    #[nested(array, group = "Slot")]
+    pub slots: [SlotMixParams; AUTOMATABLE_SLOTS],
+
+    /// Macro knobs ("Macro 1" … "Macro 8" groups), see `slots::macros`.
+    #[nested(array, group = "Macro")]
+    pub macros: [MacroParams; MACRO_COUNT],
 }
 
 impl Default for SongWalkerParams {
@@ -57,15 +105,12 @@ impl Default for SongWalkerParams {
                 },
             )
             .with_unit("")
-            .with_value_to_string(Arc::new(|v| {
-                if v.abs() < 0.01 {
-                    "C".to_string()
-                } else if v < 0.0 {
-                    format!("{:.0}L", -v * 100.0)
-                } else {
-                    format!("{:.0}R", v * 100.0)
-                }
-            })),
+            .with_value_to_string(v2s_pan()),
+
+            mono: BoolParam::new("Mono", false),
+            swap_channels: BoolParam::new("Swap L/R", false),
+            invert_left: BoolParam::new("Invert Left", false),
+            invert_right: BoolParam::new("Invert Right", false),
 
             max_voices: IntParam::new("Max Voices", 256, IntRange::Linear { min: 8, max: 1024 }),
 
@@ -75,6 +120,134 @@ impl Default for SongWalkerParams {
                 IntRange::Linear { min: 1, max: 48 },
             )
             .with_unit(" st"),
+
+            program: EnumParam::new("Program", crate::programs::FactoryProgram::Custom),
+
+            slots: Default::default(),
+            macros: Default::default(),
+        }
+    }
+}
+
+impl SongWalkerParams {
+    pub fn master_output(&self) -> MasterOutput {
+        MasterOutput {
+            mono: self.mono.value(),
+            swap: self.swap_channels.value(),
+            invert_left: self.invert_left.value(),
+            invert_right: self.invert_right.value(),
+        }
+    }
+
+    /// Positions (0–1) of the macro knobs.
+    pub fn macro_values(&self) -> [f32; MACRO_COUNT] {
+        std::array::from_fn(|i| self.macros[i].value.value())
+    }
+}
+
+/// Master output utilities, applied after master volume and pan in
+/// `render_and_mix`: mono fold-down, then channel swap, then per-channel
+/// polarity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MasterOutput {
+    pub mono: bool,
+    pub swap: bool,
+    pub invert_left: bool,
+    pub invert_right: bool,
+}
+
+impl MasterOutput {
+    /// Whether the output passes through unchanged.
+    pub fn is_bypassed(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A slot's mix settings as plain values, compared to spot changes on
+/// either side (host automation or the rack).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlotMix {
+    pub volume: f32,
+    pub pan: f32,
+    pub muted: bool,
+    pub solo: bool,
+}
+
+impl SlotMix {
+    pub fn from_config(config: &crate::state::SlotConfig) -> Self {
+        Self {
+            volume: config.volume,
+            pan: config.pan,
+            muted: config.muted,
+            solo: config.solo,
+        }
+    }
+
+    pub fn apply_to_config(&self, config: &mut crate::state::SlotConfig) {
+        config.volume = self.volume;
+        config.pan = self.pan;
+        config.muted = self.muted;
+        config.solo = self.solo;
+    }
+}
+
+/// Host-automatable mix controls of one slot. Same ranges as the rack:
+/// linear volume 0–1.5 (shown in dB), pan −1..+1.
+#[derive(Params)]
+pub struct SlotMixParams {
+    #[id = "slot_vol"]
+    pub volume: FloatParam,
+
+    #[id = "slot_pan"]
+    pub pan: FloatParam,
+
+    #[id = "slot_mute"]
+    pub mute: BoolParam,
+
+    #[id = "slot_solo"]
+    pub solo: BoolParam,
+}
+
+impl Default for SlotMixParams {
+    fn default() -> Self {
+        Self {
+            volume: FloatParam::new("Volume", 0.8, FloatRange::Linear { min: 0.0, max: 1.5 })
+                .with_unit(" dB")
+                .with_value_to_string(formatters::v2s_f32_gain_to_db(2))
+                .with_string_to_value(formatters::s2v_f32_gain_to_db()),
+            pan: FloatParam::new("Pan", 0.0, FloatRange::Linear { min: -1.0, max: 1.0 })
+                .with_value_to_string(v2s_pan()),
+            mute: BoolParam::new("Mute", false),
+            solo: BoolParam::new("Solo", false),
+        }
+    }
+}
+
+impl SlotMixParams {
+    pub fn mix(&self) -> SlotMix {
+        SlotMix {
+            volume: self.volume.value(),
+            pan: self.pan.value(),
+            muted: self.mute.value(),
+            solo: self.solo.value(),
+        }
+    }
+}
+
+/// One host-automatable macro knob (0–1).
+#[derive(Params)]
+pub struct MacroParams {
+    #[id = "macro"]
+    pub value: FloatParam,
+}
+
+impl Default for MacroParams {
+    fn default() -> Self {
+        Self {
+            value: FloatParam::new("Macro", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+                .with_unit("%")
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_string_to_value(formatters::s2v_f32_percentage()),
         }
     }
 }
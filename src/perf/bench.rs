@@ -0,0 +1,353 @@
+//! Benchmarks for the audio path.
+//!
+//! Run from the standalone binary (build with `--release` for meaningful
+//! numbers):
+//!
+//! ```text
+//! songwalker-standalone --bench [iterations]
+//! ```
+//!
+//! Each benchmark reports the average time to process one 512-sample block
+//! and its share of the real-time budget at 44.1 kHz.
+
+use std::fmt;
+use std::hint::black_box;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU32;
+use std::time::{Duration, Instant};
+
+use nih_plug::prelude::NoteEvent;
+use songwalker_core::preset::instance::{LoadedZone, PresetInstance};
+use songwalker_core::preset::{
+    AudioCodec, AudioReference, KeyRange, PresetCategory, PresetDescriptor, PresetNode,
+    SampleZone, SamplerConfig, ZonePitch,
+};
+
+use super::denormal::ScopedFtz;
+use super::simd;
+use crate::audio::{AudioEngine, render_and_mix};
+use crate::editor::visualizer::VisualizerState;
+use crate::monitor::EngineMonitor;
+use crate::slots::slot::{EnvelopeParams, Voice, next_envelope_segment};
+use crate::slots::{MAX_SLOTS, Slot, SlotManager};
+use crate::transport::TransportState;
+
+/// Samples per benchmarked block.
+pub const BLOCK: usize = 512;
+const SAMPLE_RATE: f32 = 44100.0;
+/// Default iteration count for `--bench`.
+pub const DEFAULT_ITERATIONS: u32 = 2000;
+
+/// Timing of one benchmark.
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    pub name: String,
+    /// Average time per `BLOCK`-sample block.
+    pub per_block: Duration,
+}
+
+impl BenchResult {
+    /// Fraction of the real-time budget for one block at 44.1 kHz.
+    pub fn load(&self) -> f64 {
+        self.per_block.as_secs_f64() / (BLOCK as f64 / SAMPLE_RATE as f64)
+    }
+}
+
+impl fmt::Display for BenchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<36} {:>12.2?} / block  {:>7.2}% of real time",
+            self.name,
+            self.per_block,
+            self.load() * 100.0
+        )
+    }
+}
+
+/// Run `f` `iterations` times (after a short warm-up) and time it.
+fn measure(name: impl Into<String>, iterations: u32, mut f: impl FnMut()) -> BenchResult {
+    let iterations = iterations.max(1);
+    for _ in 0..(iterations / 10).max(1) {
+        f();
+    }
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    BenchResult {
+        name: name.into(),
+        per_block: start.elapsed() / iterations,
+    }
+}
+
+/// Run every benchmark. All but the denormal comparison run with
+/// flush-to-zero enabled, as on the audio thread.
+pub fn run_all(iterations: u32) -> Vec<BenchResult> {
+    let mut results = Vec::new();
+    results.extend(bench_denormal_tail(iterations));
+
+    let _ftz = ScopedFtz::enable();
+    for voices in [1, 16, 64] {
+        results.push(bench_sine_voices(voices, iterations));
+    }
+    for voices in [16, 64] {
+        results.push(bench_sampler_voices(voices, iterations));
+    }
+    results.push(bench_envelopes(64, iterations));
+    results.push(bench_render_and_mix(false, iterations));
+    results.push(bench_render_and_mix(true, iterations));
+    results.extend(bench_mix_stereo(iterations));
+    results
+}
+
+/// Entry point for `--bench`: print every result.
+pub fn run_cli(iterations: u32) {
+    println!(
+        "SongWalker audio benchmarks ({} iterations, {}-sample blocks)",
+        iterations, BLOCK
+    );
+    for result in run_all(iterations) {
+        println!("{}", result);
+    }
+}
+
+fn note_on(note: u8) -> NoteEvent<()> {
+    NoteEvent::NoteOn {
+        timing: 0,
+        voice_id: None,
+        channel: 0,
+        note,
+        velocity: 0.8,
+    }
+}
+
+/// Hold `voices` notes on a slot; the sustain stage keeps them sounding.
+fn hold_notes(slot: &mut Slot, voices: usize, transport: &TransportState) {
+    for i in 0..voices {
+        slot.handle_midi_event(&note_on(36 + (i % 64) as u8), transport);
+    }
+}
+
+/// A one-zone mono sampler preset with a 10 s sine sample, so voices don't
+/// run off the end during a run.
+pub(crate) fn sine_sample_preset() -> Arc<PresetInstance> {
+    let sample_rate = SAMPLE_RATE as u32;
+    let pcm: Vec<f32> = (0..sample_rate as usize * 10)
+        .map(|i| (i as f32 / SAMPLE_RATE * 440.0 * std::f32::consts::TAU).sin())
+        .collect();
+    let zone = SampleZone {
+        key_range: KeyRange { low: 0, high: 127 },
+        velocity_range: None,
+        pitch: ZonePitch {
+            root_note: 69,
+            fine_tune_cents: 0.0,
+        },
+        sample_rate,
+        r#loop: None,
+        audio: AudioReference::External {
+            url: "bench.mp3".into(),
+            codec: AudioCodec::Mp3,
+            sha256: None,
+        },
+    };
+    Arc::new(PresetInstance {
+        descriptor: PresetDescriptor {
+            format: None,
+            version: None,
+            id: "bench".into(),
+            name: "Bench Sine".into(),
+            category: PresetCategory::Sampler,
+            tags: vec![],
+            metadata: None,
+            tuning: None,
+            graph: PresetNode::Sampler {
+                config: SamplerConfig {
+                    zones: vec![zone.clone()],
+                    is_drum_kit: false,
+                    envelope: None,
+                },
+            },
+        },
+        zones: vec![LoadedZone {
+            zone,
+            pcm_data: Arc::from(pcm),
+            channels: 1,
+            sample_rate,
+        }],
+    })
+}
+
+fn render_slot(slot: &mut Slot, left: &mut [f32], right: &mut [f32], transport: &TransportState) {
+    left.fill(0.0);
+    right.fill(0.0);
+    slot.render(left, right, BLOCK, SAMPLE_RATE, transport);
+    black_box((&left[0], &right[0]));
+}
+
+/// Oscillator voices (no preset loaded).
+pub fn bench_sine_voices(voices: usize, iterations: u32) -> BenchResult {
+    let transport = TransportState::default();
+    let mut slot = Slot::new(0);
+    slot.initialize(SAMPLE_RATE);
+    hold_notes(&mut slot, voices, &transport);
+    let (mut left, mut right) = (vec![0.0; BLOCK], vec![0.0; BLOCK]);
+    measure(format!("sine voices ×{}", voices), iterations, || {
+        render_slot(&mut slot, &mut left, &mut right, &transport)
+    })
+}
+
+/// Sampler voices at varied pitches, exercising interpolation.
+pub fn bench_sampler_voices(voices: usize, iterations: u32) -> BenchResult {
+    let transport = TransportState::default();
+    let mut slot = Slot::new(0);
+    slot.initialize(SAMPLE_RATE);
+    slot.preset_state_mut()
+        .load_preset(Arc::new("bench/sine".to_string()), sine_sample_preset());
+    hold_notes(&mut slot, voices, &transport);
+    let (mut left, mut right) = (vec![0.0; BLOCK], vec![0.0; BLOCK]);
+    measure(format!("sampler voices ×{}", voices), iterations, || {
+        render_slot(&mut slot, &mut left, &mut right, &transport)
+    })
+}
+
+/// Envelope advancement alone (segment per block), cycling voices through
+/// every stage.
+pub fn bench_envelopes(voices: usize, iterations: u32) -> BenchResult {
+    let adsr = EnvelopeParams::default();
+    let mut pool: Vec<Voice> = (0..voices)
+        .map(|i| Voice {
+            active: true,
+            env_stage: (i % 4) as u8,
+            env_gain: 0.8,
+            ..Voice::default()
+        })
+        .collect();
+    measure(format!("envelopes ×{}", voices), iterations, || {
+        let mut sum = 0.0;
+        for voice in &mut pool {
+            let mut done = 0;
+            while done < BLOCK {
+                let segment = next_envelope_segment(voice, &adsr, SAMPLE_RATE, BLOCK - done);
+                sum += segment.gain;
+                done += segment.len;
+            }
+            if voice.env_stage >= 4 {
+                voice.env_stage = 0;
+                voice.env_samples = 0;
+            }
+        }
+        black_box(sum);
+    })
+}
+
+/// The full rack: every slot holding 8 sampler notes, rendered serially or
+/// on the worker pool.
+pub fn bench_render_and_mix(parallel: bool, iterations: u32) -> BenchResult {
+    let mut slot_manager = SlotManager::new_empty();
+    slot_manager.initialize(SAMPLE_RATE);
+    slot_manager.allocate_all();
+    let mut engine = AudioEngine::new();
+    engine.initialize(SAMPLE_RATE, BLOCK);
+    engine.set_parallel_render(parallel);
+    let transport = TransportState::default();
+    let vis = Arc::new(VisualizerState::new(1024));
+    let voice_count = Arc::new(AtomicU32::new(0));
+    let monitor = Arc::new(EngineMonitor::new());
+
+    let preset = sine_sample_preset();
+    for slot in slot_manager.slots_mut().iter_mut() {
+        slot.preset_state_mut()
+            .load_preset(Arc::new("bench/sine".to_string()), preset.clone());
+        hold_notes(slot, 8, &transport);
+    }
+
+    measure(
+        format!(
+            "render_and_mix ({} slots ×8, {})",
+            MAX_SLOTS,
+            if parallel { "parallel" } else { "serial" }
+        ),
+        iterations,
+        || {
+            render_and_mix(
+                BLOCK,
+                &mut engine,
+                &mut slot_manager,
+                &transport,
+                1.0,
+                0.0,
+                &vis,
+                &voice_count,
+                &monitor,
+            );
+        },
+    )
+}
+
+/// The slot mixer, scalar against SIMD.
+pub fn bench_mix_stereo(iterations: u32) -> [BenchResult; 2] {
+    let in_l = vec![0.25_f32; BLOCK];
+    let in_r = vec![-0.25_f32; BLOCK];
+    let (mut out_l, mut out_r) = (vec![0.0_f32; BLOCK], vec![0.0_f32; BLOCK]);
+    let m = [[0.6, 0.1], [0.1, 0.6]];
+
+    let scalar = measure("mix_stereo (scalar)", iterations, || {
+        simd::mix_stereo_scalar(
+            &mut out_l,
+            &mut out_r,
+            black_box(&in_l),
+            black_box(&in_r),
+            m,
+            BLOCK,
+        );
+    });
+    let vector = measure("mix_stereo (simd)", iterations, || {
+        simd::mix_stereo(
+            &mut out_l,
+            &mut out_r,
+            black_box(&in_l),
+            black_box(&in_r),
+            m,
+            BLOCK,
+        );
+    });
+    [scalar, vector]
+}
+
+/// Gain on a tail deep in denormal range, without and with flush-to-zero.
+pub fn bench_denormal_tail(iterations: u32) -> [BenchResult; 2] {
+    let mut buf = vec![0.0_f32; BLOCK];
+    let decay = |buf: &mut [f32]| {
+        buf.fill(1.0e-39);
+        for _ in 0..16 {
+            simd::apply_gain(buf, black_box(0.999), BLOCK);
+        }
+    };
+
+    let slow = measure("denormal tail (no FTZ)", iterations, || decay(&mut buf));
+    let _ftz = ScopedFtz::enable();
+    let fast = measure("denormal tail (FTZ)", iterations, || decay(&mut buf));
+    [slow, fast]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_benchmarks_run() {
+        let results = run_all(1);
+        assert_eq!(results.len(), 12);
+        assert!(results.iter().all(|r| !r.name.is_empty()));
+    }
+
+    #[test]
+    fn test_load_is_fraction_of_block_time() {
+        let result = BenchResult {
+            name: "x".into(),
+            per_block: Duration::from_secs_f64(BLOCK as f64 / SAMPLE_RATE as f64 / 2.0),
+        };
+        assert!((result.load() - 0.5).abs() < 1e-9);
+    }
+}
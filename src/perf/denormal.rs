@@ -0,0 +1,110 @@
+//! Flush-to-zero for the audio threads.
+//!
+//! Decaying tails (release envelopes, the declick tail) slide into denormal
+//! range, where x86 float ops can be 100× slower. Setting FTZ/DAZ (x86) or
+//! FZ (AArch64) for the duration of a process call makes them plain zeros.
+
+/// Enables flush-to-zero until dropped, then restores the previous mode.
+///
+/// Create one at the top of every audio callback; the mode is per thread.
+pub struct ScopedFtz {
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    previous: u64,
+}
+
+#[cfg(target_arch = "x86_64")]
+mod arch {
+    /// MXCSR flush-to-zero (bit 15) and denormals-are-zero (bit 6).
+    pub const FTZ_BITS: u64 = (1 << 15) | (1 << 6);
+
+    pub fn get() -> u64 {
+        let mut csr: u32 = 0;
+        // SAFETY: stmxcsr only writes the 4 bytes behind the pointer
+        unsafe {
+            std::arch::asm!("stmxcsr [{}]", in(reg) &mut csr, options(nostack, preserves_flags));
+        }
+        csr as u64
+    }
+
+    pub fn set(value: u64) {
+        let csr = value as u32;
+        // SAFETY: only the FTZ/DAZ bits differ from the current MXCSR
+        unsafe {
+            std::arch::asm!("ldmxcsr [{}]", in(reg) &csr, options(nostack, readonly, preserves_flags));
+        }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod arch {
+    /// FPCR flush-to-zero (bit 24).
+    pub const FTZ_BITS: u64 = 1 << 24;
+
+    pub fn get() -> u64 {
+        let fpcr: u64;
+        // SAFETY: reading FPCR has no side effects
+        unsafe {
+            std::arch::asm!("mrs {}, fpcr", out(reg) fpcr, options(nomem, nostack, preserves_flags));
+        }
+        fpcr
+    }
+
+    pub fn set(value: u64) {
+        // SAFETY: only the FZ bit differs from the current FPCR
+        unsafe {
+            std::arch::asm!("msr fpcr, {}", in(reg) value, options(nomem, nostack, preserves_flags));
+        }
+    }
+}
+
+impl ScopedFtz {
+    pub fn enable() -> Self {
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        {
+            let previous = arch::get();
+            if previous & arch::FTZ_BITS != arch::FTZ_BITS {
+                arch::set(previous | arch::FTZ_BITS);
+            }
+            Self { previous }
+        }
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+        Self {}
+    }
+
+    /// Whether flush-to-zero is active on this thread.
+    pub fn is_active() -> bool {
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        {
+            arch::get() & arch::FTZ_BITS == arch::FTZ_BITS
+        }
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+        false
+    }
+}
+
+impl Drop for ScopedFtz {
+    fn drop(&mut self) {
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        if arch::get() != self.previous {
+            arch::set(self.previous);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    fn test_denormals_flush_while_enabled() {
+        let tiny = std::hint::black_box(f32::MIN_POSITIVE);
+        let was_active = ScopedFtz::is_active();
+        {
+            let _ftz = ScopedFtz::enable();
+            assert!(ScopedFtz::is_active());
+            assert_eq!(std::hint::black_box(tiny / 4.0), 0.0);
+        }
+        assert_eq!(ScopedFtz::is_active(), was_active, "mode restored on drop");
+    }
+}
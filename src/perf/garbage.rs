@@ -0,0 +1,112 @@
+//! Deferred deallocation for resources retired on the audio thread.
+//!
+//! Dropping the last `Arc` to a decoded preset frees megabytes of PCM,
+//! which must never happen inside `process()`. The audio thread instead
+//! hands retired resources to a collector thread over a bounded channel;
+//! if the channel is full the caller keeps the resource and retries on a
+//! later block.
+
+use std::sync::Arc;
+
+use crossbeam_channel::{Sender, TrySendError};
+use songwalker_core::preset::instance::PresetInstance;
+
+use crate::midi::rules::MidiRule;
+use crate::slots::slot::VoicePool;
+use crate::slots::{MacroAssignment, Slot};
+
+/// Capacity of the garbage channel (retired items in flight).
+pub const GARBAGE_CAPACITY: usize = 64;
+
+/// A resource retired on the audio thread, to be dropped elsewhere.
+pub enum Garbage {
+    Preset(Arc<PresetInstance>),
+    MidiRules(Arc<Vec<MidiRule>>),
+    Macros(Arc<Vec<MacroAssignment>>),
+    VoicePool(VoicePool),
+    /// Held by value: boxing it would allocate on the audio thread.
+    Slot(Slot),
+}
+
+/// Sending half of the garbage channel (held by the audio side).
+pub type GarbageSender = Sender<Garbage>;
+
+/// Spawn the collector thread and return the sender for the audio side.
+///
+/// The thread exits once every sender has been dropped.
+pub fn spawn_collector() -> GarbageSender {
+    let (tx, rx) = crossbeam_channel::bounded::<Garbage>(GARBAGE_CAPACITY);
+    let spawned = std::thread::Builder::new()
+        .name("songwalker-gc".into())
+        .spawn(move || {
+            while let Ok(garbage) = rx.recv() {
+                drop(garbage);
+            }
+        });
+    if let Err(e) = spawned {
+        log::warn!("Failed to spawn garbage collector thread: {}", e);
+    }
+    tx
+}
+
+/// Try to hand a preset to the collector without blocking.
+///
+/// Returns the preset back if the channel is full (or the collector is
+/// gone), so the caller can keep it alive and retry later.
+pub fn try_retire_preset(
+    tx: &GarbageSender,
+    preset: Arc<PresetInstance>,
+) -> Result<(), Arc<PresetInstance>> {
+    match tx.try_send(Garbage::Preset(preset)) {
+        Ok(()) => Ok(()),
+        Err(TrySendError::Full(Garbage::Preset(p)))
+        | Err(TrySendError::Disconnected(Garbage::Preset(p))) => Err(p),
+        Err(_) => unreachable!("sent a preset"),
+    }
+}
+
+/// Try to hand a replaced MIDI rule set to the collector without blocking.
+pub fn try_retire_rules(
+    tx: &GarbageSender,
+    rules: Arc<Vec<MidiRule>>,
+) -> Result<(), Arc<Vec<MidiRule>>> {
+    match tx.try_send(Garbage::MidiRules(rules)) {
+        Ok(()) => Ok(()),
+        Err(TrySendError::Full(Garbage::MidiRules(r)))
+        | Err(TrySendError::Disconnected(Garbage::MidiRules(r))) => Err(r),
+        Err(_) => unreachable!("sent a rule set"),
+    }
+}
+
+/// Try to hand replaced macro assignments to the collector without blocking.
+pub fn try_retire_macros(
+    tx: &GarbageSender,
+    assignments: Arc<Vec<MacroAssignment>>,
+) -> Result<(), Arc<Vec<MacroAssignment>>> {
+    match tx.try_send(Garbage::Macros(assignments)) {
+        Ok(()) => Ok(()),
+        Err(TrySendError::Full(Garbage::Macros(a)))
+        | Err(TrySendError::Disconnected(Garbage::Macros(a))) => Err(a),
+        Err(_) => unreachable!("sent macro assignments"),
+    }
+}
+
+/// Try to hand a replaced voice pool to the collector without blocking.
+pub fn try_retire_voice_pool(tx: &GarbageSender, pool: VoicePool) -> Result<(), VoicePool> {
+    match tx.try_send(Garbage::VoicePool(pool)) {
+        Ok(()) => Ok(()),
+        Err(TrySendError::Full(Garbage::VoicePool(p)))
+        | Err(TrySendError::Disconnected(Garbage::VoicePool(p))) => Err(p),
+        Err(_) => unreachable!("sent a voice pool"),
+    }
+}
+
+/// Try to hand a removed slot to the collector without blocking.
+pub fn try_retire_slot(tx: &GarbageSender, slot: Slot) -> Result<(), Slot> {
+    match tx.try_send(Garbage::Slot(slot)) {
+        Ok(()) => Ok(()),
+        Err(TrySendError::Full(Garbage::Slot(s)))
+        | Err(TrySendError::Disconnected(Garbage::Slot(s))) => Err(s),
+        Err(_) => unreachable!("sent a slot"),
+    }
+}
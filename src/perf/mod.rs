@@ -1,2 +1,8 @@
+pub mod bench;
+pub mod denormal;
+pub mod garbage;
 pub mod pool;
+pub mod render_check;
 pub mod simd;
+pub mod voice_pools;
+pub mod workers;
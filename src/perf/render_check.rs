@@ -0,0 +1,343 @@
+//! Deterministic offline renders for catching DSP regressions.
+//!
+//! Each scenario sets up a fixed rack, plays a fixed MIDI sequence through
+//! `render_and_mix` (serial rendering, fixed block size) and reduces the
+//! output to a [`Fingerprint`]: a hash of the quantized samples plus level
+//! and zero-crossing features per channel. References are kept in
+//! `src/perf/render_reference.json`.
+//!
+//! ```text
+//! songwalker-standalone --render-check            # compare
+//! songwalker-standalone --render-check --strict   # hashes must match too
+//! songwalker-standalone --render-check --bless    # rewrite the references
+//! ```
+//!
+//! The hash only matches on the same build and platform (float rounding
+//! differs with SIMD width and compiler), so by default a render passes
+//! when its features are within tolerance of the reference.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU32;
+
+use nih_plug::prelude::NoteEvent;
+use serde::{Deserialize, Serialize};
+
+use super::bench::sine_sample_preset;
+use crate::audio::{AudioEngine, render_and_mix};
+use crate::editor::visualizer::VisualizerState;
+use crate::monitor::EngineMonitor;
+use crate::slots::SlotManager;
+use crate::slots::slot::EnvelopeParams;
+use crate::transport::TransportState;
+
+const SAMPLE_RATE: f32 = 44100.0;
+/// Block size of every render; events land on block boundaries.
+const BLOCK: usize = 256;
+/// Quantization step of the hashed samples (about −120 dBFS).
+const HASH_STEP: f32 = 1.0 / (1 << 20) as f32;
+/// Allowed drift of the features (relative, with an absolute floor).
+const TOLERANCE: f32 = 0.01;
+const ABS_FLOOR: f32 = 1e-4;
+
+/// Reference file, relative to the crate root.
+pub const REFERENCE_FILE: &str = "src/perf/render_reference.json";
+
+/// A note played during a scenario.
+#[derive(Debug, Clone, Copy)]
+struct Note {
+    slot: usize,
+    note: u8,
+    velocity: f32,
+    on_secs: f32,
+    off_secs: f32,
+}
+
+/// A fixed rack and MIDI sequence.
+pub struct Scenario {
+    pub name: &'static str,
+    setup: fn(&mut SlotManager),
+    notes: &'static [Note],
+    length_secs: f32,
+}
+
+/// Summary of a render.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    /// FNV-1a of the interleaved samples quantized to `HASH_STEP`.
+    pub hash: u64,
+    pub rms: [f32; 2],
+    pub peak: [f32; 2],
+    /// Sign changes per second (tracks pitch and interpolation).
+    pub crossings: [f32; 2],
+}
+
+impl Fingerprint {
+    pub fn of(left: &[f32], right: &[f32]) -> Self {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for s in left.iter().zip(right).flat_map(|(l, r)| [*l, *r]) {
+            let q = (s / HASH_STEP).round() as i32;
+            for byte in q.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        let features = |buf: &[f32]| {
+            let rms = (buf.iter().map(|s| s * s).sum::<f32>() / buf.len().max(1) as f32).sqrt();
+            let peak = buf.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+            let flips = buf.windows(2).filter(|w| (w[0] < 0.0) != (w[1] < 0.0)).count();
+            (rms, peak, flips as f32 * SAMPLE_RATE / buf.len().max(1) as f32)
+        };
+        let (l, r) = (features(left), features(right));
+        Self {
+            hash,
+            rms: [l.0, r.0],
+            peak: [l.1, r.1],
+            crossings: [l.2, r.2],
+        }
+    }
+
+    /// Check against a reference: features within tolerance, and with
+    /// `strict` the same hash.
+    pub fn compare(&self, reference: &Fingerprint, strict: bool) -> Result<(), String> {
+        if strict && self.hash != reference.hash {
+            return Err(format!("hash {:016x} != {:016x}", self.hash, reference.hash));
+        }
+        let features = [
+            ("rms", self.rms, reference.rms),
+            ("peak", self.peak, reference.peak),
+            ("crossings", self.crossings, reference.crossings),
+        ];
+        for (name, got, want) in features {
+            for ch in 0..2 {
+                if (got[ch] - want[ch]).abs() > (want[ch].abs() * TOLERANCE).max(ABS_FLOOR) {
+                    return Err(format!("{}[{}] {} (reference {})", name, ch, got[ch], want[ch]));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Short envelope so releases finish inside a scenario.
+const SHORT_ENVELOPE: EnvelopeParams = EnvelopeParams {
+    attack_secs: 0.005,
+    decay_secs: 0.05,
+    sustain_level: 0.7,
+    release_secs: 0.1,
+};
+
+fn no_setup(_: &mut SlotManager) {}
+
+fn sampler_setup(slots: &mut SlotManager) {
+    let state = slots.slots_mut()[0].preset_state_mut();
+    state.load_preset(Arc::new("render/sine".to_string()), sine_sample_preset());
+    state.set_envelope(SHORT_ENVELOPE);
+}
+
+fn pan_setup(slots: &mut SlotManager) {
+    let slots = slots.slots_mut();
+    slots[0].set_pan(-1.0);
+    slots[0].preset_state_mut().set_envelope(SHORT_ENVELOPE);
+    slots[1].set_pan(1.0);
+    slots[1].set_volume(0.5);
+    slots[1].preset_state_mut().set_envelope(SHORT_ENVELOPE);
+}
+
+/// Every scenario, in report order.
+pub fn scenarios() -> [Scenario; 3] {
+    [
+        Scenario {
+            name: "envelope",
+            setup: no_setup,
+            notes: &[Note { slot: 0, note: 69, velocity: 0.8, on_secs: 0.0, off_secs: 0.25 }],
+            length_secs: 0.75,
+        },
+        Scenario {
+            name: "sampler_interpolation",
+            setup: sampler_setup,
+            notes: &[
+                Note { slot: 0, note: 61, velocity: 1.0, on_secs: 0.0, off_secs: 0.3 },
+                Note { slot: 0, note: 74, velocity: 0.6, on_secs: 0.1, off_secs: 0.3 },
+            ],
+            length_secs: 0.5,
+        },
+        Scenario {
+            name: "panning",
+            setup: pan_setup,
+            notes: &[
+                Note { slot: 0, note: 57, velocity: 1.0, on_secs: 0.0, off_secs: 0.3 },
+                Note { slot: 1, note: 64, velocity: 1.0, on_secs: 0.0, off_secs: 0.3 },
+            ],
+            length_secs: 0.5,
+        },
+    ]
+}
+
+/// Render a scenario to stereo buffers.
+pub fn render(scenario: &Scenario) -> (Vec<f32>, Vec<f32>) {
+    let mut slot_manager = SlotManager::new_empty();
+    slot_manager.initialize(SAMPLE_RATE);
+    slot_manager.allocate_all();
+    let mut engine = AudioEngine::new();
+    engine.initialize(SAMPLE_RATE, BLOCK);
+    engine.set_parallel_render(false);
+    let transport = TransportState::default();
+    let vis = Arc::new(VisualizerState::new(1024));
+    let voice_count = Arc::new(AtomicU32::new(0));
+    let monitor = Arc::new(EngineMonitor::new());
+    (scenario.setup)(&mut slot_manager);
+
+    // (frame, slot, event) in time order; note-offs first on ties
+    let mut events: Vec<(usize, usize, NoteEvent<()>)> = Vec::new();
+    for n in scenario.notes {
+        let frame = |secs: f32| (secs * SAMPLE_RATE) as usize;
+        let (channel, note, velocity) = (0, n.note, n.velocity);
+        events.push((frame(n.on_secs), n.slot, NoteEvent::NoteOn { timing: 0, voice_id: None, channel, note, velocity }));
+        events.push((frame(n.off_secs), n.slot, NoteEvent::NoteOff { timing: 0, voice_id: None, channel, note, velocity: 0.0 }));
+    }
+    events.sort_by_key(|(frame, _, event)| (*frame, matches!(event, NoteEvent::NoteOn { .. })));
+
+    let total = (scenario.length_secs * SAMPLE_RATE) as usize;
+    let (mut left, mut right) = (Vec::with_capacity(total), Vec::with_capacity(total));
+    let mut pending = events.iter().peekable();
+    while left.len() < total {
+        let n = BLOCK.min(total - left.len());
+        while let Some((_, slot, event)) = pending.next_if(|(frame, _, _)| *frame < left.len() + n) {
+            slot_manager.slots_mut()[*slot].handle_midi_event(event, &transport);
+        }
+        render_and_mix(n, &mut engine, &mut slot_manager, &transport, 1.0, 0.0, &vis, &voice_count, &monitor);
+        left.extend_from_slice(&engine.output_left[..n]);
+        right.extend_from_slice(&engine.output_right[..n]);
+    }
+    (left, right)
+}
+
+/// Fingerprint of every scenario.
+pub fn fingerprints() -> BTreeMap<String, Fingerprint> {
+    scenarios()
+        .iter()
+        .map(|s| {
+            let (left, right) = render(s);
+            (s.name.to_string(), Fingerprint::of(&left, &right))
+        })
+        .collect()
+}
+
+fn reference_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(REFERENCE_FILE)
+}
+
+/// Stored references (empty if the file is missing or unreadable).
+pub fn load_references() -> BTreeMap<String, Fingerprint> {
+    std::fs::read_to_string(reference_path())
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// Compare every scenario with its reference. Scenarios without one are
+/// reported as failures so a missing file can't pass silently.
+pub fn check(strict: bool) -> Vec<(String, Result<(), String>)> {
+    let references = load_references();
+    fingerprints()
+        .into_iter()
+        .map(|(name, fingerprint)| {
+            let result = match references.get(&name) {
+                Some(reference) => fingerprint.compare(reference, strict),
+                None => Err("no reference (run with --bless)".to_string()),
+            };
+            (name, result)
+        })
+        .collect()
+}
+
+/// Entry point for `--render-check`; returns false if any scenario failed.
+pub fn run_cli(bless: bool, strict: bool) -> bool {
+    if bless {
+        let json = serde_json::to_string_pretty(&fingerprints()).unwrap_or_default();
+        return match std::fs::write(reference_path(), json + "\n") {
+            Ok(()) => {
+                println!("Wrote {}", reference_path().display());
+                true
+            }
+            Err(e) => {
+                eprintln!("Failed to write {}: {}", reference_path().display(), e);
+                false
+            }
+        };
+    }
+    let mut ok = true;
+    for (name, result) in check(strict) {
+        match result {
+            Ok(()) => println!("{:<24} ok", name),
+            Err(e) => {
+                println!("{:<24} FAILED: {}", name, e);
+                ok = false;
+            }
+        }
+    }
+    ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renders_are_deterministic() {
+        for scenario in scenarios() {
+            let (l1, r1) = render(&scenario);
+            let (l2, r2) = render(&scenario);
+            assert_eq!(Fingerprint::of(&l1, &r1), Fingerprint::of(&l2, &r2), "{}", scenario.name);
+        }
+    }
+
+    #[test]
+    fn test_scenario_features() {
+        let [envelope, sampler, panning] = scenarios();
+
+        // Released notes have died away by the end
+        let (left, _) = render(&envelope);
+        let tail = Fingerprint::of(&left[left.len() - 2048..], &left[left.len() - 2048..]);
+        assert!(tail.peak[0] < 1e-4, "{}", tail.peak[0]);
+        let held = Fingerprint::of(&left[4410..8820], &left[4410..8820]);
+        assert!(held.rms[0] > 0.1);
+        // 440 Hz crosses zero 880 times a second
+        assert!((held.crossings[0] - 880.0).abs() < 20.0, "{}", held.crossings[0]);
+
+        // Sampler voices play off-root pitches without blowing up
+        let (left, right) = render(&sampler);
+        let fp = Fingerprint::of(&left, &right);
+        assert!(fp.rms[0] > 0.05 && fp.peak[0] < 2.0);
+
+        // Hard-panned slots stay on their side
+        let (left, right) = render(&panning);
+        let fp = Fingerprint::of(&left, &right);
+        assert!(fp.rms[0] > 0.05);
+        // The right slot is at half volume
+        assert!((fp.rms[1] / fp.rms[0] - 0.5).abs() < 0.05, "{:?}", fp.rms);
+    }
+
+    #[test]
+    fn test_compare_tolerance() {
+        let base = Fingerprint { hash: 1, rms: [0.5, 0.5], peak: [1.0, 1.0], crossings: [880.0, 880.0] };
+        let drifted = Fingerprint { hash: 2, rms: [0.502, 0.5], ..base };
+        assert!(drifted.compare(&base, false).is_ok());
+        assert!(drifted.compare(&base, true).is_err());
+        let broken = Fingerprint { rms: [0.5, 0.4], ..base };
+        assert!(broken.compare(&base, false).unwrap_err().starts_with("rms[1]"));
+    }
+
+    #[test]
+    fn test_matches_references() {
+        if !reference_path().exists() {
+            eprintln!("{} missing; run --render-check --bless", REFERENCE_FILE);
+            return;
+        }
+        for (name, result) in check(false) {
+            assert!(result.is_ok(), "{}: {}", name, result.unwrap_err());
+        }
+    }
+}
@@ -39,6 +39,54 @@ pub fn apply_gain(buf: &mut [f32], gain: f32, n: usize) {
     apply_gain_scalar(buf, gain, n);
 }
 
+/// Mix a stereo pair through a 2×2 gain matrix:
+/// `out_l += in_l*m[0][0] + in_r*m[0][1]`, `out_r += in_l*m[1][0] + in_r*m[1][1]`.
+///
+/// Covers gain, pan and stereo width in one pass over the slot buffer.
+#[inline]
+pub fn mix_stereo(
+    out_l: &mut [f32],
+    out_r: &mut [f32],
+    in_l: &[f32],
+    in_r: &[f32],
+    m: [[f32; 2]; 2],
+    n: usize,
+) {
+    let n = n
+        .min(out_l.len())
+        .min(out_r.len())
+        .min(in_l.len())
+        .min(in_r.len());
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx") {
+            // SAFETY: feature detection guarantees instruction availability
+            unsafe { mix_stereo_avx(out_l, out_r, in_l, in_r, m, n) };
+            return;
+        }
+    }
+
+    mix_stereo_scalar(out_l, out_r, in_l, in_r, m, n);
+}
+
+/// Scalar fallback for mix_stereo.
+#[inline]
+pub(crate) fn mix_stereo_scalar(
+    out_l: &mut [f32],
+    out_r: &mut [f32],
+    in_l: &[f32],
+    in_r: &[f32],
+    m: [[f32; 2]; 2],
+    n: usize,
+) {
+    for i in 0..n {
+        let (l, r) = (in_l[i], in_r[i]);
+        out_l[i] += l * m[0][0] + r * m[0][1];
+        out_r[i] += l * m[1][0] + r * m[1][1];
+    }
+}
+
 /// Scalar fallback for mix_add.
 #[inline]
 fn mix_add_scalar(dst: &mut [f32], src: &[f32], gain: f32, n: usize) {
@@ -107,6 +155,49 @@ unsafe fn apply_gain_avx(buf: &mut [f32], gain: f32, n: usize) {
     }
 }
 
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn mix_stereo_avx(
+    out_l: &mut [f32],
+    out_r: &mut [f32],
+    in_l: &[f32],
+    in_r: &[f32],
+    m: [[f32; 2]; 2],
+    n: usize,
+) {
+    unsafe {
+        use std::arch::x86_64::*;
+
+        let ll = _mm256_set1_ps(m[0][0]);
+        let lr = _mm256_set1_ps(m[0][1]);
+        let rl = _mm256_set1_ps(m[1][0]);
+        let rr = _mm256_set1_ps(m[1][1]);
+        let chunks = n / 8;
+
+        for i in 0..chunks {
+            let offset = i * 8;
+            let l = _mm256_loadu_ps(in_l.as_ptr().add(offset));
+            let r = _mm256_loadu_ps(in_r.as_ptr().add(offset));
+            let dl = _mm256_loadu_ps(out_l.as_ptr().add(offset));
+            let dr = _mm256_loadu_ps(out_r.as_ptr().add(offset));
+            let mixed_l = _mm256_add_ps(_mm256_mul_ps(l, ll), _mm256_mul_ps(r, lr));
+            let mixed_r = _mm256_add_ps(_mm256_mul_ps(l, rl), _mm256_mul_ps(r, rr));
+            _mm256_storeu_ps(out_l.as_mut_ptr().add(offset), _mm256_add_ps(dl, mixed_l));
+            _mm256_storeu_ps(out_r.as_mut_ptr().add(offset), _mm256_add_ps(dr, mixed_r));
+        }
+
+        let start = chunks * 8;
+        mix_stereo_scalar(
+            &mut out_l[start..],
+            &mut out_r[start..],
+            &in_l[start..],
+            &in_r[start..],
+            m,
+            n - start,
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,6 +212,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_mix_stereo_matches_scalar() {
+        // 19 samples: two AVX chunks plus a scalar remainder
+        let in_l: Vec<f32> = (0..19).map(|i| i as f32 * 0.1).collect();
+        let in_r: Vec<f32> = (0..19).map(|i| 1.0 - i as f32 * 0.05).collect();
+        let m = [[0.7, 0.2], [-0.1, 0.9]];
+        let (mut l, mut r) = (vec![0.5_f32; 19], vec![0.25_f32; 19]);
+        let (mut sl, mut sr) = (l.clone(), r.clone());
+        mix_stereo(&mut l, &mut r, &in_l, &in_r, m, 19);
+        mix_stereo_scalar(&mut sl, &mut sr, &in_l, &in_r, m, 19);
+        for i in 0..19 {
+            assert!((l[i] - sl[i]).abs() < 1e-6 && (r[i] - sr[i]).abs() < 1e-6, "sample {i}");
+        }
+    }
+
     #[test]
     fn test_apply_gain() {
         let mut buf = vec![0.5_f32; 16];
@@ -0,0 +1,114 @@
+//! Voice pool resizing off the audio thread.
+//!
+//! A slot's voice pool is a pre-allocated `Vec<Voice>`, so growing or
+//! shrinking it would allocate inside `process()`. The audio side instead
+//! asks the resizer thread for a pool of the new size, swaps it in at the
+//! start of a later block, and retires the old pool to the garbage
+//! collector (see `garbage`).
+//!
+//! The same thread builds the slot that takes a removed slot's place at
+//! the end of the rack, so the rack never shrinks below `MAX_SLOTS`.
+
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+
+use crate::slots::slot::VoicePool;
+use crate::slots::{MAX_SLOTS, Slot};
+
+/// Capacity of the request and result channels.
+pub const RESIZE_CAPACITY: usize = MAX_SLOTS * 2;
+
+/// Work for the resizer thread.
+enum Request {
+    /// A new pool for a slot.
+    Pool { slot_index: usize, voices: usize },
+    /// A fresh slot to replace a removed one.
+    Slot { sample_rate: f32 },
+}
+
+/// A pool allocated for a slot, waiting to be swapped in.
+pub struct ResizedPool {
+    pub slot_index: usize,
+    pub pool: VoicePool,
+}
+
+/// Audio-side handle to the resizer thread.
+pub struct PoolResizer {
+    request_tx: Sender<Request>,
+    pool_rx: Receiver<ResizedPool>,
+    slot_rx: Receiver<Slot>,
+}
+
+impl PoolResizer {
+    /// Spawn the resizer thread. It exits once the handle is dropped.
+    pub fn spawn() -> Self {
+        let (request_tx, request_rx) = crossbeam_channel::bounded::<Request>(RESIZE_CAPACITY);
+        let (pool_tx, pool_rx) = crossbeam_channel::bounded::<ResizedPool>(RESIZE_CAPACITY);
+        let (slot_tx, slot_rx) = crossbeam_channel::bounded::<Slot>(MAX_SLOTS);
+        let spawned = std::thread::Builder::new()
+            .name("songwalker-voices".into())
+            .spawn(move || {
+                while let Ok(request) = request_rx.recv() {
+                    let sent = match request {
+                        Request::Pool { slot_index, voices } => {
+                            pool_tx.send(ResizedPool { slot_index, pool: VoicePool::new(voices) }).is_ok()
+                        }
+                        Request::Slot { sample_rate } => {
+                            let mut slot = Slot::new(0);
+                            slot.initialize(sample_rate);
+                            slot_tx.send(slot).is_ok()
+                        }
+                    };
+                    if !sent {
+                        break;
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            log::warn!("Failed to spawn voice pool resizer thread: {}", e);
+        }
+        Self { request_tx, pool_rx, slot_rx }
+    }
+
+    /// Ask for a pool of `voices` voices without blocking. Returns false if
+    /// the request could not be queued (try again on a later block).
+    pub fn request(&self, slot_index: usize, voices: usize) -> bool {
+        self.send(Request::Pool { slot_index, voices })
+    }
+
+    /// Ask for a new slot at `sample_rate` without blocking. Returns false
+    /// if the request could not be queued.
+    pub fn request_slot(&self, sample_rate: f32) -> bool {
+        self.send(Request::Slot { sample_rate })
+    }
+
+    fn send(&self, request: Request) -> bool {
+        match self.request_tx.try_send(request) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+
+    /// A pool that is ready to be swapped in, if any.
+    pub fn try_recv(&self) -> Option<ResizedPool> {
+        self.pool_rx.try_recv().ok()
+    }
+
+    /// A slot that is ready to join the rack, if any.
+    pub fn try_recv_slot(&self) -> Option<Slot> {
+        self.slot_rx.try_recv().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resizer_allocates_requested_size() {
+        let resizer = PoolResizer::spawn();
+        assert!(resizer.request(3, 12));
+        let resized = resizer.pool_rx.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+        assert_eq!(resized.slot_index, 3);
+        assert_eq!(resized.pool.capacity(), 12);
+    }
+}
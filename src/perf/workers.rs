@@ -0,0 +1,378 @@
+//! Worker threads that render independent slots in parallel.
+//!
+//! The pool is spawned up front (never from the audio thread) and takes
+//! jobs without locks: `RenderPool::run` publishes a batch as one packed
+//! atomic (generation, job count, next job), unparks the workers and then
+//! claims jobs alongside them. It returns only once every job has finished,
+//! so a batch may borrow from the caller's stack. Which thread runs which
+//! job varies; callers keep output deterministic by giving each job its own
+//! buffer and mixing the results in a fixed order afterwards.
+//!
+//! A job a worker claimed but hasn't started after `WAIT_LIMIT` polls (the
+//! worker was descheduled) is taken over by the caller. A job that panics
+//! on a worker is caught there and run again by the caller, so a panic
+//! surfaces on the audio thread as it would without the pool instead of
+//! leaving `run` waiting forever.
+
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU64, AtomicUsize, Ordering};
+use std::thread::{self, JoinHandle};
+
+use super::denormal::ScopedFtz;
+
+/// Upper bound on worker threads (the audio thread also takes jobs).
+pub const MAX_WORKERS: usize = 3;
+
+/// Polls of the batch state before an idle worker parks. Keeps workers
+/// hot between consecutive blocks without burning a core while stopped.
+const SPIN_LIMIT: u32 = 20_000;
+
+/// Largest batch spread over the workers; bigger ones run on the caller.
+pub const MAX_JOBS: usize = 64;
+
+/// Polls of the batch's jobs before the caller runs those claimed by a
+/// worker that hasn't started them.
+const WAIT_LIMIT: u32 = 2_000;
+
+/// Job statuses, stored with the batch generation in the upper 32 bits so
+/// a worker still holding a claim from an earlier batch can't start a job
+/// of the current one.
+const QUEUED: u64 = 0;
+const RUNNING: u64 = 1;
+const DONE: u64 = 2;
+const FAILED: u64 = 3;
+
+fn job_status(generation: u32, status: u64) -> u64 {
+    ((generation as u64) << 32) | status
+}
+
+/// A type-erased `&F` plus the trampoline that calls it.
+type Call = unsafe fn(*const (), usize);
+
+/// Call the closure behind `ctx` for job `index`.
+///
+/// # Safety
+/// `ctx` must point to a live `F`.
+unsafe fn trampoline<F: Fn(usize) + Sync>(ctx: *const (), index: usize) {
+    // SAFETY: guaranteed by the caller
+    unsafe { (*ctx.cast::<F>())(index) }
+}
+
+/// Batch state packed as generation (32 bits), job count and next job
+/// (16 bits each), so a claim can never mix two batches.
+fn pack(generation: u32, len: usize, next: usize) -> u64 {
+    ((generation as u64) << 32) | ((len as u64 & 0xFFFF) << 16) | (next as u64 & 0xFFFF)
+}
+
+fn unpack(state: u64) -> (u32, usize, usize) {
+    (
+        (state >> 32) as u32,
+        ((state >> 16) & 0xFFFF) as usize,
+        (state & 0xFFFF) as usize,
+    )
+}
+
+struct Shared {
+    state: AtomicU64,
+    /// Closure of the current batch, valid while any of its jobs is unclaimed
+    /// or running.
+    ctx: AtomicPtr<()>,
+    call: AtomicUsize,
+    /// Status of each job of the current batch (see `job_status`).
+    jobs: Box<[AtomicU64]>,
+    shutdown: AtomicBool,
+}
+
+impl Shared {
+    /// Claim the next job of batch `generation`, if any is left.
+    fn claim(&self, generation: u32) -> Option<usize> {
+        let mut state = self.state.load(Ordering::Acquire);
+        loop {
+            let (current, len, next) = unpack(state);
+            if current != generation || next >= len {
+                return None;
+            }
+            match self.state.compare_exchange_weak(
+                state,
+                pack(generation, len, next + 1),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Some(next),
+                Err(actual) => state = actual,
+            }
+        }
+    }
+
+    /// Start job `index` of batch `generation`, unless another thread has.
+    fn start(&self, generation: u32, index: usize) -> bool {
+        self.jobs[index]
+            .compare_exchange(
+                job_status(generation, QUEUED),
+                job_status(generation, RUNNING),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_ok()
+    }
+
+    /// Run a started job, recording whether it panicked.
+    fn execute(&self, generation: u32, index: usize) {
+        // The batch can't be replaced while this job is unfinished, so
+        // its closure is still the one published before the start.
+        let ctx = self.ctx.load(Ordering::Relaxed);
+        // SAFETY: `call` was stored from a `Call` in `RenderPool::run`
+        let call: Call = unsafe { std::mem::transmute(self.call.load(Ordering::Relaxed)) };
+        // SAFETY: `run` keeps the closure alive until every job is done or
+        // failed, which can't happen before this job finishes
+        let result = panic::catch_unwind(AssertUnwindSafe(|| unsafe { call(ctx, index) }));
+        let status = if result.is_ok() { DONE } else { FAILED };
+        self.jobs[index].store(job_status(generation, status), Ordering::Release);
+    }
+
+    /// Claim and run jobs of batch `generation` until none are left.
+    fn drain(&self, generation: u32) {
+        while let Some(index) = self.claim(generation) {
+            if self.start(generation, index) {
+                self.execute(generation, index);
+            }
+        }
+    }
+
+    /// Whether every job of batch `generation` has finished.
+    fn finished(&self, generation: u32, len: usize) -> bool {
+        self.jobs[..len]
+            .iter()
+            .all(|job| job.load(Ordering::Acquire) >= job_status(generation, DONE))
+    }
+}
+
+/// Pre-spawned worker threads for `run`.
+pub struct RenderPool {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+    generation: u32,
+}
+
+impl RenderPool {
+    /// Spawn `workers` threads (capped at `MAX_WORKERS`). With zero
+    /// workers `run` executes every job on the calling thread.
+    pub fn new(workers: usize) -> Self {
+        let shared = Arc::new(Shared {
+            state: AtomicU64::new(pack(0, 0, 0)),
+            ctx: AtomicPtr::new(std::ptr::null_mut()),
+            call: AtomicUsize::new(0),
+            jobs: (0..MAX_JOBS).map(|_| AtomicU64::new(0)).collect(),
+            shutdown: AtomicBool::new(false),
+        });
+        let workers = (0..workers.min(MAX_WORKERS))
+            .filter_map(|i| {
+                let shared = shared.clone();
+                thread::Builder::new()
+                    .name(format!("songwalker-render-{}", i))
+                    .spawn(move || worker_loop(&shared))
+                    .inspect_err(|e| log::warn!("Failed to spawn render worker: {}", e))
+                    .ok()
+            })
+            .collect();
+        Self {
+            shared,
+            workers,
+            generation: 0,
+        }
+    }
+
+    /// A pool sized to the machine: one worker per spare core.
+    pub fn with_available_parallelism() -> Self {
+        let cores = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::new(cores.saturating_sub(1))
+    }
+
+    /// Number of worker threads (not counting the caller).
+    pub fn worker_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Run `f(0..len)` across the workers and the calling thread, returning
+    /// once every call has finished. Never allocates or blocks on a lock.
+    ///
+    /// A call that panicked on a worker is made again on the calling thread,
+    /// where a second panic propagates.
+    pub fn run<F: Fn(usize) + Sync>(&mut self, len: usize, f: &F) {
+        if self.workers.is_empty() || !(2..=MAX_JOBS).contains(&len) {
+            (0..len).for_each(f);
+            return;
+        }
+
+        // Every job of the previous batch has finished, so no worker reads
+        // `ctx`/`call` until the new state below is published
+        self.generation = self.generation.wrapping_add(1);
+        let generation = self.generation;
+        let call: Call = trampoline::<F>;
+        self.shared
+            .ctx
+            .store((f as *const F).cast_mut().cast(), Ordering::Relaxed);
+        self.shared.call.store(call as usize, Ordering::Relaxed);
+        for job in &self.shared.jobs[..len] {
+            job.store(job_status(generation, QUEUED), Ordering::Relaxed);
+        }
+        self.shared
+            .state
+            .store(pack(generation, len, 0), Ordering::Release);
+        for worker in &self.workers {
+            worker.thread().unpark();
+        }
+
+        self.shared.drain(generation);
+        let mut polls = 0;
+        while !self.shared.finished(generation, len) {
+            if polls == WAIT_LIMIT {
+                // Jobs claimed by a worker that was descheduled before
+                // starting them; those it did start must be waited for
+                for index in 0..len {
+                    if self.shared.start(generation, index) {
+                        self.shared.execute(generation, index);
+                    }
+                }
+            }
+            polls = polls.saturating_add(1);
+            std::hint::spin_loop();
+        }
+
+        for (index, job) in self.shared.jobs[..len].iter().enumerate() {
+            if job.load(Ordering::Acquire) == job_status(generation, FAILED) {
+                f(index);
+            }
+        }
+    }
+}
+
+impl Drop for RenderPool {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::Release);
+        for worker in self.workers.drain(..) {
+            worker.thread().unpark();
+            let _ = worker.join();
+        }
+    }
+}
+
+/// A mutable slice shared with `run` jobs that each touch their own
+/// elements.
+pub struct DisjointSlice<'a, T> {
+    ptr: *mut T,
+    len: usize,
+    _slice: PhantomData<&'a mut [T]>,
+}
+
+// SAFETY: elements are only reached through `get`, whose callers guarantee
+// that no element is borrowed by two jobs at once
+unsafe impl<T: Send> Sync for DisjointSlice<'_, T> {}
+
+impl<'a, T> DisjointSlice<'a, T> {
+    pub fn new(slice: &'a mut [T]) -> Self {
+        Self {
+            ptr: slice.as_mut_ptr(),
+            len: slice.len(),
+            _slice: PhantomData,
+        }
+    }
+
+    /// Borrow element `index`.
+    ///
+    /// # Safety
+    /// No other borrow of the same element may be live.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn get(&self, index: usize) -> &mut T {
+        assert!(index < self.len);
+        // SAFETY: in bounds, and exclusive per the caller's guarantee
+        unsafe { &mut *self.ptr.add(index) }
+    }
+}
+
+fn worker_loop(shared: &Shared) {
+    let _ftz = ScopedFtz::enable();
+    let mut seen = 0;
+    loop {
+        let mut spins = 0;
+        let generation = loop {
+            if shared.shutdown.load(Ordering::Acquire) {
+                return;
+            }
+            let (generation, _, _) = unpack(shared.state.load(Ordering::Acquire));
+            if generation != seen {
+                break generation;
+            }
+            if spins < SPIN_LIMIT {
+                spins += 1;
+                std::hint::spin_loop();
+            } else {
+                thread::park();
+            }
+        };
+        seen = generation;
+        shared.drain(generation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_executes_every_job_once() {
+        let mut pool = RenderPool::new(2);
+        let hits: Vec<AtomicUsize> = (0..16).map(|_| AtomicUsize::new(0)).collect();
+        for _ in 0..200 {
+            pool.run(hits.len(), &|i| {
+                hits[i].fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        assert!(hits.iter().all(|h| h.load(Ordering::Relaxed) == 200));
+    }
+
+    #[test]
+    fn test_disjoint_slice_jobs_write_their_own_element() {
+        let mut pool = RenderPool::new(2);
+        let mut values = vec![0usize; 8];
+        let shared = DisjointSlice::new(&mut values);
+        // SAFETY: each job touches only its own index
+        pool.run(8, &|i| *unsafe { shared.get(i) } = i * 2);
+        assert_eq!(values, (0..8).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_jobs_that_panic_on_a_worker_run_again_on_the_caller() {
+        let mut pool = RenderPool::new(2);
+        let caller = thread::current().id();
+        let finished = AtomicUsize::new(0);
+        for _ in 0..50 {
+            pool.run(4, &|_| {
+                if thread::current().id() != caller {
+                    panic!("render job failed on a worker");
+                }
+                finished.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        assert_eq!(finished.load(Ordering::Relaxed), 200);
+
+        // A job that also fails on the caller panics there, and the pool
+        // carries on with the next batch
+        let failed = panic::catch_unwind(AssertUnwindSafe(|| pool.run(4, &|i| assert_ne!(i, 2))));
+        assert!(failed.is_err());
+        let hits = AtomicUsize::new(0);
+        pool.run(4, &|_| {
+            hits.fetch_add(1, Ordering::Relaxed);
+        });
+        assert_eq!(hits.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn test_run_without_workers_is_inline() {
+        let mut pool = RenderPool::new(0);
+        let caller = thread::current().id();
+        pool.run(4, &|_| assert_eq!(thread::current().id(), caller));
+    }
+}
@@ -7,10 +7,16 @@ use crossbeam_channel::{Receiver, Sender};
 
 use crate::audio::AudioEngine;
 use crate::editor;
+use crate::editor::event_queue::EventSender;
 use crate::editor::{EditorEvent, PresetLoadedEvent};
 use crate::editor::visualizer::VisualizerState;
-use crate::params::SongWalkerParams;
+use crate::jobs::JobPool;
+use crate::midi::gm::{GM_CHANNEL_CAPACITY, GmRequest};
+use crate::midi::rules::{FiredRule, RULE_CHANNEL_CAPACITY, spawn_dispatcher};
+use crate::monitor::EngineMonitor;
+use crate::params::{AUTOMATABLE_SLOTS, SlotMix, SongWalkerParams};
 use crate::preset::manager::PresetManager;
+use crate::programs::{FactoryProgram, PROGRAM_CHANNEL_CAPACITY};
 use crate::slots::SlotManager;
 use crate::state::PluginState;
 use crate::transport::TransportState;
@@ -29,7 +35,7 @@ pub struct SongWalkerPlugin {
     /// Serializable plugin state for DAW save/restore.
     plugin_state: Arc<Mutex<PluginState>>,
     /// Channel sender for editor events (note on/off).
-    event_tx: Sender<EditorEvent>,
+    event_tx: EventSender,
     /// Channel receiver drained on the audio thread each process block.
     event_rx: Receiver<EditorEvent>,
     /// Channel sender for loaded presets (editor/background → audio thread).
@@ -42,6 +48,25 @@ pub struct SongWalkerPlugin {
     visualizer_state: Arc<VisualizerState>,
     /// Live voice count (updated per process block, read by editor).
     voice_count: Arc<AtomicU32>,
+    /// Per-slot telemetry (updated per process block, read by editor).
+    monitor: Arc<EngineMonitor>,
+    /// Shared background job pool (preset downloads, decoding).
+    jobs: Arc<JobPool>,
+    /// Whether the garbage collector and voice pool resizer threads have been started.
+    garbage_started: bool,
+    /// Slot mix parameter values last applied to the slots.
+    slot_params_applied: [Option<SlotMix>; AUTOMATABLE_SLOTS],
+    /// Program parameter value last seen, `None` until initialize (so a
+    /// restored project's value isn't taken as a new selection).
+    program_seen: Option<FactoryProgram>,
+    /// Selected programs, sent to the loader thread.
+    program_tx: Sender<FactoryProgram>,
+    /// Handed to the program loader thread on first initialize.
+    program_rx: Option<Receiver<FactoryProgram>>,
+    /// Fired MIDI rules, handed to the dispatcher thread on first initialize.
+    rule_rx: Option<Receiver<FiredRule>>,
+    /// GM mode program requests, handed to the loader thread on first initialize.
+    gm_rx: Option<Receiver<GmRequest>>,
     /// Sample rate provided by the host.
     sample_rate: f32,
 }
@@ -49,13 +74,19 @@ pub struct SongWalkerPlugin {
 impl Default for SongWalkerPlugin {
     fn default() -> Self {
         let params = Arc::new(SongWalkerParams::default());
-        let (event_tx, event_rx) = crossbeam_channel::bounded(64);
+        let (event_tx, event_rx) = editor::event_queue::channel();
         let (preset_loaded_tx, preset_loaded_rx) = crossbeam_channel::bounded(16);
+        let (rule_tx, rule_rx) = crossbeam_channel::bounded(RULE_CHANNEL_CAPACITY);
+        let (program_tx, program_rx) = crossbeam_channel::bounded(PROGRAM_CHANNEL_CAPACITY);
+        let (gm_tx, gm_rx) = crossbeam_channel::bounded(GM_CHANNEL_CAPACITY);
+        let mut slot_manager = SlotManager::new_empty();
+        slot_manager.set_rule_sender(rule_tx);
+        slot_manager.set_gm_sender(gm_tx);
         Self {
             params,
             audio_engine: AudioEngine::new(),
-            slot_manager: SlotManager::new_empty(),
-            preset_manager: Arc::new(Mutex::new(PresetManager::new())),
+            slot_manager,
+            preset_manager: crate::preset::shared::manager(),
             transport: TransportState::default(),
             plugin_state: Arc::new(Mutex::new(PluginState::default())),
             event_tx,
@@ -65,6 +96,15 @@ impl Default for SongWalkerPlugin {
             status_text: Arc::new(Mutex::new(String::new())),
             visualizer_state: Arc::new(VisualizerState::new(512)),
             voice_count: Arc::new(AtomicU32::new(0)),
+            monitor: Arc::new(EngineMonitor::new()),
+            jobs: Arc::new(JobPool::default()),
+            garbage_started: false,
+            slot_params_applied: [None; AUTOMATABLE_SLOTS],
+            program_seen: None,
+            program_tx,
+            program_rx: Some(program_rx),
+            rule_rx: Some(rule_rx),
+            gm_rx: Some(gm_rx),
             sample_rate: 44100.0,
         }
     }
@@ -77,6 +117,17 @@ impl Plugin for SongWalkerPlugin {
     const EMAIL: &'static str = "";
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
     const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[
+        // Optional stereo input, processed by slots holding Effect presets
+        AudioIOLayout {
+            main_input_channels: None,
+            main_output_channels: NonZeroU32::new(2),
+            aux_input_ports: &[new_nonzero_u32(2)],
+            names: PortNames {
+                aux_inputs: &["Effect Input"],
+                ..PortNames::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        },
         AudioIOLayout {
             main_input_channels: None,
             main_output_channels: NonZeroU32::new(2),
@@ -84,10 +135,10 @@ impl Plugin for SongWalkerPlugin {
         },
     ];
     const MIDI_INPUT: MidiConfig = MidiConfig::MidiCCs;
-    const MIDI_OUTPUT: MidiConfig = MidiConfig::None;
+    const MIDI_OUTPUT: MidiConfig = MidiConfig::Basic;
     const SAMPLE_ACCURATE_AUTOMATION: bool = false;
 
-    type SysExMessage = ();
+    type SysExMessage = crate::midi::SysEx;
     type BackgroundTask = ();
 
     fn params(&self) -> Arc<dyn Params> {
@@ -105,6 +156,26 @@ impl Plugin for SongWalkerPlugin {
         let status_text = self.status_text.clone();
         let visualizer_state = self.visualizer_state.clone();
         let voice_count = self.voice_count.clone();
+        let monitor = self.monitor.clone();
+        let jobs = self.jobs.clone();
+        #[cfg(feature = "vizia-editor")]
+        if editor::frontend::current() == editor::frontend::EditorFrontend::Vizia {
+            return crate::editor_vizia::create(
+                preset_manager,
+                plugin_state,
+                params,
+                crate::editor_vizia::default_state(),
+                event_tx,
+                audio_preset_loaded_tx,
+                ui_preset_loaded_tx,
+                ui_preset_loaded_rx,
+                status_text,
+                visualizer_state,
+                voice_count,
+                monitor,
+                jobs,
+            );
+        }
         editor::create(
             preset_manager,
             plugin_state,
@@ -117,6 +188,8 @@ impl Plugin for SongWalkerPlugin {
             status_text,
             visualizer_state,
             voice_count,
+            monitor,
+            jobs,
         )
     }
 
@@ -124,22 +197,89 @@ impl Plugin for SongWalkerPlugin {
         &mut self,
         _audio_io_layout: &AudioIOLayout,
         buffer_config: &BufferConfig,
-        _context: &mut impl InitContext<Self>,
+        context: &mut impl InitContext<Self>,
     ) -> bool {
         log::info!("SongWalkerPlugin::initialize() sample_rate={}", buffer_config.sample_rate);
         self.sample_rate = buffer_config.sample_rate;
         self.audio_engine
             .initialize(buffer_config.sample_rate, buffer_config.max_buffer_size as usize);
+        if let Ok(state) = self.plugin_state.lock() {
+            self.audio_engine.set_parallel_render(state.parallel_render);
+            self.slot_manager.set_reset_on_stop(state.reset_on_stop);
+            self.slot_manager.set_gm_mode(state.gm_mode);
+            self.slot_manager.set_macros(Arc::new(state.macros.clone()));
+        }
         self.slot_manager.initialize(buffer_config.sample_rate);
         
         // Ensure all slots are allocated now (not in process() which would crash)
         log::info!("SongWalkerPlugin::initialize() allocate_all");
         self.slot_manager.allocate_all();
 
+        // Restored slots get their saved mix, MIDI and sound settings and
+        // their groups, as if each had been edited in the editor
+        let restored = self.plugin_state.lock().map(|state| crate::view_model::sync_all(&state));
+        for event in restored.unwrap_or_default() {
+            crate::audio::handle_editor_event(event, &mut self.slot_manager, &self.transport);
+        }
+        crate::audio::report_latency(&mut self.audio_engine, &self.slot_manager, |samples| {
+            context.set_latency_samples(samples)
+        });
+
+        // Retired presets are dropped on a collector thread, never in process(),
+        // and resized voice pools are allocated on a thread of their own
+        if !self.garbage_started {
+            self.slot_manager.set_garbage_sender(crate::perf::garbage::spawn_collector());
+            self.slot_manager.set_pool_resizer(crate::perf::voice_pools::PoolResizer::spawn());
+            self.garbage_started = true;
+        }
+
+        // Fired MIDI rules are carried out off the audio thread
+        if let Some(rule_rx) = self.rule_rx.take() {
+            spawn_dispatcher(
+                rule_rx,
+                self.jobs.clone(),
+                self.preset_manager.clone(),
+                self.plugin_state.clone(),
+                self.preset_loaded_tx.clone(),
+                self.status_text.clone(),
+            );
+        }
+
+        // Host-selected factory programs are applied off the audio thread
+        if let Some(program_rx) = self.program_rx.take() {
+            crate::programs::spawn_loader(
+                program_rx,
+                self.jobs.clone(),
+                self.preset_manager.clone(),
+                self.plugin_state.clone(),
+                self.event_tx.clone(),
+                self.preset_loaded_tx.clone(),
+                self.status_text.clone(),
+            );
+        }
+        self.program_seen = Some(self.params.program.value());
+
+        // GM mode program changes are loaded off the audio thread too
+        if let Some(gm_rx) = self.gm_rx.take() {
+            crate::midi::gm::spawn_loader(
+                gm_rx,
+                self.jobs.clone(),
+                self.preset_manager.clone(),
+                self.plugin_state.clone(),
+                self.preset_loaded_tx.clone(),
+                self.status_text.clone(),
+            );
+        }
+
+        // Start the shared job workers (jobs queued by the editor before now run once started)
+        self.jobs.start();
+
         // Start background preset manager (fetches library indexes)
         log::info!("SongWalkerPlugin::initialize() background refresh start");
         let pm = self.preset_manager.clone();
-        PresetManager::start_background_refresh(pm);
+        crate::preset::library_index::start_background_refresh(&self.jobs, pm);
+        // Once per process: other instances share the manager (see `preset::shared`)
+        crate::preset::shared::start_services(&self.jobs, &self.preset_manager);
 
         log::info!("SongWalkerPlugin::initialize() success");
         true
@@ -153,28 +293,36 @@ impl Plugin for SongWalkerPlugin {
     fn process(
         &mut self,
         buffer: &mut Buffer,
-        _aux: &mut AuxiliaryBuffers,
+        aux: &mut AuxiliaryBuffers,
         context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus {
+        let _ftz = crate::perf::denormal::ScopedFtz::enable();
+
         // Update transport from host
         self.transport.update(context.transport());
+        if self.transport.stopped {
+            self.slot_manager.transport_stopped();
+        }
 
         // --- Drain loaded presets (background thread → audio thread) ---
-        while let Ok(loaded) = self.preset_loaded_rx.try_recv() {
+        // A slot still holding presets for a busy collector defers them
+        while self.slot_manager.presets_have_room() {
+            let Ok(loaded) = self.preset_loaded_rx.try_recv() else { break };
             // Index must be within pre-allocated bounds
             if loaded.slot_index < self.slot_manager.slot_count() {
                 let slot = &mut self.slot_manager.slots_mut()[loaded.slot_index];
                 slot.preset_state_mut()
                     .load_preset(loaded.preset_id, loaded.instance);
+                slot.preset_state_mut().set_graph(loaded.graph);
 
                 // Optionally trigger a note-on immediately after loading (preview)
-                if let Some(note) = loaded.play_note {
+                if let Some(play) = loaded.play_note {
                     let note_event = NoteEvent::NoteOn {
                         timing: 0,
                         voice_id: None,
                         channel: 0,
-                        note,
-                        velocity: 0.8,
+                        note: play.note,
+                        velocity: play.velocity,
                     };
                     self.slot_manager.slots_mut()[loaded.slot_index]
                         .handle_midi_event(&note_event, &self.transport);
@@ -182,52 +330,48 @@ impl Plugin for SongWalkerPlugin {
             }
         }
 
-        // --- Drain editor events (piano keys, stop-preview) ---
+        // --- Drain editor events (piano keys, stop-preview, rack changes) ---
         while let Ok(event) = self.event_rx.try_recv() {
             match event {
-                EditorEvent::NoteOn { slot_index, note, velocity } => {
-                    if slot_index < self.slot_manager.slot_count() {
-                        nih_plug::debug::nih_log!("[AudioThread] Received EditorEvent::NoteOn: note={} slot={}", note, slot_index);
-                        let note_event = NoteEvent::NoteOn {
-                            timing: 0,
-                            voice_id: None,
-                            channel: 0,
-                            note,
-                            velocity,
-                        };
-                        self.slot_manager.slots_mut()[slot_index]
-                            .handle_midi_event(&note_event, &self.transport);
-                    }
-                }
-                EditorEvent::NoteOff { slot_index, note } => {
-                    if let Some(slot) = self.slot_manager.slots_mut().get_mut(slot_index) {
-                        nih_plug::debug::nih_log!("[AudioThread] Received EditorEvent::NoteOff: note={} slot={}", note, slot_index);
-                        let note_event = NoteEvent::NoteOff {
-                            timing: 0,
-                            voice_id: None,
-                            channel: 0,
-                            note,
-                            velocity: 0.0,
-                        };
-                        slot.handle_midi_event(&note_event, &self.transport);
-                    }
-                }
-                EditorEvent::StopPreview => {
-                    nih_plug::debug::nih_log!("[AudioThread] Received EditorEvent::StopPreview");
-                    // All-notes-off on all slots
-                    for slot in self.slot_manager.slots_mut() {
-                        let all_off = NoteEvent::MidiCC {
-                            timing: 0,
-                            channel: 0,
-                            cc: 123,
-                            value: 0.0,
-                        };
-                        slot.handle_midi_event(&all_off, &self.transport);
-                    }
+                EditorEvent::SetParallelRender { enabled } => {
+                    self.audio_engine.set_parallel_render(enabled);
                 }
+                event => crate::audio::handle_editor_event(
+                    event,
+                    &mut self.slot_manager,
+                    &self.transport,
+                ),
             }
         }
 
+        // A program chosen from the host rebuilds the rack
+        let program = self.params.program.value();
+        if self.program_seen.is_some_and(|seen| seen != program)
+            && self.program_tx.try_send(program).is_ok()
+        {
+            self.program_seen = Some(program);
+        }
+
+        // Swap in voice pools resized for a new polyphony / Max Voices
+        self.slot_manager.update_voice_pools(self.params.max_voices.value().max(1) as usize);
+        self.slot_manager.set_bend_range(self.params.pitch_bend_range.value().clamp(1, 48) as u8);
+
+        // Host automation of the slot mix parameters
+        crate::audio::apply_slot_params(
+            &mut self.slot_manager,
+            &self.params,
+            &mut self.slot_params_applied,
+        );
+        // Macro knobs, after the mix so an assigned volume or pan follows the macro
+        self.slot_manager.apply_macros(self.params.macro_values());
+
+        // Audio input for effect slots (silence if the host gives none)
+        match aux.inputs.first().map(|input| input.as_slice_immutable()) {
+            Some([left, right, ..]) => self.audio_engine.set_input(left, right),
+            Some([mono]) => self.audio_engine.set_input(mono, mono),
+            _ => self.audio_engine.clear_input(),
+        }
+
         // Process all MIDI events and route to slots
         crate::audio::process_block(
             buffer,
@@ -238,6 +382,7 @@ impl Plugin for SongWalkerPlugin {
             &self.params,
             &self.visualizer_state,
             &self.voice_count,
+            &self.monitor,
         );
 
         ProcessStatus::Normal
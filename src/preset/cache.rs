@@ -0,0 +1,196 @@
+//! Disk cache of library indexes, preset descriptors and decoded samples.
+//!
+//! Used in place of core's `DiskCache`, with the same methods. Every file
+//! is written atomically (see `net::atomic`) and ends with the SHA-256 of
+//! its contents. A file that is truncated or fails its checksum is deleted
+//! when read and reads as missing, so the caller downloads it again
+//! instead of failing on it forever.
+
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use super::integrity::sha256_hex;
+use crate::net::atomic::write_atomic;
+
+/// Length of the checksum trailer.
+const CHECKSUM_LEN: usize = 32;
+/// Sample rate (u32 LE) and hex SHA-256 of the source file, before the PCM.
+const SAMPLE_HEADER_LEN: usize = 4 + 64;
+
+/// A decoded sample as cached.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedSample {
+    /// Mono PCM.
+    pub pcm: Vec<f32>,
+    pub sample_rate: u32,
+    /// Hex SHA-256 of the file the PCM was decoded from, to check against
+    /// the hash a preset pins.
+    pub source_sha256: String,
+}
+
+/// The cache directory, or nothing at all when there is none (every read
+/// misses and writes are dropped).
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    root: Option<PathBuf>,
+}
+
+impl Default for DiskCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DiskCache {
+    /// The cache in the user cache directory.
+    pub fn new() -> Self {
+        let root = directories::ProjectDirs::from("org", "SongWalker", "songwalker-vsti")
+            .map(|d| d.cache_dir().join("library"));
+        Self { root }
+    }
+
+    /// A cache rooted at `root`.
+    pub fn at(root: PathBuf) -> Self {
+        Self { root: Some(root) }
+    }
+
+    pub fn ensure_dirs(&self) -> std::io::Result<()> {
+        let Some(root) = &self.root else { return Ok(()) };
+        for dir in ["indexes", "presets", "samples"] {
+            std::fs::create_dir_all(root.join(dir))?;
+        }
+        Ok(())
+    }
+
+    /// File for `key` in `dir`. Keys are hashed, so any library name, path
+    /// or URL makes a valid file name.
+    fn file(&self, dir: &str, key: &str) -> Option<PathBuf> {
+        Some(self.root.as_ref()?.join(dir).join(sha256_hex(key.as_bytes())))
+    }
+
+    fn read_text(&self, file: Option<PathBuf>) -> Option<String> {
+        String::from_utf8(read_checked(&file?)?).ok()
+    }
+
+    fn write(&self, file: Option<PathBuf>, bytes: &[u8]) -> std::io::Result<()> {
+        match file {
+            Some(file) => write_checked(&file, bytes),
+            None => Ok(()),
+        }
+    }
+
+    pub fn read_root_index(&self) -> Option<String> {
+        self.read_text(self.file("indexes", ""))
+    }
+
+    pub fn write_root_index(&self, text: &str) -> std::io::Result<()> {
+        self.write(self.file("indexes", ""), text.as_bytes())
+    }
+
+    /// Index of a library or sub-index ("library/sub").
+    pub fn read_library_index(&self, key: &str) -> Option<String> {
+        self.read_text(self.file("indexes", key))
+    }
+
+    pub fn write_library_index(&self, key: &str, text: &str) -> std::io::Result<()> {
+        self.write(self.file("indexes", key), text.as_bytes())
+    }
+
+    pub fn read_preset(&self, library: &str, path: &str) -> Option<String> {
+        self.read_text(self.file("presets", &format!("{}\n{}", library, path)))
+    }
+
+    pub fn write_preset(&self, library: &str, path: &str, text: &str) -> std::io::Result<()> {
+        self.write(self.file("presets", &format!("{}\n{}", library, path)), text.as_bytes())
+    }
+
+    fn sample_file(&self, library: &str, path: &str, key: &str) -> Option<PathBuf> {
+        self.file("samples", &format!("{}\n{}\n{}", library, path, key))
+    }
+
+    /// Decoded sample of a preset, by its cache key.
+    pub fn read_sample(&self, library: &str, path: &str, key: &str) -> Option<CachedSample> {
+        let bytes = read_checked(&self.sample_file(library, path, key)?)?;
+        if bytes.len() < SAMPLE_HEADER_LEN {
+            return None;
+        }
+        let (header, pcm) = bytes.split_at(SAMPLE_HEADER_LEN);
+        Some(CachedSample {
+            sample_rate: u32::from_le_bytes([header[0], header[1], header[2], header[3]]),
+            source_sha256: String::from_utf8_lossy(&header[4..]).into_owned(),
+            pcm: pcm.chunks_exact(4).map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect(),
+        })
+    }
+
+    pub fn write_sample(&self, library: &str, path: &str, key: &str, sample: &CachedSample) -> std::io::Result<()> {
+        if sample.source_sha256.len() != SAMPLE_HEADER_LEN - 4 {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "source hash is not hex SHA-256"));
+        }
+        let mut bytes = Vec::with_capacity(SAMPLE_HEADER_LEN + sample.pcm.len() * 4);
+        bytes.extend_from_slice(&sample.sample_rate.to_le_bytes());
+        bytes.extend_from_slice(sample.source_sha256.as_bytes());
+        bytes.extend(sample.pcm.iter().flat_map(|s| s.to_le_bytes()));
+        self.write(self.sample_file(library, path, key), &bytes)
+    }
+
+    /// Drop a cached sample so the next load downloads it again.
+    pub fn remove_sample(&self, library: &str, path: &str, key: &str) {
+        if let Some(file) = self.sample_file(library, path, key) {
+            let _ = std::fs::remove_file(file);
+        }
+    }
+}
+
+/// Write `payload` followed by its checksum.
+fn write_checked(path: &Path, payload: &[u8]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut bytes = Vec::with_capacity(payload.len() + CHECKSUM_LEN);
+    bytes.extend_from_slice(payload);
+    bytes.extend_from_slice(&Sha256::digest(payload));
+    write_atomic(path, &bytes)
+}
+
+/// Payload of a file written by `write_checked`, or `None` if it is
+/// missing or corrupt (a corrupt file is deleted).
+fn read_checked(path: &Path) -> Option<Vec<u8>> {
+    let mut bytes = std::fs::read(path).ok()?;
+    let valid = bytes.len() >= CHECKSUM_LEN && {
+        let (payload, checksum) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+        Sha256::digest(payload).as_slice() == checksum
+    };
+    if !valid {
+        nih_plug::debug::nih_log!("[Cache] Evicting corrupt {}", path.display());
+        let _ = std::fs::remove_file(path);
+        return None;
+    }
+    bytes.truncate(bytes.len() - CHECKSUM_LEN);
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corrupt_entries_read_as_missing() {
+        let dir = std::env::temp_dir().join(format!("sw-disk-cache-{}", std::process::id()));
+        let cache = DiskCache::at(dir.clone());
+        cache.write_preset("lib", "piano/preset.json", "{}").unwrap();
+        let sample = CachedSample { pcm: vec![0.5, -0.25], sample_rate: 22050, source_sha256: sha256_hex(b"wav") };
+        cache.write_sample("lib", "piano/preset.json", "C4.wav", &sample).unwrap();
+        assert_eq!(cache.read_preset("lib", "piano/preset.json").as_deref(), Some("{}"));
+        assert_eq!(cache.read_sample("lib", "piano/preset.json", "C4.wav"), Some(sample));
+        assert_eq!(cache.read_sample("lib", "other/preset.json", "C4.wav"), None);
+
+        // A torn write: the checksum no longer matches, and the file goes
+        let file = cache.file("samples", "lib\npiano/preset.json\nC4.wav").unwrap();
+        let bytes = std::fs::read(&file).unwrap();
+        std::fs::write(&file, &bytes[..bytes.len() - 3]).unwrap();
+        assert_eq!(cache.read_sample("lib", "piano/preset.json", "C4.wav"), None);
+        assert!(!file.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,137 @@
+//! Presets added to a library since the user last looked at it.
+//!
+//! When a refresh (see `revalidate`) replaces a cached library index, the
+//! presets the new index lists and the old one didn't are recorded here
+//! and highlighted in the browser. They stay new, across sessions, until
+//! the library's folder is collapsed after being browsed or its presets
+//! are marked as seen. Stored per machine in `new-presets.json`.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+/// New presets per library (library name → preset paths).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NewPresets {
+    libraries: HashMap<String, HashSet<String>>,
+}
+
+impl NewPresets {
+    /// Mark presets of `library` as new.
+    pub fn add(&mut self, library: &str, paths: impl IntoIterator<Item = String>) {
+        self.libraries.entry(library.to_string()).or_default().extend(paths);
+    }
+
+    pub fn count(&self, library: &str) -> usize {
+        self.libraries.get(library).map_or(0, HashSet::len)
+    }
+
+    pub fn is_new(&self, library: &str, path: &str) -> bool {
+        self.libraries.get(library).is_some_and(|paths| paths.contains(path))
+    }
+
+    /// Mark every preset of `library` as seen. Returns whether any were new.
+    pub fn clear(&mut self, library: &str) -> bool {
+        self.libraries.remove(library).is_some_and(|paths| !paths.is_empty())
+    }
+}
+
+/// Paths of the presets `new` lists and `old` doesn't (both library
+/// index JSON).
+pub fn added_presets(old: &serde_json::Value, new: &serde_json::Value) -> Vec<String> {
+    let old: HashSet<&str> = preset_paths(old).collect();
+    preset_paths(new)
+        .filter(|path| !old.contains(path))
+        .map(str::to_string)
+        .collect()
+}
+
+fn preset_paths(index: &serde_json::Value) -> impl Iterator<Item = &str> {
+    index
+        .get("entries")
+        .and_then(|e| e.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.get("type").and_then(|t| t.as_str()) == Some("preset"))
+        .filter_map(|entry| entry.get("path").and_then(|p| p.as_str()))
+}
+
+fn store_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("org", "SongWalker", "songwalker-vsti")
+        .map(|d| d.data_dir().join("new-presets.json"))
+}
+
+/// The process-wide record (loaded from disk on first use).
+pub fn global() -> &'static Mutex<NewPresets> {
+    static STORE: OnceLock<Mutex<NewPresets>> = OnceLock::new();
+    STORE.get_or_init(|| {
+        let loaded = store_path()
+            .and_then(|p| std::fs::read(p).ok())
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Mutex::new(loaded)
+    })
+}
+
+fn save(new_presets: &NewPresets) {
+    let Some(path) = store_path() else { return };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let result = serde_json::to_vec_pretty(new_presets)
+        .map_err(|e| e.to_string())
+        .and_then(|json| crate::net::atomic::write_atomic(&path, &json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        log::warn!("Failed to save new presets: {}", e);
+    }
+}
+
+/// Record presets added to `library` and save.
+pub fn record(library: &str, paths: Vec<String>) {
+    if paths.is_empty() {
+        return;
+    }
+    if let Ok(mut new_presets) = global().lock() {
+        new_presets.add(library, paths);
+        save(&new_presets);
+    }
+}
+
+/// Mark every preset of `library` as seen and save.
+pub fn mark_seen(library: &str) {
+    if let Ok(mut new_presets) = global().lock() {
+        if new_presets.clear(library) {
+            save(&new_presets);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_added_presets_and_marking_seen() {
+        let old = serde_json::json!({ "entries": [
+            { "type": "preset", "path": "piano" },
+            { "type": "index", "path": "games" },
+        ]});
+        let new = serde_json::json!({ "entries": [
+            { "type": "preset", "path": "piano" },
+            { "type": "preset", "path": "organ" },
+        ]});
+        let added = added_presets(&old, &new);
+        assert_eq!(added, vec!["organ".to_string()]);
+
+        let mut new_presets = NewPresets::default();
+        new_presets.add("Lib", added);
+        assert_eq!(new_presets.count("Lib"), 1);
+        assert!(new_presets.is_new("Lib", "organ"));
+        assert!(!new_presets.is_new("Lib", "piano"));
+        assert!(new_presets.clear("Lib"));
+        assert!(!new_presets.clear("Lib"), "nothing left to clear");
+    }
+}
@@ -0,0 +1,153 @@
+//! Sample decoding for preset loads: WAV, MP3 and raw PCM to f32.
+//!
+//! Samples are mixed down to mono, which is how the disk cache stores
+//! them, so a freshly decoded zone plays the same as a cached one.
+
+use base64::Engine as _;
+use songwalker_core::preset::{AudioCodec, AudioReference};
+
+/// A decoded sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+    /// Mono PCM.
+    pub pcm: Vec<f32>,
+    /// Sample rate of the file, if it says.
+    pub sample_rate: Option<u32>,
+}
+
+/// Codec of an audio reference (inline PCM is raw).
+pub fn codec_of(audio: &AudioReference) -> AudioCodec {
+    match audio {
+        AudioReference::External { codec, .. }
+        | AudioReference::InlineFile { codec, .. }
+        | AudioReference::ContentAddressed { codec, .. } => codec.clone(),
+        AudioReference::InlinePcm { .. } => AudioCodec::Raw,
+    }
+}
+
+/// Bytes of an inline sample, or `None` for one that has to be fetched.
+pub fn inline_bytes(audio: &AudioReference) -> Option<Result<Vec<u8>, String>> {
+    let data = match audio {
+        AudioReference::InlineFile { data, .. } | AudioReference::InlinePcm { data, .. } => data,
+        _ => return None,
+    };
+    Some(
+        base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| format!("Failed to decode inline sample: {}", e)),
+    )
+}
+
+/// Decode a sample file (or inline PCM) of `audio`.
+pub fn decode(bytes: &[u8], audio: &AudioReference) -> Result<Decoded, String> {
+    if bytes.is_empty() {
+        return Err("Cannot decode empty audio data".to_string());
+    }
+    let decoded = match (audio, codec_of(audio)) {
+        (AudioReference::InlinePcm { bits_per_sample, .. }, _) => {
+            Decoded { pcm: decode_raw_pcm(bytes, *bits_per_sample), sample_rate: None }
+        }
+        (_, AudioCodec::Mp3) => decode_mp3(bytes)?,
+        (_, AudioCodec::Wav) => decode_wav(bytes)?,
+        // Raw = 16-bit signed LE PCM
+        (_, AudioCodec::Raw) => Decoded { pcm: decode_raw_pcm(bytes, 16), sample_rate: None },
+        (_, codec) => return Err(format!("Unsupported codec: {:?}", codec)),
+    };
+    if decoded.pcm.is_empty() {
+        return Err(format!("Decoded 0 samples from {} bytes", bytes.len()));
+    }
+    Ok(decoded)
+}
+
+/// Average interleaved frames of `channels` down to mono.
+fn mix_down(interleaved: Vec<f32>, channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return interleaved;
+    }
+    interleaved
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+fn decode_mp3(bytes: &[u8]) -> Result<Decoded, String> {
+    let mut decoder = minimp3::Decoder::new(std::io::Cursor::new(bytes));
+    let mut pcm = Vec::new();
+    let mut sample_rate = None;
+    loop {
+        match decoder.next_frame() {
+            Ok(frame) => {
+                sample_rate.get_or_insert(frame.sample_rate as u32);
+                let samples = frame.data.iter().map(|s| *s as f32 / 32768.0).collect();
+                pcm.extend(mix_down(samples, frame.channels));
+            }
+            Err(minimp3::Error::Eof) => break,
+            Err(e) => return Err(format!("MP3 decode error: {:?}", e)),
+        }
+    }
+    Ok(Decoded { pcm, sample_rate })
+}
+
+fn decode_wav(bytes: &[u8]) -> Result<Decoded, String> {
+    let reader = hound::WavReader::new(std::io::Cursor::new(bytes))
+        .map_err(|e| format!("WAV decode error: {}", e))?;
+    let spec = reader.spec();
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Int => {
+            let scale = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+            reader.into_samples::<i32>().filter_map(|s| s.ok()).map(|s| s as f32 / scale).collect()
+        }
+        hound::SampleFormat::Float => reader.into_samples::<f32>().filter_map(|s| s.ok()).collect(),
+    };
+    Ok(Decoded {
+        pcm: mix_down(samples, spec.channels as usize),
+        sample_rate: Some(spec.sample_rate),
+    })
+}
+
+/// Little-endian PCM of 16, 24 or 32 (float) bits; anything else is read
+/// as 16-bit.
+fn decode_raw_pcm(bytes: &[u8], bits_per_sample: u8) -> Vec<f32> {
+    match bits_per_sample {
+        24 => bytes
+            .chunks_exact(3)
+            .map(|c| {
+                // Sign-extend from 24 bits
+                let v = i32::from_le_bytes([0, c[0], c[1], c[2]]) >> 8;
+                v as f32 / 8_388_608.0
+            })
+            .collect(),
+        32 => bytes.chunks_exact(4).map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect(),
+        _ => bytes.chunks_exact(2).map(|c| i16::from_le_bytes([c[0], c[1]]) as f32 / 32768.0).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decodes_stereo_wav_to_mono_and_raw_pcm() {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 22050,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+        for (l, r) in [(16384_i16, 0_i16), (-16384, -16384)] {
+            writer.write_sample(l).unwrap();
+            writer.write_sample(r).unwrap();
+        }
+        writer.finalize().unwrap();
+        let audio = AudioReference::External { url: "a.wav".into(), codec: AudioCodec::Wav, sha256: None };
+
+        let decoded = decode(&cursor.into_inner(), &audio).unwrap();
+        assert_eq!(decoded, Decoded { pcm: vec![0.25, -0.5], sample_rate: Some(22050) });
+        assert!(decode(&[], &audio).is_err());
+
+        assert_eq!(decode_raw_pcm(&[0x00, 0x00, 0x80], 24), vec![-1.0]);
+        assert_eq!(decode_raw_pcm(&[0x00, 0x40], 16), vec![0.5]);
+    }
+}
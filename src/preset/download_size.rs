@@ -0,0 +1,129 @@
+//! Download size checks before a preset is loaded.
+//!
+//! The size estimate comes from the preset descriptor (see
+//! `editor::preset_info`): inline sample data, sizes declared on external
+//! samples, and a HEAD request for the ones that don't declare one. This
+//! module compares an estimate against the user's limits in
+//! [`NetworkSettings`] and measures how much the cache already holds.
+
+use std::path::Path;
+
+use super::memory::format_bytes;
+use crate::net::NetworkSettings;
+
+const MB: u64 = 1024 * 1024;
+
+/// Size limits that make a load ask first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadLimits {
+    /// A single preset larger than this asks (0 = never).
+    pub warn_bytes: u64,
+    /// A download that would grow the cache past this asks (0 = no limit).
+    pub cache_limit_bytes: u64,
+}
+
+impl DownloadLimits {
+    pub fn from_settings(settings: &NetworkSettings) -> Self {
+        Self {
+            warn_bytes: settings.warn_download_mb.saturating_mul(MB),
+            cache_limit_bytes: settings.cache_limit_mb.saturating_mul(MB),
+        }
+    }
+}
+
+/// Why a load needs confirmation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeWarning {
+    /// The preset alone is over the warning size.
+    Large { estimate: u64, threshold: u64 },
+    /// The cache would go over its limit.
+    CacheFull { estimate: u64, used: u64, limit: u64 },
+}
+
+impl SizeWarning {
+    pub fn message(&self) -> String {
+        match *self {
+            SizeWarning::Large { estimate, threshold } => format!(
+                "This preset downloads about {}, more than the {} warning size.",
+                format_bytes(estimate as usize),
+                format_bytes(threshold as usize)
+            ),
+            SizeWarning::CacheFull { estimate, used, limit } => format!(
+                "Downloading about {} would take the cache ({} used) past its {} limit.",
+                format_bytes(estimate as usize),
+                format_bytes(used as usize),
+                format_bytes(limit as usize)
+            ),
+        }
+    }
+}
+
+/// Warnings for downloading `estimate` bytes into a cache holding `used`.
+pub fn check(estimate: u64, used: u64, limits: DownloadLimits) -> Vec<SizeWarning> {
+    let mut warnings = Vec::new();
+    if limits.warn_bytes > 0 && estimate > limits.warn_bytes {
+        warnings.push(SizeWarning::Large { estimate, threshold: limits.warn_bytes });
+    }
+    if limits.cache_limit_bytes > 0 && used.saturating_add(estimate) > limits.cache_limit_bytes {
+        warnings.push(SizeWarning::CacheFull { estimate, used, limit: limits.cache_limit_bytes });
+    }
+    warnings
+}
+
+/// Bytes held in the user cache directory (downloads, decoded samples and
+/// HTTP copies).
+pub fn cache_usage() -> u64 {
+    directories::ProjectDirs::from("org", "SongWalker", "songwalker-vsti")
+        .map(|d| dir_size(d.cache_dir()))
+        .unwrap_or(0)
+}
+
+/// Total size of the files under `dir` (symlinks are not followed).
+pub fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            Ok(t) if t.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_applies_each_limit() {
+        let limits = DownloadLimits { warn_bytes: 100 * MB, cache_limit_bytes: 1000 * MB };
+        assert!(check(50 * MB, 0, limits).is_empty());
+        assert_eq!(
+            check(150 * MB, 0, limits),
+            vec![SizeWarning::Large { estimate: 150 * MB, threshold: 100 * MB }]
+        );
+        assert_eq!(
+            check(50 * MB, 980 * MB, limits),
+            vec![SizeWarning::CacheFull { estimate: 50 * MB, used: 980 * MB, limit: 1000 * MB }]
+        );
+        assert_eq!(check(150 * MB, 900 * MB, limits).len(), 2);
+
+        // Zero turns a limit off
+        let off = DownloadLimits { warn_bytes: 0, cache_limit_bytes: 0 };
+        assert!(check(u64::MAX, u64::MAX, off).is_empty());
+    }
+
+    #[test]
+    fn test_dir_size_counts_nested_files() {
+        let dir = std::env::temp_dir().join(format!("sw-dir-size-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("a/b")).unwrap();
+        std::fs::write(dir.join("one.bin"), [0u8; 10]).unwrap();
+        std::fs::write(dir.join("a/b/two.bin"), [0u8; 32]).unwrap();
+        assert_eq!(dir_size(&dir), 42);
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(dir_size(&dir), 0);
+    }
+}
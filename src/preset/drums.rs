@@ -0,0 +1,160 @@
+//! General MIDI drum map awareness.
+//!
+//! Drum presets (sampler graphs flagged `is_drum_kit`, or tagged as drums /
+//! GM channel 10) get GM drum names instead of note names in the editor, and
+//! play one-shot: note-offs are ignored and the GS exclusive classes choke
+//! each other (a closed hi-hat cuts an open one).
+
+use songwalker_core::preset::{PresetDescriptor, PresetNode};
+
+/// First note of the GM percussion map.
+const FIRST_DRUM_NOTE: u8 = 35;
+
+/// GM percussion key map, notes 35–81: (full name, short key label).
+const GM_DRUMS: [(&str, &str); 47] = [
+    ("Acoustic Bass Drum", "Kick"),
+    ("Bass Drum 1", "Kick"),
+    ("Side Stick", "Stick"),
+    ("Acoustic Snare", "Snare"),
+    ("Hand Clap", "Clap"),
+    ("Electric Snare", "Snare"),
+    ("Low Floor Tom", "Tom"),
+    ("Closed Hi-Hat", "HH"),
+    ("High Floor Tom", "Tom"),
+    ("Pedal Hi-Hat", "PHH"),
+    ("Low Tom", "Tom"),
+    ("Open Hi-Hat", "OHH"),
+    ("Low-Mid Tom", "Tom"),
+    ("Hi-Mid Tom", "Tom"),
+    ("Crash Cymbal 1", "Crash"),
+    ("High Tom", "Tom"),
+    ("Ride Cymbal 1", "Ride"),
+    ("Chinese Cymbal", "China"),
+    ("Ride Bell", "Bell"),
+    ("Tambourine", "Tamb"),
+    ("Splash Cymbal", "Splash"),
+    ("Cowbell", "Cow"),
+    ("Crash Cymbal 2", "Crash"),
+    ("Vibraslap", "Vibra"),
+    ("Ride Cymbal 2", "Ride"),
+    ("Hi Bongo", "Bongo"),
+    ("Low Bongo", "Bongo"),
+    ("Mute Hi Conga", "Conga"),
+    ("Open Hi Conga", "Conga"),
+    ("Low Conga", "Conga"),
+    ("High Timbale", "Timb"),
+    ("Low Timbale", "Timb"),
+    ("High Agogo", "Agogo"),
+    ("Low Agogo", "Agogo"),
+    ("Cabasa", "Cabasa"),
+    ("Maracas", "Maraca"),
+    ("Short Whistle", "Whstl"),
+    ("Long Whistle", "Whstl"),
+    ("Short Guiro", "Guiro"),
+    ("Long Guiro", "Guiro"),
+    ("Claves", "Claves"),
+    ("Hi Wood Block", "Block"),
+    ("Low Wood Block", "Block"),
+    ("Mute Cuica", "Cuica"),
+    ("Open Cuica", "Cuica"),
+    ("Mute Triangle", "Tri"),
+    ("Open Triangle", "Tri"),
+];
+
+/// Tags that mark a preset as percussion (compared case-insensitively).
+const DRUM_TAGS: &[&str] = &[
+    "drums",
+    "drum kit",
+    "drum-kit",
+    "drumkit",
+    "percussion",
+    "gm-percussion",
+    "channel 10",
+    "channel-10",
+];
+
+fn entry(note: u8) -> Option<&'static (&'static str, &'static str)> {
+    GM_DRUMS.get(note.checked_sub(FIRST_DRUM_NOTE)? as usize)
+}
+
+/// GM drum name of a note ("Acoustic Snare"), if it is in the map.
+pub fn gm_drum_name(note: u8) -> Option<&'static str> {
+    entry(note).map(|(name, _)| *name)
+}
+
+/// Short label for a piano key ("Snare", "HH").
+pub fn gm_drum_short_name(note: u8) -> Option<&'static str> {
+    entry(note).map(|(_, short)| *short)
+}
+
+/// GS exclusive class: notes in the same class cut each other off.
+pub fn exclusive_class(note: u8) -> Option<u8> {
+    match note {
+        42 | 44 | 46 => Some(1), // hi-hats
+        71 | 72 => Some(2),      // whistles
+        73 | 74 => Some(3),      // guiros
+        78 | 79 => Some(4),      // cuicas
+        80 | 81 => Some(5),      // triangles
+        _ => None,
+    }
+}
+
+/// Whether a preset is a drum kit. Allocation-free (runs on the audio
+/// thread when a preset is loaded).
+pub fn is_drum_kit(descriptor: &PresetDescriptor) -> bool {
+    graph_is_drum_kit(&descriptor.graph) || has_drum_tag(&descriptor.tags)
+}
+
+/// Whether any of a preset's tags marks it as percussion.
+pub fn has_drum_tag(tags: &[String]) -> bool {
+    tags.iter().any(|tag| DRUM_TAGS.iter().any(|d| tag.eq_ignore_ascii_case(d)))
+}
+
+fn graph_is_drum_kit(node: &PresetNode) -> bool {
+    match node {
+        PresetNode::Sampler { config } => config.is_drum_kit,
+        PresetNode::Composite { children, .. } => children.iter().any(graph_is_drum_kit),
+        _ => false,
+    }
+}
+
+/// Display name for a note: the GM drum name on drum kits (falling back to
+/// the note name outside the map), otherwise the note name.
+pub fn note_label(note: u8, drum_kit: bool) -> String {
+    let name = crate::editor::piano::note_name(note);
+    match gm_drum_name(note).filter(|_| drum_kit) {
+        Some(drum) => format!("{} ({})", drum, name),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gm_drum_names() {
+        assert_eq!(gm_drum_name(35), Some("Acoustic Bass Drum"));
+        assert_eq!(gm_drum_name(38), Some("Acoustic Snare"));
+        assert_eq!(gm_drum_name(42), Some("Closed Hi-Hat"));
+        assert_eq!(gm_drum_name(81), Some("Open Triangle"));
+        assert_eq!(gm_drum_name(34), None);
+        assert_eq!(gm_drum_name(82), None);
+        assert_eq!(gm_drum_short_name(36), Some("Kick"));
+    }
+
+    #[test]
+    fn test_hi_hats_share_a_class() {
+        assert_eq!(exclusive_class(42), exclusive_class(46));
+        assert_eq!(exclusive_class(44), exclusive_class(46));
+        assert_ne!(exclusive_class(42), exclusive_class(80));
+        assert_eq!(exclusive_class(38), None);
+    }
+
+    #[test]
+    fn test_note_label() {
+        assert_eq!(note_label(38, true), "Acoustic Snare (D2)");
+        assert_eq!(note_label(38, false), "D2");
+        assert_eq!(note_label(100, true), "E7");
+    }
+}
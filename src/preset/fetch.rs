@@ -0,0 +1,240 @@
+//! Preset downloads.
+//!
+//! Every preset load from this crate goes through [`PresetFetcher`]
+//! rather than core's `PresetLoader`: requests are made by
+//! [`HttpClient`], so they carry the credentials configured for their
+//! library (see `net::auth`). Descriptors and decoded samples are kept in
+//! the disk cache, which is read before the network. Samples a preset pins
+//! with a `sha256` are checked as they are downloaded, before decoding, and
+//! a cached sample is only used if it was decoded from the pinned file.
+
+use std::sync::Arc;
+
+use songwalker_core::preset::{AudioReference, PresetDescriptor, PresetNode, SampleZone};
+
+use super::cache::{CachedSample, DiskCache};
+use super::decode;
+use super::instance::{LoadedZone, PresetInstance};
+use super::integrity;
+use super::sample_cache::{self, SampleCache};
+use crate::net::{FetchError, HttpClient};
+
+/// Downloads presets of one library host.
+pub struct PresetFetcher {
+    client: HttpClient,
+    base_url: String,
+    cache: DiskCache,
+    samples: &'static SampleCache,
+}
+
+impl PresetFetcher {
+    /// Fetcher for the library at `base_url`, with the default HTTP store.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_client(HttpClient::with_default_store(), base_url)
+    }
+
+    pub fn with_client(client: HttpClient, base_url: impl Into<String>) -> Self {
+        Self { client, base_url: base_url.into(), cache: DiskCache::new(), samples: sample_cache::global() }
+    }
+
+    /// Share samples through `samples` instead of the process-wide cache.
+    pub fn with_samples(mut self, samples: &'static SampleCache) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Fetch a preset's descriptor and all its samples. Each download is
+    /// retried by the client; the error says whether the host was
+    /// unreachable.
+    pub async fn load_preset(&self, library: &str, preset_path: &str) -> Result<PresetInstance, FetchError> {
+        let descriptor = self.descriptor(library, preset_path).await?;
+        let mut zones = Vec::new();
+        for zone in sample_zones(&descriptor.graph) {
+            zones.push(self.load_zone(library, preset_path, zone).await?);
+        }
+        Ok(PresetInstance { descriptor, zones })
+    }
+
+    async fn descriptor(&self, library: &str, preset_path: &str) -> Result<PresetDescriptor, FetchError> {
+        if let Some(cached) = self.cache.read_preset(library, preset_path) {
+            if let Ok(descriptor) = serde_json::from_str(&cached) {
+                return Ok(descriptor);
+            }
+        }
+        let url = format!("{}/{}/{}", self.base_url, library, preset_path);
+        let text = String::from_utf8_lossy(&self.client.get(&url).await?).into_owned();
+        let descriptor = serde_json::from_str(&text)
+            .map_err(|e| FetchError::Other(format!("Failed to parse preset {}/{}: {}", library, preset_path, e)))?;
+        let _ = self.cache.write_preset(library, preset_path, &text);
+        Ok(descriptor)
+    }
+
+    /// URL a sample is downloaded from, or `None` for inline data.
+    fn sample_url(&self, library: &str, preset_path: &str, audio: &AudioReference) -> Option<String> {
+        match audio {
+            AudioReference::External { url, .. } => {
+                Some(integrity::sample_url(&self.base_url, library, preset_path, url))
+            }
+            AudioReference::ContentAddressed { hash, .. } => Some(format!("{}/{}/{}", self.base_url, library, hash)),
+            AudioReference::InlineFile { .. } | AudioReference::InlinePcm { .. } => None,
+        }
+    }
+
+    /// Load one zone's sample: the copy another loaded preset shares, the
+    /// cached PCM if it was decoded from the file the zone pins, else
+    /// downloaded. A pinned sample's raw bytes are
+    /// checked before they are decoded; a mismatch fails the load.
+    async fn load_zone(&self, library: &str, preset_path: &str, zone: &SampleZone) -> Result<LoadedZone, FetchError> {
+        let key = cache_key(&zone.audio);
+        let pinned = match &zone.audio {
+            AudioReference::External { sha256: Some(hash), .. } => Some(hash.as_str()),
+            _ => None,
+        };
+        // Share PCM with presets already in memory that use the same sample
+        let shared_key = sample_cache::sample_key(library, preset_path, &zone.audio);
+        let loaded = |pcm: Arc<[f32]>, sample_rate: u32| LoadedZone {
+            zone: zone.clone(),
+            pcm_data: match &shared_key {
+                Some(shared_key) => self.samples.intern(shared_key, pcm, sample_rate),
+                None => pcm,
+            },
+            channels: 1,
+            sample_rate,
+        };
+        if let Some((pcm, sample_rate)) = shared_key.as_deref().and_then(|k| self.samples.lookup(k)) {
+            return Ok(LoadedZone { zone: zone.clone(), pcm_data: pcm, channels: 1, sample_rate });
+        }
+        if let Some(cached) = self.cache.read_sample(library, preset_path, &key) {
+            match pinned {
+                Some(hash) if integrity::check_hash(&key, &cached.source_sha256, hash).is_err() => {
+                    nih_plug::debug::nih_log!("[Fetch] Cached {} doesn't match its pin, downloading it again", key);
+                }
+                _ => return Ok(loaded(Arc::from(cached.pcm), cached.sample_rate)),
+            }
+        }
+
+        let url = self.sample_url(library, preset_path, &zone.audio);
+        let bytes = match (decode::inline_bytes(&zone.audio), &url) {
+            (Some(bytes), _) => bytes?,
+            (None, Some(url)) => self.client.get(url).await?,
+            (None, None) => return Err(FetchError::Other(format!("No source for sample {}", key))),
+        };
+        let source_sha256 = integrity::sha256_hex(&bytes);
+        if let Some(hash) = pinned {
+            integrity::check_hash(url.as_deref().unwrap_or(&key), &source_sha256, hash)?;
+        }
+        let decoded = decode::decode(&bytes, &zone.audio)?;
+        let sample = CachedSample {
+            pcm: decoded.pcm,
+            sample_rate: decoded.sample_rate.unwrap_or(zone.sample_rate),
+            source_sha256,
+        };
+        let _ = self.cache.write_sample(library, preset_path, &key, &sample);
+        Ok(loaded(Arc::from(sample.pcm), sample.sample_rate))
+    }
+}
+
+/// Every sampler zone of a preset graph, in the order the zones are
+/// flattened into `PresetInstance::zones`.
+pub fn sample_zones(node: &PresetNode) -> Vec<&SampleZone> {
+    match node {
+        PresetNode::Sampler { config } => config.zones.iter().collect(),
+        PresetNode::Composite { children, .. } => children.iter().flat_map(|c| sample_zones(c)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Disk cache key of a sample (an external sample's is its URL as the
+/// preset gives it).
+fn cache_key(audio: &AudioReference) -> String {
+    match audio {
+        AudioReference::External { url, .. } => url.clone(),
+        AudioReference::ContentAddressed { hash, .. } => hash.clone(),
+        AudioReference::InlineFile { data, .. } => format!("inline:{}", integrity::sha256_hex(data.as_bytes())),
+        AudioReference::InlinePcm { data, .. } => format!("inline-pcm:{}", integrity::sha256_hex(data.as_bytes())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::mock::{FIXTURE_LIBRARY, FIXTURE_PRESET, FIXTURE_SAMPLE, MockLibrary, block_on};
+    use crate::net::{RetryPolicy, ValidatorStore};
+    use std::time::Duration;
+
+    /// Fetcher for `server` with its own sample cache, so tests running at
+    /// the same time don't share the fixture's sample.
+    fn fetcher(server: &MockLibrary, retry: RetryPolicy) -> PresetFetcher {
+        let client = HttpClient::new(ValidatorStore::in_memory()).with_retry(retry);
+        PresetFetcher::with_client(client, server.url()).with_samples(Box::leak(Box::default()))
+    }
+
+    #[test]
+    fn test_loads_preset_from_mock_library() {
+        // A library slug nothing has cached, so everything comes from the server
+        let library = format!("{}-{}", FIXTURE_LIBRARY, std::process::id());
+        let server = MockLibrary::fixture_as(&library);
+        let fetcher = fetcher(&server, RetryPolicy::NONE);
+
+        let instance = block_on(fetcher.load_preset(&library, FIXTURE_PRESET)).unwrap();
+        assert_eq!(instance.descriptor.name, "Mock Piano");
+        assert_eq!(instance.zones.len(), 1);
+        assert_eq!((instance.zones[0].pcm_data.len(), instance.zones[0].sample_rate), (11025, 44100));
+        assert_eq!(server.hits(&format!("{}/{}", library, FIXTURE_SAMPLE)), 1);
+
+        let missing = block_on(fetcher.load_preset(&library, "missing/preset.json"));
+        assert_eq!(missing.unwrap_err().status(), Some(404));
+    }
+
+    #[test]
+    fn test_checks_pinned_samples_before_decoding() {
+        let library = format!("{}-pinned-{}", FIXTURE_LIBRARY, std::process::id());
+        let server = MockLibrary::fixture_as(&library);
+        let fetcher = fetcher(&server, RetryPolicy::NONE);
+        let sample = format!("{}/{}", library, FIXTURE_SAMPLE);
+
+        // A cached copy decoded from some other file is downloaded again
+        let stale = CachedSample { pcm: vec![0.0; 4], sample_rate: 44100, source_sha256: integrity::sha256_hex(b"old") };
+        fetcher.cache.write_sample(&library, FIXTURE_PRESET, "C4.wav", &stale).unwrap();
+        let instance = block_on(fetcher.load_preset(&library, FIXTURE_PRESET)).unwrap();
+        assert_eq!(instance.zones[0].pcm_data.len(), 11025);
+        assert_eq!(server.hits(&sample), 1);
+        drop(instance);
+
+        // A download that doesn't match the pin fails the load
+        fetcher.cache.remove_sample(&library, FIXTURE_PRESET, "C4.wav");
+        server.set(&sample, b"not the sample".to_vec());
+        let err = block_on(fetcher.load_preset(&library, FIXTURE_PRESET)).unwrap_err();
+        assert!(err.to_string().starts_with("Checksum mismatch"), "{}", err);
+    }
+
+    #[test]
+    fn test_shares_samples_already_in_memory() {
+        let library = format!("{}-shared-{}", FIXTURE_LIBRARY, std::process::id());
+        let server = MockLibrary::fixture_as(&library);
+        let fetcher = fetcher(&server, RetryPolicy::NONE);
+        let first = block_on(fetcher.load_preset(&library, FIXTURE_PRESET)).unwrap();
+
+        // Neither the disk cache nor the server is asked again
+        fetcher.cache.remove_sample(&library, FIXTURE_PRESET, "C4.wav");
+        server.remove(&format!("{}/{}", library, FIXTURE_SAMPLE));
+        let second = block_on(fetcher.load_preset(&library, FIXTURE_PRESET)).unwrap();
+        assert!(Arc::ptr_eq(&first.zones[0].pcm_data, &second.zones[0].pcm_data));
+        assert_eq!(second.zones[0].sample_rate, 44100);
+        assert_eq!(fetcher.samples.stats().samples, 1);
+    }
+
+    #[test]
+    fn test_retries_a_failing_sample_download() {
+        let library = format!("{}-retry-{}", FIXTURE_LIBRARY, std::process::id());
+        let server = MockLibrary::fixture_as(&library);
+        let retry = RetryPolicy { max_attempts: 3, base_delay: Duration::ZERO, max_delay: Duration::ZERO, jitter: 0.0 };
+        let fetcher = fetcher(&server, retry);
+
+        let sample = format!("{}/{}", library, FIXTURE_SAMPLE);
+        server.fail(&sample, 503, 2);
+        let instance = block_on(fetcher.load_preset(&library, FIXTURE_PRESET)).unwrap();
+        assert_eq!(instance.zones.len(), 1);
+        assert_eq!(server.hits(&sample), 3);
+    }
+}
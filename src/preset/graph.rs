@@ -0,0 +1,499 @@
+//! Synth and effect nodes read from a preset's descriptor.
+//!
+//! The core loader only extracts sample zones from a preset graph, so
+//! oscillator and effect nodes would otherwise play as the sine fallback.
+//! Like the info panel, this reads the cached descriptor JSON directly and
+//! accepts the spellings library presets use ("type" or "kind", config
+//! inline or under "config"). The result is `Copy` and sent to the audio
+//! thread alongside the `PresetInstance`.
+//!
+//! A layering composite also keeps its children apart: the loader
+//! flattens their zones into one list, so each child becomes a [`Layer`]
+//! holding its slice of that list with its own gain, pan and key range.
+//!
+//! Sampler envelopes (`SamplerConfig::envelope`, or an `envelope` on a
+//! zone) are kept per run of zones in flattened order, so voices started
+//! on a zone release the way the preset was authored.
+
+use std::ops::Range;
+
+use serde_json::Value;
+
+use crate::slots::effects::{Effect, EffectChain};
+use crate::slots::slot::EnvelopeParams;
+use crate::slots::synth::{Oscillator, SynthPatch, Waveform};
+
+/// Children of a layering composite kept apart; further ones are dropped.
+pub const MAX_LAYERS: usize = 16;
+
+/// Runs of zones with their own envelope; zones past the last run that
+/// fits use the slot's envelope.
+pub const MAX_ENVELOPE_RUNS: usize = 32;
+
+/// Composite modes that pick one child rather than layering them.
+/// Keyswitched articulations are handled by the keyswitch map, and
+/// velocity splits by the preset's own zone lookup.
+const SELECTING_MODES: &[&str] = &["keyswitch", "switch", "articulation", "articulations", "select", "velocity"];
+
+/// One child of a layering composite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layer {
+    /// First of the child's zones in the flattened `PresetInstance::zones`.
+    pub zone_start: usize,
+    pub zone_count: usize,
+    /// Linear gain and balance (−1..+1) of the child.
+    pub gain: f32,
+    pub pan: f32,
+    /// Keys the child plays.
+    pub key_low: u8,
+    pub key_high: u8,
+    /// Oscillators of a synth child.
+    pub synth: Option<SynthPatch>,
+}
+
+impl Default for Layer {
+    fn default() -> Self {
+        Self {
+            zone_start: 0,
+            zone_count: 0,
+            gain: 1.0,
+            pan: 0.0,
+            key_low: 0,
+            key_high: 127,
+            synth: None,
+        }
+    }
+}
+
+impl Layer {
+    pub fn zones(&self) -> Range<usize> {
+        self.zone_start..self.zone_start + self.zone_count
+    }
+
+    pub fn covers(&self, note: u8) -> bool {
+        self.key_low <= note && note <= self.key_high
+    }
+
+    /// Per-channel gain: the child's gain with its balance (unity at centre).
+    pub fn channel_gains(&self) -> (f32, f32) {
+        let pan = self.pan.clamp(-1.0, 1.0);
+        (self.gain * (1.0 - pan).min(1.0), self.gain * (1.0 + pan).min(1.0))
+    }
+}
+
+/// Children of a layering composite, in descriptor order.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Layers {
+    layers: [Layer; MAX_LAYERS],
+    count: usize,
+}
+
+impl Layers {
+    /// Add a layer; false if there are already `MAX_LAYERS`.
+    pub fn push(&mut self, layer: Layer) -> bool {
+        if self.count == MAX_LAYERS {
+            return false;
+        }
+        self.layers[self.count] = layer;
+        self.count += 1;
+        true
+    }
+
+    pub fn as_slice(&self) -> &[Layer] {
+        &self.layers[..self.count]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.count = 0;
+    }
+}
+
+/// Preset-provided envelopes by zone index (flattened order).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ZoneEnvelopes {
+    /// (first zone, end zone, envelope), ascending and non-overlapping.
+    runs: [(usize, usize, EnvelopeParams); MAX_ENVELOPE_RUNS],
+    count: usize,
+    /// Zones described, with or without an envelope.
+    zone_total: usize,
+}
+
+impl ZoneEnvelopes {
+    /// Record the envelope of the next zone.
+    pub fn push_zone(&mut self, envelope: Option<EnvelopeParams>) {
+        let zone = self.zone_total;
+        self.zone_total += 1;
+        let Some(envelope) = envelope else {
+            return;
+        };
+        if let Some(last) = self.runs[..self.count].last_mut() {
+            if last.1 == zone && last.2 == envelope {
+                last.1 += 1;
+                return;
+            }
+        }
+        if self.count == MAX_ENVELOPE_RUNS {
+            return;
+        }
+        self.runs[self.count] = (zone, zone + 1, envelope);
+        self.count += 1;
+    }
+
+    /// Envelope the preset gives the zone, if any.
+    pub fn for_zone(&self, zone: usize) -> Option<EnvelopeParams> {
+        self.runs[..self.count]
+            .iter()
+            .find(|(start, end, _)| (*start..*end).contains(&zone))
+            .map(|(_, _, envelope)| *envelope)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn zone_total(&self) -> usize {
+        self.zone_total
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Audio-side view of a preset's non-sampler nodes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PresetGraph {
+    /// Oscillator stack of the synth nodes (None = no synth node).
+    pub synth: Option<SynthPatch>,
+    /// Effect nodes in signal order.
+    pub effects: EffectChain,
+    /// Children of a layering composite at the root (none otherwise).
+    pub layers: Layers,
+    /// Envelopes of the sampler nodes and zones.
+    pub envelopes: ZoneEnvelopes,
+}
+
+impl PresetGraph {
+    /// Read a descriptor's JSON text. Unreadable descriptors give an empty
+    /// graph (samples still play).
+    pub fn parse(text: &str) -> Self {
+        let Ok(json) = serde_json::from_str::<Value>(text) else {
+            return Self::default();
+        };
+        let root = json.get("graph").unwrap_or(&json);
+        let mut graph = Self::default();
+        graph.walk(root);
+        graph.read_layers(root);
+        graph.read_envelopes(root, None);
+        graph
+    }
+
+    /// Record each zone's envelope, depth-first in the loader's order. A
+    /// zone's own envelope wins over its sampler's, which wins over an
+    /// enclosing composite's.
+    fn read_envelopes(&mut self, node: &Value, inherited: Option<EnvelopeParams>) {
+        let config = node.get("config").unwrap_or(node);
+        let own = config.get("envelope").and_then(parse_envelope).or(inherited);
+        match node_type(node).as_deref() {
+            Some("sampler") => {
+                for zone in config.get("zones").and_then(Value::as_array).into_iter().flatten() {
+                    let envelope = zone
+                        .get("envelope")
+                        .or_else(|| zone.get("config").and_then(|c| c.get("envelope")))
+                        .and_then(parse_envelope);
+                    self.envelopes.push_zone(envelope.or(own));
+                }
+            }
+            Some("composite") => {
+                let children = node.get("children").or_else(|| config.get("children")).and_then(Value::as_array);
+                for child in children.into_iter().flatten() {
+                    self.read_envelopes(child, own);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Split a layering composite root into layers.
+    fn read_layers(&mut self, root: &Value) {
+        if node_type(root).as_deref() != Some("composite") {
+            return;
+        }
+        let config = root.get("config").unwrap_or(root);
+        let mode = ["mode", "compositeMode", "composite_mode"]
+            .iter()
+            .find_map(|k| root.get(*k).or_else(|| config.get(*k)).and_then(Value::as_str))
+            .map(str::to_ascii_lowercase);
+        if mode.is_some_and(|m| SELECTING_MODES.contains(&m.as_str())) {
+            return;
+        }
+        let Some(children) = root.get("children").or_else(|| config.get("children")).and_then(Value::as_array) else {
+            return;
+        };
+        let mix_levels = ["mixLevels", "mix_levels", "gains"]
+            .iter()
+            .find_map(|k| root.get(*k).or_else(|| config.get(*k)).and_then(Value::as_array));
+
+        let mut zone_start = 0;
+        for (i, child) in children.iter().enumerate() {
+            let child_config = child.get("config").unwrap_or(child);
+            let field = |keys: &[&str]| number(child, keys).or_else(|| number(child_config, keys));
+            let (key_low, key_high) = ["keyRange", "key_range"]
+                .iter()
+                .find_map(|k| child.get(*k).or_else(|| child_config.get(*k)))
+                .map_or((0, 127), |range| {
+                    let low = number(range, &["low", "min"]).unwrap_or(0.0).clamp(0.0, 127.0) as u8;
+                    let high = number(range, &["high", "max"]).unwrap_or(127.0).clamp(0.0, 127.0) as u8;
+                    (low, high)
+                });
+            let gain = field(&["gain", "level", "volume"])
+                .or_else(|| mix_levels.and_then(|levels| levels.get(i)).and_then(Value::as_f64))
+                .unwrap_or(1.0);
+            let mut child_graph = Self::default();
+            child_graph.walk(child);
+            let zone_count = zone_count(child);
+            self.layers.push(Layer {
+                zone_start,
+                zone_count,
+                gain: gain.max(0.0) as f32,
+                pan: field(&["pan"]).unwrap_or(0.0) as f32,
+                key_low,
+                key_high,
+                synth: child_graph.synth,
+            });
+            zone_start += zone_count;
+        }
+    }
+
+    fn walk(&mut self, node: &Value) {
+        let config = node.get("config").unwrap_or(node);
+        match node_type(node).as_deref() {
+            Some("oscillator" | "synth" | "synthesizer") => self.add_synth(config),
+            Some("effect" | "fx") => {
+                if let Some(effect) = parse_effect(config) {
+                    self.effects.push(effect);
+                }
+            }
+            _ => {}
+        }
+        // Children of composites, and the sources feeding an effect
+        for key in ["children", "input", "inputs", "source"] {
+            match node.get(key).or_else(|| config.get(key)) {
+                Some(Value::Array(children)) => children.iter().for_each(|c| self.walk(c)),
+                Some(child @ Value::Object(_)) => self.walk(child),
+                _ => {}
+            }
+        }
+    }
+
+    fn add_synth(&mut self, config: &Value) {
+        let patch = self.synth.get_or_insert_with(SynthPatch::default);
+        match config.get("oscillators").and_then(Value::as_array) {
+            Some(oscillators) => oscillators.iter().for_each(|o| {
+                patch.push(parse_oscillator(o));
+            }),
+            None => {
+                patch.push(parse_oscillator(config));
+            }
+        }
+        if patch.envelope.is_none() {
+            patch.envelope = config.get("envelope").and_then(parse_envelope);
+        }
+    }
+}
+
+/// Zones a node contributes, in the order the loader flattens them
+/// (sampler zones, then composite children depth-first).
+pub fn zone_count(node: &Value) -> usize {
+    let config = node.get("config").unwrap_or(node);
+    match node_type(node).as_deref() {
+        Some("sampler") => config.get("zones").and_then(Value::as_array).map_or(0, Vec::len),
+        Some("composite") => node
+            .get("children")
+            .or_else(|| config.get("children"))
+            .and_then(Value::as_array)
+            .map_or(0, |children| children.iter().map(zone_count).sum()),
+        _ => 0,
+    }
+}
+
+/// Lower-case node type, from "type", "kind" or "node".
+fn node_type(node: &Value) -> Option<String> {
+    ["type", "kind", "node"]
+        .iter()
+        .find_map(|k| node.get(*k).and_then(Value::as_str))
+        .map(str::to_ascii_lowercase)
+}
+
+/// First number among `keys`.
+fn number(object: &Value, keys: &[&str]) -> Option<f64> {
+    keys.iter().find_map(|k| object.get(*k).and_then(Value::as_f64))
+}
+
+fn parse_oscillator(config: &Value) -> Oscillator {
+    let waveform = ["waveform", "wave", "shape", "type"]
+        .iter()
+        .find_map(|k| config.get(*k).and_then(Value::as_str))
+        .and_then(Waveform::from_name)
+        .unwrap_or_default();
+    let semitones = number(config, &["octave"]).unwrap_or(0.0) * 12.0
+        + number(config, &["semitones", "transpose", "coarse"]).unwrap_or(0.0);
+    let cents = number(config, &["detune", "cents", "fine"]).unwrap_or(0.0);
+    Oscillator {
+        waveform,
+        ratio: Oscillator::ratio_for(semitones, cents),
+        level: number(config, &["level", "gain", "volume", "mix"]).unwrap_or(1.0).max(0.0) as f32,
+        pulse_width: number(config, &["pulseWidth", "pulse_width", "width"]).unwrap_or(0.5) as f32,
+    }
+}
+
+/// ADSR in seconds (sustain 0–1); missing stages take the defaults.
+pub fn parse_envelope(value: &Value) -> Option<EnvelopeParams> {
+    if !value.is_object() {
+        return None;
+    }
+    let default = EnvelopeParams::default();
+    let secs = |keys: &[&str], fallback: f32| number(value, keys).map_or(fallback, |v| v.max(0.0) as f32);
+    Some(EnvelopeParams {
+        attack_secs: secs(&["attack", "attack_secs", "attackSecs"], default.attack_secs),
+        decay_secs: secs(&["decay", "decay_secs", "decaySecs"], default.decay_secs),
+        sustain_level: secs(&["sustain", "sustain_level", "sustainLevel"], default.sustain_level).min(1.0),
+        release_secs: secs(&["release", "release_secs", "releaseSecs"], default.release_secs),
+    })
+}
+
+fn parse_effect(config: &Value) -> Option<Effect> {
+    let kind = ["effect", "effectType", "effect_type", "type", "kind"]
+        .iter()
+        .filter_map(|k| config.get(*k).and_then(Value::as_str))
+        .map(str::to_ascii_lowercase)
+        .find(|k| k != "effect")?;
+    let cutoff = number(config, &["cutoff", "cutoff_hz", "frequency", "freq"]).unwrap_or(1000.0) as f32;
+    let resonance = number(config, &["resonance", "q"]).unwrap_or(0.0) as f32;
+    match kind.as_str() {
+        "gain" | "volume" => Some(Effect::Gain {
+            gain: number(config, &["gain", "level", "volume"]).unwrap_or(1.0) as f32,
+        }),
+        "lowpass" | "low_pass" | "lpf" => Some(Effect::LowPass { cutoff_hz: cutoff, resonance }),
+        "highpass" | "high_pass" | "hpf" => Some(Effect::HighPass { cutoff_hz: cutoff, resonance }),
+        "filter" => match config.get("mode").or_else(|| config.get("filterType")).and_then(Value::as_str) {
+            Some(m) if m.to_ascii_lowercase().starts_with("high") => {
+                Some(Effect::HighPass { cutoff_hz: cutoff, resonance })
+            }
+            _ => Some(Effect::LowPass { cutoff_hz: cutoff, resonance }),
+        },
+        "drive" | "distortion" | "overdrive" | "saturation" => Some(Effect::Drive {
+            drive: number(config, &["drive", "amount"]).unwrap_or(2.0) as f32,
+            mix: number(config, &["mix", "wet"]).unwrap_or(1.0) as f32,
+        }),
+        "pan" | "panner" => Some(Effect::Pan {
+            pan: number(config, &["pan", "position"]).unwrap_or(0.0) as f32,
+        }),
+        other => {
+            nih_plug::debug::nih_log!("[PresetGraph] Unsupported effect '{}'", other);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synth_node_becomes_oscillator_stack() {
+        let graph = PresetGraph::parse(
+            r#"{"name": "Lead", "category": "synth", "graph": {
+                "type": "Oscillator",
+                "config": {
+                    "oscillators": [
+                        {"waveform": "sawtooth", "detune": 7},
+                        {"waveform": "square", "octave": -1, "level": 0.5}
+                    ],
+                    "envelope": {"attack": 0.5, "release": 2.0}
+                }
+            }}"#,
+        );
+        let synth = graph.synth.unwrap();
+        let oscillators = synth.oscillators();
+        assert_eq!(oscillators.len(), 2);
+        assert_eq!(oscillators[0].waveform, Waveform::Saw);
+        assert!((oscillators[0].ratio - Oscillator::ratio_for(0.0, 7.0)).abs() < 1e-9);
+        assert!((oscillators[1].ratio - 0.5).abs() < 1e-9);
+        assert_eq!(oscillators[1].level, 0.5);
+        let envelope = synth.envelope.unwrap();
+        assert_eq!((envelope.attack_secs, envelope.release_secs), (0.5, 2.0));
+        assert_eq!(envelope.sustain_level, EnvelopeParams::default().sustain_level);
+        assert!(graph.effects.is_empty());
+    }
+
+    #[test]
+    fn test_effects_in_composites_keep_their_order() {
+        let graph = PresetGraph::parse(
+            r#"{"graph": {"type": "Composite", "children": [
+                {"type": "Sampler", "config": {"zones": []}},
+                {"type": "Effect", "config": {"effectType": "distortion", "drive": 3}},
+                {"type": "Effect", "config": {"effectType": "filter", "mode": "highpass", "cutoff": 200}},
+                {"type": "Effect", "config": {"effectType": "reverb"}}
+            ]}}"#,
+        );
+        assert!(graph.synth.is_none());
+        let effects: Vec<Effect> = graph.effects.effects().collect();
+        assert_eq!(
+            effects,
+            vec![
+                Effect::Drive { drive: 3.0, mix: 1.0 },
+                Effect::HighPass { cutoff_hz: 200.0, resonance: 0.0 },
+            ]
+        );
+        assert_eq!(PresetGraph::parse("not json"), PresetGraph::default());
+    }
+
+    #[test]
+    fn test_sampler_and_zone_envelopes() {
+        let graph = PresetGraph::parse(
+            r#"{"graph": {"type": "Composite", "children": [
+                {"type": "Sampler", "config": {"envelope": {"release": 3.0}, "zones": [
+                    {}, {}, {"envelope": {"attack": 0.2}}
+                ]}},
+                {"type": "Sampler", "config": {"zones": [{}]}}
+            ]}}"#,
+        );
+        let envelopes = graph.envelopes;
+        assert_eq!(envelopes.zone_total(), 4);
+        assert_eq!(envelopes.for_zone(0).unwrap().release_secs, 3.0);
+        assert_eq!(envelopes.for_zone(1), envelopes.for_zone(0));
+        let zone = envelopes.for_zone(2).unwrap();
+        assert_eq!((zone.attack_secs, zone.release_secs), (0.2, EnvelopeParams::default().release_secs));
+        assert_eq!(envelopes.for_zone(3), None);
+    }
+
+    #[test]
+    fn test_layered_composite_keeps_children() {
+        let graph = PresetGraph::parse(
+            r#"{"graph": {"type": "Composite", "mode": "layer", "children": [
+                {"type": "Sampler", "gain": 0.8, "pan": -0.5, "config": {"zones": [{}, {}, {}]}},
+                {"type": "Sampler", "keyRange": {"low": 48, "high": 96}, "config": {"zones": [{}, {}]}},
+                {"type": "Oscillator", "config": {"waveform": "triangle"}}
+            ]}}"#,
+        );
+        let layers = graph.layers.as_slice();
+        assert_eq!(layers.len(), 3);
+        assert_eq!((layers[0].zones(), layers[1].zones(), layers[2].zones()), (0..3, 3..5, 5..5));
+        assert_eq!(layers[0].channel_gains(), (0.8, 0.4));
+        assert!(!layers[1].covers(47) && layers[1].covers(96));
+        assert!(layers[2].synth.is_some() && layers[0].synth.is_none());
+
+        // Articulations are not layered
+        let graph = PresetGraph::parse(
+            r#"{"graph": {"type": "Composite", "mode": "keyswitch", "children": [
+                {"type": "Sampler", "config": {"zones": [{}]}}
+            ]}}"#,
+        );
+        assert!(graph.layers.is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+//! Background search indexing of libraries nobody has expanded yet.
+//!
+//! Browser search only sees presets whose library (and sub-index) index has
+//! been fetched, which normally happens when a folder is expanded. Once the
+//! root index is known, this job fetches the remaining library and
+//! sub-index indexes one at a time, pausing between requests, and parses
+//! them into the preset manager without expanding anything, so search
+//! covers every preset, not just the folders opened so far.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::jobs::{JobContext, JobHandle, JobPool, JobPriority};
+use crate::net::HttpClient;
+use crate::net::connectivity::{self, Connectivity};
+use crate::preset::library_index;
+use crate::preset::manager::{LibraryStatus, PresetManager};
+
+/// Pause between index fetches, so indexing never competes with the
+/// user's own downloads.
+const THROTTLE: Duration = Duration::from_millis(250);
+
+/// How long to wait for the root index (or a library fetch) to finish.
+const WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Polling interval while waiting.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Outcome of one indexing pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexSummary {
+    pub libraries: usize,
+    pub sub_indexes: usize,
+    pub failed: usize,
+}
+
+impl IndexSummary {
+    /// Status bar message, or `None` if nothing new was indexed.
+    pub fn message(&self, total_presets: usize) -> Option<String> {
+        if self.libraries + self.sub_indexes == 0 {
+            return None;
+        }
+        Some(format!("Search index ready: {} presets", total_presets))
+    }
+}
+
+/// Sleep for `duration` unless the job is cancelled first. Returns false
+/// if it was.
+fn pause(ctx: &JobContext, duration: Duration) -> bool {
+    let end = Instant::now() + duration;
+    while Instant::now() < end {
+        if ctx.is_cancelled() {
+            return false;
+        }
+        std::thread::sleep(POLL_INTERVAL.min(end - Instant::now()));
+    }
+    !ctx.is_cancelled()
+}
+
+/// Wait until `done` holds for the manager, or the timeout passes.
+fn wait_for(
+    ctx: &JobContext,
+    manager: &Mutex<PresetManager>,
+    done: impl Fn(&PresetManager) -> bool,
+) -> bool {
+    let deadline = Instant::now() + WAIT_TIMEOUT;
+    while Instant::now() < deadline {
+        if manager.lock().is_ok_and(|mgr| done(&mgr)) {
+            return true;
+        }
+        if !pause(ctx, POLL_INTERVAL) {
+            return false;
+        }
+    }
+    false
+}
+
+/// Total presets known to the manager.
+fn preset_count(mgr: &PresetManager) -> usize {
+    mgr.library_presets.values().map(Vec::len).sum::<usize>()
+        + mgr.sub_index_presets.values().map(Vec::len).sum::<usize>()
+}
+
+/// Queue the indexing pass. Cancelling the handle stops it between fetches.
+pub fn spawn_indexer(jobs: &JobPool, manager: Arc<Mutex<PresetManager>>) -> JobHandle {
+    jobs.submit(JobPriority::Background, move |ctx| {
+        if !wait_for(ctx, &manager, |mgr| !mgr.libraries.is_empty()) {
+            return;
+        }
+        let libraries: Vec<String> = match manager.lock() {
+            Ok(mgr) => mgr.libraries.iter().map(|l| l.name.clone()).collect(),
+            Err(_) => return,
+        };
+
+        let client = HttpClient::with_default_store();
+        let mut summary = IndexSummary::default();
+        for name in libraries {
+            if connectivity::global().state() == Connectivity::Offline {
+                break;
+            }
+
+            // Library index: parsed and marked loaded without expanding it
+            let status = manager.lock().ok().and_then(|mgr| {
+                mgr.libraries.iter().find(|l| l.name == name).map(|l| l.status.clone())
+            });
+            if status == Some(LibraryStatus::NotLoaded) && library_index::begin_library_fetch(&manager, &name) {
+                if ctx.block_on(library_index::fetch_library_index(&client, &manager, &name)).is_none() {
+                    return;
+                }
+                let loaded = manager.lock().is_ok_and(|mgr| {
+                    mgr.libraries.iter().find(|l| l.name == name).is_some_and(|l| l.status == LibraryStatus::Loaded)
+                });
+                if loaded {
+                    summary.libraries += 1;
+                } else {
+                    summary.failed += 1;
+                }
+                if !pause(ctx, THROTTLE) {
+                    return;
+                }
+            }
+
+            // Sub-indexes not fetched yet
+            let (base_url, pending) = {
+                let Ok(mgr) = manager.lock() else { return };
+                let slug = mgr
+                    .libraries
+                    .iter()
+                    .find(|l| l.name == name)
+                    .map(|l| l.slug.clone())
+                    .unwrap_or_default();
+                let pending: Vec<(String, String)> = mgr
+                    .sub_indexes
+                    .get(&name)
+                    .into_iter()
+                    .flatten()
+                    .filter(|s| !mgr.sub_index_presets.contains_key(&format!("{}/{}", name, s.name)))
+                    .map(|s| {
+                        let path = if slug.is_empty() {
+                            s.path.clone()
+                        } else {
+                            format!("{}/{}", slug, s.path)
+                        };
+                        (format!("{}/{}", name, s.name), path)
+                    })
+                    .collect();
+                (mgr.base_url.clone(), pending)
+            };
+            for (key, path) in pending {
+                let Some(result) = ctx.block_on(library_index::fetch_index(&client, &base_url, &path, &key))
+                else {
+                    return;
+                };
+                match result {
+                    Ok(index) => {
+                        if let Ok(mut mgr) = manager.lock() {
+                            // Expanding the folder meanwhile may have parsed it already
+                            if !mgr.sub_index_presets.contains_key(&key) {
+                                mgr.parse_sub_index(&key, &index);
+                            }
+                        }
+                        summary.sub_indexes += 1;
+                    }
+                    Err(e) => {
+                        nih_plug::debug::nih_log!("[Indexer] {}: {}", key, e);
+                        summary.failed += 1;
+                    }
+                }
+                if !pause(ctx, THROTTLE) {
+                    return;
+                }
+            }
+        }
+
+        nih_plug::debug::nih_log!("[Indexer] {:?}", summary);
+        if let Ok(mut mgr) = manager.lock() {
+            let total = preset_count(&mgr);
+            if let Some(message) = summary.message(total) {
+                mgr.status_message = message;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_message() {
+        assert_eq!(IndexSummary::default().message(10), None);
+        let s = IndexSummary { sub_indexes: 3, ..Default::default() };
+        assert_eq!(s.message(1200).as_deref(), Some("Search index ready: 1200 presets"));
+        let s = IndexSummary { failed: 2, ..Default::default() };
+        assert_eq!(s.message(5), None);
+    }
+}
@@ -0,0 +1,260 @@
+//! SHA-256 verification of downloaded samples.
+//!
+//! Library presets may pin each external sample with a `sha256`. The
+//! preset fetcher (see `preset::fetch`) checks the raw bytes of a pinned
+//! sample as it downloads them, before decoding, and records the hash with
+//! the decoded copy in the disk cache; a cached copy is only used if that
+//! hash matches the pin.
+//!
+//! [`spawn_library_verification`] re-checks everything cached for a library
+//! without touching the network.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use sha2::{Digest, Sha256};
+
+use crate::jobs::{JobContext, JobHandle, JobPool, JobPriority};
+use crate::net::HttpClient;
+use crate::preset::cache::DiskCache;
+use crate::preset::manager::PresetManager;
+
+/// Lowercase hex SHA-256 of `bytes`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Check `bytes` against the expected hex digest.
+pub fn check(url: &str, bytes: &[u8], expected: &str) -> Result<(), String> {
+    check_hash(url, &sha256_hex(bytes), expected)
+}
+
+/// Check an already computed hex digest against the expected one.
+pub fn check_hash(url: &str, actual: &str, expected: &str) -> Result<(), String> {
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            url, expected, actual
+        ))
+    }
+}
+
+/// Absolute URL of an external sample (relative URLs are relative to the
+/// preset file, as in the loader).
+pub fn sample_url(base_url: &str, library: &str, preset_path: &str, url: &str) -> String {
+    if url.starts_with("http") {
+        return url.to_string();
+    }
+    match preset_path.rsplit_once('/') {
+        Some((dir, _)) if !dir.is_empty() => {
+            format!("{}/{}/{}/{}", base_url, library, dir, url)
+        }
+        _ => format!("{}/{}/{}", base_url, library, url),
+    }
+}
+
+/// Result of a library integrity check.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// Samples hashed.
+    pub checked: usize,
+    /// Samples with a pinned hash but no cached copy.
+    pub not_cached: usize,
+    /// Cached preset files that no longer parse.
+    pub corrupt_presets: usize,
+    /// Corrupt preset files replaced with a fresh download.
+    pub repaired: usize,
+    /// Mismatch messages.
+    pub mismatches: Vec<String>,
+}
+
+impl IntegrityReport {
+    pub fn message(&self, library: &str) -> String {
+        if self.mismatches.is_empty() && self.corrupt_presets == self.repaired {
+            format!(
+                "{}: {} samples verified ({} not cached)",
+                library, self.checked, self.not_cached
+            )
+        } else {
+            format!(
+                "\u{26a0} {}: {} checksum mismatches, {} corrupt presets ({} repaired)",
+                library,
+                self.mismatches.len(),
+                self.corrupt_presets,
+                self.repaired
+            )
+        }
+    }
+}
+
+/// Collect `(url, sha256)` pairs from a preset descriptor's JSON.
+fn pinned_in_json(value: &serde_json::Value, out: &mut Vec<(String, String)>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            if let (Some(url), Some(hash)) = (
+                map.get("url").and_then(|v| v.as_str()),
+                map.get("sha256").and_then(|v| v.as_str()),
+            ) {
+                out.push((url.to_string(), hash.to_string()));
+            }
+            for v in map.values() {
+                pinned_in_json(v, out);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for v in items {
+                pinned_in_json(v, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Queue a re-check of every cached preset and sample of `library`.
+/// The summary is shown in the browser status line.
+pub fn spawn_library_verification(
+    jobs: &JobPool,
+    manager: Arc<Mutex<PresetManager>>,
+    library: String,
+) -> JobHandle {
+    jobs.submit(JobPriority::Background, move |ctx| {
+        let Some(report) = verify_library(ctx, &manager, &library) else {
+            return;
+        };
+        nih_plug::debug::nih_log!("[Integrity] {}: {:?}", library, report);
+        for mismatch in &report.mismatches {
+            nih_plug::debug::nih_log!("[Integrity] {}", mismatch);
+        }
+        if let Ok(mut mgr) = manager.lock() {
+            mgr.status_message = report.message(&library);
+        }
+    })
+}
+
+fn verify_library(
+    ctx: &JobContext,
+    manager: &Arc<Mutex<PresetManager>>,
+    library: &str,
+) -> Option<IntegrityReport> {
+    let (base_url, slug, preset_paths) = {
+        let mgr = manager.lock().ok()?;
+        let slug = mgr
+            .libraries
+            .iter()
+            .find(|l| l.name == library)
+            .map(|l| l.slug.clone())
+            .unwrap_or_else(|| library.to_string());
+        let prefix = format!("{}/", library);
+        let mut paths: Vec<String> = mgr
+            .library_presets
+            .get(library)
+            .into_iter()
+            .flatten()
+            .chain(
+                mgr.sub_index_presets
+                    .iter()
+                    .filter(|(key, _)| key.starts_with(&prefix))
+                    .flat_map(|(_, presets)| presets),
+            )
+            .map(|p| p.path.clone())
+            .collect();
+        paths.sort();
+        paths.dedup();
+        (mgr.base_url.clone(), slug, paths)
+    };
+
+    let cache = DiskCache::new();
+    let client = HttpClient::with_default_store();
+    let mut report = IntegrityReport::default();
+    let mut seen = HashSet::new();
+
+    for path in preset_paths {
+        if ctx.is_cancelled() {
+            return None;
+        }
+        let Some(text) = cache.read_preset(&slug, &path) else {
+            continue;
+        };
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) else {
+            report.corrupt_presets += 1;
+            // Replace the torn copy so the next load doesn't trip over it
+            let url = format!("{}/{}/{}", base_url, slug, path);
+            if let Some(Ok(fetched)) = ctx.block_on(client.get_revalidated(&url)) {
+                let text = fetched.text();
+                if serde_json::from_str::<serde_json::Value>(&text).is_ok() {
+                    let _ = cache.write_preset(&slug, &path, &text);
+                    report.repaired += 1;
+                }
+            }
+            continue;
+        };
+        let mut pinned = Vec::new();
+        pinned_in_json(&json, &mut pinned);
+        for (url, hash) in pinned {
+            let full_url = sample_url(&base_url, &slug, &path, &url);
+            if !seen.insert(full_url.clone()) {
+                continue;
+            }
+            match cache.read_sample(&slug, &path, &url) {
+                Some(sample) => {
+                    report.checked += 1;
+                    if let Err(e) = check_hash(&full_url, &sample.source_sha256, &hash) {
+                        // Removed so the next load downloads it again
+                        cache.remove_sample(&slug, &path, &url);
+                        report.mismatches.push(e);
+                    }
+                }
+                None => report.not_cached += 1,
+            }
+        }
+    }
+    Some(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_accepts_matching_hash() {
+        // sha256("abc")
+        let hash = "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD";
+        assert!(check("u", b"abc", hash).is_ok());
+        let err = check("u", b"abd", hash).unwrap_err();
+        assert!(err.starts_with("Checksum mismatch for u"));
+    }
+
+    #[test]
+    fn test_sample_url_resolution() {
+        assert_eq!(
+            sample_url("https://h", "gm", "piano/preset.json", "C4.mp3"),
+            "https://h/gm/piano/C4.mp3"
+        );
+        assert_eq!(
+            sample_url("https://h", "gm", "preset.json", "C4.mp3"),
+            "https://h/gm/C4.mp3"
+        );
+        assert_eq!(
+            sample_url("https://h", "gm", "a/b", "https://cdn/x.mp3"),
+            "https://cdn/x.mp3"
+        );
+    }
+
+    #[test]
+    fn test_pinned_in_json_finds_nested_samples() {
+        let json = serde_json::json!({
+            "graph": {"config": {"zones": [
+                {"audio": {"type": "external", "url": "a.mp3", "sha256": "11"}},
+                {"audio": {"type": "external", "url": "b.mp3"}}
+            ]}}
+        });
+        let mut out = Vec::new();
+        pinned_in_json(&json, &mut out);
+        assert_eq!(out, vec![("a.mp3".to_string(), "11".to_string())]);
+    }
+}
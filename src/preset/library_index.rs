@@ -0,0 +1,224 @@
+//! Library index fetches: the root index, each library's index and the
+//! sub-indexes of hierarchical libraries.
+//!
+//! These replace the fetches core's `PresetManager` makes with its own
+//! client, so index requests go through [`HttpClient`] like preset
+//! downloads do: with the proxy, CA certificate and timeout from the
+//! network settings, library credentials, retries and mirrors. The manager
+//! still parses the documents. Each index is read from the disk cache
+//! when it has a copy; `revalidate` keeps those copies current.
+//!
+//! Fetches run on the shared [`JobPool`] at background priority, so they
+//! queue behind the preset loads the user asked for.
+
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+
+use crate::jobs::{JobHandle, JobPool, JobPriority};
+use crate::net::HttpClient;
+use crate::preset::cache::DiskCache;
+use crate::preset::manager::{LibraryStatus, PresetManager};
+
+/// Index at `path` under `base_url`: the disk cache's copy under
+/// `cache_key` if it has a readable one, else downloaded and cached.
+pub async fn fetch_index(client: &HttpClient, base_url: &str, path: &str, cache_key: &str) -> Result<Value, String> {
+    let cache = DiskCache::new();
+    if let Some(index) = cache.read_library_index(cache_key).and_then(|t| serde_json::from_str(&t).ok()) {
+        return Ok(index);
+    }
+    let fetched = client.get_revalidated(&format!("{}/{}", base_url, path)).await?;
+    let text = fetched.text();
+    let index = serde_json::from_str(&text).map_err(|e| format!("Failed to parse index {}: {}", path, e))?;
+    let _ = cache.write_library_index(cache_key, &text);
+    Ok(index)
+}
+
+/// Show the cached root and library indexes, then fetch the root index.
+/// Only the first call for a manager does anything.
+pub fn start_background_refresh(jobs: &JobPool, manager: Arc<Mutex<PresetManager>>) -> Option<JobHandle> {
+    {
+        let mut mgr = manager.lock().ok()?;
+        if mgr.refresh_started {
+            return None;
+        }
+        mgr.refresh_started = true;
+        mgr.status_message = "Loading index\u{2026}".to_string();
+    }
+    show_cached(&manager);
+    Some(jobs.submit(JobPriority::Background, move |ctx| {
+        ctx.block_on(refresh_root(&HttpClient::with_default_store(), &manager));
+    }))
+}
+
+/// Parse whatever the disk cache holds, for an instant library list.
+fn show_cached(manager: &Mutex<PresetManager>) {
+    let cache = DiskCache::new();
+    let _ = cache.ensure_dirs();
+    let Some(root) = cache.read_root_index().and_then(|t| serde_json::from_str::<Value>(&t).ok()) else {
+        return;
+    };
+    let Ok(mut mgr) = manager.lock() else { return };
+    mgr.parse_root_index(&root);
+    let libraries: Vec<(String, String)> = mgr.libraries.iter().map(|l| (l.name.clone(), l.slug.clone())).collect();
+    for (name, slug) in libraries {
+        let index = [&slug, &name]
+            .into_iter()
+            .find_map(|key| cache.read_library_index(key).and_then(|t| serde_json::from_str::<Value>(&t).ok()));
+        if let Some(index) = index {
+            mgr.parse_library_index(&name, &index);
+            set_status(&mut mgr, &name, LibraryStatus::Loaded);
+        }
+    }
+}
+
+async fn refresh_root(client: &HttpClient, manager: &Mutex<PresetManager>) {
+    let Some(base_url) = manager.lock().ok().map(|mgr| mgr.base_url.clone()) else { return };
+    let result = client.get_revalidated(&format!("{}/index.json", base_url)).await.and_then(|fetched| {
+        let text = fetched.text();
+        let root = serde_json::from_str::<Value>(&text).map_err(|e| format!("Failed to parse root index: {}", e))?;
+        let _ = DiskCache::new().write_root_index(&text);
+        Ok(root)
+    });
+    let Ok(mut mgr) = manager.lock() else { return };
+    match result {
+        Ok(root) => {
+            mgr.parse_root_index(&root);
+            mgr.status_message = format!("{} libraries loaded", mgr.libraries.len());
+        }
+        // Keep showing the cached libraries, if there are any
+        Err(e) if mgr.libraries.is_empty() => mgr.status_message = format!("\u{26a0} {}", e),
+        Err(_) => {}
+    }
+}
+
+fn set_status(mgr: &mut PresetManager, library: &str, status: LibraryStatus) {
+    if let Some(lib) = mgr.libraries.iter_mut().find(|l| l.name == library) {
+        lib.status = status;
+    }
+}
+
+/// Mark a library loading, unless it already is or is loaded. Returns
+/// whether its index should be fetched.
+pub fn begin_library_fetch(manager: &Mutex<PresetManager>, library: &str) -> bool {
+    let Ok(mut mgr) = manager.lock() else { return false };
+    let Some(lib) = mgr.libraries.iter_mut().find(|l| l.name == library) else { return false };
+    if matches!(lib.status, LibraryStatus::Loaded | LibraryStatus::Loading) {
+        return false;
+    }
+    lib.status = LibraryStatus::Loading;
+    mgr.status_message = format!("Loading {}\u{2026}", library);
+    true
+}
+
+/// Fetch a library's index in the background (its folder was expanded,
+/// or something needs its presets). Does nothing if it is loaded.
+pub fn start_library_fetch(jobs: &JobPool, manager: Arc<Mutex<PresetManager>>, library: String) -> Option<JobHandle> {
+    if !begin_library_fetch(&manager, &library) {
+        return None;
+    }
+    Some(jobs.submit(JobPriority::Background, move |ctx| {
+        let fetched = ctx.block_on(fetch_library_index(&HttpClient::with_default_store(), &manager, &library));
+        if fetched.is_none() {
+            // Cancelled: let the next request try again
+            if let Ok(mut mgr) = manager.lock() {
+                set_status(&mut mgr, &library, LibraryStatus::NotLoaded);
+            }
+        }
+    }))
+}
+
+/// Fetch and parse a library's index, recording the outcome in its status.
+pub async fn fetch_library_index(client: &HttpClient, manager: &Mutex<PresetManager>, library: &str) {
+    let found = manager.lock().ok().and_then(|mgr| {
+        let lib = mgr.libraries.iter().find(|l| l.name == library)?;
+        Some((mgr.base_url.clone(), lib.path.clone(), lib.slug.clone()))
+    });
+    let Some((base_url, path, slug)) = found else { return };
+    let result = fetch_index(client, &base_url, &path, &slug).await;
+
+    let Ok(mut mgr) = manager.lock() else { return };
+    match result {
+        Ok(index) => {
+            mgr.parse_library_index(library, &index);
+            set_status(&mut mgr, library, LibraryStatus::Loaded);
+            let count = mgr.library_presets.get(library).map_or(0, Vec::len);
+            mgr.status_message = format!("{}: {} presets", library, count);
+        }
+        Err(e) => {
+            mgr.status_message = format!("\u{26a0} {}", e);
+            set_status(&mut mgr, library, LibraryStatus::Error(e));
+        }
+    }
+}
+
+/// Fetch a sub-index in the background (its folder was expanded).
+pub fn start_sub_index_fetch(
+    jobs: &JobPool,
+    manager: Arc<Mutex<PresetManager>>,
+    library: String,
+    sub_name: String,
+    sub_path: String,
+) -> JobHandle {
+    if let Ok(mut mgr) = manager.lock() {
+        mgr.status_message = format!("Loading {}\u{2026}", sub_name);
+    }
+    jobs.submit(JobPriority::Background, move |ctx| {
+        let client = HttpClient::with_default_store();
+        ctx.block_on(fetch_sub_index(&client, &manager, &library, &sub_name, &sub_path));
+    })
+}
+
+/// Fetch and parse a sub-index, whose path is relative to its library.
+pub async fn fetch_sub_index(
+    client: &HttpClient,
+    manager: &Mutex<PresetManager>,
+    library: &str,
+    sub_name: &str,
+    sub_path: &str,
+) {
+    let key = format!("{}/{}", library, sub_name);
+    let found = manager.lock().ok().map(|mgr| {
+        let slug = mgr.libraries.iter().find(|l| l.name == library).map(|l| l.slug.clone()).unwrap_or_default();
+        (mgr.base_url.clone(), slug)
+    });
+    let Some((base_url, slug)) = found else { return };
+    let path = if slug.is_empty() { sub_path.to_string() } else { format!("{}/{}", slug, sub_path) };
+    let result = fetch_index(client, &base_url, &path, &key).await;
+
+    let Ok(mut mgr) = manager.lock() else { return };
+    match result {
+        Ok(index) => {
+            mgr.parse_sub_index(&key, &index);
+            let count = mgr.sub_index_presets.get(&key).map_or(0, Vec::len);
+            if let Some(sub) = mgr.sub_indexes.get_mut(library).and_then(|s| s.iter_mut().find(|s| s.name == sub_name)) {
+                sub.expanded = true;
+            }
+            mgr.status_message = format!("{}: {} presets", sub_name, count);
+        }
+        Err(e) => mgr.status_message = format!("\u{26a0} {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::mock::{FIXTURE_LIBRARY, MockLibrary, block_on};
+    use crate::net::{RetryPolicy, ValidatorStore};
+
+    #[test]
+    fn test_fetch_index_caches_the_download() {
+        let library = format!("{}-index-{}", FIXTURE_LIBRARY, std::process::id());
+        let server = MockLibrary::fixture_as(&library);
+        let client = HttpClient::new(ValidatorStore::in_memory()).with_retry(RetryPolicy::NONE);
+        let path = format!("{}/index.json", library);
+        block_on(async {
+            let index = fetch_index(&client, &server.url(), &path, &library).await.unwrap();
+            assert_eq!(index["name"], library.as_str());
+            // The second read comes from the disk cache
+            fetch_index(&client, &server.url(), &path, &library).await.unwrap();
+        });
+        assert_eq!(server.hits(&path), 1);
+        assert!(block_on(fetch_index(&client, &server.url(), "nope/index.json", "nope-key-x")).is_err());
+    }
+}
@@ -0,0 +1,558 @@
+//! Libraries built from a folder of samples on this machine.
+//!
+//! [`build`] scans a directory tree for WAV and MP3 files and makes one
+//! sampler preset per folder, with a zone per file: the root note comes
+//! from the file name ("Piano_C4.wav", "Bass-F#2.mp3", "Harp 060.wav") or,
+//! failing that, from pitch detection, and each zone covers the keys up to
+//! halfway to its neighbours. [`write_to_folder`] saves a
+//! songwalker-library compatible `index.json` and preset JSONs next to the
+//! samples, so the folder can be published as-is.
+//!
+//! The browser reads libraries through the disk cache before the network,
+//! so [`seed_cache`] stores the index, descriptors and decoded samples
+//! there under the library's slug and the library loads without a server.
+//! Local libraries are remembered in `local_libraries.json` and put back
+//! into the browser after every root index refresh.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::cache::DiskCache;
+use super::manager::{LibraryInfo, LibraryStatus, PresetManager};
+
+/// File extensions picked up by the scan.
+pub const SAMPLE_EXTENSIONS: [&str; 2] = ["wav", "mp3"];
+/// Root note of a sample whose pitch can't be found.
+pub const DEFAULT_ROOT_NOTE: u8 = 60;
+/// Slug prefix that marks a local library.
+pub const SLUG_PREFIX: &str = "local-";
+
+/// A local library the browser should list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalLibrary {
+    pub name: String,
+    pub slug: String,
+    /// Folder the library was built from.
+    pub folder: PathBuf,
+}
+
+/// How a zone's root note was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootSource {
+    FileName,
+    Detected,
+    Default,
+}
+
+/// One decoded sample file.
+#[derive(Debug, Clone)]
+pub struct LocalSample {
+    /// File name, which is also its URL relative to the preset.
+    pub file_name: String,
+    pub codec: &'static str,
+    pub size: u64,
+    pub root_note: u8,
+    pub root_source: RootSource,
+    pub sample_rate: u32,
+    /// Mono PCM.
+    pub pcm: Vec<f32>,
+}
+
+/// A preset made from one folder.
+#[derive(Debug, Clone)]
+pub struct LocalPreset {
+    pub name: String,
+    /// Path of the descriptor within the library ("Strings/preset.json").
+    pub path: String,
+    pub samples: Vec<LocalSample>,
+}
+
+/// Result of a scan, ready to be written and cached.
+#[derive(Debug, Clone)]
+pub struct GeneratedLibrary {
+    pub library: LocalLibrary,
+    pub presets: Vec<LocalPreset>,
+    /// Files that couldn't be read, with the reason.
+    pub skipped: Vec<(String, String)>,
+}
+
+impl GeneratedLibrary {
+    /// The library's `index.json`.
+    pub fn index_json(&self) -> serde_json::Value {
+        let entries: Vec<_> = self
+            .presets
+            .iter()
+            .map(|p| {
+                serde_json::json!({
+                    "type": "preset",
+                    "name": p.name,
+                    "path": p.path,
+                    "category": "sampler",
+                    "tags": ["local"],
+                    "zoneCount": p.samples.len(),
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "format": "songwalker-index",
+            "version": 1,
+            "name": self.library.name,
+            "entries": entries,
+        })
+    }
+}
+
+impl LocalPreset {
+    /// The preset's descriptor JSON.
+    pub fn descriptor_json(&self) -> serde_json::Value {
+        let ranges = key_ranges(&self.samples.iter().map(|s| s.root_note).collect::<Vec<_>>());
+        let zones: Vec<_> = self
+            .samples
+            .iter()
+            .zip(ranges)
+            .map(|(s, (low, high))| {
+                serde_json::json!({
+                    "keyRange": {"low": low, "high": high},
+                    "pitch": {"rootNote": s.root_note, "fineTuneCents": 0},
+                    "sampleRate": s.sample_rate,
+                    "audio": {"type": "external", "url": s.file_name, "codec": s.codec, "size": s.size},
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "id": self.path.trim_end_matches(".json").replace('/', "-"),
+            "name": self.name,
+            "category": "sampler",
+            "tags": ["local"],
+            "graph": {"type": "Sampler", "config": {"zones": zones}},
+        })
+    }
+}
+
+/// Key range of each zone (roots sorted ascending): each reaches halfway
+/// to its neighbours, the outer ones to the ends of the keyboard.
+pub fn key_ranges(roots: &[u8]) -> Vec<(u8, u8)> {
+    (0..roots.len())
+        .map(|i| {
+            let low = if i == 0 { 0 } else { (roots[i - 1] as u16 + roots[i] as u16) / 2 + 1 };
+            let high = match roots.get(i + 1) {
+                Some(&next) => (roots[i] as u16 + next as u16) / 2,
+                None => 127,
+            };
+            (low.min(127) as u8, high as u8)
+        })
+        .collect()
+}
+
+/// Root note written in a file name: a note name with octave ("C4",
+/// "F#2", "Bb-1", with C4 = 60), or else a standalone MIDI number of two
+/// or three digits ("060"). The last match wins.
+pub fn note_from_name(stem: &str) -> Option<u8> {
+    let chars: Vec<char> = stem.chars().collect();
+    let mut found = None;
+    for start in 0..chars.len() {
+        if start > 0 && chars[start - 1].is_ascii_alphabetic() {
+            continue;
+        }
+        if let Some(note) = parse_note_at(&chars, start) {
+            found = Some(note);
+        }
+    }
+    found.or_else(|| {
+        stem.split(|c: char| !c.is_ascii_digit())
+            .filter(|t| (2..=3).contains(&t.len()))
+            .filter_map(|t| t.parse::<u8>().ok())
+            .filter(|n| *n < 128)
+            .next_back()
+    })
+}
+
+/// A note name starting at `start` and ending at a non-alphanumeric char.
+fn parse_note_at(chars: &[char], start: usize) -> Option<u8> {
+    let pitch_class: i32 = match chars[start].to_ascii_uppercase() {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return None,
+    };
+    let mut i = start + 1;
+    let accidental = match chars.get(i) {
+        Some('#') => 1,
+        Some('b') => -1,
+        _ => 0,
+    };
+    if accidental != 0 {
+        i += 1;
+    }
+    let negative = chars.get(i) == Some(&'-');
+    if negative {
+        i += 1;
+    }
+    let digit = chars.get(i)?.to_digit(10)? as i32;
+    i += 1;
+    if chars.get(i).is_some_and(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let octave = if negative { -digit } else { digit };
+    let note = (octave + 1) * 12 + pitch_class + accidental;
+    u8::try_from(note).ok().filter(|n| *n < 128)
+}
+
+/// Fundamental of a pitched sound in Hz (YIN), or `None` if it isn't
+/// clearly pitched. Looks past the attack, between 30 Hz and 2 kHz.
+pub fn detect_pitch(pcm: &[f32], sample_rate: f32) -> Option<f32> {
+    const THRESHOLD: f32 = 0.15;
+    let min_lag = (sample_rate / 2000.0) as usize;
+    let max_lag = (sample_rate / 30.0) as usize;
+    let window = max_lag * 2;
+    let start = ((sample_rate * 0.05) as usize).min(pcm.len().saturating_sub(window));
+    let frame = pcm.get(start..start + window)?;
+    let width = window - max_lag;
+
+    // Cumulative mean normalised difference
+    let mut cmnd = vec![1.0f32; max_lag + 1];
+    let mut running = 0.0f32;
+    for lag in 1..=max_lag {
+        let d: f32 = (0..width).map(|j| (frame[j] - frame[j + lag]).powi(2)).sum();
+        running += d;
+        cmnd[lag] = if running > 0.0 { d * lag as f32 / running } else { 1.0 };
+    }
+    let mut lag = (min_lag.max(2)..max_lag).find(|&l| cmnd[l] < THRESHOLD)?;
+    while lag + 1 < max_lag && cmnd[lag + 1] < cmnd[lag] {
+        lag += 1;
+    }
+    // Parabolic interpolation around the minimum
+    let (a, b, c) = (cmnd[lag - 1], cmnd[lag], cmnd[lag + 1]);
+    let denom = a - 2.0 * b + c;
+    let offset = if denom.abs() > f32::EPSILON { 0.5 * (a - c) / denom } else { 0.0 };
+    Some(sample_rate / (lag as f32 + offset))
+}
+
+/// Nearest MIDI note to a frequency.
+pub fn hz_to_note(hz: f32) -> u8 {
+    (69.0 + 12.0 * (hz / 440.0).log2()).round().clamp(0.0, 127.0) as u8
+}
+
+/// Decode a WAV or MP3 file to mono PCM and its sample rate.
+fn decode_file(bytes: &[u8], codec: &str) -> Result<(Vec<f32>, u32), String> {
+    let (interleaved, channels, rate) = match codec {
+        "wav" => {
+            let reader = hound::WavReader::new(std::io::Cursor::new(bytes))
+                .map_err(|e| format!("WAV decode error: {}", e))?;
+            let spec = reader.spec();
+            let samples: Vec<f32> = match spec.sample_format {
+                hound::SampleFormat::Int => {
+                    let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+                    reader.into_samples::<i32>().filter_map(|s| s.ok()).map(|s| s as f32 / scale).collect()
+                }
+                hound::SampleFormat::Float => reader.into_samples::<f32>().filter_map(|s| s.ok()).collect(),
+            };
+            (samples, spec.channels as usize, spec.sample_rate)
+        }
+        _ => {
+            let mut decoder = minimp3::Decoder::new(std::io::Cursor::new(bytes));
+            let (mut samples, mut channels, mut rate) = (Vec::new(), 1, 44100);
+            loop {
+                match decoder.next_frame() {
+                    Ok(frame) => {
+                        channels = frame.channels.max(1);
+                        rate = frame.sample_rate as u32;
+                        samples.extend(frame.data.iter().map(|s| *s as f32 / 32768.0));
+                    }
+                    Err(minimp3::Error::Eof) => break,
+                    Err(e) => return Err(format!("MP3 decode error: {:?}", e)),
+                }
+            }
+            (samples, channels, rate)
+        }
+    };
+    if interleaved.is_empty() {
+        return Err("No audio".to_string());
+    }
+    let mono = interleaved
+        .chunks(channels.max(1))
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+    Ok((mono, rate))
+}
+
+/// Read one sample file and find its root note.
+fn read_sample(path: &Path) -> Result<LocalSample, String> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).ok_or("Unreadable file name")?.to_string();
+    let codec = if file_name.to_ascii_lowercase().ends_with(".mp3") { "mp3" } else { "wav" };
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let (pcm, sample_rate) = decode_file(&bytes, codec)?;
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    let (root_note, root_source) = match note_from_name(stem) {
+        Some(note) => (note, RootSource::FileName),
+        None => match detect_pitch(&pcm, sample_rate as f32) {
+            Some(hz) => (hz_to_note(hz), RootSource::Detected),
+            None => (DEFAULT_ROOT_NOTE, RootSource::Default),
+        },
+    };
+    Ok(LocalSample { file_name, codec, size: bytes.len() as u64, root_note, root_source, sample_rate, pcm })
+}
+
+/// Slug for a library name ("My Kit" → "local-my-kit").
+pub fn slug_for(name: &str) -> String {
+    let mut slug = String::from(SLUG_PREFIX);
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Sample files under `dir`, grouped by folder (relative, '/'-separated;
+/// "" for the top level).
+fn scan(dir: &Path, rel: &str, groups: &mut BTreeMap<String, Vec<PathBuf>>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    let mut entries: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+    entries.sort();
+    for path in entries {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            let child = if rel.is_empty() { name } else { format!("{}/{}", rel, name) };
+            scan(&path, &child, groups);
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| SAMPLE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        {
+            groups.entry(rel.to_string()).or_default().push(path);
+        }
+    }
+}
+
+/// Scan `folder` and build the library. `progress(done, total)` is called
+/// after each file.
+pub fn build(folder: &Path, name: &str, progress: impl Fn(usize, usize)) -> Result<GeneratedLibrary, String> {
+    let mut groups = BTreeMap::new();
+    scan(folder, "", &mut groups);
+    let total: usize = groups.values().map(Vec::len).sum();
+    if total == 0 {
+        return Err(format!("No WAV or MP3 files in {}", folder.display()));
+    }
+
+    let mut presets = Vec::new();
+    let mut skipped = Vec::new();
+    let mut done = 0;
+    for (rel, files) in groups {
+        let mut samples: Vec<LocalSample> = Vec::new();
+        for path in files {
+            match read_sample(&path) {
+                Ok(sample) => samples.push(sample),
+                Err(e) => skipped.push((path.display().to_string(), e)),
+            }
+            done += 1;
+            progress(done, total);
+        }
+        // One zone per root note; a second file at the same pitch is left out
+        samples.sort_by_key(|s| s.root_note);
+        samples.dedup_by(|dup, kept| {
+            let same = dup.root_note == kept.root_note;
+            if same {
+                skipped.push((dup.file_name.clone(), format!("Same root note as {}", kept.file_name)));
+            }
+            same
+        });
+        if samples.is_empty() {
+            continue;
+        }
+        let preset_name = rel.rsplit('/').next().filter(|n| !n.is_empty()).unwrap_or(name).to_string();
+        let path = if rel.is_empty() { "preset.json".to_string() } else { format!("{}/preset.json", rel) };
+        presets.push(LocalPreset { name: preset_name, path, samples });
+    }
+    if presets.is_empty() {
+        return Err("None of the sample files could be read".to_string());
+    }
+
+    Ok(GeneratedLibrary {
+        library: LocalLibrary { name: name.to_string(), slug: slug_for(name), folder: folder.to_path_buf() },
+        presets,
+        skipped,
+    })
+}
+
+/// Write `index.json` and the preset descriptors into the library's folder.
+pub fn write_to_folder(generated: &GeneratedLibrary) -> Result<(), String> {
+    let folder = &generated.library.folder;
+    let write = |rel: &str, json: &serde_json::Value| {
+        let text = serde_json::to_string_pretty(json).map_err(|e| e.to_string())?;
+        crate::net::atomic::write_atomic(&folder.join(rel), text.as_bytes())
+            .map_err(|e| format!("Failed to write {}: {}", rel, e))
+    };
+    write("index.json", &generated.index_json())?;
+    for preset in &generated.presets {
+        write(&preset.path, &preset.descriptor_json())?;
+    }
+    Ok(())
+}
+
+/// Store the index, descriptors and decoded samples in the disk cache,
+/// where the loader finds them before trying the network.
+pub fn seed_cache(generated: &GeneratedLibrary) -> Result<(), String> {
+    let cache = DiskCache::new();
+    let _ = cache.ensure_dirs();
+    let LocalLibrary { name, slug, .. } = &generated.library;
+    let index = generated.index_json().to_string();
+    for key in [slug, name] {
+        cache
+            .write_library_index(key, &index)
+            .map_err(|e| format!("Failed to cache library index: {}", e))?;
+    }
+    for preset in &generated.presets {
+        cache
+            .write_preset(slug, &preset.path, &preset.descriptor_json().to_string())
+            .map_err(|e| format!("Failed to cache {}: {}", preset.path, e))?;
+        for sample in &preset.samples {
+            cache
+                .write_sample(slug, &preset.path, &sample.file_name, &sample.pcm)
+                .map_err(|e| format!("Failed to cache {}: {}", sample.file_name, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Add a local library to the browser if it isn't listed. Returns whether
+/// it was added (the caller then loads its index, which comes from the
+/// cache).
+pub fn register(pm: &mut PresetManager, library: &LocalLibrary) -> bool {
+    if pm.libraries.iter().any(|l| l.name == library.name) {
+        return false;
+    }
+    pm.libraries.push(LibraryInfo {
+        name: library.name.clone(),
+        path: format!("{}/index.json", library.slug),
+        slug: library.slug.clone(),
+        description: format!("Local: {}", library.folder.display()),
+        preset_count: 0,
+        status: LibraryStatus::NotLoaded,
+        expanded: false,
+    });
+    true
+}
+
+/// Whether a library was built from a local folder.
+pub fn is_local(slug: &str) -> bool {
+    slug.starts_with(SLUG_PREFIX)
+}
+
+fn registry_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("org", "SongWalker", "songwalker-vsti")
+        .map(|d| d.config_dir().join("local_libraries.json"))
+}
+
+/// Local libraries created so far.
+pub fn load_registry() -> Vec<LocalLibrary> {
+    registry_path()
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_registry(libraries: &[LocalLibrary]) -> Result<(), String> {
+    let path = registry_path().ok_or("No config directory")?;
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let json = serde_json::to_vec_pretty(libraries).map_err(|e| e.to_string())?;
+    crate::net::atomic::write_atomic(&path, &json).map_err(|e| format!("Failed to save local libraries: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_from_name() {
+        assert_eq!(note_from_name("Piano_C4"), Some(60));
+        assert_eq!(note_from_name("bass-F#2-soft"), Some(42));
+        assert_eq!(note_from_name("Harp Bb-1"), Some(10));
+        assert_eq!(note_from_name("Kick 060"), Some(60));
+        assert_eq!(note_from_name("Strings A3 take2"), Some(57));
+        assert_eq!(note_from_name("Vox_E5_A4"), Some(69), "last match wins");
+        assert_eq!(note_from_name("Clap"), None);
+        assert_eq!(note_from_name("Bad2x"), None);
+        assert_eq!(note_from_name("Pad 7"), None);
+    }
+
+    #[test]
+    fn test_detect_pitch_of_a_sine() {
+        let rate = 44100.0;
+        let pcm: Vec<f32> = (0..8192).map(|i| (i as f32 * 220.0 * std::f32::consts::TAU / rate).sin()).collect();
+        let hz = detect_pitch(&pcm, rate).unwrap();
+        assert!((hz - 220.0).abs() < 1.0, "{}", hz);
+        assert_eq!(hz_to_note(hz), 57);
+        assert_eq!(detect_pitch(&[0.0; 16], rate), None);
+    }
+
+    #[test]
+    fn test_key_ranges_meet_halfway() {
+        assert_eq!(key_ranges(&[48, 60, 72]), vec![(0, 54), (55, 66), (67, 127)]);
+        assert_eq!(key_ranges(&[60]), vec![(0, 127)]);
+    }
+
+    #[test]
+    fn test_build_groups_folders_into_presets() {
+        let dir = std::env::temp_dir().join(format!("sw-local-lib-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("Keys")).unwrap();
+        let write_wav = |path: PathBuf, hz: f32| {
+            let spec = hound::WavSpec {
+                channels: 2,
+                sample_rate: 22050,
+                bits_per_sample: 16,
+                sample_format: hound::SampleFormat::Int,
+            };
+            let mut writer = hound::WavWriter::create(path, spec).unwrap();
+            for i in 0..8192 {
+                let s = ((i as f32 * hz * std::f32::consts::TAU / 22050.0).sin() * 16000.0) as i16;
+                writer.write_sample(s).unwrap();
+                writer.write_sample(s).unwrap();
+            }
+            writer.finalize().unwrap();
+        };
+        write_wav(dir.join("Keys/Rhodes_C3.wav"), 130.8);
+        write_wav(dir.join("Keys/Rhodes_C5.wav"), 523.3);
+        write_wav(dir.join("Keys/hum.wav"), 440.0);
+        write_wav(dir.join("drone.wav"), 110.0);
+        std::fs::write(dir.join("Keys/notes.txt"), "ignored").unwrap();
+
+        let generated = build(&dir, "My Samples", |_, _| {}).unwrap();
+        assert_eq!(generated.library.slug, "local-my-samples");
+        let paths: Vec<_> = generated.presets.iter().map(|p| p.path.as_str()).collect();
+        assert_eq!(paths, vec!["preset.json", "Keys/preset.json"]);
+
+        let keys = &generated.presets[1];
+        let roots: Vec<_> = keys.samples.iter().map(|s| (s.root_note, s.root_source)).collect();
+        assert_eq!(
+            roots,
+            vec![(48, RootSource::FileName), (69, RootSource::Detected), (72, RootSource::FileName)]
+        );
+        assert_eq!(keys.samples[0].pcm.len(), 8192, "downmixed to mono");
+        let zones = &keys.descriptor_json()["graph"]["config"]["zones"];
+        assert_eq!(zones[1]["keyRange"], serde_json::json!({"low": 59, "high": 70}));
+        assert_eq!(zones[1]["audio"]["url"], "hum.wav");
+
+        let index = generated.index_json();
+        assert_eq!(index["entries"][1]["name"], "Keys");
+        assert_eq!(index["entries"][0]["name"], "My Samples");
+
+        write_to_folder(&generated).unwrap();
+        assert!(dir.join("index.json").exists() && dir.join("Keys/preset.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,53 @@
+//! Memory accounting for loaded presets.
+
+use std::collections::HashSet;
+
+use songwalker_core::preset::instance::PresetInstance;
+
+/// Bytes of decoded PCM held by a preset (sum of its zones' `pcm_data`).
+pub fn preset_bytes(instance: &PresetInstance) -> usize {
+    instance
+        .zones
+        .iter()
+        .map(|z| z.pcm_data.len() * std::mem::size_of::<f32>())
+        .sum()
+}
+
+/// Total PCM bytes across presets, counting samples shared between them
+/// (see `sample_cache`) only once.
+pub fn total_bytes<'a>(instances: impl IntoIterator<Item = &'a PresetInstance>) -> usize {
+    let mut seen = HashSet::new();
+    let mut total = 0;
+    for instance in instances {
+        for zone in &instance.zones {
+            if seen.insert(zone.pcm_data.as_ptr() as usize) {
+                total += zone.pcm_data.len() * std::mem::size_of::<f32>();
+            }
+        }
+    }
+    total
+}
+
+/// Human-readable size ("812 KB", "14.2 MB").
+pub fn format_bytes(bytes: usize) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+    let b = bytes as f64;
+    if b >= MB {
+        format!("{:.1} MB", b / MB)
+    } else {
+        format!("{:.0} KB", b / KB)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 KB");
+        assert_eq!(format_bytes(2048), "2 KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 / 2), "1.5 MB");
+    }
+}
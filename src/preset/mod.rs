@@ -1 +1,19 @@
-pub use songwalker_core::preset::{cache, loader, manager, types, instance};
+pub use songwalker_core::preset::{loader, manager, types, instance};
+
+pub mod cache;
+pub mod changes;
+pub mod decode;
+pub mod download_size;
+pub mod drums;
+pub mod fetch;
+pub mod graph;
+pub mod indexer;
+pub mod integrity;
+pub mod library_index;
+pub mod local_library;
+pub mod memory;
+pub mod patchlist;
+pub mod revalidate;
+pub mod sample_cache;
+pub mod shared;
+pub mod updates;
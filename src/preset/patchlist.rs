@@ -0,0 +1,297 @@
+//! Patch list export: a library's GM programs as a file DAW patch browsers
+//! can read, so program changes sent to the plugin show preset names.
+//!
+//! Each GM program number becomes a program in bank 0; further presets with
+//! the same program go to banks 1, 2, … (bank select MSB) in name order.
+//! Only presets from loaded indexes are included, so expand a library's
+//! sub-indexes first to export them too.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use crate::preset::manager::{PresetInfo, PresetManager};
+
+/// Output format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PatchListFormat {
+    /// `Bank`/program lines (REAPER's ReaBank syntax).
+    #[default]
+    PatchList,
+    /// Cubase/Nuendo patch name script.
+    CubaseScript,
+    /// Generic comma-separated values with a header row.
+    Csv,
+}
+
+impl PatchListFormat {
+    pub const ALL: [Self; 3] = [Self::PatchList, Self::CubaseScript, Self::Csv];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::PatchList => ".patchlist",
+            Self::CubaseScript => "Cubase script",
+            Self::Csv => "CSV",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::PatchList => "patchlist",
+            Self::CubaseScript => "txt",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+/// One named program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+    /// Bank select MSB.
+    pub bank: u8,
+    /// Program number (0–127).
+    pub program: u8,
+    pub name: String,
+    pub category: String,
+    pub preset_id: String,
+}
+
+/// A library's presets from its loaded index and sub-indexes.
+pub fn loaded_presets<'a>(mgr: &'a PresetManager, library: &'a str) -> impl Iterator<Item = &'a PresetInfo> {
+    mgr.library_presets.get(library).into_iter().flatten().chain(
+        mgr.sub_index_presets
+            .iter()
+            .filter(move |(key, _)| key.strip_prefix(library).is_some_and(|rest| rest.starts_with('/')))
+            .flat_map(|(_, presets)| presets),
+    )
+}
+
+/// Collect the GM programs of a library's loaded presets.
+pub fn collect_patches(mgr: &PresetManager, library: &str) -> Vec<Patch> {
+    assign_banks(loaded_presets(mgr, library).filter_map(|p| {
+        let program = p.gm_program.filter(|gm| *gm < 128)?;
+        Some((
+            program,
+            p.name.clone(),
+            p.category.clone(),
+            format!("{}/{}", library, p.path),
+        ))
+    }))
+}
+
+/// Sort `(program, name, category, preset_id)` entries and give presets
+/// sharing a program successive banks. Duplicate preset ids are dropped.
+fn assign_banks(entries: impl Iterator<Item = (u8, String, String, String)>) -> Vec<Patch> {
+    let mut entries: Vec<_> = entries.collect();
+    entries.sort_by(|a, b| (a.0, &a.1, &a.3).cmp(&(b.0, &b.1, &b.3)));
+    entries.dedup_by(|a, b| a.3 == b.3);
+
+    let mut patches: Vec<Patch> = Vec::with_capacity(entries.len());
+    for (program, name, category, preset_id) in entries {
+        let bank = match patches.last() {
+            Some(prev) if prev.program == program => prev.bank.saturating_add(1),
+            _ => 0,
+        };
+        if bank < 128 {
+            patches.push(Patch {
+                bank,
+                program,
+                name,
+                category,
+                preset_id,
+            });
+        }
+    }
+    patches
+}
+
+/// Render `patches` for the device `device` (shown as the bank/script name).
+pub fn render(format: PatchListFormat, device: &str, patches: &[Patch]) -> String {
+    let mut out = String::new();
+    match format {
+        PatchListFormat::PatchList => {
+            let mut by_bank: Vec<&Patch> = patches.iter().collect();
+            by_bank.sort_by_key(|p| (p.bank, p.program));
+            let mut bank = None;
+            for p in by_bank {
+                if bank != Some(p.bank) {
+                    if bank.is_some() {
+                        out.push('\n');
+                    }
+                    let _ = writeln!(out, "Bank {} 0 {} (bank {})", p.bank, device, p.bank);
+                    bank = Some(p.bank);
+                }
+                let _ = writeln!(out, "{} {}", p.program, p.name);
+            }
+        }
+        PatchListFormat::CubaseScript => {
+            let _ = writeln!(out, "[cubase parse file]");
+            let _ = writeln!(out, "[parser version 0001]");
+            let _ = writeln!(out);
+            let _ = writeln!(out, "[creators first name]SongWalker");
+            let _ = writeln!(out, "[creators last name]");
+            let _ = writeln!(out, "[device manufacturer]SongWalker");
+            let _ = writeln!(out, "[device name]{}", device);
+            let _ = writeln!(out, "[script name]{}", device);
+            let _ = writeln!(out, "[script version]version 1.00");
+            let _ = writeln!(out);
+            let _ = writeln!(out, "[define patchnames]");
+            let _ = writeln!(out);
+            let _ = writeln!(out, "[mode]{}", device);
+            // One group per category, in first-seen order
+            let mut categories: Vec<&str> = Vec::new();
+            for p in patches {
+                if !categories.contains(&p.category.as_str()) {
+                    categories.push(&p.category);
+                }
+            }
+            for category in categories {
+                let group = if category.is_empty() {
+                    "Other"
+                } else {
+                    category
+                };
+                let _ = writeln!(out, "\n[g1]{}", group);
+                for p in patches.iter().filter(|p| p.category == category) {
+                    let _ = writeln!(out, "[p2, {}, {}, 0]{}", p.program, p.bank, p.name);
+                }
+            }
+        }
+        PatchListFormat::Csv => {
+            let _ = writeln!(out, "bank,program,name,category,preset_id");
+            for p in patches {
+                let _ = writeln!(
+                    out,
+                    "{},{},{},{},{}",
+                    p.bank,
+                    p.program,
+                    csv_field(&p.name),
+                    csv_field(&p.category),
+                    csv_field(&p.preset_id)
+                );
+            }
+        }
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Where exported lists go: `Documents/SongWalker/Patch Lists`, or the
+/// app data directory if there is no documents folder.
+pub fn export_dir() -> Option<PathBuf> {
+    directories::UserDirs::new()
+        .and_then(|d| {
+            d.document_dir()
+                .map(|p| p.join("SongWalker").join("Patch Lists"))
+        })
+        .or_else(|| {
+            directories::ProjectDirs::from("org", "SongWalker", "songwalker-vsti")
+                .map(|d| d.data_dir().join("patchlists"))
+        })
+}
+
+/// Export `library` and return the written file and program count.
+pub fn export(
+    mgr: &PresetManager,
+    library: &str,
+    format: PatchListFormat,
+) -> Result<(PathBuf, usize), String> {
+    let patches = collect_patches(mgr, library);
+    if patches.is_empty() {
+        return Err(format!("{} has no GM programs loaded", library));
+    }
+    let dir = export_dir().ok_or("No documents directory")?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let device = format!("SongWalker {}", library);
+    let file_name: String = library
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let path = dir.join(format!("{}.{}", file_name, format.extension()));
+    crate::net::atomic::write_atomic(&path, render(format, &device, &patches).as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok((path, patches.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(program: u8, name: &str, category: &str) -> (u8, String, String, String) {
+        (
+            program,
+            name.into(),
+            category.into(),
+            format!("Lib/{}", name),
+        )
+    }
+
+    fn sample() -> Vec<Patch> {
+        assign_banks(
+            vec![
+                entry(1, "Bright Piano", "Piano"),
+                entry(0, "Grand Piano", "Piano"),
+                entry(0, "Felt Piano", "Piano"),
+                entry(40, "Violin, Solo", "Strings"),
+                entry(0, "Grand Piano", "Piano"),
+            ]
+            .into_iter(),
+        )
+    }
+
+    #[test]
+    fn test_assign_banks() {
+        let patches = sample();
+        let summary: Vec<(u8, u8, &str)> = patches
+            .iter()
+            .map(|p| (p.bank, p.program, p.name.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (0, 0, "Felt Piano"),
+                (1, 0, "Grand Piano"),
+                (0, 1, "Bright Piano"),
+                (0, 40, "Violin, Solo")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_patchlist() {
+        let text = render(PatchListFormat::PatchList, "SongWalker Lib", &sample());
+        assert!(
+            text.starts_with("Bank 0 0 SongWalker Lib (bank 0)\n0 Felt Piano\n1 Bright Piano\n")
+        );
+        assert!(text.contains("\nBank 1 0 SongWalker Lib (bank 1)\n0 Grand Piano\n"));
+    }
+
+    #[test]
+    fn test_render_cubase_script() {
+        let text = render(PatchListFormat::CubaseScript, "SongWalker Lib", &sample());
+        assert!(text.starts_with("[cubase parse file]\n"));
+        assert!(text.contains("[g1]Piano\n[p2, 0, 0, 0]Felt Piano\n[p2, 0, 1, 0]Grand Piano\n"));
+        assert!(text.contains("[g1]Strings\n[p2, 40, 0, 0]Violin, Solo\n"));
+    }
+
+    #[test]
+    fn test_render_csv_quotes_fields() {
+        let text = render(PatchListFormat::Csv, "SongWalker Lib", &sample());
+        assert!(text.starts_with("bank,program,name,category,preset_id\n"));
+        assert!(text.contains("0,40,\"Violin, Solo\",Strings,\"Lib/Violin, Solo\"\n"));
+    }
+}
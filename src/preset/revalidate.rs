@@ -0,0 +1,197 @@
+//! Background revalidation of cached library indexes.
+//!
+//! The preset manager serves library indexes from the disk cache and never
+//! refetches them. This job asks the server whether each index changed
+//! (a conditional GET — unchanged indexes cost a 304), refreshes the disk
+//! cache for the ones that did, and re-parses those already shown. When the
+//! network is down the cached indexes stay in use.
+//!
+//! A pass over every library runs at startup, again on the schedule set in
+//! the network settings, and on demand; a single library can be refreshed
+//! from the browser. Presets a refreshed index adds are recorded in
+//! `changes` so the browser can highlight them, and cached presets that
+//! no longer match the index are flagged by `updates`.
+
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::jobs::{JobHandle, JobPool, JobPriority};
+use crate::net::{Freshness, HttpClient};
+use crate::preset::cache::DiskCache;
+use crate::preset::changes;
+use crate::preset::library_index;
+use crate::preset::local_library;
+use crate::preset::manager::{LibraryStatus, PresetManager};
+use crate::preset::updates;
+
+/// Outcome of one revalidation pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RevalidationSummary {
+    pub updated: usize,
+    /// Presets the updated indexes added.
+    pub new_presets: usize,
+    pub unchanged: usize,
+    pub offline: usize,
+    pub failed: usize,
+}
+
+impl RevalidationSummary {
+    /// Status bar message, or `None` if there is nothing worth reporting.
+    pub fn message(&self) -> Option<String> {
+        if self.updated > 0 && self.new_presets > 0 {
+            Some(format!("{} library index(es) updated, {} new preset(s)", self.updated, self.new_presets))
+        } else if self.updated > 0 {
+            Some(format!("{} library index(es) updated", self.updated))
+        } else if self.offline > 0 {
+            Some("Offline \u{2014} using cached libraries".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// When the last pass over every library was queued (process-wide, as
+/// instances share the preset manager).
+static LAST_PASS: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// When the last pass over every library was queued, if any was.
+pub fn last_pass() -> Option<Instant> {
+    LAST_PASS.lock().ok().and_then(|last| *last)
+}
+
+/// Queue a background pass over every known library.
+pub fn spawn_revalidation(jobs: &JobPool, manager: Arc<Mutex<PresetManager>>) -> JobHandle {
+    if let Ok(mut last) = LAST_PASS.lock() {
+        *last = Some(Instant::now());
+    }
+    spawn_pass(jobs, manager, None)
+}
+
+/// Queue a refresh of one library (the browser's refresh button).
+pub fn spawn_library_refresh(jobs: &JobPool, manager: Arc<Mutex<PresetManager>>, library: String) -> JobHandle {
+    spawn_pass(jobs, manager, Some(library))
+}
+
+fn spawn_pass(jobs: &JobPool, manager: Arc<Mutex<PresetManager>>, only: Option<String>) -> JobHandle {
+    jobs.submit(JobPriority::Background, move |ctx| {
+        let (base_url, libraries) = {
+            let Ok(mgr) = manager.lock() else { return };
+            let libs: Vec<(String, String, String, LibraryStatus)> = mgr
+                .libraries
+                .iter()
+                .filter(|l| only.as_ref().is_none_or(|name| *name == l.name))
+                .map(|l| (l.name.clone(), l.path.clone(), l.slug.clone(), l.status.clone()))
+                .collect();
+            (mgr.base_url.clone(), libs)
+        };
+
+        let client = HttpClient::with_default_store();
+        let cache = DiskCache::new();
+        let mut summary = RevalidationSummary::default();
+
+        for (name, path, slug, status) in libraries {
+            // Local libraries have nothing on the server
+            if status == LibraryStatus::Loading || local_library::is_local(&slug) {
+                continue;
+            }
+            let url = format!("{}/{}", base_url, path);
+            let Some(result) = ctx.block_on(client.get_revalidated(&url)) else {
+                return;
+            };
+            match result {
+                Ok(fetched) => match fetched.freshness {
+                    Freshness::Fresh => {
+                        let text = fetched.text();
+                        let Ok(index) = serde_json::from_str::<serde_json::Value>(&text) else {
+                            summary.failed += 1;
+                            continue;
+                        };
+                        // Nothing is new on a library's first fetch
+                        let previous = cache
+                            .read_library_index(&slug)
+                            .and_then(|t| serde_json::from_str::<serde_json::Value>(&t).ok());
+                        if let Some(previous) = previous {
+                            let added = changes::added_presets(&previous, &index);
+                            summary.new_presets += added.len();
+                            changes::record(&name, added);
+                        }
+                        let _ = cache.write_library_index(&slug, &text);
+                        if name != slug {
+                            let _ = cache.write_library_index(&name, &text);
+                        }
+                        updates::check_library(&cache, &name, &slug, &index);
+                        if status == LibraryStatus::Loaded {
+                            // Re-parse from the refreshed cache
+                            if let Ok(mut mgr) = manager.lock() {
+                                if let Some(lib) = mgr.libraries.iter_mut().find(|l| l.name == name) {
+                                    lib.status = LibraryStatus::NotLoaded;
+                                }
+                            }
+                            ctx.block_on(library_index::fetch_library_index(&client, &manager, &name));
+                        }
+                        summary.updated += 1;
+                    }
+                    Freshness::Unchanged => {
+                        summary.unchanged += 1;
+                        // Restore a torn or missing core cache entry from our
+                        // checksummed copy
+                        let intact = cache.read_library_index(&slug).is_some_and(|t| {
+                            serde_json::from_str::<serde_json::Value>(&t).is_ok()
+                        });
+                        if !intact {
+                            nih_plug::debug::nih_log!(
+                                "[Revalidate] Repairing cached index of {}",
+                                name
+                            );
+                            let text = fetched.text();
+                            let _ = cache.write_library_index(&slug, &text);
+                            if name != slug {
+                                let _ = cache.write_library_index(&name, &text);
+                            }
+                            if matches!(status, LibraryStatus::Error(_)) {
+                                ctx.block_on(library_index::fetch_library_index(&client, &manager, &name));
+                            }
+                        }
+                        if let Ok(index) = serde_json::from_slice::<serde_json::Value>(&fetched.body) {
+                            updates::check_library(&cache, &name, &slug, &index);
+                        }
+                    }
+                    Freshness::Offline => summary.offline += 1,
+                },
+                Err(e) => {
+                    nih_plug::debug::nih_log!("[Revalidate] {}: {}", name, e);
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        nih_plug::debug::nih_log!("[Revalidate] {:?}", summary);
+        let message = match &only {
+            Some(name) if summary == RevalidationSummary { unchanged: 1, ..Default::default() } => {
+                Some(format!("{} is up to date", name))
+            }
+            _ => summary.message(),
+        };
+        if let Some(message) = message {
+            if let Ok(mut mgr) = manager.lock() {
+                mgr.status_message = message;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_message() {
+        assert_eq!(RevalidationSummary::default().message(), None);
+        let s = RevalidationSummary { updated: 2, offline: 1, ..Default::default() };
+        assert_eq!(s.message().as_deref(), Some("2 library index(es) updated"));
+        let s = RevalidationSummary { updated: 1, new_presets: 4, ..Default::default() };
+        assert_eq!(s.message().as_deref(), Some("1 library index(es) updated, 4 new preset(s)"));
+        let s = RevalidationSummary { offline: 3, ..Default::default() };
+        assert!(s.message().unwrap().starts_with("Offline"));
+    }
+}
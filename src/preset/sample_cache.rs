@@ -0,0 +1,209 @@
+//! Process-wide in-memory sample deduplication.
+//!
+//! Many library presets reference the same underlying samples (identical
+//! URLs or content hashes). The preset fetcher looks each zone's sample up
+//! here before reading the disk cache or the network, and interns what it
+//! decodes, so every preset referencing the same sample shares one
+//! `Arc<[f32]>` and a sample already in memory is never fetched or decoded
+//! again. Entries are weak: a sample is freed as soon as the last preset
+//! using it is dropped.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+use songwalker_core::preset::AudioReference;
+
+/// Snapshot of cache statistics for the UI.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SampleCacheStats {
+    /// Distinct samples currently alive.
+    pub samples: usize,
+    /// Bytes of PCM held by those samples (each counted once).
+    pub bytes: usize,
+    /// Zones referencing a cached sample (across all loaded presets).
+    pub references: usize,
+    /// Lookups that reused an existing sample.
+    pub hits: u64,
+    /// Lookups that stored a new sample.
+    pub misses: u64,
+}
+
+impl SampleCacheStats {
+    /// Bytes that would have been stored without deduplication.
+    pub fn bytes_saved(&self) -> usize {
+        if self.samples == 0 {
+            return 0;
+        }
+        let avg = self.bytes / self.samples;
+        self.references.saturating_sub(self.samples) * avg
+    }
+}
+
+/// A sample shared by the presets using it.
+struct Shared {
+    pcm: Weak<[f32]>,
+    sample_rate: u32,
+}
+
+/// Shared PCM keyed by sample identity.
+#[derive(Default)]
+pub struct SampleCache {
+    entries: Mutex<HashMap<String, Shared>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// The process-wide sample cache.
+pub fn global() -> &'static SampleCache {
+    static CACHE: OnceLock<SampleCache> = OnceLock::new();
+    CACHE.get_or_init(SampleCache::default)
+}
+
+impl SampleCache {
+    /// The shared PCM and sample rate for `key`, if a loaded preset still
+    /// holds it.
+    pub fn lookup(&self, key: &str) -> Option<(Arc<[f32]>, u32)> {
+        let entries = self.entries.lock().ok()?;
+        let shared = entries.get(key)?;
+        let pcm = shared.pcm.upgrade()?;
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some((pcm, shared.sample_rate))
+    }
+
+    /// Return the shared PCM for `key`, storing `pcm` if none is alive.
+    pub fn intern(&self, key: &str, pcm: Arc<[f32]>, sample_rate: u32) -> Arc<[f32]> {
+        let Ok(mut entries) = self.entries.lock() else {
+            return pcm;
+        };
+        if let Some(existing) = entries.get(key).and_then(|s| s.pcm.upgrade()) {
+            if existing.len() == pcm.len() {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return existing;
+            }
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        entries.retain(|_, s| s.pcm.strong_count() > 0);
+        entries.insert(key.to_string(), Shared { pcm: Arc::downgrade(&pcm), sample_rate });
+        pcm
+    }
+
+    /// Stop sharing the PCM stored for `key`, so the next load keeps its
+    /// own decode (used when the sample behind the key changed).
+    pub fn forget(&self, key: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.remove(key);
+        }
+    }
+
+    pub fn stats(&self) -> SampleCacheStats {
+        let mut stats = SampleCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            ..Default::default()
+        };
+        if let Ok(entries) = self.entries.lock() {
+            for pcm in entries.values().filter_map(|s| s.pcm.upgrade()) {
+                stats.samples += 1;
+                stats.bytes += pcm.len() * std::mem::size_of::<f32>();
+                // Minus the temporary upgrade above
+                stats.references += Arc::strong_count(&pcm) - 1;
+            }
+        }
+        stats
+    }
+}
+
+/// Identity of a sample, or `None` for inline data (never shared).
+///
+/// A content hash wins when present; otherwise the URL, resolved against
+/// the preset directory when relative.
+pub fn sample_key(library: &str, preset_path: &str, audio: &AudioReference) -> Option<String> {
+    match audio {
+        AudioReference::External { url, sha256, .. } => {
+            Some(external_key(library, preset_path, url, sha256.as_deref()))
+        }
+        AudioReference::ContentAddressed { hash, .. } => Some(format!("hash:{}", hash)),
+        AudioReference::InlineFile { .. } | AudioReference::InlinePcm { .. } => None,
+    }
+}
+
+/// Identity of an external sample, from its URL and optional hash.
+pub fn external_key(library: &str, preset_path: &str, url: &str, sha256: Option<&str>) -> String {
+    if let Some(hash) = sha256 {
+        return format!("hash:{}", hash);
+    }
+    if url.starts_with("http") {
+        url.to_string()
+    } else {
+        let dir = preset_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+        format!("{}/{}/{}", library, dir, url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use songwalker_core::preset::AudioCodec;
+
+    fn external(url: &str) -> AudioReference {
+        AudioReference::External { url: url.into(), codec: AudioCodec::Mp3, sha256: None }
+    }
+
+    #[test]
+    fn test_intern_shares_pcm() {
+        let cache = SampleCache::default();
+        assert!(cache.lookup("k").is_none());
+        let a = cache.intern("k", Arc::from(vec![0.5f32; 8]), 44100);
+        let b = cache.intern("k", Arc::from(vec![0.5f32; 8]), 44100);
+        assert!(Arc::ptr_eq(&a, &b));
+        let (c, sample_rate) = cache.lookup("k").unwrap();
+        assert!(Arc::ptr_eq(&a, &c));
+        assert_eq!(sample_rate, 44100);
+        drop(c);
+
+        let stats = cache.stats();
+        assert_eq!(stats.samples, 1);
+        assert_eq!(stats.references, 2);
+        assert_eq!(stats.bytes, 32);
+        assert_eq!((stats.hits, stats.misses), (2, 1));
+        assert_eq!(stats.bytes_saved(), 32);
+    }
+
+    #[test]
+    fn test_entries_die_with_last_reference() {
+        let cache = SampleCache::default();
+        let a = cache.intern("k", Arc::from(vec![0.0f32; 4]), 44100);
+        drop(a);
+        assert_eq!(cache.stats().samples, 0);
+        assert!(cache.lookup("k").is_none());
+        let b = cache.intern("k", Arc::from(vec![1.0f32; 4]), 44100);
+        assert_eq!(b[0], 1.0, "a dead entry is replaced, not resurrected");
+    }
+
+    #[test]
+    fn test_sample_key_resolves_relative_urls() {
+        assert_eq!(
+            sample_key("gm", "piano/preset.json", &external("C4.mp3")).as_deref(),
+            Some("gm/piano/C4.mp3")
+        );
+        assert_eq!(
+            sample_key("gm", "organ/preset.json", &external("C4.mp3")).as_deref(),
+            Some("gm/organ/C4.mp3")
+        );
+        assert_eq!(
+            sample_key("gm", "x", &external("https://cdn/a.mp3")).as_deref(),
+            Some("https://cdn/a.mp3")
+        );
+    }
+
+    #[test]
+    fn test_sample_key_prefers_hash() {
+        let audio = AudioReference::External {
+            url: "C4.mp3".into(),
+            codec: AudioCodec::Mp3,
+            sha256: Some("abc".into()),
+        };
+        assert_eq!(sample_key("gm", "piano", &audio).as_deref(), Some("hash:abc"));
+    }
+}
@@ -0,0 +1,91 @@
+//! One preset manager for every plugin instance in the process.
+//!
+//! A DAW project with several SongWalker instances would otherwise load
+//! the root index, fetch and parse every library index, and run the
+//! revalidation and indexing passes once per instance. Instances instead
+//! share the manager returned by [`manager`], so the library list, parsed
+//! indexes and load status are kept once and refreshed once. The manager
+//! is freed with the last instance; the next instance starts a new one.
+//! Browser filters are kept per editor (`view_model::PresetFilter`);
+//! expanded library folders are part of the manager and so are shared.
+//!
+//! The disk cache (including HTTP validators) and the in-memory sample
+//! dedup cache (see `sample_cache`) are already per user and per process
+//! respectively, so decoded samples are shared between instances too.
+//! Hosts that run each plugin in its own process still share the disk
+//! cache, just not the in-memory state.
+
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::jobs::JobPool;
+use crate::preset::manager::PresetManager;
+
+struct Shared {
+    manager: Weak<Mutex<PresetManager>>,
+    /// Whether revalidation and indexing were queued for `manager`.
+    services_started: bool,
+}
+
+static SHARED: Mutex<Shared> = Mutex::new(Shared {
+    manager: Weak::new(),
+    services_started: false,
+});
+
+/// The process-wide preset manager, created by the first caller.
+pub fn manager() -> Arc<Mutex<PresetManager>> {
+    let Ok(mut shared) = SHARED.lock() else {
+        return Arc::new(Mutex::new(PresetManager::new()));
+    };
+    if let Some(manager) = shared.manager.upgrade() {
+        return manager;
+    }
+    let manager = Arc::new(Mutex::new(PresetManager::new()));
+    shared.manager = Arc::downgrade(&manager);
+    shared.services_started = false;
+    manager
+}
+
+/// Number of instances holding the shared manager.
+pub fn instances() -> usize {
+    SHARED.lock().map_or(0, |shared| shared.manager.strong_count())
+}
+
+/// Queue index revalidation and background indexing on `jobs`, once per
+/// shared manager. Returns whether this call queued them.
+pub fn start_services(jobs: &JobPool, manager: &Arc<Mutex<PresetManager>>) -> bool {
+    {
+        let Ok(mut shared) = SHARED.lock() else { return false };
+        let is_shared = shared.manager.upgrade().is_some_and(|m| Arc::ptr_eq(&m, manager));
+        if is_shared && shared.services_started {
+            return false;
+        }
+        if is_shared {
+            shared.services_started = true;
+        }
+    }
+    crate::preset::revalidate::spawn_revalidation(jobs, manager.clone());
+    crate::preset::indexer::spawn_indexer(jobs, manager.clone());
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instances_share_one_manager() {
+        let first = manager();
+        let second = manager();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(instances() >= 2);
+
+        // Services start once per manager (the pool is never started)
+        let jobs = JobPool::new(1);
+        start_services(&jobs, &first);
+        assert!(!start_services(&jobs, &second), "already queued for this manager");
+
+        // A manager outside the shared one always gets its own passes
+        let own = Arc::new(Mutex::new(PresetManager::new()));
+        assert!(start_services(&jobs, &own));
+    }
+}
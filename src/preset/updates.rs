@@ -0,0 +1,230 @@
+//! Cached presets that differ from the library's current version.
+//!
+//! The core loader serves preset descriptors from the disk cache and never
+//! refetches them, so a preset updated on the server keeps playing the old
+//! version. After each index refresh (see `revalidate`) the cached
+//! descriptors of the library are compared with its index entries — the
+//! entry's `sha256` when the index lists one, otherwise its `zoneCount` —
+//! and the presets that differ get an update badge in the browser.
+//!
+//! Re-downloading replaces the cached descriptor and invalidates the
+//! samples whose reference changed: their raw copies are evicted from the
+//! HTTP store and their PCM is no longer shared, so slots reloading the
+//! preset decode them afresh. Decoded samples in the core disk cache are
+//! keyed by URL or hash, so a changed reference misses it anyway.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde_json::Value;
+
+use crate::jobs::{JobHandle, JobPool, JobPriority};
+use crate::net::HttpClient;
+use crate::preset::cache::DiskCache;
+use crate::preset::manager::PresetManager;
+use crate::preset::{graph, integrity, sample_cache};
+
+/// Presets with an update available, and the ones re-downloaded since the
+/// editor last looked.
+#[derive(Debug, Default)]
+pub struct Updates {
+    /// (library, preset path)
+    outdated: HashSet<(String, String)>,
+    refreshed: Vec<(String, String)>,
+}
+
+/// The process-wide record (rebuilt by every index refresh).
+pub fn global() -> &'static Mutex<Updates> {
+    static STORE: OnceLock<Mutex<Updates>> = OnceLock::new();
+    STORE.get_or_init(|| Mutex::new(Updates::default()))
+}
+
+impl Updates {
+    pub fn is_outdated(&self, library: &str, path: &str) -> bool {
+        self.outdated.iter().any(|(lib, p)| lib == library && p == path)
+    }
+
+    pub fn count(&self, library: &str) -> usize {
+        self.outdated.iter().filter(|(lib, _)| lib == library).count()
+    }
+
+    /// Outdated preset paths of `library`.
+    pub fn outdated(&self, library: &str) -> Vec<String> {
+        let mut paths: Vec<String> =
+            self.outdated.iter().filter(|(lib, _)| lib == library).map(|(_, p)| p.clone()).collect();
+        paths.sort();
+        paths
+    }
+
+    /// Presets re-downloaded since the last call, for reloading the slots
+    /// that play them.
+    pub fn take_refreshed(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.refreshed)
+    }
+}
+
+/// Whether a cached descriptor differs from its library index entry.
+pub fn differs(entry: &Value, cached: &str) -> bool {
+    if let Some(hash) = entry.get("sha256").and_then(Value::as_str) {
+        return !integrity::sha256_hex(cached.as_bytes()).eq_ignore_ascii_case(hash);
+    }
+    let expected = entry.get("zoneCount").and_then(Value::as_u64).unwrap_or(0);
+    if expected == 0 {
+        return false;
+    }
+    let Ok(json) = serde_json::from_str::<Value>(cached) else {
+        return false;
+    };
+    graph::zone_count(json.get("graph").unwrap_or(&json)) as u64 != expected
+}
+
+/// Compare the cached descriptors of `library` with its index and record
+/// the presets that differ, replacing the library's previous record.
+pub fn check_library(cache: &DiskCache, library: &str, slug: &str, index: &Value) {
+    let outdated: Vec<String> = index
+        .get("entries")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.get("type").and_then(Value::as_str) == Some("preset"))
+        .filter_map(|entry| {
+            let path = entry.get("path").and_then(Value::as_str)?;
+            let cached = cache.read_preset(slug, path)?;
+            differs(entry, &cached).then(|| path.to_string())
+        })
+        .collect();
+    if !outdated.is_empty() {
+        nih_plug::debug::nih_log!("[Updates] {}: {} preset(s) changed", library, outdated.len());
+    }
+    if let Ok(mut updates) = global().lock() {
+        updates.outdated.retain(|(lib, _)| lib != library);
+        updates
+            .outdated
+            .extend(outdated.into_iter().map(|path| (library.to_string(), path)));
+    }
+}
+
+/// `(url, sha256)` of every external sample a descriptor references.
+fn sample_refs(value: &Value, out: &mut HashSet<(String, Option<String>)>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(url) = map.get("url").and_then(Value::as_str) {
+                let hash = map.get("sha256").and_then(Value::as_str).map(str::to_string);
+                out.insert((url.to_string(), hash));
+            }
+            for v in map.values() {
+                sample_refs(v, out);
+            }
+        }
+        Value::Array(items) => {
+            for v in items {
+                sample_refs(v, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Samples of the `old` descriptor that `new` no longer references as is
+/// (dropped, or pinned to a different hash).
+pub fn changed_samples(old: &Value, new: &Value) -> Vec<(String, Option<String>)> {
+    let mut before = HashSet::new();
+    let mut after = HashSet::new();
+    sample_refs(old, &mut before);
+    sample_refs(new, &mut after);
+    let mut changed: Vec<_> = before.difference(&after).cloned().collect();
+    changed.sort();
+    changed
+}
+
+/// Queue a re-download of one outdated preset. The result is shown in the
+/// browser status line.
+pub fn spawn_redownload(
+    jobs: &JobPool,
+    manager: Arc<Mutex<PresetManager>>,
+    library: String,
+    path: String,
+) -> JobHandle {
+    jobs.submit(JobPriority::Interactive, move |ctx| {
+        let (base_url, slug) = {
+            let Ok(mgr) = manager.lock() else { return };
+            let slug = mgr
+                .libraries
+                .iter()
+                .find(|l| l.name == library)
+                .map(|l| l.slug.clone())
+                .unwrap_or_else(|| library.clone());
+            (mgr.base_url.clone(), slug)
+        };
+        let name = path.rsplit('/').next().unwrap_or(&path).to_string();
+        let client = HttpClient::with_default_store();
+        let url = format!("{}/{}/{}", base_url, slug, path);
+        let Some(result) = ctx.block_on(client.get_revalidated(&url)) else {
+            return;
+        };
+        let text = match result.map(|fetched| fetched.text()) {
+            Ok(text) if serde_json::from_str::<Value>(&text).is_ok() => text,
+            Ok(_) => {
+                set_status(&manager, format!("Update of {} failed: invalid preset", name));
+                return;
+            }
+            Err(e) => {
+                set_status(&manager, format!("Update of {} failed: {}", name, e));
+                return;
+            }
+        };
+
+        let cache = DiskCache::new();
+        let old = cache.read_preset(&slug, &path).and_then(|t| serde_json::from_str::<Value>(&t).ok());
+        if let (Some(old), Ok(new)) = (old, serde_json::from_str::<Value>(&text)) {
+            let shared = sample_cache::global();
+            for (sample, hash) in changed_samples(&old, &new) {
+                cache.remove_sample(&slug, &path, &sample);
+                shared.forget(&sample_cache::external_key(&slug, &path, &sample, hash.as_deref()));
+            }
+        }
+        if let Err(e) = cache.write_preset(&slug, &path, &text) {
+            set_status(&manager, format!("Update of {} failed: {}", name, e));
+            return;
+        }
+
+        if let Ok(mut updates) = global().lock() {
+            updates.outdated.remove(&(library.clone(), path.clone()));
+            updates.refreshed.push((library.clone(), path.clone()));
+        }
+        set_status(&manager, format!("Updated {}", name));
+    })
+}
+
+fn set_status(manager: &Arc<Mutex<PresetManager>>, message: String) {
+    if let Ok(mut mgr) = manager.lock() {
+        mgr.status_message = message;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_differs_and_changed_samples() {
+        let cached = r#"{"graph": {"type": "sampler", "config": {"zones": [
+            {"audio": {"url": "a.mp3"}},
+            {"audio": {"url": "b.mp3", "sha256": "11"}}
+        ]}}}"#;
+        assert!(!differs(&serde_json::json!({ "zoneCount": 2 }), cached));
+        assert!(differs(&serde_json::json!({ "zoneCount": 3 }), cached));
+        assert!(!differs(&serde_json::json!({}), cached), "nothing to compare");
+        let hash = integrity::sha256_hex(cached.as_bytes());
+        assert!(!differs(&serde_json::json!({ "sha256": hash, "zoneCount": 9 }), cached));
+        assert!(differs(&serde_json::json!({ "sha256": "00" }), cached));
+
+        let old: Value = serde_json::from_str(cached).unwrap();
+        let new = serde_json::json!({ "graph": { "type": "sampler", "config": { "zones": [
+            { "audio": { "url": "a.mp3" } },
+            { "audio": { "url": "b.mp3", "sha256": "22" } },
+            { "audio": { "url": "c.mp3" } },
+        ]}}});
+        assert_eq!(changed_samples(&old, &new), vec![("b.mp3".to_string(), Some("11".to_string()))]);
+    }
+}
@@ -0,0 +1,166 @@
+//! Factory programs: ready-made racks selectable from the host.
+//!
+//! nih-plug has no VST3/CLAP factory-preset list, so the programs are a
+//! host-visible "Program" choice parameter. When it changes, the audio
+//! thread hands the program to a background thread that rebuilds the rack
+//! and fetches the presets it references, delivering them straight to the
+//! audio thread (like fired MIDI rules, this works with the editor closed).
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use nih_plug::prelude::Enum;
+
+use crate::editor::loads::{LoadManager, LoadTarget};
+use crate::editor::event_queue::EventSender;
+use crate::editor::{EditorEvent, PresetLoadedEvent};
+use crate::jobs::JobPool;
+use crate::preset::manager::PresetManager;
+use crate::state::{PluginState, SlotConfig};
+
+/// Capacity of the program channel (audio thread → loader).
+pub const PROGRAM_CHANNEL_CAPACITY: usize = 4;
+
+/// How often the loader checks for finished preset fetches.
+const DELIVER_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A factory program. `Custom` (the default) stands for whatever the rack
+/// holds and selecting it changes nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Enum)]
+pub enum FactoryProgram {
+    #[default]
+    #[name = "(Custom)"]
+    Custom,
+    #[name = "Empty Rack"]
+    EmptyRack,
+    #[name = "GM Piano"]
+    GmPiano,
+    #[name = "GM Strings"]
+    GmStrings,
+    #[name = "GM Drum Kit"]
+    GmDrumKit,
+    #[name = "Piano + Strings"]
+    PianoStrings,
+}
+
+impl FactoryProgram {
+    /// Slots of the program: (slot name, preset id).
+    pub fn slots(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Custom | Self::EmptyRack => &[],
+            Self::GmPiano => &[("Piano", "FluidR3_GM/Acoustic Grand Piano")],
+            Self::GmStrings => &[("Strings", "FluidR3_GM/Synth Strings 1")],
+            Self::GmDrumKit => &[
+                ("Kick", "FluidR3_GM/Standard Kit/Kick"),
+                ("Snare", "FluidR3_GM/Standard Kit/Snare"),
+                ("Hi-Hat", "FluidR3_GM/Standard Kit/Closed Hi-Hat"),
+            ],
+            Self::PianoStrings => &[
+                ("Piano", "FluidR3_GM/Acoustic Grand Piano"),
+                ("Strings", "FluidR3_GM/Synth Strings 1"),
+            ],
+        }
+    }
+
+    /// Replace the rack with the program's slots. Returns the number of
+    /// slots the rack had before, so the caller can unload the extras.
+    pub fn configure(self, state: &mut PluginState) -> usize {
+        let previous = state.slot_configs.len();
+        state.slot_configs = self
+            .slots()
+            .iter()
+            .map(|(name, preset_id)| SlotConfig::new_preset(name, preset_id))
+            .collect();
+        state.groups.clear();
+        previous
+    }
+}
+
+/// Spawn the thread that applies selected programs. The thread exits once
+/// every sender has been dropped.
+pub fn spawn_loader(
+    rx: Receiver<FactoryProgram>,
+    jobs: Arc<JobPool>,
+    preset_manager: Arc<Mutex<PresetManager>>,
+    plugin_state: Arc<Mutex<PluginState>>,
+    event_tx: EventSender,
+    preset_loaded_tx: Sender<PresetLoadedEvent>,
+    status_text: Arc<Mutex<String>>,
+) {
+    let spawned = std::thread::Builder::new()
+        .name("songwalker-programs".into())
+        .spawn(move || {
+            let mut loads = LoadManager::default();
+            loop {
+                match rx.recv_timeout(DELIVER_INTERVAL) {
+                    Ok(FactoryProgram::Custom) => {}
+                    Ok(program) => {
+                        nih_plug::debug::nih_log!("[Programs] {:?}", program);
+                        let Ok((previous, sync)) = plugin_state.lock().map(|mut ps| {
+                            let previous = program.configure(&mut ps);
+                            (previous, crate::view_model::sync_all(&ps))
+                        }) else {
+                            continue;
+                        };
+                        // The new slots start from default settings, and the
+                        // groups are gone
+                        for event in sync {
+                            let _ = event_tx.try_send(event);
+                        }
+                        // Slots left over from the old rack go quiet
+                        for slot_index in program.slots().len()..previous {
+                            loads.cancel(LoadTarget::Slot(slot_index));
+                            let _ = event_tx.try_send(EditorEvent::UnloadPreset { slot_index });
+                        }
+                        for (slot_index, (_, preset_id)) in program.slots().iter().enumerate() {
+                            let Some((library, path)) = preset_id.split_once('/') else {
+                                continue;
+                            };
+                            loads.request(
+                                &jobs,
+                                &preset_manager,
+                                LoadTarget::Slot(slot_index),
+                                library,
+                                path,
+                                slot_index,
+                                None,
+                            );
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                loads.deliver(&preset_loaded_tx, &status_text);
+            }
+        });
+    if let Err(e) = spawned {
+        log::warn!("Failed to spawn program loader thread: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configure_replaces_rack() {
+        let mut state = PluginState::default();
+        for _ in 0..4 {
+            state.add_slot_config(SlotConfig::default());
+        }
+        state.add_group("Drums");
+
+        let previous = FactoryProgram::PianoStrings.configure(&mut state);
+        assert_eq!(previous, 4);
+        assert_eq!(state.slot_configs.len(), 2);
+        assert_eq!(
+            state.slot_configs[1].preset_id.as_deref(),
+            Some("FluidR3_GM/Synth Strings 1")
+        );
+        assert!(state.groups.is_empty());
+
+        FactoryProgram::EmptyRack.configure(&mut state);
+        assert!(state.slot_configs.is_empty());
+    }
+}
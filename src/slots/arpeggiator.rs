@@ -0,0 +1,394 @@
+use serde::{Deserialize, Serialize};
+
+use crate::transport::TransportState;
+
+/// Maximum number of held notes the arpeggiator tracks.
+pub const MAX_HELD_NOTES: usize = 32;
+
+/// Maximum octave range.
+pub const MAX_OCTAVES: u8 = 4;
+
+/// Step order for the arpeggiator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ArpMode {
+    #[default]
+    Up,
+    Down,
+    UpDown,
+    Random,
+    AsPlayed,
+}
+
+impl ArpMode {
+    pub const ALL: [ArpMode; 5] = [
+        ArpMode::Up,
+        ArpMode::Down,
+        ArpMode::UpDown,
+        ArpMode::Random,
+        ArpMode::AsPlayed,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ArpMode::Up => "Up",
+            ArpMode::Down => "Down",
+            ArpMode::UpDown => "Up/Down",
+            ArpMode::Random => "Random",
+            ArpMode::AsPlayed => "As Played",
+        }
+    }
+}
+
+/// User-facing arpeggiator settings (persisted in `SlotConfig`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ArpSettings {
+    /// Whether held notes are arpeggiated.
+    pub enabled: bool,
+    /// Step order.
+    pub mode: ArpMode,
+    /// Step length in beats (0.25 = 1/16 note in 4/4).
+    pub rate: f64,
+    /// Note length as a fraction of the step (0.05–1.0).
+    pub gate: f32,
+    /// Octave range (1–4).
+    pub octaves: u8,
+    /// Keep arpeggiating after keys are released, until the next chord.
+    pub latch: bool,
+}
+
+impl Default for ArpSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: ArpMode::Up,
+            rate: 0.25,
+            gate: 0.5,
+            octaves: 1,
+            latch: false,
+        }
+    }
+}
+
+/// A note generated by the arpeggiator, delivered to the slot before
+/// voices are allocated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArpNote {
+    pub note: u8,
+    pub velocity: f32,
+    /// true = Note On, false = Note Off.
+    pub on: bool,
+}
+
+/// Per-slot arpeggiator engine.
+///
+/// Held notes are collected from MIDI input and replayed one step at a
+/// time at a tempo-synced rate. All storage is pre-allocated.
+pub struct Arpeggiator {
+    settings: ArpSettings,
+    /// Held notes in the order they were pressed: (note, velocity).
+    held: Vec<(u8, f32)>,
+    /// Held notes sorted by pitch.
+    sorted: Vec<(u8, f32)>,
+    /// Number of keys physically down (for latch).
+    keys_down: usize,
+    /// Step counter within the pattern.
+    step: usize,
+    /// Beats elapsed since the current step began.
+    phase: f64,
+    /// Currently sounding note.
+    sounding: Option<u8>,
+    /// Whether the pattern is running.
+    running: bool,
+    /// xorshift state for random mode.
+    rng: u32,
+    /// Notes generated since the slot last drained them.
+    events: Vec<ArpNote>,
+}
+
+impl Default for Arpeggiator {
+    fn default() -> Self {
+        Self {
+            settings: ArpSettings::default(),
+            held: Vec::with_capacity(MAX_HELD_NOTES),
+            sorted: Vec::with_capacity(MAX_HELD_NOTES),
+            keys_down: 0,
+            step: 0,
+            phase: 0.0,
+            sounding: None,
+            running: false,
+            rng: 0x9E37_79B9,
+            events: Vec::with_capacity(MAX_HELD_NOTES * 2),
+        }
+    }
+}
+
+impl Arpeggiator {
+    pub fn settings(&self) -> ArpSettings {
+        self.settings
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.settings.enabled
+    }
+
+    /// Apply new settings. Disabling (or turning latch off with no keys
+    /// down) releases the sounding note.
+    pub fn set_settings(&mut self, settings: ArpSettings) {
+        let was_enabled = self.settings.enabled;
+        self.settings = ArpSettings {
+            rate: settings.rate.max(1.0 / 64.0),
+            gate: settings.gate.clamp(0.05, 1.0),
+            octaves: settings.octaves.clamp(1, MAX_OCTAVES),
+            ..settings
+        };
+        if was_enabled && !settings.enabled {
+            self.reset();
+        } else if !settings.latch && self.keys_down == 0 {
+            self.stop();
+        }
+    }
+
+    /// A key was pressed.
+    pub fn note_on(&mut self, note: u8, velocity: f32) {
+        // In latch mode a new chord (after all keys were released) replaces the old one
+        if self.settings.latch && self.keys_down == 0 {
+            self.held.clear();
+            self.sorted.clear();
+        }
+        self.keys_down += 1;
+
+        if self.held.iter().any(|(n, _)| *n == note) || self.held.len() >= MAX_HELD_NOTES {
+            return;
+        }
+        self.held.push((note, velocity));
+        let pos = self.sorted.partition_point(|(n, _)| *n < note);
+        self.sorted.insert(pos, (note, velocity));
+
+        if !self.running {
+            // First note: start immediately on the next block
+            self.running = true;
+            self.step = 0;
+            self.phase = self.settings.rate;
+        }
+    }
+
+    /// A key was released.
+    pub fn note_off(&mut self, note: u8) {
+        self.keys_down = self.keys_down.saturating_sub(1);
+        if self.settings.latch {
+            return;
+        }
+        self.held.retain(|(n, _)| *n != note);
+        self.sorted.retain(|(n, _)| *n != note);
+        if self.held.is_empty() {
+            self.stop();
+        }
+    }
+
+    /// Forget all held notes and release the sounding note.
+    pub fn reset(&mut self) {
+        self.held.clear();
+        self.sorted.clear();
+        self.keys_down = 0;
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        if self.held.is_empty() || !self.settings.latch {
+            self.held.clear();
+            self.sorted.clear();
+        }
+        self.release_sounding();
+        self.running = false;
+        self.step = 0;
+        self.phase = 0.0;
+    }
+
+    fn release_sounding(&mut self) {
+        if let Some(note) = self.sounding.take() {
+            self.push(ArpNote { note, velocity: 0.0, on: false });
+        }
+    }
+
+    fn push(&mut self, note: ArpNote) {
+        if self.events.len() < self.events.capacity() {
+            self.events.push(note);
+        }
+    }
+
+    /// Advance by one block, generating note events at step boundaries.
+    ///
+    /// Steps and gate releases are quantized to the start of the block.
+    pub fn advance(&mut self, num_samples: usize, sample_rate: f32, transport: &TransportState) {
+        if !self.running || self.held.is_empty() {
+            return;
+        }
+
+        let beat_advance = transport.bpm / 60.0 / sample_rate as f64 * num_samples as f64;
+        let rate = self.settings.rate;
+        let gate_beats = rate * self.settings.gate as f64;
+
+        // Gate elapsed: release the current step
+        if self.phase >= gate_beats {
+            self.release_sounding();
+        }
+
+        // Steps that are due (more than one only if the block exceeds the rate)
+        while self.phase >= rate {
+            self.phase -= rate;
+            self.release_sounding();
+            if let Some((note, velocity)) = self.next_note() {
+                self.push(ArpNote { note, velocity, on: true });
+                self.sounding = Some(note);
+            }
+            self.step = self.step.wrapping_add(1);
+        }
+
+        self.phase += beat_advance;
+    }
+
+    /// Note for the current step.
+    fn next_note(&mut self) -> Option<(u8, f32)> {
+        let n = self.held.len();
+        if n == 0 {
+            return None;
+        }
+        let octaves = self.settings.octaves.max(1) as usize;
+        let len = n * octaves;
+
+        let index = match self.settings.mode {
+            ArpMode::Up | ArpMode::AsPlayed => self.step % len,
+            ArpMode::Down => len - 1 - (self.step % len),
+            ArpMode::UpDown => {
+                if len == 1 {
+                    0
+                } else {
+                    let period = 2 * len - 2;
+                    let i = self.step % period;
+                    if i < len { i } else { period - i }
+                }
+            }
+            ArpMode::Random => {
+                self.rng ^= self.rng << 13;
+                self.rng ^= self.rng >> 17;
+                self.rng ^= self.rng << 5;
+                self.rng as usize % len
+            }
+        };
+
+        let source = if self.settings.mode == ArpMode::AsPlayed { &self.held } else { &self.sorted };
+        let (note, velocity) = source[index % n];
+        let octave = (index / n) as i32;
+        let note = (note as i32 + octave * 12).clamp(0, 127) as u8;
+        Some((note, velocity))
+    }
+
+    /// Notes generated since the last drain.
+    pub fn events_mut(&mut self) -> &mut Vec<ArpNote> {
+        &mut self.events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled(mode: ArpMode) -> Arpeggiator {
+        let mut arp = Arpeggiator::default();
+        arp.set_settings(ArpSettings {
+            enabled: true,
+            mode,
+            ..ArpSettings::default()
+        });
+        arp
+    }
+
+    /// Run enough blocks for `steps` steps at 120 BPM and collect note-ons.
+    fn run_steps(arp: &mut Arpeggiator, steps: usize) -> Vec<u8> {
+        let transport = TransportState::default();
+        // 1/16 at 120 BPM = 0.125 s = 5512.5 samples at 44.1 kHz
+        let block = 5513;
+        let mut notes = Vec::new();
+        for _ in 0..steps {
+            arp.advance(block, 44100.0, &transport);
+            notes.extend(arp.events_mut().drain(..).filter(|e| e.on).map(|e| e.note));
+        }
+        notes
+    }
+
+    #[test]
+    fn test_up_mode() {
+        let mut arp = enabled(ArpMode::Up);
+        arp.note_on(64, 1.0);
+        arp.note_on(60, 1.0);
+        arp.note_on(67, 1.0);
+        assert_eq!(run_steps(&mut arp, 4), vec![60, 64, 67, 60]);
+    }
+
+    #[test]
+    fn test_down_mode() {
+        let mut arp = enabled(ArpMode::Down);
+        arp.note_on(60, 1.0);
+        arp.note_on(64, 1.0);
+        assert_eq!(run_steps(&mut arp, 3), vec![64, 60, 64]);
+    }
+
+    #[test]
+    fn test_up_down_mode() {
+        let mut arp = enabled(ArpMode::UpDown);
+        arp.note_on(60, 1.0);
+        arp.note_on(64, 1.0);
+        arp.note_on(67, 1.0);
+        assert_eq!(run_steps(&mut arp, 5), vec![60, 64, 67, 64, 60]);
+    }
+
+    #[test]
+    fn test_as_played_mode() {
+        let mut arp = enabled(ArpMode::AsPlayed);
+        arp.note_on(67, 1.0);
+        arp.note_on(60, 1.0);
+        assert_eq!(run_steps(&mut arp, 3), vec![67, 60, 67]);
+    }
+
+    #[test]
+    fn test_octave_range() {
+        let mut arp = Arpeggiator::default();
+        arp.set_settings(ArpSettings { enabled: true, octaves: 2, ..ArpSettings::default() });
+        arp.note_on(60, 1.0);
+        arp.note_on(64, 1.0);
+        assert_eq!(run_steps(&mut arp, 4), vec![60, 64, 72, 76]);
+    }
+
+    #[test]
+    fn test_release_stops_pattern() {
+        let mut arp = enabled(ArpMode::Up);
+        arp.note_on(60, 1.0);
+        run_steps(&mut arp, 1);
+        arp.note_off(60);
+        let events: Vec<ArpNote> = arp.events_mut().drain(..).collect();
+        assert_eq!(events, vec![ArpNote { note: 60, velocity: 0.0, on: false }]);
+        assert!(run_steps(&mut arp, 2).is_empty());
+    }
+
+    #[test]
+    fn test_latch_keeps_playing_until_new_chord() {
+        let mut arp = Arpeggiator::default();
+        arp.set_settings(ArpSettings { enabled: true, latch: true, ..ArpSettings::default() });
+        arp.note_on(60, 1.0);
+        arp.note_off(60);
+        assert_eq!(run_steps(&mut arp, 2), vec![60, 60]);
+        // New chord after release replaces the latched one
+        arp.note_on(62, 1.0);
+        assert_eq!(run_steps(&mut arp, 1), vec![62]);
+    }
+
+    #[test]
+    fn test_disable_releases_sounding_note() {
+        let mut arp = enabled(ArpMode::Up);
+        arp.note_on(60, 1.0);
+        run_steps(&mut arp, 1);
+        arp.set_settings(ArpSettings::default());
+        let events: Vec<ArpNote> = arp.events_mut().drain(..).collect();
+        assert_eq!(events.last().map(|e| e.on), Some(false));
+    }
+}
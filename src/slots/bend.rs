@@ -0,0 +1,86 @@
+//! Per-slot pitch bend: a range override and what the wheel moves.
+//!
+//! The bend wheel is kept as −1..1 (0 = centre). Pitch and filter cutoff
+//! follow it continuously, shifted by up to the range in semitones; the
+//! sample start is read once per note, when the voice starts.
+
+use serde::{Deserialize, Serialize};
+
+/// Sample start offset at full bend, as a fraction of the sample.
+pub const MAX_START_OFFSET: f64 = 0.5;
+
+/// What a slot's pitch bend moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BendDestination {
+    #[default]
+    Pitch,
+    /// Voice filter cutoff (needs the slot filter enabled).
+    FilterCutoff,
+    /// Where new notes start in their sample, either direction moving it later.
+    SampleStart,
+}
+
+impl BendDestination {
+    pub const ALL: [BendDestination; 3] = [
+        BendDestination::Pitch,
+        BendDestination::FilterCutoff,
+        BendDestination::SampleStart,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BendDestination::Pitch => "Pitch",
+            BendDestination::FilterCutoff => "Cutoff",
+            BendDestination::SampleStart => "Sample start",
+        }
+    }
+}
+
+/// Bend settings of a slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BendSettings {
+    /// Range in semitones (1–48), or None for the global Pitch Bend Range.
+    pub range: Option<u8>,
+    pub destination: BendDestination,
+}
+
+impl BendSettings {
+    /// Shift in semitones for a wheel position (−1..1).
+    pub fn semitones(&self, wheel: f32, global_range: u8) -> f32 {
+        wheel * self.range.unwrap_or(global_range) as f32
+    }
+}
+
+/// MIDI pitch bend (0..1, 0.5 = centre) as −1..1.
+pub fn normalize(value: f32) -> f32 {
+    ((value - 0.5) * 2.0).clamp(-1.0, 1.0)
+}
+
+/// Frequency ratio for a shift in semitones.
+pub fn ratio(semitones: f32) -> f32 {
+    2.0_f32.powf(semitones / 12.0)
+}
+
+/// Fraction of the sample skipped by a note started at this wheel position.
+pub fn start_offset(wheel: f32) -> f64 {
+    wheel.abs() as f64 * MAX_START_OFFSET
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_override_and_ratio() {
+        let global = BendSettings::default();
+        assert_eq!(global.semitones(normalize(1.0), 2), 2.0);
+        assert_eq!(global.semitones(normalize(0.5), 2), 0.0);
+
+        let wide = BendSettings { range: Some(12), ..Default::default() };
+        assert_eq!(wide.semitones(-1.0, 2), -12.0);
+        assert!((ratio(12.0) - 2.0).abs() < 1e-6);
+        assert!((ratio(-12.0) - 0.5).abs() < 1e-6);
+        assert_eq!(start_offset(-1.0), MAX_START_OFFSET);
+    }
+}
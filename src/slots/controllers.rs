@@ -0,0 +1,169 @@
+//! Expression (CC11), mod wheel (CC1) and aftertouch routing.
+//!
+//! Expression scales the slot's output by up to its depth. The mod wheel
+//! drives one destination: vibrato (pitch), the voice filter cutoff, or
+//! tremolo (amplitude). Vibrato and tremolo share a per-slot LFO at
+//! `MOD_LFO_HZ`; the wheel sets how deep it goes. Channel pressure
+//! (aftertouch) can swell the volume, add vibrato or open the filter; it
+//! is smoothed over `PRESSURE_SMOOTHING_SECS` so steppy keyboards don't
+//! zipper.
+
+use serde::{Deserialize, Serialize};
+
+/// Rate of the vibrato/tremolo LFO.
+pub const MOD_LFO_HZ: f32 = 5.5;
+/// Vibrato swing at full wheel and depth, in semitones either way.
+pub const MAX_VIBRATO_SEMITONES: f32 = 1.0;
+/// Cutoff raise at full wheel and depth, in octaves.
+pub const MAX_CUTOFF_OCTAVES: f32 = 4.0;
+/// Gain added by a volume swell at full pressure and depth (+6 dB).
+pub const MAX_SWELL: f32 = 1.0;
+/// Time constant of the aftertouch smoothing.
+pub const PRESSURE_SMOOTHING_SECS: f32 = 0.03;
+
+/// What the mod wheel moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ModDestination {
+    #[default]
+    Vibrato,
+    /// Voice filter cutoff (needs the slot filter enabled).
+    FilterCutoff,
+    Tremolo,
+}
+
+impl ModDestination {
+    pub const ALL: [ModDestination; 3] = [
+        ModDestination::Vibrato,
+        ModDestination::FilterCutoff,
+        ModDestination::Tremolo,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ModDestination::Vibrato => "Vibrato",
+            ModDestination::FilterCutoff => "Cutoff",
+            ModDestination::Tremolo => "Tremolo",
+        }
+    }
+}
+
+/// What channel pressure (aftertouch) moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PressureDestination {
+    #[default]
+    Off,
+    /// Raise the slot's volume.
+    Swell,
+    Vibrato,
+    /// Voice filter cutoff (needs the slot filter enabled).
+    FilterCutoff,
+}
+
+impl PressureDestination {
+    pub const ALL: [PressureDestination; 4] = [
+        PressureDestination::Off,
+        PressureDestination::Swell,
+        PressureDestination::Vibrato,
+        PressureDestination::FilterCutoff,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PressureDestination::Off => "Off",
+            PressureDestination::Swell => "Swell",
+            PressureDestination::Vibrato => "Vibrato",
+            PressureDestination::FilterCutoff => "Cutoff",
+        }
+    }
+}
+
+/// Controller routing of a slot.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControllerSettings {
+    pub mod_destination: ModDestination,
+    /// How far the mod wheel reaches (0–1).
+    pub mod_depth: f32,
+    /// How far expression can turn the slot down (0 = ignored, 1 = to silence).
+    pub expression_depth: f32,
+    pub pressure_destination: PressureDestination,
+    /// How far aftertouch reaches (0–1).
+    pub pressure_depth: f32,
+}
+
+impl Default for ControllerSettings {
+    fn default() -> Self {
+        Self {
+            mod_destination: ModDestination::Vibrato,
+            mod_depth: 0.5,
+            expression_depth: 1.0,
+            pressure_destination: PressureDestination::Off,
+            pressure_depth: 0.5,
+        }
+    }
+}
+
+impl ControllerSettings {
+    /// Output gain for an expression value (0–1).
+    pub fn expression_gain(&self, expression: f32) -> f32 {
+        1.0 - self.expression_depth * (1.0 - expression.clamp(0.0, 1.0))
+    }
+
+    /// Modulation amount (0–1) for a mod wheel value.
+    pub fn mod_amount(&self, wheel: f32) -> f32 {
+        self.mod_depth * wheel.clamp(0.0, 1.0)
+    }
+
+    /// Aftertouch amount (0–1) for `destination`, or 0 if routed elsewhere.
+    pub fn pressure_amount(&self, destination: PressureDestination, pressure: f32) -> f32 {
+        if self.pressure_destination == destination {
+            self.pressure_depth * pressure.clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Move a smoothed pressure toward `target` over a block of `num_samples`.
+pub fn smooth_pressure(current: f32, target: f32, num_samples: usize, sample_rate: f32) -> f32 {
+    let coeff = 1.0 - (-(num_samples as f32) / (PRESSURE_SMOOTHING_SECS * sample_rate.max(1.0))).exp();
+    current + (target - current) * coeff
+}
+
+/// The LFO (−1..1) at a phase in cycles.
+pub fn lfo(phase: f64) -> f32 {
+    (phase * std::f64::consts::TAU).sin() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expression_and_mod_depth() {
+        let settings = ControllerSettings::default();
+        assert_eq!(settings.expression_gain(1.0), 1.0);
+        assert_eq!(settings.expression_gain(0.0), 0.0);
+        assert_eq!(settings.mod_amount(1.0), 0.5);
+
+        let shallow = ControllerSettings { expression_depth: 0.25, mod_depth: 0.0, ..Default::default() };
+        assert_eq!(shallow.expression_gain(0.0), 0.75);
+        assert_eq!(shallow.mod_amount(1.0), 0.0);
+    }
+
+    #[test]
+    fn test_pressure_routing_and_smoothing() {
+        let swell = ControllerSettings {
+            pressure_destination: PressureDestination::Swell,
+            pressure_depth: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(swell.pressure_amount(PressureDestination::Swell, 0.5), 0.5);
+        assert_eq!(swell.pressure_amount(PressureDestination::Vibrato, 0.5), 0.0);
+        assert_eq!(ControllerSettings::default().pressure_amount(PressureDestination::Swell, 1.0), 0.0);
+
+        let step = smooth_pressure(0.0, 1.0, 64, 44100.0);
+        assert!(step > 0.0 && step < 1.0, "a block moves part of the way");
+        assert!(smooth_pressure(0.0, 1.0, 44100, 44100.0) > 0.999);
+    }
+}
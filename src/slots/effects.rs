@@ -0,0 +1,198 @@
+//! Effect nodes of a preset, applied to the slot's whole signal.
+//!
+//! Effects run after the voices are summed and before the slot's output
+//! stage. For an Effect-category preset the signal is the plugin's audio
+//! input (see `render_and_mix`), so the chain is the whole preset. The
+//! chain is `Copy` with a fixed number of stages, and its filter state is
+//! reset whenever a preset is loaded.
+
+/// Stages per chain; further effects in a descriptor are ignored.
+pub const MAX_EFFECTS: usize = 8;
+
+/// Lowest filter cutoff, and the highest as a fraction of the sample rate.
+const MIN_CUTOFF_HZ: f32 = 20.0;
+const MAX_CUTOFF_RATIO: f32 = 0.45;
+
+/// One effect node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    /// Linear gain.
+    Gain { gain: f32 },
+    /// Resonant filters (resonance 0–1).
+    LowPass { cutoff_hz: f32, resonance: f32 },
+    HighPass { cutoff_hz: f32, resonance: f32 },
+    /// tanh saturation; `drive` ≥ 1 is the input gain into the curve.
+    Drive { drive: f32, mix: f32 },
+    /// Constant-power pan of the stereo signal (−1..+1).
+    Pan { pan: f32 },
+}
+
+/// An effect and its running state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Stage {
+    effect: Effect,
+    /// Filter integrator states per channel.
+    ic1: [f32; 2],
+    ic2: [f32; 2],
+}
+
+/// Effects of a preset in signal order.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EffectChain {
+    stages: [Option<Stage>; MAX_EFFECTS],
+    count: usize,
+}
+
+impl EffectChain {
+    /// Append an effect; false if the chain is full.
+    pub fn push(&mut self, effect: Effect) -> bool {
+        if self.count == MAX_EFFECTS {
+            return false;
+        }
+        self.stages[self.count] = Some(Stage { effect, ic1: [0.0; 2], ic2: [0.0; 2] });
+        self.count += 1;
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Effects in signal order.
+    pub fn effects(&self) -> impl Iterator<Item = Effect> + '_ {
+        self.stages[..self.count].iter().flatten().map(|s| s.effect)
+    }
+
+    /// Clear the filter states.
+    pub fn reset(&mut self) {
+        for stage in self.stages.iter_mut().flatten() {
+            stage.ic1 = [0.0; 2];
+            stage.ic2 = [0.0; 2];
+        }
+    }
+
+    /// Run the chain over a block in place.
+    pub fn process(&mut self, left: &mut [f32], right: &mut [f32], sample_rate: f32) {
+        for stage in self.stages[..self.count].iter_mut().flatten() {
+            match stage.effect {
+                Effect::Gain { gain } => {
+                    for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+                        *l *= gain;
+                        *r *= gain;
+                    }
+                }
+                Effect::LowPass { cutoff_hz, resonance } => {
+                    stage.filter(left, right, cutoff_hz, resonance, sample_rate, false)
+                }
+                Effect::HighPass { cutoff_hz, resonance } => {
+                    stage.filter(left, right, cutoff_hz, resonance, sample_rate, true)
+                }
+                Effect::Drive { drive, mix } => {
+                    let drive = drive.max(1.0);
+                    let mix = mix.clamp(0.0, 1.0);
+                    // Normalised so a full-scale input stays near full scale
+                    let norm = 1.0 / drive.tanh();
+                    for s in left.iter_mut().chain(right.iter_mut()) {
+                        let wet = (*s * drive).tanh() * norm;
+                        *s += (wet - *s) * mix;
+                    }
+                }
+                Effect::Pan { pan } => {
+                    let (gain_l, gain_r) = crate::audio::constant_power_pan(pan.clamp(-1.0, 1.0));
+                    // Unity at centre
+                    let norm = std::f32::consts::SQRT_2;
+                    for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+                        *l *= gain_l * norm;
+                        *r *= gain_r * norm;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Stage {
+    /// State-variable filter (trapezoidal), as in the voice filter.
+    fn filter(
+        &mut self,
+        left: &mut [f32],
+        right: &mut [f32],
+        cutoff_hz: f32,
+        resonance: f32,
+        sample_rate: f32,
+        high_pass: bool,
+    ) {
+        let cutoff = cutoff_hz.clamp(MIN_CUTOFF_HZ, sample_rate * MAX_CUTOFF_RATIO);
+        let g = (std::f32::consts::PI * cutoff / sample_rate).tan();
+        let k = (2.0 - 2.0 * resonance.clamp(0.0, 1.0)).max(0.05);
+        let a1 = 1.0 / (1.0 + g * (g + k));
+        let a2 = g * a1;
+        let a3 = g * a2;
+        for (ch, buf) in [left, right].into_iter().enumerate() {
+            let (mut ic1, mut ic2) = (self.ic1[ch], self.ic2[ch]);
+            for s in buf.iter_mut() {
+                let v0 = *s;
+                let v3 = v0 - ic2;
+                let v1 = a1 * ic1 + a2 * v3;
+                let v2 = ic2 + a2 * ic1 + a3 * v3;
+                ic1 = 2.0 * v1 - ic1;
+                ic2 = 2.0 * v2 - ic2;
+                *s = if high_pass { v0 - k * v1 - v2 } else { v2 };
+            }
+            self.ic1[ch] = ic1;
+            self.ic2[ch] = ic2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SR: f32 = 48000.0;
+
+    /// Peak of the second half of a second of sine through the chain.
+    fn response(chain: &mut EffectChain, freq: f32) -> f32 {
+        let mut left: Vec<f32> = (0..SR as usize)
+            .map(|i| (std::f32::consts::TAU * freq * i as f32 / SR).sin())
+            .collect();
+        let mut right = left.clone();
+        chain.process(&mut left, &mut right, SR);
+        left[SR as usize / 2..].iter().fold(0.0_f32, |m, s| m.max(s.abs()))
+    }
+
+    #[test]
+    fn test_filters_pass_and_stop_bands() {
+        let mut low = EffectChain::default();
+        low.push(Effect::LowPass { cutoff_hz: 500.0, resonance: 0.0 });
+        assert!(response(&mut low, 100.0) > 0.9);
+        low.reset();
+        assert!(response(&mut low, 5000.0) < 0.05);
+
+        let mut high = EffectChain::default();
+        high.push(Effect::HighPass { cutoff_hz: 2000.0, resonance: 0.0 });
+        assert!(response(&mut high, 10000.0) > 0.9);
+        high.reset();
+        assert!(response(&mut high, 100.0) < 0.05);
+    }
+
+    #[test]
+    fn test_chain_runs_in_order_and_is_bounded() {
+        let mut chain = EffectChain::default();
+        chain.push(Effect::Gain { gain: 4.0 });
+        chain.push(Effect::Drive { drive: 1.0, mix: 1.0 });
+        // The drive after the gain limits the 4× peak
+        let peak = response(&mut chain, 100.0);
+        assert!(peak > 1.0 && peak < 1.4, "{}", peak);
+
+        for _ in chain.len()..MAX_EFFECTS {
+            assert!(chain.push(Effect::Pan { pan: 0.0 }));
+        }
+        assert!(!chain.push(Effect::Pan { pan: 0.0 }));
+        assert_eq!(chain.effects().count(), MAX_EFFECTS);
+    }
+}
@@ -0,0 +1,231 @@
+//! Per-voice resonant low-pass filter with its own ADSR.
+//!
+//! A state-variable filter (trapezoidal integration, so it stays stable
+//! under fast cutoff sweeps) in each voice, after the sample read and
+//! before the amplitude envelope. The filter envelope moves the cutoff by
+//! up to `env_amount` octaves; coefficients are recomputed every
+//! `CONTROL_INTERVAL` samples rather than per sample.
+
+use serde::{Deserialize, Serialize};
+
+/// Samples between cutoff (coefficient) updates.
+const CONTROL_INTERVAL: u32 = 16;
+
+/// Lowest cutoff, and the highest as a fraction of the sample rate.
+const MIN_CUTOFF_HZ: f32 = 20.0;
+const MAX_CUTOFF_RATIO: f32 = 0.45;
+
+/// Damping at full resonance; keeps the filter just short of
+/// self-oscillation.
+const MIN_DAMPING: f32 = 0.05;
+
+/// Filter settings of a slot, shared by all its voices.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VoiceFilterSettings {
+    pub enabled: bool,
+    /// Cutoff with the envelope at zero.
+    pub cutoff_hz: f32,
+    /// 0 = no resonance, 1 = nearly self-oscillating.
+    pub resonance: f32,
+    /// Cutoff shift at full envelope, in octaves (negative sweeps down).
+    pub env_amount: f32,
+    pub attack_secs: f32,
+    pub decay_secs: f32,
+    pub sustain_level: f32,
+    pub release_secs: f32,
+}
+
+impl Default for VoiceFilterSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cutoff_hz: 8000.0,
+            resonance: 0.2,
+            env_amount: 0.0,
+            attack_secs: 0.01,
+            decay_secs: 0.3,
+            sustain_level: 0.5,
+            release_secs: 0.3,
+        }
+    }
+}
+
+/// Filter envelope stage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum EnvStage {
+    #[default]
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Per-voice filter and envelope state.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VoiceFilter {
+    /// Integrator states per channel.
+    ic1: [f32; 2],
+    ic2: [f32; 2],
+    /// Coefficients for the current control block.
+    a1: f32,
+    a2: f32,
+    a3: f32,
+    /// Samples until the coefficients are recomputed.
+    countdown: u32,
+    stage: EnvStage,
+    /// Samples elapsed in the current envelope stage.
+    stage_samples: u32,
+    level: f32,
+    /// Envelope level when the release started.
+    release_from: f32,
+}
+
+impl VoiceFilter {
+    /// Clear the state for a new note.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Filter one stereo frame. `releasing` moves the envelope to its
+    /// release stage.
+    #[inline]
+    pub fn process(
+        &mut self,
+        frame: (f32, f32),
+        settings: &VoiceFilterSettings,
+        releasing: bool,
+        sample_rate: f32,
+    ) -> (f32, f32) {
+        if self.countdown == 0 {
+            self.advance_envelope(settings, releasing, sample_rate, CONTROL_INTERVAL);
+            self.update_coefficients(settings, sample_rate);
+            self.countdown = CONTROL_INTERVAL;
+        }
+        self.countdown -= 1;
+        (self.tick(0, frame.0), self.tick(1, frame.1))
+    }
+
+    #[inline]
+    fn tick(&mut self, ch: usize, v0: f32) -> f32 {
+        let v3 = v0 - self.ic2[ch];
+        let v1 = self.a1 * self.ic1[ch] + self.a2 * v3;
+        let v2 = self.ic2[ch] + self.a2 * self.ic1[ch] + self.a3 * v3;
+        self.ic1[ch] = 2.0 * v1 - self.ic1[ch];
+        self.ic2[ch] = 2.0 * v2 - self.ic2[ch];
+        v2
+    }
+
+    /// Cutoff for the current envelope level.
+    fn cutoff(&self, settings: &VoiceFilterSettings, sample_rate: f32) -> f32 {
+        let hz = settings.cutoff_hz * (settings.env_amount * self.level).exp2();
+        hz.clamp(MIN_CUTOFF_HZ, sample_rate * MAX_CUTOFF_RATIO)
+    }
+
+    fn update_coefficients(&mut self, settings: &VoiceFilterSettings, sample_rate: f32) {
+        let g = (std::f32::consts::PI * self.cutoff(settings, sample_rate) / sample_rate).tan();
+        let k = (2.0 - 2.0 * settings.resonance.clamp(0.0, 1.0)).max(MIN_DAMPING);
+        self.a1 = 1.0 / (1.0 + g * (g + k));
+        self.a2 = g * self.a1;
+        self.a3 = g * self.a2;
+    }
+
+    /// Move the envelope `samples` forward (linear segments).
+    fn advance_envelope(&mut self, settings: &VoiceFilterSettings, releasing: bool, sample_rate: f32, samples: u32) {
+        if releasing && self.stage != EnvStage::Release {
+            self.stage = EnvStage::Release;
+            self.stage_samples = 0;
+            self.release_from = self.level;
+        }
+        let sustain = settings.sustain_level.clamp(0.0, 1.0);
+        let length = |secs: f32| ((secs * sample_rate) as u32).max(1);
+        self.stage_samples = self.stage_samples.saturating_add(samples);
+        match self.stage {
+            EnvStage::Attack => {
+                let total = length(settings.attack_secs);
+                self.level = (self.stage_samples as f32 / total as f32).min(1.0);
+                if self.stage_samples >= total {
+                    self.stage = EnvStage::Decay;
+                    self.stage_samples = 0;
+                }
+            }
+            EnvStage::Decay => {
+                let total = length(settings.decay_secs);
+                let t = (self.stage_samples as f32 / total as f32).min(1.0);
+                self.level = 1.0 + (sustain - 1.0) * t;
+                if self.stage_samples >= total {
+                    self.stage = EnvStage::Sustain;
+                }
+            }
+            EnvStage::Sustain => self.level = sustain,
+            EnvStage::Release => {
+                let total = length(settings.release_secs);
+                let t = (self.stage_samples as f32 / total as f32).min(1.0);
+                self.level = self.release_from * (1.0 - t);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SR: f32 = 48000.0;
+
+    /// Peak output once settled, for a sine at `freq`.
+    fn response(settings: &VoiceFilterSettings, freq: f32) -> f32 {
+        let mut filter = VoiceFilter::default();
+        let mut peak: f32 = 0.0;
+        for i in 0..(SR as usize / 2) {
+            let x = (std::f32::consts::TAU * freq * i as f32 / SR).sin();
+            let (y, _) = filter.process((x, x), settings, false, SR);
+            if i > SR as usize / 4 {
+                peak = peak.max(y.abs());
+            }
+        }
+        peak
+    }
+
+    #[test]
+    fn test_low_pass_attenuates_above_cutoff() {
+        let settings = VoiceFilterSettings {
+            enabled: true,
+            cutoff_hz: 500.0,
+            resonance: 0.0,
+            ..Default::default()
+        };
+        assert!(response(&settings, 100.0) > 0.9);
+        assert!(response(&settings, 5000.0) < 0.05);
+    }
+
+    #[test]
+    fn test_envelope_opens_and_releases() {
+        let settings = VoiceFilterSettings {
+            enabled: true,
+            cutoff_hz: 200.0,
+            env_amount: 4.0,
+            attack_secs: 0.01,
+            decay_secs: 0.01,
+            sustain_level: 0.5,
+            release_secs: 0.01,
+            ..Default::default()
+        };
+        let mut filter = VoiceFilter::default();
+        let mut peak_cutoff: f32 = 0.0;
+        for _ in 0..(SR as usize / 100) {
+            filter.process((0.0, 0.0), &settings, false, SR);
+            peak_cutoff = peak_cutoff.max(filter.cutoff(&settings, SR));
+        }
+        assert!((peak_cutoff - 3200.0).abs() < 50.0, "{}", peak_cutoff);
+        for _ in 0..(SR as usize / 10) {
+            filter.process((0.0, 0.0), &settings, false, SR);
+        }
+        assert!((filter.level - 0.5).abs() < 1e-6);
+        for _ in 0..(SR as usize / 10) {
+            filter.process((0.0, 0.0), &settings, true, SR);
+        }
+        assert_eq!(filter.level, 0.0);
+        assert_eq!(filter.cutoff(&settings, SR), 200.0);
+    }
+}
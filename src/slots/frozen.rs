@@ -0,0 +1,199 @@
+//! Slot freeze: a slot's sound rendered to a clip and played back in its place.
+//!
+//! `render_offline` runs a stand-alone `Slot` (off the audio thread) with
+//! a chord or runner trigger held for a number of beats, then lets it ring
+//! out. The clip is taken after the slot's effects and output stage, before
+//! its fader, so a frozen slot keeps its volume, pan and routing. While
+//! frozen, a note-on starts the clip from the top and the slot's voices,
+//! runner and effects are bypassed.
+
+use std::sync::Arc;
+
+use crate::transport::TransportState;
+
+use super::Slot;
+
+/// Block size of the offline render.
+const RENDER_BLOCK: usize = 256;
+/// Longest ring-out after the notes are released.
+pub const MAX_TAIL_SECS: f32 = 8.0;
+/// Longest clip, tail included.
+pub const MAX_CLIP_SECS: f32 = 60.0;
+/// Level below which the ring-out counts as finished.
+const SILENCE: f32 = 1e-4;
+
+/// Rendered audio of a frozen slot.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrozenClip {
+    pub left: Vec<f32>,
+    pub right: Vec<f32>,
+    pub sample_rate: f32,
+}
+
+impl FrozenClip {
+    pub fn len(&self) -> usize {
+        self.left.len().min(self.right.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn duration_secs(&self) -> f32 {
+        self.len() as f32 / self.sample_rate.max(1.0)
+    }
+}
+
+/// Render `slot` with `notes` held for `beats` at `bpm`, then until it falls
+/// silent (at most `MAX_TAIL_SECS`). A slot with a runner program plays its
+/// pattern from each note.
+pub fn render_offline(slot: &mut Slot, notes: &[u8], velocity: f32, beats: f64, bpm: f64, sample_rate: f32) -> FrozenClip {
+    let mut transport = TransportState {
+        bpm,
+        playing: true,
+        sample_rate,
+        ..TransportState::default()
+    };
+    slot.initialize(sample_rate);
+    for &note in notes {
+        let on = nih_plug::prelude::NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note, velocity };
+        slot.handle_midi_event(&on, &transport);
+    }
+
+    let max_len = (MAX_CLIP_SECS * sample_rate) as usize;
+    let held = (transport.beats_to_samples(beats.max(0.0)) as usize).min(max_len);
+    let tail_end = held + (MAX_TAIL_SECS * sample_rate) as usize;
+    let mut clip = FrozenClip { left: Vec::with_capacity(held), right: Vec::with_capacity(held), sample_rate };
+    let mut left = [0.0; RENDER_BLOCK];
+    let mut right = [0.0; RENDER_BLOCK];
+    let mut released = false;
+    while clip.len() < max_len.min(tail_end) {
+        if !released && clip.len() >= held {
+            for &note in notes {
+                let off = nih_plug::prelude::NoteEvent::NoteOff { timing: 0, voice_id: None, channel: 0, note, velocity: 0.0 };
+                slot.handle_midi_event(&off, &transport);
+            }
+            released = true;
+        }
+        let n = if released { RENDER_BLOCK } else { RENDER_BLOCK.min(held - clip.len()).max(1) };
+        left[..n].fill(0.0);
+        right[..n].fill(0.0);
+        slot.render(&mut left[..n], &mut right[..n], n, sample_rate, &transport);
+        clip.left.extend_from_slice(&left[..n]);
+        clip.right.extend_from_slice(&right[..n]);
+        transport.position_samples += n as i64;
+        transport.position_beats += transport.samples_to_beats(n as f64);
+
+        let quiet = left[..n].iter().chain(&right[..n]).all(|s| s.abs() < SILENCE);
+        if released && slot.is_silent() && quiet {
+            break;
+        }
+    }
+    clip
+}
+
+/// Plays a frozen clip from the top on each trigger.
+#[derive(Debug, Default)]
+pub struct ClipPlayer {
+    clip: Option<Arc<FrozenClip>>,
+    /// Read position in clip frames (None = stopped).
+    position: Option<f64>,
+    /// Last frame output, for declicking a retrigger or stop.
+    last_frame: (f32, f32),
+}
+
+impl ClipPlayer {
+    pub fn is_frozen(&self) -> bool {
+        self.clip.is_some()
+    }
+
+    /// Replace the clip (None = unfreeze). The previous `Arc` is dropped
+    /// here, so the sender keeps its own reference.
+    pub fn set_clip(&mut self, clip: Option<Arc<FrozenClip>>) {
+        self.clip = clip;
+        self.position = None;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.position.is_some()
+    }
+
+    /// Start from the top; returns the frame that was cut off.
+    pub fn trigger(&mut self) -> (f32, f32) {
+        let cut = self.stop();
+        if self.clip.is_some() {
+            self.position = Some(0.0);
+        }
+        cut
+    }
+
+    /// Stop; returns the frame that was cut off.
+    pub fn stop(&mut self) -> (f32, f32) {
+        let cut = if self.position.is_some() { self.last_frame } else { (0.0, 0.0) };
+        self.position = None;
+        self.last_frame = (0.0, 0.0);
+        cut
+    }
+
+    /// Add the clip to the buffers, resampling to `sample_rate`.
+    pub fn render(&mut self, left: &mut [f32], right: &mut [f32], sample_rate: f32) {
+        let (Some(clip), Some(mut pos)) = (self.clip.as_deref(), self.position) else {
+            return;
+        };
+        let step = f64::from(clip.sample_rate) / f64::from(sample_rate.max(1.0));
+        let last = clip.len().saturating_sub(1);
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let i = pos as usize;
+            if i >= last {
+                self.position = None;
+                self.last_frame = (0.0, 0.0);
+                return;
+            }
+            let frac = (pos - i as f64) as f32;
+            let a = clip.left[i] + (clip.left[i + 1] - clip.left[i]) * frac;
+            let b = clip.right[i] + (clip.right[i + 1] - clip.right[i]) * frac;
+            *l += a;
+            *r += b;
+            self.last_frame = (a, b);
+            pos += step;
+        }
+        self.position = Some(pos);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_render_holds_then_rings_out() {
+        let mut slot = Slot::new(0);
+        let clip = render_offline(&mut slot, &[60, 64], 0.8, 2.0, 120.0, 48000.0);
+        // One second held, then the release, well short of the tail limit
+        let held = 48000;
+        assert!(clip.len() > held && clip.len() < held + (MAX_TAIL_SECS * 48000.0) as usize);
+        assert!(clip.left[..held].iter().any(|s| s.abs() > 0.01));
+        assert!(clip.left[clip.len() - 1].abs() < 0.01);
+    }
+
+    #[test]
+    fn test_player_resamples_and_stops_at_end() {
+        let clip = Arc::new(FrozenClip {
+            left: (0..100).map(|i| i as f32).collect(),
+            right: vec![1.0; 100],
+            sample_rate: 24000.0,
+        });
+        let mut player = ClipPlayer::default();
+        player.set_clip(Some(clip));
+        let (mut left, mut right) = (vec![0.0; 300], vec![0.0; 300]);
+        player.render(&mut left, &mut right, 48000.0);
+        assert_eq!(left[0], 0.0, "not triggered");
+
+        player.trigger();
+        player.render(&mut left, &mut right, 48000.0);
+        assert_eq!(&left[..4], &[0.0, 0.5, 1.0, 1.5]);
+        assert_eq!(left[197], 98.5);
+        assert_eq!(left[198], 0.0);
+        assert!(!player.is_playing());
+    }
+}
@@ -0,0 +1,26 @@
+/// Maximum number of slot groups (bus folders).
+pub const MAX_GROUPS: usize = 8;
+
+/// Audio-thread state for a slot group bus.
+///
+/// Slots assigned to a group are scaled by the group gain before being
+/// summed into the master bus.
+#[derive(Debug, Clone, Copy)]
+pub struct GroupBus {
+    /// Group volume gain (linear).
+    pub volume: f32,
+    /// Whether the whole group is muted.
+    pub muted: bool,
+    /// Whether the group is soloed.
+    pub solo: bool,
+}
+
+impl Default for GroupBus {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+            solo: false,
+        }
+    }
+}
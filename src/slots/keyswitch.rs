@@ -0,0 +1,128 @@
+//! Articulation keyswitches: keys (usually below the playable range) that
+//! choose which layer of a preset later notes play.
+//!
+//! A layer is one child of a composite preset, i.e. the run of zones it
+//! contributes to the loaded instance; a plain sampler preset has a single
+//! layer. Pressing a keyswitch key makes no sound.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use songwalker_core::preset::PresetNode;
+
+/// Sentinel for "not a keyswitch" in [`KeyswitchMap`].
+const NO_LAYER: u8 = u8::MAX;
+
+/// One articulation of a slot, as configured in the rack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Articulation {
+    /// MIDI note that selects this articulation.
+    pub key: u8,
+    pub name: String,
+    /// Preset layer played while selected (0-based).
+    pub layer: usize,
+}
+
+/// Audio-thread lookup from key to layer. `Copy`, so it can be sent to the
+/// audio thread without allocating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyswitchMap {
+    layers: [u8; 128],
+    /// Articulation selected before any keyswitch is pressed.
+    default_key: Option<u8>,
+}
+
+impl Default for KeyswitchMap {
+    fn default() -> Self {
+        Self {
+            layers: [NO_LAYER; 128],
+            default_key: None,
+        }
+    }
+}
+
+impl KeyswitchMap {
+    /// Build the map for a slot. The first articulation is the default.
+    pub fn from_articulations(articulations: &[Articulation]) -> Self {
+        let mut map = Self::default();
+        for a in articulations.iter().filter(|a| a.key < 128) {
+            map.layers[a.key as usize] = a.layer.min(NO_LAYER as usize - 1) as u8;
+            map.default_key.get_or_insert(a.key);
+        }
+        map
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.default_key.is_none()
+    }
+
+    pub fn default_key(&self) -> Option<u8> {
+        self.default_key
+    }
+
+    /// Layer selected by `key`, or `None` if it isn't a keyswitch.
+    pub fn layer_for(&self, key: u8) -> Option<usize> {
+        match self.layers.get(key as usize) {
+            Some(&layer) if layer != NO_LAYER => Some(layer as usize),
+            _ => None,
+        }
+    }
+}
+
+/// Range of `instance.zones` contributed by `layer` of a preset graph, or
+/// `None` if the preset has no such layer.
+pub fn layer_zone_range(graph: &PresetNode, layer: usize) -> Option<Range<usize>> {
+    match graph {
+        PresetNode::Composite { children, .. } => {
+            let mut start = 0;
+            for (i, child) in children.iter().enumerate() {
+                let count = zone_count(child);
+                if i == layer {
+                    return Some(start..start + count);
+                }
+                start += count;
+            }
+            None
+        }
+        _ if layer == 0 => Some(0..zone_count(graph)),
+        _ => None,
+    }
+}
+
+/// Zones a node contributes, in the order the loader flattens them.
+fn zone_count(node: &PresetNode) -> usize {
+    match node {
+        PresetNode::Sampler { config } => config.zones.len(),
+        PresetNode::Composite { children, .. } => children.iter().map(|c| zone_count(c)).sum(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn articulation(key: u8, layer: usize) -> Articulation {
+        Articulation {
+            key,
+            name: format!("Layer {}", layer),
+            layer,
+        }
+    }
+
+    #[test]
+    fn test_map_lookup() {
+        let map = KeyswitchMap::from_articulations(&[articulation(24, 0), articulation(25, 1)]);
+        assert_eq!(map.layer_for(24), Some(0));
+        assert_eq!(map.layer_for(25), Some(1));
+        assert_eq!(map.layer_for(60), None);
+        assert_eq!(map.default_key(), Some(24));
+        assert!(KeyswitchMap::default().is_empty());
+    }
+
+    #[test]
+    fn test_out_of_range_keys_ignored() {
+        let map = KeyswitchMap::from_articulations(&[articulation(200, 0)]);
+        assert!(map.is_empty());
+    }
+}
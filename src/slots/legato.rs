@@ -0,0 +1,90 @@
+//! Per-slot retrigger crossfades.
+//!
+//! Retriggering a key whose voice is still sounding, or (in legato mode)
+//! playing a key while others are held, fades the old voices out over the
+//! crossfade time while the new note fades in over at least as long,
+//! instead of a hard attack on top of the old note. Preset playback only;
+//! drum kits keep their one-shots.
+
+use serde::{Deserialize, Serialize};
+
+use super::slot::Voice;
+
+/// Shortest and longest crossfade, in milliseconds.
+pub const MIN_CROSSFADE_MS: f32 = 1.0;
+pub const MAX_CROSSFADE_MS: f32 = 50.0;
+
+/// Crossfade settings of a slot.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LegatoSettings {
+    /// Crossfade a key retriggered while its voice still sounds.
+    pub retrigger: bool,
+    /// A note played while others are held takes over from them.
+    pub legato: bool,
+    /// Crossfade length in ms (1–50).
+    pub crossfade_ms: f32,
+}
+
+impl Default for LegatoSettings {
+    fn default() -> Self {
+        Self {
+            retrigger: false,
+            legato: false,
+            crossfade_ms: 8.0,
+        }
+    }
+}
+
+impl LegatoSettings {
+    /// Settings clamped to their ranges.
+    pub fn clamped(self) -> Self {
+        Self {
+            crossfade_ms: self.crossfade_ms.clamp(MIN_CROSSFADE_MS, MAX_CROSSFADE_MS),
+            ..self
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.retrigger || self.legato
+    }
+
+    pub fn crossfade_secs(&self) -> f32 {
+        self.crossfade_ms / 1000.0
+    }
+
+    /// Whether a note-on of `note` fades `voice` out.
+    pub fn fades_out(&self, voice: &Voice, note: u8) -> bool {
+        if !voice.active || voice.declick || voice.fade_out_secs.is_some() {
+            return false;
+        }
+        (self.retrigger && voice.note == note) || (self.legato && !voice.releasing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retrigger_and_legato_pick_voices() {
+        let held = Voice { active: true, note: 60, ..Voice::default() };
+        let released = Voice { releasing: true, ..held.clone() };
+
+        let off = LegatoSettings::default();
+        assert!(!off.is_active() && !off.fades_out(&held, 60));
+
+        let retrigger = LegatoSettings { retrigger: true, ..off };
+        assert!(retrigger.fades_out(&held, 60) && retrigger.fades_out(&released, 60));
+        assert!(!retrigger.fades_out(&held, 62));
+
+        // Legato takes over from held keys only, whatever the note
+        let legato = LegatoSettings { legato: true, ..off };
+        assert!(legato.fades_out(&held, 62) && !legato.fades_out(&released, 62));
+
+        // A voice already fading isn't faded again
+        let fading = Voice { fade_out_secs: Some(0.008), ..held };
+        assert!(!retrigger.fades_out(&fading, 60));
+        assert_eq!(LegatoSettings { crossfade_ms: 500.0, ..off }.clamped().crossfade_ms, MAX_CROSSFADE_MS);
+    }
+}
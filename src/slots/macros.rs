@@ -0,0 +1,220 @@
+//! Macro knobs: global controls that each drive slot settings.
+//!
+//! The eight macros are host parameters (`SongWalkerParams::macros`, or
+//! atomics in the standalone). Each assignment maps one macro onto one slot
+//! target through its own range and curve; a macro can have any number of
+//! assignments. Assignments belong to the rack (`PluginState::macros`) and
+//! are applied on the audio thread when a macro moves, so a rack edit of an
+//! assigned setting holds until the macro is turned again.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::Slot;
+
+/// Number of macro knobs.
+pub const MACRO_COUNT: usize = 8;
+
+/// Lowest and highest cutoff a macro can set.
+const MIN_CUTOFF_HZ: f32 = 20.0;
+const MAX_CUTOFF_HZ: f32 = 20000.0;
+
+/// The slot setting an assignment drives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MacroTarget {
+    /// Fader gain (0–1.5).
+    #[default]
+    Volume,
+    /// Pan (−1..1).
+    Pan,
+    /// Voice filter cutoff in Hz (needs the slot filter enabled).
+    FilterCutoff,
+}
+
+impl MacroTarget {
+    pub const ALL: [MacroTarget; 3] = [MacroTarget::Volume, MacroTarget::Pan, MacroTarget::FilterCutoff];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MacroTarget::Volume => "Volume",
+            MacroTarget::Pan => "Pan",
+            MacroTarget::FilterCutoff => "Cutoff",
+        }
+    }
+
+    /// Full range of the target, the default for a new assignment.
+    pub fn range(self) -> (f32, f32) {
+        match self {
+            MacroTarget::Volume => (0.0, 1.5),
+            MacroTarget::Pan => (-1.0, 1.0),
+            MacroTarget::FilterCutoff => (MIN_CUTOFF_HZ, MAX_CUTOFF_HZ),
+        }
+    }
+}
+
+/// How a macro's travel is spread over an assignment's range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MacroCurve {
+    #[default]
+    Linear,
+    /// Slow start, fine control at the low end.
+    Exponential,
+    /// Fast start, fine control at the high end.
+    Logarithmic,
+}
+
+impl MacroCurve {
+    pub const ALL: [MacroCurve; 3] = [MacroCurve::Linear, MacroCurve::Exponential, MacroCurve::Logarithmic];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MacroCurve::Linear => "Linear",
+            MacroCurve::Exponential => "Exp",
+            MacroCurve::Logarithmic => "Log",
+        }
+    }
+
+    /// Shape a macro position (0–1).
+    pub fn shape(self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        match self {
+            MacroCurve::Linear => x,
+            MacroCurve::Exponential => x * x,
+            MacroCurve::Logarithmic => 1.0 - (1.0 - x) * (1.0 - x),
+        }
+    }
+}
+
+/// One macro driving one slot target.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MacroAssignment {
+    /// Macro index (0–7).
+    pub macro_index: usize,
+    pub slot_index: usize,
+    pub target: MacroTarget,
+    /// Target value with the macro at 0.
+    pub min: f32,
+    /// Target value with the macro at 1 (may be below `min` to invert).
+    pub max: f32,
+    #[serde(default)]
+    pub curve: MacroCurve,
+}
+
+impl MacroAssignment {
+    /// A new assignment covering the target's full range.
+    pub fn new(macro_index: usize, slot_index: usize, target: MacroTarget) -> Self {
+        let (min, max) = target.range();
+        Self { macro_index, slot_index, target, min, max, curve: MacroCurve::Linear }
+    }
+
+    /// Target value for a macro position (0–1).
+    pub fn value_at(&self, position: f32) -> f32 {
+        let (lo, hi) = self.target.range();
+        if self.target == MacroTarget::FilterCutoff {
+            // Interpolate in octaves, so the knob's travel sounds even
+            let (min, max) = (self.min.clamp(lo, hi), self.max.clamp(lo, hi));
+            return min * (max / min).powf(self.curve.shape(position));
+        }
+        (self.min + (self.max - self.min) * self.curve.shape(position)).clamp(lo, hi)
+    }
+
+    fn apply(&self, slot: &mut Slot, position: f32) {
+        let value = self.value_at(position);
+        match self.target {
+            MacroTarget::Volume => slot.set_volume(value),
+            MacroTarget::Pan => slot.set_pan(value),
+            MacroTarget::FilterCutoff => {
+                let mut filter = slot.filter();
+                filter.cutoff_hz = value;
+                slot.set_filter(filter);
+            }
+        }
+    }
+}
+
+/// Audio-thread side of the macros: the assignments and the macro
+/// positions last applied.
+pub struct MacroEngine {
+    assignments: Arc<Vec<MacroAssignment>>,
+    applied: [Option<f32>; MACRO_COUNT],
+}
+
+impl Default for MacroEngine {
+    fn default() -> Self {
+        Self {
+            assignments: Arc::new(Vec::new()),
+            applied: [None; MACRO_COUNT],
+        }
+    }
+}
+
+impl MacroEngine {
+    /// Swap in new assignments, returning the old ones so the caller can
+    /// retire them off the audio thread. Every macro is applied again on
+    /// the next block.
+    pub fn set_assignments(&mut self, assignments: Arc<Vec<MacroAssignment>>) -> Arc<Vec<MacroAssignment>> {
+        self.applied = [None; MACRO_COUNT];
+        std::mem::replace(&mut self.assignments, assignments)
+    }
+
+    /// Apply the macros that moved since the last block to their targets.
+    pub fn apply(&mut self, slots: &mut [Slot], positions: [f32; MACRO_COUNT]) {
+        for (index, (&position, last)) in positions.iter().zip(self.applied.iter_mut()).enumerate() {
+            if *last == Some(position) {
+                continue;
+            }
+            *last = Some(position);
+            for assignment in self.assignments.iter().filter(|a| a.macro_index == index) {
+                if let Some(slot) = slots.get_mut(assignment.slot_index) {
+                    assignment.apply(slot, position);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_macros_apply_ranges_and_curves_on_change() {
+        let mut slots = vec![Slot::new(0), Slot::new(1)];
+        let mut engine = MacroEngine::default();
+        let mut cutoff = MacroAssignment::new(0, 1, MacroTarget::FilterCutoff);
+        cutoff.min = 100.0;
+        cutoff.max = 6400.0;
+        let inverted_pan = MacroAssignment { min: 1.0, max: -1.0, ..MacroAssignment::new(0, 0, MacroTarget::Pan) };
+        let volume = MacroAssignment {
+            max: 1.0,
+            curve: MacroCurve::Exponential,
+            ..MacroAssignment::new(1, 0, MacroTarget::Volume)
+        };
+        engine.set_assignments(Arc::new(vec![cutoff, inverted_pan, volume]));
+
+        let mut positions = [0.0; MACRO_COUNT];
+        positions[0] = 0.5;
+        positions[1] = 0.5;
+        engine.apply(&mut slots, positions);
+        // One macro drives two slots; the cutoff moves in octaves
+        assert!((slots[1].filter().cutoff_hz - 800.0).abs() < 0.1);
+        assert_eq!(slots[0].pan(), 0.0);
+        assert_eq!(slots[0].volume(), 0.25);
+
+        // A rack edit holds until the macro moves again
+        slots[0].set_volume(0.9);
+        engine.apply(&mut slots, positions);
+        assert_eq!(slots[0].volume(), 0.9);
+        positions[1] = 1.0;
+        engine.apply(&mut slots, positions);
+        assert_eq!(slots[0].volume(), 1.0);
+
+        // Out-of-range slots are ignored
+        engine.set_assignments(Arc::new(vec![MacroAssignment::new(2, 99, MacroTarget::Volume)]));
+        engine.apply(&mut slots, positions);
+        assert_eq!(MacroCurve::Logarithmic.shape(0.5), 0.75);
+    }
+}
@@ -0,0 +1,158 @@
+//! Per-slot MIDI input filter: note range, velocity clamp/scale, blocked
+//! CCs and pitch bend.
+//!
+//! Applied at the top of `Slot::handle_midi_event`, after keyswitches (so
+//! keyswitch keys below the note range still work). Note-offs and channel
+//! mode messages (CC 120–127) always pass, so changing the filter never
+//! leaves a note hanging.
+
+use nih_plug::prelude::NoteEvent;
+use serde::{Deserialize, Serialize};
+
+/// First channel mode CC (All Sound Off); these are never blocked.
+const FIRST_MODE_CC: u8 = 120;
+
+/// A slot's filter as configured in the rack.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MidiFilterSettings {
+    /// Lowest note played.
+    pub note_low: u8,
+    /// Highest note played.
+    pub note_high: u8,
+    /// Velocity multiplier applied before clamping.
+    pub velocity_scale: f32,
+    /// Velocity range (0.0–1.0) note-ons are clamped into.
+    pub velocity_min: f32,
+    pub velocity_max: f32,
+    /// Controllers dropped before they reach the slot.
+    pub blocked_ccs: Vec<u8>,
+    pub ignore_pitch_bend: bool,
+}
+
+impl Default for MidiFilterSettings {
+    fn default() -> Self {
+        Self {
+            note_low: 0,
+            note_high: 127,
+            velocity_scale: 1.0,
+            velocity_min: 0.0,
+            velocity_max: 1.0,
+            blocked_ccs: Vec::new(),
+            ignore_pitch_bend: false,
+        }
+    }
+}
+
+impl MidiFilterSettings {
+    /// Whether the filter lets everything through unchanged.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Audio-thread form of [`MidiFilterSettings`]. `Copy`, so it can be sent
+/// to the audio thread without allocating.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MidiFilter {
+    note_low: u8,
+    note_high: u8,
+    velocity_scale: f32,
+    velocity_min: f32,
+    velocity_max: f32,
+    /// Bit `n` set = CC `n` blocked.
+    blocked_ccs: u128,
+    ignore_pitch_bend: bool,
+}
+
+impl Default for MidiFilter {
+    fn default() -> Self {
+        Self::from_settings(&MidiFilterSettings::default())
+    }
+}
+
+impl MidiFilter {
+    pub fn from_settings(settings: &MidiFilterSettings) -> Self {
+        let blocked_ccs = settings
+            .blocked_ccs
+            .iter()
+            .filter(|cc| **cc < FIRST_MODE_CC)
+            .fold(0u128, |mask, cc| mask | 1 << cc);
+        let velocity_min = settings.velocity_min.clamp(0.0, 1.0);
+        Self {
+            note_low: settings.note_low.min(127),
+            note_high: settings.note_high.min(127),
+            velocity_scale: settings.velocity_scale.max(0.0),
+            velocity_min,
+            velocity_max: settings.velocity_max.clamp(velocity_min, 1.0),
+            blocked_ccs,
+            ignore_pitch_bend: settings.ignore_pitch_bend,
+        }
+    }
+
+    /// The event as the slot should see it, or `None` to drop it.
+    pub fn apply(&self, event: &NoteEvent<()>) -> Option<NoteEvent<()>> {
+        match *event {
+            NoteEvent::NoteOn { note, .. } if note < self.note_low || note > self.note_high => None,
+            NoteEvent::NoteOn { timing, voice_id, channel, note, velocity } => Some(NoteEvent::NoteOn {
+                timing,
+                voice_id,
+                channel,
+                note,
+                velocity: (velocity * self.velocity_scale).clamp(self.velocity_min, self.velocity_max),
+            }),
+            NoteEvent::MidiCC { cc, .. } if cc < 128 && self.blocked_ccs & (1 << cc) != 0 => None,
+            NoteEvent::MidiPitchBend { .. } if self.ignore_pitch_bend => None,
+            _ => Some(*event),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note_on(note: u8, velocity: f32) -> NoteEvent<()> {
+        NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note, velocity }
+    }
+
+    fn cc(cc: u8) -> NoteEvent<()> {
+        NoteEvent::MidiCC { timing: 0, channel: 0, cc, value: 1.0 }
+    }
+
+    #[test]
+    fn test_note_range_and_velocity() {
+        let filter = MidiFilter::from_settings(&MidiFilterSettings {
+            note_low: 48,
+            note_high: 72,
+            velocity_scale: 2.0,
+            velocity_min: 0.25,
+            velocity_max: 0.75,
+            ..Default::default()
+        });
+        assert_eq!(filter.apply(&note_on(47, 0.5)), None);
+        assert_eq!(filter.apply(&note_on(73, 0.5)), None);
+        assert_eq!(filter.apply(&note_on(60, 0.1)), Some(note_on(60, 0.25)));
+        assert_eq!(filter.apply(&note_on(60, 0.5)), Some(note_on(60, 0.75)));
+        // Note-offs pass even outside the range
+        let off = NoteEvent::NoteOff { timing: 0, voice_id: None, channel: 0, note: 30, velocity: 0.0 };
+        assert_eq!(filter.apply(&off), Some(off));
+    }
+
+    #[test]
+    fn test_blocked_ccs_and_bend() {
+        let filter = MidiFilter::from_settings(&MidiFilterSettings {
+            blocked_ccs: vec![1, 64, 123],
+            ignore_pitch_bend: true,
+            ..Default::default()
+        });
+        assert_eq!(filter.apply(&cc(1)), None);
+        assert_eq!(filter.apply(&cc(64)), None);
+        assert_eq!(filter.apply(&cc(7)), Some(cc(7)));
+        // Channel mode messages can't be blocked
+        assert_eq!(filter.apply(&cc(123)), Some(cc(123)));
+        let bend = NoteEvent::MidiPitchBend { timing: 0, channel: 0, value: 0.7 };
+        assert_eq!(filter.apply(&bend), None);
+        assert_eq!(MidiFilter::default().apply(&bend), Some(bend));
+    }
+}
@@ -4,11 +4,52 @@
 //! and optionally runs `.sw` source code. This matches the web editor
 //! model where presets are loaded via `loadPreset()` in source code.
 
+pub mod arpeggiator;
+pub mod bend;
+pub mod controllers;
+pub mod effects;
+pub mod filter;
+pub mod frozen;
+pub mod group;
+pub mod keyswitch;
+pub mod legato;
+pub mod macros;
+pub mod midi_filter;
+pub mod mod_matrix;
+pub mod oversampling;
 pub mod preset_slot;
 pub mod runner_slot;
 pub mod slot;
+pub mod synth;
+pub mod unison;
+pub mod zone_usage;
 
-pub use slot::Slot;
+pub use arpeggiator::{ArpMode, ArpSettings};
+pub use bend::{BendDestination, BendSettings};
+pub use controllers::{ControllerSettings, ModDestination, PressureDestination};
+pub use filter::VoiceFilterSettings;
+pub use group::{GroupBus, MAX_GROUPS};
+pub use keyswitch::{Articulation, KeyswitchMap};
+pub use legato::LegatoSettings;
+pub use macros::{MACRO_COUNT, MacroAssignment, MacroCurve, MacroTarget};
+pub use midi_filter::{MidiFilter, MidiFilterSettings};
+pub use mod_matrix::{ModMatrix, ModSource, ModTarget};
+pub use oversampling::Oversampling;
+pub use runner_slot::{Humanize, LaunchQuantize, MidiOutNote, SlotTarget};
+pub use slot::{DEFAULT_POLYPHONY, MAX_POLYPHONY, Slot, SlotOutput, SlotTuning};
+pub use unison::UnisonSettings;
+
+use std::sync::Arc;
+
+use crossbeam_channel::Sender;
+use nih_plug::prelude::NoteEvent;
+
+use crate::midi::gm::{GmMode, GmRequest};
+use crate::midi::rules::{FiredRule, MidiRule, MidiRuleEngine, RuleAction, RuleMatch};
+use crate::midi::scale::ScaleFilter;
+use crate::perf::garbage::GarbageSender;
+use crate::perf::voice_pools::PoolResizer;
+use crate::transport::TransportState;
 
 /// Maximum number of simultaneous slots.
 pub const MAX_SLOTS: usize = 16;
@@ -16,14 +57,51 @@ pub const MAX_SLOTS: usize = 16;
 /// Manages the collection of instrument slots.
 pub struct SlotManager {
     slots: Vec<Slot>,
+    /// Group buses (pre-allocated, indexed by group id).
+    groups: [GroupBus; MAX_GROUPS],
     sample_rate: f32,
+    /// Collector channel for presets retired on the audio thread.
+    garbage_tx: Option<GarbageSender>,
+    /// NRPN/SysEx rules matched in `midi::route_event`.
+    midi_rules: MidiRuleEngine,
+    /// Dispatcher channel for fired rules.
+    rule_tx: Option<Sender<FiredRule>>,
+    /// Macro knob assignments, applied per block.
+    macros: macros::MacroEngine,
+    /// Scale lock applied to notes in `midi::route_event`.
+    scale_filter: ScaleFilter,
+    /// Removed slots, rendered until their voices fade out and then kept
+    /// until the collector has room for them.
+    retiring: Vec<Slot>,
+    /// Removed slots whose replacement hasn't been asked for yet.
+    replacements_due: usize,
+    /// Allocates resized voice pools off the audio thread.
+    resizer: Option<PoolResizer>,
+    /// Replaced voice pools the collector had no room for yet.
+    unretired_pools: Vec<slot::VoicePool>,
+    /// Reset every slot when the transport stops.
+    reset_on_stop: bool,
+    /// GM mode bank select and program requests.
+    gm_mode: GmMode,
 }
 
 impl SlotManager {
     pub fn new_empty() -> Self {
         Self {
             slots: Vec::with_capacity(MAX_SLOTS),
+            groups: [GroupBus::default(); MAX_GROUPS],
             sample_rate: 44100.0,
+            garbage_tx: None,
+            midi_rules: MidiRuleEngine::default(),
+            rule_tx: None,
+            macros: macros::MacroEngine::default(),
+            scale_filter: ScaleFilter::default(),
+            retiring: Vec::with_capacity(MAX_SLOTS),
+            replacements_due: 0,
+            resizer: None,
+            unretired_pools: Vec::with_capacity(MAX_SLOTS),
+            reset_on_stop: true,
+            gm_mode: GmMode::default(),
         }
     }
 
@@ -33,8 +111,181 @@ impl SlotManager {
             for i in 0..MAX_SLOTS {
                 let mut slot = Slot::new(i);
                 slot.initialize(self.sample_rate);
+                if let Some(tx) = &self.garbage_tx {
+                    slot.preset_state_mut().set_garbage_sender(tx.clone());
+                }
                 self.slots.push(slot);
             }
+            self.apply_gm_channels();
+        }
+    }
+
+    /// Route retired presets of every slot to a collector thread so the
+    /// final drop never happens on the audio thread.
+    pub fn set_garbage_sender(&mut self, tx: GarbageSender) {
+        for slot in &mut self.slots {
+            slot.preset_state_mut().set_garbage_sender(tx.clone());
+        }
+        self.garbage_tx = Some(tx);
+    }
+
+    /// Resize voice pools through `resizer` when polyphony changes.
+    pub fn set_pool_resizer(&mut self, resizer: PoolResizer) {
+        self.resizer = Some(resizer);
+    }
+
+    /// Global Pitch Bend Range for slots without their own. Called per block.
+    pub fn set_bend_range(&mut self, semitones: u8) {
+        for slot in &mut self.slots {
+            slot.set_global_bend_range(semitones);
+        }
+    }
+
+    /// Bring every slot's voice pool in line with its polyphony, capped at
+    /// `max_voices`. Called at the start of each block: swaps in pools the
+    /// resizer has finished and requests new ones, never allocating here.
+    pub fn update_voice_pools(&mut self, max_voices: usize) {
+        let Some(resizer) = &self.resizer else { return };
+
+        // Removed slots are replaced at the end of the rack
+        while self.replacements_due > 0 && resizer.request_slot(self.sample_rate) {
+            self.replacements_due -= 1;
+        }
+        while self.slots.len() < MAX_SLOTS {
+            let Some(mut slot) = resizer.try_recv_slot() else { break };
+            slot.set_index(self.slots.len());
+            if let Some(tx) = &self.garbage_tx {
+                slot.preset_state_mut().set_garbage_sender(tx.clone());
+            }
+            if self.gm_mode.is_enabled() {
+                slot.set_midi_channel(self.slots.len() as i32 + 1);
+            }
+            self.slots.push(slot);
+        }
+
+        // Old pools are freed by the collector; keep them until it has room
+        if let Some(tx) = &self.garbage_tx {
+            while let Some(pool) = self.unretired_pools.pop() {
+                if let Err(pool) = crate::perf::garbage::try_retire_voice_pool(tx, pool) {
+                    self.unretired_pools.push(pool);
+                    break;
+                }
+            }
+        }
+
+        while self.unretired_pools.len() < self.unretired_pools.capacity() {
+            let Some(resized) = resizer.try_recv() else { break };
+            let retired = match self.slots.get_mut(resized.slot_index) {
+                Some(slot) if slot.pending_pool() == Some(resized.pool.capacity()) => {
+                    slot.swap_voice_pool(resized.pool)
+                }
+                // Superseded by a later request, or the slot is gone
+                _ => resized.pool,
+            };
+            // Without a collector the old pool is dropped here
+            if let Some(tx) = &self.garbage_tx {
+                if let Err(pool) = crate::perf::garbage::try_retire_voice_pool(tx, retired) {
+                    self.unretired_pools.push(pool);
+                }
+            }
+        }
+
+        for slot in &mut self.slots {
+            let target = slot.target_voices(max_voices);
+            if slot.voice_capacity() == target {
+                slot.set_pending_pool(None);
+            } else if slot.pending_pool() != Some(target) && resizer.request(slot.index(), target) {
+                slot.set_pending_pool(Some(target));
+            }
+        }
+    }
+
+    /// Send fired MIDI rules to a dispatcher thread.
+    pub fn set_rule_sender(&mut self, tx: Sender<FiredRule>) {
+        self.rule_tx = Some(tx);
+    }
+
+    /// Send GM mode program requests to a loader thread.
+    pub fn set_gm_sender(&mut self, tx: Sender<GmRequest>) {
+        self.gm_mode.set_sender(tx);
+    }
+
+    pub fn gm_mode_mut(&mut self) -> &mut GmMode {
+        &mut self.gm_mode
+    }
+
+    /// Turn GM mode on or off. On, slot N listens on MIDI channel N; off,
+    /// slots keep their channels and program changes are ignored.
+    pub fn set_gm_mode(&mut self, enabled: bool) {
+        self.gm_mode.set_enabled(enabled);
+        self.apply_gm_channels();
+    }
+
+    fn apply_gm_channels(&mut self) {
+        if self.gm_mode.is_enabled() {
+            for (i, slot) in self.slots.iter_mut().enumerate() {
+                slot.set_midi_channel(i as i32 + 1);
+            }
+        }
+    }
+
+    pub fn midi_rules_mut(&mut self) -> &mut MidiRuleEngine {
+        &mut self.midi_rules
+    }
+
+    pub fn scale_filter_mut(&mut self) -> &mut ScaleFilter {
+        &mut self.scale_filter
+    }
+
+    /// Replace the MIDI rules. The old set is dropped on the collector
+    /// thread when there is one.
+    pub fn set_midi_rules(&mut self, rules: Arc<Vec<MidiRule>>) {
+        let old = self.midi_rules.set_rules(rules);
+        if let Some(tx) = &self.garbage_tx {
+            // A full channel just means this (small) set is freed here
+            let _ = crate::perf::garbage::try_retire_rules(tx, old);
+        }
+    }
+
+    /// Replace the macro assignments. The old set is dropped on the
+    /// collector thread when there is one.
+    pub fn set_macros(&mut self, assignments: Arc<Vec<MacroAssignment>>) {
+        let old = self.macros.set_assignments(assignments);
+        if let Some(tx) = &self.garbage_tx {
+            let _ = crate::perf::garbage::try_retire_macros(tx, old);
+        }
+    }
+
+    /// Apply macro positions (0–1) that changed since the last block to
+    /// their assigned slots. Called per block.
+    pub fn apply_macros(&mut self, positions: [f32; MACRO_COUNT]) {
+        self.macros.apply(&mut self.slots, positions);
+    }
+
+    /// Carry out every rule matching `m`: mute/solo toggles apply here,
+    /// and each fired rule is reported to the dispatcher.
+    pub fn fire_rules(&mut self, m: RuleMatch) {
+        let rules = self.midi_rules.rules().clone();
+        for (index, rule) in rules.iter().enumerate() {
+            if !rule.trigger.matches(&m) {
+                continue;
+            }
+            match rule.action {
+                RuleAction::ToggleMute { slot_index } => {
+                    if let Some(slot) = self.slots.get_mut(slot_index) {
+                        slot.set_muted(!slot.is_muted());
+                    }
+                }
+                RuleAction::ToggleSolo { slot_index } => {
+                    if let Some(slot) = self.slots.get_mut(slot_index) {
+                        slot.set_solo(!slot.is_solo());
+                    }
+                }
+                RuleAction::LoadPreset { .. } => {}
+            }
+            if let Some(tx) = &self.rule_tx {
+                let _ = tx.try_send(FiredRule { rules: rules.clone(), index });
+            }
         }
     }
 
@@ -51,6 +302,29 @@ impl SlotManager {
         }
     }
 
+    pub fn set_reset_on_stop(&mut self, enabled: bool) {
+        self.reset_on_stop = enabled;
+    }
+
+    /// The transport stopped: with reset-on-stop on, release every note
+    /// (held, sustained or arpeggiated) and stop runners so the next start
+    /// begins from a clean slate.
+    pub fn transport_stopped(&mut self) {
+        if !self.reset_on_stop {
+            return;
+        }
+        for slot in &mut self.slots {
+            slot.all_notes_off();
+        }
+    }
+
+    /// Silence every slot with a short fade (see `Slot::panic`).
+    pub fn panic(&mut self) {
+        for slot in &mut self.slots {
+            slot.panic();
+        }
+    }
+
     pub fn slot_count(&self) -> usize {
         self.slots.len()
     }
@@ -59,6 +333,12 @@ impl SlotManager {
         &self.slots
     }
 
+    /// Largest delay any slot's oversampling adds, in samples (the host
+    /// compensates for it; slots with less play that much early).
+    pub fn oversampling_latency(&self) -> u32 {
+        self.slots.iter().map(|s| s.oversampling().latency()).max().unwrap_or(0)
+    }
+
     pub fn slots_mut(&mut self) -> &mut Vec<Slot> {
         &mut self.slots
     }
@@ -71,17 +351,32 @@ impl SlotManager {
         let idx = self.slots.len();
         let mut slot = Slot::new(idx);
         slot.initialize(self.sample_rate);
+        if let Some(tx) = &self.garbage_tx {
+            slot.preset_state_mut().set_garbage_sender(tx.clone());
+        }
         self.slots.push(slot);
         Some(idx)
     }
 
-    /// Remove a slot by index.
+    /// Remove a slot by index. The slots after it move up, and a fresh
+    /// slot from the resizer takes the free place at the end.
+    ///
+    /// Returns false if there is no such slot, it is the last one, or
+    /// earlier removed slots are still waiting for the collector.
     pub fn remove_slot(&mut self, index: usize) -> bool {
-        if index < self.slots.len() && self.slots.len() > 1 {
-            self.slots.remove(index);
-            // Re-index remaining slots
+        let room = self.retiring.len() < self.retiring.capacity();
+        if index < self.slots.len() && self.slots.len() > 1 && room {
+            let mut slot = self.slots.remove(index);
+            slot.voice_pool_mut().kill_all();
+            self.retiring.push(slot);
+            if self.resizer.is_some() {
+                self.replacements_due += 1;
+            }
+            // Re-index remaining slots. Pools in flight were requested
+            // under the old indices, so they are dropped and asked for again.
             for (i, slot) in self.slots.iter_mut().enumerate() {
                 slot.set_index(i);
+                slot.set_pending_pool(None);
             }
             true
         } else {
@@ -89,10 +384,153 @@ impl SlotManager {
         }
     }
 
+    /// Removed slots that are still fading out.
+    pub fn retiring_mut(&mut self) -> impl Iterator<Item = &mut Slot> {
+        self.retiring.iter_mut().filter(|slot| !slot.is_silent())
+    }
+
+    /// Hand removed slots that have gone silent to the collector, keeping
+    /// them until it has room. Without a collector they are dropped here.
+    pub fn finish_retiring(&mut self) {
+        let mut i = 0;
+        while i < self.retiring.len() {
+            if !self.retiring[i].is_silent() {
+                i += 1;
+                continue;
+            }
+            let slot = self.retiring.swap_remove(i);
+            if let Some(tx) = &self.garbage_tx {
+                if let Err(slot) = crate::perf::garbage::try_retire_slot(tx, slot) {
+                    self.retiring.push(slot);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Whether every slot can take a new preset now (see
+    /// `PresetSlotState::has_room`). Loads wait in their channel until then.
+    pub fn presets_have_room(&self) -> bool {
+        self.slots.iter().all(|s| s.preset_state().has_room())
+    }
+
     /// Check if any slot has solo enabled.
     pub fn any_solo(&self) -> bool {
         self.slots.iter().any(|s| s.is_solo())
     }
+
+    /// Hand every runner note queued for MIDI output to `send`, then clear
+    /// the queues. Called once per block after rendering.
+    pub fn drain_midi_out(&mut self, mut send: impl FnMut(&MidiOutNote)) {
+        for slot in &mut self.slots {
+            let queue = &mut slot.runner_state_mut().midi_out;
+            for note in queue.iter() {
+                send(note);
+            }
+            queue.clear();
+        }
+    }
+
+    /// Advance routed runner tracks and deliver their notes to target slots.
+    ///
+    /// Runs before any slot renders, so routed notes land in the same block
+    /// regardless of slot order. A runner never routes to itself.
+    pub fn dispatch_runner_routes(
+        &mut self,
+        num_samples: usize,
+        sample_rate: f32,
+        transport: &TransportState,
+    ) {
+        for source_idx in 0..self.slots.len() {
+            if !self.slots[source_idx].has_source() {
+                continue;
+            }
+            let runner = self.slots[source_idx].runner_state_mut();
+            runner.advance_routes(num_samples, sample_rate, transport);
+            if runner.routed_out.is_empty() {
+                continue;
+            }
+
+            // Take the queue so other slots can be borrowed mutably;
+            // it is cleared and handed back without reallocating.
+            let mut queue = std::mem::take(&mut self.slots[source_idx].runner_state_mut().routed_out);
+            for routed in &queue {
+                let Some(target_idx) = self.resolve_route_target(source_idx, routed.route) else {
+                    continue;
+                };
+                let event = if routed.on {
+                    NoteEvent::NoteOn {
+                        timing: 0,
+                        voice_id: None,
+                        channel: 0,
+                        note: routed.note,
+                        velocity: routed.velocity,
+                    }
+                } else {
+                    NoteEvent::NoteOff {
+                        timing: 0,
+                        voice_id: None,
+                        channel: 0,
+                        note: routed.note,
+                        velocity: 0.0,
+                    }
+                };
+                self.slots[target_idx].handle_midi_event(&event, transport);
+            }
+            queue.clear();
+            self.slots[source_idx].runner_state_mut().routed_out = queue;
+        }
+    }
+
+    /// Resolve a runner route to a target slot index (never the source slot).
+    fn resolve_route_target(&self, source_idx: usize, route: usize) -> Option<usize> {
+        let target = &self.slots[source_idx].runner_state().program().routes.get(route)?.target;
+        let idx = match target {
+            SlotTarget::Index(i) => *i,
+            SlotTarget::Name(name) => self.slots.iter().position(|s| s.name == *name)?,
+        };
+        (idx < self.slots.len() && idx != source_idx).then_some(idx)
+    }
+
+    pub fn groups(&self) -> &[GroupBus] {
+        &self.groups
+    }
+
+    /// Update a group bus. Out-of-range indices are ignored.
+    pub fn set_group(&mut self, index: usize, bus: GroupBus) {
+        if let Some(g) = self.groups.get_mut(index) {
+            *g = bus;
+        }
+    }
+
+    /// Check if any group has solo enabled.
+    pub fn any_group_solo(&self) -> bool {
+        self.groups.iter().any(|g| g.solo)
+    }
+
+    /// Effective group gain for a slot, or None if its group silences it
+    /// (group muted, or another group soloed).
+    ///
+    /// Ungrouped slots are only silenced by group solo.
+    pub fn group_gain(&self, slot: &Slot) -> Option<f32> {
+        let any_group_solo = self.any_group_solo();
+        match slot.group().and_then(|g| self.groups.get(g)) {
+            Some(bus) => {
+                if bus.muted || (any_group_solo && !bus.solo) {
+                    None
+                } else {
+                    Some(bus.volume)
+                }
+            }
+            None => {
+                if any_group_solo {
+                    None
+                } else {
+                    Some(1.0)
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -159,6 +597,82 @@ mod tests {
         assert_eq!(sm.slots()[1].index(), 1);
     }
 
+    #[test]
+    fn test_voice_pools_follow_polyphony() {
+        let mut sm = SlotManager::new_empty();
+        sm.add_slot();
+        sm.set_pool_resizer(PoolResizer::spawn());
+        sm.slots_mut()[0].set_polyphony(16);
+
+        // Max Voices caps the slot's own setting
+        for _ in 0..1000 {
+            sm.update_voice_pools(8);
+            if sm.slots()[0].voice_capacity() == 8 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        assert_eq!(sm.slots()[0].voice_capacity(), 8);
+        assert_eq!(sm.slots()[0].pending_pool(), None);
+    }
+
+    #[test]
+    fn test_transport_stop_resets_slots() {
+        let mut sm = SlotManager::new_empty();
+        sm.add_slot();
+        let transport = TransportState::default();
+        let pedal = NoteEvent::MidiCC { timing: 0, channel: 0, cc: crate::midi::SUSTAIN_PEDAL, value: 1.0 };
+        let note_on = NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note: 60, velocity: 0.8 };
+        sm.slots_mut()[0].handle_midi_event(&pedal, &transport);
+        sm.slots_mut()[0].handle_midi_event(&note_on, &transport);
+
+        sm.set_reset_on_stop(false);
+        sm.transport_stopped();
+        assert!(sm.slots()[0].preset_state().sustain, "reset is optional");
+
+        sm.set_reset_on_stop(true);
+        sm.transport_stopped();
+        assert!(!sm.slots()[0].preset_state().sustain);
+        let mut slot_voices = sm.slots_mut()[0].voice_pool_mut().active_voices_mut();
+        assert!(slot_voices.all(|v| v.releasing));
+    }
+
+    #[test]
+    fn test_remove_sounding_slot_fades_out() {
+        let mut sm = SlotManager::new_empty();
+        sm.add_slot();
+        sm.add_slot();
+        let note_on = NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note: 60, velocity: 0.8 };
+        sm.slots_mut()[1].handle_midi_event(&note_on, &TransportState::default());
+
+        assert!(sm.remove_slot(1));
+        let transport = TransportState::default();
+        let (mut left, mut right) = (vec![0.0f32; 512], vec![0.0f32; 512]);
+        let slot = sm.retiring_mut().next().expect("sounding slot should fade out");
+        slot.render(&mut left, &mut right, 512, 44100.0, &transport);
+        sm.finish_retiring();
+        assert!(sm.retiring_mut().next().is_none(), "fade should finish within a block");
+    }
+
+    #[test]
+    fn test_removed_slot_waits_for_a_busy_collector() {
+        use crate::perf::garbage::Garbage;
+
+        let (tx, rx) = crossbeam_channel::bounded(1);
+        tx.send(Garbage::Macros(Arc::new(Vec::new()))).unwrap();
+        let mut sm = SlotManager::new_empty();
+        sm.set_garbage_sender(tx);
+        sm.add_slot();
+        sm.add_slot();
+
+        assert!(sm.remove_slot(1));
+        sm.finish_retiring();
+        assert!(matches!(rx.try_recv(), Ok(Garbage::Macros(_))));
+        assert!(rx.try_recv().is_err(), "the slot is kept while the channel is full");
+        sm.finish_retiring();
+        assert!(matches!(rx.try_recv(), Ok(Garbage::Slot(_))));
+    }
+
     #[test]
     fn test_slot_manager_remove_last_slot_rejected() {
         let mut sm = SlotManager::new_empty();
@@ -196,4 +710,60 @@ mod tests {
         // Just verify it doesn't panic
         assert_eq!(sm.slot_count(), 1);
     }
+
+    #[test]
+    fn test_group_gain_ungrouped() {
+        let mut sm = SlotManager::new_empty();
+        sm.add_slot();
+        assert_eq!(sm.group_gain(&sm.slots()[0]), Some(1.0));
+    }
+
+    #[test]
+    fn test_group_gain_applies_volume() {
+        let mut sm = SlotManager::new_empty();
+        sm.add_slot();
+        sm.slots_mut()[0].set_group(Some(2));
+        sm.set_group(2, GroupBus { volume: 0.5, ..GroupBus::default() });
+        assert_eq!(sm.group_gain(&sm.slots()[0]), Some(0.5));
+    }
+
+    #[test]
+    fn test_group_mute_silences_members() {
+        let mut sm = SlotManager::new_empty();
+        sm.add_slot();
+        sm.slots_mut()[0].set_group(Some(0));
+        sm.set_group(0, GroupBus { muted: true, ..GroupBus::default() });
+        assert_eq!(sm.group_gain(&sm.slots()[0]), None);
+    }
+
+    #[test]
+    fn test_group_solo_silences_others() {
+        let mut sm = SlotManager::new_empty();
+        sm.add_slot();
+        sm.add_slot();
+        sm.add_slot();
+        sm.slots_mut()[0].set_group(Some(0));
+        sm.slots_mut()[1].set_group(Some(1));
+        sm.set_group(0, GroupBus { solo: true, ..GroupBus::default() });
+        assert!(sm.any_group_solo());
+        assert_eq!(sm.group_gain(&sm.slots()[0]), Some(1.0));
+        assert_eq!(sm.group_gain(&sm.slots()[1]), None);
+        assert_eq!(sm.group_gain(&sm.slots()[2]), None, "ungrouped slots are silenced by group solo");
+    }
+
+    #[test]
+    fn test_set_group_out_of_range() {
+        let mut sm = SlotManager::new_empty();
+        sm.set_group(MAX_GROUPS, GroupBus { muted: true, ..GroupBus::default() });
+        assert!(sm.groups().iter().all(|g| !g.muted));
+    }
+
+    #[test]
+    fn test_dispatch_runner_routes_without_source_is_noop() {
+        let mut sm = SlotManager::new_empty();
+        sm.add_slot();
+        sm.add_slot();
+        sm.dispatch_runner_routes(512, 44100.0, &TransportState::default());
+        assert_eq!(sm.slots()[1].active_voice_count(), 0);
+    }
 }
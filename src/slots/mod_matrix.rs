@@ -0,0 +1,227 @@
+//! Per-slot modulation matrix.
+//!
+//! Any source can drive any destination, each route with its own depth
+//! (−1..1; 0 = no route), on top of the fixed bend and controller
+//! routings. Two free-running LFOs belong to the matrix. Sources are read
+//! once per block: the LFOs at the block start, the mod wheel and the
+//! smoothed aftertouch as they stand, and per voice its velocity and a
+//! random value drawn when the note started. Sample start is read once per
+//! note, when the voice starts.
+
+use serde::{Deserialize, Serialize};
+
+use super::{bend, controllers};
+
+/// Pitch swing of a full-depth route at full source, in semitones.
+pub const MAX_PITCH_SEMITONES: f32 = 12.0;
+
+/// Fastest matrix LFO.
+pub const MAX_LFO_HZ: f32 = 20.0;
+
+/// What moves a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModSource {
+    /// Matrix LFOs (−1..1).
+    Lfo1,
+    Lfo2,
+    /// CC1 (0–1).
+    ModWheel,
+    /// Smoothed channel pressure (0–1).
+    Aftertouch,
+    /// Note velocity (0–1).
+    Velocity,
+    /// Drawn per note (−1..1).
+    Random,
+}
+
+impl ModSource {
+    pub const ALL: [ModSource; 6] = [
+        ModSource::Lfo1,
+        ModSource::Lfo2,
+        ModSource::ModWheel,
+        ModSource::Aftertouch,
+        ModSource::Velocity,
+        ModSource::Random,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ModSource::Lfo1 => "LFO 1",
+            ModSource::Lfo2 => "LFO 2",
+            ModSource::ModWheel => "Mod wheel",
+            ModSource::Aftertouch => "Aftertouch",
+            ModSource::Velocity => "Velocity",
+            ModSource::Random => "Random",
+        }
+    }
+}
+
+/// What a route moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModTarget {
+    Pitch,
+    Gain,
+    Pan,
+    /// Voice filter cutoff (needs the slot filter enabled).
+    FilterCutoff,
+    /// Where new notes start in their sample.
+    SampleStart,
+}
+
+impl ModTarget {
+    pub const ALL: [ModTarget; 5] = [
+        ModTarget::Pitch,
+        ModTarget::Gain,
+        ModTarget::Pan,
+        ModTarget::FilterCutoff,
+        ModTarget::SampleStart,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ModTarget::Pitch => "Pitch",
+            ModTarget::Gain => "Gain",
+            ModTarget::Pan => "Pan",
+            ModTarget::FilterCutoff => "Cutoff",
+            ModTarget::SampleStart => "Start",
+        }
+    }
+}
+
+pub const MOD_SOURCES: usize = ModSource::ALL.len();
+pub const MOD_TARGETS: usize = ModTarget::ALL.len();
+
+/// Routes and LFO rates of a slot.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModMatrix {
+    pub lfo1_hz: f32,
+    pub lfo2_hz: f32,
+    /// Route depth (−1..1) by source, then destination, in `ALL` order.
+    pub depth: [[f32; MOD_TARGETS]; MOD_SOURCES],
+}
+
+impl Default for ModMatrix {
+    fn default() -> Self {
+        Self {
+            lfo1_hz: 2.0,
+            lfo2_hz: 0.5,
+            depth: [[0.0; MOD_TARGETS]; MOD_SOURCES],
+        }
+    }
+}
+
+/// Block-rate source values, read at the start of a block.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BlockSources {
+    pub lfo1: f32,
+    pub lfo2: f32,
+    pub mod_wheel: f32,
+    pub aftertouch: f32,
+}
+
+impl BlockSources {
+    /// Every source's value for one voice, in `ModSource::ALL` order.
+    pub fn with_voice(&self, velocity: f32, random: f32) -> [f32; MOD_SOURCES] {
+        [self.lfo1, self.lfo2, self.mod_wheel, self.aftertouch, velocity, random]
+    }
+}
+
+/// What the matrix does to one voice for a block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoiceModulation {
+    /// Playback rate factor.
+    pub pitch: f64,
+    /// Per-channel gain (gain and pan together).
+    pub gain: (f32, f32),
+    /// Cutoff factor.
+    pub cutoff: f32,
+}
+
+impl ModMatrix {
+    pub fn depth(&self, source: ModSource, destination: ModTarget) -> f32 {
+        self.depth[source as usize][destination as usize]
+    }
+
+    pub fn set_depth(&mut self, source: ModSource, destination: ModTarget, depth: f32) {
+        self.depth[source as usize][destination as usize] = depth.clamp(-1.0, 1.0);
+    }
+
+    /// Whether any route is set.
+    pub fn is_active(&self) -> bool {
+        self.depth.iter().flatten().any(|&d| d != 0.0)
+    }
+
+    /// Sum of the routes into `destination` for the given source values.
+    fn sum(&self, destination: ModTarget, values: &[f32; MOD_SOURCES]) -> f32 {
+        self.depth.iter().zip(values).map(|(row, value)| row[destination as usize] * value).sum()
+    }
+
+    /// The matrix's effect on a voice with these source values.
+    pub fn voice_modulation(&self, values: &[f32; MOD_SOURCES]) -> VoiceModulation {
+        let gain = (1.0 + self.sum(ModTarget::Gain, values)).clamp(0.0, 2.0);
+        // Balance: panning turns the far side down, the near side stays
+        let pan = self.sum(ModTarget::Pan, values).clamp(-1.0, 1.0);
+        let octaves = self.sum(ModTarget::FilterCutoff, values) * controllers::MAX_CUTOFF_OCTAVES;
+        VoiceModulation {
+            pitch: bend::ratio(self.sum(ModTarget::Pitch, values) * MAX_PITCH_SEMITONES) as f64,
+            gain: (gain * (1.0 - pan).min(1.0), gain * (1.0 + pan).min(1.0)),
+            cutoff: 2.0_f32.powf(octaves),
+        }
+    }
+
+    /// Fraction of the sample a new note skips.
+    pub fn start_offset(&self, values: &[f32; MOD_SOURCES]) -> f64 {
+        self.sum(ModTarget::SampleStart, values).clamp(0.0, 1.0) as f64 * bend::MAX_START_OFFSET
+    }
+
+    /// Advance the LFO phases (in cycles) over a block.
+    pub fn advance(&self, phases: &mut [f64; 2], num_samples: usize, sample_rate: f32) {
+        for (phase, hz) in phases.iter_mut().zip([self.lfo1_hz, self.lfo2_hz]) {
+            let hz = hz.clamp(0.0, MAX_LFO_HZ) as f64;
+            *phase = (*phase + num_samples as f64 * hz / sample_rate.max(1.0) as f64).fract();
+        }
+    }
+}
+
+/// Next per-note random value (−1..1) from a xorshift state.
+pub fn next_random(state: &mut u32) -> f32 {
+    let mut x = (*state).max(1);
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    *state = x;
+    (x as f32 / u32::MAX as f32) * 2.0 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes_sum_per_destination() {
+        let mut matrix = ModMatrix::default();
+        assert!(!matrix.is_active());
+        let neutral = matrix.voice_modulation(&BlockSources::default().with_voice(1.0, 0.5));
+        assert_eq!(neutral, VoiceModulation { pitch: 1.0, gain: (1.0, 1.0), cutoff: 1.0 });
+
+        matrix.set_depth(ModSource::Lfo1, ModTarget::Pitch, 1.0);
+        matrix.set_depth(ModSource::Velocity, ModTarget::FilterCutoff, 0.5);
+        matrix.set_depth(ModSource::Random, ModTarget::Pan, 2.0);
+        matrix.set_depth(ModSource::ModWheel, ModTarget::Gain, -1.0);
+        matrix.set_depth(ModSource::Velocity, ModTarget::SampleStart, 1.0);
+        assert_eq!(matrix.depth(ModSource::Random, ModTarget::Pan), 1.0, "depth is clamped");
+
+        let sources = BlockSources { lfo1: -1.0 / 12.0, mod_wheel: 0.5, ..Default::default() };
+        let m = matrix.voice_modulation(&sources.with_voice(1.0, -1.0));
+        assert!((m.pitch - bend::ratio(-1.0) as f64).abs() < 1e-6);
+        assert_eq!(m.cutoff, 4.0);
+        // Hard left at half gain
+        assert!((m.gain.0 - 0.5).abs() < 1e-6 && m.gain.1.abs() < 1e-6);
+        assert_eq!(matrix.start_offset(&sources.with_voice(0.5, 0.0)), 0.5 * bend::MAX_START_OFFSET);
+
+        let mut phases = [0.0, 0.0];
+        matrix.advance(&mut phases, 24000, 48000.0);
+        assert_eq!(phases, [0.0, 0.25]);
+    }
+}
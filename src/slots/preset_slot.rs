@@ -1,7 +1,21 @@
 use std::sync::Arc;
+use songwalker_core::preset::PresetCategory;
 use songwalker_core::preset::instance::PresetInstance;
 
-use super::slot::EnvelopeParams;
+use super::effects::EffectChain;
+use super::keyswitch::layer_zone_range;
+use super::slot::{EnvelopeParams, VoicePool};
+use super::synth::SynthPatch;
+use super::zone_usage::ZoneUsage;
+use crate::preset::graph::{Layer, Layers, PresetGraph, ZoneEnvelopes};
+use crate::perf::garbage::{self, GarbageSender};
+
+/// Maximum number of replaced presets kept alive for releasing voices.
+pub const MAX_RETIRED_PRESETS: usize = 4;
+
+/// Room in the retired list, leaving space for presets the collector
+/// couldn't take yet.
+const RETIRED_CAPACITY: usize = MAX_RETIRED_PRESETS * 2;
 
 /// State specific to a Preset-mode slot.
 pub struct PresetSlotState {
@@ -15,8 +29,38 @@ pub struct PresetSlotState {
     pub mod_wheel: f32,
     /// Expression (CC11).
     pub expression: f32,
+    /// Channel pressure (aftertouch), before smoothing.
+    pub pressure: f32,
+    /// Sustain pedal (CC64) is down.
+    pub sustain: bool,
+    /// The active preset is a drum kit (one-shot notes, exclusive classes).
+    pub is_drum_kit: bool,
+    /// The active preset is an effect: the slot processes the plugin's
+    /// audio input instead of generating sound.
+    pub is_effect: bool,
+    /// Oscillator stack of the active preset's synth nodes.
+    pub synth: Option<SynthPatch>,
+    /// Effect nodes of the active preset, with their running state.
+    effects: EffectChain,
+    /// Children of a layering composite, each played with its own zones,
+    /// gain, pan and key range (empty = the preset plays as one).
+    layers: Layers,
+    /// Envelopes the active preset gives its zones.
+    zone_envelopes: ZoneEnvelopes,
     /// Envelope override.
     envelope: EnvelopeParams,
+    /// Generation of `active_preset`; bumped on every load/unload so voices
+    /// can tell which preset they were started with.
+    generation: u32,
+    /// Replaced presets still referenced by sounding voices: (generation,
+    /// preset). A preset no voice may play any more, waiting for room in
+    /// the collector's channel, has no generation.
+    retired: Vec<(Option<u32>, Arc<PresetInstance>)>,
+    /// Channel to the collector thread that performs the final drop.
+    garbage_tx: Option<GarbageSender>,
+    /// Zones of the active preset played so far (kept across reloads of
+    /// the same preset, for purging).
+    zone_usage: ZoneUsage,
 }
 
 impl Default for PresetSlotState {
@@ -27,7 +71,19 @@ impl Default for PresetSlotState {
             pitch_bend: 0.0,
             mod_wheel: 0.0,
             expression: 1.0,
+            pressure: 0.0,
+            sustain: false,
+            is_drum_kit: false,
+            is_effect: false,
+            synth: None,
+            effects: EffectChain::default(),
+            layers: Layers::default(),
+            zone_envelopes: ZoneEnvelopes::default(),
             envelope: EnvelopeParams::default(),
+            generation: 0,
+            retired: Vec::with_capacity(RETIRED_CAPACITY),
+            garbage_tx: None,
+            zone_usage: ZoneUsage::default(),
         }
     }
 }
@@ -36,11 +92,11 @@ impl PresetSlotState {
     /// Handle a MIDI CC message.
     pub fn handle_cc(&mut self, cc: u8, value: f32) {
         match cc {
-            1 => self.mod_wheel = value,
+            crate::midi::MOD_WHEEL => self.mod_wheel = value,
             7 => { /* volume — handled at slot level */ }
             10 => { /* pan — handled at slot level */ }
-            11 => self.expression = value,
-            64 => { /* sustain pedal — TODO */ }
+            crate::midi::EXPRESSION => self.expression = value,
+            crate::midi::SUSTAIN_PEDAL => self.sustain = value >= 0.5,
             _ => {}
         }
     }
@@ -50,23 +106,211 @@ impl PresetSlotState {
         self.envelope
     }
 
+    /// Envelope the active preset gives a zone (None = `envelope()`).
+    pub fn zone_envelope(&self, zone: usize) -> Option<EnvelopeParams> {
+        self.zone_envelopes.for_zone(zone)
+    }
+
     /// Set envelope override from the UI.
     pub fn set_envelope(&mut self, env: EnvelopeParams) {
         self.envelope = env;
     }
 
-    /// Load a new preset (called from the background thread after fetching).
+    /// Set the channel used to drop retired presets off the audio thread.
+    pub fn set_garbage_sender(&mut self, tx: GarbageSender) {
+        self.garbage_tx = Some(tx);
+    }
+
+    /// Generation of the active preset (stamped on newly started voices).
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Preset a voice of the given generation should render from.
+    pub fn preset_for(&self, generation: u32) -> Option<&Arc<PresetInstance>> {
+        if generation == self.generation {
+            return self.active_preset.as_ref();
+        }
+        self.retired
+            .iter()
+            .find(|(g, _)| *g == Some(generation))
+            .map(|(_, p)| p)
+    }
+
+    /// Number of replaced presets still kept alive for releasing voices.
+    pub fn retired_count(&self) -> usize {
+        self.retired.len()
+    }
+
+    /// Whether the active preset can be replaced now: the retired list has
+    /// room to keep it until the collector takes it.
+    pub fn has_room(&self) -> bool {
+        self.retired.len() < self.retired.capacity()
+    }
+
+    /// Load a new preset (called on the audio thread after a background load).
     ///
     /// The `PresetInstance` must be fully prepared (samples decoded to f32 PCM).
-    pub fn load_preset(&mut self, id: Arc<String>, instance: Arc<PresetInstance>) {
+    /// The previous preset is kept alive for voices already playing it; new
+    /// notes use the new one. Returns false, leaving the previous preset
+    /// loaded, if there is no room to keep it (see `has_room`).
+    pub fn load_preset(&mut self, id: Arc<String>, instance: Arc<PresetInstance>) -> bool {
+        if !self.retire_active() {
+            return false;
+        }
+        if self.preset_id.as_deref() == Some(&*id) {
+            self.zone_usage.clear_missing();
+        } else {
+            self.zone_usage.clear();
+        }
         self.preset_id = Some(id);
+        self.is_drum_kit = crate::preset::drums::is_drum_kit(&instance.descriptor);
+        self.is_effect = matches!(instance.descriptor.category, PresetCategory::Effect);
+        self.set_graph(PresetGraph::default());
         self.active_preset = Some(instance);
+        true
+    }
+
+    /// Swap in a copy of the active preset with unplayed zones purged (see
+    /// `zone_usage`). The zone layout must match; sounding voices carry on.
+    /// Returns false if it doesn't, or there is no room to keep the
+    /// replaced copy (see `has_room`).
+    pub fn replace_zones(&mut self, instance: Arc<PresetInstance>) -> bool {
+        let matches = self.active_preset.as_ref().is_some_and(|active| active.zones.len() == instance.zones.len());
+        if !matches || !self.has_room() {
+            return false;
+        }
+        if let Some(old) = self.active_preset.replace(instance) {
+            self.dispose(old);
+        }
+        self.zone_usage.clear_missing();
+        true
     }
 
-    /// Unload the current preset.
-    pub fn unload_preset(&mut self) {
+    /// Zones of the active preset played so far.
+    pub fn zone_usage(&self) -> &ZoneUsage {
+        &self.zone_usage
+    }
+
+    /// Record a voice starting on `zone`. A purged zone that had not been
+    /// played before is marked missing, so the editor reloads it.
+    pub fn note_zone_played(&mut self, zone: usize, purged: bool) {
+        if purged && !self.zone_usage.is_used(zone) {
+            self.zone_usage.mark_missing(zone);
+        }
+        self.zone_usage.mark_used(zone);
+    }
+
+    /// Synth, effect and layer nodes of the preset just loaded (they come
+    /// from the descriptor, separately from the `PresetInstance`).
+    ///
+    /// Layers whose zones don't line up with the loaded preset's composite
+    /// are dropped, and the preset plays as one; likewise zone envelopes
+    /// when the zone count differs.
+    pub fn set_graph(&mut self, graph: PresetGraph) {
+        self.synth = graph.synth.filter(|patch| !patch.is_empty());
+        self.effects = graph.effects;
+        self.effects.reset();
+        self.layers = graph.layers;
+        let matches = self.active_preset.as_ref().is_some_and(|preset| {
+            self.layers
+                .as_slice()
+                .iter()
+                .enumerate()
+                .all(|(i, layer)| layer_zone_range(&preset.descriptor.graph, i) == Some(layer.zones()))
+        });
+        if !matches {
+            self.layers.clear();
+        }
+        self.zone_envelopes = graph.envelopes;
+        let zone_count = self.active_preset.as_ref().map_or(0, |preset| preset.zones.len());
+        if self.zone_envelopes.zone_total() != zone_count {
+            self.zone_envelopes.clear();
+        }
+    }
+
+    /// Children of the active preset played as separate layers.
+    pub fn layers(&self) -> &[Layer] {
+        self.layers.as_slice()
+    }
+
+    pub fn effects_mut(&mut self) -> &mut EffectChain {
+        &mut self.effects
+    }
+
+    /// Unload the current preset. Sounding voices finish on the old one.
+    /// Returns false, leaving it loaded, if there is no room to keep it.
+    pub fn unload_preset(&mut self) -> bool {
+        if !self.retire_active() {
+            return false;
+        }
+        self.zone_usage.clear();
         self.preset_id = None;
+        self.is_drum_kit = false;
+        self.is_effect = false;
+        self.set_graph(PresetGraph::default());
         self.active_preset = None;
+        true
+    }
+
+    /// Move the active preset to the retired list and start a new
+    /// generation. Returns false if the list has no room for it.
+    fn retire_active(&mut self) -> bool {
+        if self.active_preset.is_some() && !self.has_room() {
+            return false;
+        }
+        if let Some(old) = self.active_preset.take() {
+            let playable = self.retired.iter().filter(|(g, _)| g.is_some()).count();
+            if playable >= MAX_RETIRED_PRESETS {
+                // Too many swaps in flight: voices on the oldest preset stop
+                // rendering (see `Slot::render`), and it is released now.
+                if let Some(i) = self.retired.iter().position(|(g, _)| g.is_some()) {
+                    let (_, oldest) = self.retired.remove(i);
+                    if let Some(tx) = self.garbage_tx.as_ref() {
+                        if let Err(oldest) = garbage::try_retire_preset(tx, oldest) {
+                            // Collector busy: kept in its place, unplayable
+                            self.retired.insert(i, (None, oldest));
+                        }
+                    }
+                }
+            }
+            self.retired.push((Some(self.generation), old));
+        }
+        self.generation = self.generation.wrapping_add(1);
+        true
+    }
+
+    /// Hand retired presets that no voice references to the collector.
+    pub fn collect_garbage(&mut self, voices: &VoicePool) {
+        let mut i = 0;
+        while i < self.retired.len() {
+            let generation = self.retired[i].0;
+            if generation.is_some_and(|g| voices.any_active_with_generation(g)) {
+                i += 1;
+                continue;
+            }
+            let Some(tx) = self.garbage_tx.as_ref() else {
+                // No collector (tests, offline use): drop in place
+                self.retired.remove(i);
+                continue;
+            };
+            let (_, preset) = self.retired.remove(i);
+            if let Err(preset) = garbage::try_retire_preset(tx, preset) {
+                // Collector busy: keep it and retry next block
+                self.retired.insert(i, (generation, preset));
+                return;
+            }
+        }
+    }
+
+    /// Drop a preset via the collector if available. If the collector is
+    /// busy the preset is kept, unplayable, and `collect_garbage` retries;
+    /// callers check `has_room` first.
+    fn dispose(&mut self, preset: Arc<PresetInstance>) {
+        let Some(tx) = self.garbage_tx.as_ref() else { return };
+        if let Err(preset) = garbage::try_retire_preset(tx, preset) {
+            self.retired.push((None, preset));
+        }
     }
 }
 
@@ -138,4 +382,5 @@ mod tests {
         assert!(state.preset_id.is_none());
         assert!(state.active_preset.is_none());
     }
+
 }
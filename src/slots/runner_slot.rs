@@ -1,3 +1,6 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
 use songwalker_core::compiler::{EventKind, EventList};
 
 use super::slot::{EnvelopeParams, VoicePool};
@@ -9,34 +12,300 @@ const DEFAULT_ROOT_NOTE: u8 = 60;
 /// Maximum simultaneous runner instances (one per held MIDI note).
 const MAX_RUNNER_INSTANCES: usize = 16;
 
+/// Maximum number of routed track sections per runner slot.
+pub const MAX_ROUTES: usize = 8;
+
+/// Capacity of the routed-note output queue (per process block).
+const ROUTED_QUEUE_CAPACITY: usize = 256;
+
+/// Where a routed track section sends its notes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlotTarget {
+    /// Slot index (0-based; the source uses the 1-based number shown in the rack).
+    Index(usize),
+    /// Slot display name.
+    Name(String),
+}
+
+/// A track section of the source whose notes drive another slot.
+///
+/// Declared in source with a directive line such as `track drums -> slot 3`.
+/// Everything after the directive (until the next directive) belongs to it.
+pub struct Route {
+    /// Track name from the directive.
+    pub track_name: String,
+    /// Slot that receives the notes.
+    pub target: SlotTarget,
+    /// Compiled events for this section.
+    pub event_list: EventList,
+}
+
+/// A note event generated by a routed track, waiting to be delivered
+/// to its target slot by the `SlotManager`.
+#[derive(Debug, Clone, Copy)]
+pub struct RoutedNote {
+    /// Index into `RunnerSlotState::routes`.
+    pub route: usize,
+    /// MIDI note number.
+    pub note: u8,
+    /// Velocity (0.0–1.0). Ignored for note-offs.
+    pub velocity: f32,
+    /// true = Note On, false = Note Off.
+    pub on: bool,
+}
+
+/// Humanization applied when the runner triggers notes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Humanize {
+    /// Random timing offset range (± milliseconds).
+    pub timing_ms: f32,
+    /// Random velocity range (± fraction of the note velocity, 0.0–1.0).
+    pub velocity: f32,
+    /// Swing percentage for off-beat 8ths (50 = straight, 66 = triplet feel, max 75).
+    pub swing: f32,
+}
+
+impl Default for Humanize {
+    fn default() -> Self {
+        Self {
+            timing_ms: 0.0,
+            velocity: 0.0,
+            swing: 50.0,
+        }
+    }
+}
+
+impl Humanize {
+    /// Whether any option changes note timing or velocity.
+    pub fn is_active(&self) -> bool {
+        self.timing_ms > 0.0 || self.velocity > 0.0 || self.swing > 50.0
+    }
+}
+
+/// Grid a triggered runner waits for before it starts, like clip launching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LaunchQuantize {
+    /// Start as soon as the key is pressed.
+    #[default]
+    Off,
+    Beat,
+    Bar,
+    TwoBars,
+}
+
+impl LaunchQuantize {
+    pub const ALL: [LaunchQuantize; 4] = [
+        LaunchQuantize::Off,
+        LaunchQuantize::Beat,
+        LaunchQuantize::Bar,
+        LaunchQuantize::TwoBars,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LaunchQuantize::Off => "Off",
+            LaunchQuantize::Beat => "Beat",
+            LaunchQuantize::Bar => "Bar",
+            LaunchQuantize::TwoBars => "2 Bars",
+        }
+    }
+
+    /// Grid length in quarter-note beats for the host time signature
+    /// (None = launch immediately).
+    pub fn grid_beats(self, transport: &TransportState) -> Option<f64> {
+        if transport.time_sig_denominator <= 0 {
+            return None;
+        }
+        let beat = 4.0 / transport.time_sig_denominator as f64;
+        let bar = transport.time_sig_numerator.max(1) as f64 * beat;
+        match self {
+            LaunchQuantize::Off => None,
+            LaunchQuantize::Beat => Some(beat),
+            LaunchQuantize::Bar => Some(bar),
+            LaunchQuantize::TwoBars => Some(bar * 2.0),
+        }
+    }
+}
+
+/// A note the runner played locally, queued for the host as MIDI output.
+#[derive(Debug, Clone, Copy)]
+pub struct MidiOutNote {
+    /// Sample offset within the current block.
+    pub timing: u32,
+    /// MIDI channel (0–15).
+    pub channel: u8,
+    /// MIDI note number.
+    pub note: u8,
+    /// Velocity (0.0–1.0). Ignored for note-offs.
+    pub velocity: f32,
+    /// true = Note On, false = Note Off.
+    pub on: bool,
+}
+
+/// A compiled `.sw` program: the local event list plus routed sections.
+///
+/// Compiled off the audio thread and handed to the slot behind an `Arc`,
+/// so a live edit can be swapped in without copying or allocating.
+#[derive(Default)]
+pub struct RunnerProgram {
+    /// The source this program was compiled from.
+    pub source_code: String,
+    /// The compiled local event list (None if empty or compilation failed).
+    pub event_list: Option<EventList>,
+    /// Track sections routed to other slots.
+    pub routes: Vec<Route>,
+    /// Compilation error message (if any).
+    pub compile_error: Option<String>,
+}
+
+impl std::fmt::Debug for RunnerProgram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RunnerProgram")
+            .field("source_len", &self.source_code.len())
+            .field("has_event_list", &self.event_list.is_some())
+            .field("routes", &self.routes.len())
+            .field("compile_error", &self.compile_error)
+            .finish()
+    }
+}
+
+impl RunnerProgram {
+    /// Compile `.sw` source code into a program.
+    ///
+    /// `track <name> -> slot <n|name>` directive lines split the source into
+    /// routed sections, each compiled separately. Source before the first
+    /// directive plays on this slot.
+    pub fn compile(source: &str) -> Self {
+        let mut program = Self {
+            source_code: source.to_string(),
+            ..Default::default()
+        };
+
+        let (local, sections) = split_routes(source);
+
+        program.event_list = if local.trim().is_empty() && !sections.is_empty() {
+            None
+        } else {
+            match compile_source(&local) {
+                Ok(event_list) => Some(event_list),
+                Err(e) => {
+                    program.compile_error = Some(e);
+                    None
+                }
+            }
+        };
+
+        for section in sections.into_iter().take(MAX_ROUTES) {
+            match compile_source(&section.body) {
+                Ok(event_list) => program.routes.push(Route {
+                    track_name: section.track_name,
+                    target: section.target,
+                    event_list,
+                }),
+                Err(e) => {
+                    if program.compile_error.is_none() {
+                        program.compile_error = Some(format!("track {}: {}", section.track_name, e));
+                    }
+                }
+            }
+        }
+
+        program
+    }
+
+    /// Whether the program has anything to play (local or routed).
+    pub fn has_program(&self) -> bool {
+        self.event_list.is_some() || !self.routes.is_empty()
+    }
+}
+
+/// A scheduled note-off for a routed note (fires after its gate).
+#[derive(Debug, Clone, Copy)]
+struct PendingOff {
+    route: usize,
+    note: u8,
+    beats_remaining: f64,
+}
+
+/// A scheduled note-off for a MIDI output note.
+#[derive(Debug, Clone, Copy)]
+struct PendingMidiOff {
+    channel: u8,
+    note: u8,
+    beats_remaining: f64,
+}
+
+/// A triggered instance waiting for its launch boundary.
+#[derive(Debug, Clone, Copy)]
+struct PendingLaunch {
+    note: u8,
+    velocity: f32,
+    /// Host position (beats) the instance starts at.
+    launch_beat: f64,
+}
+
 /// State specific to a Runner-mode slot.
 pub struct RunnerSlotState {
-    /// The compiled `.sw` event list (None if no `.sw` is loaded or compilation failed).
-    pub event_list: Option<EventList>,
-    /// The `.sw` source code currently loaded in the editor.
-    pub source_code: String,
+    /// The program currently playing.
+    program: Arc<RunnerProgram>,
+    /// A recompiled program waiting for the next loop/bar boundary.
+    pending_program: Option<Arc<RunnerProgram>>,
+    /// Set once a boundary is reached; the swap happens at the start of
+    /// the next block so routed note-offs resolve against the old routes.
+    swap_due: bool,
     /// The root note for transposition (default C4).
     pub root_note: u8,
     /// Currently active runner instances.
     instances: Vec<RunnerInstance>,
-    /// Compilation error message (if any).
-    pub compile_error: Option<String>,
     /// Pitch bend from MIDI input.
     pub pitch_bend: f32,
+    /// Playback rate multiplier from the slot's tuning.
+    pub tune_ratio: f64,
     /// Envelope parameters for runner-triggered voices.
     envelope: EnvelopeParams,
+    /// Notes generated for other slots during the current block.
+    pub routed_out: Vec<RoutedNote>,
+    /// Routed notes still sounding, released once their gate elapses.
+    pending_offs: Vec<PendingOff>,
+    /// Ordinal of the most recently fired local note (None when idle).
+    play_event: Option<usize>,
+    /// MIDI output channel for locally played notes (None = MIDI out off).
+    midi_out_channel: Option<u8>,
+    /// Timing/velocity humanization for triggered notes.
+    humanize: Humanize,
+    /// xorshift state for humanization.
+    rng: u32,
+    /// Notes queued for the host's MIDI output, drained after each block.
+    pub midi_out: Vec<MidiOutNote>,
+    /// MIDI output notes still held, released once their gate elapses.
+    midi_offs: Vec<PendingMidiOff>,
+    /// Grid new instances wait for while the host transport plays.
+    launch_quantize: LaunchQuantize,
+    /// Instances triggered but not yet launched.
+    pending_launches: Vec<PendingLaunch>,
 }
 
 impl Default for RunnerSlotState {
     fn default() -> Self {
         Self {
-            event_list: None,
-            source_code: String::new(),
+            program: Arc::new(RunnerProgram::default()),
+            pending_program: None,
+            swap_due: false,
             root_note: DEFAULT_ROOT_NOTE,
             instances: Vec::with_capacity(MAX_RUNNER_INSTANCES),
-            compile_error: None,
             pitch_bend: 0.0,
+            tune_ratio: 1.0,
             envelope: EnvelopeParams::default(),
+            routed_out: Vec::with_capacity(ROUTED_QUEUE_CAPACITY),
+            pending_offs: Vec::with_capacity(ROUTED_QUEUE_CAPACITY),
+            play_event: None,
+            midi_out_channel: None,
+            humanize: Humanize::default(),
+            rng: 0x2545_F491,
+            midi_out: Vec::with_capacity(ROUTED_QUEUE_CAPACITY),
+            midi_offs: Vec::with_capacity(ROUTED_QUEUE_CAPACITY),
+            launch_quantize: LaunchQuantize::Off,
+            pending_launches: Vec::with_capacity(MAX_RUNNER_INSTANCES),
         }
     }
 }
@@ -44,6 +313,118 @@ impl Default for RunnerSlotState {
 impl RunnerSlotState {
     pub fn reset(&mut self) {
         self.instances.clear();
+        self.pending_launches.clear();
+        self.play_event = None;
+        self.flush_pending_offs();
+        self.flush_midi_offs();
+    }
+
+    /// Send note-offs for every MIDI output note still held. Those that
+    /// don't fit in the queue stay pending, due at once, and go out with
+    /// the next block.
+    fn flush_midi_offs(&mut self) {
+        let room = ROUTED_QUEUE_CAPACITY.saturating_sub(self.midi_out.len());
+        let sent = self.midi_offs.len().min(room);
+        for off in self.midi_offs.drain(..sent) {
+            self.midi_out.push(MidiOutNote {
+                timing: 0,
+                channel: off.channel,
+                note: off.note,
+                velocity: 0.0,
+                on: false,
+            });
+        }
+        for off in &mut self.midi_offs {
+            off.beats_remaining = 0.0;
+        }
+    }
+
+    pub fn humanize(&self) -> Humanize {
+        self.humanize
+    }
+
+    pub fn set_humanize(&mut self, humanize: Humanize) {
+        self.humanize = Humanize {
+            timing_ms: humanize.timing_ms.clamp(0.0, 100.0),
+            velocity: humanize.velocity.clamp(0.0, 1.0),
+            swing: humanize.swing.clamp(50.0, 75.0),
+        };
+    }
+
+    pub fn launch_quantize(&self) -> LaunchQuantize {
+        self.launch_quantize
+    }
+
+    /// Set the launch grid. Instances already waiting launch at the next
+    /// block if quantization is turned off.
+    pub fn set_launch_quantize(&mut self, quantize: LaunchQuantize) {
+        self.launch_quantize = quantize;
+    }
+
+    /// Beats until the next waiting instance launches (None if none waits).
+    pub fn launch_countdown(&self, transport: &TransportState) -> Option<f64> {
+        self.pending_launches
+            .iter()
+            .map(|l| (l.launch_beat - transport.position_beats).max(0.0))
+            .min_by(f64::total_cmp)
+    }
+
+    /// MIDI output channel for locally played notes (None = off).
+    pub fn midi_out_channel(&self) -> Option<u8> {
+        self.midi_out_channel
+    }
+
+    /// Enable MIDI output on a channel (0–15), or disable it with None.
+    ///
+    /// Held notes are released on the old channel first so nothing hangs
+    /// on the receiving instrument.
+    pub fn set_midi_out_channel(&mut self, channel: Option<u8>) {
+        if channel != self.midi_out_channel {
+            self.flush_midi_offs();
+            self.midi_out_channel = channel.map(|c| c.min(15));
+        }
+    }
+
+    /// Release anything still sounding on target slots. Note-offs that
+    /// don't fit in the queue stay pending, due at once, and go out with
+    /// the next block.
+    fn flush_pending_offs(&mut self) {
+        let room = ROUTED_QUEUE_CAPACITY.saturating_sub(self.routed_out.len());
+        let sent = self.pending_offs.len().min(room);
+        for off in self.pending_offs.drain(..sent) {
+            self.routed_out.push(RoutedNote {
+                route: off.route,
+                note: off.note,
+                velocity: 0.0,
+                on: false,
+            });
+        }
+        for off in &mut self.pending_offs {
+            off.beats_remaining = 0.0;
+        }
+    }
+
+    /// Ordinal of the most recently fired note in the local event list
+    /// (0 = first note of the loop).
+    ///
+    /// Published to the editor to highlight the current play position.
+    pub fn play_event(&self) -> Option<usize> {
+        self.play_event
+    }
+
+    /// Whether any program (local or routed) is loaded.
+    pub fn has_program(&self) -> bool {
+        self.program.has_program()
+    }
+
+    /// The program currently playing.
+    pub fn program(&self) -> &RunnerProgram {
+        &self.program
+    }
+
+    /// Whether a recompiled program is waiting to be swapped in.
+    pub fn has_pending_program(&self) -> bool {
+        self.pending_program.is_some()
     }
 
     pub fn envelope(&self) -> EnvelopeParams {
@@ -54,35 +435,109 @@ impl RunnerSlotState {
         self.envelope = env;
     }
 
-    /// Compile `.sw` source code into an event list.
+    /// Compile `.sw` source code and load it immediately.
     pub fn compile(&mut self, source: &str) {
-        self.source_code = source.to_string();
-        match songwalker_core::parse(source) {
-            Ok(program) => match songwalker_core::compiler::compile(&program) {
-                Ok(event_list) => {
-                    self.event_list = Some(event_list);
-                    self.compile_error = None;
-                }
-                Err(e) => {
-                    self.compile_error = Some(e);
-                    self.event_list = None;
-                }
-            },
-            Err(e) => {
-                self.compile_error = Some(e.to_string());
-                self.event_list = None;
+        self.pending_program = Some(Arc::new(RunnerProgram::compile(source)));
+        self.swap_program();
+    }
+
+    /// Queue a program compiled off the audio thread (live re-compile).
+    ///
+    /// While instances are playing, the swap waits for the next loop end or
+    /// host bar line so the pattern doesn't jump mid-phrase. An idle slot,
+    /// or an empty program, swaps immediately. The previous program is
+    /// dropped here, so the sender should keep its own `Arc` alive to keep
+    /// deallocation off the audio thread.
+    pub fn queue_program(&mut self, program: Arc<RunnerProgram>) {
+        let immediate = self.instances.is_empty() || !program.has_program();
+        self.pending_program = Some(program);
+        if immediate {
+            self.flush_pending_offs();
+            // Routed note-offs left over must resolve against the old routes
+            if self.pending_offs.is_empty() {
+                self.swap_program();
+            } else {
+                self.swap_due = true;
             }
         }
     }
 
+    /// Swap in the pending program and restart instances from the top.
+    fn swap_program(&mut self) {
+        self.swap_due = false;
+        let Some(program) = self.pending_program.take() else {
+            return;
+        };
+        self.program = program;
+        self.flush_midi_offs();
+        for instance in &mut self.instances {
+            instance.cursor = 0;
+            instance.note_index = 0;
+            instance.position_beats = 0.0;
+            instance.pending_offset = None;
+            instance.route_cursors = [0; MAX_ROUTES];
+            instance.route_positions = [0.0; MAX_ROUTES];
+            instance.route_offsets = [None; MAX_ROUTES];
+        }
+    }
+
     /// Spawn a new runner instance triggered by a MIDI Note On.
     ///
     /// The instance is transposed by `(note - root_note)` semitones.
-    /// Host transport values (BPM, time sig) are injected.
+    /// Host transport values (BPM, time sig) are injected. With launch
+    /// quantization on and the host playing, the instance waits for the
+    /// next grid line instead of starting right away.
     pub fn spawn_instance(&mut self, note: u8, velocity: f32, transport: &TransportState) {
-        if self.event_list.is_none() {
+        if !self.has_program() {
             return;
         }
+        if let Some(grid) = self.launch_grid(transport) {
+            let launch_beat = (transport.position_beats / grid).ceil() * grid;
+            if launch_beat > transport.position_beats {
+                if self.instances.len() + self.pending_launches.len() < MAX_RUNNER_INSTANCES {
+                    self.pending_launches.push(PendingLaunch {
+                        note,
+                        velocity,
+                        launch_beat,
+                    });
+                }
+                return;
+            }
+        }
+        self.start_instance(note, velocity, transport.bpm, 0.0);
+    }
+
+    /// Launch grid in beats, if launches are quantized right now.
+    fn launch_grid(&self, transport: &TransportState) -> Option<f64> {
+        if !transport.playing {
+            return None;
+        }
+        self.launch_quantize.grid_beats(transport)
+    }
+
+    /// Start waiting instances whose launch beat falls in the next
+    /// `beat_advance` beats. Ones left stranded by a stopped transport, a
+    /// loop jump or quantization being turned off start immediately.
+    fn launch_due(&mut self, transport: &TransportState, beat_advance: f64) {
+        let grid = self.launch_grid(transport);
+        let mut k = 0;
+        while k < self.pending_launches.len() {
+            let launch = self.pending_launches[k];
+            let wait = launch.launch_beat - transport.position_beats;
+            let stranded = grid.is_none_or(|g| wait > g);
+            if stranded || wait < beat_advance {
+                self.pending_launches.swap_remove(k);
+                // A negative start position delays the first events to the
+                // launch beat inside this block
+                let start = if stranded { 0.0 } else { -wait.max(0.0) };
+                self.start_instance(launch.note, launch.velocity, transport.bpm, start);
+            } else {
+                k += 1;
+            }
+        }
+    }
+
+    fn start_instance(&mut self, note: u8, velocity: f32, bpm: f64, start_beat: f64) {
         // Don't exceed max instances
         if self.instances.len() >= MAX_RUNNER_INSTANCES {
             return;
@@ -95,17 +550,25 @@ impl RunnerSlotState {
             transpose,
             velocity,
             cursor: 0,
-            position_beats: 0.0,
-            _bpm: transport.bpm,
+            note_index: 0,
+            pending_offset: None,
+            position_beats: start_beat,
+            _bpm: bpm,
             active: true,
             releasing: false,
+            route_cursors: [0; MAX_ROUTES],
+            route_positions: [start_beat; MAX_ROUTES],
+            route_offsets: [None; MAX_ROUTES],
         };
 
         self.instances.push(instance);
     }
 
     /// Release the runner instance triggered by the given MIDI note.
+    ///
+    /// An instance still waiting to launch is cancelled.
     pub fn release_instance(&mut self, note: u8) {
+        self.pending_launches.retain(|l| l.note != note);
         for instance in &mut self.instances {
             if instance.trigger_note == note && instance.active && !instance.releasing {
                 instance.releasing = true;
@@ -124,15 +587,53 @@ impl RunnerSlotState {
         sample_rate: f32,
         transport: &TransportState,
     ) {
-        let event_list = match &self.event_list {
-            Some(el) => el,
-            None => return,
-        };
+        // Routed note-offs still waiting hold the swap for another block
+        if self.swap_due && self.pending_offs.is_empty() {
+            self.swap_program();
+        }
 
-        let events = &event_list.events;
         let beats_per_second = transport.bpm / 60.0;
         let beats_per_sample = beats_per_second / sample_rate as f64;
         let beat_advance = beats_per_sample * num_samples as f64;
+        self.launch_due(transport, beat_advance);
+
+        if self.pending_program.is_some() && crosses_bar(transport, beat_advance) {
+            self.swap_due = true;
+        }
+
+        // Release MIDI output notes whose gate has elapsed; with the queue
+        // full they wait for the next block
+        let mut k = 0;
+        while k < self.midi_offs.len() {
+            let off = &mut self.midi_offs[k];
+            off.beats_remaining -= beat_advance;
+            if off.beats_remaining <= 0.0 && self.midi_out.len() < ROUTED_QUEUE_CAPACITY {
+                let offset = (off.beats_remaining + beat_advance) / beats_per_sample;
+                self.midi_out.push(MidiOutNote {
+                    timing: block_offset(offset, num_samples),
+                    channel: off.channel,
+                    note: off.note,
+                    velocity: 0.0,
+                    on: false,
+                });
+                self.midi_offs.swap_remove(k);
+            } else {
+                k += 1;
+            }
+        }
+
+        let event_list = match &self.program.event_list {
+            Some(el) => el,
+            None => {
+                // Routed-only source: nothing plays locally, just retire
+                // instances whose trigger note was released.
+                self.instances.retain(|inst| inst.active && !inst.releasing);
+                return;
+            }
+        };
+
+        let events = &event_list.events;
+        let humanize = self.humanize;
 
         // Process each active instance
         let mut i = 0;
@@ -154,38 +655,73 @@ impl RunnerSlotState {
             let start_beat = instance.position_beats;
             let end_beat = start_beat + beat_advance;
 
-            // Fire events in the [start_beat, end_beat) window
+            // Fire events in the [start_beat, end_beat) window (humanized
+            // events may land late; they fire at the start of the block)
             while instance.cursor < events.len() {
                 let event = &events[instance.cursor];
-                if event.time >= end_beat {
+                let offset = if humanize.is_active() && matches!(event.kind, EventKind::Note { .. }) {
+                    *instance.pending_offset.get_or_insert_with(|| {
+                        humanize_offset(&humanize, &mut self.rng, event.time, transport.bpm)
+                    })
+                } else {
+                    0.0
+                };
+                let time = event.time + offset;
+                if time >= end_beat {
                     break;
                 }
-                if event.time >= start_beat {
-                    match &event.kind {
-                        EventKind::Note {
-                            pitch,
-                            velocity: note_vel,
-                            gate: _,
-                            ..
-                        } => {
-                            // Parse pitch string to MIDI note, apply transpose
-                            if let Some(base_pitch) = parse_pitch(pitch) {
-                                let transposed_pitch = (base_pitch as i32 + instance.transpose)
-                                    .clamp(0, 127) as u8;
-                                let vel = (*note_vel as f32) * instance.velocity;
-
-                                if let Some(voice) = voice_pool.allocate(transposed_pitch, vel) {
-                                    let freq = crate::midi::midi_to_freq(transposed_pitch);
-                                    voice.phase_inc = freq as f64 / sample_rate as f64;
-                                    voice.transpose = instance.transpose;
+                instance.pending_offset = None;
+                match &event.kind {
+                    EventKind::Note {
+                        pitch,
+                        velocity: note_vel,
+                        gate,
+                        ..
+                    } => {
+                        // Parse pitch string to MIDI note, apply transpose
+                        if let Some(base_pitch) = parse_pitch(pitch) {
+                            let transposed_pitch = (base_pitch as i32 + instance.transpose)
+                                .clamp(0, 127) as u8;
+                            let vel = humanize_velocity(
+                                &humanize,
+                                &mut self.rng,
+                                (*note_vel as f32) * instance.velocity,
+                            );
+
+                            self.play_event = Some(instance.note_index);
+                            if let Some(channel) = self.midi_out_channel {
+                                if self.midi_out.len() < ROUTED_QUEUE_CAPACITY
+                                    && self.midi_offs.len() < ROUTED_QUEUE_CAPACITY
+                                {
+                                    let offset = (time - start_beat) / beats_per_sample;
+                                    self.midi_out.push(MidiOutNote {
+                                        timing: block_offset(offset, num_samples),
+                                        channel,
+                                        note: transposed_pitch,
+                                        velocity: vel.clamp(0.0, 1.0),
+                                        on: true,
+                                    });
+                                    self.midi_offs.push(PendingMidiOff {
+                                        channel,
+                                        note: transposed_pitch,
+                                        beats_remaining: (*gate as f64).max(0.0) + (time - start_beat).max(0.0),
+                                    });
                                 }
                             }
+                            if let Some(voice) = voice_pool.allocate(transposed_pitch, vel) {
+                                let freq = crate::midi::midi_to_freq(transposed_pitch);
+                                voice.phase_inc = freq as f64 * self.tune_ratio / sample_rate as f64;
+                                voice.transpose = instance.transpose;
+                            }
                         }
-                        _ => {
-                            // TrackStart, SetProperty, PresetRef handled at compile time
-                        }
+                    }
+                    _ => {
+                        // TrackStart, SetProperty, PresetRef handled at compile time
                     }
                 }
+                if matches!(event.kind, EventKind::Note { .. }) {
+                    instance.note_index += 1;
+                }
                 instance.cursor += 1;
             }
 
@@ -195,13 +731,134 @@ impl RunnerSlotState {
             if instance.cursor >= events.len()
                 && instance.position_beats >= event_list.total_beats
             {
-                // Restart from beginning (loop the pattern)
+                // Restart from beginning (loop the pattern), picking up any
+                // recompiled program at this boundary
+                if self.pending_program.is_some() {
+                    self.swap_due = true;
+                }
                 instance.cursor = 0;
+                instance.note_index = 0;
                 instance.position_beats = 0.0;
             }
 
             i += 1;
         }
+
+        if self.instances.is_empty() {
+            self.play_event = None;
+        }
+    }
+}
+
+impl RunnerSlotState {
+    /// Advance routed track sections, queueing notes for other slots
+    /// into `routed_out`.
+    ///
+    /// Called by the `SlotManager` before slots render so targets hear the
+    /// notes in the same block. Does not allocate: the queue is pre-sized
+    /// and note-ons beyond its capacity are dropped. Note-offs that don't
+    /// fit wait for the next block, so no target note is left hanging.
+    pub fn advance_routes(&mut self, num_samples: usize, sample_rate: f32, transport: &TransportState) {
+        // Launch here too so routed sections start in the same block
+        if !self.pending_launches.is_empty() {
+            let beat_advance = transport.bpm / 60.0 / sample_rate as f64 * num_samples as f64;
+            self.launch_due(transport, beat_advance);
+        }
+        if self.swap_due {
+            // Release routed notes while the old routes still resolve;
+            // `advance()` swaps the program right after dispatch.
+            self.flush_pending_offs();
+            return;
+        }
+        if self.program.routes.is_empty() && self.pending_offs.is_empty() {
+            return;
+        }
+
+        let beats_per_sample = transport.bpm / 60.0 / sample_rate as f64;
+        let beat_advance = beats_per_sample * num_samples as f64;
+
+        // Release routed notes whose gate has elapsed
+        let mut k = 0;
+        while k < self.pending_offs.len() {
+            let off = &mut self.pending_offs[k];
+            off.beats_remaining -= beat_advance;
+            if off.beats_remaining <= 0.0 && self.routed_out.len() < ROUTED_QUEUE_CAPACITY {
+                self.routed_out.push(RoutedNote {
+                    route: off.route,
+                    note: off.note,
+                    velocity: 0.0,
+                    on: false,
+                });
+                self.pending_offs.swap_remove(k);
+            } else {
+                k += 1;
+            }
+        }
+
+        let humanize = self.humanize;
+        for instance in &mut self.instances {
+            if !instance.active || instance.releasing {
+                continue;
+            }
+            for (r, route) in self.program.routes.iter().enumerate() {
+                let events = &route.event_list.events;
+                let start_beat = instance.route_positions[r];
+                let end_beat = start_beat + beat_advance;
+                let cursor = &mut instance.route_cursors[r];
+
+                while *cursor < events.len() {
+                    let event = &events[*cursor];
+                    let offset = if humanize.is_active() && matches!(event.kind, EventKind::Note { .. }) {
+                        *instance.route_offsets[r].get_or_insert_with(|| {
+                            humanize_offset(&humanize, &mut self.rng, event.time, transport.bpm)
+                        })
+                    } else {
+                        0.0
+                    };
+                    let time = event.time + offset;
+                    if time >= end_beat {
+                        break;
+                    }
+                    instance.route_offsets[r] = None;
+                    if let EventKind::Note { pitch, velocity: note_vel, gate, .. } = &event.kind {
+                        if let Some(base_pitch) = parse_pitch(pitch) {
+                            let note = (base_pitch as i32 + instance.transpose).clamp(0, 127) as u8;
+                            if self.routed_out.len() < ROUTED_QUEUE_CAPACITY
+                                && self.pending_offs.len() < ROUTED_QUEUE_CAPACITY
+                            {
+                                self.routed_out.push(RoutedNote {
+                                    route: r,
+                                    note,
+                                    velocity: humanize_velocity(
+                                        &humanize,
+                                        &mut self.rng,
+                                        *note_vel as f32 * instance.velocity,
+                                    ),
+                                    on: true,
+                                });
+                                self.pending_offs.push(PendingOff {
+                                    route: r,
+                                    note,
+                                    beats_remaining: (*gate as f64).max(0.0) + (time - start_beat).max(0.0),
+                                });
+                            }
+                        }
+                    }
+                    *cursor += 1;
+                }
+
+                instance.route_positions[r] = end_beat;
+
+                // Loop each routed section on its own length
+                if *cursor >= events.len() && end_beat >= route.event_list.total_beats {
+                    if self.pending_program.is_some() {
+                        self.swap_due = true;
+                    }
+                    *cursor = 0;
+                    instance.route_positions[r] = 0.0;
+                }
+            }
+        }
     }
 }
 
@@ -215,6 +872,10 @@ struct RunnerInstance {
     velocity: f32,
     /// Current position in the event list.
     cursor: usize,
+    /// Number of note events passed in the current loop.
+    note_index: usize,
+    /// Humanize offset (beats) rolled for the event at `cursor`.
+    pending_offset: Option<f64>,
     /// Current position in beats.
     position_beats: f64,
     /// BPM at the time of instance creation.
@@ -223,6 +884,138 @@ struct RunnerInstance {
     active: bool,
     /// Whether this instance is releasing (Note Off received).
     releasing: bool,
+    /// Per-route position in the routed event lists.
+    route_cursors: [usize; MAX_ROUTES],
+    /// Per-route position in beats.
+    route_positions: [f64; MAX_ROUTES],
+    /// Per-route humanize offset rolled for the event at the route cursor.
+    route_offsets: [Option<f64>; MAX_ROUTES],
+}
+
+/// Next xorshift value mapped to -1.0..1.0.
+fn next_random(rng: &mut u32) -> f32 {
+    *rng ^= *rng << 13;
+    *rng ^= *rng >> 17;
+    *rng ^= *rng << 5;
+    (*rng as f32 / u32::MAX as f32) * 2.0 - 1.0
+}
+
+/// Beats to delay an off-beat 8th note for the given swing percentage.
+fn swing_offset(time: f64, swing: f32) -> f64 {
+    if swing <= 50.0 {
+        return 0.0;
+    }
+    let eighths = time * 2.0;
+    let nearest = eighths.round();
+    let is_offbeat = (eighths - nearest).abs() < 1e-6 && nearest as i64 % 2 == 1;
+    if is_offbeat {
+        swing as f64 / 100.0 - 0.5
+    } else {
+        0.0
+    }
+}
+
+/// Total humanize offset in beats (swing plus random timing jitter).
+fn humanize_offset(humanize: &Humanize, rng: &mut u32, time: f64, bpm: f64) -> f64 {
+    let mut offset = swing_offset(time, humanize.swing);
+    if humanize.timing_ms > 0.0 {
+        let jitter_beats = humanize.timing_ms as f64 / 1000.0 * bpm / 60.0;
+        offset += next_random(rng) as f64 * jitter_beats;
+    }
+    offset
+}
+
+/// Apply random velocity variation, keeping the result in 0.0–1.0.
+fn humanize_velocity(humanize: &Humanize, rng: &mut u32, velocity: f32) -> f32 {
+    if humanize.velocity > 0.0 {
+        (velocity * (1.0 + next_random(rng) * humanize.velocity)).clamp(0.0, 1.0)
+    } else {
+        velocity.clamp(0.0, 1.0)
+    }
+}
+
+/// Convert a sample offset into a timing value inside the current block.
+fn block_offset(samples: f64, num_samples: usize) -> u32 {
+    (samples.max(0.0) as usize).min(num_samples.saturating_sub(1)) as u32
+}
+
+/// Whether the host transport crosses a bar line within the next `beat_advance` beats.
+fn crosses_bar(transport: &TransportState, beat_advance: f64) -> bool {
+    if !transport.playing || transport.time_sig_denominator <= 0 {
+        return false;
+    }
+    let bar_beats = transport.time_sig_numerator.max(1) as f64 * 4.0
+        / transport.time_sig_denominator as f64;
+    let start = transport.position_beats / bar_beats;
+    let end = (transport.position_beats + beat_advance) / bar_beats;
+    end.floor() > start.floor() || (start.fract() == 0.0 && beat_advance > 0.0)
+}
+
+/// Parse `.sw` source and compile it to an event list.
+fn compile_source(source: &str) -> Result<EventList, String> {
+    let program = songwalker_core::parse(source).map_err(|e| e.to_string())?;
+    songwalker_core::compiler::compile(&program)
+}
+
+/// A routed section split out of the source.
+#[derive(Debug, PartialEq)]
+struct RouteSection {
+    track_name: String,
+    target: SlotTarget,
+    body: String,
+}
+
+/// Split source into the local program and routed sections.
+///
+/// Directive lines are replaced with blank lines, and each section body is
+/// padded with the lines preceding it, so compiler error line numbers still
+/// match the editor.
+fn split_routes(source: &str) -> (String, Vec<RouteSection>) {
+    let mut local = String::new();
+    let mut sections: Vec<RouteSection> = Vec::new();
+
+    for (line_no, line) in source.lines().enumerate() {
+        if let Some((track_name, target)) = parse_route_directive(line) {
+            sections.push(RouteSection {
+                track_name,
+                target,
+                body: "\n".repeat(line_no + 1),
+            });
+            local.push('\n');
+            continue;
+        }
+        match sections.last_mut() {
+            Some(section) => {
+                section.body.push_str(line);
+                section.body.push('\n');
+                local.push('\n');
+            }
+            None => {
+                local.push_str(line);
+                local.push('\n');
+            }
+        }
+    }
+
+    (local, sections)
+}
+
+/// Parse a routing directive line: `track <name> -> slot <number|name>`.
+fn parse_route_directive(line: &str) -> Option<(String, SlotTarget)> {
+    let rest = line.trim().strip_prefix("track ")?;
+    let (name, target) = rest.split_once("->")?;
+    let name = name.trim();
+    let target = target.trim().strip_prefix("slot")?.trim();
+    if name.is_empty() || target.is_empty() {
+        return None;
+    }
+    let target = target.trim_matches('"');
+    let target = match target.parse::<usize>() {
+        Ok(n) if n >= 1 => SlotTarget::Index(n - 1),
+        Ok(_) => return None,
+        Err(_) => SlotTarget::Name(target.to_string()),
+    };
+    Some((name.to_string(), target))
 }
 
 /// Parse a pitch string like "C4", "D#5", "Eb3" to a MIDI note number.
@@ -263,3 +1056,274 @@ fn parse_pitch(pitch: &str) -> Option<u8> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_route_directive_index() {
+        let parsed = parse_route_directive("track drums -> slot 3");
+        assert_eq!(parsed, Some(("drums".to_string(), SlotTarget::Index(2))));
+    }
+
+    #[test]
+    fn test_parse_route_directive_name() {
+        let parsed = parse_route_directive("  track bass -> slot \"Fat Bass\"");
+        assert_eq!(parsed, Some(("bass".to_string(), SlotTarget::Name("Fat Bass".to_string()))));
+    }
+
+    #[test]
+    fn test_parse_route_directive_rejects_other_lines() {
+        assert_eq!(parse_route_directive("track drums"), None);
+        assert_eq!(parse_route_directive("piano(C4) /4"), None);
+        assert_eq!(parse_route_directive("track drums -> slot 0"), None);
+        assert_eq!(parse_route_directive("track -> slot 2"), None);
+    }
+
+    #[test]
+    fn test_split_routes_sections() {
+        let source = "C4 /4\ntrack drums -> slot 2\nC2 /4\nD2 /4\ntrack hats -> slot 3\nF#2 /8\n";
+        let (local, sections) = split_routes(source);
+        assert_eq!(local, "C4 /4\n\n\n\n\n\n");
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].track_name, "drums");
+        assert_eq!(sections[0].target, SlotTarget::Index(1));
+        // Bodies keep their original line numbers
+        assert_eq!(sections[0].body, "\n\nC2 /4\nD2 /4\n");
+        assert_eq!(sections[1].body, "\n\n\n\n\nF#2 /8\n");
+    }
+
+    #[test]
+    fn test_split_routes_no_directives() {
+        let (local, sections) = split_routes("C4 /4\nE4 /4");
+        assert_eq!(local, "C4 /4\nE4 /4\n");
+        assert!(sections.is_empty());
+    }
+
+    #[test]
+    fn test_play_event_idle_and_reset() {
+        let mut state = RunnerSlotState::default();
+        assert_eq!(state.play_event(), None);
+        state.play_event = Some(3);
+        state.reset();
+        assert_eq!(state.play_event(), None);
+    }
+
+    #[test]
+    fn test_queue_program_swaps_immediately_when_idle() {
+        let mut state = RunnerSlotState::default();
+        let program = Arc::new(RunnerProgram {
+            source_code: "// empty".to_string(),
+            ..Default::default()
+        });
+        state.queue_program(program.clone());
+        assert!(!state.has_pending_program());
+        assert_eq!(state.program().source_code, "// empty");
+        // The sender still holds a reference, so the audio side never frees it
+        assert_eq!(Arc::strong_count(&program), 2);
+    }
+
+    #[test]
+    fn test_set_midi_out_channel_releases_held_notes() {
+        let mut state = RunnerSlotState::default();
+        state.set_midi_out_channel(Some(2));
+        state.midi_offs.push(PendingMidiOff { channel: 2, note: 64, beats_remaining: 1.0 });
+        state.set_midi_out_channel(None);
+        assert_eq!(state.midi_out_channel(), None);
+        assert_eq!(state.midi_out.len(), 1);
+        assert!(!state.midi_out[0].on);
+        assert_eq!(state.midi_out[0].channel, 2);
+        assert_eq!(state.midi_out[0].note, 64);
+    }
+
+    #[test]
+    fn test_routed_note_off_waits_for_room_in_a_full_queue() {
+        let mut state = RunnerSlotState::default();
+        state.compile("track drums -> slot 2\nC2 /4\n");
+        let transport = TransportState::default();
+        state.spawn_instance(60, 1.0, &transport);
+        state.advance_routes(64, 44100.0, &transport);
+        assert!(state.routed_out.iter().any(|n| n.on && n.note == 36));
+        state.routed_out.clear();
+
+        // The gate ends in a block whose queue is already full
+        let filler = RoutedNote { route: 0, note: 0, velocity: 1.0, on: true };
+        state.routed_out.resize(ROUTED_QUEUE_CAPACITY, filler);
+        state.advance_routes(44100, 44100.0, &transport);
+        assert_eq!(state.routed_out.len(), ROUTED_QUEUE_CAPACITY);
+        assert_eq!(state.pending_offs.len(), 1);
+
+        // Once the queue is dispatched the note-off goes out
+        state.routed_out.clear();
+        state.advance_routes(64, 44100.0, &transport);
+        assert!(state.routed_out.iter().any(|n| !n.on && n.note == 36));
+    }
+
+    #[test]
+    fn test_midi_out_note_off_waits_for_room_in_a_full_queue() {
+        let mut state = RunnerSlotState::default();
+        let mut pool = VoicePool::new(4);
+        let transport = TransportState::default();
+        state.set_midi_out_channel(Some(0));
+        state.midi_offs.push(PendingMidiOff { channel: 0, note: 64, beats_remaining: 0.01 });
+
+        // The gate ends in a block whose queue is already full
+        let filler = MidiOutNote { timing: 0, channel: 0, note: 0, velocity: 1.0, on: true };
+        state.midi_out.resize(ROUTED_QUEUE_CAPACITY, filler);
+        state.advance(&mut pool, 512, 44100.0, &transport);
+        assert_eq!(state.midi_out.len(), ROUTED_QUEUE_CAPACITY);
+        assert_eq!(state.midi_offs.len(), 1);
+
+        // Once the host has drained the queue the note-off goes out
+        state.midi_out.clear();
+        state.advance(&mut pool, 512, 44100.0, &transport);
+        assert!(state.midi_out.iter().any(|n| !n.on && n.note == 64 && n.timing == 0));
+        assert!(state.midi_offs.is_empty());
+
+        // Turning MIDI out off with a full queue keeps the release too
+        state.midi_offs.push(PendingMidiOff { channel: 0, note: 65, beats_remaining: 4.0 });
+        state.midi_out.resize(ROUTED_QUEUE_CAPACITY, filler);
+        state.set_midi_out_channel(None);
+        assert_eq!(state.midi_offs.len(), 1);
+        state.midi_out.clear();
+        state.advance(&mut pool, 512, 44100.0, &transport);
+        assert!(state.midi_out.iter().any(|n| !n.on && n.note == 65));
+    }
+
+    #[test]
+    fn test_block_offset_clamped() {
+        assert_eq!(block_offset(-3.0, 128), 0);
+        assert_eq!(block_offset(10.7, 128), 10);
+        assert_eq!(block_offset(500.0, 128), 127);
+    }
+
+    #[test]
+    fn test_swing_offset() {
+        // Straight: no offset
+        assert_eq!(swing_offset(0.5, 50.0), 0.0);
+        // Off-beat 8ths move later, downbeats stay put
+        assert!((swing_offset(0.5, 66.0) - 0.16).abs() < 1e-6);
+        assert!((swing_offset(1.5, 75.0) - 0.25).abs() < 1e-6);
+        assert_eq!(swing_offset(1.0, 75.0), 0.0);
+        assert_eq!(swing_offset(0.25, 75.0), 0.0);
+    }
+
+    #[test]
+    fn test_humanize_velocity_bounds() {
+        let humanize = Humanize { velocity: 0.5, ..Humanize::default() };
+        let mut rng = 12345;
+        for _ in 0..1000 {
+            let v = humanize_velocity(&humanize, &mut rng, 0.8);
+            assert!((0.4..=1.0).contains(&v), "velocity {v} out of range");
+        }
+    }
+
+    #[test]
+    fn test_humanize_offset_within_jitter() {
+        let humanize = Humanize { timing_ms: 10.0, ..Humanize::default() };
+        let mut rng = 42;
+        // 10 ms at 120 BPM = 0.02 beats
+        for _ in 0..1000 {
+            let offset = humanize_offset(&humanize, &mut rng, 1.0, 120.0);
+            assert!(offset.abs() <= 0.02 + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_humanize_default_inactive() {
+        assert!(!Humanize::default().is_active());
+        assert!(Humanize { swing: 60.0, ..Humanize::default() }.is_active());
+    }
+
+    #[test]
+    fn test_crosses_bar() {
+        let mut transport = TransportState::default();
+        transport.playing = true;
+        transport.time_sig_numerator = 4;
+        transport.time_sig_denominator = 4;
+        transport.position_beats = 3.9;
+        assert!(crosses_bar(&transport, 0.2));
+        transport.position_beats = 1.5;
+        assert!(!crosses_bar(&transport, 0.2));
+        transport.playing = false;
+        transport.position_beats = 3.9;
+        assert!(!crosses_bar(&transport, 0.2));
+    }
+
+    fn playing_transport(position_beats: f64) -> TransportState {
+        let mut transport = TransportState::default();
+        transport.playing = true;
+        transport.time_sig_numerator = 4;
+        transport.time_sig_denominator = 4;
+        transport.position_beats = position_beats;
+        transport
+    }
+
+    #[test]
+    fn test_launch_quantize_grid() {
+        let mut transport = playing_transport(0.0);
+        assert_eq!(LaunchQuantize::Off.grid_beats(&transport), None);
+        assert_eq!(LaunchQuantize::Beat.grid_beats(&transport), Some(1.0));
+        assert_eq!(LaunchQuantize::TwoBars.grid_beats(&transport), Some(8.0));
+        transport.time_sig_numerator = 6;
+        transport.time_sig_denominator = 8;
+        assert_eq!(LaunchQuantize::Beat.grid_beats(&transport), Some(0.5));
+        assert_eq!(LaunchQuantize::Bar.grid_beats(&transport), Some(3.0));
+    }
+
+    #[test]
+    fn test_quantized_launch_waits_for_bar() {
+        let mut state = RunnerSlotState::default();
+        state.compile("C4 /4");
+        state.set_launch_quantize(LaunchQuantize::Bar);
+        let transport = playing_transport(5.0);
+        state.spawn_instance(60, 1.0, &transport);
+        assert!(state.instances.is_empty());
+        assert_eq!(state.launch_countdown(&transport), Some(3.0));
+
+        // Not yet: the bar line is beyond this block
+        state.launch_due(&playing_transport(6.0), 0.5);
+        assert!(state.instances.is_empty());
+
+        // The bar line falls a quarter beat into this block
+        state.launch_due(&playing_transport(7.75), 0.5);
+        assert_eq!(state.instances.len(), 1);
+        assert_eq!(state.instances[0].position_beats, -0.25);
+        assert_eq!(state.launch_countdown(&transport), None);
+    }
+
+    #[test]
+    fn test_quantized_launch_cancelled_by_release() {
+        let mut state = RunnerSlotState::default();
+        state.compile("C4 /4");
+        state.set_launch_quantize(LaunchQuantize::Beat);
+        state.spawn_instance(62, 1.0, &playing_transport(0.5));
+        state.release_instance(62);
+        state.launch_due(&playing_transport(0.9), 0.5);
+        assert!(state.instances.is_empty());
+    }
+
+    #[test]
+    fn test_launch_immediate_when_stopped_or_on_grid() {
+        let mut state = RunnerSlotState::default();
+        state.compile("C4 /4");
+        state.set_launch_quantize(LaunchQuantize::Bar);
+        let mut transport = playing_transport(4.0);
+        state.spawn_instance(60, 1.0, &transport);
+        assert_eq!(state.instances.len(), 1);
+        transport.playing = false;
+        transport.position_beats = 5.0;
+        state.spawn_instance(61, 1.0, &transport);
+        assert_eq!(state.instances.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_pitch() {
+        assert_eq!(parse_pitch("C4"), Some(60));
+        assert_eq!(parse_pitch("A4"), Some(69));
+        assert_eq!(parse_pitch("D#5"), Some(75));
+        assert_eq!(parse_pitch("Eb3"), Some(51));
+        assert_eq!(parse_pitch("H4"), None);
+    }
+}
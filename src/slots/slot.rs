@@ -1,9 +1,39 @@
-use nih_plug::prelude::*;
+use std::sync::Arc;
 
+use nih_plug::prelude::*;
+use serde::{Deserialize, Serialize};
+use songwalker_core::preset::instance::{LoadedZone, PresetInstance};
+
+use super::arpeggiator::{ArpSettings, Arpeggiator};
+use super::bend::{self, BendDestination, BendSettings};
+use super::controllers::{self, ControllerSettings, ModDestination, PressureDestination};
+use super::filter::{VoiceFilter, VoiceFilterSettings};
+use super::frozen::{ClipPlayer, FrozenClip};
+use super::keyswitch::{KeyswitchMap, layer_zone_range};
+use super::legato::LegatoSettings;
+use super::midi_filter::MidiFilter;
+use super::mod_matrix::{self, BlockSources, ModMatrix};
+use super::oversampling::{Oversampler, Oversampling};
 use super::preset_slot::PresetSlotState;
 use super::runner_slot::RunnerSlotState;
+use super::synth::{SynthPatch, SynthVoice};
+use super::unison::UnisonSettings;
+use crate::preset::graph::Layer;
+use crate::midi::{ALL_NOTES_OFF, ALL_SOUND_OFF};
 use crate::transport::TransportState;
 
+/// Length of the fade applied to force-terminated voices (seconds).
+pub const DECLICK_SECS: f32 = 0.003;
+
+/// Longest run of samples rendered at one pitch while vibrato is on.
+const MOD_BLOCK: usize = 32;
+
+/// Voices per slot unless set otherwise.
+pub const DEFAULT_POLYPHONY: usize = 64;
+
+/// Upper bound for per-slot polyphony (matches the Max Voices range).
+pub const MAX_POLYPHONY: usize = 1024;
+
 /// Voice state for a single voice in the pre-allocated pool.
 #[derive(Clone)]
 pub struct Voice {
@@ -33,6 +63,35 @@ pub struct Voice {
     pub transpose: i32,
     /// Index of the loaded zone (for sampler rendering).
     pub zone_index: Option<usize>,
+    /// Generation of the preset `zone_index` refers to (see `PresetSlotState`).
+    pub preset_generation: u32,
+    /// Released by a hard stop: fades over `DECLICK_SECS` instead of the
+    /// envelope's release.
+    pub declick: bool,
+    /// Note-off arrived while the sustain pedal was down; released when
+    /// the pedal comes up.
+    pub sustained: bool,
+    /// Last output frame (after envelope), used to declick stolen voices.
+    pub last_frame: (f32, f32),
+    /// Low-pass filter and its envelope.
+    pub filter: VoiceFilter,
+    /// Oscillator stack played when the voice has no sample zone.
+    pub synth: Option<SynthPatch>,
+    pub synth_voice: SynthVoice,
+    /// Envelope from the preset, used instead of the slot's.
+    pub envelope: Option<EnvelopeParams>,
+    /// Per-channel gain of the composite layer that started the voice.
+    pub layer_gain: (f32, f32),
+    /// Random mod matrix source drawn when the note started (−1..1).
+    pub mod_random: f32,
+    /// Note-on the voice belongs to; the voices of one note-on (unison,
+    /// composite layers) are stolen together.
+    pub group: u32,
+    /// Shortest attack, set when the note crossfades in over a retrigger.
+    pub fade_in_secs: f32,
+    /// Set when a retrigger crossfades the voice out: the release takes
+    /// this long instead of the envelope's.
+    pub fade_out_secs: Option<f32>,
 }
 
 impl Default for Voice {
@@ -51,6 +110,19 @@ impl Default for Voice {
             sample_rate_ratio: 1.0,
             transpose: 0,
             zone_index: None,
+            preset_generation: 0,
+            declick: false,
+            sustained: false,
+            last_frame: (0.0, 0.0),
+            filter: VoiceFilter::default(),
+            synth: None,
+            synth_voice: SynthVoice::default(),
+            envelope: None,
+            layer_gain: (1.0, 1.0),
+            mod_random: 0.0,
+            group: 0,
+            fade_in_secs: 0.0,
+            fade_out_secs: None,
         }
     }
 }
@@ -58,31 +130,81 @@ impl Default for Voice {
 /// Pre-allocated voice pool for a single slot.
 pub struct VoicePool {
     voices: Vec<Voice>,
-    _max_polyphony: usize,
+    /// Output of voices cut off mid-sample (stolen or evicted), faded to
+    /// zero by `render_tail`.
+    tail: (f32, f32),
+    /// Fraction of `tail` still sounding (1 → 0).
+    tail_level: f32,
+    /// Group of the voices being allocated (see `begin_group`).
+    group: u32,
 }
 
 impl VoicePool {
     pub fn new(max_polyphony: usize) -> Self {
         Self {
             voices: vec![Voice::default(); max_polyphony],
-            _max_polyphony: max_polyphony,
+            tail: (0.0, 0.0),
+            tail_level: 0.0,
+            group: 0,
+        }
+    }
+
+    /// Number of voices the pool can play at once.
+    pub fn capacity(&self) -> usize {
+        self.voices.len()
+    }
+
+    /// Move the sounding voices of `old` into this (freshly allocated)
+    /// pool, held notes before releasing ones. Voices that don't fit are
+    /// cut off into the tail. Does not allocate.
+    pub fn take_voices_from(&mut self, old: &mut VoicePool) {
+        self.tail = old.tail;
+        self.tail_level = old.tail_level;
+        self.group = old.group;
+        let mut next = 0;
+        for releasing in [false, true] {
+            for voice in old.voices.iter_mut().filter(|v| v.active && v.releasing == releasing) {
+                if next < self.voices.len() {
+                    self.voices[next].clone_from(voice);
+                    next += 1;
+                } else {
+                    let frame = voice.last_frame;
+                    self.add_tail(frame);
+                }
+                voice.active = false;
+            }
         }
     }
 
-    /// Allocate a voice for a new note. Uses round-robin stealing if full.
+    /// Start a group: the voices allocated with `allocate_in_group` until
+    /// the next call belong to one note-on.
+    pub fn begin_group(&mut self) {
+        self.group = self.group.wrapping_add(1);
+    }
+
+    /// Allocate a voice for a new note, in a group of its own.
     pub fn allocate(&mut self, note: u8, velocity: f32) -> Option<&mut Voice> {
-        // Find an inactive voice, or steal the oldest releasing/oldest voice
-        let idx = if let Some(idx) = self.voices.iter().position(|v| !v.active) {
-            idx
-        } else {
-            // Steal: prefer releasing voices, fall back to index 0
-            self.voices
-                .iter()
-                .enumerate()
-                .filter(|(_, v)| v.releasing)
-                .map(|(i, _)| i)
-                .next()
-                .unwrap_or(0)
+        self.begin_group();
+        self.allocate_in_group(note, velocity)
+    }
+
+    /// Allocate a voice in the current group. If the pool is full, the
+    /// oldest group is stolen whole, releasing groups first; None if only
+    /// the current group is left to steal from.
+    pub fn allocate_in_group(&mut self, note: u8, velocity: f32) -> Option<&mut Voice> {
+        let idx = match self.voices.iter().position(|v| !v.active) {
+            Some(idx) => idx,
+            None => {
+                let group = self.group;
+                let (victim, _) = self
+                    .voices
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, v)| v.group != group)
+                    .max_by_key(|(_, v)| (v.releasing, group.wrapping_sub(v.group)))?;
+                self.steal_group(self.voices[victim].group);
+                victim
+            }
         };
 
         let voice = &mut self.voices[idx];
@@ -95,9 +217,32 @@ impl VoicePool {
         voice.releasing = false;
         voice.phase = 0.0;
         voice.sample_pos = 0.0;
+        voice.zone_index = None;
+        voice.declick = false;
+        voice.sustained = false;
+        voice.last_frame = (0.0, 0.0);
+        voice.filter.reset();
+        voice.synth = None;
+        voice.envelope = None;
+        voice.layer_gain = (1.0, 1.0);
+        voice.mod_random = 0.0;
+        voice.group = self.group;
+        voice.fade_in_secs = 0.0;
+        voice.fade_out_secs = None;
         Some(voice)
     }
 
+    /// Cut off every voice of a group into the tail.
+    fn steal_group(&mut self, group: u32) {
+        let mut tail = (0.0, 0.0);
+        for voice in self.voices.iter_mut().filter(|v| v.active && v.group == group) {
+            tail.0 += voice.last_frame.0;
+            tail.1 += voice.last_frame.1;
+            voice.active = false;
+        }
+        self.add_tail(tail);
+    }
+
     /// Release all voices matching the given note.
     pub fn release(&mut self, note: u8) {
         for voice in &mut self.voices {
@@ -109,9 +254,64 @@ impl VoicePool {
         }
     }
 
+    /// Fade out the voices `legato` picks for a note-on of `note` over
+    /// its crossfade. Returns whether any voice was faded.
+    pub fn crossfade_out(&mut self, legato: &LegatoSettings, note: u8) -> bool {
+        let mut faded = false;
+        for voice in self.voices.iter_mut().filter(|v| legato.fades_out(v, note)) {
+            voice.releasing = true;
+            voice.sustained = false;
+            voice.fade_out_secs = Some(legato.crossfade_secs());
+            voice.env_stage = 3;
+            voice.env_samples = 0;
+            faded = true;
+        }
+        faded
+    }
+
+    /// Stop voices whose note is in a drum exclusive class with a short
+    /// declick fade (a closed hi-hat cutting an open one).
+    pub fn choke_class(&mut self, class: u8) {
+        for voice in &mut self.voices {
+            if voice.active
+                && !voice.declick
+                && crate::preset::drums::exclusive_class(voice.note) == Some(class)
+            {
+                voice.releasing = true;
+                voice.declick = true;
+                voice.env_stage = 3;
+                voice.env_samples = 0;
+            }
+        }
+    }
+
+    /// Hold voices of a released note until the sustain pedal comes up.
+    pub fn sustain(&mut self, note: u8) {
+        for voice in &mut self.voices {
+            if voice.active && voice.note == note && !voice.releasing {
+                voice.sustained = true;
+            }
+        }
+    }
+
+    /// Release the voices held by the sustain pedal.
+    pub fn release_sustained(&mut self) {
+        for voice in &mut self.voices {
+            if voice.sustained {
+                voice.sustained = false;
+                if voice.active && !voice.releasing {
+                    voice.releasing = true;
+                    voice.env_stage = 3;
+                    voice.env_samples = 0;
+                }
+            }
+        }
+    }
+
     /// Release all active voices (start envelope release).
     pub fn release_all(&mut self) {
         for voice in &mut self.voices {
+            voice.sustained = false;
             if voice.active && !voice.releasing {
                 voice.releasing = true;
                 voice.env_stage = 3;
@@ -120,11 +320,44 @@ impl VoicePool {
         }
     }
 
-    /// Immediately deactivate all voices (hard kill, no release tail).
+    /// Stop all voices with a short declick fade (no release tail).
     pub fn kill_all(&mut self) {
         for voice in &mut self.voices {
-            voice.active = false;
-            voice.env_stage = 4;
+            voice.sustained = false;
+            if voice.active {
+                voice.releasing = true;
+                voice.declick = true;
+                voice.env_stage = 3;
+                voice.env_samples = 0;
+            }
+        }
+    }
+
+    /// Add the last output of a voice that stopped abruptly to the tail.
+    pub fn add_tail(&mut self, frame: (f32, f32)) {
+        self.tail = (
+            self.tail.0 * self.tail_level + frame.0,
+            self.tail.1 * self.tail_level + frame.1,
+        );
+        self.tail_level = 1.0;
+    }
+
+    /// Whether a cut-off voice is still fading out.
+    pub fn has_tail(&self) -> bool {
+        self.tail_level > 0.0
+    }
+
+    /// Mix the fading tail of cut-off voices into the buffers.
+    pub fn render_tail(&mut self, left: &mut [f32], right: &mut [f32], num_samples: usize, sample_rate: f32) {
+        let step = 1.0 / (DECLICK_SECS * sample_rate).max(1.0);
+        for i in 0..num_samples {
+            if self.tail_level <= 0.0 {
+                self.tail_level = 0.0;
+                break;
+            }
+            left[i] += self.tail.0 * self.tail_level;
+            right[i] += self.tail.1 * self.tail_level;
+            self.tail_level -= step;
         }
     }
 
@@ -133,6 +366,13 @@ impl VoicePool {
         self.voices.iter_mut().filter(|v| v.active)
     }
 
+    /// Whether any active voice is playing a preset of the given generation.
+    pub fn any_active_with_generation(&self, generation: u32) -> bool {
+        self.voices
+            .iter()
+            .any(|v| v.active && v.zone_index.is_some() && v.preset_generation == generation)
+    }
+
     /// Count of currently active voices.
     pub fn active_count(&self) -> usize {
         self.voices.iter().filter(|v| v.active).count()
@@ -148,6 +388,61 @@ impl VoicePool {
     }
 }
 
+/// Per-slot tuning and stereo width.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SlotTuning {
+    /// Coarse tune in semitones (−24..=24).
+    pub coarse: i8,
+    /// Fine tune in cents (−100..=100).
+    pub fine: f32,
+    /// Stereo width: 0 = mono, 1 = unchanged, 2 = double side signal.
+    pub width: f32,
+}
+
+impl Default for SlotTuning {
+    fn default() -> Self {
+        Self {
+            coarse: 0,
+            fine: 0.0,
+            width: 1.0,
+        }
+    }
+}
+
+impl SlotTuning {
+    /// Playback rate multiplier for the coarse and fine offsets.
+    pub fn rate_ratio(&self) -> f64 {
+        2.0_f64.powf((self.coarse as f64 + self.fine as f64 / 100.0) / 12.0)
+    }
+}
+
+/// Per-slot output stage: trim and optional soft saturation, applied to
+/// the slot's signal before its fader.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlotOutput {
+    /// Trim in dB (−24..=12).
+    pub trim_db: f32,
+    /// Soft-clip (tanh) the trimmed signal.
+    pub saturate: bool,
+}
+
+impl Default for SlotOutput {
+    fn default() -> Self {
+        Self {
+            trim_db: 0.0,
+            saturate: false,
+        }
+    }
+}
+
+impl SlotOutput {
+    /// Linear gain for the trim.
+    pub fn trim_gain(&self) -> f32 {
+        10.0_f32.powf(self.trim_db / 20.0)
+    }
+}
+
 /// A single instrument slot in the rack.
 ///
 /// Each slot is a unified instrument that handles MIDI → preset playback.
@@ -157,8 +452,12 @@ impl VoicePool {
 pub struct Slot {
     /// Slot index in the rack.
     index: usize,
-    /// Pre-allocated voice pool.
+    /// Pre-allocated voice pool (resized by `SlotManager::update_voice_pools`).
     voice_pool: VoicePool,
+    /// Voices the slot asks for; the pool is capped by the Max Voices param.
+    polyphony: usize,
+    /// Size of the pool requested from the resizer, not yet swapped in.
+    pending_pool: Option<usize>,
     /// Volume gain (linear).
     volume: f32,
     /// Pan position (-1 to 1).
@@ -177,6 +476,52 @@ pub struct Slot {
     runner_state: RunnerSlotState,
     /// Whether this slot has .sw source code loaded.
     has_source: bool,
+    /// Group bus this slot is routed through (None = direct to master).
+    group: Option<usize>,
+    /// Arpeggiator applied to incoming notes ahead of the voice pool.
+    arp: Arpeggiator,
+    /// Articulation keyswitches.
+    keyswitches: KeyswitchMap,
+    /// Keyswitch of the active articulation (None = all layers).
+    active_keyswitch: Option<u8>,
+    /// Coarse/fine tune and stereo width.
+    tuning: SlotTuning,
+    /// Voices per note, detune and spread.
+    unison: UnisonSettings,
+    /// Retrigger and legato crossfades.
+    legato: LegatoSettings,
+    /// Bend range override and destination.
+    bend: BendSettings,
+    /// Global Pitch Bend Range (semitones), used without an override.
+    global_bend_range: u8,
+    /// Mod wheel and expression routing.
+    controllers: ControllerSettings,
+    /// Phase of the vibrato/tremolo LFO, in cycles.
+    mod_phase: f64,
+    /// Aftertouch smoothed toward `preset_state.pressure`, once per block.
+    pressure: f32,
+    /// Modulation matrix routes and LFO rates.
+    mod_matrix: ModMatrix,
+    /// Phases of the matrix LFOs, in cycles.
+    matrix_phases: [f64; 2],
+    /// State of the per-note random source.
+    mod_seed: u32,
+    /// Input filter applied to incoming MIDI.
+    midi_filter: MidiFilter,
+    /// Per-voice low-pass filter settings.
+    filter: VoiceFilterSettings,
+    /// Output trim and saturation.
+    output: SlotOutput,
+    /// Peak of the last rendered block after the output stage.
+    output_peak: f32,
+    /// Hold mode: a note-on toggles its note, note-offs are ignored.
+    hold: bool,
+    /// Notes latched by hold mode, one bit per key.
+    held_notes: u128,
+    /// Frozen clip played in place of the slot's voices (see `frozen`).
+    frozen: ClipPlayer,
+    /// Oversampled voice rendering.
+    oversampler: Oversampler,
     /// Display name for the slot.
     pub name: String,
 }
@@ -185,7 +530,9 @@ impl Slot {
     pub fn new(index: usize) -> Self {
         Self {
             index,
-            voice_pool: VoicePool::new(64),
+            voice_pool: VoicePool::new(DEFAULT_POLYPHONY),
+            polyphony: DEFAULT_POLYPHONY,
+            pending_pool: None,
             volume: 1.0,
             pan: 0.0,
             muted: false,
@@ -195,6 +542,29 @@ impl Slot {
             preset_state: PresetSlotState::default(),
             runner_state: RunnerSlotState::default(),
             has_source: false,
+            group: None,
+            arp: Arpeggiator::default(),
+            keyswitches: KeyswitchMap::default(),
+            active_keyswitch: None,
+            tuning: SlotTuning::default(),
+            unison: UnisonSettings::default(),
+            legato: LegatoSettings::default(),
+            bend: BendSettings::default(),
+            global_bend_range: 2,
+            controllers: ControllerSettings::default(),
+            mod_phase: 0.0,
+            pressure: 0.0,
+            mod_matrix: ModMatrix::default(),
+            matrix_phases: [0.0; 2],
+            mod_seed: (index as u32 + 1).wrapping_mul(0x9E37_79B9),
+            midi_filter: MidiFilter::default(),
+            filter: VoiceFilterSettings::default(),
+            output: SlotOutput::default(),
+            output_peak: 0.0,
+            hold: false,
+            held_notes: 0,
+            frozen: ClipPlayer::default(),
+            oversampler: Oversampler::default(),
             name: format!("Slot {}", index + 1),
         }
     }
@@ -204,8 +574,31 @@ impl Slot {
     }
 
     pub fn reset(&mut self) {
+        self.held_notes = 0;
+        self.stop_frozen_clip();
         self.voice_pool.release_all();
         self.runner_state.reset();
+        self.arp.reset();
+        self.arp.events_mut().clear();
+    }
+
+    /// All notes off (CC 123): release every voice, held or sustained, and
+    /// stop runner instances and the arpeggiator.
+    pub fn all_notes_off(&mut self) {
+        self.preset_state.sustain = false;
+        self.reset();
+    }
+
+    /// Panic / all sound off (CC 120): like `all_notes_off`, but voices
+    /// fade out over the declick time instead of their release.
+    pub fn panic(&mut self) {
+        self.preset_state.sustain = false;
+        self.held_notes = 0;
+        self.stop_frozen_clip();
+        self.voice_pool.kill_all();
+        self.runner_state.reset();
+        self.arp.reset();
+        self.arp.events_mut().clear();
     }
 
     pub fn set_index(&mut self, index: usize) {
@@ -226,6 +619,12 @@ impl Slot {
         self.has_source = has_source;
     }
 
+    /// Whether this slot takes the plugin's audio input (an Effect preset
+    /// in preset mode).
+    pub fn is_effect(&self) -> bool {
+        !self.has_source && self.preset_state.is_effect
+    }
+
     pub fn volume(&self) -> f32 {
         self.volume
     }
@@ -258,6 +657,283 @@ impl Slot {
         self.solo = solo;
     }
 
+    pub fn group(&self) -> Option<usize> {
+        self.group
+    }
+
+    pub fn set_group(&mut self, group: Option<usize>) {
+        self.group = group;
+    }
+
+    pub fn arp_settings(&self) -> ArpSettings {
+        self.arp.settings()
+    }
+
+    pub fn set_arp_settings(&mut self, settings: ArpSettings) {
+        self.arp.set_settings(settings);
+    }
+
+    pub fn hold(&self) -> bool {
+        self.hold
+    }
+
+    /// Turn hold mode on or off. Turning it off releases the latched notes.
+    pub fn set_hold(&mut self, hold: bool, transport: &TransportState) {
+        if !hold {
+            self.release_held(transport);
+        }
+        self.hold = hold;
+    }
+
+    /// Notes latched by hold mode, one bit per key.
+    pub fn held_notes(&self) -> u128 {
+        self.held_notes
+    }
+
+    /// Number of notes latched by hold mode.
+    pub fn held_count(&self) -> u32 {
+        self.held_notes.count_ones()
+    }
+
+    /// Release every note latched by hold mode.
+    pub fn release_held(&mut self, transport: &TransportState) {
+        while self.held_notes != 0 {
+            let note = self.held_notes.trailing_zeros() as u8;
+            self.held_notes &= !(1 << note);
+            let off = NoteEvent::NoteOff { timing: 0, voice_id: None, channel: 0, note, velocity: 0.0 };
+            self.dispatch_midi_event(&off, transport);
+        }
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.is_frozen()
+    }
+
+    /// Freeze the slot to a rendered clip, or unfreeze it (None). Sounding
+    /// voices are released. The previous clip is dropped here, so the
+    /// sender should keep its own `Arc` alive.
+    pub fn set_frozen_clip(&mut self, clip: Option<Arc<FrozenClip>>) {
+        self.stop_frozen_clip();
+        self.voice_pool.release_all();
+        self.runner_state.reset();
+        self.frozen.set_clip(clip);
+    }
+
+    /// Stop the frozen clip, fading out where it was cut.
+    fn stop_frozen_clip(&mut self) {
+        let cut = self.frozen.stop();
+        if cut != (0.0, 0.0) {
+            self.voice_pool.add_tail(cut);
+        }
+    }
+
+    /// Replace the keyswitches; the default articulation becomes active.
+    pub fn set_keyswitches(&mut self, keyswitches: KeyswitchMap) {
+        self.keyswitches = keyswitches;
+        self.active_keyswitch = keyswitches.default_key();
+    }
+
+    /// Key of the active articulation, if the slot has keyswitches.
+    pub fn active_keyswitch(&self) -> Option<u8> {
+        self.active_keyswitch
+    }
+
+    pub fn tuning(&self) -> SlotTuning {
+        self.tuning
+    }
+
+    /// Set tuning and width; applies to notes started afterwards.
+    pub fn set_tuning(&mut self, tuning: SlotTuning) {
+        self.tuning = SlotTuning {
+            coarse: tuning.coarse.clamp(-24, 24),
+            fine: tuning.fine.clamp(-100.0, 100.0),
+            width: tuning.width.clamp(0.0, 2.0),
+        };
+        self.runner_state.tune_ratio = self.tuning.rate_ratio();
+    }
+
+    pub fn unison(&self) -> UnisonSettings {
+        self.unison
+    }
+
+    /// Set the unison voices, detune and spread (clamped); applies to
+    /// notes started afterwards.
+    pub fn set_unison(&mut self, unison: UnisonSettings) {
+        self.unison = unison.clamped();
+    }
+
+    pub fn legato(&self) -> LegatoSettings {
+        self.legato
+    }
+
+    /// Set the retrigger/legato crossfades (length clamped to 1–50 ms).
+    pub fn set_legato(&mut self, legato: LegatoSettings) {
+        self.legato = legato.clamped();
+    }
+
+    pub fn bend(&self) -> BendSettings {
+        self.bend
+    }
+
+    /// Set the bend range override (clamped to 1–48 st) and destination.
+    pub fn set_bend(&mut self, settings: BendSettings) {
+        self.bend = BendSettings {
+            range: settings.range.map(|r| r.clamp(1, 48)),
+            destination: settings.destination,
+        };
+    }
+
+    /// Follow the global Pitch Bend Range where the slot has no override.
+    pub fn set_global_bend_range(&mut self, semitones: u8) {
+        self.global_bend_range = semitones;
+    }
+
+    /// Bend wheel position (−1..1) of the mode the slot plays in.
+    pub fn bend_wheel(&self) -> f32 {
+        if self.has_source {
+            self.runner_state.pitch_bend
+        } else {
+            self.preset_state.pitch_bend
+        }
+    }
+
+    pub fn controllers(&self) -> ControllerSettings {
+        self.controllers
+    }
+
+    /// Set the mod wheel destination and the mod/expression depths (0–1).
+    pub fn set_controllers(&mut self, settings: ControllerSettings) {
+        self.controllers = ControllerSettings {
+            mod_destination: settings.mod_destination,
+            mod_depth: settings.mod_depth.clamp(0.0, 1.0),
+            expression_depth: settings.expression_depth.clamp(0.0, 1.0),
+            pressure_destination: settings.pressure_destination,
+            pressure_depth: settings.pressure_depth.clamp(0.0, 1.0),
+        };
+    }
+
+    pub fn mod_matrix(&self) -> ModMatrix {
+        self.mod_matrix
+    }
+
+    /// Set the modulation matrix; sounding voices follow it from the next
+    /// block.
+    pub fn set_mod_matrix(&mut self, matrix: ModMatrix) {
+        self.mod_matrix = ModMatrix {
+            lfo1_hz: matrix.lfo1_hz.clamp(0.0, mod_matrix::MAX_LFO_HZ),
+            lfo2_hz: matrix.lfo2_hz.clamp(0.0, mod_matrix::MAX_LFO_HZ),
+            depth: matrix.depth.map(|row| row.map(|d| d.clamp(-1.0, 1.0))),
+        };
+    }
+
+    /// Block-rate mod matrix sources as they stand.
+    fn matrix_sources(&self) -> BlockSources {
+        BlockSources {
+            lfo1: controllers::lfo(self.matrix_phases[0]),
+            lfo2: controllers::lfo(self.matrix_phases[1]),
+            mod_wheel: self.preset_state.mod_wheel,
+            aftertouch: self.pressure,
+        }
+    }
+
+    /// Pitch and filter settings for this block from bend and mod wheel.
+    fn block_modulation(&self, sample_rate: f32) -> BlockModulation {
+        let semitones = self.bend.semitones(self.bend_wheel(), self.global_bend_range);
+        let mut modulation = BlockModulation {
+            pitch: 1.0,
+            vibrato: 0.0,
+            phase: self.mod_phase,
+            phase_inc: controllers::MOD_LFO_HZ as f64 / sample_rate.max(1.0) as f64,
+            filter: self.filter,
+            sample_rate,
+            matrix: self.mod_matrix.is_active().then_some(self.mod_matrix),
+            sources: self.matrix_sources(),
+            gain: (1.0, 1.0),
+        };
+        match self.bend.destination {
+            BendDestination::Pitch => modulation.pitch = bend::ratio(semitones) as f64,
+            BendDestination::FilterCutoff => modulation.filter.cutoff_hz *= bend::ratio(semitones),
+            BendDestination::SampleStart => {}
+        }
+        let amount = self.controllers.mod_amount(self.preset_state.mod_wheel);
+        let (mut vibrato, mut cutoff) = match self.controllers.mod_destination {
+            ModDestination::Vibrato => (amount, 0.0),
+            ModDestination::FilterCutoff => (0.0, amount),
+            ModDestination::Tremolo => (0.0, 0.0),
+        };
+        vibrato += self.controllers.pressure_amount(PressureDestination::Vibrato, self.pressure);
+        cutoff += self.controllers.pressure_amount(PressureDestination::FilterCutoff, self.pressure);
+        modulation.vibrato = vibrato.min(1.0) * controllers::MAX_VIBRATO_SEMITONES;
+        if cutoff > 0.0 {
+            modulation.filter.cutoff_hz *= 2.0_f32.powf(cutoff.min(1.0) * controllers::MAX_CUTOFF_OCTAVES);
+        }
+        modulation
+    }
+
+    /// Apply expression, tremolo and aftertouch swell to the rendered block
+    /// and advance the modulation LFO. `swell_from` is the smoothed
+    /// pressure of the previous block; the swell ramps from it.
+    fn apply_controllers(&mut self, left: &mut [f32], right: &mut [f32], sample_rate: f32, swell_from: f32) {
+        let expression = self.controllers.expression_gain(self.preset_state.expression);
+        let tremolo = match self.controllers.mod_destination {
+            ModDestination::Tremolo => self.controllers.mod_amount(self.preset_state.mod_wheel),
+            _ => 0.0,
+        };
+        let swell = |pressure| {
+            1.0 + self.controllers.pressure_amount(PressureDestination::Swell, pressure) * controllers::MAX_SWELL
+        };
+        let (swell_start, swell_end) = (swell(swell_from), swell(self.pressure));
+        let swell_step = (swell_end - swell_start) / left.len().max(1) as f32;
+        let phase_inc = controllers::MOD_LFO_HZ as f64 / sample_rate.max(1.0) as f64;
+        if tremolo > 0.0 || expression < 1.0 || swell_start != 1.0 || swell_end != 1.0 {
+            for (i, (l, r)) in left.iter_mut().zip(right.iter_mut()).enumerate() {
+                let lfo = 0.5 + 0.5 * controllers::lfo(self.mod_phase + i as f64 * phase_inc);
+                let gain = expression * (1.0 - tremolo * lfo) * (swell_start + swell_step * i as f32);
+                *l *= gain;
+                *r *= gain;
+            }
+        }
+        self.mod_phase = (self.mod_phase + left.len() as f64 * phase_inc).fract();
+    }
+
+    pub fn set_midi_filter(&mut self, filter: MidiFilter) {
+        self.midi_filter = filter;
+    }
+
+    pub fn filter(&self) -> VoiceFilterSettings {
+        self.filter
+    }
+
+    /// Set the voice filter; sounding voices pick it up on their next
+    /// control block.
+    pub fn set_filter(&mut self, filter: VoiceFilterSettings) {
+        self.filter = filter;
+    }
+
+    pub fn output(&self) -> SlotOutput {
+        self.output
+    }
+
+    pub fn set_output(&mut self, output: SlotOutput) {
+        self.output = SlotOutput {
+            trim_db: output.trim_db.clamp(-24.0, 12.0),
+            saturate: output.saturate,
+        };
+    }
+
+    pub fn oversampling(&self) -> Oversampling {
+        self.oversampler.mode()
+    }
+
+    pub fn set_oversampling(&mut self, mode: Oversampling) {
+        self.oversampler.set_mode(mode);
+    }
+
+    /// Peak of the last rendered block after trim and saturation.
+    pub fn output_peak(&self) -> f32 {
+        self.output_peak
+    }
+
     pub fn midi_channel(&self) -> i32 {
         self.midi_channel
     }
@@ -270,10 +946,45 @@ impl Slot {
         self.voice_pool.active_count()
     }
 
+    /// Size of the slot's current voice pool.
+    pub fn voice_capacity(&self) -> usize {
+        self.voice_pool.capacity()
+    }
+
     pub fn voice_pool_mut(&mut self) -> &mut VoicePool {
         &mut self.voice_pool
     }
 
+    pub fn polyphony(&self) -> usize {
+        self.polyphony
+    }
+
+    /// Set the slot's polyphony. The pool is resized off the audio thread.
+    pub fn set_polyphony(&mut self, voices: usize) {
+        self.polyphony = voices.clamp(1, MAX_POLYPHONY);
+    }
+
+    /// Pool size for this slot under a global voice ceiling.
+    pub fn target_voices(&self, max_voices: usize) -> usize {
+        self.polyphony.min(max_voices).max(1)
+    }
+
+    pub fn pending_pool(&self) -> Option<usize> {
+        self.pending_pool
+    }
+
+    pub fn set_pending_pool(&mut self, voices: Option<usize>) {
+        self.pending_pool = voices;
+    }
+
+    /// Swap in a resized pool, carrying over the sounding voices. Returns
+    /// the old pool, which must be dropped off the audio thread.
+    pub fn swap_voice_pool(&mut self, mut pool: VoicePool) -> VoicePool {
+        pool.take_voices_from(&mut self.voice_pool);
+        self.pending_pool = None;
+        std::mem::replace(&mut self.voice_pool, pool)
+    }
+
     pub fn preset_state(&self) -> &PresetSlotState {
         &self.preset_state
     }
@@ -292,9 +1003,86 @@ impl Slot {
 
     /// Handle an incoming MIDI event.
     ///
-    /// If the slot has source code, it routes to the runner.
-    /// Otherwise, it routes to preset playback.
-    pub fn handle_midi_event(&mut self, event: &NoteEvent<()>, transport: &TransportState) {
+    /// Keyswitch keys select an articulation and are not played. Other
+    /// events then pass the slot's input filter. In hold mode a note-on
+    /// toggles its note and note-offs are dropped. With the
+    /// arpeggiator enabled, note on/off feed the held-note list
+    /// and the arpeggiated notes are played from `render()`. Otherwise, if
+    /// the slot has source code, it routes to the runner, else to preset
+    /// playback. Returns false if the input filter dropped the event.
+    pub fn handle_midi_event(&mut self, event: &NoteEvent<()>, transport: &TransportState) -> bool {
+        match event {
+            NoteEvent::NoteOn { note, .. } if self.keyswitches.layer_for(*note).is_some() => {
+                self.active_keyswitch = Some(*note);
+                return true;
+            }
+            NoteEvent::NoteOff { note, .. } if self.keyswitches.layer_for(*note).is_some() => {
+                return true;
+            }
+            _ => {}
+        }
+        let Some(event) = self.midi_filter.apply(event) else {
+            return false;
+        };
+        let event = match event {
+            NoteEvent::NoteOn { timing, voice_id, channel, note, .. } if self.hold => {
+                let bit = 1u128 << (note & 0x7F);
+                self.held_notes ^= bit;
+                if self.held_notes & bit != 0 {
+                    event
+                } else {
+                    NoteEvent::NoteOff { timing, voice_id, channel, note, velocity: 0.0 }
+                }
+            }
+            NoteEvent::NoteOff { .. } if self.hold => return true,
+            event => event,
+        };
+        self.dispatch_midi_event(&event, transport);
+        true
+    }
+
+    /// Route a filtered event to the arpeggiator, channel mode handling or
+    /// playback.
+    fn dispatch_midi_event(&mut self, event: &NoteEvent<()>, transport: &TransportState) {
+        // A frozen slot only starts and stops its clip
+        if self.frozen.is_frozen() {
+            match event {
+                NoteEvent::NoteOn { .. } => {
+                    let cut = self.frozen.trigger();
+                    if cut != (0.0, 0.0) {
+                        self.voice_pool.add_tail(cut);
+                    }
+                }
+                NoteEvent::MidiCC { cc: ALL_SOUND_OFF | ALL_NOTES_OFF, .. } => self.stop_frozen_clip(),
+                _ => {}
+            }
+            return;
+        }
+        match event {
+            NoteEvent::NoteOn { note, velocity, .. } if self.arp.is_enabled() => {
+                self.arp.note_on(*note, *velocity);
+                return;
+            }
+            NoteEvent::NoteOff { note, .. } if self.arp.is_enabled() => {
+                self.arp.note_off(*note);
+                return;
+            }
+            // Channel mode messages, for runner and preset slots alike
+            NoteEvent::MidiCC { cc: ALL_SOUND_OFF, .. } => {
+                self.panic();
+                return;
+            }
+            NoteEvent::MidiCC { cc: ALL_NOTES_OFF, .. } => {
+                self.all_notes_off();
+                return;
+            }
+            _ => {}
+        }
+        self.play_midi_event(event, transport);
+    }
+
+    /// Play an event on the runner or preset, bypassing the arpeggiator.
+    fn play_midi_event(&mut self, event: &NoteEvent<()>, transport: &TransportState) {
         if self.has_source {
             self.handle_runner_midi(event, transport);
         } else {
@@ -305,40 +1093,152 @@ impl Slot {
     fn handle_preset_midi(&mut self, event: &NoteEvent<()>) {
         match event {
             NoteEvent::NoteOn { note, velocity, .. } => {
-                if let Some(voice) = self.voice_pool.allocate(*note, *velocity) {
-                    let freq = crate::midi::midi_to_freq(*note);
-                    voice.phase_inc = freq as f64 / self.sample_rate as f64;
-
-                    // If a sampler preset is loaded, configure sample playback
-                    if let Some(ref preset_instance) = self.preset_state.active_preset {
-                        if let Some((zone_idx, zone)) = preset_instance.find_zone_indexed(*note, *velocity) {
-                            let pitch = zone.pitch();
-                            let rate = songwalker_core::preset::sample_playback_rate(
-                                *note,
-                                pitch.root_note,
-                                pitch.fine_tune_cents,
-                                440.0,
-                            );
-                            voice.sample_rate_ratio = rate * (zone.sample_rate() as f64 / self.sample_rate as f64);
-                            voice.sample_pos = 0.0;
-                            voice.zone_index = Some(zone_idx);
+                if self.preset_state.is_drum_kit {
+                    if let Some(class) = crate::preset::drums::exclusive_class(*note) {
+                        self.voice_pool.choke_class(class);
+                    }
+                }
+                // A layering composite starts a voice for each child that
+                // plays the key; an articulation selects a single child.
+                // Every voice of the note-on is stolen as one group.
+                let fade_in = if !self.preset_state.is_drum_kit
+                    && self.legato.is_active()
+                    && self.voice_pool.crossfade_out(&self.legato, *note)
+                {
+                    self.legato.crossfade_secs()
+                } else {
+                    0.0
+                };
+                self.voice_pool.begin_group();
+                if self.active_keyswitch.is_none() && !self.preset_state.layers().is_empty() {
+                    for i in 0..self.preset_state.layers().len() {
+                        let layer = self.preset_state.layers()[i];
+                        if layer.covers(*note) {
+                            self.start_preset_voice(*note, *velocity, Some(&layer), fade_in);
                         }
                     }
+                } else {
+                    self.start_preset_voice(*note, *velocity, None, fade_in);
                 }
             }
+            // Drum hits are one-shots: they play out their sample
+            NoteEvent::NoteOff { .. } if self.preset_state.is_drum_kit => {}
+            NoteEvent::NoteOff { note, .. } if self.preset_state.sustain => {
+                self.voice_pool.sustain(*note);
+            }
             NoteEvent::NoteOff { note, .. } => {
                 self.voice_pool.release(*note);
             }
             NoteEvent::MidiPitchBend { value, .. } => {
-                self.preset_state.pitch_bend = *value;
+                self.preset_state.pitch_bend = bend::normalize(*value);
+            }
+            NoteEvent::MidiChannelPressure { pressure, .. } => {
+                self.preset_state.pressure = *pressure;
             }
             NoteEvent::MidiCC { cc, value, .. } => {
+                let was_sustained = self.preset_state.sustain;
                 self.preset_state.handle_cc(*cc, *value);
+                if was_sustained && !self.preset_state.sustain {
+                    self.voice_pool.release_sustained();
+                }
             }
             _ => {}
         }
     }
 
+    /// Start a voice for a note, on one `layer` of a composite or on the
+    /// whole preset. A layer with nothing to play for the key starts none.
+    /// `fade_in` is the shortest attack (s), for a crossfaded retrigger.
+    fn start_preset_voice(&mut self, note: u8, velocity: f32, layer: Option<&Layer>, fade_in: f32) {
+        let tune = self.tuning.rate_ratio();
+        let mut zone_found = None;
+        if let Some(ref preset_instance) = self.preset_state.active_preset {
+            let range = match layer {
+                Some(layer) => Some(layer.zones()),
+                None => self
+                    .active_keyswitch
+                    .and_then(|key| self.keyswitches.layer_for(key))
+                    .and_then(|layer| layer_zone_range(&preset_instance.descriptor.graph, layer)),
+            };
+            let found = match range {
+                // The preset's own lookup (velocity layers etc.) if it lands
+                // in the child, else the child's first zone covering the key
+                Some(range) => preset_instance
+                    .find_zone_indexed(note, velocity)
+                    .filter(|(idx, _)| range.contains(idx))
+                    .or_else(|| {
+                        let zones = preset_instance.zones.get(range.clone())?;
+                        zones
+                            .iter()
+                            .enumerate()
+                            .find(|(_, z)| z.zone.key_range.low <= note && note <= z.zone.key_range.high)
+                            .map(|(i, z)| (range.start + i, z))
+                    }),
+                None => preset_instance.find_zone_indexed(note, velocity),
+            };
+            zone_found = found.map(|(zone_idx, zone)| {
+                let pitch = zone.pitch();
+                let rate =
+                    songwalker_core::preset::sample_playback_rate(note, pitch.root_note, pitch.fine_tune_cents, 440.0);
+                let frames = zone.pcm_data.len() / (zone.channels as usize).max(1);
+                (zone_idx, rate * tune * (zone.sample_rate() as f64 / self.sample_rate as f64), frames)
+            });
+        }
+        if let Some((zone_idx, _, frames)) = zone_found {
+            // No frames: purged, the voice stays silent until it's reloaded
+            self.preset_state.note_zone_played(zone_idx, frames == 0);
+        }
+        // No sample to play: the oscillators of the layer or preset, if any
+        let synth = match layer {
+            Some(layer) => layer.synth,
+            None => self.preset_state.synth,
+        };
+        if zone_found.is_none() && synth.is_none() && layer.is_some() {
+            return;
+        }
+
+        let random = mod_matrix::next_random(&mut self.mod_seed);
+        let matrix_start = self.mod_matrix.start_offset(&self.matrix_sources().with_voice(velocity, random));
+        let start = match self.bend.destination {
+            BendDestination::SampleStart => bend::start_offset(self.preset_state.pitch_bend),
+            _ => 0.0,
+        } + matrix_start;
+        let start = start.min(bend::MAX_START_OFFSET);
+        let freq = crate::midi::midi_to_freq(note);
+        let layer_gain = layer.map_or((1.0, 1.0), |layer| layer.channel_gains());
+        let unison = self.unison;
+        for index in 0..unison.voices.max(1) {
+            let nudge = if unison.is_active() {
+                mod_matrix::next_random(&mut self.mod_seed)
+            } else {
+                0.0
+            };
+            let unison_voice = unison.voice(index, nudge);
+            let Some(voice) = self.voice_pool.allocate_in_group(note, velocity) else {
+                return;
+            };
+            voice.phase_inc = freq as f64 * tune * unison_voice.ratio / self.sample_rate as f64;
+            voice.mod_random = random;
+            voice.fade_in_secs = fade_in;
+            voice.layer_gain = (layer_gain.0 * unison_voice.gain.0, layer_gain.1 * unison_voice.gain.1);
+            match (zone_found, synth) {
+                (Some((zone_idx, ratio, frames)), _) => {
+                    voice.sample_rate_ratio = ratio * unison_voice.ratio;
+                    voice.sample_pos = (start * frames as f64).floor();
+                    voice.zone_index = Some(zone_idx);
+                    voice.preset_generation = self.preset_state.generation();
+                    voice.envelope = self.preset_state.zone_envelope(zone_idx);
+                }
+                (None, Some(patch)) => {
+                    voice.synth = Some(patch);
+                    voice.synth_voice.reset(u32::from(note).wrapping_mul(0x9E37_79B9) ^ u32::from(index));
+                    voice.envelope = patch.envelope;
+                }
+                (None, None) => {}
+            }
+        }
+    }
+
     fn handle_runner_midi(&mut self, event: &NoteEvent<()>, transport: &TransportState) {
         match event {
             NoteEvent::NoteOn { note, velocity, .. } => {
@@ -352,13 +1252,21 @@ impl Slot {
                 self.voice_pool.release(*note);
             }
             NoteEvent::MidiPitchBend { value, .. } => {
-                self.runner_state.pitch_bend = *value;
+                self.runner_state.pitch_bend = bend::normalize(*value);
+            }
+            NoteEvent::MidiCC { cc: cc @ (crate::midi::MOD_WHEEL | crate::midi::EXPRESSION), value, .. } => {
+                self.preset_state.handle_cc(*cc, *value);
+            }
+            NoteEvent::MidiChannelPressure { pressure, .. } => {
+                self.preset_state.pressure = *pressure;
             }
             _ => {}
         }
     }
 
-    /// Render this slot's audio into the provided stereo buffers.
+    /// Render this slot's audio into the provided stereo buffers. Voices
+    /// are added to what the buffers hold, which for an effect slot is the
+    /// audio input (see `render_and_mix`).
     pub fn render(
         &mut self,
         left: &mut [f32],
@@ -367,77 +1275,122 @@ impl Slot {
         sample_rate: f32,
         transport: &TransportState,
     ) {
+        if self.frozen.is_frozen() {
+            self.frozen.render(&mut left[..num_samples], &mut right[..num_samples], sample_rate);
+            self.voice_pool.render_tail(left, right, num_samples, sample_rate);
+            self.output_peak = left[..num_samples]
+                .iter()
+                .chain(&right[..num_samples])
+                .fold(0.0_f32, |m, s| m.max(s.abs()));
+            return;
+        }
+
+        // Arpeggiated notes (and releases queued by settings changes)
+        self.arp.advance(num_samples, sample_rate, transport);
+        if !self.arp.events_mut().is_empty() {
+            let mut events = std::mem::take(self.arp.events_mut());
+            for arp_note in &events {
+                let event = if arp_note.on {
+                    NoteEvent::NoteOn {
+                        timing: 0,
+                        voice_id: None,
+                        channel: 0,
+                        note: arp_note.note,
+                        velocity: arp_note.velocity,
+                    }
+                } else {
+                    NoteEvent::NoteOff {
+                        timing: 0,
+                        voice_id: None,
+                        channel: 0,
+                        note: arp_note.note,
+                        velocity: 0.0,
+                    }
+                };
+                self.play_midi_event(&event, transport);
+            }
+            events.clear();
+            *self.arp.events_mut() = events;
+        }
+
+        let pressure_from = self.pressure;
+        self.pressure =
+            controllers::smooth_pressure(self.pressure, self.preset_state.pressure, num_samples, sample_rate);
+
         if self.has_source {
             self.render_runner(left, right, num_samples, sample_rate, transport);
         } else {
             self.render_preset(left, right, num_samples, sample_rate);
         }
 
+        self.voice_pool.render_tail(left, right, num_samples, sample_rate);
+        self.apply_controllers(&mut left[..num_samples], &mut right[..num_samples], sample_rate, pressure_from);
+        self.mod_matrix.advance(&mut self.matrix_phases, num_samples, sample_rate);
+        let effects = self.preset_state.effects_mut();
+        if !effects.is_empty() {
+            effects.process(&mut left[..num_samples], &mut right[..num_samples], sample_rate);
+        }
+        self.apply_output(&mut left[..num_samples], &mut right[..num_samples]);
         self.voice_pool.cleanup_finished();
+        self.preset_state.collect_garbage(&self.voice_pool);
+    }
+
+    /// Trim and saturate the rendered block and record its peak.
+    fn apply_output(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let gain = self.output.trim_gain();
+        let mut peak = 0.0_f32;
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let (mut a, mut b) = (*l * gain, *r * gain);
+            if self.output.saturate {
+                a = a.tanh();
+                b = b.tanh();
+            }
+            *l = a;
+            *r = b;
+            peak = peak.max(a.abs()).max(b.abs());
+        }
+        self.output_peak = peak;
+    }
+
+    /// Whether the slot has stopped producing sound.
+    pub fn is_silent(&self) -> bool {
+        self.active_voice_count() == 0 && !self.voice_pool.has_tail() && !self.frozen.is_playing()
     }
 
     fn render_preset(&mut self, left: &mut [f32], right: &mut [f32], num_samples: usize, sample_rate: f32) {
         let adsr = self.preset_state.envelope();
+        self.render_voices(&adsr, &mut left[..num_samples], &mut right[..num_samples], sample_rate, true);
+    }
 
-        for voice in self.voice_pool.active_voices_mut() {
-            for i in 0..num_samples {
-                // Advance envelope
-                let env = advance_envelope(voice, &adsr, sample_rate);
-                if voice.env_stage >= 4 {
-                    break;
-                }
-
-                // Generate sample from loaded zone (sampler) or fallback to sine
-                let (sample_l, sample_r) = match (voice.zone_index, self.preset_state.active_preset.as_ref()) {
-                    (Some(zi), Some(preset)) if zi < preset.zones.len() => {
-                        let zone = &preset.zones[zi];
-                        let pcm = &zone.pcm_data;
-                        let channels = zone.channels as usize;
-                        let total_frames = pcm.len() / channels;
-
-                        if total_frames == 0 || voice.sample_pos >= total_frames as f64 {
-                            // Past end of sample — mark voice finished
-                            voice.env_stage = 4;
-                            break;
-                        }
-
-                        // Linear interpolation between adjacent frames
-                        let pos = voice.sample_pos;
-                        let idx0 = pos as usize;
-                        let frac = (pos - idx0 as f64) as f32;
-                        let idx1 = (idx0 + 1).min(total_frames - 1);
-
-                        let (l, r) = if channels >= 2 {
-                            let l0 = pcm[idx0 * 2];
-                            let l1 = pcm[idx1 * 2];
-                            let r0 = pcm[idx0 * 2 + 1];
-                            let r1 = pcm[idx1 * 2 + 1];
-                            (l0 + (l1 - l0) * frac, r0 + (r1 - r0) * frac)
-                        } else {
-                            let s0 = pcm[idx0];
-                            let s1 = pcm[idx1];
-                            let s = s0 + (s1 - s0) * frac;
-                            (s, s)
-                        };
-
-                        voice.sample_pos += voice.sample_rate_ratio;
-                        (l, r)
-                    }
-                    _ => {
-                        // Pure sine fallback (no preset loaded or no matching zone)
-                        let s = (voice.phase * std::f64::consts::TAU).sin() as f32;
-                        voice.phase += voice.phase_inc;
-                        if voice.phase >= 1.0 {
-                            voice.phase -= 1.0;
-                        }
-                        (s, s)
-                    }
-                };
-
-                let gain = env * voice.velocity;
-                left[i] += sample_l * gain;
-                right[i] += sample_r * gain;
-            }
+    /// Render the active voices into the buffers, at the oversampled rate
+    /// when oversampling is on. `cut_evicted` stops voices whose preset was
+    /// evicted (preset playback) instead of letting them play the sine.
+    fn render_voices(
+        &mut self,
+        adsr: &EnvelopeParams,
+        left: &mut [f32],
+        right: &mut [f32],
+        sample_rate: f32,
+        cut_evicted: bool,
+    ) {
+        let factor = self.oversampler.factor();
+        let mut modulation = self.block_modulation(sample_rate * factor as f32);
+        let cut = if factor == 1 {
+            render_pool(&mut self.voice_pool, &self.preset_state, adsr, &modulation, left, right, cut_evicted)
+        } else {
+            // Rendered at the higher rate, voices step proportionally slower
+            modulation.pitch /= factor as f64;
+            let (pool, presets) = (&mut self.voice_pool, &self.preset_state);
+            let mut cut = (0.0, 0.0);
+            self.oversampler.process(left, right, |up_left, up_right| {
+                let c = render_pool(pool, presets, adsr, &modulation, up_left, up_right, cut_evicted);
+                cut = (cut.0 + c.0, cut.1 + c.1);
+                modulation.phase += up_left.len() as f64 * modulation.phase_inc;
+            });
+            cut
+        };
+        if cut != (0.0, 0.0) {
+            self.voice_pool.add_tail(cut);
         }
     }
 
@@ -459,66 +1412,222 @@ impl Slot {
 
         // Render the triggered voices using sampler or sine fallback
         let adsr = self.runner_state.envelope();
-        for voice in self.voice_pool.active_voices_mut() {
-            for i in 0..num_samples {
-                let env = advance_envelope(voice, &adsr, sample_rate);
-                if voice.env_stage >= 4 {
-                    break;
-                }
+        self.render_voices(&adsr, &mut left[..num_samples], &mut right[..num_samples], sample_rate, false);
+    }
+}
 
-                let (sample_l, sample_r) = match (voice.zone_index, self.preset_state.active_preset.as_ref()) {
-                    (Some(zi), Some(preset)) if zi < preset.zones.len() => {
-                        let zone = &preset.zones[zi];
-                        let pcm = &zone.pcm_data;
-                        let channels = zone.channels as usize;
-                        let total_frames = pcm.len() / channels;
+/// Render every active voice of `pool`, each from the preset it started
+/// with. Returns the last frames of voices cut because their preset was
+/// evicted (only when `cut_evicted`), for the pool's fade-out tail.
+fn render_pool(
+    pool: &mut VoicePool,
+    presets: &PresetSlotState,
+    adsr: &EnvelopeParams,
+    modulation: &BlockModulation,
+    left: &mut [f32],
+    right: &mut [f32],
+    cut_evicted: bool,
+) -> (f32, f32) {
+    let mut cut = (0.0, 0.0);
+    for voice in pool.active_voices_mut() {
+        // Voices keep rendering from the preset they started with
+        let preset = presets.preset_for(voice.preset_generation);
+        if cut_evicted && voice.zone_index.is_some() && preset.is_none() {
+            // Its preset was evicted by too many rapid swaps
+            cut.0 += voice.last_frame.0;
+            cut.1 += voice.last_frame.1;
+            voice.env_stage = 4;
+            continue;
+        }
+        let modulation = modulation.for_voice(voice);
+        render_modulated_voice(voice, preset.map(|p| &**p), adsr, &modulation, left, right, modulation.sample_rate);
+    }
+    cut
+}
 
-                        if total_frames == 0 || voice.sample_pos >= total_frames as f64 {
-                            voice.env_stage = 4;
-                            break;
-                        }
+/// Bend, mod wheel and mod matrix state for one block.
+#[derive(Clone, Copy)]
+struct BlockModulation {
+    /// Pitch ratio from bend.
+    pitch: f64,
+    /// Vibrato swing in semitones (0 = off).
+    vibrato: f32,
+    /// LFO phase at the block start and its step per sample.
+    phase: f64,
+    phase_inc: f64,
+    /// Voice filter settings with any cutoff modulation applied.
+    filter: VoiceFilterSettings,
+    /// Rate the voices render at (the host rate times any oversampling).
+    sample_rate: f32,
+    /// The slot's mod matrix, if it has any routes, and its block sources.
+    matrix: Option<ModMatrix>,
+    sources: BlockSources,
+    /// Per-channel voice gain from the matrix.
+    gain: (f32, f32),
+}
+
+impl BlockModulation {
+    /// This block's modulation with the matrix applied for one voice.
+    fn for_voice(&self, voice: &Voice) -> Self {
+        let Some(matrix) = &self.matrix else { return *self };
+        let routed = matrix.voice_modulation(&self.sources.with_voice(voice.velocity, voice.mod_random));
+        let mut modulation = *self;
+        modulation.pitch *= routed.pitch;
+        modulation.filter.cutoff_hz *= routed.cutoff;
+        modulation.gain = routed.gain;
+        modulation
+    }
+
+    /// Pitch ratio `offset` samples into the block.
+    fn pitch_at(&self, offset: usize) -> f64 {
+        if self.vibrato == 0.0 {
+            return self.pitch;
+        }
+        let lfo = controllers::lfo(self.phase + offset as f64 * self.phase_inc);
+        self.pitch * bend::ratio(self.vibrato * lfo) as f64
+    }
+}
+
+/// Render a voice in runs of `MOD_BLOCK` samples while vibrato is on, so
+/// its pitch follows the LFO, or in one go otherwise.
+fn render_modulated_voice(
+    voice: &mut Voice,
+    preset: Option<&PresetInstance>,
+    adsr: &EnvelopeParams,
+    modulation: &BlockModulation,
+    left: &mut [f32],
+    right: &mut [f32],
+    sample_rate: f32,
+) {
+    let num_samples = left.len().min(right.len());
+    let run = if modulation.vibrato == 0.0 { num_samples.max(1) } else { MOD_BLOCK };
+    let mut start = 0;
+    while start < num_samples && voice.env_stage < 4 {
+        let end = (start + run).min(num_samples);
+        render_voice(
+            voice,
+            preset,
+            adsr,
+            modulation,
+            modulation.pitch_at(start),
+            &mut left[start..end],
+            &mut right[start..end],
+            sample_rate,
+        );
+        start = end;
+    }
+}
 
-                        let pos = voice.sample_pos;
-                        let idx0 = pos as usize;
-                        let frac = (pos - idx0 as f64) as f32;
-                        let idx1 = (idx0 + 1).min(total_frames - 1);
-
-                        let (l, r) = if channels >= 2 {
-                            let l0 = pcm[idx0 * 2];
-                            let l1 = pcm[idx1 * 2];
-                            let r0 = pcm[idx0 * 2 + 1];
-                            let r1 = pcm[idx1 * 2 + 1];
-                            (l0 + (l1 - l0) * frac, r0 + (r1 - r0) * frac)
-                        } else {
-                            let s0 = pcm[idx0];
-                            let s1 = pcm[idx1];
-                            let s = s0 + (s1 - s0) * frac;
-                            (s, s)
-                        };
-
-                        voice.sample_pos += voice.sample_rate_ratio;
-                        (l, r)
+/// Render one voice into the buffers, one linear envelope segment at a time,
+/// so the envelope branches per segment rather than per sample.
+///
+/// Plays the voice's zone of `preset` if it has one, else its synth patch
+/// or a sine, through the voice filter when it is enabled. A preset-provided
+/// envelope replaces `adsr`. `pitch` scales the playback rate (bend);
+/// `modulation` gives the filter settings and the matrix gain.
+fn render_voice(
+    voice: &mut Voice,
+    preset: Option<&PresetInstance>,
+    adsr: &EnvelopeParams,
+    modulation: &BlockModulation,
+    pitch: f64,
+    left: &mut [f32],
+    right: &mut [f32],
+    sample_rate: f32,
+) {
+    let zone = match (voice.zone_index, preset) {
+        (Some(zi), Some(preset)) => preset.zones.get(zi),
+        _ => None,
+    };
+    let num_samples = left.len().min(right.len());
+    let (envelope, synth) = (voice.envelope, voice.synth);
+    let layer_gain = (voice.layer_gain.0 * modulation.gain.0, voice.layer_gain.1 * modulation.gain.1);
+    let adsr = envelope.as_ref().unwrap_or(adsr);
+    let filter = &modulation.filter;
+
+    let mut i = 0;
+    while i < num_samples {
+        let segment = next_envelope_segment(voice, adsr, sample_rate, num_samples - i);
+        if voice.env_stage >= 4 {
+            return;
+        }
+        for k in 0..segment.len {
+            let (sample_l, sample_r) = match zone {
+                Some(zone) => match sample_frame(voice, zone, pitch) {
+                    Some(frame) => frame,
+                    None => {
+                        // Past end of sample — mark voice finished
+                        voice.env_stage = 4;
+                        return;
                     }
-                    _ => {
-                        let s = (voice.phase * std::f64::consts::TAU).sin() as f32;
-                        voice.phase += voice.phase_inc;
-                        if voice.phase >= 1.0 {
-                            voice.phase -= 1.0;
-                        }
+                },
+                None => match &synth {
+                    Some(patch) => {
+                        let s = voice.synth_voice.frame(patch, voice.phase_inc * pitch);
                         (s, s)
                     }
-                };
-
-                let gain = env * voice.velocity;
-                left[i] += sample_l * gain;
-                right[i] += sample_r * gain;
-            }
+                    None => sine_frame(voice, pitch),
+                },
+            };
+            let (sample_l, sample_r) = if filter.enabled {
+                voice.filter.process((sample_l, sample_r), filter, voice.releasing, sample_rate)
+            } else {
+                (sample_l, sample_r)
+            };
+            let gain = (segment.gain + segment.step * k as f32) * voice.velocity;
+            voice.last_frame = (sample_l * gain * layer_gain.0, sample_r * gain * layer_gain.1);
+            left[i + k] += voice.last_frame.0;
+            right[i + k] += voice.last_frame.1;
         }
+        i += segment.len;
     }
 }
 
+/// Next frame of a sampler voice (linear interpolation between adjacent
+/// frames), or None once it has played past the end of the sample.
+#[inline]
+fn sample_frame(voice: &mut Voice, zone: &LoadedZone, pitch: f64) -> Option<(f32, f32)> {
+    let pcm = &zone.pcm_data;
+    let channels = (zone.channels as usize).max(1);
+    let total_frames = pcm.len() / channels;
+    if total_frames == 0 || voice.sample_pos >= total_frames as f64 {
+        return None;
+    }
+
+    let pos = voice.sample_pos;
+    let idx0 = pos as usize;
+    let frac = (pos - idx0 as f64) as f32;
+    let idx1 = (idx0 + 1).min(total_frames - 1);
+
+    let frame = if channels >= 2 {
+        let l0 = pcm[idx0 * 2];
+        let l1 = pcm[idx1 * 2];
+        let r0 = pcm[idx0 * 2 + 1];
+        let r1 = pcm[idx1 * 2 + 1];
+        (l0 + (l1 - l0) * frac, r0 + (r1 - r0) * frac)
+    } else {
+        let s0 = pcm[idx0];
+        let s1 = pcm[idx1];
+        let s = s0 + (s1 - s0) * frac;
+        (s, s)
+    };
+    voice.sample_pos += voice.sample_rate_ratio * pitch;
+    Some(frame)
+}
+
+/// Next frame of the sine fallback (no preset loaded or no matching zone).
+#[inline]
+fn sine_frame(voice: &mut Voice, pitch: f64) -> (f32, f32) {
+    let s = (voice.phase * std::f64::consts::TAU).sin() as f32;
+    voice.phase += voice.phase_inc * pitch;
+    if voice.phase >= 1.0 {
+        voice.phase -= 1.0;
+    }
+    (s, s)
+}
+
 /// ADSR envelope parameters.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct EnvelopeParams {
     pub attack_secs: f32,
     pub decay_secs: f32,
@@ -537,23 +1646,59 @@ impl Default for EnvelopeParams {
     }
 }
 
-/// Advance envelope for a voice by one sample. Returns the envelope gain.
-#[inline]
-fn advance_envelope(voice: &mut Voice, adsr: &EnvelopeParams, sample_rate: f32) -> f32 {
-    let gain = match voice.env_stage {
+/// A run of samples over which a voice's envelope is linear: sample `k` of
+/// the run has gain `gain + k * step`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct EnvSegment {
+    pub gain: f32,
+    pub step: f32,
+    pub len: usize,
+}
+
+impl EnvSegment {
+    fn hold(gain: f32, len: usize) -> Self {
+        Self { gain, step: 0.0, len }
+    }
+
+    /// Samples `pos..` of a ramp from `from` to `to` over `total` samples,
+    /// at most `max_len` of them.
+    fn ramp(pos: u32, total: u32, from: f32, to: f32, max_len: usize) -> Self {
+        let step = (to - from) / total as f32;
+        Self {
+            gain: from + step * pos as f32,
+            step,
+            len: ((total - pos) as usize).min(max_len),
+        }
+    }
+
+    fn last(&self) -> f32 {
+        self.gain + self.step * (self.len - 1) as f32
+    }
+}
+
+/// Next linear run of a voice's envelope, at most `max_len` samples, and
+/// advance the voice past it. A stage transition is a run of one sample.
+pub(crate) fn next_envelope_segment(
+    voice: &mut Voice,
+    adsr: &EnvelopeParams,
+    sample_rate: f32,
+    max_len: usize,
+) -> EnvSegment {
+    let max_len = max_len.max(1);
+    match voice.env_stage {
         0 => {
             // Attack
-            let attack_samples = (adsr.attack_secs * sample_rate) as u32;
+            let attack_samples = (adsr.attack_secs.max(voice.fade_in_secs) * sample_rate) as u32;
             if attack_samples == 0 || voice.env_samples >= attack_samples {
                 voice.env_stage = 1;
                 voice.env_samples = 0;
                 voice.env_gain = 1.0;
-                1.0
+                EnvSegment::hold(1.0, 1)
             } else {
-                let g = voice.env_samples as f32 / attack_samples as f32;
-                voice.env_gain = g;
-                voice.env_samples += 1;
-                g
+                let segment = EnvSegment::ramp(voice.env_samples, attack_samples, 0.0, 1.0, max_len);
+                voice.env_samples += segment.len as u32;
+                voice.env_gain = segment.last();
+                segment
             }
         }
         1 => {
@@ -563,37 +1708,45 @@ fn advance_envelope(voice: &mut Voice, adsr: &EnvelopeParams, sample_rate: f32)
                 voice.env_stage = 2;
                 voice.env_samples = 0;
                 voice.env_gain = adsr.sustain_level;
-                adsr.sustain_level
+                EnvSegment::hold(adsr.sustain_level, 1)
             } else {
-                let t = voice.env_samples as f32 / decay_samples as f32;
-                let g = 1.0 - t * (1.0 - adsr.sustain_level);
-                voice.env_gain = g;
-                voice.env_samples += 1;
-                g
+                let segment =
+                    EnvSegment::ramp(voice.env_samples, decay_samples, 1.0, adsr.sustain_level, max_len);
+                voice.env_samples += segment.len as u32;
+                voice.env_gain = segment.last();
+                segment
             }
         }
-        2 => {
-            // Sustain (hold until release)
-            adsr.sustain_level
-        }
+        // Sustain (hold until release)
+        2 => EnvSegment::hold(adsr.sustain_level, max_len),
         3 => {
-            // Release
-            let release_samples = (adsr.release_secs * sample_rate) as u32;
+            // Release (never shorter than the declick fade), from the gain
+            // the voice had when it was released
+            let release_secs = match (voice.declick, voice.fade_out_secs) {
+                (true, _) => DECLICK_SECS,
+                (false, Some(secs)) => secs.max(DECLICK_SECS),
+                (false, None) => adsr.release_secs.max(DECLICK_SECS),
+            };
+            let release_samples = (release_secs * sample_rate) as u32;
             if release_samples == 0 || voice.env_samples >= release_samples {
                 voice.env_stage = 4; // Done
                 voice.env_gain = 0.0;
-                0.0
+                EnvSegment::hold(0.0, 1)
             } else {
-                let t = voice.env_samples as f32 / release_samples as f32;
-                let g = voice.env_gain * (1.0 - t);
-                voice.env_samples += 1;
-                g
+                let segment =
+                    EnvSegment::ramp(voice.env_samples, release_samples, voice.env_gain, 0.0, max_len);
+                voice.env_samples += segment.len as u32;
+                segment
             }
         }
-        _ => 0.0,
-    };
+        _ => EnvSegment::hold(0.0, max_len),
+    }
+}
 
-    gain
+/// Advance envelope for a voice by one sample. Returns the envelope gain.
+#[inline]
+pub(crate) fn advance_envelope(voice: &mut Voice, adsr: &EnvelopeParams, sample_rate: f32) -> f32 {
+    next_envelope_segment(voice, adsr, sample_rate, 1).gain
 }
 
 #[cfg(test)]
@@ -605,6 +1758,7 @@ mod tests {
         SampleZone, SamplerConfig, ZonePitch,
     };
     use std::sync::Arc;
+    use crate::slots::mod_matrix::{ModSource, ModTarget};
 
     fn default_transport() -> TransportState {
         TransportState::default()
@@ -692,6 +1846,30 @@ mod tests {
         assert_eq!(pool.active_count(), 1);
     }
 
+    #[test]
+    fn voice_pool_steal_leaves_fading_tail() {
+        let mut pool = VoicePool::new(1);
+        pool.allocate(60, 0.8).unwrap().last_frame = (0.5, 0.5);
+        pool.allocate(64, 0.8);
+        assert!(pool.has_tail());
+
+        let (mut left, mut right) = (vec![0.0f32; 256], vec![0.0f32; 256]);
+        pool.render_tail(&mut left, &mut right, 256, 44100.0);
+        assert_eq!(left[0], 0.5, "tail starts at the stolen voice's output");
+        assert!(left[1] < left[0]);
+        assert_eq!(left[255], 0.0);
+        assert!(!pool.has_tail());
+    }
+
+    #[test]
+    fn kill_all_fades_instead_of_cutting() {
+        let mut pool = VoicePool::new(4);
+        pool.allocate(60, 0.8);
+        pool.kill_all();
+        let voice = pool.active_voices_mut().next().expect("voice keeps fading");
+        assert!(voice.declick && voice.env_stage == 3);
+    }
+
     #[test]
     fn voice_pool_release_all() {
         let mut pool = VoicePool::new(4);
@@ -703,6 +1881,22 @@ mod tests {
         assert_eq!(releasing, 3);
     }
 
+    #[test]
+    fn voice_pool_resize_keeps_held_notes_first() {
+        let mut old = VoicePool::new(4);
+        old.allocate(60, 0.8);
+        old.allocate(64, 0.7);
+        old.allocate(67, 0.5);
+        old.release(60);
+
+        let mut smaller = VoicePool::new(2);
+        smaller.take_voices_from(&mut old);
+        let notes: Vec<u8> = smaller.voices.iter().filter(|v| v.active).map(|v| v.note).collect();
+        assert_eq!(notes, vec![64, 67], "the releasing voice is the one cut off");
+        assert!(smaller.has_tail());
+        assert_eq!(old.active_count(), 0);
+    }
+
     // ── Slot creation ───────────────────────────────────────────
 
     #[test]
@@ -756,6 +1950,63 @@ mod tests {
         assert_eq!(slot.active_voice_count(), 1);
     }
 
+    #[test]
+    fn retrigger_crossfades_old_voice_out_and_new_one_in() {
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        slot.set_legato(LegatoSettings { retrigger: true, crossfade_ms: 10.0, ..Default::default() });
+        let transport = default_transport();
+        let note_on = |note| NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note, velocity: 0.8 };
+
+        slot.handle_midi_event(&note_on(60), &transport);
+        slot.handle_midi_event(&note_on(62), &transport);
+        slot.handle_midi_event(&note_on(60), &transport);
+        let voices: Vec<Voice> = slot.voice_pool.active_voices_mut().map(|v| v.clone()).collect();
+        let fades: Vec<_> = voices.iter().map(|v| (v.note, v.fade_out_secs, v.fade_in_secs)).collect();
+        assert_eq!(fades, vec![(60, Some(0.01), 0.0), (62, None, 0.0), (60, None, 0.01)]);
+
+        // The new note ramps in over the crossfade even with no attack,
+        // the old one fades out over it instead of its release
+        let adsr = EnvelopeParams { attack_secs: 0.0, release_secs: 2.0, ..Default::default() };
+        let mut new_voice = voices[2].clone();
+        assert!(next_envelope_segment(&mut new_voice, &adsr, 44100.0, 64).gain < 0.01);
+        let mut old_voice = Voice { env_gain: 1.0, ..voices[0].clone() };
+        let segment = next_envelope_segment(&mut old_voice, &adsr, 44100.0, 1000);
+        assert_eq!(segment.len, (0.01_f32 * 44100.0) as usize);
+
+        // Legato takes over from the held keys
+        slot.set_legato(LegatoSettings { legato: true, ..slot.legato() });
+        slot.handle_midi_event(&note_on(64), &transport);
+        let held: Vec<u8> = slot.voice_pool.active_voices_mut().filter(|v| !v.releasing).map(|v| v.note).collect();
+        assert_eq!(held, vec![64]);
+    }
+
+    #[test]
+    fn unison_voices_release_and_steal_as_a_group() {
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        slot.swap_voice_pool(VoicePool::new(4));
+        slot.set_unison(UnisonSettings { voices: 3, ..Default::default() });
+        let transport = default_transport();
+        let note_on = |note| NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note, velocity: 0.8 };
+
+        slot.handle_midi_event(&note_on(60), &transport);
+        assert_eq!(slot.active_voice_count(), 3);
+        let ratios: Vec<f64> = slot.voice_pool.active_voices_mut().map(|v| v.phase_inc).collect();
+        assert!(ratios[0] < ratios[1] && ratios[1] < ratios[2], "voices are detuned apart");
+
+        let note_off = NoteEvent::NoteOff { timing: 0, voice_id: None, channel: 0, note: 60, velocity: 0.0 };
+        slot.handle_midi_event(&note_off, &transport);
+        assert!(slot.voice_pool.active_voices_mut().all(|v| v.releasing));
+
+        // The second note needs three voices: the first group goes whole,
+        // never leaving a partial stack behind
+        slot.handle_midi_event(&note_on(64), &transport);
+        let notes: Vec<u8> = slot.voice_pool.active_voices_mut().map(|v| v.note).collect();
+        assert_eq!(notes, vec![64, 64, 64]);
+        assert!(slot.voice_pool.has_tail());
+    }
+
     // ── Envelope ────────────────────────────────────────────────
 
     #[test]
@@ -783,6 +2034,51 @@ mod tests {
         assert!(voice.env_gain >= 0.99, "after attack, gain should be ~1.0, got {}", voice.env_gain);
     }
 
+    #[test]
+    fn envelope_segments_match_per_sample() {
+        let adsr = EnvelopeParams {
+            attack_secs: 0.001,
+            decay_secs: 0.002,
+            sustain_level: 0.5,
+            release_secs: 0.004,
+        };
+        let sample_rate = 44100.0;
+        let release_at = 300;
+        let total = 600;
+        let start = Voice { active: true, env_stage: 0, ..Voice::default() };
+        let release = |v: &mut Voice| {
+            v.releasing = true;
+            v.env_stage = 3;
+            v.env_samples = 0;
+        };
+
+        let mut voice = start.clone();
+        let mut per_sample = Vec::new();
+        for i in 0..total {
+            if i == release_at {
+                release(&mut voice);
+            }
+            per_sample.push(advance_envelope(&mut voice, &adsr, sample_rate));
+        }
+
+        // Same envelope in 64-sample blocks
+        let mut voice = start;
+        let mut segmented = Vec::new();
+        while segmented.len() < total {
+            if segmented.len() == release_at {
+                release(&mut voice);
+            }
+            let block_end = (segmented.len() / 64 + 1) * 64;
+            let limit = if segmented.len() < release_at { block_end.min(release_at) } else { block_end };
+            let segment = next_envelope_segment(&mut voice, &adsr, sample_rate, limit - segmented.len());
+            segmented.extend((0..segment.len).map(|k| segment.gain + segment.step * k as f32));
+        }
+
+        for (i, (a, b)) in per_sample.iter().zip(&segmented).enumerate() {
+            assert!((a - b).abs() < 1e-5, "sample {i}: per-sample {a}, segmented {b}");
+        }
+    }
+
     #[test]
     fn envelope_release_to_zero() {
         let mut voice = Voice::default();
@@ -815,6 +2111,112 @@ mod tests {
         assert_eq!(voice.env_stage, 4, "voice should be off after release");
     }
 
+    fn cc(cc: u8, value: f32) -> NoteEvent<()> {
+        NoteEvent::MidiCC { timing: 0, channel: 0, cc, value }
+    }
+
+    #[test]
+    fn sustain_pedal_holds_released_notes() {
+        let mut slot = Slot::new(0);
+        let transport = default_transport();
+        let on = NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note: 60, velocity: 0.8 };
+        let off = NoteEvent::NoteOff { timing: 0, voice_id: None, channel: 0, note: 60, velocity: 0.0 };
+        slot.handle_midi_event(&cc(64, 1.0), &transport);
+        slot.handle_midi_event(&on, &transport);
+        slot.handle_midi_event(&off, &transport);
+        assert!(slot.voice_pool_mut().active_voices_mut().all(|v| v.sustained && !v.releasing));
+
+        slot.handle_midi_event(&cc(64, 0.0), &transport);
+        assert!(slot.voice_pool_mut().active_voices_mut().all(|v| v.releasing && !v.sustained));
+    }
+
+    #[test]
+    fn hold_mode_toggles_notes_and_releases_on_clear() {
+        let mut slot = Slot::new(0);
+        let transport = default_transport();
+        slot.set_hold(true, &transport);
+        let on = |note| NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note, velocity: 0.8 };
+        let off = |note| NoteEvent::NoteOff { timing: 0, voice_id: None, channel: 0, note, velocity: 0.0 };
+        let releasing = |slot: &mut Slot, note| {
+            slot.voice_pool_mut().active_voices_mut().filter(|v| v.note == note).all(|v| v.releasing)
+        };
+
+        for note in [60, 64] {
+            slot.handle_midi_event(&on(note), &transport);
+            slot.handle_midi_event(&off(note), &transport);
+        }
+        assert_eq!(slot.held_count(), 2);
+        assert!(!releasing(&mut slot, 60) && !releasing(&mut slot, 64));
+
+        // Pressing a latched key again lets it go
+        slot.handle_midi_event(&on(60), &transport);
+        assert!(releasing(&mut slot, 60) && !releasing(&mut slot, 64));
+        assert_eq!(slot.held_count(), 1);
+
+        slot.release_held(&transport);
+        assert!(releasing(&mut slot, 64));
+        assert_eq!(slot.held_count(), 0);
+    }
+
+    #[test]
+    fn frozen_slot_plays_its_clip_on_note_on() {
+        let mut slot = Slot::new(0);
+        let transport = default_transport();
+        let clip = Arc::new(FrozenClip { left: vec![0.5; 1000], right: vec![0.25; 1000], sample_rate: 44100.0 });
+        slot.set_frozen_clip(Some(clip));
+        let on = NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note: 60, velocity: 0.8 };
+        slot.handle_midi_event(&on, &transport);
+        assert_eq!(slot.active_voice_count(), 0, "no voices while frozen");
+
+        let (mut left, mut right) = (vec![0.0; 64], vec![0.0; 64]);
+        slot.render(&mut left, &mut right, 64, 44100.0, &transport);
+        assert!(left.iter().all(|s| *s == 0.5) && right.iter().all(|s| *s == 0.25));
+
+        slot.set_frozen_clip(None);
+        slot.handle_midi_event(&on, &transport);
+        assert_eq!(slot.active_voice_count(), 1);
+    }
+
+    #[test]
+    fn drum_kit_ignores_note_off_and_chokes_hi_hats() {
+        let mut slot = Slot::new(0);
+        let transport = default_transport();
+        slot.preset_state_mut().is_drum_kit = true;
+        let hit = |note| NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note, velocity: 0.8 };
+        let off = NoteEvent::NoteOff { timing: 0, voice_id: None, channel: 0, note: 46, velocity: 0.0 };
+        slot.handle_midi_event(&hit(46), &transport);
+        slot.handle_midi_event(&off, &transport);
+        assert!(slot.voice_pool_mut().active_voices_mut().all(|v| !v.releasing));
+
+        // Closed hi-hat cuts the open one
+        slot.handle_midi_event(&hit(42), &transport);
+        let open = slot.voice_pool_mut().active_voices_mut().find(|v| v.note == 46).unwrap();
+        assert!(open.declick && open.releasing);
+    }
+
+    #[test]
+    fn all_sound_off_fades_and_resets_sustain() {
+        let mut slot = Slot::new(0);
+        let transport = default_transport();
+        let on = NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note: 60, velocity: 0.8 };
+        slot.handle_midi_event(&cc(64, 1.0), &transport);
+        slot.handle_midi_event(&on, &transport);
+        slot.handle_midi_event(&cc(crate::midi::ALL_SOUND_OFF, 0.0), &transport);
+        assert!(!slot.preset_state().sustain);
+        assert!(slot.voice_pool_mut().active_voices_mut().all(|v| v.declick && v.releasing));
+    }
+
+    #[test]
+    fn all_notes_off_releases_sustained_notes() {
+        let mut slot = Slot::new(0);
+        let transport = default_transport();
+        let on = NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note: 60, velocity: 0.8 };
+        slot.handle_midi_event(&cc(64, 1.0), &transport);
+        slot.handle_midi_event(&on, &transport);
+        slot.handle_midi_event(&cc(crate::midi::ALL_NOTES_OFF, 0.0), &transport);
+        assert!(slot.voice_pool_mut().active_voices_mut().all(|v| v.releasing && !v.declick));
+    }
+
     // ── Rendering ───────────────────────────────────────────────
 
     #[test]
@@ -841,6 +2243,112 @@ mod tests {
         assert!(energy > 0.0, "sine fallback should produce non-zero audio");
     }
 
+    #[test]
+    fn mod_matrix_routes_velocity_to_gain_and_pan() {
+        let transport = default_transport();
+        let render = |matrix: ModMatrix| {
+            let mut slot = Slot::new(0);
+            slot.initialize(44100.0);
+            slot.set_mod_matrix(matrix);
+            let note_on = NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note: 69, velocity: 1.0 };
+            slot.handle_midi_event(&note_on, &transport);
+            let (mut left, mut right) = (vec![0.0f32; 256], vec![0.0f32; 256]);
+            slot.render(&mut left, &mut right, 256, 44100.0, &transport);
+            let energy = |buf: &[f32]| buf.iter().map(|s| s * s).sum::<f32>();
+            (energy(&left), energy(&right))
+        };
+
+        let (dry_left, dry_right) = render(ModMatrix::default());
+        assert!(dry_left > 0.0 && dry_right > 0.0);
+
+        let mut matrix = ModMatrix::default();
+        matrix.set_depth(ModSource::Velocity, ModTarget::Pan, 1.0);
+        let (left, right) = render(matrix);
+        assert_eq!(left, 0.0, "full velocity pans hard right");
+        assert!((right - dry_right).abs() < dry_right * 1e-3);
+
+        matrix.set_depth(ModSource::Velocity, ModTarget::Gain, -1.0);
+        assert_eq!(render(matrix), (0.0, 0.0));
+    }
+
+    #[test]
+    fn expression_scales_output_and_mod_wheel_adds_vibrato() {
+        let transport = default_transport();
+        let note_on = NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note: 69, velocity: 1.0 };
+        let render = |cc: Option<(u8, f32)>| {
+            let mut slot = Slot::new(0);
+            slot.initialize(44100.0);
+            if let Some((cc, value)) = cc {
+                slot.handle_midi_event(&NoteEvent::MidiCC { timing: 0, channel: 0, cc, value }, &transport);
+            }
+            slot.handle_midi_event(&note_on, &transport);
+            let (mut left, mut right) = (vec![0.0f32; 512], vec![0.0f32; 512]);
+            slot.render(&mut left, &mut right, 512, 44100.0, &transport);
+            let phase = slot.voice_pool.active_voices_mut().next().map(|v| v.phase).unwrap();
+            (left, phase)
+        };
+
+        let (dry, dry_phase) = render(None);
+        let (quiet, _) = render(Some((crate::midi::EXPRESSION, 0.5)));
+        for (d, q) in dry.iter().zip(&quiet) {
+            assert!((d * 0.5 - q).abs() < 1e-6);
+        }
+        let (_, vibrato_phase) = render(Some((crate::midi::MOD_WHEEL, 1.0)));
+        assert!((vibrato_phase - dry_phase).abs() > 1e-6, "vibrato should move the pitch");
+    }
+
+    #[test]
+    fn aftertouch_swell_ramps_up_smoothly() {
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        let transport = default_transport();
+        slot.set_controllers(ControllerSettings {
+            pressure_destination: PressureDestination::Swell,
+            pressure_depth: 1.0,
+            ..Default::default()
+        });
+        let note_on = NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note: 69, velocity: 1.0 };
+        slot.handle_midi_event(&note_on, &transport);
+        let (mut left, mut right) = (vec![0.0f32; 256], vec![0.0f32; 256]);
+        slot.render(&mut left, &mut right, 256, 44100.0, &transport);
+
+        let pressure = NoteEvent::MidiChannelPressure { timing: 0, channel: 0, pressure: 1.0 };
+        slot.handle_midi_event(&pressure, &transport);
+        left.fill(0.0);
+        right.fill(0.0);
+        slot.render(&mut left, &mut right, 256, 44100.0, &transport);
+        assert!(slot.pressure > 0.0 && slot.pressure < 1.0, "pressure is smoothed, not jumped");
+        for _ in 0..20 {
+            left.fill(0.0);
+            right.fill(0.0);
+            slot.render(&mut left, &mut right, 256, 44100.0, &transport);
+        }
+        let peak = left.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+        assert!(peak > 1.5, "full pressure should swell toward +6 dB, got {peak}");
+    }
+
+    #[test]
+    fn output_trim_and_saturation_bound_the_peak() {
+        let transport = default_transport();
+        let render_peak = |output: SlotOutput| {
+            let mut slot = Slot::new(0);
+            slot.initialize(44100.0);
+            slot.set_output(output);
+            let note_on = NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note: 69, velocity: 1.0 };
+            slot.handle_midi_event(&note_on, &transport);
+            let mut left = vec![0.0f32; 2048];
+            let mut right = vec![0.0f32; 2048];
+            slot.render(&mut left, &mut right, 2048, 44100.0, &transport);
+            slot.output_peak()
+        };
+        let unity = render_peak(SlotOutput::default());
+        let boosted = render_peak(SlotOutput { trim_db: 12.0, saturate: false });
+        assert!((boosted / unity - SlotOutput { trim_db: 12.0, saturate: false }.trim_gain()).abs() < 1e-3);
+        assert!(boosted > 1.0);
+        let saturated = render_peak(SlotOutput { trim_db: 12.0, saturate: true });
+        assert!(saturated < 1.0);
+    }
+
     #[test]
     fn render_sampler_reads_pcm_data() {
         let mut slot = Slot::new(0);
@@ -1180,6 +2688,164 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tuning_scales_playback_rate() {
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        let transport = default_transport();
+        let preset = make_test_preset(make_sine_pcm(440.0, 44100, 44100), 69, 44100);
+        slot.preset_state_mut().load_preset(Arc::new("test/tune".to_string()), preset);
+        slot.set_tuning(SlotTuning { coarse: 12, fine: -100.0, width: 1.0 });
+
+        let note_on = NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note: 69, velocity: 0.8 };
+        slot.handle_midi_event(&note_on, &transport);
+        let rate = slot.voice_pool.active_voices_mut().find(|v| v.note == 69).map(|v| v.sample_rate_ratio).unwrap();
+        let expected = 2.0_f64.powf(11.0 / 12.0);
+        assert!((rate - expected).abs() < 1e-6, "+11 semitones should give {expected}, got {rate}");
+    }
+
+    #[test]
+    fn pitch_bend_scales_playback_and_sample_start() {
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        let transport = default_transport();
+        let preset = make_test_preset(make_sine_pcm(440.0, 44100, 44100), 69, 44100);
+        slot.preset_state_mut().load_preset(Arc::new("test/bend".to_string()), preset);
+        slot.set_bend(BendSettings { range: Some(12), destination: BendDestination::Pitch });
+
+        let bend_up = NoteEvent::MidiPitchBend { timing: 0, channel: 0, value: 1.0 };
+        slot.handle_midi_event(&bend_up, &transport);
+        let note_on = NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note: 69, velocity: 0.8 };
+        slot.handle_midi_event(&note_on, &transport);
+        let (mut left, mut right) = (vec![0.0f32; 256], vec![0.0f32; 256]);
+        slot.render(&mut left, &mut right, 256, 44100.0, &transport);
+        let pos = slot.voice_pool.active_voices_mut().next().map(|v| v.sample_pos).unwrap();
+        assert!((pos - 512.0).abs() < 1e-6, "an octave up plays twice as fast, got {pos}");
+
+        // Sample start: a full bend starts the note halfway in
+        slot.panic();
+        slot.render(&mut left, &mut right, 256, 44100.0, &transport);
+        slot.set_bend(BendSettings { range: None, destination: BendDestination::SampleStart });
+        slot.handle_midi_event(&note_on, &transport);
+        let started = slot.voice_pool.active_voices_mut().find(|v| !v.releasing).map(|v| v.sample_pos).unwrap();
+        assert_eq!(started, 22050.0);
+    }
+
+    #[test]
+    fn synth_graph_plays_oscillators_and_effects() {
+        use crate::preset::graph::PresetGraph;
+        use crate::slots::effects::{Effect, EffectChain};
+        use crate::slots::synth::{Oscillator, Waveform};
+
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        let transport = default_transport();
+        // A descriptor with no sample zones
+        let preset = Arc::new(PresetInstance {
+            descriptor: test_preset_descriptor(test_sample_zone()),
+            zones: vec![],
+        });
+        slot.preset_state_mut().load_preset(Arc::new("test/synth".to_string()), preset);
+        let mut synth = SynthPatch::default();
+        synth.push(Oscillator { waveform: Waveform::Square, ..Default::default() });
+        synth.envelope = Some(EnvelopeParams { attack_secs: 0.0, decay_secs: 0.0, sustain_level: 1.0, release_secs: 0.1 });
+        let mut effects = EffectChain::default();
+        effects.push(Effect::Gain { gain: 0.5 });
+        slot.preset_state_mut().set_graph(PresetGraph { synth: Some(synth), effects, ..Default::default() });
+
+        let note_on = NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note: 69, velocity: 1.0 };
+        slot.handle_midi_event(&note_on, &transport);
+        let mut left = vec![0.0; 512];
+        let mut right = vec![0.0; 512];
+        slot.render(&mut left, &mut right, 512, 44100.0, &transport);
+        // A square wave at full sustain through the half-gain stage sits at
+        // ±0.5 between edges (a sine would pass through every level)
+        let flat = left[100..512].iter().filter(|s| (s.abs() - 0.5).abs() < 1e-3).count();
+        assert!(flat > 300, "expected a square at ±0.5, {} flat samples", flat);
+
+        // Loading another preset drops the nodes
+        let preset = make_test_preset(make_sine_pcm(440.0, 44100, 44100), 69, 44100);
+        slot.preset_state_mut().load_preset(Arc::new("test/sampler".to_string()), preset);
+        assert!(slot.preset_state().synth.is_none());
+    }
+
+    #[test]
+    fn composite_layers_apply_key_range_gain_and_pan() {
+        use crate::preset::graph::{Layer, PresetGraph};
+
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        let transport = default_transport();
+        let preset = make_test_preset(make_sine_pcm(440.0, 44100, 44100), 69, 44100);
+        slot.preset_state_mut().load_preset(Arc::new("test/layers".to_string()), preset);
+        let mut graph = PresetGraph::default();
+        graph.layers.push(Layer { zone_count: 1, gain: 0.5, pan: 1.0, key_low: 60, key_high: 72, ..Default::default() });
+        slot.preset_state_mut().set_graph(graph);
+        assert_eq!(slot.preset_state().layers().len(), 1);
+
+        // Outside the layer's keys nothing plays
+        let note_on = |note| NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note, velocity: 1.0 };
+        slot.handle_midi_event(&note_on(50), &transport);
+        assert_eq!(slot.active_voice_count(), 0);
+
+        slot.handle_midi_event(&note_on(69), &transport);
+        assert_eq!(slot.active_voice_count(), 1);
+        let mut left = vec![0.0; 512];
+        let mut right = vec![0.0; 512];
+        slot.render(&mut left, &mut right, 512, 44100.0, &transport);
+        // Panned hard right at half gain
+        assert!(left.iter().all(|s| *s == 0.0));
+        let peak = right.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+        assert!(peak > 0.01 && peak <= 0.5, "{}", peak);
+
+        // Layers that don't match the preset's zones are dropped
+        let mut graph = PresetGraph::default();
+        graph.layers.push(Layer { zone_count: 3, ..Default::default() });
+        slot.preset_state_mut().set_graph(graph);
+        assert!(slot.preset_state().layers().is_empty());
+    }
+
+    #[test]
+    fn zone_envelope_replaces_slot_envelope() {
+        use crate::preset::graph::PresetGraph;
+
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        let transport = default_transport();
+        let preset = make_test_preset(make_sine_pcm(440.0, 44100, 44100), 69, 44100);
+        slot.preset_state_mut().load_preset(Arc::new("test/envelope".to_string()), preset);
+        let note_on = NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note: 69, velocity: 1.0 };
+        let peak = |slot: &mut Slot| {
+            let mut left = vec![0.0; 1024];
+            let mut right = vec![0.0; 1024];
+            slot.render(&mut left, &mut right, 1024, 44100.0, &transport);
+            left.iter().fold(0.0_f32, |m, s| m.max(s.abs()))
+        };
+
+        // The slot's 10 ms attack is done within the block
+        slot.handle_midi_event(&note_on, &transport);
+        assert!(peak(&mut slot) > 0.5);
+        slot.all_notes_off();
+        for _ in 0..100 {
+            peak(&mut slot);
+        }
+
+        // The preset's one-second attack has barely started
+        let mut graph = PresetGraph::default();
+        graph.envelopes.push_zone(Some(EnvelopeParams { attack_secs: 1.0, ..Default::default() }));
+        slot.preset_state_mut().set_graph(graph);
+        assert!(slot.preset_state().zone_envelope(0).is_some());
+        slot.handle_midi_event(&note_on, &transport);
+        assert!(peak(&mut slot) < 0.05);
+    }
+
+    #[test]
+    fn tuning_is_clamped() {
+        let mut slot = Slot::new(0);
+        slot.set_tuning(SlotTuning { coarse: 100, fine: 500.0, width: 9.0 });
+        assert_eq!(slot.tuning(), SlotTuning { coarse: 24, fine: 100.0, width: 2.0 });
+    }
+
     #[test]
     fn preset_load_unload_stops_audio() {
         let mut slot = Slot::new(0);
@@ -1208,14 +2874,157 @@ mod tests {
         // Unload preset — removes the active_preset
         slot.preset_state_mut().unload_preset();
 
-        // Render another block — voices still active but fall back to sine
-        // (they won't crash even without a preset)
+        // Render another block — sounding voices keep the retired preset
+        // until they finish
         let mut left2 = vec![0.0f32; 256];
         let mut right2 = vec![0.0f32; 256];
         slot.render(&mut left2, &mut right2, 256, 44100.0, &transport);
-        // Should still produce audio (sine fallback)
         let energy_after: f32 = left2.iter().map(|s| s * s).sum();
-        assert!(energy_after > 0.0, "sine fallback should still produce audio after unload");
+        assert!(energy_after > 0.0, "sounding voice should still produce audio after unload");
+    }
+
+    #[test]
+    fn preset_swap_keeps_sounding_voices_on_old_preset() {
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        let transport = default_transport();
+
+        let old = make_test_preset(make_sine_pcm(440.0, 44100, 44100), 69, 44100);
+        slot.preset_state_mut()
+            .load_preset(Arc::new("test/old".to_string()), old.clone());
+        slot.handle_midi_event(&NoteEvent::NoteOn {
+            timing: 0, voice_id: None, channel: 0, note: 69, velocity: 0.8,
+        }, &transport);
+
+        // Swap while the note is held
+        let new = make_test_preset(make_sine_pcm(220.0, 44100, 44100), 57, 44100);
+        slot.preset_state_mut()
+            .load_preset(Arc::new("test/new".to_string()), new.clone());
+        slot.handle_midi_event(&NoteEvent::NoteOn {
+            timing: 0, voice_id: None, channel: 0, note: 60, velocity: 0.8,
+        }, &transport);
+
+        let old_gen = slot.voice_pool.active_voices_mut()
+            .find(|v| v.note == 69).map(|v| v.preset_generation).unwrap();
+        let new_gen = slot.voice_pool.active_voices_mut()
+            .find(|v| v.note == 60).map(|v| v.preset_generation).unwrap();
+        assert!(Arc::ptr_eq(slot.preset_state().preset_for(old_gen).unwrap(), &old));
+        assert!(Arc::ptr_eq(slot.preset_state().preset_for(new_gen).unwrap(), &new));
+
+        let mut left = vec![0.0f32; 256];
+        let mut right = vec![0.0f32; 256];
+        slot.render(&mut left, &mut right, 256, 44100.0, &transport);
+        assert_eq!(slot.active_voice_count(), 2, "old voice should keep playing");
+        assert_eq!(slot.preset_state().retired_count(), 1);
+
+        // Once the old voice ends, the retired preset is released
+        slot.voice_pool_mut().kill_all();
+        slot.render(&mut left, &mut right, 256, 44100.0, &transport);
+        assert_eq!(slot.preset_state().retired_count(), 0);
+        assert_eq!(Arc::strong_count(&old), 1, "slot should no longer hold the old preset");
+    }
+
+    #[test]
+    fn retired_preset_is_sent_to_garbage_channel() {
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        let (tx, rx) = crossbeam_channel::bounded(4);
+        slot.preset_state_mut().set_garbage_sender(tx);
+
+        let old = make_test_preset(make_sine_pcm(440.0, 44100, 4410), 69, 44100);
+        let new = make_test_preset(make_sine_pcm(440.0, 44100, 4410), 69, 44100);
+        slot.preset_state_mut().load_preset(Arc::new("a".to_string()), old.clone());
+        slot.preset_state_mut().load_preset(Arc::new("b".to_string()), new);
+
+        // No voices reference the old preset: the next render hands it off
+        let mut left = vec![0.0f32; 64];
+        let mut right = vec![0.0f32; 64];
+        slot.render(&mut left, &mut right, 64, 44100.0, &default_transport());
+        match rx.try_recv() {
+            Ok(crate::perf::garbage::Garbage::Preset(p)) => assert!(Arc::ptr_eq(&p, &old)),
+            Ok(_) => panic!("expected the old preset"),
+            Err(_) => panic!("old preset should be queued for collection"),
+        }
+    }
+
+    #[test]
+    fn replaced_preset_waits_for_a_busy_collector() {
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        let (tx, rx) = crossbeam_channel::bounded(1);
+        slot.preset_state_mut().set_garbage_sender(tx.clone());
+
+        let old = make_test_preset(make_sine_pcm(440.0, 44100, 4410), 69, 44100);
+        let purged = make_test_preset(make_sine_pcm(440.0, 44100, 4410), 69, 44100);
+        slot.preset_state_mut().load_preset(Arc::new("a".to_string()), old.clone());
+        let generation = slot.preset_state().generation();
+
+        // The collector's channel is full: the old preset is kept, unplayable
+        let filler = make_test_preset(make_sine_pcm(440.0, 44100, 64), 69, 44100);
+        tx.try_send(crate::perf::garbage::Garbage::Preset(filler)).unwrap();
+        assert!(slot.preset_state_mut().replace_zones(purged.clone()));
+        assert_eq!(slot.preset_state().retired_count(), 1);
+        assert!(Arc::ptr_eq(slot.preset_state().preset_for(generation).unwrap(), &purged));
+
+        // Once there is room, the next render hands it off
+        let _ = rx.try_recv();
+        let mut left = vec![0.0f32; 64];
+        let mut right = vec![0.0f32; 64];
+        slot.render(&mut left, &mut right, 64, 44100.0, &default_transport());
+        match rx.try_recv() {
+            Ok(crate::perf::garbage::Garbage::Preset(p)) => assert!(Arc::ptr_eq(&p, &old)),
+            Ok(_) => panic!("expected the old preset"),
+            Err(_) => panic!("old preset should be queued for collection"),
+        }
+        assert_eq!(slot.preset_state().retired_count(), 0);
+    }
+
+    #[test]
+    fn full_retired_list_keeps_the_active_preset() {
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        // A collector that never has room
+        let (tx, _rx) = crossbeam_channel::bounded(0);
+        slot.preset_state_mut().set_garbage_sender(tx);
+        let preset = || make_test_preset(make_sine_pcm(440.0, 44100, 64), 69, 44100);
+        slot.preset_state_mut().load_preset(Arc::new("a".to_string()), preset());
+
+        let mut replaced = 0;
+        while slot.preset_state().has_room() {
+            assert!(slot.preset_state_mut().replace_zones(preset()));
+            replaced += 1;
+        }
+        let active = slot.preset_state().active_preset.clone().unwrap();
+        assert!(!slot.preset_state_mut().replace_zones(preset()));
+        assert!(!slot.preset_state_mut().load_preset(Arc::new("b".to_string()), preset()));
+        assert!(!slot.preset_state_mut().unload_preset());
+        assert!(Arc::ptr_eq(slot.preset_state().active_preset.as_ref().unwrap(), &active));
+        assert_eq!(slot.preset_state().retired_count(), replaced);
+    }
+
+    #[test]
+    fn rapid_swaps_evict_oldest_retired_preset() {
+        use super::super::preset_slot::MAX_RETIRED_PRESETS;
+
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        let transport = default_transport();
+        let preset = || make_test_preset(make_sine_pcm(440.0, 44100, 44100), 69, 44100);
+
+        // Hold one note per preset so none can be released
+        for i in 0..=MAX_RETIRED_PRESETS + 1 {
+            slot.preset_state_mut().load_preset(Arc::new(format!("p{i}")), preset());
+            slot.handle_midi_event(&NoteEvent::NoteOn {
+                timing: 0, voice_id: None, channel: 0, note: 60 + i as u8, velocity: 0.8,
+            }, &transport);
+        }
+        assert_eq!(slot.preset_state().retired_count(), MAX_RETIRED_PRESETS);
+
+        // The voice whose preset was evicted stops; the others keep playing
+        let mut left = vec![0.0f32; 64];
+        let mut right = vec![0.0f32; 64];
+        slot.render(&mut left, &mut right, 64, 44100.0, &transport);
+        assert_eq!(slot.active_voice_count(), MAX_RETIRED_PRESETS + 1);
     }
 
     #[test]
@@ -1247,4 +3056,50 @@ mod tests {
         let peak = left.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
         assert!(peak > 0.01, "mixed output should have audible level, peak={peak}");
     }
+
+    #[test]
+    fn arpeggiator_defers_notes_to_render() {
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        let transport = default_transport();
+        slot.set_arp_settings(ArpSettings { enabled: true, ..ArpSettings::default() });
+
+        for note in [60, 64] {
+            let note_on = NoteEvent::NoteOn {
+                timing: 0, voice_id: None, channel: 0, note, velocity: 0.8,
+            };
+            slot.handle_midi_event(&note_on, &transport);
+        }
+        assert_eq!(slot.active_voice_count(), 0, "held notes should not play directly");
+
+        let mut left = vec![0.0f32; 256];
+        let mut right = vec![0.0f32; 256];
+        slot.render(&mut left, &mut right, 256, 44100.0, &transport);
+        assert_eq!(slot.active_voice_count(), 1, "arpeggiator plays one step at a time");
+    }
+
+    #[test]
+    fn keyswitch_selects_articulation_silently() {
+        use crate::slots::keyswitch::Articulation;
+
+        let mut slot = Slot::new(0);
+        slot.initialize(44100.0);
+        let transport = default_transport();
+        let preset = make_test_preset(make_sine_pcm(440.0, 44100, 4410), 69, 44100);
+        slot.preset_state_mut().load_preset(Arc::new("test/ks".to_string()), preset);
+        slot.set_keyswitches(KeyswitchMap::from_articulations(&[
+            Articulation { key: 24, name: "Sustain".into(), layer: 0 },
+            Articulation { key: 25, name: "Staccato".into(), layer: 1 },
+        ]));
+        assert_eq!(slot.active_keyswitch(), Some(24), "first articulation is the default");
+
+        let key = |note| NoteEvent::NoteOn { timing: 0, voice_id: None, channel: 0, note, velocity: 0.8 };
+        slot.handle_midi_event(&key(25), &transport);
+        assert_eq!(slot.active_keyswitch(), Some(25));
+        assert_eq!(slot.active_voice_count(), 0, "keyswitches make no sound");
+
+        slot.handle_midi_event(&key(24), &transport);
+        slot.handle_midi_event(&key(69), &transport);
+        assert_eq!(slot.active_voice_count(), 1);
+    }
 }
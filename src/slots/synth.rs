@@ -0,0 +1,205 @@
+//! Oscillator voices for synth-category presets.
+//!
+//! A synth node describes a small stack of oscillators (waveform, pitch
+//! offset, level) and optionally its own envelope. The patch is `Copy` and
+//! copied into each voice at note-on, so replacing the preset never
+//! changes notes that are already sounding. Saw and square use PolyBLEP
+//! correction to keep aliasing down without oversampling.
+
+use super::slot::EnvelopeParams;
+
+/// Oscillators per patch; further ones in a descriptor are ignored.
+pub const MAX_OSCILLATORS: usize = 4;
+
+/// Oscillator waveform.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Waveform {
+    #[default]
+    Sine,
+    Triangle,
+    Saw,
+    Square,
+    Noise,
+}
+
+impl Waveform {
+    /// Parse a descriptor waveform name ("sawtooth", "saw", "pulse", …).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sine" | "sin" => Some(Self::Sine),
+            "triangle" | "tri" => Some(Self::Triangle),
+            "sawtooth" | "saw" => Some(Self::Saw),
+            "square" | "pulse" => Some(Self::Square),
+            "noise" | "white" => Some(Self::Noise),
+            _ => None,
+        }
+    }
+}
+
+/// One oscillator of a patch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oscillator {
+    pub waveform: Waveform,
+    /// Frequency relative to the note (octave, semitone and detune offsets).
+    pub ratio: f64,
+    /// Output level (linear).
+    pub level: f32,
+    /// Duty cycle of the square wave (0.05–0.95).
+    pub pulse_width: f32,
+}
+
+impl Default for Oscillator {
+    fn default() -> Self {
+        Self {
+            waveform: Waveform::Sine,
+            ratio: 1.0,
+            level: 1.0,
+            pulse_width: 0.5,
+        }
+    }
+}
+
+impl Oscillator {
+    /// Frequency ratio for an offset in semitones and cents.
+    pub fn ratio_for(semitones: f64, cents: f64) -> f64 {
+        2.0_f64.powf((semitones + cents / 100.0) / 12.0)
+    }
+}
+
+/// Oscillator stack of a synth preset.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SynthPatch {
+    oscillators: [Oscillator; MAX_OSCILLATORS],
+    count: usize,
+    /// Envelope the preset asks for (None = the slot's).
+    pub envelope: Option<EnvelopeParams>,
+}
+
+impl SynthPatch {
+    /// Add an oscillator; false if the stack is full.
+    pub fn push(&mut self, oscillator: Oscillator) -> bool {
+        if self.count == MAX_OSCILLATORS {
+            return false;
+        }
+        self.oscillators[self.count] = oscillator;
+        self.count += 1;
+        true
+    }
+
+    pub fn oscillators(&self) -> &[Oscillator] {
+        &self.oscillators[..self.count]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+}
+
+/// Per-voice oscillator state.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SynthVoice {
+    /// Phase of each oscillator (0..1).
+    phases: [f64; MAX_OSCILLATORS],
+    /// Noise generator state (xorshift).
+    noise: u32,
+}
+
+impl SynthVoice {
+    /// Start a new note. `seed` decorrelates the noise of voices.
+    pub fn reset(&mut self, seed: u32) {
+        self.phases = [0.0; MAX_OSCILLATORS];
+        self.noise = seed | 1;
+    }
+
+    /// Next sample of the stack. `phase_inc` is the note's frequency over
+    /// the sample rate.
+    #[inline]
+    pub fn frame(&mut self, patch: &SynthPatch, phase_inc: f64) -> f32 {
+        let mut out = 0.0;
+        for (osc, phase) in patch.oscillators().iter().zip(self.phases.iter_mut()) {
+            let dt = (phase_inc * osc.ratio).min(0.5);
+            let t = *phase;
+            let s = match osc.waveform {
+                Waveform::Sine => (t * std::f64::consts::TAU).sin() as f32,
+                Waveform::Triangle => (1.0 - 4.0 * (t - 0.5).abs()) as f32,
+                Waveform::Saw => (2.0 * t - 1.0 - poly_blep(t, dt)) as f32,
+                Waveform::Square => {
+                    let width = osc.pulse_width.clamp(0.05, 0.95) as f64;
+                    let high = if t < width { 1.0 } else { -1.0 };
+                    let fall = (t - width).rem_euclid(1.0);
+                    (high + poly_blep(t, dt) - poly_blep(fall, dt)) as f32
+                }
+                Waveform::Noise => {
+                    self.noise ^= self.noise << 13;
+                    self.noise ^= self.noise >> 17;
+                    self.noise ^= self.noise << 5;
+                    self.noise as f32 / u32::MAX as f32 * 2.0 - 1.0
+                }
+            };
+            out += s * osc.level;
+            *phase += dt;
+            if *phase >= 1.0 {
+                *phase -= 1.0;
+            }
+        }
+        out
+    }
+}
+
+/// PolyBLEP residual for a unit step at phase 0, with phase step `dt`.
+#[inline]
+fn poly_blep(t: f64, dt: f64) -> f64 {
+    if dt <= 0.0 {
+        0.0
+    } else if t < dt {
+        let x = t / dt;
+        x + x - x * x - 1.0
+    } else if t > 1.0 - dt {
+        let x = (t - 1.0) / dt;
+        x * x + x + x + 1.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stack_is_bounded() {
+        let mut patch = SynthPatch::default();
+        for _ in 0..MAX_OSCILLATORS {
+            assert!(patch.push(Oscillator::default()));
+        }
+        assert!(!patch.push(Oscillator::default()));
+        assert_eq!(patch.oscillators().len(), MAX_OSCILLATORS);
+        assert_eq!(Waveform::from_name("Sawtooth"), Some(Waveform::Saw));
+        assert_eq!(Waveform::from_name("wobble"), None);
+    }
+
+    #[test]
+    fn test_waveforms_stay_in_range_and_follow_pitch() {
+        for waveform in [Waveform::Sine, Waveform::Triangle, Waveform::Saw, Waveform::Square, Waveform::Noise] {
+            let mut patch = SynthPatch::default();
+            patch.push(Oscillator { waveform, ..Default::default() });
+            let mut voice = SynthVoice::default();
+            voice.reset(7);
+            let peak = (0..4410)
+                .map(|_| voice.frame(&patch, 440.0 / 44100.0).abs())
+                .fold(0.0_f32, f32::max);
+            assert!(peak > 0.5 && peak < 1.2, "{:?}: {}", waveform, peak);
+        }
+
+        // An octave up crosses zero twice as often
+        let crossings = |ratio: f64| {
+            let mut patch = SynthPatch::default();
+            patch.push(Oscillator { ratio, ..Default::default() });
+            let mut voice = SynthVoice::default();
+            let samples: Vec<f32> = (0..44100).map(|_| voice.frame(&patch, 100.0 / 44100.0)).collect();
+            samples.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count()
+        };
+        let (base, octave) = (crossings(1.0), crossings(Oscillator::ratio_for(12.0, 0.0)));
+        assert!(octave.abs_diff(2 * base) <= 1, "{} vs {}", octave, base);
+    }
+}
@@ -0,0 +1,114 @@
+//! Per-slot unison: each note-on starts several detuned voices spread
+//! across the stereo field.
+//!
+//! The voices of one note-on form a group in the voice pool, so a note-off
+//! releases them together and stealing takes the whole group. The detune
+//! is symmetric, the outer voices at ±`detune_cents`, and each voice is
+//! nudged by a small random amount per note so repeated notes don't phase
+//! the same way.
+
+use serde::{Deserialize, Serialize};
+
+/// Most voices per note.
+pub const MAX_UNISON_VOICES: u8 = 8;
+
+/// Widest detune of the outer voices, in cents.
+pub const MAX_DETUNE_CENTS: f32 = 100.0;
+
+/// Random nudge per voice, as a fraction of the spacing between voices.
+const HUMANIZE: f32 = 0.25;
+
+/// Unison settings of a slot.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnisonSettings {
+    /// Voices per note (1–8; 1 = off).
+    pub voices: u8,
+    /// Detune of the outer voices in cents (0–100).
+    pub detune_cents: f32,
+    /// Stereo spread of the outer voices (0 = centre, 1 = hard left/right).
+    pub spread: f32,
+}
+
+impl Default for UnisonSettings {
+    fn default() -> Self {
+        Self {
+            voices: 1,
+            detune_cents: 15.0,
+            spread: 0.5,
+        }
+    }
+}
+
+/// Pitch and gain of one unison voice.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnisonVoice {
+    /// Playback rate factor.
+    pub ratio: f64,
+    /// Per-channel gain (pan and level compensation together).
+    pub gain: (f32, f32),
+}
+
+impl UnisonSettings {
+    /// Settings clamped to their ranges.
+    pub fn clamped(self) -> Self {
+        Self {
+            voices: self.voices.clamp(1, MAX_UNISON_VOICES),
+            detune_cents: self.detune_cents.clamp(0.0, MAX_DETUNE_CENTS),
+            spread: self.spread.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.voices > 1
+    }
+
+    /// Voice `index` of `voices`, nudged by `random` (−1..1). Voices are
+    /// spaced evenly from the lowest (left) to the highest (right); the
+    /// level is scaled so the stack sounds about as loud as one voice.
+    pub fn voice(&self, index: u8, random: f32) -> UnisonVoice {
+        let count = self.voices.max(1);
+        if count == 1 {
+            return UnisonVoice { ratio: 1.0, gain: (1.0, 1.0) };
+        }
+        let position = index.min(count - 1) as f32 / (count - 1) as f32 * 2.0 - 1.0;
+        let spacing = 2.0 / (count - 1) as f32;
+        let cents = (position + random * spacing * HUMANIZE) * self.detune_cents;
+        let pan = position * self.spread;
+        let level = 1.0 / (count as f32).sqrt();
+        UnisonVoice {
+            ratio: 2.0_f64.powf(cents as f64 / 1200.0),
+            gain: (level * (1.0 - pan).min(1.0), level * (1.0 + pan).min(1.0)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unison_voices_are_symmetric() {
+        let unison = UnisonSettings { voices: 3, detune_cents: 12.0, spread: 1.0 };
+        let low = unison.voice(0, 0.0);
+        let mid = unison.voice(1, 0.0);
+        let high = unison.voice(2, 0.0);
+        assert!((low.ratio * high.ratio - 1.0).abs() < 1e-9);
+        assert!((high.ratio - 2.0_f64.powf(0.01)).abs() < 1e-9);
+        assert_eq!(mid.ratio, 1.0);
+        // Hard left, centre, hard right at −4.8 dB each
+        let level = 1.0 / 3.0_f32.sqrt();
+        assert_eq!(low.gain, (level, 0.0));
+        assert_eq!(mid.gain, (level, level));
+        assert_eq!(high.gain, (0.0, level));
+
+        // The nudge stays within a quarter of the spacing
+        let nudged = unison.voice(1, 1.0);
+        assert!((nudged.ratio - 2.0_f64.powf(0.25 * 12.0 / 1200.0)).abs() < 1e-9);
+
+        let off = UnisonSettings::default();
+        assert!(!off.is_active());
+        assert_eq!(off.voice(0, 0.7), UnisonVoice { ratio: 1.0, gain: (1.0, 1.0) });
+        assert_eq!(UnisonSettings { voices: 20, ..off }.clamped().voices, MAX_UNISON_VOICES);
+    }
+}
@@ -0,0 +1,148 @@
+//! Zones a slot has played this session, for purging the unused ones.
+//!
+//! The audio thread marks each zone it starts a voice on. Purging swaps in
+//! a copy of the preset whose unplayed zones hold no PCM (the zone layout
+//! stays, so voice and layer indexes keep lining up). A note that lands on
+//! a purged zone is marked missing; the editor then reloads the preset in
+//! the background and purges again, keeping the zone that was asked for.
+//!
+//! Only the first `MAX_TRACKED_ZONES` zones are tracked; the rest are
+//! treated as used and never purged.
+
+use std::sync::Arc;
+
+use songwalker_core::preset::instance::{LoadedZone, PresetInstance};
+
+/// Zones tracked per slot.
+pub const MAX_TRACKED_ZONES: usize = 2048;
+/// Words of a zone bitset.
+pub const ZONE_WORDS: usize = MAX_TRACKED_ZONES / 64;
+
+/// Played and missing zones of a slot's preset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZoneUsage {
+    pub used: [u64; ZONE_WORDS],
+    /// Played while purged (no PCM), waiting for a reload.
+    pub missing: [u64; ZONE_WORDS],
+}
+
+impl Default for ZoneUsage {
+    fn default() -> Self {
+        Self { used: [0; ZONE_WORDS], missing: [0; ZONE_WORDS] }
+    }
+}
+
+impl ZoneUsage {
+    pub fn mark_used(&mut self, zone: usize) {
+        if zone < MAX_TRACKED_ZONES {
+            self.used[zone / 64] |= 1 << (zone % 64);
+        }
+    }
+
+    pub fn mark_missing(&mut self, zone: usize) {
+        if zone < MAX_TRACKED_ZONES {
+            self.missing[zone / 64] |= 1 << (zone % 64);
+        }
+    }
+
+    pub fn is_used(&self, zone: usize) -> bool {
+        zone >= MAX_TRACKED_ZONES || self.used[zone / 64] & (1 << (zone % 64)) != 0
+    }
+
+    pub fn any_missing(&self) -> bool {
+        self.missing.iter().any(|w| *w != 0)
+    }
+
+    pub fn clear_missing(&mut self) {
+        self.missing = [0; ZONE_WORDS];
+    }
+
+    /// Forget everything (a different preset was loaded).
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Whether a zone's PCM was purged.
+pub fn is_purged(zone: &LoadedZone) -> bool {
+    zone.pcm_data.is_empty()
+}
+
+/// Copy of `instance` without the PCM of the zones `usage` hasn't seen
+/// played, and the bytes that frees. `None` when nothing would be freed.
+/// PCM shared with other presets (see `sample_cache`) is freed only once
+/// every holder lets go.
+pub fn purged_copy(instance: &PresetInstance, usage: &ZoneUsage) -> Option<(PresetInstance, usize)> {
+    let mut freed = 0;
+    let zones = instance
+        .zones
+        .iter()
+        .enumerate()
+        .map(|(i, zone)| {
+            let keep = usage.is_used(i);
+            if !keep {
+                freed += zone.pcm_data.len() * std::mem::size_of::<f32>();
+            }
+            LoadedZone {
+                zone: zone.zone.clone(),
+                pcm_data: if keep { zone.pcm_data.clone() } else { Arc::from(Vec::new()) },
+                channels: zone.channels,
+                sample_rate: zone.sample_rate,
+            }
+        })
+        .collect();
+    (freed > 0).then(|| (PresetInstance { descriptor: instance.descriptor.clone(), zones }, freed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use songwalker_core::preset::{
+        AudioCodec, AudioReference, KeyRange, PresetCategory, PresetDescriptor, PresetNode, SampleZone, SamplerConfig,
+        ZonePitch,
+    };
+
+    fn zone(pcm: Vec<f32>) -> LoadedZone {
+        let zone = SampleZone {
+            key_range: KeyRange { low: 0, high: 127 },
+            velocity_range: None,
+            pitch: ZonePitch { root_note: 60, fine_tune_cents: 0.0 },
+            sample_rate: 44100,
+            r#loop: None,
+            audio: AudioReference::External { url: "a.mp3".into(), codec: AudioCodec::Mp3, sha256: None },
+        };
+        LoadedZone { zone, pcm_data: Arc::from(pcm), channels: 1, sample_rate: 44100 }
+    }
+
+    #[test]
+    fn test_purged_copy_keeps_played_zones() {
+        let instance = PresetInstance {
+            descriptor: PresetDescriptor {
+                format: None, version: None,
+                id: "p".into(), name: "P".into(),
+                category: PresetCategory::Sampler,
+                tags: vec![], metadata: None, tuning: None,
+                graph: PresetNode::Sampler {
+                    config: SamplerConfig { zones: vec![], is_drum_kit: false, envelope: None },
+                },
+            },
+            zones: vec![zone(vec![0.1; 8]), zone(vec![0.2; 4])],
+        };
+        let mut usage = ZoneUsage::default();
+        usage.mark_used(1);
+        assert!(usage.is_used(1) && !usage.is_used(0));
+        assert!(usage.is_used(MAX_TRACKED_ZONES), "untracked zones count as used");
+
+        let (purged, freed) = purged_copy(&instance, &usage).unwrap();
+        assert_eq!(freed, 8 * 4);
+        assert!(is_purged(&purged.zones[0]));
+        assert!(Arc::ptr_eq(&purged.zones[1].pcm_data, &instance.zones[1].pcm_data));
+
+        usage.mark_used(0);
+        assert!(purged_copy(&instance, &usage).is_none(), "nothing left to free");
+        usage.mark_missing(0);
+        assert!(usage.any_missing());
+        usage.clear_missing();
+        assert!(!usage.any_missing());
+    }
+}
@@ -5,27 +5,52 @@
 
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::AtomicU32;
+use std::time::{Duration, Instant};
 
 use eframe::egui;
 use nih_plug::prelude::NoteEvent;
 
 use crate::editor;
 use crate::editor::visualizer::VisualizerState;
-use crate::editor::{DeviceState, EditorEvent, EditorState, EditorTab, PresetLoadedEvent};
+use crate::editor::loads::LoadTarget;
+use crate::editor::{DeviceState, EditorState, PresetLoadedEvent};
+use crate::jobs::JobPool;
+use crate::midi::SysEx;
+use crate::midi::gm::{self, GmRequest};
+use crate::midi::rules::{self, FiredRule};
+use crate::monitor::EngineMonitor;
 use crate::preset::manager::PresetManager;
-use crate::state::PluginState;
+use crate::state::{EditorLayout, PluginState};
 
 use super::audio_backend::AudioBackend;
+use super::link::LinkSettings;
+use super::metronome::MetronomeSettings;
 use super::midi_backend::MidiBackend;
+use super::osc::{OscCommand, OscServer, OscSettings};
 use super::params::{StandaloneGlobalParams, StandaloneParams};
+use super::recovery::{self, Autosaver, Recovery, TransportSettings};
+use super::session::{self, RestoreMode, StandaloneConfig};
+use super::transport::{LoopRegion, TransportCommand};
 
 /// Run the standalone application.
 pub fn run() {
+    // Open at the last window size, or the default size at the saved zoom
+    let config = StandaloneConfig::load();
+    let zoom = super::layout::load().zoom.clamp(0.5, 2.0);
+    let (size, min_size) = if config.performance_mode {
+        (config.performance_window_size.unwrap_or(PERFORMANCE_SIZE), PERFORMANCE_MIN_SIZE)
+    } else {
+        (config.window_size.unwrap_or([800.0 * zoom, 600.0 * zoom]), FULL_MIN_SIZE)
+    };
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size(size)
+        .with_min_inner_size(min_size)
+        .with_title("SongWalker");
+    if config.always_on_top {
+        viewport = viewport.with_always_on_top();
+    }
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([800.0, 600.0])
-            .with_min_inner_size([400.0, 300.0])
-            .with_title("SongWalker"),
+        viewport,
         ..Default::default()
     };
 
@@ -52,56 +77,139 @@ pub fn run() {
                 ));
             }
 
-            Ok(Box::new(StandaloneApp::new()))
+            Ok(Box::new(StandaloneApp::new(config)))
         }),
     );
 }
 
+/// How often the rack is written to `session.json` while it changes.
+const SESSION_SAVE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Smallest full editor window.
+const FULL_MIN_SIZE: [f32; 2] = [400.0, 300.0];
+
+/// Performance mode window until resized, and its smallest size.
+const PERFORMANCE_SIZE: [f32; 2] = [560.0, 220.0];
+const PERFORMANCE_MIN_SIZE: [f32; 2] = [320.0, 140.0];
+
+/// How long a window resize may take before its size is recorded anyway.
+const RESIZE_SETTLE: Duration = Duration::from_secs(1);
+
+/// How long after a stream (re)start safe mode ignores xruns.
+const XRUN_SETTLE: Duration = Duration::from_secs(2);
+
 /// The standalone eframe application.
 struct StandaloneApp {
     editor_state: EditorState,
     params: StandaloneParams,
     audio_backend: AudioBackend,
     midi_backend: MidiBackend,
+    /// Running OSC listener, if enabled.
+    osc_server: Option<OscServer>,
+    osc_tx: crossbeam_channel::Sender<OscCommand>,
+    osc_rx: crossbeam_channel::Receiver<OscCommand>,
+    /// Tempo last shown in the header, to tell UI edits from Link changes.
+    last_tempo: f32,
+    /// Layout last written to `layout.json`.
+    saved_layout: EditorLayout,
+    /// Config as last written to `standalone.toml`, and as it is now.
+    saved_config: StandaloneConfig,
+    config: StandaloneConfig,
+    /// Last session's rack, while the restore prompt is showing.
+    pending_session: Option<PluginState>,
+    /// "Don't ask again" in the restore prompt.
+    remember_restore_choice: bool,
+    /// Rack as last written to `session.json`, and when it was checked.
+    saved_session: Vec<u8>,
+    session_checked: Instant,
+    /// Autosave of a run that crashed, while the recovery prompt is showing.
+    pending_recovery: Option<Recovery>,
+    /// Writes `recovery.json`; taken on a clean exit.
+    autosaver: Option<Autosaver>,
+    /// Size requested on a performance mode switch, until the window
+    /// reaches it; the size isn't recorded meanwhile.
+    pending_resize: Option<(egui::Vec2, Instant)>,
+    /// Xruns already handled by safe mode, and when the stream last
+    /// restarted (xruns while it settles are ignored).
+    xruns_seen: u32,
+    stream_started: Instant,
     /// Whether the app has been initialized (first frame).
     initialized: bool,
 }
 
 impl StandaloneApp {
-    fn new() -> Self {
+    fn new(config: StandaloneConfig) -> Self {
         let params = StandaloneParams::default();
 
         // Create channels
-        let (event_tx, event_rx) = crossbeam_channel::bounded::<EditorEvent>(64);
+        let (event_tx, event_rx) = editor::event_queue::channel();
         let (audio_preset_loaded_tx, audio_preset_loaded_rx) =
             crossbeam_channel::bounded::<PresetLoadedEvent>(16);
         let (ui_preset_loaded_tx, ui_preset_loaded_rx) =
             crossbeam_channel::unbounded::<PresetLoadedEvent>();
-        let (midi_tx, midi_rx) = crossbeam_channel::bounded::<NoteEvent<()>>(256);
+        let (midi_tx, midi_rx) = crossbeam_channel::bounded::<NoteEvent<SysEx>>(256);
+        let (rule_tx, rule_rx) =
+            crossbeam_channel::bounded::<FiredRule>(rules::RULE_CHANNEL_CAPACITY);
+        let (gm_tx, gm_rx) = crossbeam_channel::bounded::<GmRequest>(gm::GM_CHANNEL_CAPACITY);
 
         let visualizer_state = Arc::new(VisualizerState::new(512));
         let voice_count = Arc::new(AtomicU32::new(0));
+        let monitor = Arc::new(EngineMonitor::new());
         let preset_manager = Arc::new(Mutex::new(PresetManager::new()));
-        let plugin_state = Arc::new(Mutex::new(PluginState::default()));
+        if let Some(url) = &config.library_url {
+            if let Ok(mut pm) = preset_manager.lock() {
+                pm.base_url = url.clone();
+            }
+        }
+        let layout = super::layout::load();
+        let plugin_state = Arc::new(Mutex::new(PluginState { layout, ..Default::default() }));
         let status_text = Arc::new(Mutex::new(String::new()));
+        let jobs = Arc::new(JobPool::default());
+        jobs.start();
+        rules::spawn_dispatcher(
+            rule_rx,
+            jobs.clone(),
+            preset_manager.clone(),
+            plugin_state.clone(),
+            audio_preset_loaded_tx.clone(),
+            status_text.clone(),
+        );
+        gm::spawn_loader(
+            gm_rx,
+            jobs.clone(),
+            preset_manager.clone(),
+            plugin_state.clone(),
+            audio_preset_loaded_tx.clone(),
+            status_text.clone(),
+        );
 
         // Create audio backend
-        let audio_backend = AudioBackend::new(
+        let mut audio_backend = AudioBackend::new(
             48000.0,
             midi_rx,
             event_rx,
             audio_preset_loaded_rx,
+            rule_tx,
+            gm_tx,
             params.clone(),
             visualizer_state.clone(),
             voice_count.clone(),
+            monitor.clone(),
         );
 
+        audio_backend.set_buffer_frames(config.buffer.frames);
+
         // Create MIDI backend
         let midi_backend = MidiBackend::new(midi_tx);
 
         // Enumerate devices for the Settings UI
         let audio_devices = AudioBackend::enumerate_devices();
         let midi_devices = MidiBackend::enumerate_inputs();
+        let osc_settings = OscSettings::load();
+        let metronome = MetronomeSettings::load();
+        audio_backend.set_metronome(metronome);
+        let link_settings = LinkSettings::load();
+        audio_backend.set_link_enabled(link_settings.enabled && super::link::available());
         let audio_device_names: Vec<String> = audio_devices.iter().map(|d| d.name.clone()).collect();
 
         let device_state = DeviceState {
@@ -110,18 +218,55 @@ impl StandaloneApp {
             midi_input_names: midi_devices,
             selected_midi_idx: None,
             pending_audio_switch: None,
+            buffer_settings: config.buffer,
+            pending_buffer: None,
+            latency: audio_backend.latency(),
             pending_midi_switch: None,
             needs_refresh: false,
+            osc_settings,
+            // Started on the first frame like audio
+            pending_osc: osc_settings.enabled.then_some(osc_settings),
+            osc_status: String::new(),
+            tempo: params.tempo_value(),
+            metronome,
+            pending_metronome: None,
+            count_in_requested: false,
+            count_in_beats: audio_backend.count_in_beats(),
+            time_signature: (4, 4),
+            loop_region: LoopRegion::default(),
+            pending_transport: Vec::new(),
+            transport_display: audio_backend.transport_display(),
+            link_settings,
+            pending_link: None,
+            link_status: audio_backend.link_status(),
+            restore_session: config.restore_session,
+            performance_mode: config.performance_mode,
+            always_on_top: config.always_on_top,
         };
+        let (osc_tx, osc_rx) = crossbeam_channel::unbounded::<OscCommand>();
 
         let editor_state = EditorState {
             egui_state: None, // standalone — no nih-plug EguiState
             preset_manager: preset_manager.clone(),
             plugin_state,
-            current_tab: EditorTab::SlotRack,
+            current_tab: layout.tab,
             browser_state: editor::browser::BrowserState::default(),
             slot_rack_state: editor::slot_rack::SlotRackState::default(),
-            piano_state: editor::piano::PianoState::default(),
+            compile_state: editor::compile::CompileState::default(),
+            loads: editor::loads::LoadManager::default(),
+            network: editor::network::NetworkState::default(),
+            midi_rules: editor::midi_rules::MidiRulesState::default(),
+            patch_export: editor::patch_export::PatchExportState::default(),
+            local_library: editor::local_library::LocalLibraryState::default(),
+            web_bridge: editor::web_bridge::WebBridgeState::default(),
+            slot_clipboard: editor::slot_clipboard::SlotClipboardState::default(),
+            quick_load: editor::quick_load::QuickLoadState::default(),
+            midi_capture: editor::midi_capture::MidiCaptureState::default(),
+            surprise: editor::surprise::SurpriseState::default(),
+            freeze: editor::freeze::FreezeState::default(),
+            purge: editor::purge::PurgeState::default(),
+            download_check: editor::download_check::DownloadCheckState::default(),
+            piano_state: editor::piano::PianoState::new(layout.piano_visible),
             event_tx,
             audio_preset_loaded_tx: audio_preset_loaded_tx.clone(),
             ui_preset_loaded_tx,
@@ -129,29 +274,315 @@ impl StandaloneApp {
             status_text,
             visualizer_state,
             voice_count,
-            zoom_level: 1.0,
+            monitor,
+            jobs,
+            zoom_level: layout.zoom.clamp(0.5, 2.0),
             resize_drag_start: None,
             active_presets_ui: std::collections::HashMap::new(),
             device_state: Some(Box::new(device_state)),
+            slot_params_seen: Default::default(),
+            focus: Default::default(),
+            log_panel: Default::default(),
+            midi_monitor: Default::default(),
+            onboarding: Default::default(),
+            theme: Default::default(),
+            accessibility: Default::default(),
+            layout,
         };
 
         // Start background preset refresh
-        PresetManager::start_background_refresh(preset_manager);
+        crate::preset::library_index::start_background_refresh(&editor_state.jobs, preset_manager.clone());
+        crate::preset::indexer::spawn_indexer(&editor_state.jobs, preset_manager.clone());
+        crate::preset::revalidate::spawn_revalidation(&editor_state.jobs, preset_manager);
 
-        Self {
+        // An autosave left by a crash is newer than the last session
+        let crashed = recovery::start_run();
+        let pending_recovery = if crashed { recovery::load() } else { None };
+        let last_session = match config.restore_session {
+            RestoreMode::Ask | RestoreMode::Always if pending_recovery.is_none() => session::load_session(),
+            _ => None,
+        };
+        let mut app = Self {
             editor_state,
+            last_tempo: params.tempo_value(),
             params,
             audio_backend,
             midi_backend,
+            osc_server: None,
+            osc_tx,
+            osc_rx,
+            saved_layout: layout,
+            saved_config: config.clone(),
+            config,
+            pending_session: None,
+            remember_restore_choice: false,
+            saved_session: Vec::new(),
+            session_checked: Instant::now(),
+            pending_recovery,
+            autosaver: Some(Autosaver::start()),
+            pending_resize: None,
+            xruns_seen: 0,
+            stream_started: Instant::now(),
             initialized: false,
+        };
+        if let Some(last) = last_session {
+            if app.config.restore_session == RestoreMode::Always {
+                app.restore_session(last);
+            } else {
+                app.pending_session = Some(last);
+            }
+        }
+        app
+    }
+
+    /// Replace the rack with a saved one and load its presets.
+    fn restore_session(&mut self, mut last: PluginState) {
+        let state = &mut self.editor_state;
+        let Ok(mut ps) = state.plugin_state.lock() else { return };
+        // The layout file already has the latest layout
+        last.layout = ps.layout;
+        *ps = last;
+        let _ = state.event_tx.try_send(editor::EditorEvent::SetMacros { assignments: Arc::new(ps.macros.clone()) });
+        // Each slot's mix, MIDI and sound settings, and the groups
+        for event in crate::view_model::sync_all(&ps) {
+            let _ = state.event_tx.try_send(event);
+        }
+        for (slot_index, config) in ps.slot_configs.iter().enumerate() {
+            if let Some((library, path)) = config.preset_id.as_deref().and_then(|id| id.split_once('/')) {
+                state.loads.request(
+                    &state.jobs,
+                    &state.preset_manager,
+                    LoadTarget::Slot(slot_index),
+                    library,
+                    path,
+                    slot_index,
+                    None,
+                );
+            }
+            if !config.source_code.trim().is_empty() {
+                state.compile_state.schedule(slot_index);
+            }
+        }
+        log::info!("[Standalone] Restored {} slots from the last session", ps.slot_configs.len());
+        self.saved_session = ps.to_bytes();
+    }
+
+    /// "Restore last session?" prompt, shown until answered.
+    fn draw_restore_prompt(&mut self, ctx: &egui::Context) {
+        let Some(last) = &self.pending_session else { return };
+        let slots = last.slot_configs.len();
+        let mut answer = None;
+        egui::Window::new("Restore last session?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(match slots {
+                    1 => "The last session had 1 slot.".to_string(),
+                    n => format!("The last session had {} slots.", n),
+                });
+                ui.checkbox(&mut self.remember_restore_choice, "Don't ask again");
+                ui.horizontal(|ui| {
+                    if ui.button("Restore").clicked() {
+                        answer = Some(true);
+                    }
+                    if ui.button("Start empty").clicked() {
+                        answer = Some(false);
+                    }
+                });
+            });
+        let Some(restore) = answer else { return };
+        if self.remember_restore_choice {
+            let mode = if restore { RestoreMode::Always } else { RestoreMode::Never };
+            if let Some(ref mut ds) = self.editor_state.device_state {
+                ds.restore_session = mode;
+            }
+        }
+        if let Some(last) = self.pending_session.take() {
+            if restore {
+                self.restore_session(last);
+            }
+        }
+    }
+
+    /// "Recover unsaved session?" prompt after a crash, shown until
+    /// answered.
+    fn draw_recovery_prompt(&mut self, ctx: &egui::Context) {
+        let Some(autosave) = &self.pending_recovery else { return };
+        let slots = autosave.state.slot_configs.len();
+        let mut answer = None;
+        egui::Window::new("Recover unsaved session?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("SongWalker didn't close properly last time.");
+                ui.label(format!(
+                    "A session with {} slot{} was autosaved {}.",
+                    slots,
+                    if slots == 1 { "" } else { "s" },
+                    autosave.age_text(),
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Recover").clicked() {
+                        answer = Some(true);
+                    }
+                    if ui.button("Discard").clicked() {
+                        answer = Some(false);
+                    }
+                });
+            });
+        let Some(recover) = answer else { return };
+        let Some(autosave) = self.pending_recovery.take() else { return };
+        if !recover {
+            return;
+        }
+        self.restore_session(autosave.state);
+        if let Some(ref mut ds) = self.editor_state.device_state {
+            let transport = autosave.transport;
+            // Picked up as a header edit by `handle_device_commands`
+            ds.tempo = transport.tempo;
+            ds.time_signature = transport.time_signature;
+            ds.loop_region = transport.loop_region;
+            let (numerator, denominator) = transport.time_signature;
+            ds.pending_transport.push(TransportCommand::TimeSignature { numerator, denominator });
+            ds.pending_transport.push(TransportCommand::Loop(transport.loop_region));
+        }
+    }
+
+    /// Hand the rack and transport settings to the autosave thread when
+    /// due, and stop autosaving on a clean exit. Nothing is autosaved
+    /// while a restore prompt is up, so the autosave being offered stays.
+    fn autosave(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.viewport().close_requested()) {
+            if let Some(autosaver) = self.autosaver.take() {
+                autosaver.finish();
+            }
+            return;
+        }
+        if self.pending_recovery.is_some() || self.pending_session.is_some() {
+            return;
+        }
+        let Some(autosaver) = &mut self.autosaver else { return };
+        if !autosaver.due() {
+            return;
+        }
+        let Some(ref ds) = self.editor_state.device_state else { return };
+        let transport = TransportSettings {
+            tempo: ds.tempo,
+            time_signature: ds.time_signature,
+            loop_region: ds.loop_region,
+        };
+        let Ok(state) = self.editor_state.plugin_state.lock().map(|ps| ps.clone()) else { return };
+        autosaver.submit(state, transport);
+    }
+
+    /// Apply the header's performance mode and always-on-top toggles to
+    /// the window. Each layout keeps its own window size.
+    fn apply_window_mode(&mut self, ctx: &egui::Context) {
+        let Some(ref ds) = self.editor_state.device_state else { return };
+        if ds.always_on_top != self.config.always_on_top {
+            self.config.always_on_top = ds.always_on_top;
+            let level = if ds.always_on_top { egui::WindowLevel::AlwaysOnTop } else { egui::WindowLevel::Normal };
+            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(level));
+        }
+        if ds.performance_mode != self.config.performance_mode {
+            self.config.performance_mode = ds.performance_mode;
+            let (size, min_size) = if ds.performance_mode {
+                (self.config.performance_window_size.unwrap_or(PERFORMANCE_SIZE), PERFORMANCE_MIN_SIZE)
+            } else {
+                let zoom = self.editor_state.zoom_level;
+                (self.config.window_size.unwrap_or([800.0 * zoom, 600.0 * zoom]), FULL_MIN_SIZE)
+            };
+            let size = egui::Vec2::from(size);
+            ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(egui::Vec2::from(min_size)));
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+            self.pending_resize = Some((size, Instant::now()));
+        }
+    }
+
+    /// Write the config and rack when they change. The rack is left alone
+    /// while a restore prompt is up, so the last session isn't lost.
+    fn save_session_and_config(&mut self, ctx: &egui::Context) {
+        let (pointer_down, close_requested, window_size) = ctx.input(|i| {
+            let viewport = i.viewport();
+            let native_ppp = viewport.native_pixels_per_point.unwrap_or(1.0);
+            let size = viewport
+                .inner_rect
+                .map(|r| r.size() * i.pixels_per_point / native_ppp);
+            (i.pointer.any_down(), viewport.close_requested(), size)
+        });
+
+        if let Some(ref ds) = self.editor_state.device_state {
+            self.config.restore_session = ds.restore_session;
+        }
+        if let Ok(pm) = self.editor_state.preset_manager.lock() {
+            if self.config.library_url.as_deref() != Some(pm.base_url.as_str()) {
+                self.config.library_url = Some(pm.base_url.clone());
+            }
+        }
+        if let Some(size) = window_size.filter(|s| s.x > 0.0 && s.y > 0.0) {
+            let settled = match self.pending_resize {
+                Some((target, since)) => (size - target).length() < 1.0 || since.elapsed() >= RESIZE_SETTLE,
+                None => true,
+            };
+            if settled {
+                self.pending_resize = None;
+                let size = Some([size.x.round(), size.y.round()]);
+                if self.config.performance_mode {
+                    self.config.performance_window_size = size;
+                } else {
+                    self.config.window_size = size;
+                }
+            }
+        }
+        if self.config != self.saved_config && (!pointer_down || close_requested) {
+            self.saved_config = self.config.clone();
+            if let Err(e) = self.config.save() {
+                log::warn!("[Standalone] {e}");
+            }
+        }
+
+        if self.pending_session.is_some() || self.pending_recovery.is_some() {
+            return;
+        }
+        if close_requested || self.session_checked.elapsed() >= SESSION_SAVE_INTERVAL {
+            self.session_checked = Instant::now();
+            let bytes = match self.editor_state.plugin_state.lock() {
+                Ok(ps) => ps.to_bytes(),
+                Err(_) => return,
+            };
+            if bytes != self.saved_session {
+                if let Err(e) = session::save_session(&bytes) {
+                    log::warn!("[Standalone] {e}");
+                }
+                self.saved_session = bytes;
+            }
         }
     }
 
-    /// Start audio on the default device (called on first frame).
+    /// Start audio on the saved device, or the default one (called on
+    /// first frame), and reconnect the saved MIDI input.
     fn initialize_audio(&mut self) {
-        match self.audio_backend.start_default() {
+        let saved = self.config.audio_device.clone().and_then(|name| {
+            match self.audio_backend.start_named(&name) {
+                Ok(()) => Some(name),
+                Err(e) => {
+                    log::warn!("[Standalone] {e}; using the default device");
+                    None
+                }
+            }
+        });
+        let started = match saved {
+            Some(name) => Ok(name),
+            None => self.audio_backend.start_default(),
+        };
+        self.stream_started = Instant::now();
+        self.reconnect_midi();
+        match started {
             Ok(name) => {
                 log::info!("[Standalone] Audio started on: {name}");
+                self.config.audio_device = Some(name.clone());
                 // Update selected device in UI
                 if let Some(ref mut ds) = self.editor_state.device_state {
                     if let Some(idx) = ds.audio_device_names.iter().position(|n| n == &name) {
@@ -168,21 +599,82 @@ impl StandaloneApp {
         }
     }
 
+    /// Connect the MIDI input saved in the config, if it is present.
+    fn reconnect_midi(&mut self) {
+        let Some(name) = self.config.midi_device.clone() else { return };
+        let Some(ref mut ds) = self.editor_state.device_state else { return };
+        let Some(idx) = ds.midi_input_names.iter().position(|n| *n == name) else {
+            log::warn!("[Standalone] MIDI input '{name}' not found");
+            return;
+        };
+        match self.midi_backend.connect(&name) {
+            Ok(()) => {
+                log::info!("[Standalone] MIDI connected: {name}");
+                ds.selected_midi_idx = Some(idx);
+            }
+            Err(e) => log::error!("[Standalone] MIDI connect failed: {e}"),
+        }
+    }
+
     /// Handle pending device switch commands from the Settings UI.
     fn handle_device_commands(&mut self) {
-        let (audio_switch, midi_switch, needs_refresh) = {
+        let (audio_switch, buffer, midi_switch, needs_refresh, osc_settings) = {
             let Some(ref mut ds) = self.editor_state.device_state else { return };
+            // Push header edits; otherwise show the audio side's tempo,
+            // which Link peers may have changed
+            if ds.tempo != self.last_tempo {
+                self.params.set_tempo(ds.tempo);
+            } else {
+                ds.tempo = self.params.tempo_value();
+            }
+            self.last_tempo = ds.tempo;
+            if let Some(settings) = ds.pending_link.take() {
+                if let Err(e) = settings.save() {
+                    log::error!("[Standalone] {e}");
+                }
+                self.audio_backend.set_link_enabled(settings.enabled);
+            }
+            if let Some(settings) = ds.pending_metronome.take() {
+                if let Err(e) = settings.save() {
+                    log::error!("[Standalone] {e}");
+                }
+                self.audio_backend.set_metronome(settings);
+            }
+            if std::mem::take(&mut ds.count_in_requested) {
+                self.audio_backend.count_in();
+            }
+            for command in ds.pending_transport.drain(..) {
+                self.audio_backend.send_transport(command);
+            }
             (
                 ds.pending_audio_switch.take(),
+                ds.pending_buffer.take(),
                 ds.pending_midi_switch.take(),
                 std::mem::replace(&mut ds.needs_refresh, false),
+                ds.pending_osc.take(),
             )
         };
 
+        if let Some(settings) = osc_settings {
+            self.apply_osc_settings(settings);
+        }
+
+        if let Some(settings) = buffer {
+            let frames_changed = settings.frames != self.config.buffer.frames;
+            self.config.buffer = settings;
+            self.audio_backend.set_buffer_frames(settings.frames);
+            // A device switch below restarts the stream anyway
+            if frames_changed && audio_switch.is_none() {
+                self.apply_buffer_frames(settings.frames);
+            }
+        }
+
         if let Some(device_name) = audio_switch {
+            self.stream_started = Instant::now();
             match self.audio_backend.switch_device(&device_name) {
                 Ok(()) => {
                     log::info!("[Standalone] Switched audio to: {device_name}");
+                    self.config.audio_device = Some(device_name.clone());
                     if let Ok(mut s) = self.editor_state.status_text.lock() {
                         *s = format!("Audio: {device_name}");
                     }
@@ -199,10 +691,12 @@ impl StandaloneApp {
         if let Some(ref device_name) = midi_switch {
             if device_name.is_empty() {
                 self.midi_backend.disconnect();
+                self.config.midi_device = None;
             } else {
                 match self.midi_backend.connect(device_name) {
                     Ok(()) => {
                         log::info!("[Standalone] MIDI connected: {device_name}");
+                        self.config.midi_device = Some(device_name.clone());
                     }
                     Err(e) => {
                         log::error!("[Standalone] MIDI connect failed: {e}");
@@ -223,6 +717,77 @@ impl StandaloneApp {
             }
         }
     }
+
+    /// Restart audio with a new buffer size.
+    fn apply_buffer_frames(&mut self, frames: Option<u32>) {
+        self.audio_backend.set_buffer_frames(frames);
+        self.stream_started = Instant::now();
+        if let Err(e) = self.audio_backend.restart() {
+            log::error!("[Standalone] Audio restart failed: {e}");
+            if let Ok(mut s) = self.editor_state.status_text.lock() {
+                *s = format!("⚠ {e}");
+            }
+        }
+    }
+
+    /// In safe mode, double the buffer after an xrun (once the stream has
+    /// settled after its last restart).
+    fn check_xruns(&mut self) {
+        let Some(ref ds) = self.editor_state.device_state else { return };
+        let xruns = ds.latency.xruns();
+        let current = ds.latency.buffer_frames();
+        if xruns == self.xruns_seen {
+            return;
+        }
+        self.xruns_seen = xruns;
+        if !self.config.buffer.safe_mode || self.stream_started.elapsed() < XRUN_SETTLE || current == 0 {
+            return;
+        }
+        let Some(frames) = super::latency::safe_mode_step(current) else { return };
+        log::warn!("[Standalone] Audio dropout; safe mode raises the buffer to {frames} frames");
+        self.config.buffer.frames = Some(frames);
+        if let Some(ref mut ds) = self.editor_state.device_state {
+            ds.buffer_settings.frames = Some(frames);
+        }
+        self.apply_buffer_frames(Some(frames));
+        if let Ok(mut s) = self.editor_state.status_text.lock() {
+            *s = format!("Safe mode: buffer raised to {frames} frames");
+        }
+    }
+
+    /// Save OSC settings and (re)start or stop the listener to match.
+    fn apply_osc_settings(&mut self, settings: OscSettings) {
+        if let Err(e) = settings.save() {
+            log::error!("[Standalone] {e}");
+        }
+        let running = self.osc_server.as_ref().map(OscServer::port);
+        let status = if !settings.enabled {
+            self.osc_server = None;
+            String::new()
+        } else if running == Some(settings.port) {
+            return;
+        } else {
+            // Release the old port before binding the new one
+            self.osc_server = None;
+            match OscServer::start(
+                settings.port,
+                self.editor_state.event_tx.clone(),
+                self.osc_tx.clone(),
+            ) {
+                Ok(server) => {
+                    self.osc_server = Some(server);
+                    format!("Listening on UDP port {}", settings.port)
+                }
+                Err(e) => {
+                    log::error!("[Standalone] {e}");
+                    format!("⚠ {e}")
+                }
+            }
+        };
+        if let Some(ref mut ds) = self.editor_state.device_state {
+            ds.osc_status = status;
+        }
+    }
 }
 
 impl eframe::App for StandaloneApp {
@@ -233,6 +798,11 @@ impl eframe::App for StandaloneApp {
             self.initialize_audio();
         }
 
+        // Apply OSC commands before drawing so the rack shows them this frame
+        while let Ok(cmd) = self.osc_rx.try_recv() {
+            cmd.apply(&mut self.editor_state);
+        }
+
         // Drain UI preset loaded events is done inside draw_editor()
         // (it stores in active_presets_ui and forwards to audio thread)
 
@@ -242,5 +812,21 @@ impl eframe::App for StandaloneApp {
 
         // Handle device switch commands after drawing
         self.handle_device_commands();
+        self.check_xruns();
+        self.apply_window_mode(ctx);
+
+        self.draw_recovery_prompt(ctx);
+        self.draw_restore_prompt(ctx);
+        self.save_session_and_config(ctx);
+        self.autosave(ctx);
+
+        // Save layout changes once a panel drag has finished
+        let layout = self.editor_state.layout;
+        if layout != self.saved_layout && !ctx.input(|i| i.pointer.any_down()) {
+            self.saved_layout = layout;
+            if let Err(e) = super::layout::save(&layout) {
+                log::warn!("[Standalone] {e}");
+            }
+        }
     }
 }
@@ -1,19 +1,27 @@
 //! Audio backend using cpal — supports runtime device enumeration and switching.
 
 use std::sync::Arc;
-use std::sync::atomic::AtomicU32;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use crossbeam_channel::Receiver;
+use crossbeam_channel::{Receiver, Sender};
 use nih_plug::prelude::NoteEvent;
 
 use crate::audio::{self, AudioEngine};
 use crate::editor::visualizer::VisualizerState;
 use crate::editor::{EditorEvent, PresetLoadedEvent};
+use crate::midi::SysEx;
+use crate::midi::gm::GmRequest;
+use crate::midi::rules::FiredRule;
+use crate::monitor::EngineMonitor;
 use crate::slots::SlotManager;
 use crate::transport::TransportState;
 
+use super::latency::{CallbackClock, LatencyStats};
+use super::link::LinkStatus;
+use super::metronome::{Metronome, MetronomeCommand};
 use super::params::StandaloneParams;
+use super::transport::{self, TransportCommand, TransportDisplay};
 
 /// All mutable state needed by the audio callback.
 /// Protected by parking_lot::Mutex for lock-free try_lock in the callback.
@@ -21,6 +29,11 @@ pub struct AudioCallbackState {
     pub engine: AudioEngine,
     pub slot_manager: SlotManager,
     pub transport: TransportState,
+    /// Click track, mixed in after the slots.
+    pub metronome: Metronome,
+    /// Ableton Link session the transport follows while enabled.
+    #[cfg(feature = "ableton-link")]
+    pub link: super::link::LinkSync,
 }
 
 /// Manages the cpal audio output stream and device switching.
@@ -30,15 +43,37 @@ pub struct AudioBackend {
     /// Current audio output stream (dropped to stop, recreated to switch devices).
     stream: Option<cpal::Stream>,
     /// Channels drained by the audio callback.
-    midi_rx: Receiver<NoteEvent<()>>,
+    midi_rx: Receiver<NoteEvent<SysEx>>,
     event_rx: Receiver<EditorEvent>,
     preset_loaded_rx: Receiver<PresetLoadedEvent>,
+    metronome_rx: Receiver<MetronomeCommand>,
+    /// Sending half for `set_metronome` / `count_in`.
+    metronome_tx: Sender<MetronomeCommand>,
+    /// Beats left in a running count-in (0 = none), for the UI.
+    count_in_beats: Arc<AtomicU32>,
+    transport_rx: Receiver<TransportCommand>,
+    /// Sending half for `send_transport`.
+    transport_tx: Sender<TransportCommand>,
+    /// Play head published by the callback, for the UI.
+    transport_display: Arc<TransportDisplay>,
+    /// Link peer count and state, for the UI.
+    link_status: Arc<LinkStatus>,
+    #[cfg(feature = "ableton-link")]
+    link: super::link::LinkHandle,
     /// Parameter atomics read by the audio callback.
     params: StandaloneParams,
     /// Visualizer state (lock-free), fed from the audio callback.
     visualizer_state: Arc<VisualizerState>,
     /// Voice count, updated from the audio callback.
     voice_count: Arc<AtomicU32>,
+    /// Per-slot telemetry, updated from the audio callback.
+    monitor: Arc<EngineMonitor>,
+    /// Callback timing, updated from the audio callback.
+    latency: Arc<LatencyStats>,
+    /// Frames per callback (device default if None).
+    buffer_frames: Option<u32>,
+    /// Device the current stream plays on.
+    device_name: Option<String>,
 }
 
 /// Information about an available audio device.
@@ -51,12 +86,15 @@ impl AudioBackend {
     /// Create a new audio backend (no stream started yet).
     pub fn new(
         sample_rate: f32,
-        midi_rx: Receiver<NoteEvent<()>>,
+        midi_rx: Receiver<NoteEvent<SysEx>>,
         event_rx: Receiver<EditorEvent>,
         preset_loaded_rx: Receiver<PresetLoadedEvent>,
+        rule_tx: Sender<FiredRule>,
+        gm_tx: Sender<GmRequest>,
         params: StandaloneParams,
         visualizer_state: Arc<VisualizerState>,
         voice_count: Arc<AtomicU32>,
+        monitor: Arc<EngineMonitor>,
     ) -> Self {
         let mut engine = AudioEngine::new();
         engine.initialize(sample_rate, 1024);
@@ -64,12 +102,25 @@ impl AudioBackend {
         let mut slot_manager = SlotManager::new_empty();
         slot_manager.initialize(sample_rate);
         slot_manager.allocate_all();
+        slot_manager.set_garbage_sender(crate::perf::garbage::spawn_collector());
+        slot_manager.set_pool_resizer(crate::perf::voice_pools::PoolResizer::spawn());
+        slot_manager.set_rule_sender(rule_tx);
+        slot_manager.set_gm_sender(gm_tx);
+
+        let link_status = Arc::new(LinkStatus::default());
+        #[cfg(feature = "ableton-link")]
+        let link = super::link::LinkHandle::new(params.tempo_value() as f64, link_status.clone());
 
         let callback_state = Arc::new(parking_lot::Mutex::new(AudioCallbackState {
             engine,
             slot_manager,
             transport: TransportState::default(),
+            metronome: Metronome::default(),
+            #[cfg(feature = "ableton-link")]
+            link: link.sync(),
         }));
+        let (metronome_tx, metronome_rx) = crossbeam_channel::bounded(16);
+        let (transport_tx, transport_rx) = crossbeam_channel::bounded(16);
 
         Self {
             callback_state,
@@ -77,12 +128,83 @@ impl AudioBackend {
             midi_rx,
             event_rx,
             preset_loaded_rx,
+            metronome_rx,
+            metronome_tx,
+            count_in_beats: Arc::new(AtomicU32::new(0)),
+            transport_rx,
+            transport_tx,
+            transport_display: Arc::new(TransportDisplay::default()),
+            link_status,
+            #[cfg(feature = "ableton-link")]
+            link,
             params,
             visualizer_state,
             voice_count,
+            monitor,
+            latency: Arc::new(LatencyStats::default()),
+            buffer_frames: None,
+            device_name: None,
         }
     }
 
+    /// Callback timing, for the latency read-out and safe mode.
+    pub fn latency(&self) -> Arc<LatencyStats> {
+        self.latency.clone()
+    }
+
+    /// Set the frames per callback (None = device default). Takes effect
+    /// when a stream starts; see `restart`.
+    pub fn set_buffer_frames(&mut self, frames: Option<u32>) {
+        self.buffer_frames = frames;
+    }
+
+    /// Restart the stream on the current device (to apply buffer settings).
+    pub fn restart(&mut self) -> Result<(), String> {
+        let Some(name) = self.device_name.clone() else {
+            return Err("No audio device running".to_string());
+        };
+        self.switch_device(&name)
+    }
+
+    /// Apply metronome settings on the audio callback.
+    pub fn set_metronome(&self, settings: super::metronome::MetronomeSettings) {
+        let _ = self.metronome_tx.try_send(MetronomeCommand::Settings(settings));
+    }
+
+    /// Start a count-in.
+    pub fn count_in(&self) {
+        let _ = self.metronome_tx.try_send(MetronomeCommand::CountIn);
+    }
+
+    /// Beats left in a running count-in (0 = none), updated by the callback.
+    pub fn count_in_beats(&self) -> Arc<AtomicU32> {
+        self.count_in_beats.clone()
+    }
+
+    /// Play, stop or reconfigure the internal transport.
+    pub fn send_transport(&self, command: TransportCommand) {
+        let _ = self.transport_tx.try_send(command);
+    }
+
+    /// Play head published by the callback.
+    pub fn transport_display(&self) -> Arc<TransportDisplay> {
+        self.transport_display.clone()
+    }
+
+    /// Join or leave the Ableton Link session (no-op without the
+    /// `ableton-link` feature).
+    pub fn set_link_enabled(&self, enabled: bool) {
+        #[cfg(feature = "ableton-link")]
+        self.link.set_enabled(enabled);
+        #[cfg(not(feature = "ableton-link"))]
+        let _ = enabled;
+    }
+
+    /// Link peer count and state.
+    pub fn link_status(&self) -> Arc<LinkStatus> {
+        self.link_status.clone()
+    }
+
     /// Enumerate available output devices.
     pub fn enumerate_devices() -> Vec<AudioDeviceInfo> {
         let host = cpal::default_host();
@@ -132,11 +254,18 @@ impl AudioBackend {
 
         let sample_rate = supported.sample_rate().0;
         let channels = 2u16; // We always want stereo
+        let buffer_size = match (self.buffer_frames, supported.buffer_size()) {
+            (Some(frames), cpal::SupportedBufferSize::Range { min, max }) => {
+                cpal::BufferSize::Fixed(frames.clamp(*min, *max))
+            }
+            (Some(frames), cpal::SupportedBufferSize::Unknown) => cpal::BufferSize::Fixed(frames),
+            (None, _) => cpal::BufferSize::Default,
+        };
 
         let config = cpal::StreamConfig {
             channels,
             sample_rate: cpal::SampleRate(sample_rate),
-            buffer_size: cpal::BufferSize::Default,
+            buffer_size,
         };
 
         // Re-initialize engine with the device's sample rate
@@ -152,14 +281,25 @@ impl AudioBackend {
         let midi_rx = self.midi_rx.clone();
         let event_rx = self.event_rx.clone();
         let preset_loaded_rx = self.preset_loaded_rx.clone();
+        let metronome_rx = self.metronome_rx.clone();
+        let count_in_beats = self.count_in_beats.clone();
+        let transport_rx = self.transport_rx.clone();
+        let transport_display = self.transport_display.clone();
         let params = self.params.clone();
         let visualizer_state = self.visualizer_state.clone();
         let voice_count = self.voice_count.clone();
+        let monitor = self.monitor.clone();
+        let mut clock = CallbackClock::new(self.latency.clone(), sample_rate);
+        let error_latency = self.latency.clone();
         let ch = channels as usize;
 
         let stream = device.build_output_stream(
             &config,
-            move |data: &mut [f32], _info: &cpal::OutputCallbackInfo| {
+            move |data: &mut [f32], info: &cpal::OutputCallbackInfo| {
+                let _ftz = crate::perf::denormal::ScopedFtz::enable();
+                let timestamp = info.timestamp();
+                let started = clock.begin(data.len() / ch, timestamp.playback.duration_since(&timestamp.callback));
+
                 // Try to lock — if UI is switching devices, output silence
                 let Some(mut guard) = callback_state.try_lock() else {
                     data.fill(0.0);
@@ -175,26 +315,52 @@ impl AudioBackend {
                 let AudioCallbackState {
                     ref mut engine,
                     ref mut slot_manager,
-                    ref transport,
+                    ref mut transport,
+                    ref mut metronome,
+                    #[cfg(feature = "ableton-link")]
+                    ref mut link,
                 } = *guard;
+                transport.bpm = params.tempo_value() as f64;
+                let was_playing = transport.playing;
+                while let Ok(command) = transport_rx.try_recv() {
+                    // Play/stop go through the Link session while it is on
+                    #[cfg(feature = "ableton-link")]
+                    if link.handle_command(command, transport) {
+                        continue;
+                    }
+                    transport::handle_command(command, transport);
+                }
+                // Follow the session's tempo and phase; peers may change the tempo
+                #[cfg(feature = "ableton-link")]
+                if let Some(bpm) = link.sync(transport) {
+                    params.set_tempo(bpm as f32);
+                }
+                transport.stopped = was_playing && !transport.playing;
+                if transport.stopped {
+                    slot_manager.transport_stopped();
+                }
+                while let Ok(command) = metronome_rx.try_recv() {
+                    metronome.handle_command(command, transport);
+                }
 
-                // Drain loaded presets
-                while let Ok(loaded) = preset_loaded_rx.try_recv() {
+                // Drain loaded presets (deferred while a slot still holds
+                // presets for a busy collector)
+                while slot_manager.presets_have_room() {
+                    let Ok(loaded) = preset_loaded_rx.try_recv() else { break };
                     log::info!("[AudioCB] Preset loaded: preset={}, slot={}, play_note={:?}, zones={}",
                         loaded.preset_id, loaded.slot_index, loaded.play_note, loaded.instance.zones.len());
                     if loaded.slot_index < slot_manager.slot_count() {
                         {
+                            // Voices already playing keep the old preset until they end
                             let slot = &mut slot_manager.slots_mut()[loaded.slot_index];
-                            // Kill any voices still playing the old preset on this slot
-                            // (their zone_index references would be stale after replacing the preset)
-                            slot.voice_pool_mut().kill_all();
                             slot.preset_state_mut()
                                 .load_preset(loaded.preset_id.clone(), loaded.instance.clone());
+                            slot.preset_state_mut().set_graph(loaded.graph);
                         }
-                        if let Some(note) = loaded.play_note {
+                        if let Some(play) = loaded.play_note {
                             let note_event = NoteEvent::NoteOn {
                                 timing: 0, voice_id: None, channel: 0,
-                                note, velocity: 0.8,
+                                note: play.note, velocity: play.velocity,
                             };
                             slot_manager.slots_mut()[loaded.slot_index]
                                 .handle_midi_event(&note_event, transport);
@@ -206,50 +372,36 @@ impl AudioBackend {
 
                 // Drain MIDI events from hardware
                 while let Ok(event) = midi_rx.try_recv() {
-                    crate::midi::route_event(&event, slot_manager, transport);
+                    monitor.midi_recorder().record(&event);
+                    let taken = crate::midi::route_event(&event, slot_manager, transport);
+                    monitor.midi_inspector().record(&event, taken);
                 }
 
-                // Drain editor events (piano keys, stop preview)
+                // Drain editor events (piano keys, stop preview, rack changes)
                 while let Ok(event) = event_rx.try_recv() {
                     match event {
-                        EditorEvent::NoteOn { slot_index, note, velocity } => {
-                            if slot_index < slot_manager.slot_count() {
-                                let note_event = NoteEvent::NoteOn {
-                                    timing: 0, voice_id: None, channel: 0,
-                                    note, velocity,
-                                };
-                                slot_manager.slots_mut()[slot_index]
-                                    .handle_midi_event(&note_event, transport);
-                            }
-                        }
-                        EditorEvent::NoteOff { slot_index, note } => {
-                            if let Some(slot) = slot_manager.slots_mut().get_mut(slot_index) {
-                                let note_event = NoteEvent::NoteOff {
-                                    timing: 0, voice_id: None, channel: 0,
-                                    note, velocity: 0.0,
-                                };
-                                slot.handle_midi_event(&note_event, transport);
-                            }
-                        }
-                        EditorEvent::StopPreview => {
-                            for slot in slot_manager.slots_mut() {
-                                let all_off = NoteEvent::MidiCC {
-                                    timing: 0, channel: 0, cc: 123, value: 0.0,
-                                };
-                                slot.handle_midi_event(&all_off, transport);
-                            }
+                        EditorEvent::SetParallelRender { enabled } => {
+                            engine.set_parallel_render(enabled);
                         }
+                        event => audio::handle_editor_event(event, slot_manager, transport),
                     }
                 }
+                slot_manager.update_voice_pools(params.max_voices_value());
+                slot_manager.set_bend_range(params.pitch_bend_range_value());
+                slot_manager.apply_macros(params.macro_values());
 
                 // Render and mix in chunks (cpal buffer may exceed engine capacity)
                 let master_gain = params.master_volume_gain_value();
                 let master_pan = params.master_pan_value();
+                engine.set_master_output(params.master_output_value());
                 let max_chunk = engine.max_buffer_size();
                 let mut offset = 0;
 
                 while offset < num_frames {
-                    let chunk = (num_frames - offset).min(max_chunk);
+                    // Split at the loop end so the play head jumps between chunks
+                    let chunk = (num_frames - offset)
+                        .min(max_chunk)
+                        .min(transport::samples_until_loop_end(transport).unwrap_or(usize::MAX));
                     audio::render_and_mix(
                         chunk,
                         engine,
@@ -259,6 +411,17 @@ impl AudioBackend {
                         master_pan,
                         &visualizer_state,
                         &voice_count,
+                        &monitor,
+                    );
+
+                    // Click track on the master bus, after the slots
+                    let sample_rate = engine.sample_rate();
+                    metronome.render(
+                        &mut engine.output_left,
+                        &mut engine.output_right,
+                        chunk,
+                        sample_rate,
+                        transport,
                     );
 
                     // Interleave this chunk into the cpal output buffer
@@ -270,21 +433,48 @@ impl AudioBackend {
                         }
                     }
 
+                    // No host to receive runner MIDI output in standalone mode
+                    slot_manager.drain_midi_out(|_| {});
+
+                    transport::advance(transport, chunk);
+                    monitor.midi_recorder().end_block(chunk, sample_rate);
+                    // A finished count-in starts playback
+                    if metronome.take_count_in_finished() {
+                        #[cfg(feature = "ableton-link")]
+                        let started = link.handle_command(TransportCommand::Play, transport);
+                        #[cfg(not(feature = "ableton-link"))]
+                        let started = false;
+                        if !started {
+                            transport::handle_command(TransportCommand::Play, transport);
+                        }
+                    }
+
                     offset += chunk;
                 }
+                count_in_beats.store(
+                    metronome.count_in_remaining(transport).unwrap_or(0),
+                    Ordering::Relaxed,
+                );
+                transport_display.publish(transport);
+                clock.end(started);
             },
-            |err| {
+            move |err| {
                 log::error!("[AudioBackend] Stream error: {err}");
+                // Backends report underruns as backend errors
+                if matches!(err, cpal::StreamError::BackendSpecific { .. }) {
+                    error_latency.record_xrun();
+                }
             },
             None, // no timeout
         ).map_err(|e| format!("Failed to build output stream: {e}"))?;
 
         stream.play().map_err(|e| format!("Failed to start playback: {e}"))?;
 
-        log::info!("[AudioBackend] Stream started: {}Hz, {} channels",
-            sample_rate, channels);
+        log::info!("[AudioBackend] Stream started: {}Hz, {} channels, buffer {:?}",
+            sample_rate, channels, config.buffer_size);
 
         self.stream = Some(stream);
+        self.device_name = device.name().ok();
         Ok(())
     }
 }
@@ -0,0 +1,199 @@
+//! Output latency, callback timing and safe mode for the standalone.
+//!
+//! The audio callback times itself against its buffer period: the spread
+//! of the intervals between callbacks is the jitter, and a callback that
+//! arrives well past its period means the device ran dry, an xrun. The
+//! output latency is what the device reports between a callback and its
+//! first sample reaching the speakers, or the buffer length where the
+//! backend doesn't say.
+//!
+//! In safe mode the app doubles the buffer and restarts the stream on
+//! each xrun, up to `MAX_BUFFER_FRAMES`. The buffer settings are saved in
+//! `standalone.toml`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Buffer sizes offered in Settings.
+pub const BUFFER_SIZES: [u32; 7] = [64, 128, 256, 512, 1024, 2048, 4096];
+/// Largest buffer safe mode goes to.
+pub const MAX_BUFFER_FRAMES: u32 = 4096;
+/// A callback this many periods after the last counts as an xrun.
+const XRUN_PERIODS: f64 = 1.75;
+/// Per-callback decay of the jitter peak (about 2 s at 512 frames/48 kHz).
+const JITTER_DECAY: f64 = 0.99;
+
+/// Output buffer settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BufferSettings {
+    /// Frames per callback, or None for the device default.
+    pub frames: Option<u32>,
+    /// Enlarge the buffer when xruns are detected.
+    pub safe_mode: bool,
+}
+
+/// Next buffer size in safe mode after an xrun at `current` frames, or
+/// None if it is already as large as it goes.
+pub fn safe_mode_step(current: u32) -> Option<u32> {
+    let next = current.max(BUFFER_SIZES[0]).next_power_of_two().saturating_mul(2).min(MAX_BUFFER_FRAMES);
+    (next > current).then_some(next)
+}
+
+/// Timing published by the audio callback for the UI.
+#[derive(Default)]
+pub struct LatencyStats {
+    buffer_frames: AtomicU32,
+    sample_rate: AtomicU32,
+    /// Device-reported output latency in µs (0 = not reported).
+    output_latency_us: AtomicU32,
+    /// Decaying peak deviation of the callback interval, in µs.
+    jitter_us: AtomicU32,
+    /// Render time over the buffer period, as f32 bits.
+    load: AtomicU32,
+    xruns: AtomicU32,
+}
+
+impl LatencyStats {
+    /// Frames in the last callback.
+    pub fn buffer_frames(&self) -> u32 {
+        self.buffer_frames.load(Ordering::Relaxed)
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate.load(Ordering::Relaxed)
+    }
+
+    /// Output latency: what the device reports, else the buffer length.
+    pub fn output_latency(&self) -> Duration {
+        match self.output_latency_us.load(Ordering::Relaxed) {
+            0 => period(self.buffer_frames(), self.sample_rate()),
+            us => Duration::from_micros(us as u64),
+        }
+    }
+
+    pub fn jitter(&self) -> Duration {
+        Duration::from_micros(self.jitter_us.load(Ordering::Relaxed) as u64)
+    }
+
+    /// Share of the buffer period spent rendering (1.0 = all of it).
+    pub fn load(&self) -> f32 {
+        f32::from_bits(self.load.load(Ordering::Relaxed))
+    }
+
+    /// Xruns since the app started.
+    pub fn xruns(&self) -> u32 {
+        self.xruns.load(Ordering::Relaxed)
+    }
+
+    /// Count an xrun reported by the stream itself.
+    pub fn record_xrun(&self) {
+        self.xruns.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// "10.7 ms (512 @ 48 kHz), jitter ±0.3 ms, load 12%, 2 xruns".
+    pub fn summary(&self) -> String {
+        if self.sample_rate() == 0 {
+            return "Not running".to_string();
+        }
+        let xruns = match self.xruns() {
+            0 => "no xruns".to_string(),
+            1 => "1 xrun".to_string(),
+            n => format!("{} xruns", n),
+        };
+        format!(
+            "{:.1} ms ({} @ {} kHz), jitter \u{b1}{:.1} ms, load {:.0}%, {}",
+            self.output_latency().as_secs_f64() * 1000.0,
+            self.buffer_frames(),
+            self.sample_rate() as f64 / 1000.0,
+            self.jitter().as_secs_f64() * 1000.0,
+            self.load() * 100.0,
+            xruns,
+        )
+    }
+}
+
+fn period(frames: u32, sample_rate: u32) -> Duration {
+    Duration::from_secs_f64(frames as f64 / sample_rate.max(1) as f64)
+}
+
+/// Callback-side timing; one per stream, owned by the callback.
+pub struct CallbackClock {
+    stats: Arc<LatencyStats>,
+    sample_rate: u32,
+    last: Option<Instant>,
+    jitter: f64,
+}
+
+impl CallbackClock {
+    pub fn new(stats: Arc<LatencyStats>, sample_rate: u32) -> Self {
+        stats.sample_rate.store(sample_rate, Ordering::Relaxed);
+        Self { stats, sample_rate, last: None, jitter: 0.0 }
+    }
+
+    /// Start of a callback of `frames`, with the device's reported output
+    /// latency if it has one. Returns the time to pass to `end`.
+    pub fn begin(&mut self, frames: usize, output_latency: Option<Duration>) -> Instant {
+        let now = Instant::now();
+        self.stats.buffer_frames.store(frames as u32, Ordering::Relaxed);
+        let latency_us = output_latency.map_or(0, |l| l.as_micros().min(u32::MAX as u128) as u32);
+        self.stats.output_latency_us.store(latency_us, Ordering::Relaxed);
+        if let Some(last) = self.last {
+            let period = period(frames as u32, self.sample_rate);
+            if self.record_interval(now - last, period) {
+                self.stats.record_xrun();
+            }
+        }
+        self.last = Some(now);
+        now
+    }
+
+    /// End of the callback started at `started`.
+    pub fn end(&mut self, started: Instant) {
+        let period = period(self.stats.buffer_frames(), self.sample_rate).as_secs_f64();
+        let load = (started.elapsed().as_secs_f64() / period.max(1e-6)) as f32;
+        self.stats.load.store(load.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Fold an interval between callbacks into the jitter; returns
+    /// whether it was late enough to be an xrun.
+    fn record_interval(&mut self, interval: Duration, period: Duration) -> bool {
+        let deviation = (interval.as_secs_f64() - period.as_secs_f64()).abs();
+        self.jitter = (self.jitter * JITTER_DECAY).max(deviation);
+        self.stats.jitter_us.store((self.jitter * 1e6).round() as u32, Ordering::Relaxed);
+        interval.as_secs_f64() > period.as_secs_f64() * XRUN_PERIODS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_late_callbacks_count_as_xruns_and_jitter() {
+        let stats = Arc::new(LatencyStats::default());
+        assert_eq!(stats.summary(), "Not running");
+        let mut clock = CallbackClock::new(stats.clone(), 48000);
+        let period = period(480, 48000);
+        assert_eq!(period, Duration::from_millis(10));
+
+        assert!(!clock.record_interval(Duration::from_millis(11), period));
+        assert_eq!(stats.jitter(), Duration::from_millis(1));
+        assert!(!clock.record_interval(Duration::from_millis(10), period), "the peak decays slowly");
+        assert_eq!(stats.jitter(), Duration::from_micros(990));
+        assert!(clock.record_interval(Duration::from_millis(25), period));
+
+        // Without a device figure the latency is the buffer length
+        clock.begin(480, None);
+        assert_eq!(stats.output_latency(), period);
+        clock.begin(480, Some(Duration::from_micros(12_500)));
+        assert_eq!(stats.output_latency(), Duration::from_micros(12_500));
+
+        assert_eq!(safe_mode_step(256), Some(512));
+        assert_eq!(safe_mode_step(441), Some(1024));
+        assert_eq!(safe_mode_step(MAX_BUFFER_FRAMES), None);
+    }
+}
@@ -0,0 +1,38 @@
+//! Editor layout file for the standalone.
+//!
+//! The plugin saves `PluginState::layout` with the host project; the
+//! standalone has no project, so the layout is stored per machine in
+//! `layout.json` under the config directory and restored at startup.
+
+use crate::state::EditorLayout;
+
+/// The saved layout (defaults if unset).
+pub fn load() -> EditorLayout {
+    crate::config::load_json("layout.json")
+}
+
+pub fn save(layout: &EditorLayout) -> Result<(), String> {
+    crate::config::save_json("layout.json", layout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::EditorTab;
+
+    #[test]
+    fn test_layout_json_roundtrip() {
+        let layout = EditorLayout {
+            zoom: 1.3,
+            tab: EditorTab::Settings,
+            piano_visible: true,
+            browser_width: 260.0,
+            visualizer_width: 90.0,
+        };
+        let json = serde_json::to_string(&layout).unwrap();
+        assert_eq!(serde_json::from_str::<EditorLayout>(&json).unwrap(), layout);
+        let partial: EditorLayout = serde_json::from_str(r#"{"tab":"settings"}"#).unwrap();
+        assert_eq!(partial.tab, EditorTab::Settings);
+        assert_eq!(partial.zoom, 1.0);
+    }
+}
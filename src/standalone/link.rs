@@ -0,0 +1,179 @@
+//! Ableton Link for the standalone.
+//!
+//! With Link enabled the internal transport follows the shared session:
+//! tempo, play/stop (start/stop sync) and the bar phase, so runner slots,
+//! arpeggiators and the metronome line up with other Link apps on the
+//! network. Local tempo edits and play/stop are pushed to the session.
+//!
+//! The session itself needs the `ableton-link` cargo feature (it builds the
+//! Link C++ library); without it only the settings exist and Settings hides
+//! the toggle. Settings are stored per machine in `link.json` under the
+//! config directory.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+// ── Settings ─────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkSettings {
+    pub enabled: bool,
+}
+
+impl LinkSettings {
+    /// Saved settings (disabled if unset).
+    pub fn load() -> Self {
+        crate::config::load_json("link.json")
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        crate::config::save_json("link.json", self)
+    }
+}
+
+/// Whether this build can join a Link session.
+pub fn available() -> bool {
+    cfg!(feature = "ableton-link")
+}
+
+/// Session state shown in Settings.
+#[derive(Default)]
+pub struct LinkStatus {
+    enabled: AtomicBool,
+    peers: AtomicU32,
+}
+
+impl LinkStatus {
+    pub fn enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Other Link apps in the session.
+    pub fn peers(&self) -> u32 {
+        self.peers.load(Ordering::Relaxed)
+    }
+}
+
+// ── Session ──────────────────────────────────────────────────
+
+#[cfg(feature = "ableton-link")]
+pub use session::{LinkHandle, LinkSync};
+
+#[cfg(feature = "ableton-link")]
+mod session {
+    use std::sync::Arc;
+    use std::sync::atomic::Ordering;
+
+    use rusty_link::{AblLink, SessionState};
+
+    use super::LinkStatus;
+    use crate::standalone::transport::{self, TransportCommand};
+    use crate::transport::TransportState;
+
+    /// UI-thread side: joins and leaves the session (not realtime-safe).
+    pub struct LinkHandle {
+        link: Arc<AblLink>,
+        status: Arc<LinkStatus>,
+    }
+
+    impl LinkHandle {
+        pub fn new(bpm: f64, status: Arc<LinkStatus>) -> Self {
+            let mut link = AblLink::new(bpm);
+            link.enable_start_stop_sync(true);
+            let peers = status.clone();
+            link.set_num_peers_callback(move |n| {
+                peers.peers.store(n as u32, Ordering::Relaxed);
+            });
+            Self {
+                link: Arc::new(link),
+                status,
+            }
+        }
+
+        pub fn set_enabled(&self, enabled: bool) {
+            self.link.enable(enabled);
+            self.status.enabled.store(enabled, Ordering::Relaxed);
+            if !enabled {
+                self.status.peers.store(0, Ordering::Relaxed);
+            }
+        }
+
+        /// Audio-thread side sharing this session.
+        pub fn sync(&self) -> LinkSync {
+            LinkSync {
+                link: self.link.clone(),
+                session: SessionState::new(),
+                last_bpm: None,
+            }
+        }
+    }
+
+    /// Audio-thread side: reads and commits the session each callback.
+    pub struct LinkSync {
+        link: Arc<AblLink>,
+        session: SessionState,
+        /// Tempo last agreed with the session, to tell local edits apart.
+        last_bpm: Option<f64>,
+    }
+
+    impl LinkSync {
+        /// Route play/stop through the session so peers follow. Returns
+        /// false (the command is handled locally) while Link is off.
+        pub fn handle_command(&mut self, command: TransportCommand, transport: &TransportState) -> bool {
+            if !self.link.is_enabled() {
+                return false;
+            }
+            let now = self.link.clock_micros();
+            self.link.capture_audio_session_state(&mut self.session);
+            match command {
+                // Start on the next bar line of the shared phase
+                TransportCommand::Play => self.session.set_is_playing_and_request_beat_at_time(
+                    true,
+                    now as u64,
+                    0.0,
+                    transport::bar_beats(transport),
+                ),
+                TransportCommand::Stop if self.session.is_playing() => {
+                    self.session.set_is_playing(false, now as u64)
+                }
+                _ => return false,
+            }
+            self.link.commit_audio_session_state(&self.session);
+            true
+        }
+
+        /// Pull tempo, play state and position from the session at the
+        /// start of a callback, pushing a local tempo edit first. Returns
+        /// the session tempo, or None while Link is off.
+        pub fn sync(&mut self, transport: &mut TransportState) -> Option<f64> {
+            if !self.link.is_enabled() {
+                self.last_bpm = None;
+                return None;
+            }
+            let now = self.link.clock_micros();
+            self.link.capture_audio_session_state(&mut self.session);
+            if self.last_bpm.is_some_and(|bpm| (bpm - transport.bpm).abs() > 1.0e-3) {
+                self.session.set_tempo(transport.bpm, now);
+                self.link.commit_audio_session_state(&self.session);
+            }
+            let bpm = self.session.tempo();
+            self.last_bpm = Some(bpm);
+            transport.bpm = bpm;
+
+            if self.session.is_playing() {
+                // Negative until the requested start bar comes round
+                let beat = self.session.beat_at_time(now, transport::bar_beats(transport));
+                transport.playing = beat >= 0.0;
+                if transport.playing {
+                    let position = transport::loop_position(transport, beat);
+                    transport::locate(transport, position);
+                }
+            } else {
+                transport.playing = false;
+            }
+            Some(bpm)
+        }
+    }
+}
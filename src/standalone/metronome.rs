@@ -0,0 +1,296 @@
+//! Metronome and count-in for the standalone.
+//!
+//! A short sine click on every beat while the transport plays (accented on
+//! the downbeat), mixed into the master output after the slots. A count-in
+//! clicks a number of bars on its own clock, so it works before the
+//! transport starts; the internal transport starts playing when it ends.
+
+use serde::{Deserialize, Serialize};
+
+use crate::transport::TransportState;
+
+/// Click pitch on the first beat of a bar.
+const ACCENT_HZ: f32 = 1760.0;
+/// Click pitch on the other beats.
+const BEAT_HZ: f32 = 880.0;
+/// Time for a click to decay by 1/e.
+const CLICK_DECAY_SECS: f32 = 0.012;
+/// Longest count-in offered.
+pub const MAX_COUNT_IN_BARS: u8 = 4;
+
+// ── Settings ─────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetronomeSettings {
+    /// Click while the transport plays.
+    pub enabled: bool,
+    /// Click level (0.0–1.0).
+    pub volume: f32,
+    /// Bars counted in before playback (0 = no count-in).
+    pub count_in_bars: u8,
+}
+
+impl Default for MetronomeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            volume: 0.5,
+            count_in_bars: 1,
+        }
+    }
+}
+
+impl MetronomeSettings {
+    /// Saved settings (defaults if unset).
+    pub fn load() -> Self {
+        crate::config::load_json("metronome.json")
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        crate::config::save_json("metronome.json", self)
+    }
+}
+
+/// Commands from the UI to the metronome on the audio callback.
+#[derive(Debug, Clone, Copy)]
+pub enum MetronomeCommand {
+    Settings(MetronomeSettings),
+    CountIn,
+}
+
+// ── Click generator ──────────────────────────────────────────
+
+/// Beat length (in quarter notes) and beats per bar for the time signature.
+fn beat_grid(transport: &TransportState) -> (f64, i64) {
+    let beat = 4.0 / transport.time_sig_denominator.max(1) as f64;
+    (beat, transport.time_sig_numerator.max(1) as i64)
+}
+
+pub struct Metronome {
+    settings: MetronomeSettings,
+    /// Index of the beat last clicked (None after a stop, so a transport
+    /// starting mid-beat waits for the next beat).
+    last_beat: Option<i64>,
+    /// Count-in position and length in quarter notes, while counting in.
+    count_in: Option<(f64, f64)>,
+    /// Set when a count-in completes; taken by the caller.
+    count_in_finished: bool,
+    phase: f32,
+    phase_inc: f32,
+    level: f32,
+}
+
+impl Default for Metronome {
+    fn default() -> Self {
+        Self {
+            settings: MetronomeSettings::default(),
+            last_beat: None,
+            count_in: None,
+            count_in_finished: false,
+            phase: 0.0,
+            phase_inc: 0.0,
+            level: 0.0,
+        }
+    }
+}
+
+impl Metronome {
+    pub fn settings(&self) -> MetronomeSettings {
+        self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: MetronomeSettings) {
+        self.settings = MetronomeSettings {
+            volume: settings.volume.clamp(0.0, 1.0),
+            count_in_bars: settings.count_in_bars.min(MAX_COUNT_IN_BARS),
+            ..settings
+        };
+    }
+
+    pub fn handle_command(&mut self, command: MetronomeCommand, transport: &TransportState) {
+        match command {
+            MetronomeCommand::Settings(settings) => self.set_settings(settings),
+            MetronomeCommand::CountIn => {
+                self.start_count_in(transport);
+            }
+        }
+    }
+
+    /// Start counting in `count_in_bars` bars. Returns false if the
+    /// count-in is off.
+    pub fn start_count_in(&mut self, transport: &TransportState) -> bool {
+        if self.settings.count_in_bars == 0 {
+            return false;
+        }
+        let (beat, per_bar) = beat_grid(transport);
+        let length = beat * per_bar as f64 * self.settings.count_in_bars as f64;
+        self.count_in = Some((0.0, length));
+        self.last_beat = None;
+        self.count_in_finished = false;
+        true
+    }
+
+    /// Beats left in the running count-in.
+    pub fn count_in_remaining(&self, transport: &TransportState) -> Option<u32> {
+        let (beat, _) = beat_grid(transport);
+        self.count_in
+            .map(|(position, length)| ((length - position) / beat).ceil().max(1.0) as u32)
+    }
+
+    /// Whether a count-in completed since the last call.
+    pub fn take_count_in_finished(&mut self) -> bool {
+        std::mem::take(&mut self.count_in_finished)
+    }
+
+    fn trigger(&mut self, accent: bool, sample_rate: f32) {
+        self.phase = 0.0;
+        self.phase_inc = if accent { ACCENT_HZ } else { BEAT_HZ } / sample_rate;
+        self.level = if accent { 1.0 } else { 0.6 };
+    }
+
+    /// Add clicks for the next `num_samples` to the output.
+    pub fn render(
+        &mut self,
+        left: &mut [f32],
+        right: &mut [f32],
+        num_samples: usize,
+        sample_rate: f32,
+        transport: &TransportState,
+    ) {
+        let num_samples = num_samples.min(left.len()).min(right.len());
+        let (beat, per_bar) = beat_grid(transport);
+        let beats_per_sample = transport.bpm / 60.0 / sample_rate as f64;
+        let decay = (-1.0 / (CLICK_DECAY_SECS * sample_rate)).exp();
+        let follow_transport = self.settings.enabled && transport.playing;
+
+        for i in 0..num_samples {
+            let position = if let Some((position, length)) = self.count_in.as_mut() {
+                let current = *position;
+                *position += beats_per_sample;
+                if *position >= *length {
+                    self.count_in = None;
+                    self.count_in_finished = true;
+                }
+                Some(current)
+            } else if follow_transport {
+                Some(transport.position_beats + i as f64 * beats_per_sample)
+            } else {
+                None
+            };
+
+            match position {
+                Some(position) => {
+                    let index = (position / beat).floor() as i64;
+                    let on_beat = position - index as f64 * beat < beats_per_sample;
+                    let new_beat = match self.last_beat {
+                        Some(last) => index != last,
+                        None => on_beat,
+                    };
+                    if new_beat {
+                        self.trigger(index.rem_euclid(per_bar) == 0, sample_rate);
+                    }
+                    if new_beat || self.last_beat.is_some() {
+                        self.last_beat = Some(index);
+                    }
+                }
+                None => self.last_beat = None,
+            }
+
+            if self.level > 1.0e-4 {
+                let click =
+                    (self.phase * std::f32::consts::TAU).sin() * self.level * self.settings.volume;
+                left[i] += click;
+                right[i] += click;
+                self.phase = (self.phase + self.phase_inc).fract();
+                self.level *= decay;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SR: f32 = 48000.0;
+
+    fn transport(playing: bool) -> TransportState {
+        TransportState {
+            bpm: 120.0,
+            playing,
+            sample_rate: SR,
+            time_sig_numerator: 4,
+            time_sig_denominator: 4,
+            ..TransportState::default()
+        }
+    }
+
+    /// Sample indices where a click starts (silence before a rise).
+    fn click_starts(buffer: &[f32]) -> Vec<usize> {
+        (1..buffer.len())
+            .filter(|&i| buffer[i - 1] == 0.0 && buffer[i] != 0.0)
+            .collect()
+    }
+
+    fn render(metronome: &mut Metronome, samples: usize, transport: &TransportState) -> Vec<f32> {
+        let (mut left, mut right) = (vec![0.0; samples], vec![0.0; samples]);
+        metronome.render(&mut left, &mut right, samples, SR, transport);
+        left
+    }
+
+    #[test]
+    fn test_silent_when_disabled_or_stopped() {
+        let mut metronome = Metronome::default();
+        assert!(
+            render(&mut metronome, 48000, &transport(true))
+                .iter()
+                .all(|&s| s == 0.0)
+        );
+        metronome.set_settings(MetronomeSettings {
+            enabled: true,
+            ..Default::default()
+        });
+        assert!(
+            render(&mut metronome, 48000, &transport(false))
+                .iter()
+                .all(|&s| s == 0.0)
+        );
+    }
+
+    #[test]
+    fn test_clicks_on_every_beat_while_playing() {
+        let mut metronome = Metronome::default();
+        metronome.set_settings(MetronomeSettings {
+            enabled: true,
+            ..Default::default()
+        });
+        // 120 BPM: a beat every 24000 samples, starting on the downbeat
+        let out = render(&mut metronome, 96000, &transport(true));
+        // The first click starts at sample 0 (sin(0) = 0, so it shows at 1)
+        assert_eq!(click_starts(&out), vec![1, 24001, 48001, 72001]);
+    }
+
+    #[test]
+    fn test_count_in_runs_without_transport() {
+        let mut metronome = Metronome::default();
+        let transport = transport(false);
+        assert!(metronome.start_count_in(&transport));
+        assert_eq!(metronome.count_in_remaining(&transport), Some(4));
+        let out = render(&mut metronome, 96000, &transport);
+        assert_eq!(click_starts(&out).len(), 4);
+        assert!(metronome.take_count_in_finished());
+        assert!(!metronome.take_count_in_finished());
+        assert_eq!(metronome.count_in_remaining(&transport), None);
+    }
+
+    #[test]
+    fn test_count_in_off() {
+        let mut metronome = Metronome::default();
+        metronome.set_settings(MetronomeSettings {
+            count_in_bars: 0,
+            ..Default::default()
+        });
+        assert!(!metronome.start_count_in(&transport(false)));
+    }
+}
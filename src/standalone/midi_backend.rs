@@ -1,19 +1,21 @@
 //! MIDI input backend using midir — supports runtime device enumeration and switching.
 
 use crossbeam_channel::Sender;
-use midir::{MidiInput, MidiInputConnection};
-use nih_plug::prelude::NoteEvent;
+use midir::{Ignore, MidiInput, MidiInputConnection};
+use nih_plug::prelude::{NoteEvent, SysExMessage};
+
+use crate::midi::SysEx;
 
 /// Manages MIDI input connections.
 pub struct MidiBackend {
     /// Active MIDI input connection (dropped to disconnect).
     connection: Option<MidiInputConnection<()>>,
     /// Channel to send parsed NoteEvents to the audio callback.
-    midi_tx: Sender<NoteEvent<()>>,
+    midi_tx: Sender<NoteEvent<SysEx>>,
 }
 
 impl MidiBackend {
-    pub fn new(midi_tx: Sender<NoteEvent<()>>) -> Self {
+    pub fn new(midi_tx: Sender<NoteEvent<SysEx>>) -> Self {
         Self {
             connection: None,
             midi_tx,
@@ -35,8 +37,10 @@ impl MidiBackend {
         // Disconnect existing
         self.disconnect();
 
-        let midi_in = MidiInput::new("SongWalker MIDI Input")
+        let mut midi_in = MidiInput::new("SongWalker MIDI Input")
             .map_err(|e| format!("Failed to create MIDI input: {e}"))?;
+        // SysEx is needed for MIDI rules; timing and active sensing are not
+        midi_in.ignore(Ignore::TimeAndActiveSense);
 
         let port = midi_in.ports().into_iter()
             .find(|p| midi_in.port_name(p).as_deref() == Ok(port_name))
@@ -70,11 +74,16 @@ impl MidiBackend {
 }
 
 /// Parse raw MIDI bytes into a nih-plug NoteEvent.
-fn parse_midi_bytes(data: &[u8]) -> Option<NoteEvent<()>> {
+fn parse_midi_bytes(data: &[u8]) -> Option<NoteEvent<SysEx>> {
     if data.is_empty() {
         return None;
     }
 
+    // System Exclusive (whole message, F0 … F7)
+    if data[0] == 0xF0 {
+        return SysEx::from_buffer(data).map(|message| NoteEvent::MidiSysEx { timing: 0, message });
+    }
+
     let status = data[0] & 0xF0;
     let channel = data[0] & 0x0F;
 
@@ -6,7 +6,15 @@
 
 pub mod app;
 pub mod audio_backend;
+pub mod latency;
+pub mod layout;
+pub mod link;
+pub mod metronome;
 pub mod midi_backend;
+pub mod osc;
 pub mod params;
+pub mod recovery;
+pub mod session;
+pub mod transport;
 
 pub use app::run;
@@ -0,0 +1,544 @@
+//! OSC remote control for the standalone app.
+//!
+//! An optional UDP server that lets a control surface (TouchOSC, Open Stage
+//! Control, a DAW) drive the rack. Slots are numbered from 1, as in the UI:
+//!
+//! | Address                   | Arguments              |
+//! |---------------------------|------------------------|
+//! | `/slot/{n}/volume`        | `f` 0.0–1.5            |
+//! | `/slot/{n}/pan`           | `f` -1.0–1.0           |
+//! | `/slot/{n}/mute`          | `i`/`f`/`T`/`F`        |
+//! | `/slot/{n}/solo`          | `i`/`f`/`T`/`F`        |
+//! | `/slot/{n}/load`          | `s` preset id (`library/path`) |
+//! | `/slot/{n}/note_on`       | `i` note, optional `f` velocity 0.0–1.0 |
+//! | `/slot/{n}/note_off`      | `i` note               |
+//!
+//! Notes go straight to the audio thread's event channel. Everything else
+//! is handed to the UI thread, which applies it like a click in the rack.
+//! Settings are stored per machine in `osc.json` under the config directory.
+
+use std::net::UdpSocket;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crossbeam_channel::Sender;
+use serde::{Deserialize, Serialize};
+
+use crate::editor::loads::LoadTarget;
+use crate::editor::event_queue::EventSender;
+use crate::editor::{EditorEvent, EditorState};
+use crate::view_model;
+
+/// Default UDP port.
+pub const DEFAULT_PORT: u16 = 9000;
+
+/// How often the listener wakes up to check whether it should stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+// ── Settings ─────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OscSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for OscSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+        }
+    }
+}
+
+impl OscSettings {
+    /// Saved settings (disabled if unset).
+    pub fn load() -> Self {
+        crate::config::load_json("osc.json")
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        crate::config::save_json("osc.json", self)
+    }
+}
+
+// ── Wire format ──────────────────────────────────────────────
+
+/// One OSC argument (only the types a control surface sends).
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    Str(String),
+    Bool(bool),
+}
+
+impl OscArg {
+    fn as_f32(&self) -> Option<f32> {
+        match self {
+            Self::Int(i) => Some(*i as f32),
+            Self::Float(f) => Some(*f),
+            Self::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            Self::Str(_) => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        self.as_f32().map(|v| v >= 0.5)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+/// Decode a UDP packet: a single message or a (possibly nested) bundle.
+pub fn decode_packet(bytes: &[u8]) -> Result<Vec<OscMessage>, String> {
+    let mut messages = Vec::new();
+    decode_into(bytes, &mut messages)?;
+    Ok(messages)
+}
+
+fn decode_into(bytes: &[u8], out: &mut Vec<OscMessage>) -> Result<(), String> {
+    let mut pos = 0;
+    if bytes.starts_with(b"#bundle\0") {
+        // Timetag is ignored: everything is applied on arrival
+        pos = 16;
+        while pos < bytes.len() {
+            let len = read_i32(bytes, &mut pos)?;
+            let len = usize::try_from(len).map_err(|_| "Negative bundle element size")?;
+            let element = bytes
+                .get(pos..pos + len)
+                .ok_or("Truncated bundle element")?;
+            decode_into(element, out)?;
+            pos += len;
+        }
+        return Ok(());
+    }
+
+    let address = read_string(bytes, &mut pos)?;
+    if !address.starts_with('/') {
+        return Err(format!("Invalid OSC address: {}", address));
+    }
+    // A message may omit the type tag string entirely
+    let tags = if pos < bytes.len() {
+        read_string(bytes, &mut pos)?
+    } else {
+        ",".into()
+    };
+    let tags = tags.strip_prefix(',').ok_or("Missing type tag string")?;
+
+    let mut args = Vec::with_capacity(tags.len());
+    for tag in tags.chars() {
+        args.push(match tag {
+            'i' => OscArg::Int(read_i32(bytes, &mut pos)?),
+            'f' => OscArg::Float(f32::from_bits(read_i32(bytes, &mut pos)? as u32)),
+            's' => OscArg::Str(read_string(bytes, &mut pos)?),
+            'T' => OscArg::Bool(true),
+            'F' => OscArg::Bool(false),
+            other => return Err(format!("Unsupported OSC type tag '{}'", other)),
+        });
+    }
+    out.push(OscMessage { address, args });
+    Ok(())
+}
+
+fn read_i32(bytes: &[u8], pos: &mut usize) -> Result<i32, String> {
+    let raw = bytes.get(*pos..*pos + 4).ok_or("Truncated OSC argument")?;
+    *pos += 4;
+    Ok(i32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]))
+}
+
+/// Null-terminated string padded to a multiple of 4 bytes.
+fn read_string(bytes: &[u8], pos: &mut usize) -> Result<String, String> {
+    let rest = bytes.get(*pos..).ok_or("Truncated OSC string")?;
+    let len = rest
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or("Unterminated OSC string")?;
+    let s = std::str::from_utf8(&rest[..len]).map_err(|_| "OSC string is not UTF-8")?;
+    *pos += (len + 4) & !3;
+    Ok(s.to_string())
+}
+
+// ── Commands ─────────────────────────────────────────────────
+
+/// A decoded remote-control action. Slot indices are 0-based.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscCommand {
+    SetVolume {
+        slot_index: usize,
+        volume: f32,
+    },
+    SetPan {
+        slot_index: usize,
+        pan: f32,
+    },
+    SetMute {
+        slot_index: usize,
+        muted: bool,
+    },
+    SetSolo {
+        slot_index: usize,
+        solo: bool,
+    },
+    LoadPreset {
+        slot_index: usize,
+        preset_id: String,
+    },
+    NoteOn {
+        slot_index: usize,
+        note: u8,
+        velocity: f32,
+    },
+    NoteOff {
+        slot_index: usize,
+        note: u8,
+    },
+}
+
+impl OscCommand {
+    /// Map a message to a command, or `None` for unknown addresses and
+    /// malformed arguments.
+    pub fn from_message(msg: &OscMessage) -> Option<Self> {
+        let mut parts = msg.address.strip_prefix("/slot/")?.splitn(2, '/');
+        let slot: usize = parts.next()?.parse().ok()?;
+        let slot_index = slot.checked_sub(1)?;
+        let arg = |i: usize| msg.args.get(i);
+        let note = || {
+            let n = arg(0)?.as_f32()?;
+            (0.0..=127.0).contains(&n).then_some(n as u8)
+        };
+
+        Some(match parts.next()? {
+            "volume" => Self::SetVolume {
+                slot_index,
+                volume: arg(0)?.as_f32()?.clamp(0.0, 1.5),
+            },
+            "pan" => Self::SetPan {
+                slot_index,
+                pan: arg(0)?.as_f32()?.clamp(-1.0, 1.0),
+            },
+            "mute" => Self::SetMute {
+                slot_index,
+                muted: arg(0)?.as_bool()?,
+            },
+            "solo" => Self::SetSolo {
+                slot_index,
+                solo: arg(0)?.as_bool()?,
+            },
+            "load" => match arg(0)? {
+                OscArg::Str(id) if id.contains('/') => Self::LoadPreset {
+                    slot_index,
+                    preset_id: id.clone(),
+                },
+                _ => return None,
+            },
+            "note_on" => Self::NoteOn {
+                slot_index,
+                note: note()?,
+                velocity: arg(1)
+                    .and_then(OscArg::as_f32)
+                    .unwrap_or(0.8)
+                    .clamp(0.0, 1.0),
+            },
+            "note_off" => Self::NoteOff {
+                slot_index,
+                note: note()?,
+            },
+            _ => return None,
+        })
+    }
+
+    /// Apply a non-note command on the UI thread. Commands for slots that
+    /// don't exist are ignored.
+    pub fn apply(self, state: &mut EditorState) {
+        match self {
+            Self::SetVolume { slot_index, volume } => {
+                view_model::update_slot(&state.plugin_state, slot_index, |c| c.volume = volume);
+            }
+            Self::SetPan { slot_index, pan } => {
+                view_model::update_slot(&state.plugin_state, slot_index, |c| c.pan = pan);
+            }
+            Self::SetMute { slot_index, muted } => {
+                view_model::update_slot(&state.plugin_state, slot_index, |c| c.muted = muted);
+            }
+            Self::SetSolo { slot_index, solo } => {
+                view_model::update_slot(&state.plugin_state, slot_index, |c| c.solo = solo);
+            }
+            Self::LoadPreset {
+                slot_index,
+                preset_id,
+            } => {
+                let Some((library, path)) = preset_id.split_once('/') else {
+                    return;
+                };
+                let name = path.rsplit('/').next().unwrap_or(path).to_string();
+                let exists = view_model::update_slot(&state.plugin_state, slot_index, |c| {
+                    c.assign_preset(&name, preset_id.clone());
+                });
+                if exists {
+                    state.loads.request(
+                        &state.jobs,
+                        &state.preset_manager,
+                        LoadTarget::Slot(slot_index),
+                        library,
+                        path,
+                        slot_index,
+                        None,
+                    );
+                }
+            }
+            Self::NoteOn {
+                slot_index,
+                note,
+                velocity,
+            } => {
+                let _ = state.event_tx.try_send(EditorEvent::NoteOn {
+                    slot_index,
+                    note,
+                    velocity,
+                });
+            }
+            Self::NoteOff { slot_index, note } => {
+                let _ = state
+                    .event_tx
+                    .try_send(EditorEvent::NoteOff { slot_index, note });
+            }
+        }
+    }
+}
+
+// ── Server ───────────────────────────────────────────────────
+
+/// A running listener. Dropping it stops the thread.
+pub struct OscServer {
+    port: u16,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl OscServer {
+    /// Listen on `port` (all interfaces). Notes are sent to `event_tx`,
+    /// other commands to `commands` for the UI thread.
+    pub fn start(
+        port: u16,
+        event_tx: EventSender,
+        commands: Sender<OscCommand>,
+    ) -> Result<Self, String> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))
+            .map_err(|e| format!("OSC: can't listen on port {}: {}", port, e))?;
+        socket
+            .set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(|e| e.to_string())?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = std::thread::Builder::new()
+            .name("songwalker-osc".into())
+            .spawn(move || {
+                let mut buf = [0u8; 4096];
+                while !thread_stop.load(Ordering::Relaxed) {
+                    let Ok((len, _)) = socket.recv_from(&mut buf) else {
+                        continue;
+                    };
+                    let messages = match decode_packet(&buf[..len]) {
+                        Ok(m) => m,
+                        Err(e) => {
+                            log::warn!("[OSC] {e}");
+                            continue;
+                        }
+                    };
+                    for msg in &messages {
+                        let Some(cmd) = OscCommand::from_message(msg) else {
+                            log::debug!("[OSC] Ignoring {}", msg.address);
+                            continue;
+                        };
+                        match cmd {
+                            OscCommand::NoteOn {
+                                slot_index,
+                                note,
+                                velocity,
+                            } => {
+                                let _ = event_tx.try_send(EditorEvent::NoteOn {
+                                    slot_index,
+                                    note,
+                                    velocity,
+                                });
+                            }
+                            OscCommand::NoteOff { slot_index, note } => {
+                                let _ =
+                                    event_tx.try_send(EditorEvent::NoteOff { slot_index, note });
+                            }
+                            other => {
+                                let _ = commands.send(other);
+                            }
+                        }
+                    }
+                }
+            })
+            .map_err(|e| e.to_string())?;
+
+        log::info!("[OSC] Listening on UDP port {port}");
+        Ok(Self {
+            port,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+impl Drop for OscServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode a message the way a control surface would.
+    fn encode(address: &str, args: &[OscArg]) -> Vec<u8> {
+        fn push_str(out: &mut Vec<u8>, s: &str) {
+            out.extend_from_slice(s.as_bytes());
+            out.push(0);
+            while out.len() % 4 != 0 {
+                out.push(0);
+            }
+        }
+        let mut out = Vec::new();
+        push_str(&mut out, address);
+        let mut tags = String::from(",");
+        for arg in args {
+            tags.push(match arg {
+                OscArg::Int(_) => 'i',
+                OscArg::Float(_) => 'f',
+                OscArg::Str(_) => 's',
+                OscArg::Bool(true) => 'T',
+                OscArg::Bool(false) => 'F',
+            });
+        }
+        push_str(&mut out, &tags);
+        for arg in args {
+            match arg {
+                OscArg::Int(i) => out.extend_from_slice(&i.to_be_bytes()),
+                OscArg::Float(f) => out.extend_from_slice(&f.to_bits().to_be_bytes()),
+                OscArg::Str(s) => push_str(&mut out, s),
+                OscArg::Bool(_) => {}
+            }
+        }
+        out
+    }
+
+    fn command(address: &str, args: &[OscArg]) -> Option<OscCommand> {
+        let msgs = decode_packet(&encode(address, args)).unwrap();
+        OscCommand::from_message(&msgs[0])
+    }
+
+    #[test]
+    fn test_decode_roundtrip() {
+        let args = vec![
+            OscArg::Int(60),
+            OscArg::Float(0.5),
+            OscArg::Str("abc".into()),
+            OscArg::Bool(true),
+        ];
+        let msgs = decode_packet(&encode("/slot/1/x", &args)).unwrap();
+        assert_eq!(
+            msgs,
+            vec![OscMessage {
+                address: "/slot/1/x".into(),
+                args
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decode_bundle() {
+        let a = encode("/slot/1/mute", &[OscArg::Int(1)]);
+        let b = encode("/slot/2/solo", &[OscArg::Bool(false)]);
+        let mut bundle = b"#bundle\0".to_vec();
+        bundle.extend_from_slice(&1u64.to_be_bytes());
+        for element in [&a, &b] {
+            bundle.extend_from_slice(&(element.len() as i32).to_be_bytes());
+            bundle.extend_from_slice(element);
+        }
+        let msgs = decode_packet(&bundle).unwrap();
+        assert_eq!(msgs.len(), 2);
+        assert_eq!(msgs[1].address, "/slot/2/solo");
+    }
+
+    #[test]
+    fn test_decode_rejects_garbage() {
+        assert!(decode_packet(b"no").is_err());
+        assert!(decode_packet(b"/a\0\0,i\0\0\0\0").is_err(), "truncated int");
+        assert!(decode_packet(b"#bundle\0\0\0\0\0\0\0\0\0\0\0\0\x40").is_err());
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(
+            command("/slot/1/volume", &[OscArg::Float(2.0)]),
+            Some(OscCommand::SetVolume {
+                slot_index: 0,
+                volume: 1.5
+            })
+        );
+        assert_eq!(
+            command("/slot/3/mute", &[OscArg::Float(1.0)]),
+            Some(OscCommand::SetMute {
+                slot_index: 2,
+                muted: true
+            })
+        );
+        assert_eq!(
+            command("/slot/2/load", &[OscArg::Str("FluidR3_GM/Piano".into())]),
+            Some(OscCommand::LoadPreset {
+                slot_index: 1,
+                preset_id: "FluidR3_GM/Piano".into()
+            })
+        );
+        assert_eq!(
+            command("/slot/1/note_on", &[OscArg::Int(60)]),
+            Some(OscCommand::NoteOn {
+                slot_index: 0,
+                note: 60,
+                velocity: 0.8
+            })
+        );
+        assert_eq!(
+            command("/slot/0/pan", &[OscArg::Float(0.0)]),
+            None,
+            "slots start at 1"
+        );
+        assert_eq!(command("/slot/1/note_on", &[OscArg::Int(200)]), None);
+        assert_eq!(command("/slot/1/load", &[OscArg::Int(1)]), None);
+        assert_eq!(command("/slot/1/unknown", &[]), None);
+    }
+
+    #[test]
+    fn test_settings_default() {
+        let s: OscSettings = serde_json::from_str("{\"enabled\": true}").unwrap();
+        assert_eq!(
+            s,
+            OscSettings {
+                enabled: true,
+                port: DEFAULT_PORT
+            }
+        );
+    }
+}
@@ -6,6 +6,8 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicU32, Ordering};
 
 use crate::editor::GlobalParams;
+use crate::params::MasterOutput;
+use crate::slots::MACRO_COUNT;
 
 /// Atomic f32 helper — stores f32 as u32 bits for lock-free sharing.
 fn load_f32(atom: &AtomicU32) -> f32 {
@@ -24,6 +26,35 @@ fn store_i32(atom: &AtomicU32, val: i32) {
     atom.store(val as u32, Ordering::Relaxed);
 }
 
+/// Bits of `StandaloneParams::master_output`.
+const MASTER_MONO: u32 = 1;
+const MASTER_SWAP: u32 = 1 << 1;
+const MASTER_INVERT_L: u32 = 1 << 2;
+const MASTER_INVERT_R: u32 = 1 << 3;
+
+fn load_master_output(atom: &AtomicU32) -> MasterOutput {
+    let bits = atom.load(Ordering::Relaxed);
+    MasterOutput {
+        mono: bits & MASTER_MONO != 0,
+        swap: bits & MASTER_SWAP != 0,
+        invert_left: bits & MASTER_INVERT_L != 0,
+        invert_right: bits & MASTER_INVERT_R != 0,
+    }
+}
+
+fn store_master_output(atom: &AtomicU32, output: MasterOutput) {
+    let bits = [
+        (output.mono, MASTER_MONO),
+        (output.swap, MASTER_SWAP),
+        (output.invert_left, MASTER_INVERT_L),
+        (output.invert_right, MASTER_INVERT_R),
+    ]
+    .iter()
+    .filter(|(on, _)| *on)
+    .fold(0, |bits, (_, bit)| bits | bit);
+    atom.store(bits, Ordering::Relaxed);
+}
+
 /// Standalone parameter storage — uses atomics for lock-free audio thread access.
 #[derive(Clone)]
 pub struct StandaloneParams {
@@ -31,6 +62,12 @@ pub struct StandaloneParams {
     pub master_pan: Arc<AtomicU32>,
     pub max_voices: Arc<AtomicU32>,
     pub pitch_bend_range: Arc<AtomicU32>,
+    /// Master output utilities as bit flags (see `MASTER_*`).
+    pub master_output: Arc<AtomicU32>,
+    /// Standalone tempo in BPM (there is no host to provide one).
+    pub tempo: Arc<AtomicU32>,
+    /// Macro knob positions (0–1).
+    pub macros: Arc<[AtomicU32; MACRO_COUNT]>,
 }
 
 impl Default for StandaloneParams {
@@ -40,6 +77,9 @@ impl Default for StandaloneParams {
             master_pan: Arc::new(AtomicU32::new(0.0_f32.to_bits())),     // center
             max_voices: Arc::new(AtomicU32::new(256)),
             pitch_bend_range: Arc::new(AtomicU32::new(2)),
+            master_output: Arc::new(AtomicU32::new(0)),
+            tempo: Arc::new(AtomicU32::new(120.0_f32.to_bits())),
+            macros: Arc::new(std::array::from_fn(|_| AtomicU32::new(0.0_f32.to_bits()))),
         }
     }
 }
@@ -54,6 +94,35 @@ impl StandaloneParams {
     pub fn master_pan_value(&self) -> f32 {
         load_f32(&self.master_pan)
     }
+
+    /// Read the Max Voices ceiling (at least 1).
+    pub fn max_voices_value(&self) -> usize {
+        load_i32(&self.max_voices).max(1) as usize
+    }
+
+    /// Read the global Pitch Bend Range (semitones).
+    pub fn pitch_bend_range_value(&self) -> u8 {
+        load_i32(&self.pitch_bend_range).clamp(1, 48) as u8
+    }
+
+    /// Read the master output utilities.
+    pub fn master_output_value(&self) -> MasterOutput {
+        load_master_output(&self.master_output)
+    }
+
+    /// Read the tempo (BPM).
+    pub fn tempo_value(&self) -> f32 {
+        load_f32(&self.tempo)
+    }
+
+    pub fn set_tempo(&self, bpm: f32) {
+        store_f32(&self.tempo, bpm.clamp(20.0, 300.0));
+    }
+
+    /// Read the macro knob positions (0–1).
+    pub fn macro_values(&self) -> [f32; MACRO_COUNT] {
+        std::array::from_fn(|i| load_f32(&self.macros[i]))
+    }
 }
 
 /// GlobalParams implementation for the standalone UI.
@@ -81,4 +150,22 @@ impl GlobalParams for StandaloneGlobalParams<'_> {
     fn set_pitch_bend_range(&self, v: i32) {
         store_i32(&self.params.pitch_bend_range, v);
     }
+    fn master_output(&self) -> MasterOutput {
+        load_master_output(&self.params.master_output)
+    }
+    fn set_master_output(&self, output: MasterOutput) {
+        store_master_output(&self.params.master_output, output);
+    }
+    fn slot_mix(&self, _slot_index: usize) -> Option<crate::params::SlotMix> {
+        None
+    }
+    fn set_slot_mix(&self, _slot_index: usize, _mix: crate::params::SlotMix) {}
+    fn macro_value(&self, index: usize) -> f32 {
+        self.params.macros.get(index).map_or(0.0, load_f32)
+    }
+    fn set_macro_value(&self, index: usize, value: f32) {
+        if let Some(m) = self.params.macros.get(index) {
+            store_f32(m, value.clamp(0.0, 1.0));
+        }
+    }
 }
@@ -0,0 +1,195 @@
+//! Autosave and crash recovery for the standalone.
+//!
+//! While the app runs, the rack and the transport settings are written to
+//! `recovery.json` under the config directory every `AUTOSAVE_INTERVAL`,
+//! and a `running` marker file sits next to it. A clean exit removes both;
+//! if the marker is still there at the next launch the last run crashed,
+//! and the app offers the autosaved session back.
+//!
+//! The UI thread only clones the state and hands it over; serializing and
+//! writing happen on the autosave thread, and a snapshot that arrives
+//! while the previous one is still being written is dropped.
+
+use std::path::PathBuf;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crossbeam_channel::{Sender, TrySendError};
+use serde::{Deserialize, Serialize};
+
+use super::session;
+use super::transport::LoopRegion;
+use crate::state::PluginState;
+
+/// How often the session is autosaved.
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(15);
+
+const RECOVERY_FILE: &str = "recovery.json";
+const RUNNING_MARKER: &str = "running";
+
+/// Header transport settings saved along with the rack.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TransportSettings {
+    pub tempo: f32,
+    pub time_signature: (i32, i32),
+    pub loop_region: LoopRegion,
+}
+
+/// Contents of `recovery.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recovery {
+    /// Seconds since the Unix epoch.
+    pub saved_at: u64,
+    pub transport: TransportSettings,
+    pub state: PluginState,
+}
+
+impl Recovery {
+    /// How long ago the autosave was written, as "3 min ago".
+    pub fn age_text(&self) -> String {
+        let age = unix_now().saturating_sub(self.saved_at);
+        match age {
+            0..60 => "less than a minute ago".to_string(),
+            60..3600 => format!("{} min ago", age / 60),
+            _ => format!("{} h ago", age / 3600),
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn path(name: &str) -> Option<PathBuf> {
+    session::config_dir().map(|d| d.join(name))
+}
+
+/// Mark this run as started. Returns whether the previous run ended
+/// without calling `end_run` (it crashed or was killed).
+pub fn start_run() -> bool {
+    let Some(marker) = path(RUNNING_MARKER) else { return false };
+    let crashed = marker.exists();
+    if let Err(e) = session::write(marker, b"", "run marker") {
+        log::warn!("[Standalone] {e}");
+    }
+    crashed
+}
+
+/// Mark this run as cleanly finished and drop its autosave.
+pub fn end_run() {
+    for name in [RECOVERY_FILE, RUNNING_MARKER] {
+        if let Some(file) = path(name) {
+            let _ = std::fs::remove_file(file);
+        }
+    }
+}
+
+/// The autosaved session, if it had any slots.
+pub fn load() -> Option<Recovery> {
+    path(RECOVERY_FILE)
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|bytes| serde_json::from_slice::<Recovery>(&bytes).ok())
+        .filter(|r| !r.state.slot_configs.is_empty())
+}
+
+/// What the UI thread hands to the autosave thread.
+struct Snapshot {
+    state: PluginState,
+    transport: TransportSettings,
+}
+
+/// Background writer of `recovery.json`.
+pub struct Autosaver {
+    tx: Option<Sender<Snapshot>>,
+    thread: Option<JoinHandle<()>>,
+    last_submit: Instant,
+}
+
+impl Autosaver {
+    pub fn start() -> Self {
+        let (tx, rx) = crossbeam_channel::bounded::<Snapshot>(1);
+        let thread = std::thread::Builder::new()
+            .name("songwalker-autosave".into())
+            .spawn(move || {
+                let mut saved: Vec<u8> = Vec::new();
+                // Ends when the sender is dropped
+                for snapshot in rx {
+                    // Compare without the timestamp, so an unchanged session
+                    // isn't rewritten
+                    let body = serde_json::to_vec(&(&snapshot.transport, &snapshot.state)).unwrap_or_default();
+                    if body == saved {
+                        continue;
+                    }
+                    let recovery = Recovery { saved_at: unix_now(), transport: snapshot.transport, state: snapshot.state };
+                    let Ok(bytes) = serde_json::to_vec(&recovery) else { continue };
+                    let Some(file) = path(RECOVERY_FILE) else { continue };
+                    match session::write(file, &bytes, "autosave") {
+                        Ok(()) => saved = body,
+                        Err(e) => log::warn!("[Standalone] {e}"),
+                    }
+                }
+            });
+        let thread = match thread {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                log::error!("[Standalone] Failed to start autosave: {e}");
+                None
+            }
+        };
+        Self { tx: thread.is_some().then_some(tx), thread, last_submit: Instant::now() }
+    }
+
+    /// Whether the next autosave is due.
+    pub fn due(&self) -> bool {
+        self.tx.is_some() && self.last_submit.elapsed() >= AUTOSAVE_INTERVAL
+    }
+
+    /// Hand a snapshot to the autosave thread. Skipped (and retried at the
+    /// next interval) while the previous one is still being written.
+    pub fn submit(&mut self, state: PluginState, transport: TransportSettings) {
+        self.last_submit = Instant::now();
+        let Some(tx) = &self.tx else { return };
+        if let Err(TrySendError::Full(_)) = tx.try_send(Snapshot { state, transport }) {
+            log::debug!("[Standalone] Autosave still writing; skipped");
+        }
+    }
+
+    /// Stop the thread after any write in progress, then mark the run as
+    /// finished (see `end_run`).
+    pub fn finish(mut self) {
+        self.tx = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        end_run();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::SlotConfig;
+
+    #[test]
+    fn test_recovery_roundtrip() {
+        let mut state = PluginState::default();
+        let mut slot = SlotConfig::default();
+        slot.assign_preset("Piano", "lib/piano".to_string());
+        state.slot_configs.push(slot);
+        let recovery = Recovery {
+            saved_at: unix_now() - 125,
+            transport: TransportSettings {
+                tempo: 96.0,
+                time_signature: (6, 8),
+                loop_region: LoopRegion { enabled: true, start_bar: 2, length_bars: 4 },
+            },
+            state,
+        };
+
+        let bytes = serde_json::to_vec(&recovery).unwrap();
+        let back: Recovery = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(back.transport, recovery.transport);
+        assert_eq!(back.state.slot_configs[0].preset_id.as_deref(), Some("lib/piano"));
+        assert_eq!(back.age_text(), "2 min ago");
+    }
+}
@@ -0,0 +1,128 @@
+//! Standalone config file and last-session restore.
+//!
+//! `standalone.toml` under the config directory keeps the chosen audio and
+//! MIDI devices, the output buffer settings, the library URL, the window size and whether to restore
+//! the last session. The rack itself (`PluginState`, as the plugin would
+//! save it with a project) is written to `session.json` next to it while
+//! the app runs, and offered back at the next launch. Crash recovery (an
+//! autosave with the transport settings) lives in `recovery`.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::latency::BufferSettings;
+use crate::state::PluginState;
+
+/// What to do with the last session at startup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RestoreMode {
+    /// Ask with a prompt.
+    #[default]
+    Ask,
+    Always,
+    Never,
+}
+
+impl RestoreMode {
+    pub const ALL: [RestoreMode; 3] = [Self::Ask, Self::Always, Self::Never];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Ask => "Ask",
+            Self::Always => "Always",
+            Self::Never => "Never",
+        }
+    }
+}
+
+/// Contents of `standalone.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StandaloneConfig {
+    /// Audio output device name (system default if unset or missing).
+    pub audio_device: Option<String>,
+    /// Output buffer size and safe mode.
+    pub buffer: BufferSettings,
+    /// MIDI input port name (none if unset or missing).
+    pub midi_device: Option<String>,
+    /// Preset library base URL (the built-in library if unset).
+    pub library_url: Option<String>,
+    /// Window inner size in logical points.
+    pub window_size: Option<[f32; 2]>,
+    pub restore_session: RestoreMode,
+    /// Open in the compact performance layout.
+    pub performance_mode: bool,
+    /// Window inner size in performance mode.
+    pub performance_window_size: Option<[f32; 2]>,
+    /// Keep the window above other applications.
+    pub always_on_top: bool,
+}
+
+pub(super) fn config_dir() -> Option<PathBuf> {
+    directories::ProjectDirs::from("org", "SongWalker", "songwalker-vsti")
+        .map(|d| d.config_dir().to_path_buf())
+}
+
+pub(super) fn write(path: PathBuf, bytes: &[u8], what: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    crate::net::atomic::write_atomic(&path, bytes)
+        .map_err(|e| format!("Failed to save {}: {}", what, e))
+}
+
+impl StandaloneConfig {
+    /// The saved config (defaults if unset or unreadable).
+    pub fn load() -> Self {
+        config_dir()
+            .and_then(|d| std::fs::read_to_string(d.join("standalone.toml")).ok())
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let dir = config_dir().ok_or("No config directory")?;
+        let text = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        write(dir.join("standalone.toml"), text.as_bytes(), "standalone config")
+    }
+}
+
+/// The rack saved by the last run, if it had any slots.
+pub fn load_session() -> Option<PluginState> {
+    config_dir()
+        .and_then(|d| std::fs::read(d.join("session.json")).ok())
+        .and_then(|bytes| PluginState::from_bytes(&bytes))
+        .filter(|state| !state.slot_configs.is_empty())
+}
+
+/// Save the rack for the next launch.
+pub fn save_session(bytes: &[u8]) -> Result<(), String> {
+    let dir = config_dir().ok_or("No config directory")?;
+    write(dir.join("session.json"), bytes, "session")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_toml_roundtrip() {
+        let config = StandaloneConfig {
+            audio_device: Some("pipewire".into()),
+            buffer: BufferSettings { frames: Some(256), safe_mode: true },
+            midi_device: None,
+            library_url: Some("https://example.com/library".into()),
+            window_size: Some([1024.0, 768.0]),
+            restore_session: RestoreMode::Always,
+            performance_mode: true,
+            performance_window_size: Some([560.0, 240.0]),
+            always_on_top: true,
+        };
+        let text = toml::to_string_pretty(&config).unwrap();
+        assert!(text.contains("restore_session = \"always\""));
+        assert_eq!(toml::from_str::<StandaloneConfig>(&text).unwrap(), config);
+        assert_eq!(toml::from_str::<StandaloneConfig>("").unwrap(), StandaloneConfig::default());
+    }
+}
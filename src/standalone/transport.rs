@@ -0,0 +1,258 @@
+//! Internal transport for the standalone.
+//!
+//! With no host to follow, the audio callback runs its own play head:
+//! play/stop, time signature and an optional loop region, advanced per
+//! rendered chunk. It writes into the same `TransportState` the plugin
+//! fills from the host, so runner slots, arpeggiators and the metronome
+//! behave identically in both modes.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::transport::TransportState;
+
+/// Time signatures offered in the header (numerator, denominator).
+pub const TIME_SIGNATURES: &[(i32, i32)] = &[
+    (2, 4),
+    (3, 4),
+    (4, 4),
+    (5, 4),
+    (6, 8),
+    (7, 8),
+    (9, 8),
+    (12, 8),
+];
+
+/// Loop region in bars, as edited in the header.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LoopRegion {
+    pub enabled: bool,
+    /// First bar of the loop (0-based).
+    pub start_bar: u32,
+    /// Loop length in bars (at least 1).
+    pub length_bars: u32,
+}
+
+impl Default for LoopRegion {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start_bar: 0,
+            length_bars: 4,
+        }
+    }
+}
+
+/// Commands from the UI to the transport on the audio callback.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransportCommand {
+    Play,
+    /// Stop; stopping while already stopped returns to the start.
+    Stop,
+    TimeSignature { numerator: i32, denominator: i32 },
+    Loop(LoopRegion),
+}
+
+/// Play head state published by the audio callback for the UI.
+#[derive(Default)]
+pub struct TransportDisplay {
+    playing: AtomicBool,
+    /// Position in beats, stored as f64 bits.
+    position_beats: AtomicU64,
+}
+
+impl TransportDisplay {
+    pub fn playing(&self) -> bool {
+        self.playing.load(Ordering::Relaxed)
+    }
+
+    pub fn position_beats(&self) -> f64 {
+        f64::from_bits(self.position_beats.load(Ordering::Relaxed))
+    }
+
+    pub fn publish(&self, transport: &TransportState) {
+        self.playing.store(transport.playing, Ordering::Relaxed);
+        self.position_beats
+            .store(transport.position_beats.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Bar length in beats (quarter notes) for the transport's time signature.
+pub fn bar_beats(transport: &TransportState) -> f64 {
+    transport.time_sig_numerator.max(1) as f64 * 4.0
+        / transport.time_sig_denominator.max(1) as f64
+}
+
+/// Format a position as "bar.beat" (1-based), counting beats in the
+/// time signature's denominator.
+pub fn format_position(position_beats: f64, numerator: i32, denominator: i32) -> String {
+    let beat = 4.0 / denominator.max(1) as f64;
+    let per_bar = numerator.max(1) as i64;
+    let index = (position_beats.max(0.0) / beat).floor() as i64;
+    format!("{}.{}", index / per_bar + 1, index % per_bar + 1)
+}
+
+/// Apply a UI command to the transport.
+pub fn handle_command(command: TransportCommand, transport: &mut TransportState) {
+    match command {
+        TransportCommand::Play => transport.playing = true,
+        TransportCommand::Stop => {
+            if transport.playing {
+                transport.playing = false;
+            } else {
+                let start = if transport.looping { transport.loop_start_beats } else { 0.0 };
+                locate(transport, start);
+            }
+        }
+        TransportCommand::TimeSignature { numerator, denominator } => {
+            transport.time_sig_numerator = numerator.max(1);
+            transport.time_sig_denominator = denominator.max(1);
+        }
+        TransportCommand::Loop(region) => {
+            let bar = bar_beats(transport);
+            transport.looping = region.enabled;
+            transport.loop_start_beats = region.start_bar as f64 * bar;
+            transport.loop_end_beats =
+                (region.start_bar + region.length_bars.max(1)) as f64 * bar;
+            // Jump into a newly enabled loop if the play head is past it
+            if region.enabled && transport.position_beats >= transport.loop_end_beats {
+                let start = transport.loop_start_beats;
+                locate(transport, start);
+            }
+        }
+    }
+}
+
+/// Move the play head to a position in beats.
+pub fn locate(transport: &mut TransportState, position_beats: f64) {
+    transport.position_beats = position_beats;
+    transport.position_samples = transport.beats_to_samples(position_beats).round() as i64;
+}
+
+/// Map a free-running beat position into the loop region (for positions
+/// that come from outside, like an Ableton Link session).
+pub fn loop_position(transport: &TransportState, position_beats: f64) -> f64 {
+    let length = transport.loop_end_beats - transport.loop_start_beats;
+    if !transport.looping || length <= 0.0 || position_beats < transport.loop_end_beats {
+        return position_beats;
+    }
+    transport.loop_start_beats + (position_beats - transport.loop_start_beats) % length
+}
+
+/// Samples until the play head reaches the loop end, if it will. The
+/// callback splits its chunks there so no block straddles the jump.
+pub fn samples_until_loop_end(transport: &TransportState) -> Option<usize> {
+    if !transport.playing
+        || !transport.looping
+        || transport.loop_end_beats <= transport.loop_start_beats
+        || transport.position_beats >= transport.loop_end_beats
+    {
+        return None;
+    }
+    let samples = transport.beats_to_samples(transport.loop_end_beats - transport.position_beats);
+    Some((samples.ceil() as usize).max(1))
+}
+
+/// Advance the play head past a rendered chunk, wrapping at the loop end.
+pub fn advance(transport: &mut TransportState, num_samples: usize) {
+    if !transport.playing {
+        return;
+    }
+    transport.position_beats += transport.samples_to_beats(num_samples as f64);
+    transport.position_samples += num_samples as i64;
+    if transport.looping
+        && transport.loop_end_beats > transport.loop_start_beats
+        && transport.position_beats >= transport.loop_end_beats
+    {
+        let overshoot = transport.position_beats - transport.loop_end_beats;
+        let length = transport.loop_end_beats - transport.loop_start_beats;
+        let start = transport.loop_start_beats + overshoot % length;
+        locate(transport, start);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transport() -> TransportState {
+        TransportState {
+            bpm: 120.0,
+            sample_rate: 48000.0,
+            ..TransportState::default()
+        }
+    }
+
+    #[test]
+    fn test_advance_only_while_playing() {
+        let mut t = transport();
+        advance(&mut t, 24000);
+        assert_eq!(t.position_beats, 0.0);
+        handle_command(TransportCommand::Play, &mut t);
+        // 120 BPM at 48 kHz: 24000 samples per beat
+        advance(&mut t, 24000);
+        assert!((t.position_beats - 1.0).abs() < 1e-9);
+        assert_eq!(t.position_samples, 24000);
+    }
+
+    #[test]
+    fn test_stop_twice_returns_to_start() {
+        let mut t = transport();
+        handle_command(TransportCommand::Play, &mut t);
+        advance(&mut t, 48000);
+        handle_command(TransportCommand::Stop, &mut t);
+        assert!(!t.playing);
+        assert!((t.position_beats - 2.0).abs() < 1e-9);
+        handle_command(TransportCommand::Stop, &mut t);
+        assert_eq!(t.position_beats, 0.0);
+        assert_eq!(t.position_samples, 0);
+    }
+
+    #[test]
+    fn test_loop_wraps_at_end() {
+        let mut t = transport();
+        handle_command(
+            TransportCommand::Loop(LoopRegion {
+                enabled: true,
+                start_bar: 1,
+                length_bars: 1,
+            }),
+            &mut t,
+        );
+        assert_eq!((t.loop_start_beats, t.loop_end_beats), (4.0, 8.0));
+        handle_command(TransportCommand::Play, &mut t);
+        locate(&mut t, 7.5);
+        assert_eq!(samples_until_loop_end(&t), Some(12000));
+        advance(&mut t, 12000);
+        assert!((t.position_beats - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_loop_position_folds_external_beats() {
+        let mut t = transport();
+        handle_command(
+            TransportCommand::Loop(LoopRegion {
+                enabled: true,
+                start_bar: 0,
+                length_bars: 2,
+            }),
+            &mut t,
+        );
+        assert_eq!(loop_position(&t, 5.0), 5.0);
+        assert_eq!(loop_position(&t, 9.5), 1.5);
+        assert_eq!(loop_position(&t, 17.0), 1.0);
+    }
+
+    #[test]
+    fn test_time_signature_changes_bar_length() {
+        let mut t = transport();
+        handle_command(
+            TransportCommand::TimeSignature { numerator: 6, denominator: 8 },
+            &mut t,
+        );
+        assert_eq!(bar_beats(&t), 3.0);
+        assert_eq!(format_position(3.5, 6, 8), "2.2");
+        assert_eq!(format_position(0.0, 4, 4), "1.1");
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::slots::{ArpSettings, BendSettings, ControllerSettings, DEFAULT_POLYPHONY, Humanize, LaunchQuantize, LegatoSettings, MacroAssignment, MidiFilterSettings, ModMatrix, Oversampling, SlotOutput, SlotTuning, UnisonSettings, VoiceFilterSettings};
+
 /// Serialized plugin state – saved/restored by the host.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginState {
@@ -7,6 +9,30 @@ pub struct PluginState {
     pub library_urls: Vec<String>,
     /// Per-slot configuration.
     pub slot_configs: Vec<SlotConfig>,
+    /// Named slot groups (bus folders) shown in the rack.
+    #[serde(default)]
+    pub groups: Vec<SlotGroup>,
+    /// NRPN/SysEx rules that load presets or toggle slots.
+    #[serde(default)]
+    pub midi_rules: Vec<crate::midi::rules::MidiRule>,
+    /// What each macro knob drives.
+    #[serde(default)]
+    pub macros: Vec<MacroAssignment>,
+    /// Scale lock applied to incoming notes.
+    #[serde(default)]
+    pub scale_lock: crate::midi::scale::ScaleLock,
+    /// Render slots in parallel on worker threads.
+    #[serde(default)]
+    pub parallel_render: bool,
+    /// Stop every slot's notes, sustain and runners when the transport stops.
+    #[serde(default = "default_reset_on_stop")]
+    pub reset_on_stop: bool,
+    /// Slots 1–16 follow MIDI channels 1–16 as a GM module (see `midi::gm`).
+    #[serde(default)]
+    pub gm_mode: bool,
+    /// Editor layout, restored when the editor opens.
+    #[serde(default)]
+    pub layout: EditorLayout,
 }
 
 impl Default for PluginState {
@@ -16,6 +42,41 @@ impl Default for PluginState {
                 "https://clevertree.github.io/songwalker-library".to_string(),
             ],
             slot_configs: Vec::new(),
+            groups: Vec::new(),
+            midi_rules: Vec::new(),
+            macros: Vec::new(),
+            scale_lock: Default::default(),
+            parallel_render: false,
+            reset_on_stop: default_reset_on_stop(),
+            gm_mode: false,
+            layout: EditorLayout::default(),
+        }
+    }
+}
+
+fn default_reset_on_stop() -> bool {
+    true
+}
+
+/// Editor layout preferences: zoom, tab, piano and panel widths (in points).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorLayout {
+    pub zoom: f32,
+    pub tab: crate::editor::EditorTab,
+    pub piano_visible: bool,
+    pub browser_width: f32,
+    pub visualizer_width: f32,
+}
+
+impl Default for EditorLayout {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            tab: crate::editor::EditorTab::SlotRack,
+            piano_visible: false,
+            browser_width: 200.0,
+            visualizer_width: 130.0,
         }
     }
 }
@@ -35,6 +96,42 @@ impl PluginState {
         }
     }
 
+    /// Add a new slot group and return its index, or None if the maximum is reached.
+    pub fn add_group(&mut self, name: &str) -> Option<usize> {
+        if self.groups.len() >= crate::slots::group::MAX_GROUPS {
+            return None;
+        }
+        let idx = self.groups.len();
+        self.groups.push(SlotGroup::new(name));
+        Some(idx)
+    }
+
+    /// Remove a group by index. Member slots become ungrouped and
+    /// references to later groups are shifted down.
+    pub fn remove_group(&mut self, index: usize) {
+        if index >= self.groups.len() {
+            return;
+        }
+        self.groups.remove(index);
+        for config in &mut self.slot_configs {
+            config.group = match config.group {
+                Some(g) if g == index => None,
+                Some(g) if g > index => Some(g - 1),
+                other => other,
+            };
+        }
+    }
+
+    /// Indices of the slots assigned to the given group (None = ungrouped).
+    pub fn slots_in_group(&self, group: Option<usize>) -> Vec<usize> {
+        self.slot_configs
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.group == group)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
     /// Serialize the state to JSON bytes for host persistence.
     pub fn to_bytes(&self) -> Vec<u8> {
         serde_json::to_vec(self).unwrap_or_default()
@@ -57,6 +154,10 @@ impl PluginState {
 pub struct SlotConfig {
     /// Display name (typically the preset or file name).
     pub name: String,
+    /// `name` was set by the user: it is shown instead of the preset and
+    /// kept when another preset is loaded.
+    #[serde(default)]
+    pub renamed: bool,
     /// Preset identifier (library_key / instrument_key), if any.
     pub preset_id: Option<String>,
     /// MIDI channel this slot responds to (0 = omni, 1-16 = specific).
@@ -76,12 +177,71 @@ pub struct SlotConfig {
     /// Last compilation error, not persisted.
     #[serde(skip)]
     pub compile_error: Option<String>,
+    /// Index into `PluginState::groups`, or None if ungrouped.
+    #[serde(default)]
+    pub group: Option<usize>,
+    /// MIDI output channel (0–15) for notes the runner plays, or None if off.
+    #[serde(default)]
+    pub midi_out_channel: Option<u8>,
+    /// Arpeggiator settings.
+    #[serde(default)]
+    pub arp: ArpSettings,
+    /// Hold mode: note-ons toggle notes instead of needing the key held.
+    #[serde(default)]
+    pub hold: bool,
+    /// Runner playback humanization (timing jitter, velocity, swing).
+    #[serde(default)]
+    pub humanize: Humanize,
+    /// Grid a triggered runner waits for before it starts.
+    #[serde(default)]
+    pub launch_quantize: LaunchQuantize,
+    /// Coarse/fine tune and stereo width.
+    #[serde(default)]
+    pub tuning: SlotTuning,
+    /// Unison voices per note, detune and stereo spread.
+    #[serde(default)]
+    pub unison: UnisonSettings,
+    /// Retrigger and legato crossfades.
+    #[serde(default)]
+    pub legato: LegatoSettings,
+    /// Articulation keyswitches; the first is selected on load.
+    #[serde(default)]
+    pub articulations: Vec<crate::slots::Articulation>,
+    /// Input MIDI filter (note range, velocity, blocked CCs, pitch bend).
+    #[serde(default)]
+    pub midi_filter: MidiFilterSettings,
+    /// Per-voice low-pass filter and its envelope.
+    #[serde(default)]
+    pub filter: VoiceFilterSettings,
+    /// Output trim and saturation ahead of the fader.
+    #[serde(default)]
+    pub output: SlotOutput,
+    /// Oversampled voice rendering.
+    #[serde(default)]
+    pub oversampling: Oversampling,
+    /// Pitch bend range override and destination.
+    #[serde(default)]
+    pub bend: BendSettings,
+    /// Mod wheel destination and mod/expression depths.
+    #[serde(default)]
+    pub controllers: ControllerSettings,
+    /// Modulation matrix routes and LFO rates.
+    #[serde(default)]
+    pub mod_matrix: ModMatrix,
+    /// Voices the slot can play at once (capped by Max Voices).
+    #[serde(default = "default_polyphony")]
+    pub polyphony: u16,
+}
+
+fn default_polyphony() -> u16 {
+    DEFAULT_POLYPHONY as u16
 }
 
 impl Default for SlotConfig {
     fn default() -> Self {
         Self {
             name: "New Slot".to_string(),
+            renamed: false,
             preset_id: None,
             midi_channel: 0,
             volume: 0.8,
@@ -91,6 +251,24 @@ impl Default for SlotConfig {
             root_note: 60,
             source_code: String::new(),
             compile_error: None,
+            group: None,
+            midi_out_channel: None,
+            arp: ArpSettings::default(),
+            hold: false,
+            humanize: Humanize::default(),
+            launch_quantize: LaunchQuantize::default(),
+            tuning: SlotTuning::default(),
+            unison: UnisonSettings::default(),
+            legato: LegatoSettings::default(),
+            articulations: Vec::new(),
+            midi_filter: MidiFilterSettings::default(),
+            filter: VoiceFilterSettings::default(),
+            output: SlotOutput::default(),
+            oversampling: Oversampling::default(),
+            bend: BendSettings::default(),
+            controllers: ControllerSettings::default(),
+            mod_matrix: ModMatrix::default(),
+            polyphony: default_polyphony(),
         }
     }
 }
@@ -105,6 +283,37 @@ impl SlotConfig {
         }
     }
 
+    /// Assign a preset; the slot takes its name unless it was renamed.
+    pub fn assign_preset(&mut self, name: &str, preset_id: String) {
+        if !self.renamed {
+            self.name = name.to_string();
+        }
+        self.preset_id = Some(preset_id);
+    }
+
+    /// Give the slot a custom name, or go back to the automatic one if
+    /// `name` is blank.
+    pub fn rename(&mut self, name: &str) {
+        let name = name.trim();
+        self.renamed = !name.is_empty();
+        if self.renamed {
+            self.name = name.to_string();
+        }
+    }
+
+    /// Name shown for the slot: the custom name, else the preset id.
+    pub fn display_name(&self) -> String {
+        if self.renamed {
+            self.name.clone()
+        } else if let Some(ref preset_id) = self.preset_id {
+            preset_id.clone()
+        } else if !self.source_code.is_empty() {
+            "Source".to_string()
+        } else {
+            "Empty".to_string()
+        }
+    }
+
     /// Create a new slot with source code.
     pub fn new_with_source(name: &str, source: &str) -> Self {
         Self {
@@ -115,6 +324,45 @@ impl SlotConfig {
     }
 }
 
+/// A named group of slots (bus folder) with its own gain stage.
+///
+/// Group gain sits between slot gain and master gain in the mix.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlotGroup {
+    /// Display name (e.g. "Drums", "Strings").
+    pub name: String,
+    /// Group volume 0.0–1.5 (linear).
+    pub volume: f32,
+    /// Muted flag.
+    pub muted: bool,
+    /// Solo flag.
+    pub solo: bool,
+    /// Whether the group is collapsed in the rack UI.
+    pub collapsed: bool,
+}
+
+impl Default for SlotGroup {
+    fn default() -> Self {
+        Self {
+            name: "Group".to_string(),
+            volume: 1.0,
+            muted: false,
+            solo: false,
+            collapsed: false,
+        }
+    }
+}
+
+impl SlotGroup {
+    /// Create a new group with the given name.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,6 +392,22 @@ mod tests {
         assert_eq!(restored.slot_configs[1].source_code, "loadPreset('test')");
     }
 
+    #[test]
+    fn test_slot_rename_survives_preset_loads() {
+        let mut config = SlotConfig::new_preset("Piano", "lib/piano");
+        assert_eq!(config.display_name(), "lib/piano");
+
+        config.rename("  Lead  ");
+        config.assign_preset("Organ", "lib/organ".into());
+        assert_eq!(config.display_name(), "Lead");
+        assert_eq!(config.preset_id.as_deref(), Some("lib/organ"));
+
+        config.rename("");
+        assert_eq!(config.display_name(), "lib/organ");
+        config.assign_preset("Bass", "lib/bass".into());
+        assert_eq!(config.name, "Bass");
+    }
+
     #[test]
     fn test_plugin_state_from_invalid_bytes() {
         let result = PluginState::from_bytes(b"not valid json");
@@ -163,6 +427,7 @@ mod tests {
         assert_eq!(config.root_note, 60);
         assert!(config.source_code.is_empty());
         assert!(config.compile_error.is_none());
+        assert!(config.group.is_none());
     }
 
     #[test]
@@ -204,4 +469,77 @@ mod tests {
         assert_eq!(config.source_code, "C D E F");
         assert!(config.preset_id.is_none());
     }
+
+    #[test]
+    fn test_add_group() {
+        let mut state = PluginState::default();
+        assert_eq!(state.add_group("Drums"), Some(0));
+        assert_eq!(state.add_group("Strings"), Some(1));
+        assert_eq!(state.groups[1].name, "Strings");
+        assert_eq!(state.groups[0].volume, 1.0);
+    }
+
+    #[test]
+    fn test_add_group_max() {
+        let mut state = PluginState::default();
+        for _ in 0..crate::slots::group::MAX_GROUPS {
+            assert!(state.add_group("G").is_some());
+        }
+        assert_eq!(state.add_group("Overflow"), None);
+    }
+
+    #[test]
+    fn test_remove_group_reassigns_members() {
+        let mut state = PluginState::default();
+        state.add_group("Drums");
+        state.add_group("Strings");
+        state.add_group("Brass");
+        let mut a = SlotConfig::default();
+        a.group = Some(0);
+        let mut b = SlotConfig::default();
+        b.group = Some(1);
+        let mut c = SlotConfig::default();
+        c.group = Some(2);
+        state.add_slot_config(a);
+        state.add_slot_config(b);
+        state.add_slot_config(c);
+
+        state.remove_group(1);
+        assert_eq!(state.groups.len(), 2);
+        assert_eq!(state.slot_configs[0].group, Some(0));
+        assert_eq!(state.slot_configs[1].group, None);
+        assert_eq!(state.slot_configs[2].group, Some(1));
+    }
+
+    #[test]
+    fn test_slots_in_group() {
+        let mut state = PluginState::default();
+        state.add_group("Drums");
+        let mut grouped = SlotConfig::default();
+        grouped.group = Some(0);
+        state.add_slot_config(SlotConfig::default());
+        state.add_slot_config(grouped);
+        assert_eq!(state.slots_in_group(Some(0)), vec![1]);
+        assert_eq!(state.slots_in_group(None), vec![0]);
+    }
+
+    #[test]
+    fn test_groups_missing_from_old_state() {
+        // State saved before groups existed must still load
+        let json = br#"{"library_urls":[],"slot_configs":[{"name":"A","preset_id":null,"midi_channel":0,"volume":0.8,"pan":0.0,"muted":false,"solo":false,"root_note":60,"source_code":""}]}"#;
+        let state = PluginState::from_bytes(json).expect("old state should deserialize");
+        assert!(state.groups.is_empty());
+        assert!(state.slot_configs[0].group.is_none());
+        assert!(state.slot_configs[0].midi_out_channel.is_none());
+    }
+
+    #[test]
+    fn test_midi_out_channel_roundtrip() {
+        let mut state = PluginState::default();
+        let mut config = SlotConfig::default();
+        config.midi_out_channel = Some(9);
+        state.add_slot_config(config);
+        let restored = PluginState::from_bytes(&state.to_bytes()).unwrap();
+        assert_eq!(restored.slot_configs[0].midi_out_channel, Some(9));
+    }
 }
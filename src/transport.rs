@@ -14,6 +14,8 @@ pub struct TransportState {
     pub time_sig_denominator: i32,
     /// Whether the host transport is currently playing.
     pub playing: bool,
+    /// The transport went from playing to stopped in the last update.
+    pub stopped: bool,
     /// Current position in beats from the start of the song.
     pub position_beats: f64,
     /// Current position in samples from the start of the song.
@@ -35,6 +37,7 @@ impl Default for TransportState {
             time_sig_numerator: 4,
             time_sig_denominator: 4,
             playing: false,
+            stopped: false,
             position_beats: 0.0,
             position_samples: 0,
             sample_rate: 44100.0,
@@ -55,6 +58,7 @@ impl TransportState {
             self.time_sig_numerator = num;
             self.time_sig_denominator = denom;
         }
+        self.stopped = self.playing && !transport.playing;
         self.playing = transport.playing;
         if let Some(pos) = transport.pos_beats() {
             self.position_beats = pos;
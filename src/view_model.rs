@@ -0,0 +1,549 @@
+//! Editor-agnostic view-model operations.
+//!
+//! Both front-ends (egui and, with the `vizia-editor` feature, vizia) drive
+//! the shared `PluginState` / `PresetManager` through these functions
+//! instead of each re-implementing the same mutations.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crossbeam_channel::{Receiver, Sender};
+
+use crate::editor::{EditorEvent, GlobalParams, PlayNote, PresetLoadedEvent};
+use crate::jobs::JobPool;
+use crate::params::{AUTOMATABLE_SLOTS, SlotMix};
+use crate::preset::instance::PresetInstance;
+use crate::preset::library_index;
+use crate::preset::manager::{LibraryStatus, PresetInfo, PresetManager};
+use crate::slots::{GroupBus, KeyswitchMap, MAX_GROUPS, MidiFilter};
+use crate::state::{PluginState, SlotConfig, SlotGroup};
+
+/// UI-side references to the preset loaded in each slot.
+pub type ActivePresets = HashMap<usize, (Arc<String>, Arc<PresetInstance>)>;
+
+/// Put a library preset into the first empty slot (or a new one).
+/// Returns the slot index, or `None` if the state is unavailable.
+pub fn assign_preset(
+    plugin_state: &Mutex<PluginState>,
+    library: &str,
+    preset_name: &str,
+    preset_path: &str,
+) -> Option<usize> {
+    let preset_id = format!("{}/{}", library, preset_path);
+    let mut ps = plugin_state.lock().ok()?;
+    let empty_idx = ps
+        .slot_configs
+        .iter()
+        .position(|c| c.preset_id.is_none() && c.source_code.is_empty());
+    Some(match empty_idx {
+        Some(idx) => {
+            ps.slot_configs[idx].assign_preset(preset_name, preset_id);
+            idx
+        }
+        None => ps.add_slot_config(SlotConfig::new_preset(preset_name, &preset_id)),
+    })
+}
+
+/// Events that bring slot `idx` on the audio thread in line with
+/// `config`: its group, mix, MIDI handling and sound settings. The preset
+/// and runner program are loaded separately.
+pub fn sync_slot(idx: usize, config: &SlotConfig) -> [EditorEvent; 19] {
+    [
+        EditorEvent::SetSlotGroup { slot_index: idx, group: config.group },
+        EditorEvent::SetSlotMix { slot_index: idx, volume: config.volume, pan: config.pan },
+        EditorEvent::SetSlotMidiOut { slot_index: idx, channel: config.midi_out_channel },
+        EditorEvent::SetSlotArp { slot_index: idx, settings: config.arp },
+        EditorEvent::SetSlotHold { slot_index: idx, hold: config.hold },
+        EditorEvent::SetSlotHumanize { slot_index: idx, humanize: config.humanize },
+        EditorEvent::SetSlotLaunchQuantize { slot_index: idx, quantize: config.launch_quantize },
+        EditorEvent::SetSlotTuning { slot_index: idx, tuning: config.tuning },
+        EditorEvent::SetSlotUnison { slot_index: idx, unison: config.unison },
+        EditorEvent::SetSlotLegato { slot_index: idx, legato: config.legato },
+        EditorEvent::SetSlotBend { slot_index: idx, bend: config.bend },
+        EditorEvent::SetSlotControllers { slot_index: idx, controllers: config.controllers },
+        EditorEvent::SetSlotModMatrix { slot_index: idx, matrix: config.mod_matrix },
+        EditorEvent::SetSlotPolyphony { slot_index: idx, voices: config.polyphony as usize },
+        EditorEvent::SetSlotMidiFilter { slot_index: idx, filter: MidiFilter::from_settings(&config.midi_filter) },
+        EditorEvent::SetSlotFilter { slot_index: idx, filter: config.filter },
+        EditorEvent::SetSlotOutput { slot_index: idx, output: config.output },
+        EditorEvent::SetSlotOversampling { slot_index: idx, oversampling: config.oversampling },
+        EditorEvent::SetSlotKeyswitches {
+            slot_index: idx,
+            keyswitches: KeyswitchMap::from_articulations(&config.articulations),
+        },
+    ]
+}
+
+/// Events that set every group bus from `groups`; buses past the end are
+/// reset.
+pub fn sync_groups(groups: &[SlotGroup]) -> [EditorEvent; MAX_GROUPS] {
+    std::array::from_fn(|group_index| EditorEvent::SetGroup {
+        group_index,
+        bus: groups
+            .get(group_index)
+            .map(|g| GroupBus { volume: g.volume, muted: g.muted, solo: g.solo })
+            .unwrap_or_default(),
+    })
+}
+
+/// [`sync_groups`] then [`sync_slot`] for every slot: the whole rack, as
+/// after a state load.
+pub fn sync_all(ps: &PluginState) -> Vec<EditorEvent> {
+    let mut events = sync_groups(&ps.groups).to_vec();
+    for (idx, config) in ps.slot_configs.iter().enumerate() {
+        events.extend(sync_slot(idx, config));
+    }
+    events
+}
+
+/// Remove slot `idx` from the rack. Returns the events that remove it on
+/// the audio thread and resync the slots that moved up (empty if there is
+/// no such slot).
+pub fn remove_slot(ps: &mut PluginState, idx: usize) -> Vec<EditorEvent> {
+    if idx >= ps.slot_configs.len() {
+        return Vec::new();
+    }
+    ps.remove_slot_config(idx);
+    let mut events = vec![EditorEvent::RemoveSlot { slot_index: idx }];
+    for (i, config) in ps.slot_configs.iter().enumerate().skip(idx) {
+        events.extend(sync_slot(i, config));
+    }
+    events
+}
+
+/// Drop slot `idx` from a map keyed by slot index and move the entries
+/// after it up one place, as [`remove_slot`] does to the rack. Returns
+/// the removed entry.
+pub fn remove_slot_key<V>(map: &mut HashMap<usize, V>, idx: usize) -> Option<V> {
+    let removed = map.remove(&idx);
+    let mut moved: Vec<usize> = map.keys().copied().filter(|&k| k > idx).collect();
+    moved.sort_unstable();
+    for k in moved {
+        if let Some(value) = map.remove(&k) {
+            map.insert(k - 1, value);
+        }
+    }
+    removed
+}
+
+/// Put library presets, given as (library, name, path), into consecutive
+/// slots: the empty slots at the end of the rack, then new ones. Returns
+/// the slot indexes in order.
+pub fn assign_presets(plugin_state: &Mutex<PluginState>, presets: &[(String, String, String)]) -> Vec<usize> {
+    let Ok(mut ps) = plugin_state.lock() else { return Vec::new() };
+    let is_empty = |c: &SlotConfig| c.preset_id.is_none() && c.source_code.is_empty();
+    let first = ps.slot_configs.iter().rposition(|c| !is_empty(c)).map_or(0, |i| i + 1);
+    presets
+        .iter()
+        .enumerate()
+        .map(|(k, (library, name, path))| {
+            let preset_id = format!("{}/{}", library, path);
+            match ps.slot_configs.get_mut(first + k) {
+                Some(cfg) => {
+                    cfg.assign_preset(name, preset_id);
+                    first + k
+                }
+                None => ps.add_slot_config(SlotConfig::new_preset(name, &preset_id)),
+            }
+        })
+        .collect()
+}
+
+/// Apply `f` to one slot's config. Returns false if there is no such slot.
+pub fn update_slot(
+    plugin_state: &Mutex<PluginState>,
+    slot_index: usize,
+    f: impl FnOnce(&mut SlotConfig),
+) -> bool {
+    let Ok(mut ps) = plugin_state.lock() else {
+        return false;
+    };
+    match ps.slot_configs.get_mut(slot_index) {
+        Some(cfg) => {
+            f(cfg);
+            true
+        }
+        None => false,
+    }
+}
+
+/// The slot shown above (`up`) or below a slot in the rack: the previous
+/// or next member of its group.
+pub fn neighbour_slot(plugin_state: &Mutex<PluginState>, slot_index: usize, up: bool) -> Option<usize> {
+    let ps = plugin_state.lock().ok()?;
+    let group = ps.slot_configs.get(slot_index)?.group;
+    let members = ps.slots_in_group(group);
+    let pos = members.iter().position(|&i| i == slot_index)?;
+    let next = if up { pos.checked_sub(1)? } else { pos + 1 };
+    members.get(next).copied()
+}
+
+/// Expand or collapse a library, fetching its index on first expand.
+pub fn toggle_library(jobs: &JobPool, preset_manager: &Arc<Mutex<PresetManager>>, library: &str) {
+    let should_fetch = {
+        let Ok(mut pm) = preset_manager.lock() else {
+            return;
+        };
+        let Some(lib) = pm.libraries.iter_mut().find(|l| l.name == library) else {
+            return;
+        };
+        lib.expanded = !lib.expanded;
+        lib.expanded && lib.status == LibraryStatus::NotLoaded
+    };
+    if should_fetch {
+        library_index::start_library_fetch(jobs, preset_manager.clone(), library.to_string());
+    }
+}
+
+/// The browser's search text and category. Kept by each editor rather
+/// than in the `PresetManager`, which plugin instances share.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PresetFilter {
+    /// Lowercase search text, matched against names and tags.
+    query: String,
+    /// Category to show (`None` = all categories).
+    category: Option<String>,
+}
+
+impl PresetFilter {
+    pub fn new(query: &str, category: Option<&str>) -> Self {
+        Self {
+            query: query.to_lowercase(),
+            category: category.map(str::to_string),
+        }
+    }
+
+    pub fn matches_name(&self, name: &str) -> bool {
+        self.query.is_empty() || name.to_lowercase().contains(&self.query)
+    }
+
+    pub fn matches(&self, preset: &PresetInfo) -> bool {
+        if self.category.as_ref().is_some_and(|c| &preset.category != c) {
+            return false;
+        }
+        self.matches_name(&preset.name)
+            || preset.tags.iter().any(|t| t.to_lowercase().contains(&self.query))
+    }
+
+    /// Presets of a library that pass the filter.
+    pub fn library_presets<'a>(&self, pm: &'a PresetManager, library: &str) -> Vec<&'a PresetInfo> {
+        pm.library_presets.get(library).into_iter().flatten().filter(|p| self.matches(p)).collect()
+    }
+
+    /// Presets of a sub-index ("library/subindex") that pass the filter.
+    pub fn sub_index_presets<'a>(&self, pm: &'a PresetManager, key: &str) -> Vec<&'a PresetInfo> {
+        pm.sub_index_presets.get(key).into_iter().flatten().filter(|p| self.matches(p)).collect()
+    }
+
+    /// Every indexed preset that passes the filter, with its library:
+    /// flat library presets first, then those of loaded sub-indexes.
+    pub fn all_presets<'a>(&self, pm: &'a PresetManager) -> Vec<(&'a str, &'a PresetInfo)> {
+        let mut all = Vec::new();
+        for lib in &pm.libraries {
+            all.extend(self.library_presets(pm, &lib.name).into_iter().map(|p| (lib.name.as_str(), p)));
+        }
+        for key in pm.sub_index_presets.keys() {
+            let library = key.split('/').next().unwrap_or(key);
+            all.extend(self.sub_index_presets(pm, key).into_iter().map(|p| (library, p)));
+        }
+        all
+    }
+
+    /// How well a name matches the search text, best first: 0 for the
+    /// whole name, 1 for its start, 2 for anywhere else.
+    pub fn rank(&self, name: &str) -> u8 {
+        let name = name.to_lowercase();
+        if name == self.query {
+            0
+        } else if name.starts_with(&self.query) {
+            1
+        } else {
+            2
+        }
+    }
+}
+
+/// Forward presets delivered to the UI on to the audio thread, keeping a
+/// UI-side reference so the audio thread is never the one to drop them.
+///
+/// Returns `(slot_index, note)` for each forwarded preset that starts a
+/// preview note, so the caller can schedule the rest of the preview.
+pub fn forward_loaded_presets(
+    ui_rx: &Receiver<PresetLoadedEvent>,
+    audio_tx: &Sender<PresetLoadedEvent>,
+    active: &mut ActivePresets,
+) -> Vec<(usize, PlayNote)> {
+    let mut previews = Vec::new();
+    while let Ok(loaded) = ui_rx.try_recv() {
+        nih_plug::debug::nih_log!(
+            "[UI] Received PresetLoadedEvent for {} into slot {}, play_note={:?}",
+            loaded.preset_id,
+            loaded.slot_index,
+            loaded.play_note
+        );
+        active.insert(
+            loaded.slot_index,
+            (loaded.preset_id.clone(), loaded.instance.clone()),
+        );
+        let preview = loaded.play_note.map(|note| (loaded.slot_index, note));
+        match audio_tx.try_send(loaded) {
+            Ok(()) => {
+                nih_plug::debug::nih_log!("[UI] Forwarded preset to audio thread");
+                previews.extend(preview);
+            }
+            Err(e) => {
+                nih_plug::debug::nih_log!("[UI] FAILED to forward preset to audio thread: {:?}", e)
+            }
+        }
+    }
+    previews
+}
+
+/// Host parameter values of each automatable slot as last synced, `None`
+/// until the first sync.
+pub type SlotParamsSeen = [Option<SlotMix>; AUTOMATABLE_SLOTS];
+
+/// Keep the host mix parameters and the rack's `SlotConfig`s in step.
+///
+/// A parameter that moved since the last sync (host automation, a restored
+/// project) is written into the config; otherwise a config that differs
+/// (rack edit, MIDI rule, slot removal) is pushed to the parameters. On the
+/// first sync the parameters win, since the host restores them.
+pub fn sync_slot_params(
+    plugin_state: &Mutex<PluginState>,
+    params: &dyn GlobalParams,
+    seen: &mut SlotParamsSeen,
+) {
+    let Ok(mut ps) = plugin_state.lock() else { return };
+    for (slot_index, config) in ps.slot_configs.iter_mut().enumerate().take(AUTOMATABLE_SLOTS) {
+        let Some(param) = params.slot_mix(slot_index) else { continue };
+        if seen[slot_index] != Some(param) {
+            param.apply_to_config(config);
+            seen[slot_index] = Some(param);
+            continue;
+        }
+        let edited = SlotMix::from_config(config);
+        if edited != param {
+            params.set_slot_mix(slot_index, edited);
+            seen[slot_index] = Some(edited);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assign_preset_fills_empty_slot_first() {
+        let ps = Mutex::new(PluginState::default());
+        let first = assign_preset(&ps, "gm", "Piano", "piano/preset.json").unwrap();
+        let second = assign_preset(&ps, "gm", "Organ", "organ/preset.json").unwrap();
+        assert_ne!(first, second);
+
+        let state = ps.lock().unwrap();
+        assert_eq!(
+            state.slot_configs[first].preset_id.as_deref(),
+            Some("gm/piano/preset.json")
+        );
+        assert_eq!(state.slot_configs[second].name, "Organ");
+    }
+
+    #[test]
+    fn test_assign_presets_to_consecutive_slots() {
+        let ps = Mutex::new(PluginState::default());
+        for _ in 0..3 {
+            ps.lock().unwrap().add_slot_config(SlotConfig::default());
+        }
+        update_slot(&ps, 1, |cfg| cfg.assign_preset("Piano", "gm/piano".into()));
+
+        let presets: Vec<(String, String, String)> = ["Organ", "Strings", "Choir"]
+            .iter()
+            .map(|name| ("gm".to_string(), name.to_string(), name.to_lowercase()))
+            .collect();
+        // The empty slot before the piano is skipped to keep them together
+        assert_eq!(assign_presets(&ps, &presets), vec![2, 3, 4]);
+        let state = ps.lock().unwrap();
+        assert!(state.slot_configs[0].preset_id.is_none());
+        assert_eq!(state.slot_configs[4].preset_id.as_deref(), Some("gm/choir"));
+    }
+
+    #[test]
+    fn test_sync_all_restores_slots_and_groups() {
+        use crate::audio::handle_editor_event;
+        use crate::slots::SlotManager;
+        use crate::transport::TransportState;
+
+        let mut ps = PluginState::default();
+        ps.groups.push(SlotGroup { volume: 0.5, muted: true, ..SlotGroup::default() });
+        let idx = ps.add_slot_config(SlotConfig::default());
+        let config = &mut ps.slot_configs[idx];
+        config.volume = 0.25;
+        config.group = Some(0);
+        config.polyphony = 3;
+
+        let mut slot_manager = SlotManager::new_empty();
+        slot_manager.initialize(44100.0);
+        slot_manager.allocate_all();
+        let transport = TransportState::default();
+        for event in sync_all(&ps) {
+            handle_editor_event(event, &mut slot_manager, &transport);
+        }
+        let slot = &slot_manager.slots_mut()[idx];
+        assert_eq!((slot.volume(), slot.group(), slot.polyphony()), (0.25, Some(0), 3));
+        let bus = slot_manager.groups()[0];
+        assert_eq!((bus.volume, bus.muted, bus.solo), (0.5, true, false));
+        assert!(!slot_manager.groups()[1].muted);
+    }
+
+    #[test]
+    fn test_removed_slot_leaves_the_audio_rack_in_step() {
+        use crate::audio::handle_editor_event;
+        use crate::perf::voice_pools::PoolResizer;
+        use crate::slots::{MAX_SLOTS, SlotManager};
+        use crate::transport::TransportState;
+
+        let mut ps = PluginState::default();
+        for volume in [0.1, 0.2, 0.3] {
+            let idx = ps.add_slot_config(SlotConfig::default());
+            ps.slot_configs[idx].volume = volume;
+        }
+        let mut slot_manager = SlotManager::new_empty();
+        slot_manager.initialize(44100.0);
+        slot_manager.allocate_all();
+        slot_manager.set_pool_resizer(PoolResizer::spawn());
+        let transport = TransportState::default();
+        for event in sync_all(&ps) {
+            handle_editor_event(event, &mut slot_manager, &transport);
+        }
+
+        for event in remove_slot(&mut ps, 1) {
+            handle_editor_event(event, &mut slot_manager, &transport);
+        }
+        assert!(remove_slot(&mut ps, 5).is_empty());
+        let volumes: Vec<f32> = slot_manager.slots().iter().take(2).map(|s| s.volume()).collect();
+        assert_eq!(volumes, [0.1, 0.3]);
+
+        // A fresh slot takes the free place at the end of the rack
+        for _ in 0..1000 {
+            slot_manager.update_voice_pools(8);
+            if slot_manager.slot_count() == MAX_SLOTS {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        assert_eq!(slot_manager.slot_count(), MAX_SLOTS);
+        assert_eq!(slot_manager.slots()[MAX_SLOTS - 1].index(), MAX_SLOTS - 1);
+    }
+
+    #[test]
+    fn test_remove_slot_key_moves_later_slots_up() {
+        let mut map: HashMap<usize, &str> = [(0, "a"), (1, "b"), (2, "c"), (4, "e")].into_iter().collect();
+        assert_eq!(remove_slot_key(&mut map, 1), Some("b"));
+        let mut entries: Vec<_> = map.into_iter().collect();
+        entries.sort_unstable();
+        assert_eq!(entries, [(0, "a"), (1, "c"), (3, "e")]);
+    }
+
+    #[test]
+    fn test_update_slot_out_of_range() {
+        let ps = Mutex::new(PluginState::default());
+        let idx = assign_preset(&ps, "gm", "Piano", "piano").unwrap();
+        assert!(update_slot(&ps, idx, |cfg| cfg.muted = true));
+        assert!(ps.lock().unwrap().slot_configs[idx].muted);
+        assert!(!update_slot(&ps, idx + 100, |cfg| cfg.muted = true));
+    }
+
+    #[test]
+    fn test_neighbour_slot_stays_in_group() {
+        let ps = Mutex::new(PluginState::default());
+        for group in [None, Some(0), None, Some(0)] {
+            let idx = ps.lock().unwrap().add_slot_config(SlotConfig::default());
+            update_slot(&ps, idx, |cfg| cfg.group = group);
+        }
+        assert_eq!(neighbour_slot(&ps, 0, false), Some(2));
+        assert_eq!(neighbour_slot(&ps, 2, true), Some(0));
+        assert_eq!(neighbour_slot(&ps, 0, true), None);
+        assert_eq!(neighbour_slot(&ps, 1, false), Some(3));
+        assert_eq!(neighbour_slot(&ps, 3, false), None);
+        assert_eq!(neighbour_slot(&ps, 9, true), None);
+    }
+
+    #[test]
+    fn test_preset_filter() {
+        let preset = PresetInfo {
+            name: "Grand Piano".into(),
+            path: "piano".into(),
+            category: "sampler".into(),
+            tags: vec!["Keys".into()],
+            gm_program: Some(0),
+            zone_count: 1,
+        };
+        assert!(PresetFilter::default().matches(&preset));
+        assert!(PresetFilter::new("PIANO", None).matches(&preset));
+        assert!(PresetFilter::new("keys", Some("sampler")).matches(&preset), "tags match too");
+        assert!(!PresetFilter::new("organ", None).matches(&preset));
+        assert!(!PresetFilter::new("", Some("synth")).matches(&preset));
+
+        let filter = PresetFilter::new("pia", None);
+        assert!(filter.rank("Piano") < filter.rank("Grand Piano"));
+        assert_eq!(PresetFilter::new("piano", None).rank("PIANO"), 0);
+    }
+
+    /// Host parameters held in memory.
+    struct FakeParams(Mutex<Vec<SlotMix>>);
+
+    impl GlobalParams for FakeParams {
+        fn master_volume_gain(&self) -> f32 {
+            1.0
+        }
+        fn set_master_volume_gain(&self, _gain: f32) {}
+        fn max_voices(&self) -> i32 {
+            256
+        }
+        fn set_max_voices(&self, _v: i32) {}
+        fn pitch_bend_range(&self) -> i32 {
+            2
+        }
+        fn set_pitch_bend_range(&self, _v: i32) {}
+        fn master_output(&self) -> crate::params::MasterOutput {
+            Default::default()
+        }
+        fn set_master_output(&self, _output: crate::params::MasterOutput) {}
+        fn slot_mix(&self, slot_index: usize) -> Option<SlotMix> {
+            self.0.lock().unwrap().get(slot_index).copied()
+        }
+        fn set_slot_mix(&self, slot_index: usize, mix: SlotMix) {
+            self.0.lock().unwrap()[slot_index] = mix;
+        }
+        fn macro_value(&self, _index: usize) -> f32 {
+            0.0
+        }
+        fn set_macro_value(&self, _index: usize, _value: f32) {}
+    }
+
+    #[test]
+    fn test_sync_slot_params_both_ways() {
+        let ps = Mutex::new(PluginState::default());
+        let idx = assign_preset(&ps, "gm", "Piano", "piano").unwrap();
+        let restored = SlotMix { volume: 0.5, pan: -0.25, muted: false, solo: true };
+        let params = FakeParams(Mutex::new(vec![restored]));
+        let mut seen: SlotParamsSeen = Default::default();
+
+        // First sync: the host's restored values win
+        sync_slot_params(&ps, &params, &mut seen);
+        assert_eq!(SlotMix::from_config(&ps.lock().unwrap().slot_configs[idx]), restored);
+
+        // Rack edit goes to the host
+        update_slot(&ps, idx, |cfg| cfg.muted = true);
+        sync_slot_params(&ps, &params, &mut seen);
+        assert!(params.slot_mix(idx).unwrap().muted);
+
+        // Host automation comes back to the rack
+        params.set_slot_mix(idx, SlotMix { volume: 1.2, ..restored });
+        sync_slot_params(&ps, &params, &mut seen);
+        let cfg = ps.lock().unwrap().slot_configs[idx].clone();
+        assert_eq!(cfg.volume, 1.2);
+        assert!(!cfg.muted);
+    }
+}